@@ -0,0 +1,61 @@
+//! # Benchmark do custo dos eventos
+//!
+//! Compara a análise síncrona (`analyze_with_mode`), que não constrói eventos de progresso,
+//! com a análise em streaming (`analyze_streaming`) consumida até o `Done`, que clona
+//! tokens, features e tabelas do Viterbi para o canal. A diferença é o que um usuário em
+//! lote economiza por não pedir a visualização passo a passo.
+//!
+//! Execute com `cargo bench -p ner-core --bench event_overhead [-- <sentenças>]`.
+
+use std::sync::mpsc;
+use std::time::Instant;
+
+use ner_core::synthetic::{SyntheticConfig, SyntheticCorpus};
+use ner_core::{AlgorithmMode, NerPipeline, PipelineEvent, TokenizerMode};
+
+fn main() {
+    let sentences: usize = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(2_000);
+
+    let pipeline = NerPipeline::new();
+    let corpus = SyntheticCorpus::generate(&SyntheticConfig { sentences, ..Default::default() });
+    let texts: Vec<&str> = corpus.texts().collect();
+
+    println!("{} sentenças, {} tokens", corpus.len(), corpus.token_count());
+    println!("{:<12} {:>14} {:>14} {:>10}", "modo", "síncrono ms", "streaming ms", "ganho");
+    for mode in [AlgorithmMode::RulesOnly, AlgorithmMode::CrfOnly, AlgorithmMode::Hybrid] {
+        let start = Instant::now();
+        let direct: usize = texts
+            .iter()
+            .map(|text| pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard).1.len())
+            .sum();
+        let direct_secs = start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let streamed: usize = texts
+            .iter()
+            .map(|text| {
+                let (tx, rx) = mpsc::channel();
+                pipeline.analyze_streaming(text, mode, TokenizerMode::Standard, tx);
+                rx.iter()
+                    .find_map(|event| match event {
+                        PipelineEvent::Done { entities, .. } => Some(entities.len()),
+                        _ => None,
+                    })
+                    .unwrap_or(0)
+            })
+            .sum();
+        let streaming_secs = start.elapsed().as_secs_f64();
+
+        assert_eq!(direct, streamed, "os dois caminhos devem achar as mesmas entidades");
+        println!(
+            "{:<12} {:>14.1} {:>14.1} {:>9.2}x",
+            format!("{mode:?}"),
+            direct_secs * 1000.0,
+            streaming_secs * 1000.0,
+            streaming_secs / direct_secs
+        );
+    }
+}
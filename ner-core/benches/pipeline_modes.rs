@@ -0,0 +1,46 @@
+//! # Benchmark de vazão por modo
+//!
+//! Mede o tempo para analisar um corpus sintético em cada modo. `RulesOnly` pula a
+//! extração de features e o Viterbi (ver [`AlgorithmMode::stages`]), então deve ser
+//! bem mais rápido que `Hybrid` — o caso típico de anonimização com dicionários.
+//!
+//! Execute com `cargo bench -p ner-core --bench pipeline_modes [-- <sentenças>]`.
+
+use std::time::Instant;
+
+use ner_core::synthetic::{SyntheticConfig, SyntheticCorpus};
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+fn main() {
+    let sentences: usize = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(2_000);
+
+    let pipeline = NerPipeline::new();
+    let corpus = SyntheticCorpus::generate(&SyntheticConfig { sentences, ..Default::default() });
+    let texts: Vec<&str> = corpus.texts().collect();
+
+    println!("{} sentenças, {} tokens", corpus.len(), corpus.token_count());
+    println!("{:<14} {:>10} {:>14} {:>10}", "modo", "ms", "tokens/s", "entidades");
+    for mode in [
+        AlgorithmMode::RulesOnly,
+        AlgorithmMode::FeaturesOnly,
+        AlgorithmMode::CrfOnly,
+        AlgorithmMode::Hybrid,
+    ] {
+        let start = Instant::now();
+        let entities: usize = texts
+            .iter()
+            .map(|text| pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard).1.len())
+            .sum();
+        let secs = start.elapsed().as_secs_f64();
+        println!(
+            "{:<14} {:>10.1} {:>14.0} {:>10}",
+            format!("{mode:?}"),
+            secs * 1000.0,
+            corpus.token_count() as f64 / secs,
+            entities
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! # Benchmark de memória do Viterbi
+//!
+//! Compara, para um documento longo, a memória de pico de três formas de decodificar:
+//!
+//! - o layout antigo de backpointers (`Vec<Vec<usize>>`, um `Vec` por token);
+//! - [`viterbi_decode`], que ainda monta a tabela de `steps` para visualização;
+//! - [`ViterbiScratch::best_path`], com backpointers `u8` e buffers reaproveitados.
+//!
+//! Execute com `cargo bench -p ner-core --bench viterbi_memory [-- <tokens>]`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use ner_core::features::extract_features;
+use ner_core::model::NerModel;
+use ner_core::tokenizer::tokenize;
+use ner_core::viterbi::{viterbi_decode, ViterbiScratch};
+use ner_core::Tag;
+
+/// Alocador que contabiliza os bytes em uso e o pico desde o último [`reset_peak`].
+struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn reset_peak() -> usize {
+    let now = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(now, Ordering::Relaxed);
+    now
+}
+
+/// Executa `f` e devolve o resultado, os bytes extras no pico e o tempo gasto.
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, f64) {
+    let base = reset_peak();
+    let start = Instant::now();
+    let out = f();
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+    (out, PEAK.load(Ordering::Relaxed) - base, elapsed)
+}
+
+fn main() {
+    let n_tokens: usize = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(20_000);
+
+    let model = NerModel::build();
+    let sentence = "O presidente Lula visitou a Petrobras no Rio de Janeiro ontem . ";
+    let per_sentence = tokenize(sentence).len();
+    let text = sentence.repeat(n_tokens.div_ceil(per_sentence));
+    let tokens = tokenize(&text);
+    let features = extract_features(&tokens[..n_tokens.min(tokens.len())], &model.gazetteers());
+    let n_tags = Tag::all().len();
+
+    let (_, legacy_bytes, _) = measure(|| vec![vec![0usize; n_tags]; features.len()]);
+    let (full, full_bytes, full_ms) = measure(|| viterbi_decode(&model.crf, &features));
+    let mut scratch = ViterbiScratch::new();
+    let ((path, _), path_bytes, path_ms) = measure(|| scratch.best_path(&model.crf, &features));
+    // Segunda passada com o mesmo scratchpad: nenhuma realocação da tabela
+    let (_, reuse_bytes, reuse_ms) = measure(|| scratch.best_path(&model.crf, &features));
+    assert_eq!(path, full.best_sequence);
+
+    println!("Viterbi em {} tokens × {} tags", features.len(), n_tags);
+    println!("{:<40} {:>14} {:>10}", "variante", "pico (bytes)", "ms");
+    println!(
+        "{:<40} {:>14} {:>10}",
+        "backpointers Vec<Vec<usize>> (só tabela)", legacy_bytes, "-"
+    );
+    println!(
+        "{:<40} {:>14} {:>10.1}",
+        "viterbi_decode (com steps)", full_bytes, full_ms
+    );
+    println!(
+        "{:<40} {:>14} {:>10.1}",
+        "ViterbiScratch::best_path", path_bytes, path_ms
+    );
+    println!(
+        "{:<40} {:>14} {:>10.1}",
+        "ViterbiScratch::best_path (reuso)", reuse_bytes, reuse_ms
+    );
+    println!(
+        "tabela u8: {} bytes ({:.1}x menor que o layout antigo)",
+        scratch.backpointer_bytes(),
+        legacy_bytes as f64 / scratch.backpointer_bytes() as f64
+    );
+}
@@ -0,0 +1,40 @@
+//! # Anotação de arquivos em lote
+//!
+//! Analisa cada arquivo de texto passado na linha de comando e escreve uma linha
+//! JSON por arquivo (JSONL) com as entidades encontradas:
+//!
+//! ```text
+//! cargo run -p ner-core --example annotate_files -- noticia1.txt noticia2.txt > entidades.jsonl
+//! ```
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+/// Anota `paths` e escreve uma linha JSON por arquivo em `out`; retorna o total de entidades.
+pub fn run(pipeline: &NerPipeline, paths: &[PathBuf], mut out: impl Write) -> io::Result<usize> {
+    let mut total = 0;
+    for path in paths {
+        let text = std::fs::read_to_string(path)?;
+        let (_, entities) = pipeline.analyze_with_mode(&text, AlgorithmMode::Hybrid, TokenizerMode::Standard);
+        total += entities.len();
+        let line = serde_json::json!({
+            "file": path.display().to_string(),
+            "entities": entities,
+        });
+        writeln!(out, "{line}")?;
+    }
+    Ok(total)
+}
+
+fn main() -> io::Result<()> {
+    let paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        eprintln!("uso: annotate_files <arquivo.txt>...");
+        std::process::exit(2);
+    }
+    let total = run(&NerPipeline::new(), &paths, io::stdout().lock())?;
+    eprintln!("{total} entidade(s) em {} arquivo(s)", paths.len());
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! # Comparando dois modos em um conjunto anotado
+//!
+//! Roda `RulesOnly` e `Hybrid` nas amostras embutidas ([`SampleRegistry::builtin`]),
+//! alinha as entidades com o ouro ([`ner_core::diff`]) e imprime precisão, revocação e F1:
+//!
+//! ```text
+//! cargo run -p ner-core --example compare_modes
+//! ```
+
+use ner_core::diff::{diff_entities, DiffKind, GoldEntity};
+use ner_core::samples::SampleRegistry;
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+/// Contagens e métricas de um modo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeScore {
+    pub mode: AlgorithmMode,
+    pub correct: usize,
+    pub predicted: usize,
+    pub gold: usize,
+}
+
+impl ModeScore {
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct, self.predicted)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct, self.gold)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 {
+            0.0
+        } else {
+            2.0 * p * r / (p + r)
+        }
+    }
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+/// Avalia cada modo em `(texto, ouro)`; um acerto exige mesmas fronteiras e categoria.
+pub fn run(pipeline: &NerPipeline, data: &[(String, Vec<GoldEntity>)], modes: &[AlgorithmMode]) -> Vec<ModeScore> {
+    modes
+        .iter()
+        .map(|&mode| {
+            let mut score = ModeScore { mode, correct: 0, predicted: 0, gold: 0 };
+            for (text, gold) in data {
+                let (_, predicted) = pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard);
+                let diff = diff_entities(gold, &predicted);
+                score.correct += diff.iter().filter(|d| d.kind == DiffKind::Correct).count();
+                score.predicted += predicted.len();
+                score.gold += gold.len();
+            }
+            score
+        })
+        .collect()
+}
+
+/// Amostras embutidas como ouro (a primeira ocorrência de cada entidade esperada).
+pub fn builtin_gold() -> Vec<(String, Vec<GoldEntity>)> {
+    SampleRegistry::builtin()
+        .samples()
+        .map(|(_, sample)| {
+            let gold = sample
+                .expected
+                .iter()
+                .filter_map(|e| GoldEntity::find(&sample.text, &e.text, e.category))
+                .collect();
+            (sample.text.clone(), gold)
+        })
+        .collect()
+}
+
+fn main() {
+    let pipeline = NerPipeline::new();
+    let scores = run(&pipeline, &builtin_gold(), &[AlgorithmMode::RulesOnly, AlgorithmMode::Hybrid]);
+    println!("{:<10} {:>9} {:>9} {:>6}", "modo", "precisão", "revocação", "F1");
+    for s in scores {
+        println!(
+            "{:<10} {:>9.3} {:>9.3} {:>6.3}",
+            format!("{:?}", s.mode),
+            s.precision(),
+            s.recall(),
+            s.f1()
+        );
+    }
+}
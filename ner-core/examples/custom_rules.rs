@@ -0,0 +1,48 @@
+//! # Gazetteers e regras próprios
+//!
+//! Estende o motor de regras do pipeline com nomes do domínio (uma fintech fictícia),
+//! uma regra de padrão de tokens e uma de expressão regular, e analisa uma frase no
+//! modo `RulesOnly`:
+//!
+//! ```text
+//! cargo run -p ner-core --example custom_rules -- "A Zubrex contratou a analista Joana Prado."
+//! ```
+
+use ner_core::tagger::{EntityCategory, EntitySpan};
+use ner_core::token_pattern::PatternError;
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+/// Pipeline com o gazetteer e as regras do domínio.
+pub fn build_pipeline() -> Result<NerPipeline, PatternError> {
+    let mut pipeline = NerPipeline::new();
+    let rules = &mut pipeline.model.rule_engine;
+
+    // Gazetteers: nomes conhecidos do domínio
+    rules.add_org("Zubrex");
+    rules.add_misc("Pix Automático");
+
+    // Regra declarativa: cargo seguido de nome capitalizado → só o nome é PER
+    rules.add_word_class("cargo", &["analista", "gerente", "diretora", "diretor"]);
+    rules.add_pattern("cargo_nome", "[cargo] ([Cap]+)", EntityCategory::Per, 0.9)?;
+
+    // Regra de expressão regular: valores em reais → MISC
+    rules.add_regex_rule("dinheiro", r"R\$ ?\d{1,3}(?:\.\d{3})*(?:,\d{2})?", EntityCategory::Misc, 0.95)?;
+    Ok(pipeline)
+}
+
+/// Entidades de `text` segundo as regras do domínio.
+pub fn run(pipeline: &NerPipeline, text: &str) -> Vec<EntitySpan> {
+    let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    entities
+}
+
+fn main() -> Result<(), PatternError> {
+    let text = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "A Zubrex contratou a analista Joana Prado.".to_string());
+    let pipeline = build_pipeline()?;
+    for entity in run(&pipeline, &text) {
+        println!("{}\t{}\t{}", entity.category.name(), entity.text, entity.source);
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! # Treinando um Perceptron em um corpus CoNLL próprio
+//!
+//! Lê um arquivo CoNLL (`palavra tag`, sentenças separadas por linha em branco) em
+//! streaming, treina um [`PerceptronModel`] e etiqueta uma frase:
+//!
+//! ```text
+//! cargo run -p ner-core --example train_perceptron -- meu_corpus.conll "Ana visitou Recife"
+//! ```
+
+use std::path::Path;
+
+use ner_core::corpus_reader::{CorpusError, CorpusFormat, CorpusReader};
+use ner_core::perceptron::PerceptronModel;
+use ner_core::tokenizer::tokenize;
+
+/// Épocas de treinamento.
+pub const EPOCHS: usize = 10;
+
+/// Treina no corpus de `path` e devolve `(token, tag)` para cada token de `text`.
+pub fn run(path: &Path, text: &str) -> Result<Vec<(String, String)>, CorpusError> {
+    let reader = CorpusReader::open(path, CorpusFormat::Conll)?;
+    // Falha cedo, antes de um treino longo, se o arquivo tiver linhas malformadas
+    reader.validate()?;
+
+    let mut model = PerceptronModel::new();
+    model.train_stream(reader, EPOCHS);
+
+    let tokens: Vec<String> = tokenize(text).into_iter().map(|t| t.text).collect();
+    let tags = model.predict(&tokens);
+    Ok(tokens.into_iter().zip(tags).collect())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [path, text] = args.as_slice() else {
+        eprintln!("uso: train_perceptron <corpus.conll> <texto>");
+        std::process::exit(2);
+    };
+    match run(Path::new(path), text) {
+        Ok(tagged) => {
+            for (token, tag) in tagged {
+                println!("{token}\t{tag}");
+            }
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! # Aprendizado de Abreviações
+//!
+//! A lista embutida de abreviações do tokenizador cobre o português geral ("Dr.",
+//! "art.", "etc."), mas cada domínio tem as suas: "ref.", "proc.", "aprox.", "obs.".
+//! Sem elas, "proc. administrativo" vira "proc" + "." e a sentença termina no meio.
+//!
+//! O [`AbbreviationLearner`] varre textos do domínio (tipicamente o corpus de treino)
+//! procurando palavras que:
+//!
+//! 1. São curtas (até [`AbbreviationLearner::max_len`] caracteres) e alfabéticas.
+//! 2. Quase sempre aparecem coladas a um ponto ([`AbbreviationLearner::min_dot_ratio`]).
+//! 3. Têm esse ponto seguido de minúscula, dígito ou vírgula — ou seja, o ponto **não**
+//!    encerrou a sentença — pelo menos [`AbbreviationLearner::min_count`] vezes.
+//!
+//! As candidatas ([`AbbreviationCandidate`]) podem ser revisadas ou gravadas direto em
+//! uma [`TokenizerConfig`] com [`AbbreviationLearner::learn_into`].
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::abbreviations::AbbreviationLearner;
+//! use ner_core::tokenizer::{TokenizerConfig, TokenizerMode};
+//!
+//! let mut learner = AbbreviationLearner::new();
+//! learner.observe("O proc. administrativo foi aberto. Ver proc. nº 12 e o proc. seguinte.");
+//!
+//! let mut config = TokenizerConfig::new();
+//! assert_eq!(learner.learn_into(&mut config), vec!["proc".to_string()]);
+//!
+//! let tokens = config.tokenize("O proc. administrativo", TokenizerMode::Standard);
+//! assert_eq!(tokens[1].text, "proc.");
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokenizer::{tokenize, TokenizerConfig};
+
+/// Uma palavra proposta como abreviação, com as contagens que a justificam.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbbreviationCandidate {
+    /// A palavra, sem o ponto.
+    pub word: String,
+    /// Ocorrências seguidas de ponto.
+    pub dotted: usize,
+    /// Ocorrências sem ponto.
+    pub plain: usize,
+    /// Ocorrências com ponto seguido de minúscula, dígito ou vírgula (meio de sentença).
+    pub mid_sentence: usize,
+}
+
+/// Contagens acumuladas de uma palavra.
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    dotted: usize,
+    plain: usize,
+    mid_sentence: usize,
+}
+
+/// Acumula evidências de abreviação em textos do domínio.
+#[derive(Debug, Clone)]
+pub struct AbbreviationLearner {
+    /// Ocorrências mínimas no meio da sentença. Padrão: 2.
+    pub min_count: usize,
+    /// Fração mínima das ocorrências que vêm com ponto. Padrão: 0.8.
+    pub min_dot_ratio: f64,
+    /// Tamanho máximo da palavra, em caracteres. Padrão: 6.
+    pub max_len: usize,
+    counts: HashMap<String, Counts>,
+}
+
+impl Default for AbbreviationLearner {
+    fn default() -> Self {
+        Self { min_count: 2, min_dot_ratio: 0.8, max_len: 6, counts: HashMap::new() }
+    }
+}
+
+impl AbbreviationLearner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_count(mut self, min_count: usize) -> Self {
+        self.min_count = min_count;
+        self
+    }
+
+    pub fn with_min_dot_ratio(mut self, min_dot_ratio: f64) -> Self {
+        self.min_dot_ratio = min_dot_ratio;
+        self
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Acumula as ocorrências de `text`.
+    ///
+    /// O texto é tokenizado com as abreviações embutidas, então as já conhecidas
+    /// ("Dr.") chegam inteiras e não são propostas de novo.
+    pub fn observe(&mut self, text: &str) {
+        let tokens = tokenize(text);
+        for (i, token) in tokens.iter().enumerate() {
+            let len = token.text.chars().count();
+            if len == 0 || len > self.max_len || !token.text.chars().all(char::is_alphabetic) {
+                continue;
+            }
+            let counts = self.counts.entry(token.text.clone()).or_default();
+            let dot = tokens.get(i + 1).filter(|next| next.text == "." && next.start == token.end);
+            if dot.is_none() {
+                counts.plain += 1;
+                continue;
+            }
+            counts.dotted += 1;
+            let continues = tokens.get(i + 2).is_some_and(|after| {
+                after.text == "," || after.text.starts_with(|c: char| c.is_lowercase() || c.is_ascii_digit())
+            });
+            if continues {
+                counts.mid_sentence += 1;
+            }
+        }
+    }
+
+    /// Acumula as ocorrências de vários textos.
+    pub fn observe_all<'a>(&mut self, texts: impl IntoIterator<Item = &'a str>) {
+        for text in texts {
+            self.observe(text);
+        }
+    }
+
+    /// Palavras que atendem aos critérios, da mais para a menos frequente no meio de sentença.
+    pub fn candidates(&self) -> Vec<AbbreviationCandidate> {
+        let mut candidates: Vec<AbbreviationCandidate> = self
+            .counts
+            .iter()
+            .filter(|(_, c)| {
+                c.mid_sentence >= self.min_count.max(1)
+                    && c.dotted as f64 / (c.dotted + c.plain) as f64 >= self.min_dot_ratio
+            })
+            .map(|(word, c)| AbbreviationCandidate {
+                word: word.clone(),
+                dotted: c.dotted,
+                plain: c.plain,
+                mid_sentence: c.mid_sentence,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.mid_sentence.cmp(&a.mid_sentence).then_with(|| a.word.cmp(&b.word)));
+        candidates
+    }
+
+    /// Grava as candidatas em `config`; retorna as que ainda não eram conhecidas.
+    pub fn learn_into(&self, config: &mut TokenizerConfig) -> Vec<String> {
+        self.candidates().into_iter().map(|c| c.word).filter(|word| config.add_abbreviation(word)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::{sentence_ranges, TokenizerMode};
+
+    #[test]
+    fn test_learns_domain_abbreviations() {
+        let corpus = [
+            "Conforme o proc. administrativo, o valor aprox. de R$ 10 foi pago.",
+            "O proc. nº 45 tramita em Brasília. A obs. anterior vale.",
+            "Ver obs. 3 e aprox. 20 dias. O prazo terminou ontem.",
+            "O processo terminou. Ele voltou ontem. Dr. Silva chegou ontem, cedo.",
+        ];
+        let mut learner = AbbreviationLearner::new();
+        learner.observe_all(corpus);
+
+        let candidates = learner.candidates();
+        let words: Vec<&str> = candidates.iter().map(|c| c.word.as_str()).collect();
+        // "ontem." encerra sentenças e também aparece sem ponto; "Dr" já é embutida
+        assert_eq!(words, vec!["aprox", "obs", "proc"]);
+        assert_eq!(candidates[2], AbbreviationCandidate { word: "proc".into(), dotted: 2, plain: 0, mid_sentence: 2 });
+
+        let mut config = TokenizerConfig::new().with_abbreviation("obs.");
+        assert_eq!(learner.learn_into(&mut config), vec!["aprox", "proc"]);
+        assert!(!config.add_abbreviation("Dr"));
+
+        // O ponto da abreviação deixa de encerrar a sentença
+        let text = "O proc. administrativo foi aberto. Nada mais.";
+        assert_eq!(sentence_ranges(&crate::tokenizer::tokenize(text)).len(), 3);
+        let tokens = config.tokenize(text, TokenizerMode::Standard);
+        assert_eq!(tokens[1].text, "proc.");
+        assert_eq!(sentence_ranges(&tokens).len(), 2);
+
+        let path = std::env::temp_dir().join(format!("ner_tokenizer_config_{}.json", std::process::id()));
+        config.save(&path).unwrap();
+        let loaded = TokenizerConfig::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, config);
+
+        // O pipeline usa a configuração em todos os modos derivados do Standard
+        let pipeline = crate::NerPipeline::new().with_tokenizer_config(config);
+        let (tokens, _) = pipeline.analyze_with_mode(text, crate::AlgorithmMode::CrfOnly, TokenizerMode::Aggressive);
+        assert!(tokens.iter().any(|t| t.token.text == "proc."));
+    }
+}
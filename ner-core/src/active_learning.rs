@@ -0,0 +1,238 @@
+//! # Aprendizado Ativo: o que Anotar Primeiro
+//!
+//! Anotar um corpus do zero é caro; anotar as sentenças em que o modelo já acerta é
+//! desperdício. A **amostragem por incerteza** roda o pipeline sobre um conjunto de
+//! textos não anotados, mede em cada sentença o quanto o modelo hesita e devolve as
+//! `k` mais incertas, as que mais ensinam ao próximo treino.
+//!
+//! Duas medidas ([`UncertaintyStrategy`]):
+//!
+//! - **Margem por token** ([`UncertaintyStrategy::TokenMargin`]): em cada token, a
+//!   diferença entre a probabilidade da melhor tag do CRF e a da segunda (marginais do
+//!   forward-backward, ver [`crate::probabilities`]). A sentença vale `1 - menor margem`:
+//!   basta um token em dúvida (ex: "Vale" empresa ou relevo) para ela subir na fila.
+//!   Pega também as entidades que o modelo quase marcou.
+//! - **Entropia das entidades** ([`UncertaintyStrategy::EntityEntropy`]): a entropia
+//!   binária (em bits) da confiança de cada entidade encontrada no modo escolhido; a
+//!   sentença vale a maior delas. Confiança 0.5 vale 1, confiança 1.0 vale 0. Funciona
+//!   em qualquer modo, mas sentenças sem entidades valem 0.
+//!
+//! Sentenças repetidas no conjunto (mesmo texto) aparecem uma vez só.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::active_learning::{select_for_annotation, SamplingConfig};
+//! use ner_core::NerPipeline;
+//!
+//! let pool = vec![
+//!     "Lula visitou a Petrobras em Brasília. O tempo estava bom.".to_string(),
+//!     "A Vale anunciou resultados no vale do Jequitinhonha.".to_string(),
+//! ];
+//! let picks = select_for_annotation(&NerPipeline::new(), &pool, &SamplingConfig::default().with_top_k(2));
+//! assert_eq!(picks.len(), 2);
+//! assert!(picks[0].uncertainty >= picks[1].uncertainty);
+//! // Cada sentença escolhida aponta de volta para o texto de origem
+//! let first = &picks[0];
+//! assert_eq!(&pool[first.text_index][first.start..first.end], first.sentence);
+//! ```
+
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::offsets::slice_lossy;
+use crate::pipeline::{AlgorithmMode, NerPipeline};
+use crate::tagger::EntitySpan;
+use crate::tokenizer::{sentence_ranges, Token, TokenizerMode};
+
+/// Como medir a incerteza de uma sentença.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UncertaintyStrategy {
+    /// `1 -` a menor margem entre as duas tags mais prováveis do CRF num token.
+    #[default]
+    TokenMargin,
+    /// Maior entropia binária da confiança das entidades encontradas no modo.
+    EntityEntropy,
+}
+
+/// Parâmetros de [`select_for_annotation`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    pub strategy: UncertaintyStrategy,
+    /// Quantas sentenças devolver. Padrão: 20.
+    pub top_k: usize,
+    /// Modo cujas entidades acompanham cada sentença (e que a entropia mede). Padrão: `Hybrid`.
+    pub mode: AlgorithmMode,
+    pub tokenizer_mode: TokenizerMode,
+}
+
+impl SamplingConfig {
+    pub fn with_strategy(mut self, strategy: UncertaintyStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: AlgorithmMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_tokenizer_mode(mut self, tokenizer_mode: TokenizerMode) -> Self {
+        self.tokenizer_mode = tokenizer_mode;
+        self
+    }
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: UncertaintyStrategy::default(),
+            top_k: 20,
+            mode: AlgorithmMode::Hybrid,
+            tokenizer_mode: TokenizerMode::Standard,
+        }
+    }
+}
+
+/// Uma sentença sugerida para anotação.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationCandidate {
+    /// Índice do texto de origem no conjunto.
+    pub text_index: usize,
+    /// Offsets de byte da sentença no texto de origem (`end` exclusivo).
+    pub start: usize,
+    pub end: usize,
+    pub sentence: String,
+    /// Incerteza entre 0 (o modelo tem certeza) e 1.
+    pub uncertainty: f64,
+    /// Entidades previstas na sentença (offsets no texto de origem), para pré-anotação.
+    pub entities: Vec<EntitySpan>,
+}
+
+/// As `config.top_k` sentenças de `pool` em que o modelo está mais incerto, da mais
+/// incerta para a menos (empates na ordem do conjunto). Os textos são analisados em
+/// paralelo.
+pub fn select_for_annotation(pipeline: &NerPipeline, pool: &[String], config: &SamplingConfig) -> Vec<AnnotationCandidate> {
+    let mut candidates: Vec<AnnotationCandidate> = pool
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(text_index, text)| score_sentences(pipeline, text_index, text, config))
+        .collect();
+
+    let mut seen = HashSet::new();
+    candidates.retain(|c| seen.insert(c.sentence.clone()));
+    candidates.sort_by(|a, b| b.uncertainty.total_cmp(&a.uncertainty));
+    candidates.truncate(config.top_k);
+    candidates
+}
+
+/// Entropia binária (em bits) de uma confiança `p`: 1 em 0.5, 0 nos extremos.
+pub fn binary_entropy(p: f64) -> f64 {
+    let p = p.clamp(0.0, 1.0);
+    if p == 0.0 || p == 1.0 {
+        return 0.0;
+    }
+    -(p * p.log2() + (1.0 - p) * (1.0 - p).log2())
+}
+
+/// Todas as sentenças de `text`, com a incerteza de cada uma.
+fn score_sentences(pipeline: &NerPipeline, text_index: usize, text: &str, config: &SamplingConfig) -> Vec<AnnotationCandidate> {
+    let (tagged, entities) = pipeline.analyze_with_mode(text, config.mode, config.tokenizer_mode);
+    let tokens: Vec<Token> = tagged.into_iter().map(|t| t.token).collect();
+    // (início do token, margem) do CRF, casados com as sentenças pelos offsets
+    let margins: Vec<(usize, f64)> = match config.strategy {
+        UncertaintyStrategy::TokenMargin => pipeline
+            .token_probabilities(text, config.tokenizer_mode)
+            .iter()
+            .map(|row| {
+                let mut probs = row.probabilities.clone();
+                probs.sort_by(|a, b| b.total_cmp(a));
+                (row.start, probs.first().copied().unwrap_or(1.0) - probs.get(1).copied().unwrap_or(0.0))
+            })
+            .collect(),
+        UncertaintyStrategy::EntityEntropy => Vec::new(),
+    };
+
+    sentence_ranges(&tokens)
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let (start, end) = (tokens[range.start].start, tokens[range.end - 1].end);
+            let sentence_entities: Vec<EntitySpan> =
+                entities.iter().filter(|e| e.start >= start && e.end <= end).cloned().collect();
+            let uncertainty = match config.strategy {
+                UncertaintyStrategy::TokenMargin => {
+                    let min_margin = margins
+                        .iter()
+                        .filter(|(token_start, _)| (start..end).contains(token_start))
+                        .map(|&(_, margin)| margin)
+                        .fold(1.0, f64::min);
+                    1.0 - min_margin
+                }
+                UncertaintyStrategy::EntityEntropy => {
+                    sentence_entities.iter().map(|e| binary_entropy(e.confidence)).fold(0.0, f64::max)
+                }
+            };
+            AnnotationCandidate {
+                text_index,
+                start,
+                end,
+                sentence: slice_lossy(text, start, end).to_string(),
+                uncertainty: uncertainty.clamp(0.0, 1.0),
+                entities: sentence_entities,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_entropy() {
+        assert_eq!(binary_entropy(0.5), 1.0);
+        assert_eq!(binary_entropy(1.0), 0.0);
+        assert_eq!(binary_entropy(0.0), 0.0);
+        assert!((binary_entropy(0.9) - binary_entropy(0.1)).abs() < 1e-12);
+        assert!(binary_entropy(0.9) < binary_entropy(0.7));
+    }
+
+    #[test]
+    fn test_select_for_annotation() {
+        let pipeline = NerPipeline::new();
+        let pool = vec![
+            "O presidente Lula visitou a Petrobras no Rio de Janeiro. Ele voltou ontem.".to_string(),
+            "A Vale anunciou lucro. Xyzq Wvbn falou com Qwert em Plmok.".to_string(),
+            "O presidente Lula visitou a Petrobras no Rio de Janeiro.".to_string(),
+        ];
+
+        for strategy in [UncertaintyStrategy::TokenMargin, UncertaintyStrategy::EntityEntropy] {
+            let config = SamplingConfig::default().with_strategy(strategy).with_top_k(10);
+            let picks = select_for_annotation(&pipeline, &pool, &config);
+            // 4 sentenças distintas: a repetida no terceiro texto conta uma vez
+            assert_eq!(picks.len(), 4, "{strategy:?}");
+            assert!(picks.windows(2).all(|w| w[0].uncertainty >= w[1].uncertainty), "{strategy:?}");
+            for pick in &picks {
+                assert!((0.0..=1.0).contains(&pick.uncertainty));
+                assert_eq!(&pool[pick.text_index][pick.start..pick.end], pick.sentence);
+                assert!(pick.entities.iter().all(|e| e.start >= pick.start && e.end <= pick.end));
+            }
+        }
+
+        // Sem entidades, a entropia é zero; o top-k corta a lista
+        let config = SamplingConfig::default().with_strategy(UncertaintyStrategy::EntityEntropy).with_top_k(1);
+        let quiet = select_for_annotation(&pipeline, &["o dia foi calmo.".to_string()], &config);
+        assert_eq!(quiet.len(), 1);
+        assert_eq!(quiet[0].uncertainty, 0.0);
+        assert!(select_for_annotation(&pipeline, &pool, &config.with_top_k(0)).is_empty());
+    }
+}
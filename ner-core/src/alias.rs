@@ -0,0 +1,262 @@
+//! # Tabela de Aliases (Siglas e Nomes Alternativos)
+//!
+//! Uma mesma entidade do mundo real costuma aparecer com nomes diferentes no texto:
+//! "Fundação Oswaldo Cruz" e "Fiocruz", "Supremo Tribunal Federal" e "STF".
+//! A [`AliasTable`] mapeia cada **alias** para o seu **nome canônico**, permitindo que
+//! normalização, desambiguação ([`crate::ned`]) e linking ([`crate::nel`]) tratem
+//! ambas as formas como a mesma entidade.
+//!
+//! ## Extração Automática
+//!
+//! Textos jornalísticos costumam apresentar a sigla entre parênteses logo após o nome
+//! completo na primeira menção:
+//!
+//! ```text
+//! "... a Fundação Oswaldo Cruz (Fiocruz) anunciou ..."
+//!        └──── nome canônico ────┘ └alias┘
+//! ```
+//!
+//! [`extract_alias_pairs`] reconhece esse padrão e [`AliasTable::from_texts`] o aplica
+//! a uma coleção de textos. O modelo ([`crate::model::NerModel`]) constrói a tabela a
+//! partir do corpus e dos textos de demonstração.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::alias::AliasTable;
+//!
+//! let mut table = AliasTable::from_texts(["O Supremo Tribunal Federal (STF) decidiu."]);
+//! assert_eq!(table.canonical("STF"), Some("Supremo Tribunal Federal"));
+//!
+//! // Aliases definidos pelo usuário
+//! table.add_alias("Lula", "Luiz Inácio Lula da Silva");
+//! assert_eq!(table.normalize("lula"), "Luiz Inácio Lula da Silva");
+//! ```
+
+use crate::offsets::slice_lossy;
+use crate::tokenizer::{tokenize, Token};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Palavras funcionais permitidas no meio de um nome composto ("Ordem **dos** Advogados").
+const CONNECTORS: &[&str] = &["de", "da", "do", "das", "dos", "e"];
+
+/// Artigos que podem aparecer capitalizados no início da frase ("**O** Supremo Tribunal").
+const ARTICLES: &[&str] = &["a", "o", "as", "os"];
+
+/// Mapeamento alias → nome canônico.
+///
+/// As chaves são armazenadas normalizadas (minúsculas, espaços colapsados) para que
+/// a busca não dependa de capitalização.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AliasTable {
+    aliases: HashMap<String, String>,
+}
+
+impl AliasTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constrói a tabela extraindo pares "Nome Completo (SIGLA)" de cada texto.
+    pub fn from_texts<'a>(texts: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut table = Self::new();
+        for text in texts {
+            for (alias, canonical) in extract_alias_pairs(text) {
+                table.add_alias(&alias, &canonical);
+            }
+        }
+        table
+    }
+
+    /// Registra um alias para um nome canônico. Um alias repetido sobrescreve o anterior.
+    pub fn add_alias(&mut self, alias: &str, canonical: &str) {
+        let key = normalize_key(alias);
+        if key.is_empty() || key == normalize_key(canonical) {
+            return;
+        }
+        self.aliases.insert(key, canonical.trim().to_string());
+    }
+
+    /// Nome canônico de uma menção, se ela for um alias conhecido.
+    pub fn canonical(&self, mention: &str) -> Option<&str> {
+        self.aliases.get(&normalize_key(mention)).map(String::as_str)
+    }
+
+    /// Normaliza uma menção: retorna o nome canônico quando a menção é um alias
+    /// conhecido, ou a própria menção (com espaços colapsados) caso contrário.
+    pub fn normalize(&self, mention: &str) -> String {
+        match self.canonical(mention) {
+            Some(canonical) => canonical.to_string(),
+            None => mention.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+
+    /// Todos os aliases (normalizados) registrados para um nome canônico.
+    pub fn aliases_of(&self, canonical: &str) -> Vec<&str> {
+        let target = normalize_key(canonical);
+        let mut found: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, c)| normalize_key(c) == target)
+            .map(|(a, _)| a.as_str())
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Itera sobre os pares (alias normalizado, nome canônico).
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(a, c)| (a.as_str(), c.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+}
+
+/// Chave de busca: minúsculas e espaços colapsados.
+fn normalize_key(s: &str) -> String {
+    s.split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn starts_uppercase(word: &str) -> bool {
+    word.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+/// Iniciais das palavras capitalizadas (conectores não contam): "Ordem dos Advogados" → "OA".
+fn initials(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .filter_map(|t| t.text.chars().next().filter(|c| c.is_uppercase()))
+        .collect()
+}
+
+/// Extrai pares `(alias, nome canônico)` no padrão "Nome Completo (SIGLA)".
+///
+/// # Heurística
+/// 1. O conteúdo dos parênteses deve ser uma única palavra alfabética iniciada por maiúscula.
+/// 2. O nome canônico é a sequência de palavras capitalizadas (e conectores como
+///    "de", "dos") imediatamente antes do parêntese.
+/// 3. Artigos capitalizados no início ("O Supremo...") e conectores nas bordas são removidos.
+/// 4. Se a sigla está toda em maiúsculas e as iniciais de um sufixo do nome a formam,
+///    o nome começa nesse sufixo.
+pub fn extract_alias_pairs(text: &str) -> Vec<(String, String)> {
+    let tokens = tokenize(text);
+    let mut pairs = Vec::new();
+
+    for i in 1..tokens.len().saturating_sub(2) {
+        if tokens[i].text != "(" || tokens[i + 2].text != ")" {
+            continue;
+        }
+        let alias = &tokens[i + 1].text;
+        if alias.chars().count() < 2
+            || !starts_uppercase(alias)
+            || !alias.chars().all(char::is_alphabetic)
+        {
+            continue;
+        }
+
+        // Recua enquanto houver palavras capitalizadas ou conectores
+        let mut first = i;
+        while first > 0 {
+            let word = &tokens[first - 1].text;
+            if starts_uppercase(word) || CONNECTORS.contains(&word.as_str()) {
+                first -= 1;
+            } else {
+                break;
+            }
+        }
+        let mut last = i; // exclusivo
+
+        // Apara conectores nas bordas e artigos no início
+        while first < last {
+            let word = tokens[first].text.to_lowercase();
+            let is_leading_article = ARTICLES.contains(&word.as_str()) && last - first > 1;
+            if CONNECTORS.contains(&word.as_str()) || is_leading_article {
+                first += 1;
+            } else {
+                break;
+            }
+        }
+        while last > first && CONNECTORS.contains(&tokens[last - 1].text.as_str()) {
+            last -= 1;
+        }
+        if first == last {
+            continue;
+        }
+
+        // Siglas em maiúsculas: prefere o início cujas iniciais formam a sigla,
+        // descartando palavras capitalizadas só por iniciarem a frase ("Pesquisadores do INPE")
+        if alias.chars().all(char::is_uppercase) {
+            if let Some(start) = (first..last).find(|&f| {
+                starts_uppercase(&tokens[f].text)
+                    && initials(&tokens[f..last]) == *alias
+            }) {
+                first = start;
+            }
+        }
+
+        let canonical = slice_lossy(text, tokens[first].start, tokens[last - 1].end);
+        if normalize_key(canonical) != normalize_key(alias) {
+            pairs.push((alias.clone(), canonical.to_string()));
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_alias_pairs() {
+        let pairs = extract_alias_pairs(
+            "Juntamente com a Fundação Oswaldo Cruz (Fiocruz), a Ordem dos Advogados do Brasil (OAB) se manifestou.",
+        );
+        assert_eq!(
+            pairs,
+            vec![
+                ("Fiocruz".to_string(), "Fundação Oswaldo Cruz".to_string()),
+                ("OAB".to_string(), "Ordem dos Advogados do Brasil".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_ignores_non_alias_parentheses() {
+        assert!(extract_alias_pairs("A Sra. Silva (nascida em 1980) chegou.").is_empty());
+        assert!(extract_alias_pairs("o valor subiu (2025) ontem").is_empty());
+        // Palavra capitalizada por iniciar a frase é descartada via iniciais da sigla
+        assert_eq!(
+            extract_alias_pairs("Pesquisadores do Instituto Nacional de Pesquisas Espaciais (INPE) alertam."),
+            vec![("INPE".to_string(), "Instituto Nacional de Pesquisas Espaciais".to_string())]
+        );
+        // Artigo capitalizado no início da frase não faz parte do nome
+        assert_eq!(
+            extract_alias_pairs("O Supremo Tribunal Federal (STF) decidiu."),
+            vec![("STF".to_string(), "Supremo Tribunal Federal".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_user_aliases_and_lookup() {
+        let mut table = AliasTable::new();
+        table.add_alias("OMS", "Organização Mundial da Saúde");
+        table.add_alias("oms", "Organização Mundial da Saúde");
+        table.add_alias("Brasil", "brasil"); // alias idêntico ao canônico é ignorado
+
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.canonical("Oms"), Some("Organização Mundial da Saúde"));
+        assert_eq!(table.normalize("Rio  de Janeiro"), "Rio de Janeiro");
+        assert_eq!(table.aliases_of("organização mundial da saúde"), vec!["oms"]);
+    }
+}
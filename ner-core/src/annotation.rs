@@ -0,0 +1,210 @@
+//! # Exportação para Plataformas de Anotação
+//!
+//! Ferramentas como INCEpTION e Label Studio importam pré-anotações para que um
+//! anotador humano apenas revise (em vez de marcar tudo do zero). Este módulo converte
+//! as entidades do pipeline em dois formatos padronizados:
+//!
+//! - **W3C Web Annotation** ([`to_web_annotations`]): uma anotação por entidade, com
+//!   a categoria no `body` e o trecho no `target`, descrito por dois seletores —
+//!   posição (`TextPositionSelector`) e citação (`TextQuoteSelector`, que sobrevive
+//!   a pequenas edições no documento).
+//! - **JSON Patch (RFC 6902)** ([`to_json_patch`]): uma operação `add` por entidade,
+//!   para acrescentar as entidades a um documento JSON já existente.
+//!
+//! Os offsets de [`EntitySpan`] são de byte; ambos os formatos usam offsets de
+//! **caractere** (ver [`crate::offsets::char_offset`]).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::annotation::to_web_annotations;
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let pipeline = NerPipeline::new();
+//! let text = "Ação em Brasília.";
+//! let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!
+//! let annotations = to_web_annotations(text, &entities, "doc.txt");
+//! let json = serde_json::to_value(&annotations[0]).unwrap();
+//! assert_eq!(json["body"][0]["value"], "LOC");
+//! // "Brasília" começa no byte 10, mas no caractere 8 ("ç" e "ã" ocupam 2 bytes)
+//! assert_eq!(entities[0].start, 10);
+//! assert_eq!(json["target"]["selector"][0]["start"], 8);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::offsets::{char_offset, slice_lossy};
+use crate::tagger::EntitySpan;
+
+/// Contexto JSON-LD do modelo de dados do W3C Web Annotation.
+pub const WEB_ANNOTATION_CONTEXT: &str = "http://www.w3.org/ns/anno.jsonld";
+
+/// Quantos caracteres de contexto o `TextQuoteSelector` guarda antes e depois do trecho.
+pub const QUOTE_CONTEXT_CHARS: usize = 32;
+
+/// Uma anotação no formato W3C Web Annotation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebAnnotation {
+    #[serde(rename = "@context")]
+    pub context: String,
+    /// Identificador da anotação (`<source>#entity-<n>`).
+    pub id: String,
+    /// Sempre `"Annotation"`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub body: Vec<AnnotationBody>,
+    pub target: AnnotationTarget,
+}
+
+/// Corpo da anotação: a categoria da entidade como etiqueta (`purpose: "tagging"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationBody {
+    /// Sempre `"TextualBody"`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub purpose: String,
+    /// Rótulo da categoria (ex: "PER").
+    pub value: String,
+}
+
+/// Alvo da anotação: o documento e os seletores do trecho.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationTarget {
+    pub source: String,
+    pub selector: Vec<Selector>,
+}
+
+/// Seletores de trecho do W3C. Offsets em caracteres, `end` exclusivo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Selector {
+    TextPositionSelector { start: usize, end: usize },
+    TextQuoteSelector { exact: String, prefix: String, suffix: String },
+}
+
+/// Entidade com offsets de caractere, valor das operações de [`to_json_patch`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotatedEntity {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub label: String,
+    pub confidence: f64,
+    pub source: String,
+}
+
+impl AnnotatedEntity {
+    /// Converte uma entidade do pipeline, trocando offsets de byte por offsets de caractere.
+    pub fn from_span(text: &str, span: &EntitySpan) -> Self {
+        Self {
+            start: char_offset(text, span.start),
+            end: char_offset(text, span.end),
+            text: span.text.clone(),
+            label: span.category.name().to_string(),
+            confidence: span.confidence,
+            source: span.source.clone(),
+        }
+    }
+}
+
+/// Operação de JSON Patch (RFC 6902). Apenas `add` é gerada pelo exportador.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: AnnotatedEntity },
+}
+
+/// Uma [`WebAnnotation`] por entidade, na ordem recebida.
+///
+/// `source` identifica o documento no alvo da anotação (URI ou nome de arquivo).
+pub fn to_web_annotations(text: &str, entities: &[EntitySpan], source: &str) -> Vec<WebAnnotation> {
+    entities
+        .iter()
+        .enumerate()
+        .map(|(i, span)| {
+            let entity = AnnotatedEntity::from_span(text, span);
+            WebAnnotation {
+                context: WEB_ANNOTATION_CONTEXT.to_string(),
+                id: format!("{source}#entity-{i}"),
+                kind: "Annotation".to_string(),
+                body: vec![AnnotationBody {
+                    kind: "TextualBody".to_string(),
+                    purpose: "tagging".to_string(),
+                    value: entity.label,
+                }],
+                target: AnnotationTarget {
+                    source: source.to_string(),
+                    selector: vec![
+                        Selector::TextPositionSelector { start: entity.start, end: entity.end },
+                        quote_selector(text, span),
+                    ],
+                },
+            }
+        })
+        .collect()
+}
+
+/// Operações `add` que acrescentam cada entidade ao array em `array_path`
+/// (ex: `"/entities"` gera `{"op": "add", "path": "/entities/-", ...}`).
+pub fn to_json_patch(text: &str, entities: &[EntitySpan], array_path: &str) -> Vec<PatchOperation> {
+    let path = format!("{}/-", array_path.trim_end_matches('/'));
+    entities
+        .iter()
+        .map(|span| PatchOperation::Add {
+            path: path.clone(),
+            value: AnnotatedEntity::from_span(text, span),
+        })
+        .collect()
+}
+
+/// Citação exata do trecho com até [`QUOTE_CONTEXT_CHARS`] caracteres de cada lado.
+fn quote_selector(text: &str, span: &EntitySpan) -> Selector {
+    let before = slice_lossy(text, 0, span.start);
+    let after = slice_lossy(text, span.end, text.len());
+    let prefix_start = before
+        .char_indices()
+        .rev()
+        .nth(QUOTE_CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    Selector::TextQuoteSelector {
+        exact: slice_lossy(text, span.start, span.end).to_string(),
+        prefix: before[prefix_start..].to_string(),
+        suffix: after.chars().take(QUOTE_CONTEXT_CHARS).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::EntityCategory;
+
+    #[test]
+    fn test_exports_use_char_offsets() {
+        let text = "Ação em São Paulo hoje";
+        let entities = vec![EntitySpan::test_at(text, "São Paulo", EntityCategory::Loc)];
+
+        let annotations = to_web_annotations(text, &entities, "urn:doc:1");
+        assert_eq!(annotations[0].id, "urn:doc:1#entity-0");
+        assert_eq!(
+            annotations[0].target.selector,
+            vec![
+                Selector::TextPositionSelector { start: 8, end: 17 },
+                Selector::TextQuoteSelector {
+                    exact: "São Paulo".to_string(),
+                    prefix: "Ação em ".to_string(),
+                    suffix: " hoje".to_string(),
+                },
+            ]
+        );
+        let json = serde_json::to_value(&annotations[0]).unwrap();
+        assert_eq!(json["@context"], WEB_ANNOTATION_CONTEXT);
+        assert_eq!(json["target"]["selector"][1]["type"], "TextQuoteSelector");
+
+        let patch = serde_json::to_value(to_json_patch(text, &entities, "/entities/")).unwrap();
+        assert_eq!(patch[0]["op"], "add");
+        assert_eq!(patch[0]["path"], "/entities/-");
+        assert_eq!(patch[0]["value"]["start"], 8);
+        assert_eq!(patch[0]["value"]["label"], "LOC");
+    }
+}
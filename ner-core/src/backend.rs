@@ -0,0 +1,103 @@
+//! # Interface Comum dos Algoritmos (Backends)
+//!
+//! HMM, MaxEnt, Perceptron e o modelo de spans nasceram como experimentos separados,
+//! cada um com o seu `predict`. Para o pipeline, porém, eles fazem a mesma coisa:
+//! recebem as palavras de uma sentença e devolvem tags (ou spans). Este módulo dá
+//! nome a esses dois contratos:
+//!
+//! - [`SequenceTagger`]: uma tag BIO por token (`"B-PER"`, `"O"`, ...).
+//! - [`SpanPredictor`]: trechos rotulados ([`Span`]), possivelmente sobrepostos.
+//!
+//! O pipeline despacha os modos `Hmm`, `MaxEnt`, `Perceptron` e `SpanBased` por meio
+//! desses traits, e qualquer tipo que os implemente pode substituir o backend de um modo
+//! ([`crate::NerPipeline::with_sequence_tagger`], [`crate::NerPipeline::with_span_predictor`]).
+//!
+//! ## Exemplo: um backend próprio
+//!
+//! ```rust
+//! use ner_core::backend::SequenceTagger;
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! /// Marca como pessoa toda palavra terminada em "inho".
+//! struct Apelidos;
+//!
+//! impl SequenceTagger for Apelidos {
+//!     fn name(&self) -> &str {
+//!         "apelidos"
+//!     }
+//!
+//!     fn predict(&self, tokens: &[String]) -> Vec<String> {
+//!         tokens.iter().map(|t| if t.ends_with("inho") { "B-PER" } else { "O" }.to_string()).collect()
+//!     }
+//! }
+//!
+//! let pipeline = NerPipeline::new().with_sequence_tagger(AlgorithmMode::Perceptron, Apelidos);
+//! let (_, entities) = pipeline.analyze_with_mode("Chamaram o Ronaldinho.", AlgorithmMode::Perceptron, TokenizerMode::Standard);
+//! assert_eq!(entities[0].text, "Ronaldinho");
+//! assert_eq!(entities[0].source, "apelidos");
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::features::FeatureExtractor;
+
+/// Representa um span (intervalo) de tokens com uma label associada.
+///
+/// # Exemplo
+/// Em "Universidade de São Paulo", o span "São Paulo":
+/// `Span { start: 2, end: 4, label: "LOC" }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    /// Índice do token inicial (inclusivo)
+    pub start: usize,
+    /// Índice do token final (exclusivo)
+    pub end: usize,
+    /// Rótulo da entidade (ex: "PER", "ORG")
+    pub label: String,
+}
+
+/// Um algoritmo que atribui uma tag BIO a cada token.
+pub trait SequenceTagger: Send + Sync {
+    /// Nome curto, usado como `source` dos eventos `TagAssigned` e das entidades.
+    fn name(&self) -> &str;
+
+    /// Uma tag BIO por token de `tokens` (mesmo tamanho). Tags desconhecidas viram `O`.
+    fn predict(&self, tokens: &[String]) -> Vec<String>;
+
+    /// Como [`SequenceTagger::predict`], com a probabilidade de cada tag escolhida (entre
+    /// 0 e 1), usada como confiança dos tokens e das entidades. Por padrão, 1.0.
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
+        self.predict(tokens).into_iter().map(|tag| (tag, 1.0)).collect()
+    }
+
+    /// O backend pode ser usado? Backends indisponíveis fazem o pipeline seguir a cadeia de fallback.
+    fn is_trained(&self) -> bool {
+        true
+    }
+
+    /// Extrator de features do backend, se houver; o pipeline o usa para emitir
+    /// os eventos `FeaturesComputed`.
+    fn extractor(&self) -> Option<&dyn FeatureExtractor> {
+        None
+    }
+}
+
+/// Um algoritmo que classifica trechos inteiros em vez de tokens.
+pub trait SpanPredictor: Send + Sync {
+    /// Nome curto, usado como `source` dos eventos `TagAssigned` e das entidades.
+    fn name(&self) -> &str;
+
+    /// Trechos rotulados encontrados em `tokens`; podem se sobrepor.
+    fn predict(&self, tokens: &[String]) -> Vec<Span>;
+
+    /// Como [`SpanPredictor::predict`], com a probabilidade do rótulo de cada trecho
+    /// (entre 0 e 1), usada como confiança das entidades. Por padrão, 1.0.
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(Span, f64)> {
+        self.predict(tokens).into_iter().map(|span| (span, 1.0)).collect()
+    }
+
+    /// O backend pode ser usado? Backends indisponíveis fazem o pipeline seguir a cadeia de fallback.
+    fn is_trained(&self) -> bool {
+        true
+    }
+}
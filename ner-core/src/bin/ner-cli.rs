@@ -0,0 +1,262 @@
+//! # `ner-cli`: NER em lote pela linha de comando
+//!
+//! Lê texto de arquivos (ou da entrada padrão), roda o pipeline no modo e tokenizador
+//! escolhidos e escreve o resultado em JSON (uma linha por documento), CoNLL, texto
+//! anotado ou um relatório em HTML ou Markdown com as entidades destacadas:
+//!
+//! ```text
+//! cat noticia.txt | cargo run -p ner-core --bin ner-cli -- --format inline
+//! cargo run -p ner-core --bin ner-cli -- --mode crf_only --format conll a.txt b.txt > saida.conll
+//! cargo run -p ner-core --bin ner-cli -- --lines --model modelo.bin frases.txt > entidades.jsonl
+//! cargo run -p ner-core --bin ner-cli -- --format markdown noticia.txt > relatorio.md
+//! ```
+//!
+//! Modos e tokenizadores usam os mesmos nomes do protocolo WebSocket (`rules_only`,
+//! `bpe_lite`...). Com `--lines`, cada linha não vazia é um documento.
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ner_core::export::{to_html, to_markdown};
+use ner_core::model::NerModel;
+use ner_core::render::{to_conll, to_inline};
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+use serde::de::DeserializeOwned;
+
+const USAGE: &str = "uso: ner-cli [--mode MODO] [--tokenizer TOKENIZADOR] [--format FORMATO] [--lines] [--model ARQUIVO] [ARQUIVO...]
+
+Sem arquivos (ou com `-`), lê da entrada padrão.
+
+  --mode        hybrid (padrão), rules_only, crf_only, features_only, hmm, max_ent, memm, perceptron, span_based
+  --tokenizer   standard (padrão), char_level, aggressive, conservative, bpe_lite, bpe
+  --format      json (padrão, uma linha por documento), conll, inline ([trecho]{CAT}),
+                html (entidades em <mark> coloridos) ou markdown (**trecho** `CAT`)
+  --lines       cada linha não vazia da entrada é um documento
+  --model       modelo gravado com NerModel::save, em vez de construir o padrão";
+
+/// Formato da saída.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Uma linha JSON por documento, com a origem e as entidades.
+    Json,
+    /// `token<TAB>tag`, com uma linha em branco entre sentenças e entre documentos.
+    Conll,
+    /// O texto com as entidades marcadas como `[trecho]{CAT}`.
+    Inline,
+    /// O texto em HTML, com as entidades destacadas na cor da categoria.
+    Html,
+    /// O texto em Markdown, com as entidades em negrito e a categoria em código.
+    Markdown,
+}
+
+/// Opções da linha de comando.
+#[derive(Debug, PartialEq)]
+struct Options {
+    mode: AlgorithmMode,
+    tokenizer: TokenizerMode,
+    format: OutputFormat,
+    lines: bool,
+    model: Option<PathBuf>,
+    /// Arquivos de entrada; `-` é a entrada padrão.
+    inputs: Vec<String>,
+}
+
+/// Interpreta os argumentos (sem o nome do programa). `Ok(None)` pede a ajuda.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        mode: AlgorithmMode::Hybrid,
+        tokenizer: TokenizerMode::Standard,
+        format: OutputFormat::Json,
+        lines: false,
+        model: None,
+        inputs: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} precisa de um valor"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--mode" => options.mode = parse_name("--mode", &value("--mode")?)?,
+            "--tokenizer" => options.tokenizer = parse_name("--tokenizer", &value("--tokenizer")?)?,
+            "--format" => {
+                options.format = match value("--format")?.as_str() {
+                    "json" => OutputFormat::Json,
+                    "conll" => OutputFormat::Conll,
+                    "inline" => OutputFormat::Inline,
+                    "html" => OutputFormat::Html,
+                    "markdown" | "md" => OutputFormat::Markdown,
+                    other => {
+                        return Err(format!("--format desconhecido: {other} (use json, conll, inline, html ou markdown)"))
+                    }
+                }
+            }
+            "--lines" => options.lines = true,
+            "--model" => options.model = Some(PathBuf::from(value("--model")?)),
+            flag if flag.starts_with("--") => return Err(format!("opção desconhecida: {flag}")),
+            _ => options.inputs.push(arg),
+        }
+    }
+    if options.inputs.is_empty() {
+        options.inputs.push("-".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Lê um modo ou tokenizador pelo nome usado no JSON (`rules_only`, `bpe_lite`...).
+fn parse_name<T: DeserializeOwned>(flag: &str, name: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).map_err(|_| format!("{flag} desconhecido: {name}"))
+}
+
+/// Documentos de uma entrada: o conteúdo inteiro ou, com `--lines`, cada linha não vazia.
+fn documents(mut reader: impl BufRead, lines: bool) -> io::Result<Vec<String>> {
+    if lines {
+        return reader.lines().filter(|line| !matches!(line, Ok(l) if l.trim().is_empty())).collect();
+    }
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(vec![text])
+}
+
+/// Analisa cada documento e escreve o resultado em `out`; retorna o total de entidades.
+///
+/// Em JSON com `--lines`, cada linha leva também o texto e a posição do documento
+/// (`index`, a partir de 0, sem contar as linhas vazias).
+fn annotate(
+    pipeline: &NerPipeline,
+    options: &Options,
+    source: &str,
+    docs: &[String],
+    out: &mut impl Write,
+) -> io::Result<usize> {
+    let mut total = 0;
+    for (i, text) in docs.iter().enumerate() {
+        let (tagged, entities) = pipeline.analyze_with_mode(text, options.mode, options.tokenizer);
+        total += entities.len();
+        match options.format {
+            OutputFormat::Json => {
+                let mut line = serde_json::json!({ "source": source, "entities": entities });
+                if options.lines {
+                    line["index"] = i.into();
+                    line["text"] = text.as_str().into();
+                }
+                writeln!(out, "{line}")?;
+            }
+            OutputFormat::Conll => writeln!(out, "{}", to_conll(&tagged))?,
+            OutputFormat::Inline | OutputFormat::Html | OutputFormat::Markdown => {
+                let rendered = match options.format {
+                    OutputFormat::Html => to_html(text, &entities),
+                    OutputFormat::Markdown => to_markdown(text, &entities),
+                    _ => to_inline(text, &entities),
+                };
+                if rendered.ends_with('\n') {
+                    write!(out, "{rendered}")?;
+                } else {
+                    writeln!(out, "{rendered}")?;
+                }
+            }
+        }
+    }
+    Ok(total)
+}
+
+fn run(options: &Options) -> io::Result<usize> {
+    let pipeline = match &options.model {
+        Some(path) => NerPipeline::from_model(NerModel::load(path)?),
+        None => NerPipeline::new(),
+    };
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut total = 0;
+    for input in &options.inputs {
+        let docs = if input == "-" {
+            documents(io::stdin().lock(), options.lines)?
+        } else {
+            let file = std::fs::File::open(input).map_err(|e| io::Error::new(e.kind(), format!("{input}: {e}")))?;
+            documents(io::BufReader::new(file), options.lines)?
+        };
+        total += annotate(&pipeline, options, input, &docs, &mut out)?;
+    }
+    out.flush()?;
+    Ok(total)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("ner-cli: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(total) => {
+            eprintln!("{total} entidade(s) em {} entrada(s)", options.inputs.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("ner-cli: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Option<Options>, String> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = args("--mode rules_only --tokenizer bpe_lite --format conll --lines a.txt -").unwrap().unwrap();
+        assert_eq!(options.mode, AlgorithmMode::RulesOnly);
+        assert_eq!(options.tokenizer, TokenizerMode::BpeLite);
+        assert_eq!(options.format, OutputFormat::Conll);
+        assert!(options.lines);
+        assert_eq!(options.inputs, ["a.txt", "-"]);
+
+        // Sem arquivos: entrada padrão
+        assert_eq!(args("").unwrap().unwrap().inputs, ["-"]);
+        assert_eq!(args("--help").unwrap(), None);
+        assert!(args("--mode rapido").unwrap_err().contains("--mode desconhecido"));
+        assert!(args("--format").unwrap_err().contains("precisa de um valor"));
+        assert!(args("--verbose").is_err());
+    }
+
+    #[cfg(feature = "rules")]
+    #[test]
+    fn test_annotate_formats() {
+        let pipeline = NerPipeline::new();
+        let docs = documents("O Brasil venceu.\n\nA Argentina perdeu.\n".as_bytes(), true).unwrap();
+        assert_eq!(docs.len(), 2);
+
+        let render = |format| {
+            let options = Options { format, lines: true, ..args("--mode rules_only").unwrap().unwrap() };
+            let mut out = Vec::new();
+            let total = annotate(&pipeline, &options, "-", &docs, &mut out).unwrap();
+            (total, String::from_utf8(out).unwrap())
+        };
+
+        let (total, inline) = render(OutputFormat::Inline);
+        assert_eq!(total, 2);
+        assert_eq!(inline, "O [Brasil]{LOC} venceu.\nA [Argentina]{LOC} perdeu.\n");
+        let (_, markdown) = render(OutputFormat::Markdown);
+        assert_eq!(markdown, "O **Brasil** `LOC` venceu.\nA **Argentina** `LOC` perdeu.\n");
+        assert!(render(OutputFormat::Html).1.contains(r#"<mark class="ner-LOC""#));
+
+        let (_, conll) = render(OutputFormat::Conll);
+        assert!(conll.starts_with("O\tO\nBrasil\tB-LOC\n") && conll.contains("\n\nA\tO\nArgentina\tB-LOC\n"));
+
+        let (_, json) = render(OutputFormat::Json);
+        let first: serde_json::Value = serde_json::from_str(json.lines().next().unwrap()).unwrap();
+        assert_eq!(first["index"], 0);
+        assert_eq!(first["entities"][0]["text"], "Brasil");
+    }
+}
@@ -0,0 +1,222 @@
+//! # Estados, Capitais, Siglas de UF e Gentílicos do Brasil
+//!
+//! Notícias brasileiras citam lugares de várias formas: pelo nome ("Minas Gerais"), pela
+//! capital ("Belo Horizonte"), pela sigla da UF ("Uberlândia (MG)") ou só pelo gentílico
+//! ("o governo mineiro"). Este módulo reúne as 26 unidades federativas e o Distrito
+//! Federal com essas quatro formas e classifica cada menção com um [`LocSubtype`].
+//!
+//! O recurso alimenta o resto do sistema:
+//! - os nomes de estados e capitais entram nos gazetteers de localização do modelo;
+//! - a regra `uf_code` marca como `LOC` a sigla que segue uma localização
+//!   ("Campinas, SP", "Campinas (SP)", "Campinas - SP");
+//! - as features `is_uf_code`, `is_demonym` e `demonym_of=<uf>` permitem ao modelo
+//!   estatístico associar o gentílico ao lugar, mesmo quando ele não é citado.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::brazil::{demonym_place, lookup_place, LocSubtype};
+//!
+//! let place = lookup_place("Belo Horizonte").unwrap();
+//! assert_eq!((place.state.uf, place.subtype), ("MG", LocSubtype::Capital));
+//!
+//! assert_eq!(lookup_place("SP").unwrap().subtype, LocSubtype::UfCode);
+//! assert!(lookup_place("sp").is_none()); // a sigla só vale em maiúsculas
+//!
+//! let place = demonym_place("cariocas").unwrap();
+//! assert_eq!((place.state.uf, place.subtype), ("RJ", LocSubtype::Capital));
+//! assert_eq!(demonym_place("gaúcha").unwrap().state.name, "Rio Grande do Sul");
+//! ```
+
+use serde::Serialize;
+
+use crate::features::GazetteerKey;
+
+/// Que tipo de lugar uma menção designa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocSubtype {
+    /// Um dos 26 estados ("Bahia").
+    State,
+    /// O Distrito Federal.
+    FederalDistrict,
+    /// A capital de uma UF ("Salvador", "Brasília").
+    Capital,
+    /// A sigla de uma UF ("BA", "DF").
+    UfCode,
+}
+
+/// Uma unidade federativa e suas formas de menção.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BrazilianState {
+    pub name: &'static str,
+    /// Sigla oficial ("SP").
+    pub uf: &'static str,
+    pub capital: &'static str,
+    /// Região geográfica ("Sudeste").
+    pub region: &'static str,
+    /// Gentílicos do estado, no masculino singular ("mineiro").
+    pub demonyms: &'static [&'static str],
+    /// Gentílicos da capital, no masculino singular ("belo-horizontino").
+    pub capital_demonyms: &'static [&'static str],
+}
+
+impl BrazilianState {
+    /// Subtipo do nome do estado (o Distrito Federal não é estado).
+    pub fn subtype(&self) -> LocSubtype {
+        if self.uf == "DF" { LocSubtype::FederalDistrict } else { LocSubtype::State }
+    }
+}
+
+/// Uma menção reconhecida: a UF a que se refere e o tipo de lugar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlaceMatch {
+    pub state: &'static BrazilianState,
+    pub subtype: LocSubtype,
+}
+
+const fn uf(
+    name: &'static str,
+    uf: &'static str,
+    capital: &'static str,
+    region: &'static str,
+    demonyms: &'static [&'static str],
+    capital_demonyms: &'static [&'static str],
+) -> BrazilianState {
+    BrazilianState { name, uf, capital, region, demonyms, capital_demonyms }
+}
+
+/// As 27 unidades federativas, em ordem alfabética de sigla.
+pub const STATES: [BrazilianState; 27] = [
+    uf("Acre", "AC", "Rio Branco", "Norte", &["acriano", "acreano"], &["rio-branquense"]),
+    uf("Alagoas", "AL", "Maceió", "Nordeste", &["alagoano"], &["maceioense"]),
+    uf("Amazonas", "AM", "Manaus", "Norte", &["amazonense"], &["manauara", "manauense"]),
+    uf("Amapá", "AP", "Macapá", "Norte", &["amapaense"], &["macapaense"]),
+    uf("Bahia", "BA", "Salvador", "Nordeste", &["baiano"], &["soteropolitano"]),
+    uf("Ceará", "CE", "Fortaleza", "Nordeste", &["cearense"], &["fortalezense"]),
+    uf("Distrito Federal", "DF", "Brasília", "Centro-Oeste", &[], &["brasiliense", "candango"]),
+    uf("Espírito Santo", "ES", "Vitória", "Sudeste", &["capixaba", "espírito-santense"], &["vitoriense"]),
+    uf("Goiás", "GO", "Goiânia", "Centro-Oeste", &["goiano"], &["goianiense"]),
+    uf("Maranhão", "MA", "São Luís", "Nordeste", &["maranhense"], &["ludovicense"]),
+    uf("Minas Gerais", "MG", "Belo Horizonte", "Sudeste", &["mineiro"], &["belo-horizontino"]),
+    uf("Mato Grosso do Sul", "MS", "Campo Grande", "Centro-Oeste", &["sul-mato-grossense"], &["campo-grandense"]),
+    uf("Mato Grosso", "MT", "Cuiabá", "Centro-Oeste", &["mato-grossense"], &["cuiabano"]),
+    uf("Pará", "PA", "Belém", "Norte", &["paraense"], &["belenense"]),
+    uf("Paraíba", "PB", "João Pessoa", "Nordeste", &["paraibano"], &["pessoense"]),
+    uf("Pernambuco", "PE", "Recife", "Nordeste", &["pernambucano"], &["recifense"]),
+    uf("Piauí", "PI", "Teresina", "Nordeste", &["piauiense"], &["teresinense"]),
+    uf("Paraná", "PR", "Curitiba", "Sul", &["paranaense"], &["curitibano"]),
+    uf("Rio de Janeiro", "RJ", "Rio de Janeiro", "Sudeste", &["fluminense"], &["carioca"]),
+    uf("Rio Grande do Norte", "RN", "Natal", "Nordeste", &["potiguar", "norte-rio-grandense"], &["natalense"]),
+    uf("Rondônia", "RO", "Porto Velho", "Norte", &["rondoniense"], &["porto-velhense"]),
+    uf("Roraima", "RR", "Boa Vista", "Norte", &["roraimense"], &["boa-vistense"]),
+    uf("Rio Grande do Sul", "RS", "Porto Alegre", "Sul", &["gaúcho", "sul-rio-grandense"], &["porto-alegrense"]),
+    uf("Santa Catarina", "SC", "Florianópolis", "Sul", &["catarinense"], &["florianopolitano"]),
+    uf("Sergipe", "SE", "Aracaju", "Nordeste", &["sergipano"], &["aracajuano"]),
+    uf("São Paulo", "SP", "São Paulo", "Sudeste", &["paulista"], &["paulistano"]),
+    uf("Tocantins", "TO", "Palmas", "Norte", &["tocantinense"], &["palmense"]),
+];
+
+/// UF da sigla, que precisa estar em maiúsculas ("SE" é Sergipe; "se" é pronome).
+pub fn state_by_uf(code: &str) -> Option<&'static BrazilianState> {
+    STATES.iter().find(|s| s.uf == code)
+}
+
+/// `word` é uma sigla de UF (em maiúsculas)?
+pub fn is_uf_code(word: &str) -> bool {
+    state_by_uf(word).is_some()
+}
+
+/// Reconhece o nome de um estado, de uma capital ou uma sigla de UF.
+///
+/// Nomes são comparados sem diferenciar maiúsculas; siglas, só em maiúsculas. Quando
+/// estado e capital têm o mesmo nome ("São Paulo", "Rio de Janeiro"), vale o estado.
+pub fn lookup_place(text: &str) -> Option<PlaceMatch> {
+    if let Some(state) = state_by_uf(text) {
+        return Some(PlaceMatch { state, subtype: LocSubtype::UfCode });
+    }
+    let key = GazetteerKey::normalize(text);
+    let is = |name: &str| GazetteerKey::normalize(name) == key;
+    STATES
+        .iter()
+        .find(|s| is(s.name))
+        .map(|state| PlaceMatch { state, subtype: state.subtype() })
+        .or_else(|| STATES.iter().find(|s| is(s.capital)).map(|state| PlaceMatch { state, subtype: LocSubtype::Capital }))
+}
+
+/// Lugar designado por um gentílico ("paulista" → SP; "paulistanas" → a capital de SP).
+///
+/// Aceita as flexões de gênero e número ("mineiro", "mineira", "mineiros", "mineiras").
+/// O subtipo é o do estado ou [`LocSubtype::Capital`] para gentílicos de capital.
+pub fn demonym_place(word: &str) -> Option<PlaceMatch> {
+    let word = word.to_lowercase();
+    STATES.iter().find_map(|state| {
+        if state.demonyms.iter().any(|d| is_inflection(d, &word)) {
+            Some(PlaceMatch { state, subtype: state.subtype() })
+        } else if state.capital_demonyms.iter().any(|d| is_inflection(d, &word)) {
+            Some(PlaceMatch { state, subtype: LocSubtype::Capital })
+        } else {
+            None
+        }
+    })
+}
+
+/// `word` é `demonym` flexionado em gênero ou número?
+fn is_inflection(demonym: &str, word: &str) -> bool {
+    let Some(rest) = word.strip_prefix(demonym.trim_end_matches('o')) else {
+        return false;
+    };
+    match demonym.strip_suffix('o') {
+        // "mineiro": mineiro, mineira, mineiros, mineiras
+        Some(_) => matches!(rest, "o" | "a" | "os" | "as"),
+        // "paulista", "potiguar": + "s" / "es"
+        None => matches!(rest, "" | "s" | "es"),
+    }
+}
+
+/// Todos os nomes de estados e capitais (sem repetição), para os gazetteers.
+pub fn place_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = STATES.iter().flat_map(|s| [s.name, s.capital]).collect();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_state_is_found_by_all_forms() {
+        assert_eq!(STATES.iter().filter(|s| s.subtype() == LocSubtype::State).count(), 26);
+        for state in &STATES {
+            assert_eq!(lookup_place(state.uf).unwrap().state, state);
+            assert_eq!(lookup_place(&state.name.to_uppercase()).unwrap().state, state, "{}", state.name);
+            assert_eq!(lookup_place(state.capital).unwrap().state, state, "{}", state.capital);
+            for demonym in state.demonyms.iter().chain(state.capital_demonyms) {
+                assert_eq!(demonym_place(demonym).unwrap().state, state, "{demonym}");
+            }
+        }
+        // Siglas são únicas
+        let mut codes: Vec<&str> = STATES.iter().map(|s| s.uf).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), 27);
+    }
+
+    #[test]
+    fn test_subtypes_and_inflections() {
+        assert_eq!(lookup_place("Distrito Federal").unwrap().subtype, LocSubtype::FederalDistrict);
+        assert_eq!(lookup_place("Brasília").unwrap().subtype, LocSubtype::Capital);
+        assert_eq!(lookup_place("São Paulo").unwrap().subtype, LocSubtype::State);
+        assert_eq!(lookup_place("Campinas"), None);
+        assert_eq!(lookup_place("To"), None);
+
+        assert_eq!(demonym_place("Mineiras").unwrap().state.uf, "MG");
+        assert_eq!(demonym_place("potiguares").unwrap().state.uf, "RN");
+        assert_eq!(demonym_place("paulistas").unwrap().subtype, LocSubtype::State);
+        assert_eq!(demonym_place("paulistanos").unwrap().subtype, LocSubtype::Capital);
+        assert_eq!(demonym_place("brasilienses").unwrap().subtype, LocSubtype::Capital);
+        assert_eq!(demonym_place("mineir"), None);
+        assert_eq!(demonym_place("mineiroso"), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! # Calibração de Confiança (Platt Scaling)
+//!
+//! Cada modo produz a `confidence` das entidades numa escala própria: a marginal do CRF
+//! ([`crate::viterbi::marginals`]), a posterior do HMM, o softmax do MaxEnt ou do
+//! Perceptron, a confiança fixa de uma regra. Uma entidade com 0.9 não acerta 90% das
+//! vezes em nenhum deles. A **calibração** aprende, em dados de desenvolvimento com ouro
+//! conhecido, uma função que leva a confiança bruta à probabilidade de acerto:
+//!
+//! ```text
+//! P(acerto | c) = 1 / (1 + exp(A · logit(c) + B))
+//! ```
+//!
+//! Os parâmetros `A` e `B` ([`PlattScaling`]) são ajustados por máxima verossimilhança
+//! (Newton com busca linear, como em Platt 1999 / Lin et al. 2007), com os alvos
+//! suavizados para não saturar em 0 e 1 quando há poucas entidades. Com `A = -1` e
+//! `B = 0` a função é a identidade.
+//!
+//! O ajuste é por modo ([`ConfidenceCalibration`]) e fica em `NerModel::calibration`; o
+//! pipeline o aplica à confiança de toda entidade do modo antes dos limiares por
+//! categoria ([`crate::thresholds`]). Os pares de treino devem vir de sentenças que o
+//! modelo não viu, como a fatia reservada por [`crate::self_check::SelfCheckConfig`]
+//! (ver [`crate::NerPipeline::calibrate_confidence`]).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::calibration::PlattScaling;
+//!
+//! // Confiança bruta alta, mas só metade das entidades estava certa
+//! let samples: Vec<(f64, bool)> = (0..40).map(|i| (0.95, i % 2 == 0)).collect();
+//! let scaling = PlattScaling::fit(&samples).unwrap();
+//! assert!((scaling.apply(0.95) - 0.5).abs() < 0.05);
+//! assert_eq!(PlattScaling::IDENTITY.apply(0.7), 0.7);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::GoldEntity;
+use crate::pipeline::AlgorithmMode;
+use crate::tagger::EntitySpan;
+
+/// Menor distância de 0 e 1 usada no logit da confiança bruta.
+const LOGIT_EPSILON: f64 = 1e-6;
+
+/// Sigmoide ajustada sobre o logit da confiança bruta.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlattScaling {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl PlattScaling {
+    /// Não altera a confiança.
+    pub const IDENTITY: PlattScaling = PlattScaling { a: -1.0, b: 0.0 };
+
+    /// Ajusta `A` e `B` em pares (confiança bruta, acertou?). `None` sem amostras.
+    pub fn fit(samples: &[(f64, bool)]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let positives = samples.iter().filter(|(_, correct)| *correct).count() as f64;
+        let negatives = samples.len() as f64 - positives;
+        // Alvos suavizados (Platt): evitam A e B infinitos com classes separáveis
+        let hi = (positives + 1.0) / (positives + 2.0);
+        let lo = 1.0 / (negatives + 2.0);
+        let data: Vec<(f64, f64)> = samples.iter().map(|&(c, correct)| (logit(c), if correct { hi } else { lo })).collect();
+
+        let loss = |a: f64, b: f64| -> f64 {
+            data.iter()
+                .map(|&(f, t)| {
+                    let z = f * a + b;
+                    if z >= 0.0 { t * z + (-z).exp().ln_1p() } else { (t - 1.0) * z + z.exp().ln_1p() }
+                })
+                .sum()
+        };
+
+        let (mut a, mut b) = (0.0, ((negatives + 1.0) / (positives + 1.0)).ln());
+        let mut value = loss(a, b);
+        for _ in 0..100 {
+            // Gradiente e hessiana (com um pequeno termo na diagonal)
+            let (mut h11, mut h22, mut h21, mut g1, mut g2) = (1e-12, 1e-12, 0.0, 0.0, 0.0);
+            for &(f, t) in &data {
+                let p = sigmoid(-(f * a + b));
+                let d2 = p * (1.0 - p);
+                h11 += f * f * d2;
+                h22 += d2;
+                h21 += f * d2;
+                g1 += f * (t - p);
+                g2 += t - p;
+            }
+            if g1.abs() < 1e-5 && g2.abs() < 1e-5 {
+                break;
+            }
+
+            let det = h11 * h22 - h21 * h21;
+            let (da, db) = (-(h22 * g1 - h21 * g2) / det, -(-h21 * g1 + h11 * g2) / det);
+            let descent = g1 * da + g2 * db;
+            let mut step = 1.0;
+            while step >= 1e-10 {
+                let (next_a, next_b) = (a + step * da, b + step * db);
+                let next = loss(next_a, next_b);
+                if next < value + 1e-4 * step * descent {
+                    (a, b, value) = (next_a, next_b, next);
+                    break;
+                }
+                step /= 2.0;
+            }
+            if step < 1e-10 {
+                break;
+            }
+        }
+        Some(Self { a, b })
+    }
+
+    /// Probabilidade calibrada de acerto para a confiança bruta `confidence`.
+    pub fn apply(&self, confidence: f64) -> f64 {
+        if *self == Self::IDENTITY {
+            return confidence;
+        }
+        sigmoid(-(self.a * logit(confidence) + self.b))
+    }
+}
+
+impl Default for PlattScaling {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Calibração de cada modo; modos sem calibração mantêm a confiança bruta.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceCalibration {
+    by_mode: HashMap<AlgorithmMode, PlattScaling>,
+}
+
+impl ConfidenceCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, mode: AlgorithmMode) -> Option<PlattScaling> {
+        self.by_mode.get(&mode).copied()
+    }
+
+    pub fn set(&mut self, mode: AlgorithmMode, scaling: PlattScaling) {
+        self.by_mode.insert(mode, scaling);
+    }
+
+    pub fn remove(&mut self, mode: AlgorithmMode) -> Option<PlattScaling> {
+        self.by_mode.remove(&mode)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_mode.is_empty()
+    }
+
+    /// Confiança calibrada de uma entidade produzida por `mode`.
+    pub fn calibrate(&self, mode: AlgorithmMode, confidence: f64) -> f64 {
+        self.get(mode).map_or(confidence, |scaling| scaling.apply(confidence))
+    }
+}
+
+/// Pares (confiança bruta, acertou?) das entidades previstas, a partir de pares
+/// (ouro, previstas) de cada documento. Acerto = mesmas fronteiras e categoria do ouro.
+pub fn calibration_samples(predictions: &[(Vec<GoldEntity>, Vec<EntitySpan>)]) -> Vec<(f64, bool)> {
+    predictions
+        .iter()
+        .flat_map(|(gold, predicted)| {
+            predicted.iter().map(move |entity| {
+                let correct = gold.iter().any(|g| g.start == entity.start && g.end == entity.end && g.category == entity.category);
+                (entity.confidence, correct)
+            })
+        })
+        .collect()
+}
+
+fn logit(confidence: f64) -> f64 {
+    let c = confidence.clamp(LOGIT_EPSILON, 1.0 - LOGIT_EPSILON);
+    (c / (1.0 - c)).ln()
+}
+
+fn sigmoid(z: f64) -> f64 {
+    if z >= 0.0 { 1.0 / (1.0 + (-z).exp()) } else { z.exp() / (1.0 + z.exp()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platt_fit_tracks_accuracy() {
+        // Confiança baixa acerta pouco, alta acerta quase sempre
+        let mut samples = Vec::new();
+        for i in 0..100 {
+            samples.push((0.3, i % 5 == 0));
+            samples.push((0.9, i % 10 != 0));
+        }
+        let scaling = PlattScaling::fit(&samples).unwrap();
+        assert!((scaling.apply(0.3) - 0.2).abs() < 0.05, "{}", scaling.apply(0.3));
+        assert!((scaling.apply(0.9) - 0.9).abs() < 0.05, "{}", scaling.apply(0.9));
+        // Monotônica e dentro de [0, 1], inclusive nos extremos
+        assert!(scaling.apply(0.0) < scaling.apply(0.5) && scaling.apply(0.5) < scaling.apply(1.0));
+        assert!((0.0..=1.0).contains(&scaling.apply(1.0)));
+
+        // Classes separáveis não levam a parâmetros infinitos
+        let separable = [(0.2, false), (0.8, true)];
+        let scaling = PlattScaling::fit(&separable).unwrap();
+        assert!(scaling.a.is_finite() && scaling.b.is_finite());
+        assert_eq!(PlattScaling::fit(&[]), None);
+    }
+
+    #[test]
+    fn test_calibration_per_mode() {
+        let mut calibration = ConfidenceCalibration::new();
+        calibration.set(AlgorithmMode::Hmm, PlattScaling { a: -1.0, b: 1.0 });
+        assert!(calibration.calibrate(AlgorithmMode::Hmm, 0.5) < 0.5);
+        assert_eq!(calibration.calibrate(AlgorithmMode::Hybrid, 0.5), 0.5);
+
+        let json = serde_json::to_string(&calibration).unwrap();
+        assert_eq!(serde_json::from_str::<ConfidenceCalibration>(&json).unwrap(), calibration);
+        assert_eq!(calibration.remove(AlgorithmMode::Hmm), Some(PlattScaling { a: -1.0, b: 1.0 }));
+        assert!(calibration.is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+//! # Categorias Personalizadas
+//!
+//! As seis categorias embutidas (`PER`, `ORG`, `LOC`, `MISC`, `DATE`, `TIME`) cobrem
+//! notícias, mas um domínio jurídico quer `LEGISLACAO` e `JURISPRUDENCIA`, um de saúde
+//! quer `DOENCA` e `MEDICAMENTO`. Este módulo permite declarar essas categorias em tempo
+//! de execução, sem recompilar:
+//!
+//! - [`CustomCategory`]: uma categoria pelo nome. O nome é registrado uma única vez por
+//!   processo, então a categoria é `Copy` e entra em [`EntityCategory::Custom`] como as
+//!   embutidas — regras, agrupamento de entidades, filtros e serialização funcionam igual.
+//! - [`CategorySet`]: o conjunto de categorias de um modelo (as embutidas mais as
+//!   personalizadas, em ordem). Define as tags BIO que o CRF pontua e o índice de cada
+//!   uma; é salvo junto com o modelo e pode ser lido de um arquivo JSON
+//!   (`["LEGISLACAO", "JURISPRUDENCIA"]`).
+//!
+//! Depois de registrada, uma categoria é reconhecida por [`EntityCategory::from_str`] e
+//! [`Tag::from_label`] ("B-LEGISLACAO"), o que basta para os modelos que trabalham com
+//! rótulos em texto (HMM, MaxEnt, Perceptron, spans) e para a leitura de corpora.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::categories::CategorySet;
+//! use ner_core::tagger::{EntityCategory, Tag};
+//!
+//! let set = CategorySet::new().with_category("LEGISLACAO").unwrap();
+//! let legislacao = set.parse("LEGISLACAO").unwrap();
+//! assert_eq!(legislacao.name(), "LEGISLACAO");
+//! assert_eq!(Tag::from_label("B-LEGISLACAO"), Some(Tag::Begin(legislacao)));
+//!
+//! // As 13 tags embutidas, mais B- e I- da nova categoria
+//! assert_eq!(set.tag_count(), 15);
+//! assert_eq!(set.tag_index(&Tag::Inside(legislacao)), Some(14));
+//! assert_eq!(set.tag_index(&Tag::Begin(EntityCategory::Per)), Some(1));
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::format;
+use crate::tagger::{EntityCategory, Tag};
+
+/// Tamanho máximo do nome de uma categoria personalizada.
+pub const MAX_CATEGORY_NAME_LEN: usize = 32;
+
+/// Categorias personalizadas por conjunto: o Viterbi guarda os backpointers em `u8`,
+/// o que limita o modelo a 256 tags (13 embutidas + 2 por categoria).
+pub const MAX_CUSTOM_CATEGORIES: usize = 120;
+
+/// Nomes registrados no processo. Cada nome é alocado uma única vez e nunca liberado:
+/// o conjunto é pequeno (dezenas de categorias) e isso permite `CustomCategory: Copy`.
+static REGISTRY: RwLock<Vec<&'static str>> = RwLock::new(Vec::new());
+
+/// Nome inválido para uma categoria personalizada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidCategory {
+    Empty,
+    /// Mais de [`MAX_CATEGORY_NAME_LEN`] caracteres.
+    TooLong(String),
+    /// Fora do formato `LETRAS_MAIUSCULAS_E_DIGITOS`, começando por letra.
+    BadFormat(String),
+    /// Nome de uma categoria embutida (`PER`, `ORG`...) ou a tag `O`.
+    Reserved(String),
+    /// O conjunto já tem [`MAX_CUSTOM_CATEGORIES`] categorias personalizadas.
+    TooMany,
+}
+
+impl fmt::Display for InvalidCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "nome de categoria vazio"),
+            Self::TooLong(name) => write!(f, "categoria \"{name}\" tem mais de {MAX_CATEGORY_NAME_LEN} caracteres"),
+            Self::BadFormat(name) => {
+                write!(f, "categoria \"{name}\" inválida (use letras maiúsculas, dígitos e _, começando por letra)")
+            }
+            Self::Reserved(name) => write!(f, "\"{name}\" é uma categoria embutida"),
+            Self::TooMany => write!(f, "mais de {MAX_CUSTOM_CATEGORIES} categorias personalizadas"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidCategory {}
+
+/// Uma categoria definida pelo usuário, identificada pelo nome (ex: `LEGISLACAO`).
+///
+/// Duas `CustomCategory` com o mesmo nome são iguais, em qualquer parte do processo.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CustomCategory(&'static str);
+
+impl CustomCategory {
+    /// Registra (ou reencontra) a categoria `name`.
+    ///
+    /// O nome segue o formato dos rótulos BIO: letras maiúsculas ASCII, dígitos e `_`,
+    /// começando por letra ("B-LEGISLACAO" precisa ser inequívoco).
+    pub fn new(name: &str) -> Result<Self, InvalidCategory> {
+        validate_name(name)?;
+        if let Some(existing) = Self::lookup(name) {
+            return Ok(existing);
+        }
+        let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+        // Outra thread pode ter registrado o mesmo nome entre a leitura e a escrita
+        if let Some(&existing) = registry.iter().find(|&&n| n == name) {
+            return Ok(Self(existing));
+        }
+        let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+        registry.push(leaked);
+        Ok(Self(leaked))
+    }
+
+    /// A categoria `name`, se já registrada. Não registra nomes novos.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+        registry.iter().find(|&&n| n == name).map(|&n| Self(n))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), InvalidCategory> {
+    if name.is_empty() {
+        return Err(InvalidCategory::Empty);
+    }
+    if name.len() > MAX_CATEGORY_NAME_LEN {
+        return Err(InvalidCategory::TooLong(name.to_string()));
+    }
+    let starts_with_letter = name.starts_with(|c: char| c.is_ascii_uppercase());
+    if !starts_with_letter || !name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
+        return Err(InvalidCategory::BadFormat(name.to_string()));
+    }
+    if name == "O" || EntityCategory::all().iter().any(|c| c.name() == name) {
+        return Err(InvalidCategory::Reserved(name.to_string()));
+    }
+    Ok(())
+}
+
+impl fmt::Debug for CustomCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for CustomCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Serialize for CustomCategory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomCategory {
+    /// Registra a categoria ao ler: um modelo salvo traz as suas categorias consigo.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        CustomCategory::new(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Conjunto de categorias de um modelo: as embutidas, sempre presentes, seguidas das
+/// personalizadas na ordem em que foram adicionadas.
+///
+/// A ordem define o índice das tags ([`CategorySet::tag_index`]): as 13 embutidas
+/// mantêm os índices de [`Tag::index`], e cada categoria personalizada ocupa as duas
+/// posições seguintes (`B-`, depois `I-`). Serializa como a lista de nomes personalizados.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "Vec<CustomCategory>", try_from = "Vec<CustomCategory>")]
+pub struct CategorySet {
+    custom: Vec<CustomCategory>,
+}
+
+impl TryFrom<Vec<CustomCategory>> for CategorySet {
+    type Error = InvalidCategory;
+
+    fn try_from(custom: Vec<CustomCategory>) -> Result<Self, Self::Error> {
+        Self::from_names(custom.iter().map(CustomCategory::name))
+    }
+}
+
+impl From<CategorySet> for Vec<CustomCategory> {
+    fn from(set: CategorySet) -> Self {
+        set.custom
+    }
+}
+
+impl CategorySet {
+    /// Só as categorias embutidas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Conjunto com as categorias personalizadas `names` (repetidas contam uma vez).
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self, InvalidCategory> {
+        let mut set = Self::new();
+        for name in names {
+            set.add(name)?;
+        }
+        Ok(set)
+    }
+
+    /// Conjunto com as categorias que aparecem em rótulos BIO e não são embutidas
+    /// (ex: as tags de um corpus CoNLL de domínio).
+    ///
+    /// ```rust
+    /// use ner_core::categories::CategorySet;
+    /// let set = CategorySet::from_labels(["O", "B-PER", "B-DOENCA", "I-DOENCA"]).unwrap();
+    /// assert_eq!(set.names(), ["DOENCA"]);
+    /// ```
+    pub fn from_labels<'a>(labels: impl IntoIterator<Item = &'a str>) -> Result<Self, InvalidCategory> {
+        let names = labels.into_iter().filter_map(|label| match label.split_once('-') {
+            Some(("B" | "I", name)) if EntityCategory::all().iter().all(|c| c.name() != name) => Some(name),
+            _ => None,
+        });
+        Self::from_names(names)
+    }
+
+    /// Acrescenta a categoria `name` (registrando-a) e a devolve.
+    pub fn add(&mut self, name: &str) -> Result<EntityCategory, InvalidCategory> {
+        let category = CustomCategory::new(name)?;
+        if !self.custom.contains(&category) {
+            if self.custom.len() == MAX_CUSTOM_CATEGORIES {
+                return Err(InvalidCategory::TooMany);
+            }
+            self.custom.push(category);
+        }
+        Ok(EntityCategory::Custom(category))
+    }
+
+    pub fn with_category(mut self, name: &str) -> Result<Self, InvalidCategory> {
+        self.add(name)?;
+        Ok(self)
+    }
+
+    /// Categorias personalizadas, na ordem do conjunto.
+    pub fn custom(&self) -> &[CustomCategory] {
+        &self.custom
+    }
+
+    /// Nomes das categorias personalizadas.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.custom.iter().map(CustomCategory::name).collect()
+    }
+
+    /// Todas as categorias: as embutidas e depois as personalizadas.
+    pub fn categories(&self) -> Vec<EntityCategory> {
+        EntityCategory::all().into_iter().chain(self.custom.iter().map(|&c| EntityCategory::Custom(c))).collect()
+    }
+
+    pub fn contains(&self, category: EntityCategory) -> bool {
+        match category {
+            EntityCategory::Custom(c) => self.custom.contains(&c),
+            _ => true,
+        }
+    }
+
+    /// Categoria do conjunto com este nome ("PER" ou um nome personalizado).
+    pub fn parse(&self, name: &str) -> Option<EntityCategory> {
+        EntityCategory::from_str(name).filter(|&c| self.contains(c))
+    }
+
+    /// Número de tags BIO: `O`, mais `B-` e `I-` de cada categoria.
+    pub fn tag_count(&self) -> usize {
+        Tag::COUNT + 2 * self.custom.len()
+    }
+
+    /// Todas as tags, na ordem dos índices.
+    pub fn tags(&self) -> Vec<Tag> {
+        let custom = self.custom.iter().flat_map(|&c| {
+            let category = EntityCategory::Custom(c);
+            [Tag::Begin(category), Tag::Inside(category)]
+        });
+        Tag::all().into_iter().chain(custom).collect()
+    }
+
+    /// Índice de `tag` neste conjunto (`None` se a categoria não faz parte dele).
+    pub fn tag_index(&self, tag: &Tag) -> Option<usize> {
+        match tag.category() {
+            Some(EntityCategory::Custom(c)) => {
+                let position = self.custom.iter().position(|&other| other == c)?;
+                let inside = usize::from(matches!(tag, Tag::Inside(_)));
+                Some(Tag::COUNT + 2 * position + inside)
+            }
+            _ => Some(tag.index()),
+        }
+    }
+
+    /// Lê um conjunto salvo como lista JSON de nomes (`["LEGISLACAO", "JURISPRUDENCIA"]`).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+
+    /// Salva o conjunto em disco (JSON), no formato lido por [`CategorySet::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_category_names_are_validated_and_interned() {
+        let a = CustomCategory::new("JURISPRUDENCIA").unwrap();
+        assert_eq!(CustomCategory::new("JURISPRUDENCIA").unwrap(), a);
+        assert_eq!(CustomCategory::lookup("JURISPRUDENCIA"), Some(a));
+        assert_eq!(CustomCategory::lookup("NUNCA_REGISTRADA"), None);
+
+        assert_eq!(CustomCategory::new(""), Err(InvalidCategory::Empty));
+        assert!(matches!(CustomCategory::new("Lei"), Err(InvalidCategory::BadFormat(_))));
+        assert!(matches!(CustomCategory::new("B-LEI"), Err(InvalidCategory::BadFormat(_))));
+        assert!(matches!(CustomCategory::new("9LEI"), Err(InvalidCategory::BadFormat(_))));
+        assert!(matches!(CustomCategory::new("PER"), Err(InvalidCategory::Reserved(_))));
+        assert!(matches!(CustomCategory::new("O"), Err(InvalidCategory::Reserved(_))));
+        assert!(matches!(CustomCategory::new(&"A".repeat(40)), Err(InvalidCategory::TooLong(_))));
+    }
+
+    #[test]
+    fn test_category_set_indexes_and_round_trips() {
+        let set = CategorySet::from_names(["LEGISLACAO", "JURISPRUDENCIA", "LEGISLACAO"]).unwrap();
+        assert_eq!(set.names(), ["LEGISLACAO", "JURISPRUDENCIA"]);
+        assert_eq!(set.categories().len(), 8);
+
+        let tags = set.tags();
+        assert_eq!(tags.len(), set.tag_count());
+        for (i, tag) in tags.iter().enumerate() {
+            assert_eq!(set.tag_index(tag), Some(i), "{tag}");
+        }
+        let outsider = CategorySet::new().with_category("DOENCA").unwrap();
+        let doenca = outsider.parse("DOENCA").unwrap();
+        assert_eq!(set.tag_index(&Tag::Begin(doenca)), None);
+        assert_eq!(set.parse("DOENCA"), None);
+
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"["LEGISLACAO","JURISPRUDENCIA"]"#);
+        assert_eq!(serde_json::from_str::<CategorySet>(&json).unwrap(), set);
+        assert!(serde_json::from_str::<CategorySet>(r#"["legislacao"]"#).is_err());
+
+        let path = std::env::temp_dir().join(format!("ner_categories_{}.json", std::process::id()));
+        set.save(&path).unwrap();
+        assert_eq!(CategorySet::load(&path).unwrap(), set);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_entity_category_json_does_not_register_names() {
+        let set = CategorySet::from_names(["PATENTE"]).unwrap();
+        let patente = set.parse("PATENTE").unwrap();
+        assert_eq!(serde_json::from_str::<EntityCategory>(r#""PATENTE""#).unwrap(), patente);
+        assert_eq!(serde_json::from_str::<EntityCategory>(r#""Per""#).unwrap(), EntityCategory::Per);
+
+        // Um nome vindo de uma requisição não entra no registro
+        assert!(serde_json::from_str::<EntityCategory>(r#""NUNCA_CONFIGURADA""#).is_err());
+        assert_eq!(CustomCategory::lookup("NUNCA_CONFIGURADA"), None);
+    }
+}
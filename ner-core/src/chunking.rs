@@ -0,0 +1,186 @@
+//! # Divisão de Documentos Preservando Entidades (RAG)
+//!
+//! Pipelines de RAG e prompts de LLM trabalham com trechos de tamanho limitado. Cortar
+//! o texto a cada N caracteres é simples, mas parte sentenças ao meio e, pior, parte
+//! entidades: "Fundação Oswaldo | Cruz" vira duas meias-entidades que nenhum dos
+//! trechos consegue recuperar.
+//!
+//! [`split_preserving_entities`] divide o texto em [`TextChunk`]s de até `max_chars`
+//! caracteres com estas preferências, nesta ordem:
+//!
+//! 1. Cortar entre sentenças (ver [`crate::tokenizer::sentence_ranges`]), juntando no
+//!    mesmo trecho quantas sentenças couberem.
+//! 2. Se uma sentença sozinha não cabe, cortar entre tokens — nunca dentro de uma entidade.
+//! 3. Se nem isso cabe (uma entidade maior que `max_chars`), o trecho excede o limite
+//!    em vez de partir a entidade.
+//!
+//! Cada trecho traz seus offsets no documento (para mapear de volta com
+//! [`TextChunk::to_original`]) e as entidades que contém, com offsets relativos ao trecho.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::NerPipeline;
+//!
+//! let text = "A Fiocruz fica no Rio de Janeiro. O Brasil venceu a Copa. Lula visitou Recife.";
+//! let chunks = NerPipeline::new().split_preserving_entities(text, 40);
+//!
+//! assert_eq!(chunks[0].text, "A Fiocruz fica no Rio de Janeiro.");
+//! for chunk in &chunks {
+//!     assert!(chunk.text.chars().count() <= 40);
+//!     assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+//!     for entity in &chunk.entities {
+//!         assert_eq!(&chunk.text[entity.start..entity.end], entity.text);
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::offsets::slice_checked;
+use crate::tagger::EntitySpan;
+use crate::tokenizer::{sentence_ranges, tokenize};
+
+/// Um trecho do documento.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChunk {
+    /// Texto do trecho, sem espaços nas pontas.
+    pub text: String,
+    /// Offset de byte do início do trecho no documento.
+    pub start: usize,
+    /// Offset de byte (exclusivo) do fim do trecho no documento.
+    pub end: usize,
+    /// Entidades inteiramente dentro do trecho. `start`/`end` são relativos a `text`;
+    /// `start_token`/`end_token` continuam se referindo aos tokens do documento.
+    pub entities: Vec<EntitySpan>,
+}
+
+impl TextChunk {
+    /// Offset no documento de um offset de byte de `text`.
+    pub fn to_original(&self, offset: usize) -> usize {
+        self.start + offset
+    }
+}
+
+/// Um ponto onde o texto pode ser cortado.
+struct Cut {
+    byte: usize,
+    /// Caracteres antes do corte (o limite é em caracteres, não em bytes).
+    chars: usize,
+    /// Fronteira de sentença (preferida) ou apenas de token.
+    sentence: bool,
+}
+
+/// Divide `text` em trechos de até `max_chars` caracteres sem partir sentenças (quando
+/// possível) nem `entities` (nunca). Ver o [módulo](self) para as regras de corte.
+///
+/// `entities` usa offsets de byte em `text`, como a saída do pipeline.
+pub fn split_preserving_entities(text: &str, entities: &[EntitySpan], max_chars: usize) -> Vec<TextChunk> {
+    let tokens = tokenize(text);
+    let inside_entity = |byte: usize| entities.iter().any(|e| e.start < byte && byte < e.end);
+    let sentence_starts: Vec<usize> = sentence_ranges(&tokens).iter().map(|r| tokens[r.start].start).collect();
+
+    // Cortes possíveis: início de cada token precedido de espaço ("viajou." não vira
+    // "viajou" + ".") e fora de entidade, mais o fim do texto
+    let after_space = |byte: usize| slice_checked(text, 0, byte).is_ok_and(|before| before.ends_with(char::is_whitespace));
+    let mut cuts: Vec<Cut> = Vec::new();
+    let (mut counted_byte, mut counted_chars) = (0, 0);
+    let starts = tokens.iter().map(|t| t.start).filter(|&b| b > 0 && after_space(b));
+    for byte in starts.chain(std::iter::once(text.len())) {
+        if byte < text.len() && inside_entity(byte) {
+            continue;
+        }
+        counted_chars += slice_checked(text, counted_byte, byte).map_or(0, |s| s.chars().count());
+        counted_byte = byte;
+        let sentence = byte == text.len() || sentence_starts.binary_search(&byte).is_ok();
+        cuts.push(Cut { byte, chars: counted_chars, sentence });
+    }
+
+    let mut chunks = Vec::new();
+    let (mut from_byte, mut from_chars) = (0, 0);
+    let mut next = 0;
+    while from_byte < text.len() {
+        let remaining = &cuts[next..];
+        let fits = remaining.iter().take_while(|c| c.chars - from_chars <= max_chars);
+        let chosen = fits
+            .clone()
+            .enumerate()
+            .filter(|(_, c)| c.sentence)
+            .last()
+            .or_else(|| fits.enumerate().last())
+            // Nada cabe: o menor trecho possível, mesmo acima do limite
+            .map_or(0, |(i, _)| i);
+        let cut = &remaining[chosen];
+        if let Some(chunk) = make_chunk(text, from_byte, cut.byte, entities) {
+            chunks.push(chunk);
+        }
+        from_byte = cut.byte;
+        from_chars = cut.chars;
+        next += chosen + 1;
+    }
+    chunks
+}
+
+/// O trecho `start..end` sem os espaços das pontas, com as suas entidades; `None` se vazio.
+fn make_chunk(text: &str, start: usize, end: usize, entities: &[EntitySpan]) -> Option<TextChunk> {
+    let raw = slice_checked(text, start, end).ok()?;
+    let trimmed_start = raw.trim_start();
+    let start = start + (raw.len() - trimmed_start.len());
+    let trimmed = trimmed_start.trim_end();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let end = start + trimmed.len();
+    let entities = entities
+        .iter()
+        .filter(|e| start <= e.start && e.end <= end)
+        .map(|e| EntitySpan { start: e.start - start, end: e.end - start, ..e.clone() })
+        .collect();
+    Some(TextChunk { text: trimmed.to_string(), start, end, entities })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::EntityCategory;
+
+    #[test]
+    fn test_split_preserving_entities() {
+        let text = "Ana foi à Fundação Oswaldo Cruz ontem. Depois viajou.  Voltou para São José dos Campos no sábado à noite.";
+        let entities = vec![
+            EntitySpan::test_at(text, "Ana", EntityCategory::Per),
+            EntitySpan::test_at(text, "Fundação Oswaldo Cruz", EntityCategory::Org),
+            EntitySpan::test_at(text, "São José dos Campos", EntityCategory::Loc),
+        ];
+
+        // Limite folgado: sentenças inteiras agrupadas
+        let chunks = split_preserving_entities(text, &entities, 60);
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Ana foi à Fundação Oswaldo Cruz ontem. Depois viajou.",
+                "Voltou para São José dos Campos no sábado à noite."
+            ]
+        );
+        assert_eq!(chunks[0].entities.len(), 2);
+        assert_eq!(chunks[1].entities[0].start, "Voltou para ".len());
+        assert_eq!(chunks[1].to_original(chunks[1].entities[0].start), entities[2].start);
+
+        // Limite apertado: corta entre tokens, mas nunca dentro das entidades
+        let chunks = split_preserving_entities(text, &entities, 15);
+        for chunk in &chunks {
+            assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+            let fits = chunk.text.chars().count() <= 15;
+            assert!(fits || entities.iter().any(|e| chunk.text.contains(&e.text)), "{chunk:?}");
+        }
+        let found: Vec<&str> = chunks.iter().flat_map(|c| c.entities.iter().map(|e| e.text.as_str())).collect();
+        assert_eq!(found, vec!["Ana", "Fundação Oswaldo Cruz", "São José dos Campos"]);
+        assert!(chunks.iter().any(|c| c.text == "Fundação Oswaldo Cruz"));
+        // Nada se perde: os trechos cobrem todo o texto não branco, em ordem
+        let joined: String = chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join(" ");
+        assert_eq!(joined.split_whitespace().collect::<Vec<_>>(), text.split_whitespace().collect::<Vec<_>>());
+
+        assert!(split_preserving_entities("   ", &[], 10).is_empty());
+    }
+}
@@ -0,0 +1,529 @@
+//! # Clusters de Palavras (Brown)
+//!
+//! Os gazetteers só ajudam com nomes que estão nas listas. Para as demais palavras,
+//! os modelos lineares contam com a ortografia e o contexto imediato. Os clusters de
+//! Brown acrescentam um sinal **distribucional**: palavras que aparecem em contextos
+//! parecidos num texto cru ("Recife", "Manaus", "Curitiba" depois de "em") caem no
+//! mesmo cluster. Assim um nome nunca visto no corpus anotado herda os pesos dos
+//! vizinhos de cluster.
+//!
+//! ## Algoritmo
+//!
+//! [`WordClusters::train`] segue o algoritmo de Brown com janela (Liang, 2005):
+//!
+//! 1. As palavras do vocabulário entram em ordem de frequência. Cada uma abre um
+//!    cluster próprio.
+//! 2. Quando há `clusters + 1` clusters, os dois cuja fusão menos reduz a informação
+//!    mútua entre clusters vizinhos (bigramas) são fundidos.
+//! 3. Com todo o vocabulário distribuído, os clusters restantes são fundidos até
+//!    sobrar um, formando uma árvore binária.
+//!
+//! O caminho da raiz até o cluster da palavra (`0` à esquerda, `1` à direita) é o seu
+//! código. Prefixos curtos do código são classes mais gerais. As features
+//! `cluster4=` e `cluster6=` usam os 4 e 6 primeiros bits
+//! ([`crate::features::insert_cluster_features`]).
+//!
+//! Cada fusão avalia todos os pares de clusters, então o custo cresce com o cubo de
+//! `clusters` e linearmente com o vocabulário. `min_count` corta as palavras raras,
+//! que também são as de código menos confiável.
+//!
+//! As palavras são indexadas pela mesma chave dos gazetteers ([`GazetteerKey::normalize`]).
+//! Clusters já treinados por outra ferramenta entram por [`WordClusters::from_paths`].
+//!
+//! ## Uso nos modelos
+//!
+//! [`ClusterFeatures`] é um [`FeatureExtractor`]: acrescenta as features de cluster às
+//! de um [`FeatureTemplate`]. Injetado num modelo (`with_extractor`), ele é usado tanto
+//! no treino quanto na análise.
+//!
+//! ```rust
+//! use ner_core::clusters::{ClusterConfig, ClusterFeatures, WordClusters};
+//! use ner_core::features::{FeatureExtractor, Gazetteers};
+//! use ner_core::perceptron::PerceptronModel;
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let texts = [
+//!     "Ele mora em Recife .",
+//!     "Ela mora em Manaus .",
+//!     "Ele trabalha em Recife .",
+//!     "Ela trabalha em Manaus .",
+//! ];
+//! let clusters = WordClusters::train(texts, &ClusterConfig { clusters: 4, min_count: 1 });
+//! assert_eq!(clusters.path("Recife"), clusters.path("manaus"));
+//!
+//! let extractor = ClusterFeatures::new(clusters);
+//! let features = extractor.extract(&tokenize("Ele mora em Recife"), &Gazetteers::new());
+//! assert!(features[3].features.keys().any(|k| k.starts_with("cluster4=")));
+//!
+//! let perceptron = PerceptronModel::new().with_extractor(extractor);
+//! # let _ = perceptron;
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::features::{
+    insert_cluster_features, FeatureExtractor, FeatureTemplate, FeatureVector, GazetteerKey, GazetteerLookups,
+    Gazetteers,
+};
+use crate::format;
+use crate::tokenizer::{tokenize, Token};
+
+/// Parâmetros do treino de [`WordClusters::train`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// Quantidade de clusters (folhas da árvore).
+    pub clusters: usize,
+    /// Ocorrências mínimas para uma palavra entrar no vocabulário.
+    pub min_count: usize,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self { clusters: 32, min_count: 2 }
+    }
+}
+
+/// Código binário do cluster de cada palavra.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordClusters {
+    /// Chave normalizada da palavra → código (`"0110"`).
+    paths: HashMap<String, String>,
+}
+
+impl WordClusters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clusters a partir de pares (palavra, código), por exemplo lidos do arquivo `paths`
+    /// da implementação de Liang (`código<TAB>palavra<TAB>frequência`).
+    pub fn from_paths<W: AsRef<str>, P: Into<String>>(paths: impl IntoIterator<Item = (W, P)>) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|(word, path)| (GazetteerKey::normalize(word.as_ref()), path.into()))
+            .collect();
+        Self { paths }
+    }
+
+    /// Código do cluster da palavra, se ela estava no vocabulário do treino.
+    pub fn path(&self, word: &str) -> Option<&str> {
+        self.path_for_key(&GazetteerKey::normalize(word))
+    }
+
+    /// Como [`WordClusters::path`], para uma chave já normalizada.
+    pub fn path_for_key(&self, key: &str) -> Option<&str> {
+        self.paths.get(key).map(String::as_str)
+    }
+
+    /// Quantidade de palavras com cluster.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Salva os clusters em JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega clusters salvos com [`WordClusters::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+
+    /// Aprende os clusters de um corpus de texto cru (ver a documentação do módulo).
+    ///
+    /// Cada texto é tokenizado à parte; bigramas não cruzam textos. Tokens fora do
+    /// vocabulário interrompem a sequência de bigramas.
+    pub fn train<T: AsRef<str>>(texts: impl IntoIterator<Item = T>, config: &ClusterConfig) -> Self {
+        let sequences: Vec<Vec<String>> = texts
+            .into_iter()
+            .map(|text| tokenize(text.as_ref()).iter().map(|t| GazetteerKey::normalize(&t.text)).collect())
+            .collect();
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for word in sequences.iter().flatten() {
+            *counts.entry(word.as_str()).or_default() += 1;
+        }
+        let mut vocab: Vec<(&str, usize)> =
+            counts.into_iter().filter(|&(_, c)| c >= config.min_count.max(1)).collect();
+        // Mais frequentes primeiro; o desempate alfabético deixa o treino determinístico
+        vocab.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        if vocab.is_empty() {
+            return Self::new();
+        }
+        let index: HashMap<&str, usize> = vocab.iter().enumerate().map(|(i, &(w, _))| (w, i)).collect();
+
+        // Bigramas entre palavras do vocabulário, por palavra da esquerda
+        let mut bigrams: HashMap<(usize, usize), f64> = HashMap::new();
+        for sequence in &sequences {
+            for pair in sequence.windows(2) {
+                if let (Some(&a), Some(&b)) = (index.get(pair[0].as_str()), index.get(pair[1].as_str())) {
+                    *bigrams.entry((a, b)).or_default() += 1.0;
+                }
+            }
+        }
+        // Vizinhos de cada palavra, nos dois sentidos
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); vocab.len()];
+        for &(a, b) in bigrams.keys() {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        let mut state = BrownState::new(config.clusters.max(1), bigrams.values().sum());
+        for (word, neighbors) in neighbors.iter().enumerate() {
+            state.add_word(word, &bigrams, neighbors);
+            if state.active_count() > config.clusters.max(1) {
+                state.merge_best(false);
+            }
+        }
+        while state.active_count() > 1 {
+            state.merge_best(true);
+        }
+
+        let vocab = &vocab;
+        let paths = state
+            .paths()
+            .into_iter()
+            .flat_map(|(members, path)| members.into_iter().map(move |w| (vocab[w].0.to_string(), path.clone())))
+            .collect();
+        Self { paths }
+    }
+}
+
+/// Extrator que acrescenta os prefixos de cluster (`cluster4=`, `cluster6=`) às
+/// features de um [`FeatureTemplate`] (o padrão, se não for trocado).
+#[derive(Clone)]
+pub struct ClusterFeatures {
+    template: FeatureTemplate,
+    clusters: WordClusters,
+}
+
+impl ClusterFeatures {
+    pub fn new(clusters: WordClusters) -> Self {
+        Self { template: FeatureTemplate::default(), clusters }
+    }
+
+    /// Troca o template das demais features.
+    pub fn with_template(mut self, template: FeatureTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    pub fn clusters(&self) -> &WordClusters {
+        &self.clusters
+    }
+
+    fn add_clusters<'a>(&self, keys: impl Iterator<Item = &'a str>, features: &mut [FeatureVector]) {
+        for (key, fv) in keys.zip(features) {
+            if let Some(path) = self.clusters.path_for_key(key) {
+                insert_cluster_features(fv, path);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ClusterFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClusterFeatures")
+            .field("template", &self.template)
+            .field("words", &self.clusters.len())
+            .finish()
+    }
+}
+
+impl FeatureExtractor for ClusterFeatures {
+    fn extract(&self, tokens: &[Token], gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+        let mut features = self.template.extract(tokens, gazetteers);
+        let keys: Vec<String> = tokens.iter().map(|t| GazetteerKey::normalize(&t.text)).collect();
+        self.add_clusters(keys.iter().map(String::as_str), &mut features);
+        features
+    }
+
+    fn extract_with_lookups(
+        &self,
+        tokens: &[Token],
+        gazetteers: &Gazetteers,
+        lookups: &GazetteerLookups,
+    ) -> Vec<FeatureVector> {
+        let mut features = self.template.extract_with_lookups(tokens, gazetteers, lookups);
+        // A chave do próprio texto, mesmo quando a consulta usou a grafia do gazetteer
+        let keys = (0..tokens.len()).map(|i| {
+            let entry = lookups.entry(i);
+            entry.variant.as_deref().unwrap_or(&entry.key)
+        });
+        self.add_clusters(keys, &mut features);
+        features
+    }
+}
+
+/// Nó da árvore de clusters: folhas guardam palavras, nós internos, os dois filhos.
+#[derive(Debug)]
+enum Node {
+    Leaf(Vec<usize>),
+    Inner(usize, usize),
+}
+
+/// Estado do algoritmo de Brown: uma matriz de bigramas entre os clusters ativos.
+///
+/// Os clusters ocupam `capacity + 1` posições; uma fusão libera uma posição, que a
+/// próxima palavra reaproveita.
+struct BrownState {
+    /// Contagem de bigramas (cluster da esquerda, cluster da direita), por posição.
+    counts: Vec<Vec<f64>>,
+    /// Somas das linhas (marginal da esquerda) e das colunas (da direita).
+    left: Vec<f64>,
+    right: Vec<f64>,
+    /// Nó da árvore ocupando cada posição (`None` = posição livre).
+    slots: Vec<Option<usize>>,
+    /// Posição do cluster de cada palavra já distribuída.
+    word_slot: HashMap<usize, usize>,
+    nodes: Vec<Node>,
+    total: f64,
+}
+
+impl BrownState {
+    fn new(capacity: usize, total: f64) -> Self {
+        let size = capacity + 1;
+        Self {
+            counts: vec![vec![0.0; size]; size],
+            left: vec![0.0; size],
+            right: vec![0.0; size],
+            slots: vec![None; size],
+            word_slot: HashMap::new(),
+            nodes: Vec::new(),
+            total: total.max(1.0),
+        }
+    }
+
+    fn active(&self) -> impl Iterator<Item = usize> + '_ {
+        self.slots.iter().enumerate().filter(|(_, n)| n.is_some()).map(|(i, _)| i)
+    }
+
+    fn active_count(&self) -> usize {
+        self.slots.iter().filter(|n| n.is_some()).count()
+    }
+
+    /// Abre um cluster para `word`, com os bigramas que ela forma com as palavras já distribuídas.
+    fn add_word(&mut self, word: usize, bigrams: &HashMap<(usize, usize), f64>, neighbors: &[usize]) {
+        let slot = self.slots.iter().position(Option::is_none).expect("posição livre após a fusão");
+        self.slots[slot] = Some(self.nodes.len());
+        self.nodes.push(Node::Leaf(vec![word]));
+        self.word_slot.insert(word, slot);
+
+        for &other in neighbors {
+            let Some(&other_slot) = self.word_slot.get(&other) else { continue };
+            let forward = bigrams.get(&(word, other)).copied().unwrap_or(0.0);
+            if other == word {
+                self.add_count(slot, slot, forward);
+                continue;
+            }
+            self.add_count(slot, other_slot, forward);
+            self.add_count(other_slot, slot, bigrams.get(&(other, word)).copied().unwrap_or(0.0));
+        }
+    }
+
+    fn add_count(&mut self, a: usize, b: usize, count: f64) {
+        self.counts[a][b] += count;
+        self.left[a] += count;
+        self.right[b] += count;
+    }
+
+    /// Termo de informação mútua de um par de clusters.
+    fn term(&self, count: f64, left: f64, right: f64) -> f64 {
+        if count <= 0.0 {
+            return 0.0;
+        }
+        let p = count / self.total;
+        p * (count * self.total / (left * right)).ln()
+    }
+
+    /// Variação da informação mútua ao fundir os clusters das posições `a` e `b`.
+    fn merge_delta(&self, a: usize, b: usize) -> f64 {
+        let q = |x: usize, y: usize| self.term(self.counts[x][y], self.left[x], self.right[y]);
+        let (left_m, right_m) = (self.left[a] + self.left[b], self.right[a] + self.right[b]);
+
+        let mut before = -(q(a, a) + q(b, b) + q(a, b) + q(b, a));
+        let mut after = self.term(
+            self.counts[a][a] + self.counts[a][b] + self.counts[b][a] + self.counts[b][b],
+            left_m,
+            right_m,
+        );
+        for y in self.active() {
+            before += q(a, y) + q(y, a) + q(b, y) + q(y, b);
+            if y != a && y != b {
+                after += self.term(self.counts[a][y] + self.counts[b][y], left_m, self.right[y]);
+                after += self.term(self.counts[y][a] + self.counts[y][b], self.left[y], right_m);
+            }
+        }
+        after - before
+    }
+
+    /// Funde o par que menos perde informação mútua. Com `build_tree`, a fusão vira um
+    /// nó interno da árvore; senão, os dois clusters viram uma só folha.
+    fn merge_best(&mut self, build_tree: bool) {
+        let active: Vec<usize> = self.active().collect();
+        let mut best: Option<(f64, usize, usize)> = None;
+        for (i, &a) in active.iter().enumerate() {
+            for &b in &active[i + 1..] {
+                let delta = self.merge_delta(a, b);
+                if best.is_none_or(|(d, _, _)| delta > d) {
+                    best = Some((delta, a, b));
+                }
+            }
+        }
+        let Some((_, a, b)) = best else { return };
+
+        let size = self.slots.len();
+        for y in 0..size {
+            let moved = self.counts[b][y];
+            self.counts[a][y] += moved;
+        }
+        for y in 0..size {
+            let moved = self.counts[y][b];
+            self.counts[y][a] += moved;
+        }
+        for y in 0..size {
+            self.counts[b][y] = 0.0;
+            self.counts[y][b] = 0.0;
+        }
+        self.left[a] += self.left[b];
+        self.right[a] += self.right[b];
+        self.left[b] = 0.0;
+        self.right[b] = 0.0;
+
+        let (node_a, node_b) = (self.slots[a].expect("posição ativa"), self.slots[b].expect("posição ativa"));
+        self.slots[b] = None;
+        if build_tree {
+            self.slots[a] = Some(self.nodes.len());
+            self.nodes.push(Node::Inner(node_a, node_b));
+        } else {
+            let Node::Leaf(moved) = std::mem::replace(&mut self.nodes[node_b], Node::Leaf(Vec::new())) else {
+                unreachable!("fusões da janela só envolvem folhas")
+            };
+            for &word in &moved {
+                self.word_slot.insert(word, a);
+            }
+            if let Node::Leaf(members) = &mut self.nodes[node_a] {
+                members.extend(moved);
+            }
+        }
+    }
+
+    /// Palavras de cada folha com o código do caminho até ela.
+    fn paths(mut self) -> Vec<(Vec<usize>, String)> {
+        let Some(root) = self.slots.iter().flatten().next().copied() else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        let mut stack = vec![(root, String::new())];
+        while let Some((node, path)) = stack.pop() {
+            match std::mem::replace(&mut self.nodes[node], Node::Leaf(Vec::new())) {
+                Node::Leaf(members) => {
+                    // Um único cluster não tem bits; "0" mantém o código não vazio
+                    let path = if path.is_empty() { "0".to_string() } else { path };
+                    out.push((members, path));
+                }
+                Node::Inner(left, right) => {
+                    stack.push((right, format!("{path}1")));
+                    stack.push((left, format!("{path}0")));
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Vec<String> {
+        let mut texts = Vec::new();
+        for city in ["Recife", "Manaus", "Curitiba", "Salvador"] {
+            for verb in ["mora", "trabalha", "nasceu"] {
+                texts.push(format!("Ele {verb} em {city} ."));
+                texts.push(format!("Ela {verb} em {city} ."));
+            }
+        }
+        texts
+    }
+
+    #[test]
+    fn test_brown_groups_words_by_context() {
+        let clusters = WordClusters::train(corpus(), &ClusterConfig { clusters: 6, min_count: 1 });
+        // Palavras do mesmo papel ficam na mesma subárvore (prefixo de 2 bits)
+        let class = |w: &str| clusters.path(w).unwrap()[..2].to_string();
+        for city in ["Manaus", "Curitiba", "Salvador"] {
+            assert_eq!(class(city), class("Recife"), "{city}");
+        }
+        assert_eq!(class("mora"), class("nasceu"));
+        assert_eq!(class("mora"), class("trabalha"));
+        assert_eq!(class("ele"), class("ela"));
+        assert_ne!(class("Recife"), class("mora"));
+        assert_ne!(class("Recife"), class("ele"));
+        assert!(clusters.path("Brasília").is_none());
+    }
+
+    #[test]
+    fn test_paths_are_prefix_free_and_deterministic() {
+        let config = ClusterConfig { clusters: 4, min_count: 1 };
+        let clusters = WordClusters::train(corpus(), &config);
+        assert_eq!(clusters, WordClusters::train(corpus(), &config));
+
+        let mut codes: Vec<&str> = clusters.paths.values().map(String::as_str).collect();
+        codes.sort();
+        codes.dedup();
+        assert!(codes.len() <= 4);
+        for a in &codes {
+            assert!(a.chars().all(|c| c == '0' || c == '1'));
+            assert!(!codes.iter().any(|b| b != a && b.starts_with(a)), "{a} é prefixo de outro código");
+        }
+    }
+
+    #[test]
+    fn test_min_count_and_from_paths() {
+        let clusters = WordClusters::train(["a b a b c"], &ClusterConfig { clusters: 2, min_count: 2 });
+        assert!(clusters.path("a").is_some());
+        assert!(clusters.path("c").is_none());
+        assert!(WordClusters::train(Vec::<String>::new(), &ClusterConfig::default()).is_empty());
+
+        let loaded = WordClusters::from_paths([("São Paulo", "0110"), ("Recife", "0111")]);
+        assert_eq!(loaded.path("são paulo"), Some("0110"));
+        assert_eq!(loaded.len(), 2);
+    }
+
+    #[test]
+    fn test_cluster_features_extractor() {
+        let clusters = WordClusters::from_paths([("recife", "0110101"), ("mora", "10")]);
+        let extractor = ClusterFeatures::new(clusters);
+        let tokens = tokenize("Ele mora em Recife");
+        let gaz = Gazetteers::new();
+
+        let features = extractor.extract(&tokens, &gaz);
+        assert!(features[1].features.contains_key("cluster4=10"));
+        assert!(features[3].features.contains_key("cluster4=0110"));
+        assert!(features[3].features.contains_key("cluster6=011010"));
+        assert!(features[3].features.contains_key("word=recife")); // as do template continuam
+        assert!(!features[2].features.keys().any(|k| k.starts_with("cluster")));
+
+        let lookups = GazetteerLookups::new(&tokens, &gaz);
+        let with_lookups = extractor.extract_with_lookups(&tokens, &gaz, &lookups);
+        for (a, b) in features.iter().zip(&with_lookups) {
+            assert_eq!(a.features, b.features);
+        }
+    }
+}
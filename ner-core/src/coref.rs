@@ -0,0 +1,233 @@
+//! # Resolução de Pronomes (Correferência Leve)
+//!
+//! Correferência completa (quem é "o ex-presidente", "o petista", "ele"...) exige
+//! modelos pesados. Para muitos usos de extração de informação basta algo bem mais
+//! simples: ligar os pronomes pessoais de terceira pessoa à **pessoa mencionada mais
+//! recentemente**.
+//!
+//! [`resolve_pronouns`] percorre os pronomes "ele/ela", "dele/dela" e "nele/nela" e,
+//! para cada um, procura para trás a entidade PER mais próxima:
+//!
+//! - dentro de até [`CorefConfig::max_sentences`] sentenças anteriores;
+//! - de gênero compatível ([`guess_gender`]): "ela" não aponta para "Lula". Nomes de
+//!   gênero desconhecido ("Silva", "Temer") são compatíveis com ambos, mas só são
+//!   escolhidos se não houver na janela um nome do mesmo gênero do pronome.
+//!
+//! Cada pronome resolvido vira um [`PronounMention`], um trecho secundário cujo
+//! `refers_to` é o índice da entidade antecedente na lista recebida.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::coref::{resolve_pronouns, CorefConfig};
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let pipeline = NerPipeline::new();
+//! let text = "A presidente Dilma Rousseff recebeu o ex-presidente Lula. Ela elogiou o discurso dele.";
+//! let (tagged, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//! let tokens: Vec<_> = tagged.into_iter().map(|t| t.token).collect();
+//!
+//! let mentions = resolve_pronouns(&tokens, &entities, &CorefConfig::default());
+//! let pairs: Vec<(&str, &str)> = mentions
+//!     .iter()
+//!     .map(|m| (m.text.as_str(), entities[m.refers_to].text.as_str()))
+//!     .collect();
+//! assert_eq!(pairs, vec![("Ela", "Dilma"), ("dele", "Lula")]);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::tagger::{EntityCategory, EntitySpan};
+use crate::tokenizer::{sentence_ranges, Token};
+
+/// Gênero gramatical de um pronome ou nome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gender {
+    Masculine,
+    Feminine,
+}
+
+/// Pronomes pessoais de terceira pessoa do singular resolvidos pelo módulo.
+const PRONOUNS: &[(&str, Gender)] = &[
+    ("ele", Gender::Masculine),
+    ("dele", Gender::Masculine),
+    ("nele", Gender::Masculine),
+    ("ela", Gender::Feminine),
+    ("dela", Gender::Feminine),
+    ("nela", Gender::Feminine),
+];
+
+/// Nomes masculinos terminados em "-a".
+const MASCULINE_A_NAMES: &[&str] = &["lula", "garrincha", "luca", "nicola", "vavá", "bira"];
+
+/// Sobrenomes terminados em "-a", que não indicam gênero.
+const NEUTRAL_A_NAMES: &[&str] = &[
+    "silva", "costa", "souza", "sousa", "moura", "rocha", "oliveira", "pereira", "ferreira", "vieira",
+    "cunha", "lima", "almeida", "mota", "motta", "sequeira", "siqueira", "moreira", "teixeira",
+];
+
+/// Terminações de nomes femininos que não acabam em "-a" ("Gisele", "Clarice", "Beatriz").
+const FEMININE_ENDINGS: &[&str] = &["ele", "ice", "ane", "ene", "ine", "iz", "bel", "uel", "ete", "ith"];
+
+/// Configuração da resolução de pronomes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorefConfig {
+    /// Quantas sentenças antes da do pronome o antecedente pode estar (0 = só a mesma sentença).
+    pub max_sentences: usize,
+}
+
+impl Default for CorefConfig {
+    fn default() -> Self {
+        Self { max_sentences: 2 }
+    }
+}
+
+impl CorefConfig {
+    pub fn with_max_sentences(mut self, max_sentences: usize) -> Self {
+        self.max_sentences = max_sentences;
+        self
+    }
+}
+
+/// Um pronome ligado a uma entidade PER (trecho secundário).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PronounMention {
+    /// O pronome como aparece no texto ("Ela", "dele").
+    pub text: String,
+    pub token_index: usize,
+    /// Offsets de byte do pronome, `end` exclusivo.
+    pub start: usize,
+    pub end: usize,
+    pub gender: Gender,
+    /// Índice da entidade antecedente na lista passada a [`resolve_pronouns`].
+    pub refers_to: usize,
+}
+
+/// Gênero provável de um nome de pessoa pelo primeiro nome; `None` se não der para dizer.
+///
+/// ```rust
+/// use ner_core::coref::{guess_gender, Gender};
+///
+/// assert_eq!(guess_gender("Dilma Rousseff"), Some(Gender::Feminine));
+/// assert_eq!(guess_gender("Lula"), Some(Gender::Masculine));
+/// assert_eq!(guess_gender("Silva"), None);
+/// ```
+pub fn guess_gender(name: &str) -> Option<Gender> {
+    let first = name.split_whitespace().next()?.to_lowercase();
+    if MASCULINE_A_NAMES.contains(&first.as_str()) {
+        return Some(Gender::Masculine);
+    }
+    if NEUTRAL_A_NAMES.contains(&first.as_str()) {
+        return None;
+    }
+    if first.ends_with('a') || FEMININE_ENDINGS.iter().any(|e| first.ends_with(e)) {
+        Some(Gender::Feminine)
+    } else if first.ends_with('o') {
+        Some(Gender::Masculine)
+    } else {
+        None
+    }
+}
+
+/// Gênero do token, se ele for um dos pronomes resolvidos.
+fn pronoun_gender(word: &str) -> Option<Gender> {
+    let lower = word.to_lowercase();
+    PRONOUNS.iter().find(|(p, _)| *p == lower).map(|(_, g)| *g)
+}
+
+/// Liga cada pronome de terceira pessoa à entidade PER compatível mais recente (ver o módulo).
+///
+/// `entities` precisa ter índices de token relativos a `tokens`. Pronomes sem
+/// antecedente compatível na janela não geram menção.
+pub fn resolve_pronouns(tokens: &[Token], entities: &[EntitySpan], config: &CorefConfig) -> Vec<PronounMention> {
+    let sentences = sentence_ranges(tokens);
+    let sentence_of = |token: usize| sentences.iter().position(|r| r.contains(&token)).unwrap_or(0);
+
+    let mut persons: Vec<(usize, &EntitySpan)> = entities
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.category == EntityCategory::Per)
+        .collect();
+    persons.sort_by_key(|(_, e)| e.end_token);
+
+    let mut mentions = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let Some(gender) = pronoun_gender(&token.text) else {
+            continue;
+        };
+        if entities.iter().any(|e| (e.start_token..=e.end_token).contains(&i)) {
+            continue;
+        }
+        let first_sentence = sentence_of(i).saturating_sub(config.max_sentences);
+        let window: Vec<&(usize, &EntitySpan)> = persons
+            .iter()
+            .rev()
+            .filter(|(_, e)| e.end_token < i)
+            .take_while(|(_, e)| sentence_of(e.start_token) >= first_sentence)
+            .collect();
+        // Um nome do mesmo gênero vence um mais recente de gênero desconhecido
+        let antecedent = window
+            .iter()
+            .find(|(_, e)| guess_gender(&e.text) == Some(gender))
+            .or_else(|| window.iter().find(|(_, e)| guess_gender(&e.text).is_none()));
+        if let Some(&&(refers_to, _)) = antecedent {
+            mentions.push(PronounMention {
+                text: token.text.clone(),
+                token_index: i,
+                start: token.start,
+                end: token.end,
+                gender,
+                refers_to,
+            });
+        }
+    }
+    mentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::tokenize;
+
+    fn entity(tokens: &[Token], first: &str, len: usize, category: EntityCategory) -> EntitySpan {
+        let start_token = tokens.iter().position(|t| t.text == first).unwrap();
+        let end_token = start_token + len - 1;
+        EntitySpan {
+            text: tokens[start_token..=end_token].iter().map(|t| t.text.as_str()).collect::<Vec<_>>().join(" "),
+            category,
+            start_token,
+            end_token,
+            start: tokens[start_token].start,
+            end: tokens[end_token].end,
+            confidence: 0.9,
+            source: "crf".to_string(),
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn test_pronouns_resolve_to_recent_compatible_person() {
+        let text = "Dilma Rousseff encontrou Temer na Petrobras. Ela falou com ele. \
+                    A reunião durou horas. Depois houve chuva. Ninguém sabe o que ela disse.";
+        let tokens = tokenize(text);
+        let entities = vec![
+            entity(&tokens, "Dilma", 2, EntityCategory::Per),
+            entity(&tokens, "Temer", 1, EntityCategory::Per),
+            entity(&tokens, "Petrobras", 1, EntityCategory::Org),
+        ];
+
+        let mentions = resolve_pronouns(&tokens, &entities, &CorefConfig::default());
+        let resolved: Vec<(&str, usize, Gender)> =
+            mentions.iter().map(|m| (m.text.as_str(), m.refers_to, m.gender)).collect();
+        // "Temer" (gênero desconhecido) é mais recente, mas "Dilma" é feminino e vence para "Ela";
+        // o último "ela" está 4 sentenças depois de "Dilma", fora da janela padrão
+        assert_eq!(resolved, vec![("Ela", 0, Gender::Feminine), ("ele", 1, Gender::Masculine)]);
+        assert_eq!(&text[mentions[0].start..mentions[0].end], "Ela");
+
+        let wide = resolve_pronouns(&tokens, &entities, &CorefConfig::default().with_max_sentences(4));
+        assert_eq!(wide.len(), 3);
+        assert_eq!(wide[2].refers_to, 0);
+        assert!(resolve_pronouns(&tokens, &entities, &CorefConfig::default().with_max_sentences(0)).is_empty());
+    }
+}
@@ -14,6 +14,17 @@
 //! - Cultura e entretenimento
 //! - Meio ambiente
 //! - Educação
+//! - Expressões temporais (datas e horários)
+//!
+//! Corpora externos em colunas CoNLL (HAREM, LeNER-Br) são carregados com [`load_conll`];
+//! os anotados em outro padrão (OntoNotes), com [`load_conll_mapped`].
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::corpus_reader::{CorpusError, CorpusFormat, CorpusReader};
+use crate::label_map::LabelMapping;
 
 /// Uma sentença anotada no formato BIO
 ///
@@ -21,19 +32,141 @@
 /// - **B-TYPE**: Início de uma entidade do tipo TYPE.
 /// - **I-TYPE**: Continuação de uma entidade do tipo TYPE.
 /// - **O**: Fora de qualquer entidade.
-pub struct AnnotatedSentence {
+///
+/// O corpus embutido usa `'static`; o tempo de vida `'a` permite também sentenças
+/// geradas em tempo de execução (ver [`crate::synthetic`]).
+pub struct AnnotatedSentence<'a> {
     /// O texto completo da sentença (idealmente sem tokenização prévia,
     /// mas aqui já estruturado para facilitar).
-    pub text: &'static str,
+    pub text: &'a str,
     /// Domínio temático (utilizado para análises de performance por área).
-    pub domain: &'static str,
+    pub domain: &'a str,
     /// Pares (palavra, tag_BIO).
     /// Exemplo: `[("Lula", "B-PER"), ("viajou", "O")]`
-    pub annotations: &'static [(&'static str, &'static str)],
+    pub annotations: &'a [(&'a str, &'a str)],
+}
+
+/// Versão com dados próprios de [`AnnotatedSentence`], produzida por leitores que
+/// carregam o corpus do disco (ver [`crate::corpus_reader`]).
+///
+/// Os treinadores com `train_stream` consomem um iterador destas sentenças; o corpus
+/// embutido é convertido com `From<&AnnotatedSentence>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedAnnotatedSentence {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub domain: String,
+    /// Pares (palavra, tag_BIO).
+    pub annotations: Vec<(String, String)>,
+}
+
+impl From<&AnnotatedSentence<'_>> for OwnedAnnotatedSentence {
+    fn from(sentence: &AnnotatedSentence<'_>) -> Self {
+        Self {
+            text: sentence.text.to_string(),
+            domain: sentence.domain.to_string(),
+            annotations: sentence
+                .annotations
+                .iter()
+                .map(|(w, t)| (w.to_string(), t.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Carrega um corpus externo no formato de colunas CoNLL-2002/2003 (HAREM, LeNER-Br...).
+///
+/// Cada linha traz a palavra na primeira coluna e a tag na última; sentenças são
+/// separadas por linha em branco, e linhas `-DOCSTART-` e comentários são ignorados
+/// (ver [`crate::corpus_reader`]). O arquivo precisa estar em UTF-8.
+///
+/// Tags no esquema IOB1 do CoNLL-2003, em que `I-X` também abre uma entidade, são
+/// convertidas para BIO (`I-X` sem `B-X`/`I-X` antes vira `B-X`). Diferente da leitura
+/// em streaming, uma linha malformada interrompe a carga com o número da linha.
+///
+/// Para os treinadores que recebem `&[AnnotatedSentence]`, use [`with_annotated`]:
+///
+/// ```rust
+/// use ner_core::corpus::{load_conll, with_annotated};
+/// use ner_core::hmm::HmmModel;
+///
+/// let path = std::env::temp_dir().join(format!("ner_doc_load_{}.conll", std::process::id()));
+/// std::fs::write(&path, "-DOCSTART- O\n\nLula I-PER\nviajou O\n\nO O\nBrasil I-LOC\n").unwrap();
+///
+/// let sentences = load_conll(&path).unwrap();
+/// assert_eq!(sentences[0].annotations[0], ("Lula".to_string(), "B-PER".to_string()));
+///
+/// let mut hmm = HmmModel::new();
+/// with_annotated(&sentences, |corpus| hmm.train(corpus));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn load_conll(path: impl AsRef<Path>) -> Result<Vec<OwnedAnnotatedSentence>, CorpusError> {
+    let mut reader = CorpusReader::open(path, CorpusFormat::Conll)?;
+    let mut sentences = Vec::new();
+    while let Some(mut sentence) = reader.next_sentence()? {
+        iob1_to_bio(&mut sentence.annotations);
+        sentences.push(sentence);
+    }
+    Ok(sentences)
+}
+
+/// Como [`load_conll`], traduzindo as categorias de outro padrão de anotação
+/// (OntoNotes, CoNLL-2003...) pelo `mapping` (ver [`crate::label_map`]).
+///
+/// ```rust
+/// use ner_core::corpus::load_conll_mapped;
+/// use ner_core::label_map::LabelMapping;
+///
+/// let path = std::env::temp_dir().join(format!("ner_doc_mapped_{}.conll", std::process::id()));
+/// std::fs::write(&path, "Lula B-PERSON\nvisitou O\nRecife B-GPE\n").unwrap();
+///
+/// let sentences = load_conll_mapped(&path, &LabelMapping::ontonotes()).unwrap();
+/// let tags: Vec<&str> = sentences[0].annotations.iter().map(|(_, t)| t.as_str()).collect();
+/// assert_eq!(tags, ["B-PER", "O", "B-LOC"]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn load_conll_mapped(path: impl AsRef<Path>, mapping: &LabelMapping) -> Result<Vec<OwnedAnnotatedSentence>, CorpusError> {
+    let mut sentences = load_conll(path)?;
+    mapping.import_corpus(&mut sentences);
+    Ok(sentences)
+}
+
+/// Converte tags IOB1 para BIO: um `I-X` que não continua uma entidade `X` vira `B-X`.
+pub(crate) fn iob1_to_bio(annotations: &mut [(String, String)]) {
+    let mut previous = String::from("O");
+    for (_, tag) in annotations.iter_mut() {
+        if let Some(category) = tag.strip_prefix("I-") {
+            let continues = previous.strip_prefix("B-").or_else(|| previous.strip_prefix("I-")) == Some(category);
+            if !continues {
+                *tag = format!("B-{category}");
+            }
+        }
+        previous.clone_from(tag);
+    }
+}
+
+/// Executa `f` com sentenças próprias no formato aceito pelos treinadores
+/// (`train(&[AnnotatedSentence])`), sem copiar os textos.
+pub fn with_annotated<R>(sentences: &[OwnedAnnotatedSentence], f: impl FnOnce(&[AnnotatedSentence<'_>]) -> R) -> R {
+    let pairs: Vec<Vec<(&str, &str)>> = sentences
+        .iter()
+        .map(|s| s.annotations.iter().map(|(w, t)| (w.as_str(), t.as_str())).collect())
+        .collect();
+    let annotated: Vec<AnnotatedSentence<'_>> = sentences
+        .iter()
+        .zip(&pairs)
+        .map(|(s, annotations)| AnnotatedSentence {
+            text: &s.text,
+            domain: &s.domain,
+            annotations,
+        })
+        .collect();
+    f(&annotated)
 }
 
 /// Retorna o corpus completo em PT-BR
-pub fn get_corpus() -> Vec<AnnotatedSentence> {
+pub fn get_corpus() -> Vec<AnnotatedSentence<'static>> {
     vec![
         // ===== SAÚDE =====
         AnnotatedSentence {
@@ -42,7 +175,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             annotations: &[
                 ("A", "O"), ("Fiocruz", "B-ORG"), ("desenvolveu", "O"), ("a", "O"),
                 ("vacina", "O"), ("contra", "O"), ("a", "O"), ("dengue", "B-MISC"),
-                ("aprovada", "O"), ("pela", "O"), ("Anvisa", "B-ORG"), ("em", "O"), ("2023", "O"), (".", "O"),
+                ("aprovada", "O"), ("pela", "O"), ("Anvisa", "B-ORG"), ("em", "O"), ("2023", "B-DATE"), (".", "O"),
             ],
         },
         AnnotatedSentence {
@@ -93,7 +226,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
                 ("A", "O"), ("Organização", "B-ORG"), ("Mundial", "I-ORG"), ("da", "I-ORG"), ("Saúde", "I-ORG"),
                 ("declarou", "O"), ("o", "O"), ("fim", "O"), ("da", "O"), ("emergência", "O"),
                 ("global", "O"), ("da", "O"), ("Covid-19", "B-MISC"), ("em", "O"),
-                ("maio", "O"), ("de", "O"), ("2023", "O"), (".", "O"),
+                ("maio", "B-DATE"), ("de", "I-DATE"), ("2023", "I-DATE"), (".", "O"),
             ],
         },
 
@@ -188,7 +321,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             domain: "religião",
             annotations: &[
                 ("A", "O"), ("Umbanda", "B-MISC"), ("surgiu", "O"), ("no", "O"), ("Brasil", "B-LOC"),
-                ("no", "O"), ("início", "O"), ("do", "O"), ("século", "O"), ("XX", "O"),
+                ("no", "O"), ("início", "O"), ("do", "O"), ("século", "B-DATE"), ("XX", "I-DATE"),
                 (",", "O"), ("combinando", "O"), ("elementos", "O"), ("do", "O"),
                 ("Candomblé", "B-MISC"), (",", "O"), ("do", "O"), ("Espiritismo", "B-MISC"),
                 ("e", "O"), ("do", "O"), ("catolicismo", "O"), (".", "O"),
@@ -200,7 +333,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             annotations: &[
                 ("Allan", "B-PER"), ("Kardec", "I-PER"), ("codificou", "O"),
                 ("o", "O"), ("Espiritismo", "B-MISC"), ("na", "O"), ("França", "B-LOC"),
-                ("no", "O"), ("século", "O"), ("XIX", "O"), (",", "O"), ("obra", "O"),
+                ("no", "O"), ("século", "B-DATE"), ("XIX", "I-DATE"), (",", "O"), ("obra", "O"),
                 ("que", "O"), ("se", "O"), ("tornou", "O"), ("base", "O"),
                 ("para", "O"), ("o", "O"), ("espiritismo", "O"), ("brasileiro", "O"), (".", "O"),
             ],
@@ -214,7 +347,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
                 ("Dom", "B-PER"), ("Pedro", "I-PER"), ("I", "I-PER"), ("proclamou", "O"), ("a", "O"),
                 ("Independência", "B-MISC"), ("do", "I-MISC"), ("Brasil", "I-MISC"),
                 ("às", "O"), ("margens", "O"), ("do", "O"), ("Rio", "B-LOC"), ("Ipiranga", "I-LOC"),
-                ("em", "O"), ("1822", "O"), (".", "O"),
+                ("em", "O"), ("1822", "B-DATE"), (".", "O"),
             ],
         },
         AnnotatedSentence {
@@ -222,7 +355,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             domain: "história",
             annotations: &[
                 ("Tiradentes", "B-PER"), ("foi", "O"), ("enforcado", "O"), ("em", "O"),
-                ("21", "O"), ("de", "O"), ("abril", "O"), ("de", "O"), ("1792", "O"),
+                ("21", "B-DATE"), ("de", "I-DATE"), ("abril", "I-DATE"), ("de", "I-DATE"), ("1792", "I-DATE"),
                 ("no", "O"), ("Rio", "B-LOC"), ("de", "I-LOC"), ("Janeiro", "I-LOC"),
                 ("por", "O"), ("liderar", "O"), ("a", "O"),
                 ("Inconfidência", "B-MISC"), ("Mineira", "I-MISC"), (".", "O"),
@@ -244,7 +377,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             domain: "história",
             annotations: &[
                 ("A", "O"), ("Semana", "B-MISC"), ("de", "I-MISC"), ("Arte", "I-MISC"),
-                ("Moderna", "I-MISC"), ("de", "O"), ("1922", "O"), ("em", "O"),
+                ("Moderna", "I-MISC"), ("de", "O"), ("1922", "B-DATE"), ("em", "O"),
                 ("São", "B-LOC"), ("Paulo", "I-LOC"), ("marcou", "O"), ("o", "O"),
                 ("início", "O"), ("do", "O"), ("Modernismo", "B-MISC"),
                 ("na", "O"), ("cultura", "O"), ("brasileira", "O"), (".", "O"),
@@ -266,8 +399,8 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             domain: "história",
             annotations: &[
                 ("Princesa", "O"), ("Isabel", "B-PER"), ("assinou", "O"), ("a", "O"),
-                ("Lei", "B-MISC"), ("Áurea", "I-MISC"), ("em", "O"), ("13", "O"),
-                ("de", "O"), ("maio", "O"), ("de", "O"), ("1888", "O"), (",", "O"),
+                ("Lei", "B-MISC"), ("Áurea", "I-MISC"), ("em", "O"), ("13", "B-DATE"),
+                ("de", "I-DATE"), ("maio", "I-DATE"), ("de", "I-DATE"), ("1888", "I-DATE"), (",", "O"),
                 ("abolindo", "O"), ("a", "O"), ("escravidão", "O"), ("no", "O"), ("Brasil", "B-LOC"), (".", "O"),
             ],
         },
@@ -278,7 +411,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
                 ("Santos", "B-PER"), ("Dumont", "I-PER"), ("realizou", "O"), ("o", "O"),
                 ("primeiro", "O"), ("voo", "O"), ("reconhecido", "O"), ("da", "O"),
                 ("história", "O"), ("com", "O"), ("o", "O"),
-                ("14-Bis", "B-MISC"), ("em", "O"), ("Paris", "B-LOC"), ("em", "O"), ("1906", "O"), (".", "O"),
+                ("14-Bis", "B-MISC"), ("em", "O"), ("Paris", "B-LOC"), ("em", "O"), ("1906", "B-DATE"), (".", "O"),
             ],
         },
 
@@ -356,7 +489,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
                 ("Beatriz", "B-PER"), ("Souza", "I-PER"), ("conquistou", "O"), ("a", "O"),
                 ("medalha", "O"), ("de", "O"), ("ouro", "O"), ("no", "O"), ("judô", "O"),
                 ("nos", "O"), ("Jogos", "B-MISC"), ("Olímpicos", "I-MISC"), ("de", "O"),
-                ("Paris", "B-LOC"), ("em", "O"), ("2024", "O"), (".", "O"),
+                ("Paris", "B-LOC"), ("em", "O"), ("2024", "B-DATE"), (".", "O"),
             ],
         },
 
@@ -407,7 +540,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             annotations: &[
                 ("Carmen", "B-PER"), ("Miranda", "I-PER"), ("representou", "O"), ("o", "O"),
                 ("Brasil", "B-LOC"), ("no", "O"), ("cinema", "O"), ("americano", "O"),
-                ("nas", "O"), ("décadas", "O"), ("de", "O"), ("1940", "O"), ("e", "O"), ("1950", "O"), (".", "O"),
+                ("nas", "O"), ("décadas", "O"), ("de", "O"), ("1940", "B-DATE"), ("e", "O"), ("1950", "B-DATE"), (".", "O"),
             ],
         },
 
@@ -418,7 +551,7 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
             annotations: &[
                 ("O", "O"), ("desmatamento", "O"), ("da", "O"), ("Floresta", "B-LOC"),
                 ("Amazônica", "I-LOC"), ("atingiu", "O"), ("11", "O"), ("mil", "O"), ("km²", "O"),
-                ("em", "O"), ("2022", "O"), (",", "O"), ("segundo", "O"), ("o", "O"), ("INPE", "B-ORG"), (".", "O"),
+                ("em", "O"), ("2022", "B-DATE"), (",", "O"), ("segundo", "O"), ("o", "O"), ("INPE", "B-ORG"), (".", "O"),
             ],
         },
         AnnotatedSentence {
@@ -430,6 +563,48 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
                 ("e", "O"), ("é", "O"), ("vital", "O"), ("para", "O"), ("o", "O"), ("Nordeste", "B-LOC"), (".", "O"),
             ],
         },
+        // ===== EXPRESSÕES TEMPORAIS =====
+        AnnotatedSentence {
+            text: "O Jornal Nacional vai ao ar às 20h30 de segunda-feira a sábado na TV Globo.",
+            domain: "temporal",
+            annotations: &[
+                ("O", "O"), ("Jornal", "B-MISC"), ("Nacional", "I-MISC"), ("vai", "O"),
+                ("ao", "O"), ("ar", "O"), ("às", "O"), ("20h30", "B-TIME"), ("de", "O"),
+                ("segunda-feira", "B-DATE"), ("a", "O"), ("sábado", "B-DATE"), ("na", "O"),
+                ("TV", "B-ORG"), ("Globo", "I-ORG"), (".", "O"),
+            ],
+        },
+        AnnotatedSentence {
+            text: "A sessão do Senado Federal começou às 14h de quarta-feira e terminou à meia-noite.",
+            domain: "temporal",
+            annotations: &[
+                ("A", "O"), ("sessão", "O"), ("do", "O"), ("Senado", "B-ORG"), ("Federal", "I-ORG"),
+                ("começou", "O"), ("às", "O"), ("14h", "B-TIME"), ("de", "O"),
+                ("quarta-feira", "B-DATE"), ("e", "O"), ("terminou", "O"), ("à", "O"),
+                ("meia-noite", "B-TIME"), (".", "O"),
+            ],
+        },
+        AnnotatedSentence {
+            text: "O Réveillon de Copacabana reuniu dois milhões de pessoas na noite de 31 de dezembro de 2023.",
+            domain: "temporal",
+            annotations: &[
+                ("O", "O"), ("Réveillon", "B-MISC"), ("de", "O"), ("Copacabana", "B-LOC"),
+                ("reuniu", "O"), ("dois", "O"), ("milhões", "O"), ("de", "O"), ("pessoas", "O"),
+                ("na", "O"), ("noite", "O"), ("de", "O"), ("31", "B-DATE"), ("de", "I-DATE"),
+                ("dezembro", "I-DATE"), ("de", "I-DATE"), ("2023", "I-DATE"), (".", "O"),
+            ],
+        },
+        AnnotatedSentence {
+            text: "O voo da Azul partiu de Campinas às 7h45 do dia 2 de janeiro.",
+            domain: "temporal",
+            annotations: &[
+                ("O", "O"), ("voo", "O"), ("da", "O"), ("Azul", "B-ORG"), ("partiu", "O"),
+                ("de", "O"), ("Campinas", "B-LOC"), ("às", "O"), ("7h45", "B-TIME"),
+                ("do", "O"), ("dia", "O"), ("2", "B-DATE"), ("de", "I-DATE"),
+                ("janeiro", "I-DATE"), (".", "O"),
+            ],
+        },
+
         // ===== DESAMBIGUAÇÃO =====
         AnnotatedSentence {
             text: "Paris Hilton viajou para Paris na França para participar de um desfile de moda.",
@@ -462,159 +637,109 @@ pub fn get_corpus() -> Vec<AnnotatedSentence> {
     ]
 }
 
+/// Entidades conhecidas extraídas do corpus, agrupadas por categoria (lowercase).
+#[derive(Debug, Clone, Default)]
+pub struct CorpusGazetteers {
+    pub persons: Vec<String>,
+    pub locations: Vec<String>,
+    pub orgs: Vec<String>,
+    pub misc: Vec<String>,
+    pub dates: Vec<String>,
+    pub times: Vec<String>,
+}
+
 /// Extrai gazetteers do corpus: conjuntos de entidades conhecidas por categoria
 ///
 /// Varre todo o corpus de treinamento e constrói listas (sets) de nomes conhecidos.
 /// Isso é usado para criar features binárias poderosas (ex: "está_no_gazetteer_de_pessoas?").
 ///
 /// # Retorno
-/// Tupla contendo vetores de strings para:
-/// (Pessoas, Locais, Organizações, Miscelânea)
-pub fn extract_gazetteers_from_corpus() -> (
-    Vec<String>, // persons
-    Vec<String>, // locations
-    Vec<String>, // orgs
-    Vec<String>, // misc
-) {
+/// Um [`CorpusGazetteers`] com as listas de Pessoas, Locais, Organizações, Miscelânea,
+/// Datas e Horários.
+pub fn extract_gazetteers_from_corpus() -> CorpusGazetteers {
     let corpus = get_corpus();
     let mut persons = std::collections::HashSet::new();
     let mut locations = std::collections::HashSet::new();
     let mut orgs = std::collections::HashSet::new();
     let mut misc = std::collections::HashSet::new();
+    let mut dates = std::collections::HashSet::new();
+    let mut times = std::collections::HashSet::new();
+
+    // Fecha a entidade acumulada, inserindo-a no conjunto da sua categoria
+    let mut flush = |entity_tokens: &mut Vec<&str>, current_type: &str| {
+        if entity_tokens.is_empty() {
+            return;
+        }
+        let entity = entity_tokens.join(" ").to_lowercase();
+        match current_type {
+            "PER" => { persons.insert(entity); }
+            "LOC" => { locations.insert(entity); }
+            "ORG" => { orgs.insert(entity); }
+            "MISC" => { misc.insert(entity); }
+            "DATE" => { dates.insert(entity); }
+            "TIME" => { times.insert(entity); }
+            _ => {}
+        }
+        entity_tokens.clear();
+    };
 
     for sentence in &corpus {
         let mut entity_tokens: Vec<&str> = vec![];
         let mut current_type = "";
 
         for (word, tag) in sentence.annotations {
-            match *tag {
-                "B-PER" => {
-                    if !entity_tokens.is_empty() {
-                        let entity = entity_tokens.join(" ").to_lowercase();
-                        match current_type {
-                            "PER" => { persons.insert(entity); }
-                            "LOC" => { locations.insert(entity); }
-                            "ORG" => { orgs.insert(entity); }
-                            "MISC" => { misc.insert(entity); }
-                            _ => {}
-                        }
-                    }
-                    entity_tokens = vec![word];
-                    current_type = "PER";
-                }
-                "B-LOC" => {
-                    if !entity_tokens.is_empty() {
-                        let entity = entity_tokens.join(" ").to_lowercase();
-                        match current_type {
-                            "PER" => { persons.insert(entity); }
-                            "LOC" => { locations.insert(entity); }
-                            "ORG" => { orgs.insert(entity); }
-                            "MISC" => { misc.insert(entity); }
-                            _ => {}
-                        }
-                    }
-                    entity_tokens = vec![word];
-                    current_type = "LOC";
-                }
-                "B-ORG" => {
-                    if !entity_tokens.is_empty() {
-                        let entity = entity_tokens.join(" ").to_lowercase();
-                        match current_type {
-                            "PER" => { persons.insert(entity); }
-                            "LOC" => { locations.insert(entity); }
-                            "ORG" => { orgs.insert(entity); }
-                            "MISC" => { misc.insert(entity); }
-                            _ => {}
-                        }
-                    }
-                    entity_tokens = vec![word];
-                    current_type = "ORG";
-                }
-                "B-MISC" => {
-                    if !entity_tokens.is_empty() {
-                        let entity = entity_tokens.join(" ").to_lowercase();
-                        match current_type {
-                            "PER" => { persons.insert(entity); }
-                            "LOC" => { locations.insert(entity); }
-                            "ORG" => { orgs.insert(entity); }
-                            "MISC" => { misc.insert(entity); }
-                            _ => {}
-                        }
-                    }
-                    entity_tokens = vec![word];
-                    current_type = "MISC";
-                }
-                tag if tag.starts_with("I-") => {
-                    entity_tokens.push(word);
-                }
-                _ => {
-                    if !entity_tokens.is_empty() {
-                        let entity = entity_tokens.join(" ").to_lowercase();
-                        match current_type {
-                            "PER" => { persons.insert(entity); }
-                            "LOC" => { locations.insert(entity); }
-                            "ORG" => { orgs.insert(entity); }
-                            "MISC" => { misc.insert(entity); }
-                            _ => {}
-                        }
-                        entity_tokens = vec![];
-                        current_type = "";
-                    }
-                }
+            if let Some(category) = tag.strip_prefix("B-") {
+                flush(&mut entity_tokens, current_type);
+                entity_tokens.push(word);
+                current_type = category;
+            } else if tag.starts_with("I-") {
+                entity_tokens.push(word);
+            } else {
+                flush(&mut entity_tokens, current_type);
+                current_type = "";
             }
         }
+        flush(&mut entity_tokens, current_type);
     }
 
-    (
-        persons.into_iter().collect(),
-        locations.into_iter().collect(),
-        orgs.into_iter().collect(),
-        misc.into_iter().collect(),
-    )
+    CorpusGazetteers {
+        persons: persons.into_iter().collect(),
+        locations: locations.into_iter().collect(),
+        orgs: orgs.into_iter().collect(),
+        misc: misc.into_iter().collect(),
+        dates: dates.into_iter().collect(),
+        times: times.into_iter().collect(),
+    }
 }
 
-/// Textos de demonstração para a interface web
-pub fn demo_texts() -> Vec<(&'static str, &'static str)> {
-    vec![
-        (
-            "Saúde",
-            "O Hospital Albert Einstein, localizado no Morumbi em São Paulo, é uma referência internacional em medicina de alta complexidade. Juntamente com o Instituto Butantan e a Fundação Oswaldo Cruz (Fiocruz), a instituição tem liderado pesquisas inovadoras no combate a doenças tropicais. A Agência Nacional de Vigilância Sanitária (Anvisa) aprovou recentemente novos protocolos clínicos densenvolvidos pela pesquisadora Margareth Dalcolmo para o tratamento de variantes da Covid-19.",
-        ),
-        (
-            "História",
-            "Em 7 de setembro de 1822, Dom Pedro I proclamou a Independência do Brasil às margens do Rio Ipiranga. Décadas mais tarde, a Princesa Isabel sancionou a Lei Áurea em 13 de maio de 1888, encerrando oficialmente o ciclo da escravidão no país. Figuras como Zumbi dos Palmares, líder do maior quilombo das Américas, e Tiradentes, mártir da Inconfidência Mineira, são celebrados como heróis nacionais que lutaram pela liberdade e justiça social.",
-        ),
-        (
-            "Tecnologia",
-            "A startup brasileira Nubank, fundada por David Vélez, Cristina Junqueira e Edward Wible, revolucionou o setor bancário na América Latina. Com sede em São Paulo, a empresa expandiu operações para o México e Colômbia, alcançando mais de 90 milhões de clientes. Recentemente, a Embraer anunciou uma parceria estratégica com a Boeing para o desenvolvimento de combustíveis sustentáveis de aviação, reforçando a posição do Brasil como líder em tecnologia aeroespacial.",
-        ),
-        (
-            "Cultura",
-            "A Semana de Arte Moderna de 1922, realizada no Theatro Municipal de São Paulo, contou com a participação de Mário de Andrade, Oswald de Andrade e Tarsila do Amaral. O evento marcou o início do Modernismo no Brasil, rompendo com o conservadorismo acadêmico. Na música, Heitor Villa-Lobos e, posteriormente, Carmen Miranda, levaram a identidade cultural brasileira para os palcos internacionais, consolidando o samba e a bossa nova como gêneros de exportação.",
-        ),
-        (
-            "Desambiguação",
-            "A socialite Paris Hilton viajou para Paris, capital da França, na última semana. Durante a viagem, ela sentou em um banco próximo à Torre Eiffel após autorizar saques em sua conta no Banco do Brasil. O porta-voz do Estado do Rio de Janeiro confirmou que o estado de calamidade pública impede o envio de representantes.",
-        ),
-        (
-            "Tokenização",
-            "A Sra. Silva (nascida em 15/03/1980) comprou U$5.000,00 na bolsa de N.Y. às 14h30min usando seu e-mail ana.silva@exemplo.com.br! O site www.financas.com reportou que as ações da Apple Inc. subiram 2,5%. E aí, será que a Bovespa (IBOV) vai acompanhar essa alta-frequência de mercado?",
-        ),
-        (
-            "Esportes",
-            "Neymar Jr. marcou dois gols pelo Al-Hilal no estádio King Fahd em Riad, na Arábia Saudita. A Confederação Brasileira de Futebol (CBF) convocou Vinícius Jr., do Real Madrid, e Endrick, também do Real Madrid, para a Copa América. O técnico Dorival Júnior declarou que o Maracanã será palco do próximo amistoso contra a Argentina de Lionel Messi.",
-        ),
-        (
-            "Direito",
-            "O Supremo Tribunal Federal (STF), sob a presidência do Ministro Luís Roberto Barroso, julgou a constitucionalidade da Emenda Constitucional nº 45. O Procurador-Geral da República, Paulo Gonet, apresentou parecer ao Tribunal Superior Eleitoral (TSE) em Brasília. A Ordem dos Advogados do Brasil (OAB) emitiu nota conjunta com o Conselho Nacional de Justiça (CNJ) sobre a reforma do Código Penal.",
-        ),
-        (
-            "Economia",
-            "O Banco Central do Brasil, presidido por Gabriel Galípolo, manteve a taxa Selic em 13,75%. O Fundo Monetário Internacional (FMI) revisou a previsão de crescimento do PIB brasileiro. A Petrobras anunciou investimentos de R$ 100 bilhões em parceria com a Shell e a TotalEnergies para exploração de petróleo na Bacia de Santos, litoral de São Paulo.",
-        ),
-        (
-            "Ciência",
-            "Pesquisadores do Instituto Nacional de Pesquisas Espaciais (INPE), em São José dos Campos, detectaram aumento no desmatamento da Amazônia usando satélites do programa CBERS, desenvolvido em parceria com a Agência Espacial Chinesa. A bióloga Natália Pasternak, do Instituto Questão de Ciência, publicou estudo na revista Nature sobre a eficácia de vacinas produzidas pelo Instituto Butantan em colaboração com a Universidade de Oxford.",
-        ),
-    ]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_conll_converts_iob1() {
+        let path = std::env::temp_dir().join(format!("ner_load_conll_{}.conll", std::process::id()));
+        std::fs::write(
+            &path,
+            "-DOCSTART- -X- O O\n\nBanco NNP I-ORG\ndo PREP I-ORG\nBrasil NNP I-ORG\ne CONJ O\nCaixa NNP I-ORG\nFederal NNP B-ORG\n\n\nAna NNP I-PER\nsaiu VB O\n",
+        )
+        .unwrap();
+        let sentences = load_conll(&path).unwrap();
+        assert_eq!(sentences.len(), 2);
+        let tags: Vec<&str> = sentences[0].annotations.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(tags, ["B-ORG", "I-ORG", "I-ORG", "O", "B-ORG", "B-ORG"]);
+        assert_eq!(sentences[1].text, "Ana saiu");
+        assert_eq!(sentences[1].domain, "conll");
+
+        let tokens = with_annotated(&sentences, |corpus| {
+            assert_eq!(corpus[1].annotations[0], ("Ana", "B-PER"));
+            corpus.iter().map(|s| s.annotations.len()).sum::<usize>()
+        });
+        assert_eq!(tokens, 8);
+
+        std::fs::write(&path, "Lula B-PER\nsolto\n").unwrap();
+        assert!(matches!(load_conll(&path), Err(CorpusError::Malformed { line: 2, .. })));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(load_conll(&path), Err(CorpusError::Io(_))));
+    }
 }
@@ -0,0 +1,300 @@
+//! # Leitura de Corpus do Disco (Streaming)
+//!
+//! O corpus embutido ([`crate::corpus::get_corpus`]) cabe folgado na memória, mas
+//! corpora reais (CoNLL-2003, HAREM, WikiNER) chegam a milhões de tokens. Em vez de
+//! carregar tudo num `Vec`, [`CorpusReader`] lê **uma sentença por vez** do arquivo e a
+//! entrega como [`OwnedAnnotatedSentence`]; só o buffer de leitura fica em memória.
+//!
+//! Formatos aceitos ([`CorpusFormat`]):
+//!
+//! - **CoNLL**: um token por linha (`palavra ... tag`, a palavra na primeira coluna e
+//!   a tag BIO na última), sentenças separadas por linha em branco. Linhas
+//!   `-DOCSTART-` e comentários (`# sent_id = 1`) são ignorados.
+//! - **JSONL**: uma sentença por linha, no formato serializado de
+//!   [`OwnedAnnotatedSentence`] (`{"text": ..., "annotations": [["Lula", "B-PER"], ...]}`).
+//!
+//! ## Várias épocas
+//!
+//! Os treinadores (`train_stream` em perceptron, maxent, span) percorrem o corpus
+//! várias vezes clonando o iterador no início de cada época. Clonar um
+//! [`CorpusReader`] é barato: o clone abre o próprio descritor de arquivo, na mesma
+//! posição do original, apenas quando for lido.
+//!
+//! Linhas malformadas são puladas durante a iteração (ver [`CorpusReader::skipped`]);
+//! [`CorpusReader::validate`] percorre o arquivo e reporta o primeiro erro, para
+//! checar o corpus antes de um treino longo.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::corpus_reader::{CorpusFormat, CorpusReader};
+//!
+//! let path = std::env::temp_dir().join(format!("ner_doc_corpus_{}.conll", std::process::id()));
+//! std::fs::write(&path, "Lula B-PER\nviajou O\n\nO O\nBrasil B-LOC\n").unwrap();
+//!
+//! let reader = CorpusReader::open(&path, CorpusFormat::Conll).unwrap();
+//! assert_eq!(reader.validate().unwrap(), 2);
+//! let texts: Vec<String> = reader.map(|s| s.text).collect();
+//! assert_eq!(texts, vec!["Lula viajou", "O Brasil"]);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::corpus::OwnedAnnotatedSentence;
+
+/// Formato do arquivo de corpus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusFormat {
+    Conll,
+    Jsonl,
+}
+
+impl CorpusFormat {
+    /// Formato pela extensão: `.jsonl` é JSONL; `.conll`, `.bio`, `.iob` e `.txt` são CoNLL.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "jsonl" => Some(Self::Jsonl),
+            "conll" | "bio" | "iob" | "txt" => Some(Self::Conll),
+            _ => None,
+        }
+    }
+}
+
+/// Erro ao ler um corpus.
+#[derive(Debug)]
+pub enum CorpusError {
+    Io(io::Error),
+    /// Linha que não segue o formato (numerada a partir de 1).
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for CorpusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "erro de leitura do corpus: {e}"),
+            Self::Malformed { line, message } => write!(f, "linha {line} do corpus malformada: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CorpusError {}
+
+impl From<io::Error> for CorpusError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Iterador de sentenças lidas sob demanda de um arquivo de corpus.
+#[derive(Debug)]
+pub struct CorpusReader {
+    path: PathBuf,
+    format: CorpusFormat,
+    /// Aberto na primeira leitura (e em cada clone), posicionado em `offset`.
+    reader: Option<BufReader<File>>,
+    /// Bytes já consumidos do arquivo.
+    offset: u64,
+    /// Linhas já consumidas (para as mensagens de erro).
+    line: usize,
+    skipped: usize,
+    buffer: String,
+}
+
+impl CorpusReader {
+    /// Abre `path`, falhando já aqui se o arquivo não puder ser lido.
+    pub fn open(path: impl AsRef<Path>, format: CorpusFormat) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let reader = BufReader::new(File::open(&path)?);
+        Ok(Self {
+            path,
+            format,
+            reader: Some(reader),
+            offset: 0,
+            line: 0,
+            skipped: 0,
+            buffer: String::new(),
+        })
+    }
+
+    pub fn format(&self) -> CorpusFormat {
+        self.format
+    }
+
+    /// Sentenças puladas até agora por estarem malformadas.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Percorre (um clone de) o leitor a partir da posição atual e conta as sentenças,
+    /// parando no primeiro erro.
+    pub fn validate(&self) -> Result<usize, CorpusError> {
+        let mut reader = self.clone();
+        let mut count = 0;
+        while reader.next_sentence()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Lê a próxima linha (sem o `\n`) para `self.buffer`; `false` no fim do arquivo.
+    fn read_line(&mut self) -> io::Result<bool> {
+        if self.reader.is_none() {
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(self.offset))?;
+            self.reader = Some(BufReader::new(file));
+        }
+        let reader = self.reader.as_mut().expect("leitor aberto acima");
+        self.buffer.clear();
+        let read = reader.read_line(&mut self.buffer)?;
+        self.offset += read as u64;
+        self.line += 1;
+        let trimmed = self.buffer.trim_end_matches(['\n', '\r']).len();
+        self.buffer.truncate(trimmed);
+        Ok(read > 0)
+    }
+
+    /// Próxima sentença, ou o erro da primeira linha malformada.
+    pub(crate) fn next_sentence(&mut self) -> Result<Option<OwnedAnnotatedSentence>, CorpusError> {
+        match self.format {
+            CorpusFormat::Conll => self.next_conll(),
+            CorpusFormat::Jsonl => self.next_jsonl(),
+        }
+    }
+
+    fn next_conll(&mut self) -> Result<Option<OwnedAnnotatedSentence>, CorpusError> {
+        let mut annotations: Vec<(String, String)> = Vec::new();
+        while self.read_line()? {
+            let columns: Vec<&str> = self.buffer.split_whitespace().collect();
+            match columns.as_slice() {
+                [] if annotations.is_empty() => continue,
+                [] => break,
+                ["-DOCSTART-", ..] => continue,
+                [word, .., tag] if is_bio_tag(tag) => annotations.push((word.to_string(), tag.to_string())),
+                _ if self.buffer.starts_with('#') => continue,
+                _ => {
+                    let error = CorpusError::Malformed {
+                        line: self.line,
+                        message: format!("esperado `palavra tag`, encontrado {:?}", self.buffer),
+                    };
+                    // Descarta o resto da sentença, para não emendá-lo na próxima
+                    while self.read_line()? && !self.buffer.trim().is_empty() {}
+                    return Err(error);
+                }
+            }
+        }
+        if annotations.is_empty() {
+            return Ok(None);
+        }
+        let text = annotations.iter().map(|(w, _)| w.as_str()).collect::<Vec<_>>().join(" ");
+        Ok(Some(OwnedAnnotatedSentence { text, domain: "conll".to_string(), annotations }))
+    }
+
+    fn next_jsonl(&mut self) -> Result<Option<OwnedAnnotatedSentence>, CorpusError> {
+        while self.read_line()? {
+            if self.buffer.trim().is_empty() {
+                continue;
+            }
+            let mut sentence: OwnedAnnotatedSentence =
+                serde_json::from_str(&self.buffer).map_err(|e| CorpusError::Malformed {
+                    line: self.line,
+                    message: e.to_string(),
+                })?;
+            if let Some((_, tag)) = sentence.annotations.iter().find(|(_, t)| !is_bio_tag(t)) {
+                return Err(CorpusError::Malformed {
+                    line: self.line,
+                    message: format!("tag BIO inválida {tag:?}"),
+                });
+            }
+            if sentence.text.is_empty() {
+                sentence.text = sentence.annotations.iter().map(|(w, _)| w.as_str()).collect::<Vec<_>>().join(" ");
+            }
+            return Ok(Some(sentence));
+        }
+        Ok(None)
+    }
+}
+
+impl Clone for CorpusReader {
+    /// O clone lê do mesmo ponto que o original, com seu próprio descritor de arquivo.
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            format: self.format,
+            reader: None,
+            offset: self.offset,
+            line: self.line,
+            skipped: self.skipped,
+            buffer: String::new(),
+        }
+    }
+}
+
+impl Iterator for CorpusReader {
+    type Item = OwnedAnnotatedSentence;
+
+    /// Próxima sentença. Sentenças malformadas são puladas; um erro de E/S encerra a leitura.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_sentence() {
+                Ok(sentence) => return sentence,
+                Err(CorpusError::Malformed { .. }) => self.skipped += 1,
+                Err(CorpusError::Io(_)) => return None,
+            }
+        }
+    }
+}
+
+/// `O`, `B-XXX` ou `I-XXX`.
+fn is_bio_tag(tag: &str) -> bool {
+    tag == "O" || tag.strip_prefix("B-").or_else(|| tag.strip_prefix("I-")).is_some_and(|c| !c.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_corpus(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ner_corpus_{}_{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_conll_and_jsonl_streaming() {
+        let conll = temp_corpus(
+            "stream.conll",
+            "-DOCSTART- -X- O O\n\n# sent_id = 1\nLula NNP B-PER\nvisitou VB O\n\n\nBanco B-ORG\ndo I-ORG\nBrasil I-ORG\nsolto\n\nRio B-LOC\n",
+        );
+        let mut reader = CorpusReader::open(&conll, CorpusFormat::Conll).unwrap();
+        assert!(matches!(reader.validate(), Err(CorpusError::Malformed { line: 11, .. })));
+
+        let first = reader.next().unwrap();
+        assert_eq!(first.text, "Lula visitou");
+        assert_eq!(first.annotations[0], ("Lula".to_string(), "B-PER".to_string()));
+
+        // O clone recomeça do ponto atual, e não do início do arquivo
+        let rest: Vec<String> = reader.clone().map(|s| s.text).collect();
+        assert_eq!(rest, vec!["Rio"]);
+        assert_eq!(reader.by_ref().count(), 1);
+        assert_eq!(reader.skipped(), 1);
+
+        let jsonl = temp_corpus(
+            "stream.jsonl",
+            "{\"text\": \"Ana mora no Recife\", \"annotations\": [[\"Ana\", \"B-PER\"], [\"mora\", \"O\"], [\"no\", \"O\"], [\"Recife\", \"B-LOC\"]]}\n\n{\"annotations\": [[\"Petrobras\", \"B-ORG\"]]}\n",
+        );
+        assert_eq!(CorpusFormat::from_path(&jsonl), Some(CorpusFormat::Jsonl));
+        let reader = CorpusReader::open(&jsonl, CorpusFormat::Jsonl).unwrap();
+        assert_eq!(reader.validate().unwrap(), 2);
+        let sentences: Vec<OwnedAnnotatedSentence> = reader.collect();
+        assert_eq!(sentences[0].annotations.len(), 4);
+        assert_eq!(sentences[1].text, "Petrobras");
+
+        std::fs::remove_file(conll).unwrap();
+        std::fs::remove_file(jsonl).unwrap();
+        assert!(CorpusReader::open(std::env::temp_dir().join("ner_corpus_inexistente.conll"), CorpusFormat::Conll).is_err());
+    }
+}
@@ -21,13 +21,48 @@
 //! ```
 //!
 //! A probabilidade é a softmax dos scores (via normalização Z).
+//!
+//! ## Treinamento
+//!
+//! Os pesos do modelo padrão são heurísticos (ver `model.rs`). [`CrfModel::train`]
+//! os estima a partir de um corpus anotado por **máxima verossimilhança condicional**:
+//!
+//! ```text
+//! log P(y|x) = score(y, x) - log Z(x)
+//! ∂/∂w_k     = contagem observada de f_k - contagem esperada de f_k sob P(·|x)
+//! ```
+//!
+//! As contagens esperadas vêm das marginais calculadas pelo algoritmo
+//! *forward-backward*; a otimização é SGD (uma atualização por sentença) com
+//! regularização L2.
+//!
+//! ```rust
+//! use ner_core::corpus::get_corpus;
+//! use ner_core::crf::{CrfModel, TrainOptions};
+//!
+//! let mut model = CrfModel::new();
+//! let losses = model.train(&get_corpus()[..20], TrainOptions::default().with_epochs(3));
+//! assert!(model.is_trained());
+//! assert!(losses[2] < losses[0]);
+//! ```
 
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::features::FeatureVector;
+use crate::categories::CategorySet;
+use crate::corpus::{AnnotatedSentence, OwnedAnnotatedSentence};
+use crate::features::{FeatureExtractor, FeatureVector, Gazetteers, SharedExtractor};
 use crate::tagger::Tag;
+use crate::tokenizer::Token;
+use crate::training::TrainingSchedule;
+
+/// Identificador de uma feature: o próprio nome emitido pelo extrator (ex: `"word=brasil"`).
+pub type FeatureId = String;
+
+/// Pesos de uma feature para cada tag, indexados por [`CategorySet::tag_index`] do
+/// conjunto de categorias do modelo (para as tags embutidas, o mesmo que [`Tag::index`]).
+pub type TagWeights = Vec<f64>;
 
 /// Modelo CRF (Conditional Random Field) Linear-Chain.
 ///
@@ -41,18 +76,32 @@ use crate::tagger::Tag;
 /// # Estrutura
 /// - **Pesos de Emissão**: Associam features do texto (ex: "é maiúscula") a tags específicas.
 /// - **Pesos de Transição**: Associam pares de tags consecutivas ($y_{i-1} \to y_i$).
+///
+/// # Categorias
+/// As tags pontuadas são as de [`Self::categories`]: as embutidas e, se declaradas com
+/// [`Self::with_categories`], as personalizadas (ex: `B-LEGISLACAO`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrfModel {
-    /// Mapa de pesos de emissão.
-    /// A chave é uma string composta: `"feature_name|tag_label"`.
-    /// O valor é o peso $w_k$ aprendido (ou definido heuristicamente).
-    pub emission_weights: HashMap<String, f64>,
+    /// Mapa de pesos de emissão: para cada feature, uma linha com o peso $w_k$ de
+    /// cada tag (aprendido ou definido heuristicamente).
+    ///
+    /// Pontuar um token contra todas as tags custa uma busca por feature ativa mais
+    /// uma soma de vetores (ver [`Self::emission_row`]).
+    pub emission_weights: HashMap<FeatureId, TagWeights>,
     
     /// Matriz de transição $T[u][v]$ onde $u$ é a tag anterior e $v$ a atual.
     ///
     /// O valor representa a "afinidade" entre as duas tags.
     /// Ex: `Score(B-PER -> I-PER)` deve ser alto, enquanto `Score(B-PER -> I-ORG)` deve ser baixo.
     pub transition_weights: Vec<Vec<f64>>,
+
+    /// Categorias do modelo; definem as tags e a ordem dos pesos.
+    #[serde(default)]
+    categories: CategorySet,
+
+    /// Extrator das features que o pipeline entrega ao CRF (não serializado).
+    #[serde(skip)]
+    extractor: SharedExtractor,
 }
 
 impl CrfModel {
@@ -62,9 +111,73 @@ impl CrfModel {
         Self {
             emission_weights: HashMap::new(),
             transition_weights: vec![vec![0.0f64; n]; n],
+            categories: CategorySet::new(),
+            extractor: SharedExtractor::default(),
         }
     }
 
+    /// Troca o conjunto de categorias (ver [`crate::categories`]).
+    ///
+    /// Os pesos das tags presentes nos dois conjuntos são mantidos; as tags novas começam
+    /// com peso zero e precisam de treino ([`Self::train`]) ou de [`Self::set_emission`].
+    ///
+    /// ```rust
+    /// use ner_core::categories::CategorySet;
+    /// use ner_core::crf::CrfModel;
+    ///
+    /// let set = CategorySet::from_names(["LEGISLACAO"]).unwrap();
+    /// let model = CrfModel::new().with_categories(set);
+    /// assert_eq!(model.tags().len(), 15);
+    /// assert_eq!(model.transition_weights.len(), 15);
+    /// ```
+    pub fn with_categories(mut self, categories: CategorySet) -> Self {
+        self.set_categories(categories);
+        self
+    }
+
+    pub fn set_categories(&mut self, categories: CategorySet) {
+        // Para cada tag nova, o índice que ela tinha no conjunto antigo
+        let old_index: Vec<Option<usize>> = categories.tags().iter().map(|tag| self.categories.tag_index(tag)).collect();
+        let remap = |row: &[f64]| -> Vec<f64> { old_index.iter().map(|old| old.map_or(0.0, |i| row[i])).collect() };
+        for row in self.emission_weights.values_mut() {
+            *row = remap(row);
+        }
+        self.transition_weights = old_index
+            .iter()
+            .map(|old| old.map_or_else(|| vec![0.0; old_index.len()], |i| remap(&self.transition_weights[i])))
+            .collect();
+        self.categories = categories;
+    }
+
+    pub fn categories(&self) -> &CategorySet {
+        &self.categories
+    }
+
+    /// Tags pontuadas pelo modelo, na ordem dos pesos.
+    pub fn tags(&self) -> Vec<Tag> {
+        self.categories.tags()
+    }
+
+    /// Troca o extrator de features (ver [`FeatureExtractor`]). Os pesos precisam
+    /// conhecer os nomes das features que ele emite.
+    pub fn with_extractor(mut self, extractor: impl FeatureExtractor + 'static) -> Self {
+        self.set_extractor(extractor);
+        self
+    }
+
+    pub fn set_extractor(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.extractor = SharedExtractor::new(extractor);
+    }
+
+    pub fn extractor(&self) -> &dyn FeatureExtractor {
+        &*self.extractor
+    }
+
+    /// Indica se o modelo possui pesos de emissão (carregados ou definidos).
+    pub fn is_trained(&self) -> bool {
+        !self.emission_weights.is_empty()
+    }
+
     /// Calcula o **Score de Emissão** para uma tag em um determinado token.
     ///
     /// # O que é Score de Emissão?
@@ -85,20 +198,49 @@ impl CrfModel {
     /// - `in_location_gazetteer`
     ///
     /// O score para `B-LOC` somará os pesos de todas essas features associadas a `B-LOC`.
+    ///
+    /// Tags de categorias fora de [`Self::categories`] pontuam 0.
     pub fn emission_score(&self, features: &FeatureVector, tag: &Tag) -> f64 {
-        let tag_label = tag.label();
+        let Some(t) = self.categories.tag_index(tag) else {
+            return 0.0;
+        };
         features
             .features
             .iter()
-            .map(|(feat_name, feat_val)| {
-                // Concatena nome da feature e label da tag para buscar no mapa plano
-                let key = format!("{feat_name}|{tag_label}");
-                let weight = self.emission_weights.get(&key).unwrap_or(&0.0);
-                feat_val * weight
-            })
+            .filter_map(|(feat_name, feat_val)| self.emission_weights.get(feat_name).map(|row| feat_val * row[t]))
             .sum()
     }
 
+    /// Scores de emissão de todas as tags de um token, na ordem de [`Self::tags`].
+    ///
+    /// Uma busca no mapa por feature ativa; preferível a chamar [`Self::emission_score`]
+    /// para cada tag.
+    pub fn emission_row(&self, features: &FeatureVector) -> TagWeights {
+        let mut row = vec![0.0; self.categories.tag_count()];
+        self.emission_row_into(features, &mut row);
+        row
+    }
+
+    /// Como [`Self::emission_row`], escrevendo em `row` (sem alocar).
+    pub fn emission_row_into(&self, features: &FeatureVector, row: &mut [f64]) {
+        row.fill(0.0);
+        for (feat_name, feat_val) in &features.features {
+            if let Some(weights) = self.emission_weights.get(feat_name) {
+                for (score, w) in row.iter_mut().zip(weights) {
+                    *score += feat_val * w;
+                }
+            }
+        }
+    }
+
+    /// Peso de emissão de `feature` para `tag` (0 se não definido).
+    pub fn emission_weight(&self, feature: &str, tag: &Tag) -> f64 {
+        match (self.emission_weights.get(feature), self.categories.tag_index(tag)) {
+            (Some(row), Some(t)) => row[t],
+            _ => 0.0,
+        }
+    }
+
     /// Calcula o **Score de Transição** entre duas tags consecutivas.
     ///
     /// # O que é Score de Transição?
@@ -112,34 +254,245 @@ impl CrfModel {
     /// - Uma entidade `I-PER` (Inside Person) só deve vir depois de `B-PER` ou outro `I-PER`.
     /// - Não faz sentido `I-ORG` vir logo depois de `B-LOC`.
     pub fn transition_score(&self, prev: &Tag, next: &Tag) -> f64 {
-        self.transition_weights[prev.index()][next.index()]
+        match (self.categories.tag_index(prev), self.categories.tag_index(next)) {
+            (Some(u), Some(v)) => self.transition_weights[u][v],
+            _ => 0.0,
+        }
     }
 
     /// Pontua todas as tags possíveis para um token.
     ///
     /// Retorna um vetor de pares `(Tag, Score)` para uso no Viterbi.
     pub fn score_all_tags(&self, features: &FeatureVector) -> Vec<(Tag, f64)> {
-        Tag::all()
-            .into_iter()
-            .map(|tag| {
-                let score = self.emission_score(features, &tag);
-                (tag, score)
-            })
-            .collect()
+        self.tags().into_iter().zip(self.emission_row(features)).collect()
     }
 
     /// Define manualmente um peso de emissão (útil para construção heurística).
+    ///
+    /// Tags de categorias fora de [`Self::categories`] são ignoradas.
     pub fn set_emission(&mut self, feature: &str, tag: &Tag, weight: f64) {
-        let key = format!("{feature}|{}", tag.label());
-        self.emission_weights.insert(key, weight);
+        let Some(t) = self.categories.tag_index(tag) else {
+            return;
+        };
+        let n_tags = self.categories.tag_count();
+        let row = self.emission_weights.entry(feature.to_string()).or_insert_with(|| vec![0.0; n_tags]);
+        row[t] = weight;
     }
 
-    /// Define manualmente um peso de transição.
+    /// Define manualmente um peso de transição (ignorado para tags fora de [`Self::categories`]).
     pub fn set_transition(&mut self, from: &Tag, to: &Tag, weight: f64) {
-        self.transition_weights[from.index()][to.index()] = weight;
+        if let (Some(u), Some(v)) = (self.categories.tag_index(from), self.categories.tag_index(to)) {
+            self.transition_weights[u][v] = weight;
+        }
+    }
+
+    /// Estima os pesos de emissão e transição a partir de `corpus` (máxima
+    /// verossimilhança condicional; ver o [módulo](self)).
+    ///
+    /// Os pesos atuais são descartados: o treino parte de zero. As features vêm do
+    /// extrator do modelo ([`Self::extractor`]) e dos gazetteers de `opts`; tags
+    /// desconhecidas no corpus, ou de categorias fora de [`Self::categories`], contam
+    /// como `O`. Para aprender categorias personalizadas, declare-as antes (ex:
+    /// `with_categories(CategorySet::from_labels(...))`).
+    ///
+    /// Retorna a log-verossimilhança negativa média por sentença de cada época
+    /// (medida antes da atualização de cada sentença), útil para acompanhar a convergência.
+    pub fn train(&mut self, corpus: &[AnnotatedSentence], opts: TrainOptions) -> Vec<f64> {
+        let n_tags = self.categories.tag_count();
+        let owned: Vec<OwnedAnnotatedSentence> = corpus.iter().map(OwnedAnnotatedSentence::from).collect();
+
+        // Features e tags de cada sentença, extraídas uma única vez
+        let sentences: Vec<(Vec<FeatureVector>, Vec<usize>)> = owned
+            .iter()
+            .map(|sentence| {
+                let tokens: Vec<Token> = sentence
+                    .annotations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (text, _))| Token::new(text.clone(), 0, 0, i))
+                    .collect();
+                let gold = sentence
+                    .annotations
+                    .iter()
+                    .map(|(_, tag)| Tag::from_label(tag).and_then(|t| self.categories.tag_index(&t)).unwrap_or(0))
+                    .collect();
+                (self.extractor.extract(&tokens, &opts.gazetteers), gold)
+            })
+            .collect();
+
+        let mut emission: HashMap<FeatureId, TagWeights> = HashMap::new();
+        let mut transition = vec![vec![0.0f64; n_tags]; n_tags];
+        let mut losses = Vec::with_capacity(opts.epochs);
+
+        for epoch in 0..opts.epochs {
+            let mut total_loss = 0.0;
+            let order = opts.schedule.epoch_order(&owned, epoch);
+            for &i in &order {
+                let (features, gold) = &sentences[i];
+                if features.is_empty() {
+                    continue;
+                }
+                total_loss += sgd_step(&mut emission, &mut transition, features, gold, &opts);
+            }
+            losses.push(if order.is_empty() { 0.0 } else { total_loss / order.len() as f64 });
+        }
+
+        self.emission_weights = emission;
+        self.transition_weights = transition;
+        losses
     }
 }
 
+/// Hiperparâmetros de [`CrfModel::train`].
+#[derive(Debug, Clone)]
+pub struct TrainOptions {
+    /// Passadas completas pelo corpus.
+    pub epochs: usize,
+    /// Passo do SGD.
+    pub learning_rate: f64,
+    /// Força da regularização L2 (decaimento dos pesos a cada atualização).
+    pub l2: f64,
+    /// Ordem das sentenças em cada época (ver [`crate::training`]).
+    pub schedule: TrainingSchedule,
+    /// Gazetteers usados na extração de features. Para treinar um CRF que será usado
+    /// pelo pipeline, passe os mesmos do modelo ([`crate::model::NerModel::gazetteers`]).
+    pub gazetteers: Gazetteers,
+}
+
+impl Default for TrainOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 10,
+            learning_rate: 0.1,
+            l2: 1e-4,
+            schedule: TrainingSchedule::new(),
+            gazetteers: Gazetteers::new(),
+        }
+    }
+}
+
+impl TrainOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_epochs(mut self, epochs: usize) -> Self {
+        self.epochs = epochs;
+        self
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    pub fn with_l2(mut self, l2: f64) -> Self {
+        self.l2 = l2;
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: TrainingSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn with_gazetteers(mut self, gazetteers: Gazetteers) -> Self {
+        self.gazetteers = gazetteers;
+        self
+    }
+}
+
+/// `log Σ exp(x)` sem overflow.
+pub(crate) fn log_sum_exp(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+/// Uma atualização de SGD com a sentença (`features`, `gold`); retorna `-log P(gold|x)`
+/// com os pesos de antes da atualização.
+fn sgd_step(
+    emission: &mut HashMap<FeatureId, TagWeights>,
+    transition: &mut [Vec<f64>],
+    features: &[FeatureVector],
+    gold: &[usize],
+    opts: &TrainOptions,
+) -> f64 {
+    let n_tags = transition.len();
+    let n = features.len();
+
+    // Scores de emissão E[i][t]
+    let scores: Vec<TagWeights> = features
+        .iter()
+        .map(|fv| {
+            let mut row = vec![0.0; n_tags];
+            for (name, value) in &fv.features {
+                if let Some(weights) = emission.get(name) {
+                    for (score, w) in row.iter_mut().zip(weights) {
+                        *score += value * w;
+                    }
+                }
+            }
+            row
+        })
+        .collect();
+
+    // Forward: alpha[i][t] = log-soma dos scores dos prefixos que terminam em t
+    let mut alpha = vec![vec![0.0; n_tags]; n];
+    alpha[0].copy_from_slice(&scores[0]);
+    for i in 1..n {
+        for t in 0..n_tags {
+            alpha[i][t] = scores[i][t] + log_sum_exp((0..n_tags).map(|u| alpha[i - 1][u] + transition[u][t]));
+        }
+    }
+    // Backward: beta[i][u] = log-soma dos scores dos sufixos que partem de u
+    let mut beta = vec![vec![0.0; n_tags]; n];
+    for i in (0..n - 1).rev() {
+        for u in 0..n_tags {
+            beta[i][u] = log_sum_exp((0..n_tags).map(|t| transition[u][t] + scores[i + 1][t] + beta[i + 1][t]));
+        }
+    }
+    let log_z = log_sum_exp(alpha[n - 1].iter().copied());
+
+    let gold_score: f64 = (0..n).map(|i| scores[i][gold[i]]).sum::<f64>()
+        + (1..n).map(|i| transition[gold[i - 1]][gold[i]]).sum::<f64>();
+    let loss = log_z - gold_score;
+
+    let rate = opts.learning_rate;
+    let decay = 1.0 - rate * opts.l2;
+
+    // Transições: observado - esperado (marginais das arestas)
+    let mut gradient = vec![vec![0.0; n_tags]; n_tags];
+    for i in 1..n {
+        gradient[gold[i - 1]][gold[i]] += 1.0;
+        for (u, row) in gradient.iter_mut().enumerate() {
+            for (t, g) in row.iter_mut().enumerate() {
+                *g -= (alpha[i - 1][u] + transition[u][t] + scores[i][t] + beta[i][t] - log_z).exp();
+            }
+        }
+    }
+    for (weights, grads) in transition.iter_mut().zip(&gradient) {
+        for (w, g) in weights.iter_mut().zip(grads) {
+            *w = *w * decay + rate * g;
+        }
+    }
+
+    // Emissões: para cada feature ativa, (1[tag = ouro] - P(tag)) × valor
+    for (i, fv) in features.iter().enumerate() {
+        let marginals: Vec<f64> = (0..n_tags).map(|t| (alpha[i][t] + beta[i][t] - log_z).exp()).collect();
+        for (name, value) in &fv.features {
+            let weights = emission.entry(name.clone()).or_insert_with(|| vec![0.0; n_tags]);
+            for (t, w) in weights.iter_mut().enumerate() {
+                let observed = if t == gold[i] { 1.0 } else { 0.0 };
+                *w = *w * decay + rate * value * (observed - marginals[t]);
+            }
+        }
+    }
+    loss
+}
+
 impl Default for CrfModel {
     fn default() -> Self {
         Self::new()
@@ -167,11 +520,7 @@ pub fn compute_emission_scores(
     model: &CrfModel,
     feature_vectors: &[FeatureVector],
 ) -> Vec<Vec<f64>> {
-    let tags = Tag::all();
-    feature_vectors
-        .iter()
-        .map(|fv| tags.iter().map(|tag| model.emission_score(fv, tag)).collect())
-        .collect()
+    feature_vectors.iter().map(|fv| model.emission_row(fv)).collect()
 }
 
 #[cfg(test)]
@@ -192,6 +541,34 @@ mod tests {
         assert!((score - 2.5).abs() < 1e-9);
     }
 
+    #[test]
+    fn test_emission_row_matches_per_tag_scores() {
+        let mut model = CrfModel::new();
+        model.set_emission("is_capitalized", &Tag::Begin(EntityCategory::Per), 2.5);
+        model.set_emission("is_capitalized", &Tag::Outside, -1.0);
+        model.set_emission("in_loc_gazetteer", &Tag::Begin(EntityCategory::Loc), 3.0);
+        model.set_emission("is_capitalized", &Tag::Outside, -2.0);
+        assert_eq!(model.emission_weights.len(), 2);
+        assert_eq!(model.emission_weight("is_capitalized", &Tag::Outside), -2.0);
+        assert_eq!(model.emission_weight("desconhecida", &Tag::Outside), 0.0);
+
+        let mut fv = FeatureVector::new(0);
+        fv.features.insert("is_capitalized".to_string(), 1.0);
+        fv.features.insert("in_loc_gazetteer".to_string(), 0.5);
+        fv.features.insert("desconhecida".to_string(), 1.0);
+
+        let row = model.emission_row(&fv);
+        for tag in Tag::all() {
+            assert!((row[tag.index()] - model.emission_score(&fv, &tag)).abs() < 1e-12);
+        }
+        assert!((row[Tag::Begin(EntityCategory::Loc).index()] - 1.5).abs() < 1e-12);
+        assert!((row[Tag::Outside.index()] + 2.0).abs() < 1e-12);
+
+        let json = serde_json::to_string(&model).unwrap();
+        let restored: CrfModel = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.emission_weights, model.emission_weights);
+    }
+
     #[test]
     fn test_transition_score() {
         let mut model = CrfModel::new();
@@ -203,4 +580,81 @@ mod tests {
         // Transição default é 0
         assert!((model.transition_score(&Tag::Outside, &i_per)).abs() < 1e-9);
     }
+
+    #[test]
+    fn test_train_fits_corpus() {
+        use crate::corpus::get_corpus;
+        use crate::viterbi::viterbi_decode;
+
+        let corpus = &get_corpus()[..30];
+        let mut model = CrfModel::new();
+        model.set_emission("heuristica", &Tag::Outside, 5.0);
+        let losses = model.train(corpus, TrainOptions::new().with_epochs(8).with_schedule(TrainingSchedule::new().with_shuffle(3)));
+
+        assert_eq!(losses.len(), 8);
+        assert!(losses.windows(2).all(|w| w[1] < w[0]), "{losses:?}");
+        // O treino parte de zero: pesos anteriores somem
+        assert!(!model.emission_weights.contains_key("heuristica"));
+        // B-PER → I-PER é aprendida como mais provável que O → I-PER
+        let (b_per, i_per) = (Tag::Begin(EntityCategory::Per), Tag::Inside(EntityCategory::Per));
+        assert!(model.transition_score(&b_per, &i_per) > model.transition_score(&Tag::Outside, &i_per));
+
+        // O modelo treinado reproduz as tags do próprio corpus
+        let (mut correct, mut total) = (0, 0);
+        for sentence in corpus {
+            let tokens: Vec<Token> = sentence
+                .annotations
+                .iter()
+                .enumerate()
+                .map(|(i, (text, _))| Token::new(text.to_string(), 0, 0, i))
+                .collect();
+            let features = model.extractor().extract(&tokens, &Gazetteers::new());
+            let decoded = viterbi_decode(&model, &features).best_sequence;
+            for (tag, (_, gold)) in decoded.iter().zip(sentence.annotations) {
+                correct += usize::from(tag.label() == *gold);
+                total += 1;
+            }
+        }
+        assert!(correct as f64 / total as f64 > 0.95, "{correct}/{total}");
+    }
+    #[test]
+    fn test_train_with_custom_categories() {
+        use crate::categories::CategorySet;
+        use crate::viterbi::viterbi_decode;
+
+        let corpus = [
+            AnnotatedSentence {
+                text: "a Lei Maria da Penha protege mulheres",
+                domain: "juridico",
+                annotations: &[("a", "O"), ("Lei", "B-LEI"), ("Maria", "I-LEI"), ("da", "I-LEI"), ("Penha", "I-LEI"), ("protege", "O"), ("mulheres", "O")],
+            },
+            AnnotatedSentence {
+                text: "Lula citou a Lei Rouanet",
+                domain: "juridico",
+                annotations: &[("Lula", "B-PER"), ("citou", "O"), ("a", "O"), ("Lei", "B-LEI"), ("Rouanet", "I-LEI")],
+            },
+        ];
+        let labels = corpus.iter().flat_map(|s| s.annotations.iter().map(|(_, tag)| *tag));
+        let categories = CategorySet::from_labels(labels).unwrap();
+        let mut model = CrfModel::new().with_categories(categories);
+        assert_eq!(model.tags().len(), Tag::COUNT + 2);
+        model.train(&corpus, TrainOptions::new().with_epochs(10));
+
+        let lei = Tag::from_label("B-LEI").unwrap();
+        assert!(lei.category().is_some_and(|c| c.is_custom()));
+        let bytes = bincode::serialize(&model).unwrap();
+        let restored: CrfModel = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored.categories(), model.categories());
+        assert_eq!(restored.emission_weights, model.emission_weights);
+
+        let tokens: Vec<Token> = ["a", "Lei", "Rouanet", "protege"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Token::new(text.to_string(), 0, 0, i))
+            .collect();
+        let features = restored.extractor().extract(&tokens, &Gazetteers::new());
+        let decoded = viterbi_decode(&restored, &features).best_sequence;
+        assert_eq!(decoded[1], lei);
+        assert_eq!(Tag::from_label("I-LEI").as_ref(), decoded.get(2));
+    }
 }
@@ -0,0 +1,196 @@
+//! # Comparação Ouro × Previsto
+//!
+//! Métricas agregadas (precisão, revocação) dizem **quanto** o modelo erra; para saber
+//! **como** ele erra é preciso olhar trecho a trecho. [`diff_entities`] alinha as
+//! entidades anotadas à mão (o "ouro") com as previstas e classifica cada par:
+//!
+//! | Tipo             | Ouro | Previsto | Situação                                        |
+//! |------------------|------|----------|-------------------------------------------------|
+//! | `Correct`        | sim  | sim      | mesmas fronteiras e mesma categoria             |
+//! | `WrongLabel`     | sim  | sim      | mesmas fronteiras, categoria diferente          |
+//! | `WrongBoundary`  | sim  | sim      | trechos se sobrepõem, fronteiras diferentes     |
+//! | `Missed`         | sim  | não      | nenhuma previsão toca o trecho (falso negativo) |
+//! | `Spurious`       | não  | sim      | previsão sem trecho ouro (falso positivo)       |
+//!
+//! Os registros vêm na ordem do texto, prontos para uma visualização lado a lado.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::diff::{diff_entities, DiffKind, GoldEntity};
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let text = "A Petrobras anunciou lucro no Brasil.";
+//! let gold = vec![
+//!     GoldEntity::find(text, "Petrobras", EntityCategory::Org).unwrap(),
+//!     GoldEntity::find(text, "Brasil", EntityCategory::Loc).unwrap(),
+//! ];
+//! let pipeline = NerPipeline::new();
+//! let (_, predicted) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!
+//! let diff = diff_entities(&gold, &predicted);
+//! assert!(diff.iter().all(|d| d.kind == DiffKind::Correct));
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::tagger::{EntityCategory, EntitySpan};
+
+/// Uma entidade anotada à mão. Offsets de byte, `end` exclusivo (como em [`EntitySpan`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldEntity {
+    pub start: usize,
+    pub end: usize,
+    pub category: EntityCategory,
+}
+
+impl GoldEntity {
+    pub fn new(start: usize, end: usize, category: EntityCategory) -> Self {
+        Self { start, end, category }
+    }
+
+    /// Anotação da primeira ocorrência de `needle` em `text`.
+    pub fn find(text: &str, needle: &str, category: EntityCategory) -> Option<Self> {
+        text.find(needle).map(|start| Self::new(start, start + needle.len(), category))
+    }
+
+    fn overlap(&self, span: &EntitySpan) -> usize {
+        self.end.min(span.end).saturating_sub(self.start.max(span.start))
+    }
+}
+
+/// Classificação de um registro do diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Correct,
+    WrongLabel,
+    WrongBoundary,
+    Missed,
+    Spurious,
+}
+
+/// Um registro alinhado: o trecho ouro, o previsto, ou ambos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpanDiff {
+    pub kind: DiffKind,
+    pub gold: Option<GoldEntity>,
+    pub predicted: Option<EntitySpan>,
+}
+
+impl SpanDiff {
+    /// Início do registro no texto (o menor entre ouro e previsto).
+    pub fn start(&self) -> usize {
+        let gold = self.gold.as_ref().map(|g| g.start);
+        let predicted = self.predicted.as_ref().map(|p| p.start);
+        gold.into_iter().chain(predicted).min().unwrap_or(0)
+    }
+}
+
+/// Alinha `gold` com `predicted` e classifica cada trecho (ver a tabela do módulo).
+///
+/// Cada trecho ouro é pareado com a previsão de mesmas fronteiras, se houver; senão,
+/// com a previsão ainda livre de maior sobreposição. Uma previsão é pareada no máximo
+/// uma vez; as que sobram são `Spurious`.
+pub fn diff_entities(gold: &[GoldEntity], predicted: &[EntitySpan]) -> Vec<SpanDiff> {
+    let mut used = vec![false; predicted.len()];
+    let mut records = Vec::with_capacity(gold.len() + predicted.len());
+
+    for g in gold {
+        let exact = predicted
+            .iter()
+            .enumerate()
+            .find(|(i, p)| !used[*i] && p.start == g.start && p.end == g.end)
+            .map(|(i, _)| i);
+        let best = exact.or_else(|| {
+            predicted
+                .iter()
+                .enumerate()
+                .filter(|(i, p)| !used[*i] && g.overlap(p) > 0)
+                .max_by_key(|(i, p)| (g.overlap(p), std::cmp::Reverse(*i)))
+                .map(|(i, _)| i)
+        });
+
+        let record = match best {
+            Some(i) => {
+                used[i] = true;
+                let p = &predicted[i];
+                let kind = if p.start != g.start || p.end != g.end {
+                    DiffKind::WrongBoundary
+                } else if p.category != g.category {
+                    DiffKind::WrongLabel
+                } else {
+                    DiffKind::Correct
+                };
+                SpanDiff { kind, gold: Some(g.clone()), predicted: Some(p.clone()) }
+            }
+            None => SpanDiff { kind: DiffKind::Missed, gold: Some(g.clone()), predicted: None },
+        };
+        records.push(record);
+    }
+
+    records.extend(
+        predicted
+            .iter()
+            .zip(&used)
+            .filter(|(_, &u)| !u)
+            .map(|(p, _)| SpanDiff { kind: DiffKind::Spurious, gold: None, predicted: Some(p.clone()) }),
+    );
+    records.sort_by_key(SpanDiff::start);
+    records
+}
+
+/// Quantos registros de cada tipo (para o cabeçalho da visualização).
+pub fn count_kinds(records: &[SpanDiff]) -> Vec<(DiffKind, usize)> {
+    [DiffKind::Correct, DiffKind::WrongLabel, DiffKind::WrongBoundary, DiffKind::Missed, DiffKind::Spurious]
+        .into_iter()
+        .map(|kind| (kind, records.iter().filter(|r| r.kind == kind).count()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EntityCategory::{Loc, Org, Per};
+
+    #[test]
+    fn test_diff_classifies_every_span() {
+        let text = "Lula e Dilma foram ao Banco do Brasil em Brasília ontem com Temer.";
+        let gold = vec![
+            GoldEntity::find(text, "Lula", Per).unwrap(),
+            GoldEntity::find(text, "Dilma", Per).unwrap(),
+            GoldEntity::find(text, "Banco do Brasil", Org).unwrap(),
+            GoldEntity::find(text, "Brasília", Loc).unwrap(),
+            GoldEntity::find(text, "Temer", Per).unwrap(),
+        ];
+        let predictions = vec![
+            EntitySpan::test_at(text, "Lula", Per),
+            EntitySpan::test_at(text, "Dilma", Loc),
+            EntitySpan::test_at(text, "Brasil", Loc),
+            EntitySpan::test_at(text, "ontem", Per),
+            EntitySpan::test_at(text, "Temer", Per),
+        ];
+
+        let diff = diff_entities(&gold, &predictions);
+        let kinds: Vec<DiffKind> = diff.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffKind::Correct,
+                DiffKind::WrongLabel,
+                DiffKind::WrongBoundary,
+                DiffKind::Missed,
+                DiffKind::Spurious,
+                DiffKind::Correct,
+            ]
+        );
+        assert_eq!(diff[2].predicted.as_ref().unwrap().text, "Brasil");
+        assert_eq!(diff[4].gold, None);
+
+        let counts = count_kinds(&diff);
+        assert_eq!(counts[0], (DiffKind::Correct, 2));
+        assert_eq!(counts[3], (DiffKind::Missed, 1));
+        assert_eq!(serde_json::to_value(DiffKind::WrongBoundary).unwrap(), "wrong_boundary");
+    }
+}
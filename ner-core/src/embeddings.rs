@@ -0,0 +1,565 @@
+//! # Embeddings de Palavras Pré-treinados
+//!
+//! Vetores densos aprendidos em corpora enormes (word2vec, fastText) colocam palavras
+//! de uso parecido perto umas das outras: "Recife" fica perto de "Salvador" e
+//! "Fortaleza", longe de "governou". Este módulo lê esses vetores e os leva ao NER
+//! por dois caminhos:
+//!
+//! - **Features** ([`EmbeddingFeatures`]): um [`FeatureExtractor`] que acrescenta às
+//!   features de um [`FeatureTemplate`] as dimensões do vetor binarizadas
+//!   (`emb12=+`, `emb40=-`) e, opcionalmente, a similaridade de cosseno com protótipos
+//!   de categoria (`proto=LOC`, com o cosseno como valor). Os modelos lineares não
+//!   lidam bem com valores contínuos sem escala; a binarização de Guo et al. (2014)
+//!   marca só os componentes acima da média dos positivos ou abaixo da média dos
+//!   negativos de cada dimensão.
+//! - **Expansão de gazetteers** ([`WordEmbeddings::expand_gazetteer`]): os vizinhos
+//!   mais próximos das entradas de uma categoria viram entradas novas dela.
+//!
+//! ## Formato
+//!
+//! [`WordEmbeddings::read_vec`] lê o formato texto do word2vec/fastText (`.vec`): uma
+//! linha opcional de cabeçalho (`quantidade dimensão`) e uma palavra por linha, seguida
+//! dos componentes. As palavras são indexadas pela chave dos gazetteers
+//! ([`GazetteerKey::normalize`]); quando duas grafias dão a mesma chave ("Brasil",
+//! "brasil"), vale a primeira do arquivo, que nos `.vec` usuais é a mais frequente.
+//! Os vetores são normalizados para norma 1, então o produto escalar é o cosseno.
+//!
+//! As buscas de vizinhos percorrem o vocabulário inteiro; para arquivos grandes, corte
+//! o vocabulário antes (as primeiras linhas de um `.vec` são as palavras mais
+//! frequentes) ou use `max_words` em [`WordEmbeddings::read_vec_limited`].
+//!
+//! ```rust
+//! use ner_core::embeddings::{EmbeddingFeatures, ExpansionConfig, WordEmbeddings};
+//! use ner_core::features::{FeatureExtractor, Gazetteers};
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let vec = "5 3\n\
+//!            recife 0.9 0.1 0.0\n\
+//!            salvador 0.85 0.2 0.05\n\
+//!            fortaleza 0.8 0.15 0.1\n\
+//!            governou 0.0 0.1 0.95\n\
+//!            visitou 0.05 0.0 0.9\n";
+//! let embeddings = WordEmbeddings::read_vec(vec.as_bytes()).unwrap();
+//! assert_eq!(embeddings.nearest("Recife", 1)[0].0, "salvador");
+//!
+//! // Expansão: "recife" puxa as outras capitais para o gazetteer de locais
+//! let mut gaz = Gazetteers::new();
+//! gaz.insert(EntityCategory::Loc, "Recife");
+//! let config = ExpansionConfig { neighbors: 2, min_similarity: 0.9, min_votes: 1 };
+//! let added = embeddings.expand_gazetteer(&mut gaz, EntityCategory::Loc, &config);
+//! assert_eq!(added, vec!["fortaleza", "salvador"]);
+//!
+//! // Features: dimensões binarizadas e similaridade com o protótipo de LOC
+//! let extractor = EmbeddingFeatures::new(embeddings).with_prototype("LOC", &["recife", "salvador"]);
+//! let features = extractor.extract(&tokenize("Lula visitou Fortaleza"), &gaz);
+//! assert!(features[2].features["proto=LOC"] > 0.9);
+//! assert!(features[1].features.keys().any(|k| k.starts_with("emb")));
+//! assert!(!features[0].features.keys().any(|k| k.starts_with("emb"))); // sem vetor
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use crate::features::{FeatureExtractor, FeatureTemplate, FeatureVector, GazetteerKey, GazetteerLookups, Gazetteers};
+use crate::tagger::EntityCategory;
+use crate::tokenizer::Token;
+
+/// Erro ao ler um arquivo de embeddings.
+#[derive(Debug)]
+pub enum EmbeddingError {
+    Io(io::Error),
+    /// Linha que não segue o formato (numerada a partir de 1).
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for EmbeddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "erro de leitura dos embeddings: {e}"),
+            Self::Malformed { line, message } => write!(f, "linha {line} dos embeddings malformada: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddingError {}
+
+impl From<io::Error> for EmbeddingError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Vetores de palavras pré-treinados, normalizados para norma 1.
+#[derive(Debug, Clone, Default)]
+pub struct WordEmbeddings {
+    dim: usize,
+    /// Chave de cada linha, na ordem do arquivo.
+    words: Vec<String>,
+    /// Vetores concatenados: o da palavra `i` ocupa `[i * dim, (i + 1) * dim)`.
+    vectors: Vec<f32>,
+    index: HashMap<String, usize>,
+    /// Limiares de binarização por dimensão: médias dos componentes positivos e negativos.
+    upper: Vec<f32>,
+    lower: Vec<f32>,
+}
+
+impl WordEmbeddings {
+    /// Lê o formato texto do word2vec/fastText (ver a documentação do módulo).
+    pub fn read_vec(reader: impl BufRead) -> Result<Self, EmbeddingError> {
+        Self::read_vec_limited(reader, usize::MAX)
+    }
+
+    /// Como [`WordEmbeddings::read_vec`], parando depois de `max_words` palavras.
+    pub fn read_vec_limited(reader: impl BufRead, max_words: usize) -> Result<Self, EmbeddingError> {
+        let mut embeddings = Self::default();
+        let mut dim: Option<usize> = None;
+
+        for (n, line) in reader.lines().enumerate() {
+            if embeddings.words.len() >= max_words {
+                break;
+            }
+            let line = line?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = |message: String| EmbeddingError::Malformed { line: n + 1, message };
+
+            if n == 0 {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if let [_, size] = fields[..] {
+                    if fields.iter().all(|f| f.parse::<usize>().is_ok()) {
+                        dim = size.parse().ok();
+                        continue;
+                    }
+                }
+            }
+
+            // Sem cabeçalho, a primeira linha define a dimensão. Os componentes são os
+            // últimos campos, então palavras com espaço no meio continuam legíveis.
+            let dim = *dim.get_or_insert_with(|| line.split_whitespace().count().saturating_sub(1));
+            if dim == 0 {
+                return Err(malformed("linha sem componentes".to_string()));
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() <= dim {
+                return Err(malformed(format!("esperados a palavra e {dim} componentes")));
+            }
+            let (word, components) = fields.split_at(fields.len() - dim);
+            let vector = components
+                .iter()
+                .map(|c| c.parse::<f32>().map_err(|_| malformed(format!("componente inválido '{c}'"))))
+                .collect::<Result<Vec<_>, _>>()?;
+            embeddings.push(&word.join(" "), vector);
+        }
+
+        embeddings.dim = dim.unwrap_or(0);
+        embeddings.compute_thresholds();
+        Ok(embeddings)
+    }
+
+    /// Lê um arquivo `.vec` do disco.
+    pub fn load_vec(path: impl AsRef<Path>) -> Result<Self, EmbeddingError> {
+        Self::read_vec(BufReader::new(File::open(path)?))
+    }
+
+    fn push(&mut self, word: &str, mut vector: Vec<f32>) {
+        let key = GazetteerKey::normalize(word);
+        if key.is_empty() || self.index.contains_key(&key) {
+            return;
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        self.index.insert(key.clone(), self.words.len());
+        self.words.push(key);
+        self.vectors.extend(vector);
+    }
+
+    fn compute_thresholds(&mut self) {
+        let mut sums = vec![(0.0f32, 0usize, 0.0f32, 0usize); self.dim];
+        for vector in self.vectors.chunks_exact(self.dim.max(1)) {
+            for (sum, &x) in sums.iter_mut().zip(vector) {
+                if x > 0.0 {
+                    sum.0 += x;
+                    sum.1 += 1;
+                } else if x < 0.0 {
+                    sum.2 += x;
+                    sum.3 += 1;
+                }
+            }
+        }
+        // Dimensão sem componentes de um sinal nunca dispara a feature desse sinal
+        self.upper = sums.iter().map(|s| if s.1 > 0 { s.0 / s.1 as f32 } else { f32::INFINITY }).collect();
+        self.lower = sums.iter().map(|s| if s.3 > 0 { s.2 / s.3 as f32 } else { f32::NEG_INFINITY }).collect();
+    }
+
+    /// Dimensão dos vetores.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Quantidade de palavras.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Vetor (normalizado) da palavra.
+    pub fn vector(&self, word: &str) -> Option<&[f32]> {
+        self.vector_for_key(&GazetteerKey::normalize(word))
+    }
+
+    /// Como [`WordEmbeddings::vector`], para uma chave já normalizada.
+    pub fn vector_for_key(&self, key: &str) -> Option<&[f32]> {
+        self.index.get(key).map(|&i| self.row(i))
+    }
+
+    fn row(&self, i: usize) -> &[f32] {
+        &self.vectors[i * self.dim..(i + 1) * self.dim]
+    }
+
+    /// Cosseno entre as duas palavras, se ambas tiverem vetor.
+    pub fn similarity(&self, a: &str, b: &str) -> Option<f32> {
+        Some(dot(self.vector(a)?, self.vector(b)?))
+    }
+
+    /// As `k` palavras mais próximas de `word` (sem ela mesma), do cosseno maior para o menor.
+    pub fn nearest(&self, word: &str, k: usize) -> Vec<(String, f32)> {
+        let key = GazetteerKey::normalize(word);
+        match self.vector_for_key(&key) {
+            Some(vector) => self.nearest_where(vector, k, |candidate| candidate != key),
+            None => Vec::new(),
+        }
+    }
+
+    /// As `k` palavras mais próximas de um vetor qualquer (ex: um centróide).
+    pub fn nearest_to(&self, vector: &[f32], k: usize) -> Vec<(String, f32)> {
+        self.nearest_where(vector, k, |_| true)
+    }
+
+    fn nearest_where(&self, vector: &[f32], k: usize, keep: impl Fn(&str) -> bool) -> Vec<(String, f32)> {
+        if vector.len() != self.dim || k == 0 {
+            return Vec::new();
+        }
+        let mut scored: Vec<(f32, usize)> = (0..self.words.len())
+            .filter(|&i| keep(&self.words[i]))
+            .map(|i| (dot(vector, self.row(i)), i))
+            .collect();
+        let by_score = |a: &(f32, usize), b: &(f32, usize)| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1));
+        if scored.len() > k {
+            scored.select_nth_unstable_by(k - 1, by_score);
+            scored.truncate(k);
+        }
+        scored.sort_by(by_score);
+        scored.into_iter().map(|(score, i)| (self.words[i].clone(), score)).collect()
+    }
+
+    /// Centróide normalizado dos vetores das palavras conhecidas (`None` se nenhuma for).
+    pub fn centroid<S: AsRef<str>>(&self, words: &[S]) -> Option<Vec<f32>> {
+        let mut sum = vec![0.0f32; self.dim];
+        let mut found = false;
+        for vector in words.iter().filter_map(|w| self.vector(w.as_ref())) {
+            sum.iter_mut().zip(vector).for_each(|(s, x)| *s += x);
+            found = true;
+        }
+        let norm = sum.iter().map(|x| x * x).sum::<f32>().sqrt();
+        (found && norm > 0.0).then(|| sum.into_iter().map(|x| x / norm).collect())
+    }
+
+    /// Palavras novas próximas das sementes (ver [`ExpansionConfig`]), com o maior cosseno
+    /// a alguma semente, do maior para o menor.
+    pub fn expand<S: AsRef<str> + Sync>(&self, seeds: &[S], config: &ExpansionConfig) -> Vec<(String, f32)> {
+        let seed_keys: Vec<String> = seeds.iter().map(|s| GazetteerKey::normalize(s.as_ref())).collect();
+        let neighbors: Vec<Vec<(String, f32)>> =
+            seed_keys.par_iter().map(|seed| self.nearest(seed, config.neighbors)).collect();
+
+        // Candidato → (votos, maior cosseno)
+        let mut candidates: HashMap<String, (usize, f32)> = HashMap::new();
+        for (word, score) in neighbors.into_iter().flatten() {
+            if score < config.min_similarity || seed_keys.contains(&word) {
+                continue;
+            }
+            let entry = candidates.entry(word).or_insert((0, score));
+            entry.0 += 1;
+            entry.1 = entry.1.max(score);
+        }
+        let mut expanded: Vec<(String, f32)> = candidates
+            .into_iter()
+            .filter(|(_, (votes, _))| *votes >= config.min_votes)
+            .map(|(word, (_, score))| (word, score))
+            .collect();
+        expanded.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        expanded
+    }
+
+    /// Expande o gazetteer da categoria com os vizinhos das suas entradas.
+    ///
+    /// Palavras sem letras e palavras já presentes em algum gazetteer (de qualquer
+    /// categoria) são descartadas: "paulo", de pessoas, não entra em locais por ser
+    /// vizinho de "são". Devolve as entradas acrescentadas, em ordem alfabética. Para
+    /// que o motor de regras também as conheça, repasse-as a
+    /// [`crate::model::NerModel::add_gazetteer_entry`] em vez de usar `gazetteers` à parte.
+    pub fn expand_gazetteer(
+        &self,
+        gazetteers: &mut Gazetteers,
+        category: EntityCategory,
+        config: &ExpansionConfig,
+    ) -> Vec<String> {
+        let seeds: Vec<&String> = gazetteers.keys(category).map(|keys| keys.iter().collect()).unwrap_or_default();
+        let mut added: Vec<String> = self
+            .expand(&seeds, config)
+            .into_iter()
+            .map(|(word, _)| word)
+            .filter(|word| word.chars().any(char::is_alphabetic) && gazetteers.hits(word).is_empty())
+            .collect();
+        added.sort();
+        for word in &added {
+            gazetteers.insert(category, word);
+        }
+        added
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Parâmetros de [`WordEmbeddings::expand`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpansionConfig {
+    /// Vizinhos consultados por semente.
+    pub neighbors: usize,
+    /// Cosseno mínimo entre o candidato e a semente.
+    pub min_similarity: f32,
+    /// Quantas sementes precisam ter o candidato entre os vizinhos. Acima de 1, uma
+    /// semente ambígua ("são", de "São Paulo") não traz vizinhos sozinha.
+    pub min_votes: usize,
+}
+
+impl Default for ExpansionConfig {
+    fn default() -> Self {
+        Self { neighbors: 10, min_similarity: 0.6, min_votes: 2 }
+    }
+}
+
+/// Extrator que acrescenta features de embeddings às de um [`FeatureTemplate`] (ver a
+/// documentação do módulo). Tokens sem vetor só recebem as features do template.
+#[derive(Clone)]
+pub struct EmbeddingFeatures {
+    template: FeatureTemplate,
+    embeddings: Arc<WordEmbeddings>,
+    dimensions: bool,
+    /// Nome e centróide de cada protótipo (`proto=<nome>`).
+    prototypes: Vec<(String, Vec<f32>)>,
+}
+
+impl EmbeddingFeatures {
+    /// Extrator com as dimensões binarizadas e sem protótipos.
+    pub fn new(embeddings: impl Into<Arc<WordEmbeddings>>) -> Self {
+        Self {
+            template: FeatureTemplate::default(),
+            embeddings: embeddings.into(),
+            dimensions: true,
+            prototypes: Vec::new(),
+        }
+    }
+
+    /// Troca o template das demais features.
+    pub fn with_template(mut self, template: FeatureTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Liga ou desliga as features `emb{d}=+`/`emb{d}=-`.
+    pub fn with_dimensions(mut self, enabled: bool) -> Self {
+        self.dimensions = enabled;
+        self
+    }
+
+    /// Acrescenta o protótipo `name` (centróide de `seeds`): cada token com vetor recebe
+    /// `proto=<name>` com o cosseno ao protótipo, se positivo. Sementes sem vetor são
+    /// ignoradas; se nenhuma tiver, o protótipo não é criado.
+    pub fn with_prototype<S: AsRef<str>>(mut self, name: &str, seeds: &[S]) -> Self {
+        if let Some(centroid) = self.embeddings.centroid(seeds) {
+            self.prototypes.push((name.to_string(), centroid));
+        }
+        self
+    }
+
+    pub fn embeddings(&self) -> &WordEmbeddings {
+        &self.embeddings
+    }
+
+    fn add_embeddings<'a>(&self, keys: impl Iterator<Item = &'a str>, features: &mut [FeatureVector]) {
+        let embeddings = &self.embeddings;
+        for (key, fv) in keys.zip(features) {
+            let Some(vector) = embeddings.vector_for_key(key) else { continue };
+            if self.dimensions {
+                for (d, &x) in vector.iter().enumerate() {
+                    if x >= embeddings.upper[d] {
+                        fv.insert(format!("emb{d}=+"), 1.0);
+                    } else if x <= embeddings.lower[d] {
+                        fv.insert(format!("emb{d}=-"), 1.0);
+                    }
+                }
+            }
+            for (name, prototype) in &self.prototypes {
+                let similarity = dot(vector, prototype);
+                if similarity > 0.0 {
+                    fv.insert(format!("proto={name}"), similarity as f64);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EmbeddingFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prototypes: Vec<&str> = self.prototypes.iter().map(|(name, _)| name.as_str()).collect();
+        f.debug_struct("EmbeddingFeatures")
+            .field("template", &self.template)
+            .field("words", &self.embeddings.len())
+            .field("dim", &self.embeddings.dim())
+            .field("dimensions", &self.dimensions)
+            .field("prototypes", &prototypes)
+            .finish()
+    }
+}
+
+impl FeatureExtractor for EmbeddingFeatures {
+    fn extract(&self, tokens: &[Token], gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+        let mut features = self.template.extract(tokens, gazetteers);
+        let keys: Vec<String> = tokens.iter().map(|t| GazetteerKey::normalize(&t.text)).collect();
+        self.add_embeddings(keys.iter().map(String::as_str), &mut features);
+        features
+    }
+
+    fn extract_with_lookups(
+        &self,
+        tokens: &[Token],
+        gazetteers: &Gazetteers,
+        lookups: &GazetteerLookups,
+    ) -> Vec<FeatureVector> {
+        let mut features = self.template.extract_with_lookups(tokens, gazetteers, lookups);
+        // A chave do próprio texto, mesmo quando a consulta usou a grafia do gazetteer
+        let keys = (0..tokens.len()).map(|i| {
+            let entry = lookups.entry(i);
+            entry.variant.as_deref().unwrap_or(&entry.key)
+        });
+        self.add_embeddings(keys, &mut features);
+        features
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::tokenize;
+
+    const VEC: &str = "6 4\n\
+                       recife 0.9 0.1 0.0 0.1\n\
+                       Recife 0.0 0.0 1.0 0.0\n\
+                       salvador 0.8 0.2 0.1 0.0\n\
+                       manaus 0.85 0.0 0.1 0.2\n\
+                       paulo 0.7 0.3 0.0 0.0\n\
+                       governou -0.1 0.1 0.9 -0.3\n";
+
+    fn embeddings() -> WordEmbeddings {
+        WordEmbeddings::read_vec(VEC.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_vec_header_duplicates_and_normalization() {
+        let emb = embeddings();
+        assert_eq!(emb.dim(), 4);
+        assert_eq!(emb.len(), 5); // "Recife" repete a chave de "recife" e fica de fora
+        let recife = emb.vector("RECIFE").unwrap();
+        assert!(recife[0] > 0.9);
+        assert!((dot(recife, recife) - 1.0).abs() < 1e-5);
+        assert!(emb.similarity("recife", "salvador").unwrap() > emb.similarity("recife", "governou").unwrap());
+        assert!(emb.vector("brasília").is_none());
+
+        // Sem cabeçalho, a primeira linha define a dimensão
+        let headerless = WordEmbeddings::read_vec("a 1 0\nb 0 1\n".as_bytes()).unwrap();
+        assert_eq!((headerless.dim(), headerless.len()), (2, 2));
+        let limited = WordEmbeddings::read_vec_limited(VEC.as_bytes(), 2).unwrap();
+        assert_eq!(limited.len(), 2);
+        assert!(limited.vector("manaus").is_none());
+    }
+
+    #[test]
+    fn test_read_vec_malformed() {
+        let err = WordEmbeddings::read_vec("2 3\nrecife 0.1 0.2 0.3\nsalvador 0.1 x 0.3\n".as_bytes()).unwrap_err();
+        match err {
+            EmbeddingError::Malformed { line, message } => {
+                assert_eq!(line, 3);
+                assert!(message.contains("'x'"), "{message}");
+            }
+            other => panic!("erro inesperado: {other}"),
+        }
+        assert!(WordEmbeddings::read_vec("2 3\n0.1 0.2 0.3\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_nearest_and_expand() {
+        let emb = embeddings();
+        let nearest = emb.nearest("recife", 2);
+        assert_eq!(nearest.len(), 2);
+        assert!(nearest.iter().all(|(w, _)| w != "recife"));
+        assert!(nearest[0].1 >= nearest[1].1);
+
+        let config = ExpansionConfig { neighbors: 3, min_similarity: 0.8, min_votes: 2 };
+        let expanded: Vec<String> = emb.expand(&["recife", "salvador"], &config).into_iter().map(|(w, _)| w).collect();
+        assert!(expanded.contains(&"manaus".to_string()));
+        assert!(!expanded.contains(&"governou".to_string()));
+        assert!(!expanded.contains(&"recife".to_string()));
+    }
+
+    #[test]
+    fn test_expand_gazetteer_skips_known_entries() {
+        let emb = embeddings();
+        let mut gaz = Gazetteers::new();
+        gaz.insert(EntityCategory::Loc, "Recife");
+        gaz.insert(EntityCategory::Per, "Paulo");
+        let config = ExpansionConfig { neighbors: 4, min_similarity: 0.8, min_votes: 1 };
+        let added = emb.expand_gazetteer(&mut gaz, EntityCategory::Loc, &config);
+        assert_eq!(added, vec!["manaus", "salvador"]);
+        assert!(gaz.contains_phrase(EntityCategory::Loc, "Manaus"));
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "Paulo"));
+    }
+
+    #[test]
+    fn test_embedding_features() {
+        let extractor = EmbeddingFeatures::new(embeddings()).with_prototype("LOC", &["recife", "salvador", "nada"]);
+        let tokens = tokenize("Paulo governou Manaus");
+        let gaz = Gazetteers::new();
+        let features = extractor.extract(&tokens, &gaz);
+
+        assert!(features[2].features["proto=LOC"] > 0.9);
+        assert!(features[2].features.contains_key("emb0=+"));
+        assert!(features[1].features.contains_key("emb3=-")); // "governou" é o único negativo
+        assert!(features[2].features.contains_key("word=manaus"));
+
+        let lookups = GazetteerLookups::new(&tokens, &gaz);
+        let with_lookups = extractor.extract_with_lookups(&tokens, &gaz, &lookups);
+        for (a, b) in features.iter().zip(&with_lookups) {
+            assert_eq!(a.features, b.features);
+        }
+
+        let plain = EmbeddingFeatures::new(embeddings()).with_dimensions(false);
+        let features = plain.extract(&tokens, &gaz);
+        assert!(!features.iter().any(|fv| fv.features.keys().any(|k| k.starts_with("emb") || k.starts_with("proto"))));
+    }
+}
@@ -0,0 +1,278 @@
+//! # Validação Cruzada (K-Fold)
+//!
+//! Avaliar um modelo nas mesmas sentenças em que ele foi treinado mede memorização, não
+//! generalização. Com um corpus pequeno também não dá para reservar muitas sentenças só
+//! para teste. A **validação cruzada em k partes** resolve os dois problemas:
+//!
+//! ```text
+//! corpus:  [ 1 | 2 | 3 | 4 | 5 ]      k = 5
+//! rodada 1: treina em 2..5, avalia em 1
+//! rodada 2: treina em 1,3..5, avalia em 2
+//! ...
+//! F1 final = média (± desvio padrão) das 5 rodadas
+//! ```
+//!
+//! Toda sentença é avaliada exatamente uma vez, sempre por um modelo que não a viu.
+//! O desvio padrão mostra o quanto o resultado depende da divisão: dois modelos cujas
+//! médias diferem menos que o desvio não são, de fato, distinguíveis neste corpus.
+//!
+//! As partes são formadas de forma intercalada (a sentença `i` vai para a parte `i % k`),
+//! para que cada uma tenha sentenças de todos os domínios do corpus embutido, que está
+//! ordenado por domínio.
+//!
+//! A métrica é o F1 **por entidade** (estilo CoNLL): uma entidade só conta como acerto se
+//! início, fim e categoria coincidirem com a anotação ouro.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::corpus::get_corpus;
+//! use ner_core::eval::cross_validate;
+//! use ner_core::AlgorithmMode;
+//!
+//! let report = cross_validate(&get_corpus(), 4, AlgorithmMode::Hmm).unwrap();
+//! assert_eq!(report.folds.len(), 4);
+//! println!("HMM: F1 = {:.3} ± {:.3}", report.mean_f1, report.std_f1);
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::corpus::AnnotatedSentence;
+use crate::hmm::HmmModel;
+use crate::maxent::MaxEntModel;
+use crate::perceptron::PerceptronModel;
+use crate::pipeline::AlgorithmMode;
+use crate::span::SpanModel;
+
+/// Uma entidade como intervalo de tokens `[start, end)` e categoria.
+type TokenSpan = (usize, usize, String);
+
+/// Um modelo treinado, visto como função de tokens para entidades.
+type Predictor = Box<dyn Fn(&[String]) -> Vec<TokenSpan>>;
+
+/// Erro de configuração da validação cruzada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossValidationError {
+    /// `k` precisa estar entre 2 e o número de sentenças.
+    InvalidFolds { k: usize, sentences: usize },
+    /// O modo não é treinável a partir do corpus (regras, CRF de pesos manuais...).
+    NotTrainable(AlgorithmMode),
+}
+
+impl fmt::Display for CrossValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFolds { k, sentences } => {
+                write!(f, "k = {k} inválido para um corpus de {sentences} sentenças (use 2 ≤ k ≤ {sentences})")
+            }
+            Self::NotTrainable(mode) => write!(f, "o modo {mode:?} não é treinável a partir do corpus"),
+        }
+    }
+}
+
+impl std::error::Error for CrossValidationError {}
+
+/// Resultado de uma rodada: treino em k-1 partes, avaliação na restante.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FoldResult {
+    /// Índice da parte avaliada (0..k).
+    pub fold: usize,
+    pub train_sentences: usize,
+    pub test_sentences: usize,
+    /// Entidades ouro na parte avaliada.
+    pub gold: usize,
+    /// Entidades previstas na parte avaliada.
+    pub predicted: usize,
+    /// Previstas com início, fim e categoria corretos.
+    pub correct: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Resultado da validação cruzada de um modo.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossValidationReport {
+    pub mode: AlgorithmMode,
+    pub k: usize,
+    pub folds: Vec<FoldResult>,
+    /// Média do F1 das rodadas.
+    pub mean_f1: f64,
+    /// Desvio padrão amostral (n - 1) do F1 das rodadas.
+    pub std_f1: f64,
+}
+
+/// Modos que [`cross_validate`] sabe treinar.
+pub const TRAINABLE_MODES: [AlgorithmMode; 5] = [
+    AlgorithmMode::Hmm,
+    AlgorithmMode::MaxEnt,
+    AlgorithmMode::Memm,
+    AlgorithmMode::Perceptron,
+    AlgorithmMode::SpanBased,
+];
+
+/// Validação cruzada em `k` partes de um modo treinável (ver o módulo).
+///
+/// Os hiperparâmetros são os mesmos do modelo padrão ([`crate::model::NerModel::build`]).
+pub fn cross_validate(
+    corpus: &[AnnotatedSentence],
+    k: usize,
+    mode: AlgorithmMode,
+) -> Result<CrossValidationReport, CrossValidationError> {
+    if !TRAINABLE_MODES.contains(&mode) {
+        return Err(CrossValidationError::NotTrainable(mode));
+    }
+    if k < 2 || k > corpus.len() {
+        return Err(CrossValidationError::InvalidFolds { k, sentences: corpus.len() });
+    }
+
+    let folds: Vec<FoldResult> = (0..k)
+        .map(|fold| {
+            let (test, train): (Vec<_>, Vec<_>) = corpus.iter().enumerate().partition(|(i, _)| i % k == fold);
+            let train: Vec<AnnotatedSentence> = train.into_iter().map(|(_, s)| borrow(s)).collect();
+            let test: Vec<&AnnotatedSentence> = test.into_iter().map(|(_, s)| s).collect();
+            let predict = train_predictor(mode, &train);
+
+            let (mut gold, mut predicted, mut correct) = (0, 0, 0);
+            for sentence in &test {
+                let tokens: Vec<String> = sentence.annotations.iter().map(|(w, _)| w.to_string()).collect();
+                let expected: HashSet<TokenSpan> = bio_spans(sentence.annotations.iter().map(|(_, t)| *t)).into_iter().collect();
+                let found: HashSet<TokenSpan> = predict(&tokens).into_iter().collect();
+                gold += expected.len();
+                predicted += found.len();
+                correct += expected.intersection(&found).count();
+            }
+            let precision = ratio(correct, predicted);
+            let recall = ratio(correct, gold);
+            let f1 = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+            FoldResult {
+                fold,
+                train_sentences: train.len(),
+                test_sentences: test.len(),
+                gold,
+                predicted,
+                correct,
+                precision,
+                recall,
+                f1,
+            }
+        })
+        .collect();
+
+    let mean_f1 = folds.iter().map(|f| f.f1).sum::<f64>() / k as f64;
+    let variance = folds.iter().map(|f| (f.f1 - mean_f1).powi(2)).sum::<f64>() / (k - 1) as f64;
+    Ok(CrossValidationReport { mode, k, folds, mean_f1, std_f1: variance.sqrt() })
+}
+
+/// Treina o modelo do modo nas sentenças de treino e devolve seu preditor de spans.
+fn train_predictor(mode: AlgorithmMode, train: &[AnnotatedSentence]) -> Predictor {
+    match mode {
+        AlgorithmMode::Hmm => {
+            let mut model = HmmModel::new();
+            model.train(train);
+            Box::new(move |tokens| bio_spans(model.predict(tokens).iter().map(String::as_str)))
+        }
+        AlgorithmMode::MaxEnt | AlgorithmMode::Memm => {
+            let mut model = if mode == AlgorithmMode::Memm { MaxEntModel::memm() } else { MaxEntModel::new() };
+            model.train(train, 10, 0.1, 0.01);
+            Box::new(move |tokens| bio_spans(model.predict(tokens).iter().map(String::as_str)))
+        }
+        AlgorithmMode::Perceptron => {
+            let mut model = PerceptronModel::new();
+            model.train(train, 5);
+            Box::new(move |tokens| bio_spans(model.predict(tokens).iter().map(String::as_str)))
+        }
+        _ => {
+            let mut model = SpanModel::new();
+            model.train(train, 5);
+            Box::new(move |tokens| model.predict(tokens).into_iter().map(|s| (s.start, s.end, s.label)).collect())
+        }
+    }
+}
+
+/// Entidades de uma sequência de tags BIO. Um `I-X` que não continua `X` abre uma entidade nova.
+fn bio_spans<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<TokenSpan> {
+    let mut spans: Vec<TokenSpan> = Vec::new();
+    let mut open: Option<(usize, &str)> = None;
+    let mut len = 0;
+    for (i, tag) in tags.into_iter().enumerate() {
+        len = i + 1;
+        let (prefix, category) = tag.split_once('-').unwrap_or((tag, ""));
+        let continues = prefix == "I" && open.is_some_and(|(_, c)| c == category);
+        if continues {
+            continue;
+        }
+        if let Some((start, c)) = open.take() {
+            spans.push((start, i, c.to_string()));
+        }
+        if matches!(prefix, "B" | "I") && !category.is_empty() {
+            open = Some((i, category));
+        }
+    }
+    if let Some((start, c)) = open {
+        spans.push((start, len, c.to_string()));
+    }
+    spans
+}
+
+/// Cópia rasa de uma sentença (os treinadores recebem um slice contíguo).
+fn borrow<'a>(sentence: &AnnotatedSentence<'a>) -> AnnotatedSentence<'a> {
+    AnnotatedSentence { text: sentence.text, domain: sentence.domain, annotations: sentence.annotations }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::get_corpus;
+
+    #[test]
+    fn test_bio_spans() {
+        let tags = ["B-PER", "I-PER", "O", "I-LOC", "B-ORG", "B-ORG", "I-ORG", "I-LOC"];
+        assert_eq!(
+            bio_spans(tags),
+            vec![
+                (0, 2, "PER".to_string()),
+                (3, 4, "LOC".to_string()),
+                (4, 5, "ORG".to_string()),
+                (5, 7, "ORG".to_string()),
+                (7, 8, "LOC".to_string()),
+            ]
+        );
+        assert!(bio_spans(["O", "O"]).is_empty());
+    }
+
+    #[test]
+    fn test_cross_validate_every_sentence_tested_once() {
+        let corpus = get_corpus();
+        let report = cross_validate(&corpus, 5, AlgorithmMode::Perceptron).unwrap();
+        assert_eq!(report.folds.len(), 5);
+        assert_eq!(report.folds.iter().map(|f| f.test_sentences).sum::<usize>(), corpus.len());
+        assert!(report.folds.iter().all(|f| f.train_sentences + f.test_sentences == corpus.len()));
+        assert!(report.folds.iter().all(|f| (0.0..=1.0).contains(&f.f1) && f.correct <= f.gold.min(f.predicted)));
+        assert!(report.mean_f1 > 0.0 && report.std_f1 >= 0.0);
+
+        // Determinística: a mesma divisão e o mesmo treino
+        assert_eq!(cross_validate(&corpus, 5, AlgorithmMode::Perceptron).unwrap(), report);
+    }
+
+    #[test]
+    fn test_cross_validate_rejects_bad_configuration() {
+        let corpus = get_corpus();
+        assert_eq!(
+            cross_validate(&corpus, 1, AlgorithmMode::Hmm),
+            Err(CrossValidationError::InvalidFolds { k: 1, sentences: corpus.len() })
+        );
+        assert!(cross_validate(&corpus[..3], 4, AlgorithmMode::Hmm).is_err());
+        assert_eq!(
+            cross_validate(&corpus, 3, AlgorithmMode::RulesOnly),
+            Err(CrossValidationError::NotTrainable(AlgorithmMode::RulesOnly))
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! # Exportação para Ferramentas de NLP e Anotação
+//!
+//! Fluxos de anotação costumam combinar várias ferramentas; este módulo escreve as
+//! entidades do pipeline nos formatos delas, sem serializadores feitos à mão:
+//!
+//! - **spaCy** ([`to_spacy`]): o JSON de `Doc.to_json()` / `displacy.render(manual=True)`,
+//!   com o texto e as entidades em `ents` (`start`, `end`, `label`). Com
+//!   [`SpacyDoc::with_tokens`], leva também os tokens, que `Doc.from_json()` exige.
+//! - **BRAT standoff** ([`to_brat`]): o arquivo `.ann` que acompanha o `.txt` do
+//!   documento, uma linha `T<n>\t<CAT> <início> <fim>\t<trecho>` por entidade. Como o
+//!   BRAT não aceita quebra de linha dentro de uma anotação, uma entidade que atravessa
+//!   linhas vira um trecho descontínuo (`0 5;6 12`).
+//!
+//! Os dois formatos contam offsets em **caracteres**, não em bytes (ver
+//! [`crate::offsets::char_offset`]).
+//!
+//! Para relatórios legíveis fora da interface web (CLI, notebooks, e-mails):
+//!
+//! - **HTML autônomo** ([`to_html`]): cada entidade em `<mark class="ner-PER">` com a cor
+//!   da categoria ([`crate::tagger::EntityCategory::color`]) no próprio `style`, sem depender da folha
+//!   de estilos do servidor (diferente de [`crate::render::to_html`]).
+//! - **Markdown** ([`to_markdown`]): a entidade em negrito seguida da categoria em código,
+//!   `**Brasília** `LOC``.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::export::{to_brat, to_spacy};
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let text = "Ação em Brasília.";
+//! let (_, entities) = NerPipeline::new().analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!
+//! let doc = serde_json::to_value(to_spacy(text, &entities)).unwrap();
+//! assert_eq!(doc["ents"][0], serde_json::json!({"start": 8, "end": 16, "label": "LOC"}));
+//! assert_eq!(to_brat(text, &entities), "T1\tLOC 8 16\tBrasília\n");
+//! assert_eq!(ner_core::export::to_markdown(text, &entities), "Ação em **Brasília** `LOC`.");
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::offsets::{char_offset, floor_char_boundary, slice_lossy};
+use crate::render::{escape_html, nested};
+use crate::tagger::EntitySpan;
+use crate::tokenizer::Token;
+
+/// Documento no formato JSON do spaCy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpacyDoc {
+    pub text: String,
+    pub ents: Vec<SpacyEnt>,
+    /// Tokens do documento; vazio (e omitido do JSON) a menos que se use [`SpacyDoc::with_tokens`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<SpacyToken>,
+}
+
+/// Uma entidade de [`SpacyDoc`]. Offsets em caracteres, `end` exclusivo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpacyEnt {
+    pub start: usize,
+    pub end: usize,
+    /// Rótulo da categoria (ex: "PER").
+    pub label: String,
+}
+
+/// Um token de [`SpacyDoc`]. Offsets em caracteres, `end` exclusivo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpacyToken {
+    pub id: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SpacyDoc {
+    /// Acrescenta os `tokens` da análise de `text` (o mesmo texto do documento).
+    pub fn with_tokens(mut self, tokens: &[Token]) -> Self {
+        self.tokens = tokens
+            .iter()
+            .enumerate()
+            .map(|(id, t)| SpacyToken { id, start: char_offset(&self.text, t.start), end: char_offset(&self.text, t.end) })
+            .collect();
+        self
+    }
+}
+
+/// Entidades de `text` no formato JSON do spaCy, na ordem do texto.
+pub fn to_spacy(text: &str, entities: &[EntitySpan]) -> SpacyDoc {
+    let mut ents: Vec<SpacyEnt> = entities
+        .iter()
+        .map(|e| SpacyEnt {
+            start: char_offset(text, e.start),
+            end: char_offset(text, e.end),
+            label: e.category.name().to_string(),
+        })
+        .collect();
+    ents.sort_by_key(|e| (e.start, e.end));
+    SpacyDoc { text: text.to_string(), ents, tokens: Vec::new() }
+}
+
+/// Uma anotação de entidade (`T<n>`) do formato standoff do BRAT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BratEntity {
+    /// Número da anotação (o `n` de `T<n>`), a partir de 1.
+    pub id: usize,
+    pub label: String,
+    /// Trechos em caracteres (`end` exclusivo); mais de um quando a entidade atravessa linhas.
+    pub fragments: Vec<(usize, usize)>,
+    /// Texto dos trechos, unidos por espaço.
+    pub text: String,
+}
+
+impl BratEntity {
+    /// Converte `span` (offsets de byte em `text`), partindo-o nas quebras de linha.
+    /// `None` se não sobrar nenhum trecho com texto.
+    pub fn from_span(id: usize, text: &str, span: &EntitySpan) -> Option<Self> {
+        let covered = slice_lossy(text, span.start, span.end);
+        let mut fragments = Vec::new();
+        let mut parts = Vec::new();
+        // Mesmo ajuste de fronteira de caractere feito por `slice_lossy`
+        let mut line_start = floor_char_boundary(text, span.start.min(text.len()));
+        for line in covered.split('\n') {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                let start = line_start + (line.len() - line.trim_start().len());
+                fragments.push((char_offset(text, start), char_offset(text, start + trimmed.len())));
+                parts.push(trimmed);
+            }
+            line_start += line.len() + 1;
+        }
+        if fragments.is_empty() {
+            return None;
+        }
+        Some(Self { id, label: span.category.name().to_string(), fragments, text: parts.join(" ") })
+    }
+}
+
+impl fmt::Display for BratEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fragments: Vec<String> = self.fragments.iter().map(|(start, end)| format!("{start} {end}")).collect();
+        write!(f, "T{}\t{} {}\t{}", self.id, self.label, fragments.join(";"), self.text)
+    }
+}
+
+/// Entidades de `text` como anotações do BRAT, numeradas na ordem do texto.
+pub fn to_brat_entities(text: &str, entities: &[EntitySpan]) -> Vec<BratEntity> {
+    let mut sorted: Vec<&EntitySpan> = entities.iter().collect();
+    sorted.sort_by_key(|e| (e.start, e.end));
+    sorted
+        .into_iter()
+        .filter_map(|span| BratEntity::from_span(0, text, span))
+        .enumerate()
+        .map(|(i, entity)| BratEntity { id: i + 1, ..entity })
+        .collect()
+}
+
+/// Conteúdo do arquivo `.ann` do BRAT para `text`: uma linha por entidade.
+///
+/// O `.txt` ao lado dele deve ter exatamente o mesmo `text`, já que os offsets se
+/// referem a ele.
+pub fn to_brat(text: &str, entities: &[EntitySpan]) -> String {
+    to_brat_entities(text, entities).iter().map(|entity| format!("{entity}\n")).collect()
+}
+
+/// `text` (escapado) como HTML autônomo, com cada entidade destacada na cor da categoria:
+/// `<mark class="ner-LOC" title="LOC" style="...">Brasília</mark>`.
+///
+/// Entidades sobrepostas a uma anterior são ignoradas, como em [`crate::render::to_html`].
+pub fn to_html(text: &str, entities: &[EntitySpan]) -> String {
+    let mut out = String::with_capacity(text.len() + entities.len() * 128);
+    let mut copied = 0;
+    for entity in nested(text, entities) {
+        let (name, color) = (entity.category.name(), entity.category.color());
+        out.push_str(&escape_html(slice_lossy(text, copied, entity.start)));
+        out.push_str(&format!(
+            r#"<mark class="ner-{name}" title="{name}" style="background-color: {color}33; border-bottom: 2px solid {color}">"#
+        ));
+        out.push_str(&escape_html(slice_lossy(text, entity.start, entity.end)));
+        out.push_str("</mark>");
+        copied = entity.end;
+    }
+    out.push_str(&escape_html(slice_lossy(text, copied, text.len())));
+    out
+}
+
+/// `text` em Markdown, com cada entidade em negrito seguida da categoria em código
+/// (`**Brasília** `LOC``). Os caracteres de marcação do texto são escapados.
+pub fn to_markdown(text: &str, entities: &[EntitySpan]) -> String {
+    let mut out = String::with_capacity(text.len() + entities.len() * 16);
+    let mut copied = 0;
+    for entity in nested(text, entities) {
+        out.push_str(&escape_markdown(slice_lossy(text, copied, entity.start)));
+        out.push_str("**");
+        out.push_str(&escape_markdown(slice_lossy(text, entity.start, entity.end)));
+        out.push_str("** `");
+        out.push_str(entity.category.name());
+        out.push('`');
+        copied = entity.end;
+    }
+    out.push_str(&escape_markdown(slice_lossy(text, copied, text.len())));
+    out
+}
+
+/// Escapa com `\` os caracteres que o Markdown interpretaria como marcação.
+pub fn escape_markdown(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|' | '~') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::EntityCategory;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_spacy_and_brat_export() {
+        let text = "A Fundação\nOswaldo Cruz fica em São Paulo.";
+        let entities = vec![
+            EntitySpan::test_at(text, "São Paulo", EntityCategory::Loc),
+            EntitySpan::test_at(text, "Fundação\nOswaldo Cruz", EntityCategory::Org),
+        ];
+
+        // spaCy: ordem do texto, offsets em caracteres
+        let doc = to_spacy(text, &entities).with_tokens(&tokenize(text));
+        assert_eq!(doc.ents[0], SpacyEnt { start: 2, end: 23, label: "ORG".into() });
+        assert_eq!(doc.ents[1], SpacyEnt { start: 32, end: 41, label: "LOC".into() });
+        assert_eq!(doc.tokens[1], SpacyToken { id: 1, start: 2, end: 10 });
+        let json = serde_json::to_string(&doc).unwrap();
+        assert_eq!(serde_json::from_str::<SpacyDoc>(&json).unwrap(), doc);
+        assert!(!serde_json::to_string(&to_spacy(text, &entities)).unwrap().contains("tokens"));
+
+        // BRAT: a entidade que atravessa a quebra de linha vira dois trechos
+        let chars: Vec<char> = text.chars().collect();
+        let ann = to_brat(text, &entities);
+        assert_eq!(ann, "T1\tORG 2 10;11 23\tFundação Oswaldo Cruz\nT2\tLOC 32 41\tSão Paulo\n");
+        for entity in to_brat_entities(text, &entities) {
+            let covered: Vec<String> = entity.fragments.iter().map(|&(s, e)| chars[s..e].iter().collect()).collect();
+            assert_eq!(covered.join(" "), entity.text);
+        }
+
+        // Só espaços e quebras de linha: nada a anotar
+        let newline = text.find('\n').unwrap();
+        let blank = EntitySpan { start: newline, end: newline + 1, ..entities[1].clone() };
+        assert!(BratEntity::from_span(1, text, &blank).is_none());
+    }
+
+    #[test]
+    fn test_html_and_markdown_highlight() {
+        let text = "A *Petrobras* <abriu> vagas em São Paulo.";
+        let entities = vec![
+            EntitySpan::test_at(text, "Petrobras", EntityCategory::Org),
+            EntitySpan::test_at(text, "São Paulo", EntityCategory::Loc),
+            // Sobreposta a "São Paulo": ignorada
+            EntitySpan::test_at(text, "Paulo", EntityCategory::Per),
+        ];
+
+        let html = to_html(text, &entities);
+        let org = EntityCategory::Org.color();
+        assert!(html.starts_with(&format!(
+            r#"A *<mark class="ner-ORG" title="ORG" style="background-color: {org}33; border-bottom: 2px solid {org}">Petrobras</mark>* &lt;abriu&gt;"#
+        )));
+        assert!(html.ends_with(">São Paulo</mark>."));
+        assert!(!html.contains("ner-PER"));
+
+        let markdown = to_markdown(text, &entities);
+        assert_eq!(markdown, r"A \***Petrobras** `ORG`\* \<abriu\> vagas em **São Paulo** `LOC`.");
+    }
+}
@@ -13,21 +13,69 @@
 //! - Sufixos de 2, 3 e 4 caracteres
 //! - Contém dígitos, hífens, pontos
 //! - É apenas dígito
+//! - Tem forma de ano (ex: "1888") ou de horário (ex: "14h30")
+//! - Radical da palavra (`lemma=govern` para "governou", "governa"...; ver [`crate::morphology`])
+//! - Classe gramatical da palavra e da anterior (`pos=PROPN`, `prev_pos=ADP`; ver [`crate::pos`])
+//! - Prefixos do cluster de Brown da palavra (`cluster4=0110`, `cluster6=011010`), com o
+//!   extrator [`crate::clusters::ClusterFeatures`] (ver [`insert_cluster_features`])
+//! - Dimensões binarizadas do embedding da palavra (`emb12=+`) e cosseno com protótipos
+//!   de categoria (`proto=LOC`), com o extrator [`crate::embeddings::EmbeddingFeatures`]
 //!
-//! ### Features de contexto (janela de 2 tokens)
-//! - Palavra anterior e posterior
-//! - Tag da palavra anterior (para features de transição)
+//! ### Features de contexto (janela configurável, padrão de 2 tokens)
+//! - Palavras anteriores e posteriores (`prev_word`, `prev2_word`, `next3_word`, ...)
+//! - Capitalização dos vizinhos imediatos
+//! - Bigrama formado pelos vizinhos imediatos
+//! - Radicais dos vizinhos imediatos (`prev_lemma`, `next_lemma`)
+//!
+//! Quais features posicionais são emitidas, e até que distância, é definido por um
+//! [`FeatureTemplate`]. Janelas maiores ajudam em nomes longos de organizações
+//! ("Instituto Nacional de Pesquisas Espaciais"), em que a palavra que decide a
+//! categoria ("Instituto") está a mais de 2 tokens do fim do nome.
+//!
+//! ### Nomes Estrangeiros
+//! Prefixos e sufixos foram pensados para o português; sobrenomes como "Bündchen",
+//! "Müller" ou "Kubitschek" fogem desses padrões. Para eles há duas features extras:
+//! - `translit=...`: a palavra transliterada para ASCII ([`transliterate`]), de modo que
+//!   "Müller" e "Mueller" ativem a mesma feature.
+//! - `looks_foreign`: palavra capitalizada com n-gramas de caracteres atípicos do
+//!   português ([`looks_foreign`]), um indício de nome próprio estrangeiro.
 //!
 //! ### Features de Gazetteer
 //! - Pertence à lista de nomes de pessoas
 //! - Pertence à lista de cidades/estados
 //! - Pertence à lista de organizações
+//! - Pertence à lista de expressões temporais (meses, dias da semana, horários)
+//!
+//! Toda inserção e consulta de gazetteer (aqui, no motor de regras e na construção
+//! do modelo) passa por [`GazetteerKey`], para que uma palavra encontrada pelas regras
+//! também ative a feature correspondente, e vice-versa. Para validar a cobertura dos
+//! dicionários de fora do crate, use [`Gazetteers::contains_phrase`] e [`Gazetteers::check`].
+//!
+//! ### Priors por entrada
+//! Nem toda entrada é igualmente confiável: "São Paulo" cidade é muito mais frequente que
+//! o clube homônimo. [`Gazetteers::set_prior`] associa um peso em `[0, 1]` (frequência no
+//! corpus, população...) a uma entrada, e o valor da feature `in_*_gazetteer` passa a ser
+//! esse peso em vez de `1.0`. Entradas sem peso usam [`DEFAULT_GAZETTEER_PRIOR`].
+//!
+//! ### Consultas memorizadas
+//! Numa análise, os mesmos tokens ("de", "Brasil") se repetem muitas vezes. O pipeline
+//! monta uma [`GazetteerLookups`] por análise: cada forma distinta é normalizada e
+//! consultada nos gazetteers uma única vez, e o resultado é compartilhado entre a
+//! extração de features ([`FeatureExtractor::extract_with_lookups`]) e o motor de regras.
 
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-use crate::tokenizer::Token;
+use crate::brazil::{demonym_place, is_uf_code};
+use crate::categories::CustomCategory;
+use crate::morphology::stem;
+use crate::pos::{builtin_tagger, PosTag};
+use crate::tagger::EntityCategory;
+use crate::tokenizer::{Token, TokenKind};
+use crate::variants::SpellingVariants;
 
 /// Estrutura para representar as características de um token.
 ///
@@ -72,13 +120,30 @@ impl FeatureVector {
     }
 }
 
+/// Peso de uma entrada de gazetteer sem prior explícito.
+pub const DEFAULT_GAZETTEER_PRIOR: f64 = 1.0;
+
 /// Listas de gazetteer compiladas a partir do corpus PT-BR
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Gazetteers {
     pub persons: HashSet<String>,
     pub locations: HashSet<String>,
     pub organizations: HashSet<String>,
     pub misc: HashSet<String>,
+    /// Meses, dias da semana e demais palavras de data (lowercase).
+    pub dates: HashSet<String>,
+    /// Marcos horários (lowercase). Ex: "meia-noite", "meio-dia".
+    pub times: HashSet<String>,
+    /// Chaves das categorias personalizadas (ver [`Gazetteers::insert`]).
+    #[serde(default)]
+    pub custom: HashMap<CustomCategory, HashSet<String>>,
+    /// Priors explícitos por categoria e chave (ver [`Gazetteers::set_prior`]).
+    #[serde(default)]
+    priors: HashMap<EntityCategory, HashMap<String, f64>>,
+    /// Chaves completas dos nomes de várias palavras inseridos por [`Gazetteers::insert`]
+    /// (ver [`Gazetteers::contains_phrase`]).
+    #[serde(default)]
+    phrases: HashMap<EntityCategory, HashSet<String>>,
 }
 
 impl Gazetteers {
@@ -88,8 +153,157 @@ impl Gazetteers {
             locations: HashSet::new(),
             organizations: HashSet::new(),
             misc: HashSet::new(),
+            dates: HashSet::new(),
+            times: HashSet::new(),
+            custom: HashMap::new(),
+            priors: HashMap::new(),
+            phrases: HashMap::new(),
+        }
+    }
+
+    /// Acrescenta um nome ao gazetteer da categoria, indexado por palavra como os
+    /// embutidos ([`GazetteerKey::words`]).
+    ///
+    /// ```rust
+    /// use ner_core::categories::CategorySet;
+    /// use ner_core::features::Gazetteers;
+    ///
+    /// let doenca = CategorySet::new().add("DOENCA").unwrap();
+    /// let mut gaz = Gazetteers::new();
+    /// gaz.insert(doenca, "febre amarela");
+    /// assert!(gaz.contains_phrase(doenca, "Febre Amarela"));
+    /// assert_eq!(gaz.hits("amarela")[0].0, doenca);
+    /// ```
+    pub fn insert(&mut self, category: EntityCategory, name: &str) {
+        let key = GazetteerKey::normalize(name);
+        if key.contains(' ') {
+            self.phrases.entry(category).or_default().insert(key);
+        }
+        let words = GazetteerKey::words(name);
+        match category {
+            EntityCategory::Per => self.persons.extend(words),
+            EntityCategory::Org => self.organizations.extend(words),
+            EntityCategory::Loc => self.locations.extend(words),
+            EntityCategory::Misc => self.misc.extend(words),
+            EntityCategory::Date => self.dates.extend(words),
+            EntityCategory::Time => self.times.extend(words),
+            EntityCategory::Custom(c) => self.custom.entry(c).or_default().extend(words),
+        }
+    }
+
+    /// Categorias com gazetteer: as embutidas e as personalizadas (por nome).
+    pub fn categories(&self) -> Vec<EntityCategory> {
+        let mut custom: Vec<CustomCategory> = self.custom.keys().copied().collect();
+        custom.sort_unstable();
+        EntityCategory::all().into_iter().chain(custom.into_iter().map(EntityCategory::Custom)).collect()
+    }
+
+    /// Define o prior (peso em `[0, 1]`) de uma entrada da categoria.
+    ///
+    /// O peso vale para a chave inteira e para cada palavra indexável dela
+    /// ([`GazetteerKey::words`]); uma palavra compartilhada com outra entrada da mesma
+    /// categoria fica com o último peso definido.
+    ///
+    /// ```rust
+    /// use ner_core::features::{Gazetteers, GazetteerKey, DEFAULT_GAZETTEER_PRIOR};
+    /// use ner_core::tagger::EntityCategory;
+    ///
+    /// let mut gaz = Gazetteers::new();
+    /// gaz.organizations.extend(GazetteerKey::words("São Paulo"));
+    /// gaz.set_prior(EntityCategory::Org, "São Paulo", 0.4);
+    /// assert_eq!(gaz.prior(EntityCategory::Org, "paulo"), 0.4);
+    /// assert_eq!(gaz.prior(EntityCategory::Loc, "paulo"), DEFAULT_GAZETTEER_PRIOR);
+    /// ```
+    pub fn set_prior(&mut self, category: EntityCategory, name: &str, prior: f64) {
+        let key = GazetteerKey::normalize(name);
+        if key.is_empty() {
+            return;
+        }
+        let prior = prior.clamp(0.0, 1.0);
+        let priors = self.priors.entry(category).or_default();
+        for word in GazetteerKey::words(&key) {
+            priors.insert(word, prior);
+        }
+        priors.insert(key, prior);
+    }
+
+    /// Prior de uma chave já normalizada ([`DEFAULT_GAZETTEER_PRIOR`] se não definido).
+    pub fn prior(&self, category: EntityCategory, key: &str) -> f64 {
+        self.priors
+            .get(&category)
+            .and_then(|priors| priors.get(key))
+            .copied()
+            .unwrap_or(DEFAULT_GAZETTEER_PRIOR)
+    }
+
+    /// Categorias cujo conjunto contém a chave já normalizada, com o prior da entrada.
+    pub fn hits(&self, key: &str) -> Vec<(EntityCategory, f64)> {
+        self.categories()
+            .into_iter()
+            .filter(|&category| self.keys(category).is_some_and(|keys| keys.contains(key)))
+            .map(|category| (category, self.prior(category, key)))
+            .collect()
+    }
+
+    /// Conjunto de chaves da categoria (`None` para uma personalizada sem entradas).
+    pub fn keys(&self, category: EntityCategory) -> Option<&HashSet<String>> {
+        match category {
+            EntityCategory::Per => Some(&self.persons),
+            EntityCategory::Org => Some(&self.organizations),
+            EntityCategory::Loc => Some(&self.locations),
+            EntityCategory::Misc => Some(&self.misc),
+            EntityCategory::Date => Some(&self.dates),
+            EntityCategory::Time => Some(&self.times),
+            EntityCategory::Custom(c) => self.custom.get(&c),
+        }
+    }
+
+    /// Verifica se uma frase está coberta pelo gazetteer da categoria.
+    ///
+    /// A frase passa por [`GazetteerKey::normalize`] ("  SÃO paulo" → "são paulo") e está
+    /// coberta se a chave inteira for uma entrada: uma palavra do conjunto ou um nome
+    /// composto inserido por [`Gazetteers::insert`] ("Rio de Janeiro", não "Janeiro Rio").
+    ///
+    /// Uma categoria montada só com chaves de palavra (conjuntos estendidos diretamente,
+    /// modelos salvos antes do índice de frases) não sabe quais palavras formam um nome:
+    /// a frase é aceita se todas as suas palavras indexáveis ([`GazetteerKey::words`])
+    /// estiverem no conjunto, e recusada se não tiver nenhuma ("de da").
+    pub fn contains_phrase(&self, category: EntityCategory, phrase: &str) -> bool {
+        let Some(keys) = self.keys(category) else {
+            return false;
+        };
+        let key = GazetteerKey::normalize(phrase);
+        if key.is_empty() {
+            return false;
+        }
+        if keys.contains(&key) {
+            return true;
+        }
+        match self.phrases.get(&category).filter(|phrases| !phrases.is_empty()) {
+            Some(phrases) => phrases.contains(&key),
+            None => {
+                let words = GazetteerKey::words(&key);
+                !words.is_empty() && words.iter().all(|word| keys.contains(word))
+            }
         }
     }
+
+    /// Versão em lote de [`Gazetteers::contains_phrase`]: um `bool` por frase, na mesma ordem.
+    ///
+    /// ```rust
+    /// use ner_core::features::{GazetteerKey, Gazetteers};
+    /// use ner_core::tagger::EntityCategory;
+    ///
+    /// let mut gaz = Gazetteers::new();
+    /// gaz.locations.extend(GazetteerKey::words("Rio de Janeiro"));
+    /// assert_eq!(
+    ///     gaz.check(EntityCategory::Loc, &["RIO DE JANEIRO", "Rio", "Niterói"]),
+    ///     vec![true, true, false]
+    /// );
+    /// ```
+    pub fn check(&self, category: EntityCategory, phrases: &[&str]) -> Vec<bool> {
+        phrases.iter().map(|phrase| self.contains_phrase(category, phrase)).collect()
+    }
 }
 
 impl Default for Gazetteers {
@@ -98,6 +312,106 @@ impl Default for Gazetteers {
     }
 }
 
+/// Normalização única das chaves de gazetteer.
+///
+/// Quem escreve (construção do modelo, `RuleEngine::add_*`) e quem lê (features,
+/// regras, modelo de spans) usa as mesmas funções, evitando que "Brasília" seja
+/// encontrada por um componente e ignorada por outro.
+pub struct GazetteerKey;
+
+impl GazetteerKey {
+    /// Tamanho mínimo (em caracteres) de uma palavra de nome composto para ser indexada
+    /// sozinha. Descarta preposições como "de" e "da" em "Rio de Janeiro".
+    pub const MIN_WORD_CHARS: usize = 3;
+
+    /// Chave de busca: minúsculas e espaços colapsados ("  São  Paulo" → "são paulo").
+    pub fn normalize(text: &str) -> String {
+        text.split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Chaves de palavra única derivadas de um nome do gazetteer.
+    ///
+    /// Um nome de uma só palavra é sempre indexado ("PT" → `["pt"]`); em nomes compostos,
+    /// apenas palavras com pelo menos [`GazetteerKey::MIN_WORD_CHARS`] caracteres
+    /// ("Rio de Janeiro" → `["rio", "janeiro"]`).
+    pub fn words(name: &str) -> Vec<String> {
+        let key = Self::normalize(name);
+        let words: Vec<&str> = key.split(' ').filter(|w| !w.is_empty()).collect();
+        if words.len() == 1 {
+            return vec![words[0].to_string()];
+        }
+        words
+            .into_iter()
+            .filter(|w| w.chars().count() >= Self::MIN_WORD_CHARS)
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Configuração das features de contexto (quais posições e templates emitir).
+///
+/// O padrão reproduz exatamente a janela fixa de ±2 tokens usada pelos pesos
+/// heurísticos do CRF; ampliar a janela apenas adiciona features (`prev3_word=...`),
+/// sem alterar as existentes.
+///
+/// # Exemplo
+/// ```rust
+/// use ner_core::features::{extract_features_with_template, FeatureTemplate, Gazetteers};
+/// use ner_core::tokenizer::tokenize;
+///
+/// let tokens = tokenize("o Instituto Nacional de Pesquisas Espaciais alertou");
+/// let template = FeatureTemplate { window: 4, ..FeatureTemplate::default() };
+/// let features = extract_features_with_template(&tokens, &Gazetteers::new(), &template);
+/// // "Espaciais" (índice 5) enxerga "Instituto", 4 posições antes
+/// assert!(features[5].features.contains_key("prev4_word=instituto"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureTemplate {
+    /// Distância máxima (em tokens, para cada lado) das features `prev{k}_word`/`next{k}_word`.
+    pub window: usize,
+    /// Distância máxima das features de capitalização dos vizinhos (`prev{k}_is_capitalized`).
+    /// Limitada por `window`.
+    pub capitalization_window: usize,
+    /// Emite o bigrama formado pelos vizinhos imediatos (`bigram=anterior_posterior`).
+    pub context_bigram: bool,
+    /// Emite `BOS`/`EOS` quando o token está no início/fim da sentença.
+    pub boundary_markers: bool,
+    /// Emite o radical ([`crate::morphology::stem`]) da palavra e dos vizinhos imediatos
+    /// (`lemma=`, `prev_lemma=`, `next_lemma=`), para que as flexões de uma mesma palavra
+    /// ("governou", "governa") dividam os pesos aprendidos.
+    pub lemmas: bool,
+    /// Emite a classe gramatical do token e do anterior (`pos=PROPN`, `prev_pos=ADP`),
+    /// atribuída pelo etiquetador embutido ([`crate::pos::builtin_tagger`]).
+    pub pos: bool,
+}
+
+impl Default for FeatureTemplate {
+    fn default() -> Self {
+        Self {
+            window: 2,
+            capitalization_window: 1,
+            context_bigram: true,
+            boundary_markers: true,
+            lemmas: true,
+            pos: true,
+        }
+    }
+}
+
+/// Nome da posição relativa: distância 1 mantém o nome histórico (`prev`), as demais
+/// recebem a distância como sufixo (`prev2`, `prev3`, ...).
+fn position_name(direction: &str, distance: usize) -> String {
+    if distance == 1 {
+        direction.to_string()
+    } else {
+        format!("{direction}{distance}")
+    }
+}
+
 use rayon::prelude::*;
 
 /// Gera vetores de features para toda a sequência de tokens.
@@ -121,15 +435,207 @@ use rayon::prelude::*;
 /// - `next_word=venceu`
 /// - `in_location_gazetteer` (se estiver no gazetteer)
 pub fn extract_features(tokens: &[Token], gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+    extract_features_with_template(tokens, gazetteers, &FeatureTemplate::default())
+}
+
+/// Igual a [`extract_features`], mas com as features de contexto definidas por `template`.
+pub fn extract_features_with_template(
+    tokens: &[Token],
+    gazetteers: &Gazetteers,
+    template: &FeatureTemplate,
+) -> Vec<FeatureVector> {
     // Usando rayon (par_iter + enumerate + map + collect) para acelerar a extração 
     // em CPU multi-core mantendo a ordem dos tokens inalterada.
+    let pos_tags = pos_tags(tokens, template);
     tokens
         .par_iter()
         .enumerate()
-        .map(|(i, _)| extract_for_token(tokens, i, gazetteers))
+        .map(|(i, _)| {
+            let mut fv = token_features(tokens, i, template, &pos_tags);
+            let hits = gazetteers.hits(&GazetteerKey::normalize(&tokens[i].text));
+            insert_gazetteer_features(&mut fv, &hits);
+            fv
+        })
         .collect()
 }
 
+/// Extrator de features de um modelo, injetável por algoritmo.
+///
+/// Cada modelo (CRF, MaxEnt, Perceptron, Span) guarda o seu extrator
+/// ([`SharedExtractor`]); o padrão é um [`FeatureTemplate::default`]. Experimentos podem
+/// dar a cada algoritmo um template diferente, ou um extrator próprio, e o pipeline usa
+/// automaticamente o extrator do modelo que estiver rodando.
+///
+/// ```rust
+/// use ner_core::features::{FeatureExtractor, FeatureTemplate, FeatureVector, Gazetteers};
+/// use ner_core::perceptron::PerceptronModel;
+/// use ner_core::tokenizer::Token;
+///
+/// /// Só a palavra em minúsculas, sem contexto.
+/// #[derive(Debug)]
+/// struct WordOnly;
+///
+/// impl FeatureExtractor for WordOnly {
+///     fn extract(&self, tokens: &[Token], _gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+///         tokens
+///             .iter()
+///             .enumerate()
+///             .map(|(i, t)| {
+///                 let mut fv = FeatureVector::new(i);
+///                 fv.insert(format!("word={}", t.text.to_lowercase()), 1.0);
+///                 fv
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// let perceptron = PerceptronModel::new().with_extractor(WordOnly);
+/// assert_eq!(format!("{:?}", perceptron.extractor()), "WordOnly");
+///
+/// // Ou apenas um template diferente do padrão
+/// let wide = PerceptronModel::new().with_extractor(FeatureTemplate { window: 4, ..FeatureTemplate::default() });
+/// assert!(format!("{:?}", wide.extractor()).contains("window: 4"));
+/// ```
+pub trait FeatureExtractor: fmt::Debug + Send + Sync {
+    /// Um vetor de features por token, na ordem dos tokens.
+    fn extract(&self, tokens: &[Token], gazetteers: &Gazetteers) -> Vec<FeatureVector>;
+
+    /// Features do trecho `tokens[start..end]`, usadas pelo modelo de spans.
+    /// O padrão é [`span_features`].
+    fn extract_span(
+        &self,
+        tokens: &[Token],
+        start: usize,
+        end: usize,
+        gazetteers: &Gazetteers,
+    ) -> FeatureVector {
+        span_features(tokens, start, end, gazetteers)
+    }
+
+    /// Como [`FeatureExtractor::extract`], reaproveitando as consultas aos gazetteers
+    /// já feitas na análise. O padrão ignora `lookups`.
+    fn extract_with_lookups(
+        &self,
+        tokens: &[Token],
+        gazetteers: &Gazetteers,
+        lookups: &GazetteerLookups,
+    ) -> Vec<FeatureVector> {
+        let _ = lookups;
+        self.extract(tokens, gazetteers)
+    }
+}
+
+impl FeatureExtractor for FeatureTemplate {
+    fn extract(&self, tokens: &[Token], gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+        extract_features_with_template(tokens, gazetteers, self)
+    }
+
+    fn extract_with_lookups(
+        &self,
+        tokens: &[Token],
+        _gazetteers: &Gazetteers,
+        lookups: &GazetteerLookups,
+    ) -> Vec<FeatureVector> {
+        extract_features_with_lookups(tokens, lookups, self)
+    }
+}
+
+/// Extrator compartilhado entre os clones de um modelo.
+///
+/// Não é serializado: um modelo lido do disco volta ao [`FeatureTemplate`] padrão e o
+/// extrator próprio precisa ser injetado de novo.
+#[derive(Clone)]
+pub struct SharedExtractor(Arc<dyn FeatureExtractor>);
+
+impl SharedExtractor {
+    pub fn new(extractor: impl FeatureExtractor + 'static) -> Self {
+        Self(Arc::new(extractor))
+    }
+}
+
+impl Default for SharedExtractor {
+    fn default() -> Self {
+        Self::new(FeatureTemplate::default())
+    }
+}
+
+impl fmt::Debug for SharedExtractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::ops::Deref for SharedExtractor {
+    type Target = dyn FeatureExtractor;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+/// Features de um trecho de tokens: bordas, contexto, tamanho, palavras internas e
+/// casamento do trecho inteiro com os gazetteers.
+pub fn span_features(
+    tokens: &[Token],
+    start: usize,
+    end: usize,
+    gaz: &Gazetteers,
+) -> FeatureVector {
+    let mut fv = FeatureVector::new(start);
+
+    // Features de borda (Boundary features)
+    let first_token = &tokens[start];
+    let last_token = &tokens[end - 1];
+
+    fv.insert(
+        format!("span_first={}", first_token.text.to_lowercase()),
+        1.0,
+    );
+    fv.insert(format!("span_last={}", last_token.text.to_lowercase()), 1.0);
+
+    // Contexto
+    if start > 0 {
+        fv.insert(
+            format!("ctx_prev={}", tokens[start - 1].text.to_lowercase()),
+            1.0,
+        );
+    }
+    if end < tokens.len() {
+        fv.insert(format!("ctx_next={}", tokens[end].text.to_lowercase()), 1.0);
+    }
+
+    // Tamanho
+    fv.insert(format!("span_len={}", end - start), 1.0);
+
+    // Bag of words interno
+    for token in &tokens[start..end] {
+        fv.insert(format!("in_span={}", token.text.to_lowercase()), 1.0);
+        if token.is_capitalized() {
+            fv.insert("span_has_cap", 1.0);
+        }
+    }
+
+    // Gazetteer match (se o span inteiro bater com gazetteer)
+    let span_text = GazetteerKey::normalize(
+        &tokens[start..end]
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    if gaz.persons.contains(&span_text) {
+        fv.insert("span_is_person_gaz", 1.0);
+    }
+    if gaz.locations.contains(&span_text) {
+        fv.insert("span_is_loc_gaz", 1.0);
+    }
+    if gaz.organizations.contains(&span_text) {
+        fv.insert("span_is_org_gaz", 1.0);
+    }
+
+    fv
+}
+
 /// Extrai features para um único token em seu contexto
 ///
 /// Implementa a lógica detalhada de extração, cobrindo:
@@ -138,6 +644,35 @@ pub fn extract_features(tokens: &[Token], gazetteers: &Gazetteers) -> Vec<Featur
 /// 3. **Conhecimento Externo**: Verificação em gazetteers.
 /// 4. **Posição**: Se é início ou fim de frase.
 pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) -> FeatureVector {
+    extract_for_token_with_template(tokens, i, gazetteers, &FeatureTemplate::default())
+}
+
+/// Igual a [`extract_for_token`], mas com as features de contexto definidas por `template`.
+pub fn extract_for_token_with_template(
+    tokens: &[Token],
+    i: usize,
+    gazetteers: &Gazetteers,
+    template: &FeatureTemplate,
+) -> FeatureVector {
+    // O etiquetador é guloso e olha um token à frente: basta etiquetar até `i + 1`.
+    let pos_tags = pos_tags(&tokens[..(i + 2).min(tokens.len())], template);
+    let mut fv = token_features(tokens, i, template, &pos_tags);
+    let hits = gazetteers.hits(&GazetteerKey::normalize(&tokens[i].text));
+    insert_gazetteer_features(&mut fv, &hits);
+    fv
+}
+
+/// Classes gramaticais da sentença, ou nenhuma se o template não usa `pos=`.
+fn pos_tags(tokens: &[Token], template: &FeatureTemplate) -> Vec<PosTag> {
+    if template.pos {
+        builtin_tagger().tag_tokens(tokens)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Features do token `i` que não dependem dos gazetteers (`pos_tags` vem de [`pos_tags`]).
+fn token_features(tokens: &[Token], i: usize, template: &FeatureTemplate, pos_tags: &[PosTag]) -> FeatureVector {
     let mut fv = FeatureVector::new(i);
     let token = &tokens[i];
     let word = &token.text;
@@ -147,8 +682,8 @@ pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) ->
     fv.insert(format!("word={lower}"), 1.0);
     fv.insert("bias", 1.0);
 
-    // Capitalização
-    let first_char_upper = word.chars().next().map(|c| c.is_uppercase()).unwrap_or(false);
+    // Capitalização e forma ("Xxxx", "XX-99")
+    let first_char_upper = token.is_capitalized();
     let all_upper = word.chars().all(|c| c.is_uppercase() || !c.is_alphabetic());
     let has_upper_in_middle = word.chars().skip(1).any(|c| c.is_uppercase());
 
@@ -161,6 +696,7 @@ pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) ->
     if has_upper_in_middle {
         fv.insert("is_mixed_case", 1.0);
     }
+    fv.insert(format!("shape={}", token.shape), 1.0);
 
     // Prefixos e sufixos
     let chars: Vec<char> = word.chars().collect();
@@ -174,18 +710,45 @@ pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) ->
     }
 
     // Padrões numéricos e de pontuação
-    if word.chars().all(char::is_numeric) {
+    if token.kind == TokenKind::Number {
         fv.insert("is_digit", 1.0);
     }
+    if is_year(word) {
+        fv.insert("is_year", 1.0);
+    }
+    if is_time_expression(word) {
+        fv.insert("is_time", 1.0);
+    }
     if word.contains('-') {
         fv.insert("has_hyphen", 1.0);
     }
     if word.contains('.') {
         fv.insert("has_period", 1.0);
     }
-    if word.len() == 1 && !word.chars().next().unwrap().is_alphanumeric() {
+    if token.kind == TokenKind::Punctuation {
         fv.insert("is_punctuation", 1.0);
     }
+    // Abreviações, URLs, e-mails, hashtags, menções e valores (ver `TokenKind`)
+    if token.kind != TokenKind::Word {
+        fv.insert(format!("kind={}", token.kind.as_str()), 1.0);
+    }
+
+    // Lugares do Brasil citados pela sigla ou pelo gentílico ("Campinas (SP)", "governo mineiro")
+    if is_uf_code(word) {
+        fv.insert("is_uf_code", 1.0);
+    }
+    if let Some(place) = demonym_place(&lower) {
+        fv.insert("is_demonym", 1.0);
+        fv.insert(format!("demonym_of={}", place.state.uf.to_lowercase()), 1.0);
+    }
+
+    // Nomes estrangeiros
+    if first_char_upper && word.chars().all(char::is_alphabetic) {
+        fv.insert(format!("translit={}", transliterate(word)), 1.0);
+        if looks_foreign(word) {
+            fv.insert("looks_foreign", 1.0);
+        }
+    }
 
     // Posição na sequência
     if i == 0 {
@@ -197,54 +760,52 @@ pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) ->
 
     // === Features de contexto ===
 
-    // Token anterior
-    if i > 0 {
-        let prev = &tokens[i - 1];
-        fv.insert(format!("prev_word={}", prev.text.to_lowercase()), 1.0);
-        let prev_first_upper = prev
-            .text
-            .chars()
-            .next()
-            .map(|c| c.is_uppercase())
-            .unwrap_or(false);
-        if prev_first_upper {
-            fv.insert("prev_is_capitalized", 1.0);
+    // Tokens anteriores e posteriores dentro da janela
+    let capitalization_window = template.capitalization_window.min(template.window);
+    for distance in 1..=template.window {
+        let neighbors = [
+            ("prev", i.checked_sub(distance)),
+            ("next", Some(i + distance).filter(|&j| j < tokens.len())),
+        ];
+        for (direction, j) in neighbors {
+            let Some(j) = j else { continue };
+            let name = position_name(direction, distance);
+            fv.insert(format!("{name}_word={}", tokens[j].text.to_lowercase()), 1.0);
+            if distance <= capitalization_window && tokens[j].is_capitalized() {
+                fv.insert(format!("{name}_is_capitalized"), 1.0);
+            }
         }
-    } else {
-        fv.insert("BOS", 1.0); // Beginning Of Sentence
-    }
-
-    // Token dois posições antes
-    if i > 1 {
-        let prev2 = &tokens[i - 2];
-        fv.insert(format!("prev2_word={}", prev2.text.to_lowercase()), 1.0);
-    }
-
-    // Token seguinte
-    if i + 1 < tokens.len() {
-        let next = &tokens[i + 1];
-        fv.insert(format!("next_word={}", next.text.to_lowercase()), 1.0);
-        let next_first_upper = next
-            .text
-            .chars()
-            .next()
-            .map(|c| c.is_uppercase())
-            .unwrap_or(false);
-        if next_first_upper {
-            fv.insert("next_is_capitalized", 1.0);
+    }
+
+    if template.boundary_markers {
+        if i == 0 {
+            fv.insert("BOS", 1.0); // Beginning Of Sentence
+        }
+        if i + 1 == tokens.len() {
+            fv.insert("EOS", 1.0); // End Of Sentence
+        }
+    }
+
+    // Radicais da palavra e dos vizinhos imediatos (só palavras: "R$", "1888" e "," não flexionam)
+    if template.lemmas {
+        let neighbors = [("lemma", Some(i)), ("prev_lemma", i.checked_sub(1)), ("next_lemma", Some(i + 1))];
+        for (name, j) in neighbors {
+            if let Some(neighbor) = j.and_then(|j| tokens.get(j)).filter(|t| t.kind == TokenKind::Word) {
+                fv.insert(format!("{name}={}", stem(&neighbor.text)), 1.0);
+            }
         }
-    } else {
-        fv.insert("EOS", 1.0); // End Of Sentence
     }
 
-    // Token dois posições depois
-    if i + 2 < tokens.len() {
-        let next2 = &tokens[i + 2];
-        fv.insert(format!("next2_word={}", next2.text.to_lowercase()), 1.0);
+    // Classe gramatical do token e do anterior
+    if let Some(tag) = pos_tags.get(i) {
+        fv.insert(format!("pos={tag}"), 1.0);
+    }
+    if let Some(prev) = i.checked_sub(1).and_then(|j| pos_tags.get(j)) {
+        fv.insert(format!("prev_pos={prev}"), 1.0);
     }
 
     // Bigramas de contexto
-    if i > 0 && i + 1 < tokens.len() {
+    if template.context_bigram && i > 0 && i + 1 < tokens.len() {
         let bigram = format!(
             "bigram={}_{}",
             tokens[i - 1].text.to_lowercase(),
@@ -253,29 +814,278 @@ pub fn extract_for_token(tokens: &[Token], i: usize, gazetteers: &Gazetteers) ->
         fv.insert(bigram, 1.0);
     }
 
-    // === Features de Gazetteer ===
-    let word_lower = word.to_lowercase();
+    fv
+}
+
+/// Como [`extract_features_with_template`], mas com as consultas aos gazetteers já
+/// feitas em `lookups` (montada sobre os mesmos `tokens`).
+pub fn extract_features_with_lookups(
+    tokens: &[Token],
+    lookups: &GazetteerLookups,
+    template: &FeatureTemplate,
+) -> Vec<FeatureVector> {
+    let pos_tags = pos_tags(tokens, template);
+    tokens
+        .par_iter()
+        .enumerate()
+        .map(|(i, _)| {
+            let mut fv = token_features(tokens, i, template, &pos_tags);
+            insert_gazetteer_features(&mut fv, &lookups.entry(i).hits);
+            fv
+        })
+        .collect()
+}
+
+/// Features `in_*_gazetteer`, com o prior da entrada como valor.
+fn insert_gazetteer_features(fv: &mut FeatureVector, hits: &[(EntityCategory, f64)]) {
+    for &(category, prior) in hits {
+        let name = match category {
+            EntityCategory::Per => "in_person_gazetteer",
+            EntityCategory::Loc => "in_location_gazetteer",
+            EntityCategory::Org => "in_org_gazetteer",
+            EntityCategory::Misc => "in_misc_gazetteer",
+            EntityCategory::Date => "in_date_gazetteer",
+            EntityCategory::Time => "in_time_gazetteer",
+            EntityCategory::Custom(c) => {
+                fv.insert(format!("in_{}_gazetteer", c.name().to_lowercase()), prior);
+                continue;
+            }
+        };
+        fv.insert(name, prior);
+    }
+}
+
+/// Comprimentos dos prefixos do código de cluster emitidos como features.
+pub const CLUSTER_PREFIXES: [usize; 2] = [4, 6];
+
+/// Acrescenta as features `cluster{n}=` de um código de cluster de Brown ("0110...") com
+/// os `n` primeiros bits, ou o código inteiro se for mais curto ([`CLUSTER_PREFIXES`]).
+///
+/// ```rust
+/// use ner_core::features::{insert_cluster_features, FeatureVector};
+///
+/// let mut fv = FeatureVector::new(0);
+/// insert_cluster_features(&mut fv, "01101");
+/// assert!(fv.features.contains_key("cluster4=0110"));
+/// assert!(fv.features.contains_key("cluster6=01101"));
+/// ```
+pub fn insert_cluster_features(fv: &mut FeatureVector, path: &str) {
+    for n in CLUSTER_PREFIXES {
+        fv.insert(format!("cluster{n}={}", &path[..n.min(path.len())]), 1.0);
+    }
+}
+
+/// Resultado memorizado das consultas de uma chave normalizada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupEntry {
+    /// A chave ([`GazetteerKey::normalize`] do texto do token), ou a chave do gazetteer
+    /// de que ela é uma grafia variante (ver [`GazetteerLookups::with_variants`]).
+    pub key: String,
+    /// Categorias cujo gazetteer contém a chave, com o prior da entrada.
+    pub hits: Vec<(EntityCategory, f64)>,
+    /// Chave do texto quando `key` foi trocada pela grafia do gazetteer ("sam" → "são").
+    pub variant: Option<String>,
+}
+
+/// Consultas aos gazetteers de uma análise, memorizadas em dois níveis.
+///
+/// O primeiro nível liga cada forma de superfície ("Brasil", "BRASIL") à sua chave
+/// normalizada; o segundo guarda uma [`LookupEntry`] por chave distinta. Assim cada
+/// forma é normalizada uma vez e cada chave é consultada uma vez, por mais que se repita.
+/// A estrutura é montada antes dos estágios e só lida depois, então é compartilhada sem
+/// travas entre as threads da extração de features.
+///
+/// ```rust
+/// use ner_core::features::{GazetteerLookups, Gazetteers};
+/// use ner_core::tagger::EntityCategory;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let mut gaz = Gazetteers::new();
+/// gaz.locations.insert("brasil".to_string());
+///
+/// let tokens = tokenize("Brasil , BRASIL e brasil");
+/// let lookups = GazetteerLookups::new(&tokens, &gaz);
+/// assert_eq!(lookups.entries().len(), 3); // "brasil", ",", "e"
+/// assert_eq!(lookups.entry(2).key, "brasil");
+/// assert_eq!(lookups.entry(2).hits, vec![(EntityCategory::Loc, 1.0)]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GazetteerLookups {
+    /// Índice do token → posição da sua entrada em `entries`.
+    slots: Vec<usize>,
+    entries: Vec<LookupEntry>,
+}
+
+impl GazetteerLookups {
+    pub fn new(tokens: &[Token], gazetteers: &Gazetteers) -> Self {
+        Self::build(tokens, gazetteers, None)
+    }
+
+    /// Como [`GazetteerLookups::new`], trocando as chaves de tokens capitalizados que não
+    /// estão em nenhum gazetteer pela chave de que são grafia variante, se houver. Palavras
+    /// minúsculas ficam como estão: "para" não vira o estado do Pará.
+    ///
+    /// ```rust
+    /// use ner_core::features::{GazetteerLookups, Gazetteers};
+    /// use ner_core::tokenizer::tokenize;
+    /// use ner_core::variants::SpellingVariants;
+    ///
+    /// let mut gaz = Gazetteers::new();
+    /// gaz.locations.insert("bahia".to_string());
+    ///
+    /// let lookups = GazetteerLookups::with_variants(&tokenize("Baía"), &gaz, &SpellingVariants::new());
+    /// assert_eq!(lookups.entry(0).key, "bahia");
+    /// assert_eq!(lookups.entry(0).variant.as_deref(), Some("baía"));
+    /// ```
+    pub fn with_variants(tokens: &[Token], gazetteers: &Gazetteers, variants: &SpellingVariants) -> Self {
+        Self::build(tokens, gazetteers, Some(variants))
+    }
+
+    fn build(tokens: &[Token], gazetteers: &Gazetteers, variants: Option<&SpellingVariants>) -> Self {
+        let mut by_surface: HashMap<&str, usize> = HashMap::new();
+        // Só formas capitalizadas buscam variantes, então a chave sozinha não basta
+        let mut by_key: HashMap<(String, bool), usize> = HashMap::new();
+        let mut entries = Vec::new();
+        let slots = tokens
+            .iter()
+            .map(|token| {
+                *by_surface.entry(token.text.as_str()).or_insert_with(|| {
+                    let key = GazetteerKey::normalize(&token.text);
+                    let variants = variants.filter(|_| token.text.starts_with(char::is_uppercase));
+                    *by_key.entry((key, variants.is_some())).or_insert_with_key(|(key, _)| {
+                        let entry = match variants.and_then(|v| v.canonical(gazetteers, key)) {
+                            Some(canonical) => LookupEntry {
+                                hits: gazetteers.hits(&canonical),
+                                key: canonical,
+                                variant: Some(key.clone()),
+                            },
+                            None => LookupEntry { key: key.clone(), hits: gazetteers.hits(key), variant: None },
+                        };
+                        entries.push(entry);
+                        entries.len() - 1
+                    })
+                })
+            })
+            .collect();
+        Self { slots, entries }
+    }
+
+    /// Só as chaves normalizadas, sem gazetteers ([`LookupEntry::hits`] ficam vazias).
+    pub fn keys_only(tokens: &[Token]) -> Self {
+        Self::new(tokens, &Gazetteers::new())
+    }
+
+    /// Entrada do token `i`.
+    pub fn entry(&self, i: usize) -> &LookupEntry {
+        &self.entries[self.slots[i]]
+    }
 
-    if gazetteers.persons.contains(&word_lower)
-        || gazetteers.persons.contains(word.as_str())
-    {
-        fv.insert("in_person_gazetteer", 1.0);
+    /// Posição da entrada do token `i` em [`GazetteerLookups::entries`]; tokens com a
+    /// mesma chave têm a mesma posição.
+    pub fn slot(&self, i: usize) -> usize {
+        self.slots[i]
     }
-    if gazetteers.locations.contains(&word_lower)
-        || gazetteers.locations.contains(word.as_str())
-    {
-        fv.insert("in_location_gazetteer", 1.0);
+
+    /// Uma entrada por chave distinta, na ordem em que apareceram.
+    pub fn entries(&self) -> &[LookupEntry] {
+        &self.entries
     }
-    if gazetteers.organizations.contains(&word_lower)
-        || gazetteers.organizations.contains(word.as_str())
-    {
-        fv.insert("in_org_gazetteer", 1.0);
+
+    /// Número de tokens cobertos.
+    pub fn len(&self) -> usize {
+        self.slots.len()
     }
-    if gazetteers.misc.contains(&word_lower) || gazetteers.misc.contains(word.as_str()) {
-        fv.insert("in_misc_gazetteer", 1.0);
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
     }
+}
 
-    fv
+/// Translitera uma palavra para ASCII minúsculo: tremas e ligaduras viram a grafia
+/// alemã/escandinava usual ("Müller" → "mueller", "Strauß" → "strauss") e os demais
+/// diacríticos são removidos ("São" → "sao", "Łódź" → "lodz").
+pub fn transliterate(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    for c in word.chars().flat_map(char::to_lowercase) {
+        let mapped = match c {
+            'ä' | 'æ' => "ae",
+            'ö' | 'œ' => "oe",
+            'ü' => "ue",
+            'ß' => "ss",
+            'þ' => "th",
+            'á' | 'à' | 'â' | 'ã' | 'å' | 'ą' => "a",
+            'é' | 'è' | 'ê' | 'ë' | 'ę' | 'ě' => "e",
+            'í' | 'ì' | 'î' | 'ï' | 'ı' => "i",
+            'ó' | 'ò' | 'ô' | 'õ' | 'ø' => "o",
+            'ú' | 'ù' | 'û' | 'ů' => "u",
+            'ç' | 'č' | 'ć' => "c",
+            'ñ' | 'ń' | 'ň' => "n",
+            'š' | 'ś' | 'ş' => "s",
+            'ž' | 'ź' | 'ż' => "z",
+            'ý' | 'ÿ' => "y",
+            'ł' => "l",
+            'đ' | 'ð' | 'ď' => "d",
+            'ř' => "r",
+            'ğ' => "g",
+            'ť' => "t",
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        out.push_str(mapped);
+    }
+    out
+}
+
+/// Letras raras em palavras portuguesas.
+const FOREIGN_LETTERS: &[char] = &['k', 'w', 'y', 'ä', 'ö', 'ü', 'ß', 'ø', 'å', 'æ', 'ł', 'č', 'š', 'ž', 'ř'];
+
+/// N-gramas de caracteres comuns em nomes alemães, eslavos, ingleses e neerlandeses,
+/// mas atípicos do português.
+const FOREIGN_NGRAMS: &[&str] = &[
+    "sch", "tz", "ck", "th", "sh", "ph", "gh", "kh", "cz", "sz", "ff", "zz", "dt", "ij", "oo", "ee", "aa", "wr",
+];
+
+/// Finais de palavra possíveis em português ("-s", "-r", "-l", "-m", "-z", "-x", "-n").
+const PORTUGUESE_FINAL_CONSONANTS: &[char] = &['s', 'r', 'l', 'm', 'z', 'x', 'n'];
+
+/// Palavra capitalizada (3+ letras) com grafia atípica do português: letras raras
+/// ("Kubitschek", "Müller"), n-gramas estrangeiros ("Schmidt", "Rousseff") ou final em
+/// consoante que o português não usa ("Haddad", "Bach").
+pub fn looks_foreign(word: &str) -> bool {
+    let starts_upper = word.chars().next().is_some_and(char::is_uppercase);
+    if !starts_upper || word.chars().count() < 3 || !word.chars().all(char::is_alphabetic) {
+        return false;
+    }
+    let lower = word.to_lowercase();
+    let last = lower.chars().last().unwrap_or('a');
+    lower.chars().any(|c| FOREIGN_LETTERS.contains(&c))
+        || FOREIGN_NGRAMS.iter().any(|ngram| lower.contains(ngram))
+        || (last.is_ascii_alphabetic() && !"aeiou".contains(last) && !PORTUGUESE_FINAL_CONSONANTS.contains(&last))
+}
+
+/// Verifica se o token tem forma de ano (4 dígitos entre 1000 e 2099).
+pub fn is_year(word: &str) -> bool {
+    word.len() == 4
+        && word.chars().all(|c| c.is_ascii_digit())
+        && matches!(word.parse::<u32>(), Ok(1000..=2099))
+}
+
+/// Verifica se o token tem forma de horário brasileiro (ex: "14h", "14h30", "7h45min").
+pub fn is_time_expression(word: &str) -> bool {
+    let Some((hours, rest)) = word.split_once('h') else {
+        return false;
+    };
+    let minutes = rest.strip_suffix("min").unwrap_or(rest);
+    let valid_hours = matches!(hours.len(), 1 | 2)
+        && hours.chars().all(|c| c.is_ascii_digit())
+        && matches!(hours.parse::<u32>(), Ok(0..=23));
+    let valid_minutes = minutes.is_empty()
+        || (minutes.len() == 2
+            && minutes.chars().all(|c| c.is_ascii_digit())
+            && matches!(minutes.parse::<u32>(), Ok(0..=59)));
+    valid_hours && valid_minutes
 }
 
 #[cfg(test)]
@@ -292,7 +1102,29 @@ mod tests {
         // "Lula" é capitalizado
         assert_eq!(features[0].features.get("is_capitalized"), Some(&1.0));
         // "é" não é capitalizado
-        assert!(features[1].features.get("is_capitalized").is_none());
+        assert!(!features[1].features.contains_key("is_capitalized"));
+    }
+
+    #[test]
+    fn test_foreign_name_features() {
+        assert_eq!(transliterate("Müller"), "mueller");
+        assert_eq!(transliterate("Bündchen"), "buendchen");
+        assert_eq!(transliterate("Strauß"), "strauss");
+        assert_eq!(transliterate("São"), "sao");
+
+        for name in ["Kubitschek", "Müller", "Schmidt", "Rousseff", "Haddad", "Washington"] {
+            assert!(looks_foreign(name), "{name}");
+        }
+        for word in ["Bolsonaro", "Lula", "Petrobras", "Brasil", "São", "Itaú", "kit", "Fé"] {
+            assert!(!looks_foreign(word), "{word}");
+        }
+
+        let tokens = tokenize("a modelo Gisele Bündchen e o técnico Mueller");
+        let features = extract_features(&tokens, &Gazetteers::default());
+        assert!(features[3].features.contains_key("looks_foreign"));
+        assert!(!features[2].features.contains_key("looks_foreign"));
+        assert!(features[7].features.contains_key("translit=mueller"));
+        assert!(!features[0].features.keys().any(|k| k.starts_with("translit=")));
     }
 
     #[test]
@@ -328,5 +1160,174 @@ mod tests {
             features[0].features.get("in_location_gazetteer"),
             Some(&1.0)
         );
+
+        // O prior da entrada vira o valor da feature
+        gaz.set_prior(EntityCategory::Loc, "Brasília", 0.7);
+        let features = extract_features(&tokens, &gaz);
+        assert_eq!(features[0].features.get("in_location_gazetteer"), Some(&0.7));
+    }
+
+    #[test]
+    fn test_temporal_features() {
+        let tokens = tokenize("Em maio de 1888 , às 14h30");
+        let mut gaz = Gazetteers::default();
+        gaz.dates.insert("maio".to_string());
+
+        let features = extract_features(&tokens, &gaz);
+        assert!(features[1].features.contains_key("in_date_gazetteer"));
+        assert!(features[3].features.contains_key("is_year"));
+        assert!(features[6].features.contains_key("is_time"));
+        assert!(!is_time_expression("hoje"));
+        assert!(!is_time_expression("25h"));
+    }
+
+    #[test]
+    fn test_shape_and_kind_features() {
+        let tokens = tokenize("O Dr. Silva pagou R$ 10 ao STF-SP .");
+        let features = extract_features(&tokens, &Gazetteers::default());
+        assert!(features[2].features.contains_key("shape=Xxxxx"));
+        assert!(features[1].features.contains_key("kind=abbreviation"));
+        assert!(features[4].features.contains_key("kind=money"));
+        assert!(features[6].features.contains_key("shape=XXX-XX"));
+        assert!(features[7].features.contains_key("is_punctuation"));
+        assert!(!features[2].features.keys().any(|k| k.starts_with("kind=")));
+    }
+
+    #[test]
+    fn test_gazetteer_key_normalization() {
+        assert_eq!(GazetteerKey::normalize("  São   PAULO "), "são paulo");
+        assert_eq!(GazetteerKey::words("Rio de Janeiro"), vec!["rio", "janeiro"]);
+        assert_eq!(GazetteerKey::words("PT"), vec!["pt"]);
+
+        // Inserção e consulta com a mesma chave, independente da grafia do texto
+        let mut gaz = Gazetteers::new();
+        gaz.locations.extend(GazetteerKey::words("Rio de Janeiro"));
+        let tokens = tokenize("RIO de janeiro");
+        let features = extract_features(&tokens, &gaz);
+        assert!(features[0].features.contains_key("in_location_gazetteer"));
+        assert!(!features[1].features.contains_key("in_location_gazetteer"));
+        assert!(features[2].features.contains_key("in_location_gazetteer"));
+    }
+
+    #[test]
+    fn test_gazetteer_membership() {
+        let mut gaz = Gazetteers::new();
+        gaz.locations.extend(GazetteerKey::words("Rio de Janeiro"));
+        gaz.times.insert(GazetteerKey::normalize("meia-noite"));
+
+        assert!(gaz.contains_phrase(EntityCategory::Loc, "  rio   de JANEIRO "));
+        assert!(gaz.contains_phrase(EntityCategory::Time, "Meia-Noite"));
+        assert!(!gaz.contains_phrase(EntityCategory::Per, "Rio de Janeiro"));
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "Rio Branco"));
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "   "));
+        // Só palavras curtas: nada indexável, nem num gazetteer vazio
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "de da"));
+        assert!(!Gazetteers::new().contains_phrase(EntityCategory::Loc, "o a"));
+        assert!(!Gazetteers::new().contains_phrase(EntityCategory::Loc, "Rio de Janeiro"));
+
+        // Com nomes inseridos, palavras de entradas diferentes não formam uma frase
+        let mut gaz = Gazetteers::new();
+        gaz.insert(EntityCategory::Loc, "Rio de Janeiro");
+        gaz.insert(EntityCategory::Loc, "Belo Horizonte");
+        assert!(gaz.contains_phrase(EntityCategory::Loc, "rio de JANEIRO"));
+        assert!(gaz.contains_phrase(EntityCategory::Loc, "Horizonte"));
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "Janeiro Rio"));
+        assert!(!gaz.contains_phrase(EntityCategory::Loc, "Belo Janeiro"));
+        assert_eq!(gaz.check(EntityCategory::Loc, &[]), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_lookups_match_direct_extraction() {
+        let mut gaz = Gazetteers::default();
+        gaz.locations.extend(GazetteerKey::words("Rio de Janeiro"));
+        gaz.persons.insert("lula".to_string());
+        gaz.set_prior(EntityCategory::Loc, "Rio", 0.6);
+
+        let tokens = tokenize("Lula foi do Rio de Janeiro ao RIO e de lá a LULA");
+        let lookups = GazetteerLookups::new(&tokens, &gaz);
+        assert_eq!(lookups.len(), tokens.len());
+        // 13 tokens: "de" (2x), "Rio"/"RIO" e "Lula"/"LULA" dividem a entrada
+        assert_eq!(lookups.entries().len(), 10);
+        assert_eq!(lookups.slot(3), lookups.slot(7));
+        assert_eq!(lookups.entry(7).hits, vec![(EntityCategory::Loc, 0.6)]);
+
+        let template = FeatureTemplate::default();
+        let direct = template.extract(&tokens, &gaz);
+        let cached = template.extract_with_lookups(&tokens, &gaz, &lookups);
+        for (a, b) in direct.iter().zip(&cached) {
+            assert_eq!(a.features, b.features);
+        }
+
+        let keys = GazetteerLookups::keys_only(&tokens);
+        assert_eq!(keys.entry(12).key, "lula");
+        assert!(keys.entries().iter().all(|e| e.hits.is_empty()));
+    }
+
+    #[test]
+    fn test_feature_template_window() {
+        let tokens = tokenize("o Instituto Nacional de Pesquisas Espaciais alertou");
+        let gaz = Gazetteers::new();
+
+        // O template padrão é idêntico à extração histórica
+        let default = extract_features_with_template(&tokens, &gaz, &FeatureTemplate::default());
+        assert_eq!(default[5].features, extract_features(&tokens, &gaz)[5].features);
+        assert!(!default[5].features.contains_key("prev3_word=nacional"));
+
+        let wide = FeatureTemplate {
+            window: 4,
+            capitalization_window: 4,
+            context_bigram: false,
+            ..FeatureTemplate::default()
+        };
+        let features = extract_features_with_template(&tokens, &gaz, &wide);
+        let espaciais = &features[5].features;
+        assert!(espaciais.contains_key("prev_word=pesquisas"));
+        assert!(espaciais.contains_key("prev3_word=nacional"));
+        assert!(espaciais.contains_key("prev4_word=instituto"));
+        assert!(espaciais.contains_key("prev4_is_capitalized"));
+        assert!(!espaciais.contains_key("prev2_is_capitalized")); // "de"
+        assert!(!espaciais.keys().any(|k| k.starts_with("bigram=")));
+        assert!(features[1].features.contains_key("next4_word=espaciais"));
+    }
+
+    #[test]
+    fn test_lemma_features() {
+        let gaz = Gazetteers::new();
+        let governou = extract_features(&tokenize("Lula governou o país"), &gaz);
+        let governa = extract_features(&tokenize("Lula governa o país"), &gaz);
+        // Formas diferentes, mesmo radical no token e no contexto de "Lula"
+        assert!(governou[1].features.contains_key("lemma=govern"));
+        assert!(governa[1].features.contains_key("lemma=govern"));
+        assert!(governou[0].features.contains_key("next_lemma=govern"));
+        assert!(governa[0].features.contains_key("next_lemma=govern"));
+        assert!(governou[2].features.contains_key("prev_lemma=govern"));
+
+        // Números e pontuação não têm radical; o template pode desligar os radicais
+        let tokens = tokenize("Em 1888 , ali");
+        let features = extract_features(&tokens, &gaz);
+        assert!(!features[1].features.keys().any(|k| k.starts_with("lemma=")));
+        let plain = FeatureTemplate { lemmas: false, ..FeatureTemplate::default() };
+        let features = extract_features_with_template(&tokens, &gaz, &plain);
+        assert!(!features.iter().any(|fv| fv.features.keys().any(|k| k.contains("lemma="))));
+    }
+
+    #[test]
+    fn test_pos_features() {
+        let gaz = Gazetteers::new();
+        let tokens = tokenize("O presidente visitou a Petrobras em Brasília .");
+        let features = extract_features(&tokens, &gaz);
+        assert!(features[0].features.contains_key("pos=DET"));
+        assert!(!features[0].features.keys().any(|k| k.starts_with("prev_pos=")));
+        assert!(features[6].features.contains_key("pos=PROPN"));
+        assert!(features[6].features.contains_key("prev_pos=ADP"));
+
+        // A extração de um token isolado enxerga as mesmas classes que a da sentença
+        for (i, fv) in features.iter().enumerate() {
+            assert_eq!(extract_for_token(&tokens, i, &gaz).features, fv.features);
+        }
+
+        let plain = FeatureTemplate { pos: false, ..FeatureTemplate::default() };
+        let features = extract_features_with_template(&tokens, &gaz, &plain);
+        assert!(!features.iter().any(|fv| fv.features.keys().any(|k| k.contains("pos="))));
     }
 }
@@ -0,0 +1,217 @@
+//! # Feedback de Revisores
+//!
+//! Quem revisa os resultados do NER marca cada entidade como correta, errada ou com a
+//! categoria trocada. O [`FeedbackStore`] guarda essas marcações em um
+//! [`crate::storage::Storage`] (arquivos, memória ou um banco plugado pelo servidor) e as
+//! devolve como anotação ouro ([`GoldEntity`]), prontas para [`crate::diff`] ou para
+//! alimentar um novo treino.
+//!
+//! Cada marcação fica sob a chave `feedback/<documento>/<início>-<fim>`: marcar de novo
+//! o mesmo trecho substitui o veredito anterior.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use ner_core::feedback::{Feedback, FeedbackStore, Verdict};
+//! use ner_core::storage::MemoryStorage;
+//! use ner_core::tagger::EntityCategory;
+//!
+//! let store = FeedbackStore::new(Arc::new(MemoryStorage::new()));
+//! let text = "A Vale fica em Minas.";
+//! store.record(&Feedback::new("d1", 2, 6, &text[2..6], EntityCategory::Loc, Verdict::Relabel(EntityCategory::Org)))?;
+//!
+//! let gold = store.gold_entities("d1")?;
+//! assert_eq!(gold[0].category, EntityCategory::Org);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::GoldEntity;
+use crate::storage::{encode_key, get_json, put_json, Storage};
+use crate::tagger::EntityCategory;
+
+/// Decisão do revisor sobre uma entidade prevista.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    /// Entidade e categoria corretas.
+    Correct,
+    /// O trecho não é uma entidade.
+    Wrong,
+    /// É uma entidade, mas de outra categoria.
+    Relabel(EntityCategory),
+}
+
+/// Uma marcação de revisor sobre um trecho de um documento (offsets de byte, `end` exclusivo).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub doc_id: String,
+    pub start: usize,
+    pub end: usize,
+    /// Texto do trecho, para conferência sem o documento original.
+    pub text: String,
+    /// Categoria prevista pelo pipeline.
+    pub category: EntityCategory,
+    pub verdict: Verdict,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Momento da marcação (segundos desde a época Unix).
+    pub created_at: u64,
+}
+
+impl Feedback {
+    pub fn new(
+        doc_id: &str,
+        start: usize,
+        end: usize,
+        text: &str,
+        category: EntityCategory,
+        verdict: Verdict,
+    ) -> Self {
+        Self {
+            doc_id: doc_id.to_string(),
+            start,
+            end,
+            text: text.to_string(),
+            category,
+            verdict,
+            note: None,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Categoria correta segundo o revisor; `None` se o trecho não é entidade.
+    pub fn gold_category(&self) -> Option<EntityCategory> {
+        match self.verdict {
+            Verdict::Correct => Some(self.category),
+            Verdict::Wrong => None,
+            Verdict::Relabel(category) => Some(category),
+        }
+    }
+}
+
+/// Marcações de revisores persistidas em um [`Storage`].
+#[derive(Clone)]
+pub struct FeedbackStore {
+    storage: Arc<dyn Storage>,
+}
+
+impl FeedbackStore {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Grava a marcação, substituindo a anterior do mesmo trecho.
+    pub fn record(&self, feedback: &Feedback) -> io::Result<()> {
+        let key = format!("{}{}-{}", doc_prefix(&feedback.doc_id), feedback.start, feedback.end);
+        put_json(self.storage.as_ref(), &key, feedback)
+    }
+
+    /// Marcações de um documento, na ordem do texto.
+    pub fn for_document(&self, doc_id: &str) -> io::Result<Vec<Feedback>> {
+        self.load_prefix(&doc_prefix(doc_id))
+    }
+
+    /// Todas as marcações, agrupadas por documento.
+    pub fn all(&self) -> io::Result<Vec<Feedback>> {
+        self.load_prefix("feedback/")
+    }
+
+    /// Remove a marcação de um trecho. Retorna `false` se ela não existia.
+    pub fn remove(&self, doc_id: &str, start: usize, end: usize) -> io::Result<bool> {
+        self.storage.delete(&format!("{}{start}-{end}", doc_prefix(doc_id)))
+    }
+
+    /// Anotação ouro de um documento: as entidades confirmadas ou recategorizadas.
+    pub fn gold_entities(&self, doc_id: &str) -> io::Result<Vec<GoldEntity>> {
+        Ok(self
+            .for_document(doc_id)?
+            .iter()
+            .filter_map(|f| f.gold_category().map(|category| GoldEntity::new(f.start, f.end, category)))
+            .collect())
+    }
+
+    fn load_prefix(&self, prefix: &str) -> io::Result<Vec<Feedback>> {
+        let mut feedback = Vec::new();
+        for key in self.storage.keys(prefix)? {
+            if let Some(entry) = get_json::<Feedback>(self.storage.as_ref(), &key)? {
+                feedback.push(entry);
+            }
+        }
+        // As chaves ordenam "10-19" antes de "2-5": reordena pelos offsets
+        feedback.sort_by(|a, b| (&a.doc_id, a.start, a.end).cmp(&(&b.doc_id, b.start, b.end)));
+        Ok(feedback)
+    }
+}
+
+/// Prefixo das chaves de um documento. O `doc_id` é codificado: "a/b" não pode cair
+/// dentro do prefixo de "a".
+fn doc_prefix(doc_id: &str) -> String {
+    format!("feedback/{}/", encode_key(doc_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FileStorage, MemoryStorage};
+
+    fn exercise(store: &FeedbackStore) {
+        let text = "Lula visitou a Vale em Itabira em 2010.";
+        let mark = |word: &str, category, verdict| {
+            let start = text.find(word).unwrap();
+            Feedback::new("doc-1", start, start + word.len(), word, category, verdict)
+        };
+        store.record(&mark("Vale", EntityCategory::Loc, Verdict::Correct)).unwrap();
+        store.record(&mark("Lula", EntityCategory::Per, Verdict::Correct)).unwrap();
+        store.record(&mark("Itabira", EntityCategory::Org, Verdict::Wrong).with_note("é cidade")).unwrap();
+        store.record(&mark("2010", EntityCategory::Misc, Verdict::Relabel(EntityCategory::Date))).unwrap();
+        // Segunda revisão do mesmo trecho substitui a primeira
+        store.record(&mark("Vale", EntityCategory::Loc, Verdict::Relabel(EntityCategory::Org))).unwrap();
+        store.record(&Feedback::new("doc-2", 0, 4, "Lula", EntityCategory::Per, Verdict::Correct)).unwrap();
+
+        let marks = store.for_document("doc-1").unwrap();
+        assert_eq!(marks.len(), 4);
+        assert_eq!(marks[0].text, "Lula");
+        assert_eq!(marks[2].note.as_deref(), Some("é cidade"));
+        assert_eq!(store.all().unwrap().len(), 5);
+
+        let gold: Vec<(&str, EntityCategory)> = store
+            .gold_entities("doc-1")
+            .unwrap()
+            .iter()
+            .map(|g| (&text[g.start..g.end], g.category))
+            .collect();
+        assert_eq!(gold, vec![("Lula", EntityCategory::Per), ("Vale", EntityCategory::Org), ("2010", EntityCategory::Date)]);
+
+        assert!(store.remove("doc-2", 0, 4).unwrap());
+        assert!(store.for_document("doc-2").unwrap().is_empty());
+
+        // Um doc_id com "/" não se mistura ao documento cujo id é o seu prefixo
+        store.record(&Feedback::new("a", 0, 4, "Lula", EntityCategory::Per, Verdict::Correct)).unwrap();
+        store.record(&Feedback::new("a/b", 0, 4, "Lula", EntityCategory::Per, Verdict::Wrong)).unwrap();
+        let docs = |id: &str| store.for_document(id).unwrap().into_iter().map(|f| f.doc_id).collect::<Vec<_>>();
+        assert_eq!(docs("a"), ["a"]);
+        assert_eq!(docs("a/b"), ["a/b"]);
+        assert!(store.remove("a", 0, 4).unwrap());
+        assert_eq!(docs("a/b"), ["a/b"]);
+    }
+
+    #[test]
+    fn test_feedback_in_memory_and_files() {
+        exercise(&FeedbackStore::new(Arc::new(MemoryStorage::new())));
+
+        let dir = std::env::temp_dir().join(format!("ner_feedback_{}", std::process::id()));
+        exercise(&FeedbackStore::new(Arc::new(FileStorage::new(&dir).unwrap())));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+//! # Versão do Formato dos Artefatos Salvos
+//!
+//! Modelos e recursos gravados em disco sobrevivem a atualizações do crate, mas o layout
+//! deles muda: um campo novo no `NerModel` desloca todos os bytes do `bincode`, e ler
+//! um arquivo antigo com o layout novo pode "funcionar" com pesos trocados. Todo
+//! artefato gravado por este crate carrega [`MODEL_FORMAT_VERSION`]:
+//!
+//! - **Modelo** ([`crate::model::NerModel::save`], binário): cabeçalho com os bytes
+//!   [`MODEL_MAGIC`] e a versão (`u32` little-endian) antes do conteúdo.
+//! - **JSON** (pacotes de idioma com os gazetteers, tabelas de merges BPE, configuração
+//!   do tokenizador): campo `"format_version"` ao lado dos demais.
+//!
+//! Na leitura, uma versão mais nova que a do crate, ou um modelo binário sem cabeçalho
+//! (gravado antes do versionamento), gera um [`FormatError`] claro dentro do
+//! `io::Error` (tipo `InvalidData`; recupere-o com `get_ref` e `downcast_ref`). Versões
+//! antigas ainda suportadas passam por uma migração explícita: os JSON sem
+//! `"format_version"` têm o mesmo layout da versão 1 e são lidos normalmente.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::format::{FormatError, MODEL_FORMAT_VERSION};
+//! use ner_core::tokenizer::BpeMergeTable;
+//!
+//! let path = std::env::temp_dir().join(format!("ner_doc_format_{}.json", std::process::id()));
+//! BpeMergeTable::new("teste", vec![]).save(&path).unwrap();
+//! let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+//! assert_eq!(json["format_version"], MODEL_FORMAT_VERSION);
+//!
+//! // Um arquivo de uma versão futura é recusado com um erro claro
+//! std::fs::write(&path, r#"{"format_version": 99, "profile": "x", "merges": []}"#).unwrap();
+//! let err = BpeMergeTable::load(&path).unwrap_err();
+//! let format = err.get_ref().and_then(|e| e.downcast_ref::<FormatError>()).unwrap();
+//! assert_eq!(*format, FormatError::TooNew { found: 99, supported: MODEL_FORMAT_VERSION });
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Versão atual do formato de todos os artefatos gravados pelo crate.
+///
+/// Incrementada sempre que o layout de um artefato muda; a leitura de versões
+/// anteriores ganha então um passo de migração (ou um [`FormatError::TooOld`]).
+pub const MODEL_FORMAT_VERSION: u32 = 1;
+
+/// Versão mais antiga que ainda é lida (com migração, se preciso).
+pub const MIN_SUPPORTED_FORMAT_VERSION: u32 = 1;
+
+/// Bytes iniciais de um modelo binário, antes da versão.
+pub const MODEL_MAGIC: [u8; 4] = *b"NERM";
+
+/// Campo com a versão nos artefatos JSON.
+const VERSION_FIELD: &str = "format_version";
+
+/// Artefato incompatível com esta versão do crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatError {
+    /// Gravado por uma versão mais nova do crate.
+    TooNew { found: u32, supported: u32 },
+    /// Gravado numa versão que não é mais lida.
+    TooOld { found: u32, min_supported: u32 },
+    /// Modelo binário sem cabeçalho, gravado antes do versionamento.
+    Unversioned,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooNew { found, supported } => {
+                write!(f, "artefato no formato {found}, mais novo que o suportado ({supported}); atualize o ner-core")
+            }
+            Self::TooOld { found, min_supported } => {
+                write!(f, "artefato no formato {found}, anterior ao mínimo suportado ({min_supported}); gere-o novamente")
+            }
+            Self::Unversioned => {
+                write!(f, "modelo sem versão de formato (gravado antes do versionamento); reconstrua-o e salve de novo")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<FormatError> for io::Error {
+    fn from(e: FormatError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Confere se `version` pode ser lida por este crate.
+pub fn check_version(version: u32) -> Result<(), FormatError> {
+    if version > MODEL_FORMAT_VERSION {
+        return Err(FormatError::TooNew { found: version, supported: MODEL_FORMAT_VERSION });
+    }
+    if version < MIN_SUPPORTED_FORMAT_VERSION {
+        return Err(FormatError::TooOld { found: version, min_supported: MIN_SUPPORTED_FORMAT_VERSION });
+    }
+    Ok(())
+}
+
+/// Grava `value` em `bincode` precedido do cabeçalho ([`MODEL_MAGIC`] e versão).
+pub(crate) fn save_binary<T: Serialize>(path: impl AsRef<Path>, value: &T) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&MODEL_MAGIC)?;
+    writer.write_all(&MODEL_FORMAT_VERSION.to_le_bytes())?;
+    bincode::serialize_into(writer, value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Lê um arquivo gravado com [`save_binary`], conferindo o cabeçalho.
+pub(crate) fn load_binary<T: DeserializeOwned>(path: impl AsRef<Path>) -> io::Result<T> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => FormatError::Unversioned.into(),
+        _ => e,
+    })?;
+    if header[..4] != MODEL_MAGIC {
+        return Err(FormatError::Unversioned.into());
+    }
+    let version = u32::from_le_bytes(header[4..].try_into().expect("4 bytes"));
+    check_version(version)?;
+    // Migrações entre versões binárias entram aqui quando o formato mudar
+    bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Grava `value` (um objeto JSON) com o campo `"format_version"`.
+pub(crate) fn save_json<T: Serialize>(path: impl AsRef<Path>, value: &T) -> io::Result<()> {
+    let mut json = serde_json::to_value(value)?;
+    if let Some(object) = json.as_object_mut() {
+        object.insert(VERSION_FIELD.to_string(), MODEL_FORMAT_VERSION.into());
+    }
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, &json)?;
+    Ok(())
+}
+
+/// Lê um JSON gravado com [`save_json`]. Sem `"format_version"`, o arquivo é da época
+/// anterior ao versionamento, com o mesmo layout da versão 1.
+pub(crate) fn load_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> io::Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut json: serde_json::Value = serde_json::from_reader(reader)?;
+    let version = match json.as_object_mut().and_then(|object| object.remove(VERSION_FIELD)) {
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{VERSION_FIELD} inválido: {version}")))?,
+        None => 1,
+    };
+    check_version(version)?;
+    Ok(serde_json::from_value(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Artifact {
+        name: String,
+        weights: Vec<f64>,
+    }
+
+    fn format_error(err: &io::Error) -> Option<FormatError> {
+        err.get_ref().and_then(|e| e.downcast_ref::<FormatError>()).copied()
+    }
+
+    #[test]
+    fn test_binary_and_json_round_trip_with_version() {
+        let dir = std::env::temp_dir();
+        let artifact = Artifact { name: "crf".into(), weights: vec![0.5, -1.0] };
+
+        let bin = dir.join(format!("ner_format_{}.bin", std::process::id()));
+        save_binary(&bin, &artifact).unwrap();
+        assert_eq!(load_binary::<Artifact>(&bin).unwrap(), artifact);
+
+        // Sem cabeçalho (anterior ao versionamento) ou de uma versão futura: erro claro
+        std::fs::write(&bin, bincode::serialize(&artifact).unwrap()).unwrap();
+        assert_eq!(format_error(&load_binary::<Artifact>(&bin).unwrap_err()), Some(FormatError::Unversioned));
+        let mut future = MODEL_MAGIC.to_vec();
+        future.extend_from_slice(&(MODEL_FORMAT_VERSION + 1).to_le_bytes());
+        future.extend(bincode::serialize(&artifact).unwrap());
+        std::fs::write(&bin, future).unwrap();
+        let err = load_binary::<Artifact>(&bin).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(format_error(&err), Some(FormatError::TooNew { .. })));
+        std::fs::remove_file(&bin).unwrap();
+
+        // JSON: o campo de versão não vaza para o tipo, e arquivos sem ele ainda são lidos
+        let json = dir.join(format!("ner_format_{}.json", std::process::id()));
+        save_json(&json, &artifact).unwrap();
+        assert_eq!(load_json::<Artifact>(&json).unwrap(), artifact);
+        std::fs::write(&json, serde_json::to_string(&artifact).unwrap()).unwrap();
+        assert_eq!(load_json::<Artifact>(&json).unwrap(), artifact);
+        std::fs::write(&json, r#"{"format_version": 0, "name": "x", "weights": []}"#).unwrap();
+        assert!(matches!(format_error(&load_json::<Artifact>(&json).unwrap_err()), Some(FormatError::TooOld { .. })));
+        std::fs::remove_file(&json).unwrap();
+    }
+}
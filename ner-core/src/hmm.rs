@@ -10,10 +10,105 @@
 //! 3. Probabilidade Inicial: P(tag_inicial)
 //!
 //! A decodificação é feita via algoritmo de Viterbi, maximizando P(tags | palavras).
+//!
+//! ## Palavras Desconhecidas
+//!
+//! Um HMM puro atribui a toda palavra fora do vocabulário a mesma emissão `<UNK>`,
+//! então "Mariana" (nunca vista) não se distingue de "ontem" (nunca vista). Para isso,
+//! a emissão é **interpolada** com um modelo de n-gramas de caracteres por tag
+//! ([`CharNgramModel`]):
+//!
+//! ```text
+//! log P(w | t) = λ · log P_palavra(w | t) + (1 − λ) · Σ log P(c_i | c_{i−2} c_{i−1}, t)
+//! ```
+//!
+//! O modelo de caracteres aprende, por exemplo, que palavras de `B-PER` começam com
+//! maiúscula e terminam em "-ana", "-son", "-ilma".
 
 use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
-use crate::corpus::AnnotatedSentence;
+use crate::backend::SequenceTagger;
+use crate::corpus::{AnnotatedSentence, OwnedAnnotatedSentence};
+use crate::viterbi::forward_backward;
+
+/// Peso padrão (λ) da emissão por palavra na interpolação com o modelo de caracteres.
+pub const DEFAULT_WORD_WEIGHT: f64 = 0.7;
+
+/// Marcadores de início e fim de palavra usados nos trigramas de caracteres.
+const WORD_START: char = '^';
+const WORD_END: char = '$';
+
+/// Modelo de trigramas de caracteres por tag: $P(w | t) = \prod_i P(c_i | c_{i-2} c_{i-1}, t)$.
+///
+/// A palavra é delimitada por marcadores ("^^Lula$"), de modo que o modelo também aprende
+/// como palavras de cada tag começam (maiúscula?) e terminam (sufixos).
+/// As probabilidades usam *Add-1 Smoothing* sobre o alfabeto visto no treino.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CharNgramModel {
+    /// Contagem de trigramas por tag. Chave: `tag` → `"^Lu"`.
+    trigram_counts: HashMap<String, HashMap<String, u32>>,
+    /// Contagem dos contextos (dois primeiros caracteres do trigrama) por tag.
+    context_counts: HashMap<String, HashMap<String, u32>>,
+    /// Número de caracteres distintos vistos (mais um para caracteres inéditos).
+    alphabet_size: usize,
+}
+
+impl CharNgramModel {
+    /// Treina a partir de pares `(palavra, tag)`.
+    pub fn train<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut model = Self::default();
+        let mut alphabet: HashSet<char> = HashSet::new();
+        for (word, tag) in pairs {
+            model.observe(&mut alphabet, word, tag);
+        }
+        model.finish(&alphabet);
+        model
+    }
+
+    /// Conta os trigramas de um par `(palavra, tag)`.
+    fn observe(&mut self, alphabet: &mut HashSet<char>, word: &str, tag: &str) {
+        alphabet.extend(word.chars());
+        for trigram in char_trigrams(word) {
+            let context: String = trigram.chars().take(2).collect();
+            *self.trigram_counts.entry(tag.to_string()).or_default().entry(trigram).or_insert(0) += 1;
+            *self.context_counts.entry(tag.to_string()).or_default().entry(context).or_insert(0) += 1;
+        }
+    }
+
+    fn finish(&mut self, alphabet: &HashSet<char>) {
+        self.alphabet_size = alphabet.len() + 2; // + marcador de fim + caractere inédito
+    }
+
+    /// $\log P(w | t)$: soma dos log-probs dos trigramas de caracteres da palavra.
+    pub fn log_prob(&self, word: &str, tag: &str) -> f64 {
+        let alphabet = self.alphabet_size.max(1) as f64;
+        let trigrams = self.trigram_counts.get(tag);
+        let contexts = self.context_counts.get(tag);
+        char_trigrams(word)
+            .into_iter()
+            .map(|trigram| {
+                let context: String = trigram.chars().take(2).collect();
+                let count = trigrams.and_then(|t| t.get(&trigram)).copied().unwrap_or(0) as f64;
+                let context_count = contexts.and_then(|c| c.get(&context)).copied().unwrap_or(0) as f64;
+                ((count + 1.0) / (context_count + alphabet)).ln()
+            })
+            .sum()
+    }
+}
+
+/// Trigramas de caracteres da palavra delimitada: "Rio" → `["^^R", "^Ri", "Rio", "io$"]`.
+fn char_trigrams(word: &str) -> Vec<String> {
+    let chars: Vec<char> = [WORD_START, WORD_START]
+        .into_iter()
+        .chain(word.chars())
+        .chain([WORD_END])
+        .collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+fn default_word_weight() -> f64 {
+    DEFAULT_WORD_WEIGHT
+}
 
 
 /// Modelo HMM (Hidden Markov Model) treinado para NER.
@@ -42,6 +137,12 @@ pub struct HmmModel {
     all_tags: Vec<String>,
     /// Vocabulário conhecido (para identificar e tratar tokens desconhecidos `<UNK>`).
     vocab: HashSet<String>,
+    /// Emissão por n-gramas de caracteres, interpolada com a emissão por palavra.
+    #[serde(default)]
+    char_model: CharNgramModel,
+    /// Peso λ da emissão por palavra (1.0 desliga o modelo de caracteres).
+    #[serde(default = "default_word_weight")]
+    word_weight: f64,
 }
 
 impl HmmModel {
@@ -52,9 +153,22 @@ impl HmmModel {
             start_probs: HashMap::new(),
             all_tags: Vec::new(),
             vocab: HashSet::new(),
+            char_model: CharNgramModel::default(),
+            word_weight: DEFAULT_WORD_WEIGHT,
         }
     }
 
+    /// Define o peso λ da emissão por palavra (entre 0 e 1; o restante vai para os caracteres).
+    pub fn with_word_weight(mut self, weight: f64) -> Self {
+        self.word_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Indica se o modelo já foi treinado (possui tags conhecidas).
+    pub fn is_trained(&self) -> bool {
+        !self.all_tags.is_empty()
+    }
+
     /// Treina o HMM com o corpus fornecido (Supervised Learning).
     ///
     /// # Processo de Treinamento
@@ -63,6 +177,7 @@ impl HmmModel {
     /// 2. **Smoothing (Suavização)**: Aplica *Add-1 Smoothing* (Laplace) para garantir que
     ///    nenhuma probabilidade seja zero (o que quebraria o logaritmo).
     /// 3. **Log-Probabilidades**: Converte tudo para logaritmo para estabilidade numérica.
+    /// 4. **Caracteres**: Treina o [`CharNgramModel`] com os mesmos pares (palavra, tag).
     ///
     /// # Exemplo
     /// ```rust
@@ -70,6 +185,16 @@ impl HmmModel {
     /// // P("Lula" | "B-PER") = count("Lula", "B-PER") / count("B-PER")
     /// ```
     pub fn train(&mut self, corpus: &[AnnotatedSentence]) {
+        self.train_stream(corpus.iter().map(OwnedAnnotatedSentence::from));
+    }
+
+    /// Como [`Self::train`], mas lendo as sentenças de um iterador (ex: um
+    /// [`crate::corpus_reader::CorpusReader`]). O HMM só conta frequências, então uma
+    /// única passada basta e nenhuma sentença fica em memória.
+    pub fn train_stream(&mut self, corpus: impl IntoIterator<Item = OwnedAnnotatedSentence>) {
+        let mut char_model = CharNgramModel::default();
+        let mut alphabet: HashSet<char> = HashSet::new();
+        let mut sentence_count = 0usize;
         let mut transition_counts: HashMap<(String, String), u32> = HashMap::new();
         let mut emission_counts: HashMap<(String, String), u32> = HashMap::new();
         let mut start_counts: HashMap<String, u32> = HashMap::new();
@@ -79,9 +204,11 @@ impl HmmModel {
 
         // 1. Contagem das frequências brutas
         for sentence in corpus {
+            sentence_count += 1;
             let mut prev_tag: Option<String> = None;
 
             for (i, (word, tag)) in sentence.annotations.iter().enumerate() {
+                char_model.observe(&mut alphabet, word, tag);
                 let w = word.to_string();
                 let t = tag.to_string();
 
@@ -113,7 +240,7 @@ impl HmmModel {
         let num_tags = self.all_tags.len() as f64;
 
         // Probabilidades Iniciais P(tag)
-        let total_starts = corpus.len() as f64;
+        let total_starts = sentence_count as f64;
         for tag in &self.all_tags {
             let count = *start_counts.get(tag).unwrap_or(&0) as f64;
             // Add-1 smoothing: evita log(0) se uma tag nunca começar frases (raro, mas possível)
@@ -150,6 +277,24 @@ impl HmmModel {
             let prob_unk = 1.0 / (tag_count + vocab_size + 1.0);
             self.emission_probs.insert((tag.clone(), "<UNK>".to_string()), prob_unk.ln());
         }
+
+        // 3. Modelo de caracteres por tag
+        char_model.finish(&alphabet);
+        self.char_model = char_model;
+    }
+
+    /// Emissão interpolada $\log P(w | t)$ (palavra + caracteres), em log-space.
+    fn emission(&self, tag: &str, word: &str) -> f64 {
+        let key = if self.vocab.contains(word) { word } else { "<UNK>" };
+        let word_lp = self
+            .emission_probs
+            .get(&(tag.to_string(), key.to_string()))
+            .copied()
+            .unwrap_or(f64::NEG_INFINITY);
+        if self.word_weight >= 1.0 {
+            return word_lp;
+        }
+        self.word_weight * word_lp + (1.0 - self.word_weight) * self.char_model.log_prob(word, tag)
     }
 
     /// Decodifica uma sequência de tokens para encontrar a melhor sequência de tags.
@@ -176,20 +321,16 @@ impl HmmModel {
         let mut backptr = vec![vec![0usize; n_tags]; n_tokens];
 
         // 1. Inicialização (t=0)
-        let first_token = if self.vocab.contains(&tokens[0]) { &tokens[0] } else { "<UNK>" };
-        
         for (s, tag) in self.all_tags.iter().enumerate() {
             let start_p = self.start_probs.get(tag).cloned().unwrap_or(f64::NEG_INFINITY);
-            let emit_p = self.emission_probs.get(&(tag.clone(), first_token.to_string())).cloned().unwrap_or(f64::NEG_INFINITY);
+            let emit_p = self.emission(tag, &tokens[0]);
             viterbi[0][s] = start_p + emit_p;
         }
 
         // 2. Recursão (t=1..N)
         for t in 1..n_tokens {
-            let token = if self.vocab.contains(&tokens[t]) { &tokens[t] } else { "<UNK>" };
-            
             for (s, curr_tag) in self.all_tags.iter().enumerate() {
-                let emit_p = self.emission_probs.get(&(curr_tag.clone(), token.to_string())).cloned().unwrap_or(f64::NEG_INFINITY);
+                let emit_p = self.emission(curr_tag, &tokens[t]);
                 
                 let mut best_prob = f64::NEG_INFINITY;
                 let mut best_prev = 0;
@@ -213,9 +354,9 @@ impl HmmModel {
         let mut best_last_prob = f64::NEG_INFINITY;
         let mut best_last_tag_idx = 0;
         
-        for s in 0..n_tags {
-            if viterbi[n_tokens-1][s] > best_last_prob {
-                best_last_prob = viterbi[n_tokens-1][s];
+        for (s, &prob) in viterbi[n_tokens-1].iter().enumerate() {
+            if prob > best_last_prob {
+                best_last_prob = prob;
                 best_last_tag_idx = s;
             }
         }
@@ -233,6 +374,67 @@ impl HmmModel {
 
         best_path
     }
+
+    /// Como [`HmmModel::predict`], com a probabilidade posterior da tag escolhida em cada
+    /// token (ver [`HmmModel::marginals`]).
+    pub fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
+        let posteriors = self.marginals(tokens);
+        self.predict(tokens)
+            .into_iter()
+            .enumerate()
+            .map(|(i, tag)| {
+                let p = self.all_tags.iter().position(|t| *t == tag).and_then(|t| posteriors.get(i)?.get(t)).copied();
+                (tag, p.unwrap_or(0.0))
+            })
+            .collect()
+    }
+
+    /// $P(y_i = t | x)$ de cada token e tag (na ordem das tags conhecidas), somando todos
+    /// os caminhos com o algoritmo forward-backward.
+    pub fn marginals(&self, tokens: &[String]) -> Vec<Vec<f64>> {
+        let emission: Vec<Vec<f64>> =
+            tokens.iter().map(|word| self.all_tags.iter().map(|tag| self.emission(tag, word)).collect()).collect();
+        let transition: Vec<Vec<f64>> = self
+            .all_tags
+            .iter()
+            .map(|prev| {
+                self.all_tags
+                    .iter()
+                    .map(|curr| self.transition_probs.get(&(prev.clone(), curr.clone())).copied().unwrap_or(f64::NEG_INFINITY))
+                    .collect()
+            })
+            .collect();
+        forward_backward(
+            tokens.len(),
+            self.all_tags.len(),
+            |t| self.start_probs.get(&self.all_tags[t]).copied().unwrap_or(f64::NEG_INFINITY) + emission[0][t],
+            |i, u, t| transition[u][t] + emission[i][t],
+        )
+    }
+}
+
+impl Default for HmmModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceTagger for HmmModel {
+    fn name(&self) -> &str {
+        "hmm"
+    }
+
+    fn predict(&self, tokens: &[String]) -> Vec<String> {
+        HmmModel::predict(self, tokens)
+    }
+
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
+        HmmModel::predict_with_confidence(self, tokens)
+    }
+
+    fn is_trained(&self) -> bool {
+        HmmModel::is_trained(self)
+    }
 }
 
 #[cfg(test)]
@@ -287,4 +489,25 @@ mod tests {
         // Pelo menos o tamanho deve ser igual
         assert_eq!(tags.len(), 3);
     }
+
+    #[test]
+    fn test_char_ngrams_for_unseen_names() {
+        // Início de sentença e transições empatados entre B-PER e B-LOC: só a emissão decide
+        let corpus = vec![
+            AnnotatedSentence { text: "", domain: "test", annotations: &[("Dilma", "B-PER"), ("visitou", "O"), ("Curitiba", "B-LOC")] },
+            AnnotatedSentence { text: "", domain: "test", annotations: &[("Curitiba", "B-LOC"), ("recebeu", "O"), ("Dilma", "B-PER")] },
+            AnnotatedSentence { text: "", domain: "test", annotations: &[("Mariana", "B-PER"), ("visitou", "O"), ("Salvador", "B-LOC")] },
+            AnnotatedSentence { text: "", domain: "test", annotations: &[("Salvador", "B-LOC"), ("recebeu", "O"), ("Mariana", "B-PER")] },
+        ];
+        let mut model = HmmModel::new();
+        model.train(&corpus);
+
+        assert_eq!(char_trigrams("Rio"), vec!["^^R", "^Ri", "Rio", "io$"]);
+        assert!(model.char_model.log_prob("Dilmara", "B-PER") > model.char_model.log_prob("Dilmara", "B-LOC"));
+        assert!(model.char_model.log_prob("Curitibanos", "B-LOC") > model.char_model.log_prob("Curitibanos", "B-PER"));
+
+        let tokens = |ws: &[&str]| ws.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(model.predict(&tokens(&["Dilmara", "visitou", "Salvador"]))[0], "B-PER");
+        assert_eq!(model.predict(&tokens(&["Curitibanos", "recebeu", "Mariana"]))[0], "B-LOC");
+    }
 }
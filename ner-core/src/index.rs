@@ -0,0 +1,342 @@
+//! # Índice Invertido de Entidades
+//!
+//! Transforma os resultados do NER em um pequeno **motor de busca por entidades**.
+//! Depois de analisar muitos documentos, queremos responder perguntas como:
+//!
+//! > "Quais documentos mencionam ORG=Petrobras **e** LOC=Bacia de Santos?"
+//!
+//! ## Estrutura
+//!
+//! Um índice invertido associa cada **termo** (categoria + texto normalizado da entidade)
+//! à lista de documentos onde ele aparece (a *posting list*):
+//!
+//! ```text
+//! ORG|petrobras         → [0, 2, 5]
+//! LOC|bacia de santos   → [2, 7]
+//! ```
+//!
+//! Os documentos são identificados internamente por números (`u32`) e as posting lists
+//! ficam ordenadas, de modo que a consulta com vários termos é uma interseção linear
+//! de listas ordenadas. O índice é serializado em JSON compacto com [`EntityIndex::save`].
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::index::EntityIndex;
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let pipeline = NerPipeline::new();
+//! let mut index = EntityIndex::new();
+//! for (id, text) in [("d1", "A Petrobras anunciou lucro."), ("d2", "O Brasil venceu.")] {
+//!     let (_, entities) =
+//!         pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!     index.add_document(id, &entities);
+//! }
+//! assert_eq!(index.search("ORG=Petrobras").unwrap(), vec!["d1"]);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{get_json, put_json, Storage};
+use crate::tagger::{EntityCategory, EntitySpan};
+
+/// Erro ao interpretar uma consulta textual (ex: `"ORG=Petrobras AND LOC=Brasil"`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// A consulta não contém nenhum termo.
+    Empty,
+    /// Um termo não segue o formato `CATEGORIA=texto`.
+    MalformedTerm(String),
+    /// A categoria do termo não existe (ex: `"XYZ=foo"`).
+    UnknownCategory(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Empty => write!(f, "consulta vazia"),
+            QueryError::MalformedTerm(term) => {
+                write!(f, "termo '{term}' não segue o formato CATEGORIA=texto")
+            }
+            QueryError::UnknownCategory(cat) => write!(f, "categoria desconhecida: '{cat}'"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Índice invertido `(categoria, entidade) → documentos`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityIndex {
+    /// Identificadores externos dos documentos; a posição é o número interno.
+    documents: Vec<String>,
+    /// Termo (`"ORG|petrobras"`) → números dos documentos, ordenados e sem repetição.
+    postings: BTreeMap<String, Vec<u32>>,
+    /// Identificador externo → número interno (reconstruído ao carregar).
+    #[serde(skip)]
+    doc_numbers: HashMap<String, u32>,
+}
+
+impl EntityIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexa as entidades de um documento.
+    ///
+    /// Se o identificador já existir, as novas entidades são somadas às já indexadas.
+    /// Retorna o número interno do documento.
+    pub fn add_document(&mut self, doc_id: &str, entities: &[EntitySpan]) -> u32 {
+        let doc = match self.doc_numbers.get(doc_id) {
+            Some(&doc) => doc,
+            None => {
+                let doc = self.documents.len() as u32;
+                self.documents.push(doc_id.to_string());
+                self.doc_numbers.insert(doc_id.to_string(), doc);
+                doc
+            }
+        };
+
+        for entity in entities {
+            let list = self
+                .postings
+                .entry(term_key(entity.category, &entity.text))
+                .or_default();
+            // Documentos chegam em ordem crescente, exceto quando um id é reindexado
+            if let Err(pos) = list.binary_search(&doc) {
+                list.insert(pos, doc);
+            }
+        }
+        doc
+    }
+
+    /// Documentos que mencionam **todos** os termos `(categoria, texto)` informados.
+    ///
+    /// A comparação de texto ignora maiúsculas e espaços repetidos.
+    /// Uma consulta sem termos não retorna nenhum documento.
+    pub fn query(&self, terms: &[(EntityCategory, &str)]) -> Vec<&str> {
+        let mut lists: Vec<&[u32]> = Vec::with_capacity(terms.len());
+        for (category, text) in terms {
+            match self.postings.get(&term_key(*category, text)) {
+                Some(list) => lists.push(list),
+                None => return vec![],
+            }
+        }
+        // Começa pela lista mais curta para minimizar o trabalho da interseção
+        lists.sort_by_key(|l| l.len());
+
+        let Some((first, rest)) = lists.split_first() else {
+            return vec![];
+        };
+        let mut result: Vec<u32> = first.to_vec();
+        for list in rest {
+            result = intersect_sorted(&result, list);
+        }
+
+        result
+            .into_iter()
+            .map(|doc| self.documents[doc as usize].as_str())
+            .collect()
+    }
+
+    /// Interpreta e executa uma consulta textual como `"ORG=Petrobras AND LOC=Bacia de Santos"`.
+    ///
+    /// Os termos podem ser separados por `AND` ou por `;`.
+    pub fn search(&self, query: &str) -> Result<Vec<&str>, QueryError> {
+        let terms = parse_query(query)?;
+        let borrowed: Vec<(EntityCategory, &str)> =
+            terms.iter().map(|(c, t)| (*c, t.as_str())).collect();
+        Ok(self.query(&borrowed))
+    }
+
+    /// Número de documentos indexados.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Número de termos distintos no índice.
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Persiste o índice em disco (JSON compacto).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Carrega um índice salvo com [`EntityIndex::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let index: Self = serde_json::from_reader(reader)?;
+        index.validated()
+    }
+
+    /// Grava o índice sob `key` em um [`Storage`] (ex: o mesmo banco do feedback).
+    pub fn save_to(&self, storage: &dyn Storage, key: &str) -> io::Result<()> {
+        put_json(storage, key, self)
+    }
+
+    /// Carrega um índice gravado com [`EntityIndex::save_to`]; `None` se a chave não existe.
+    pub fn load_from(storage: &dyn Storage, key: &str) -> io::Result<Option<Self>> {
+        get_json::<Self>(storage, key)?.map(Self::validated).transpose()
+    }
+
+    /// Confere um índice lido de fora e reconstrói o mapa de números internos, que não
+    /// é serializado. Um arquivo corrompido ou truncado vira erro aqui, e não um pânico
+    /// em [`EntityIndex::query`].
+    fn validated(mut self) -> io::Result<Self> {
+        for (term, list) in &self.postings {
+            let in_range = list.iter().all(|&doc| (doc as usize) < self.documents.len());
+            if !in_range || !list.windows(2).all(|pair| pair[0] < pair[1]) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("índice corrompido: documentos inválidos no termo \"{term}\""),
+                ));
+            }
+        }
+        self.doc_numbers = self
+            .documents
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d.clone(), i as u32))
+            .collect();
+        Ok(self)
+    }
+}
+
+/// Divide uma consulta textual em termos `(categoria, texto)`.
+pub fn parse_query(query: &str) -> Result<Vec<(EntityCategory, String)>, QueryError> {
+    let mut terms = Vec::new();
+    for raw in query.split(';').flat_map(|part| part.split(" AND ")) {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            continue;
+        }
+        let (cat, text) = raw
+            .split_once('=')
+            .ok_or_else(|| QueryError::MalformedTerm(raw.to_string()))?;
+        let (cat, text) = (cat.trim(), text.trim());
+        if text.is_empty() {
+            return Err(QueryError::MalformedTerm(raw.to_string()));
+        }
+        let category = EntityCategory::from_str(&cat.to_uppercase())
+            .ok_or_else(|| QueryError::UnknownCategory(cat.to_string()))?;
+        terms.push((category, text.to_string()));
+    }
+    if terms.is_empty() {
+        return Err(QueryError::Empty);
+    }
+    Ok(terms)
+}
+
+/// Chave do termo no índice: `"CATEGORIA|texto normalizado"`.
+fn term_key(category: EntityCategory, text: &str) -> String {
+    let normalized = text
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{}|{}", category.name(), normalized)
+}
+
+/// Interseção de duas listas ordenadas em tempo linear.
+fn intersect_sorted(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_index() -> EntityIndex {
+        let mut index = EntityIndex::new();
+        index.add_document("a", &[EntitySpan::test_at("Petrobras", "Petrobras", EntityCategory::Org), EntitySpan::test_at("Bacia de Santos", "Bacia de Santos", EntityCategory::Loc)]);
+        index.add_document("b", &[EntitySpan::test_at("Petrobras", "Petrobras", EntityCategory::Org)]);
+        index.add_document("c", &[EntitySpan::test_at("bacia  de santos", "bacia  de santos", EntityCategory::Loc), EntitySpan::test_at("Vale", "Vale", EntityCategory::Org)]);
+        index
+    }
+
+    #[test]
+    fn test_conjunctive_query() {
+        let index = sample_index();
+        assert_eq!(index.query(&[(EntityCategory::Org, "petrobras")]), vec!["a", "b"]);
+        assert_eq!(index.query(&[(EntityCategory::Loc, "Bacia de Santos")]), vec!["a", "c"]);
+        assert_eq!(
+            index.search("ORG=Petrobras AND LOC=Bacia de Santos").unwrap(),
+            vec!["a"]
+        );
+        // Categoria faz parte do termo
+        assert!(index.query(&[(EntityCategory::Per, "Petrobras")]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_query_errors() {
+        assert_eq!(parse_query("  "), Err(QueryError::Empty));
+        assert_eq!(parse_query("Petrobras"), Err(QueryError::MalformedTerm("Petrobras".into())));
+        assert_eq!(parse_query("XYZ=foo"), Err(QueryError::UnknownCategory("XYZ".into())));
+        assert_eq!(parse_query("org=Vale; loc=Brasil").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let index = sample_index();
+        let path = std::env::temp_dir().join(format!("ner_index_{}.json", std::process::id()));
+        index.save(&path).unwrap();
+        let loaded = EntityIndex::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.term_count(), index.term_count());
+        assert_eq!(loaded.search("ORG=Vale").unwrap(), vec!["c"]);
+
+        // Reindexar um id existente não cria um novo documento
+        let mut loaded = loaded;
+        loaded.add_document("b", &[EntitySpan::test_at("Vale", "Vale", EntityCategory::Org)]);
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.search("ORG=Vale").unwrap(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_save_to_storage() {
+        let storage = crate::storage::MemoryStorage::new();
+        assert!(EntityIndex::load_from(&storage, "index/entidades").unwrap().is_none());
+        sample_index().save_to(&storage, "index/entidades").unwrap();
+
+        let mut loaded = EntityIndex::load_from(&storage, "index/entidades").unwrap().unwrap();
+        loaded.add_document("c", &[EntitySpan::test_at("Petrobras", "Petrobras", EntityCategory::Org)]);
+        assert_eq!(loaded.len(), 3);
+
+        // Postings que apontam além da lista de documentos (ou fora de ordem) são recusados
+        for postings in [r#"{"ORG|vale": [0, 7]}"#, r#"{"ORG|vale": [1, 0]}"#] {
+            let json = format!(r#"{{"documents": ["a", "b"], "postings": {postings}}}"#);
+            storage.put("index/corrompido", json.as_bytes()).unwrap();
+            let err = EntityIndex::load_from(&storage, "index/corrompido").unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
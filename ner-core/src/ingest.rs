@@ -0,0 +1,544 @@
+//! # Ingestão de Formatos: Extração de Texto de HTML e de PDF
+//!
+//! O pipeline trabalha com texto puro, mas muitos documentos chegam como páginas web.
+//! Simplesmente apagar as tags perderia a ligação com a marcação original, e não
+//! conseguiríamos destacar as entidades **na própria página**.
+//!
+//! [`extract_html`] remove as tags e guarda um **mapa de offsets**: para cada byte do
+//! texto extraído, o intervalo de bytes do HTML de onde ele veio.
+//!
+//! ```text
+//! HTML:  <p>A <b>Petrobras</b> &amp; a Vale</p>
+//!              └───┬───┘       └─┬─┘
+//! Texto:   A Petrobras & a Vale
+//!            └──┬───┘  └ veio de "&amp;"
+//! ```
+//!
+//! ## O que a extração faz
+//! - Remove tags, comentários e o conteúdo de `<script>` e `<style>`.
+//! - Decodifica entidades HTML (`&amp;`, `&atilde;`, `&#233;`, `&#xE9;`...).
+//! - Colapsa espaços em branco repetidos, como o navegador faria.
+//! - Insere uma quebra de linha nas tags de bloco (`<p>`, `<br>`, `<li>`...), para que
+//!   parágrafos não sejam colados ("fim.Início").
+//!
+//! [`crate::NerPipeline::analyze_html`] faz a extração e a análise em uma chamada.
+//! Depois da análise, [`ExtractedText::project`] leva um intervalo do texto de volta ao
+//! HTML e [`ExtractedText::highlight`] envolve as entidades em `<mark>` na marcação original.
+//!
+//! ## Texto extraído de PDF
+//!
+//! Texto copiado de PDFs chega com as quebras de linha da diagramação, inclusive no
+//! meio das palavras ("Petro-\nbras"), e o tokenizador veria dois tokens. [`unwrap_lines`]
+//! desfaz a diagramação com o mesmo mapa de offsets:
+//!
+//! - palavra hifenizada na quebra de linha seguida de minúscula é emendada
+//!   ("Petro-\nbras" → "Petrobras"); com maiúscula o hífen fica ("Norte-\nAmericana");
+//! - hífens opcionais (`U+00AD`) são removidos;
+//! - uma quebra de linha simples vira espaço; uma linha em branco vira uma quebra de
+//!   parágrafo (`\n`).
+//!
+//! [`crate::NerPipeline::analyze_wrapped`] faz o pré-processamento, a análise e devolve as
+//! entidades já com offsets do texto original (ver [`ExtractedText::project_entities`]).
+//!
+//! ## Normalização e encadeamento de estágios
+//!
+//! [`sanitize`] limpa o que o tokenizador não deveria ver: remove caracteres de controle e
+//! invisíveis (`U+200B`, `U+FEFF`...), troca espaços Unicode (`&nbsp;`, `U+2009`) por
+//! espaço comum e compõe acentos combinantes ("Sa\u{303}o" → "São").
+//!
+//! Cada estágio devolve um [`ExtractedText`] cujo mapa ([`OffsetMap`]) aponta para a
+//! entrada do estágio. [`ExtractedText::and_then`] aplica o próximo estágio e compõe os
+//! mapas, de modo que o resultado sempre aponta para o documento original:
+//!
+//! ```rust
+//! use ner_core::ingest::{extract_html, sanitize, unwrap_lines};
+//!
+//! let html = "<p>Sa\u{303}o&nbsp;Paulo e a Petro-<br>bras\u{200B}</p>";
+//! let prepared = extract_html(html).and_then(sanitize).and_then(unwrap_lines);
+//! assert_eq!(prepared.text, "São Paulo e a Petrobras");
+//!
+//! let (s, e) = prepared.project(0, "São Paulo".len()).unwrap();
+//! assert_eq!(&html[s..e], "Sa\u{303}o&nbsp;Paulo");
+//! ```
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let html = "<p>A <b>Petrobras</b> anunciou lucro.</p>";
+//! let (extracted, entities) =
+//!     NerPipeline::new().analyze_html(html, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//! assert_eq!(extracted.text, "A Petrobras anunciou lucro.");
+//!
+//! let highlighted = extracted.highlight(html, &entities);
+//! assert!(highlighted.contains(r#"<b><mark class="ent-org" data-category="ORG">Petrobras</mark></b>"#));
+//! ```
+
+use crate::offsets::{floor_char_boundary, OffsetMap};
+use crate::render::mark_open;
+use crate::tagger::EntitySpan;
+
+/// Tags cujo conteúdo não é texto visível.
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template"];
+
+/// Tags que iniciam ou terminam um bloco de texto (viram quebra de linha).
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "figcaption",
+    "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "nav", "ol",
+    "p", "pre", "section", "table", "td", "th", "tr", "ul",
+];
+
+/// Caracteres invisíveis removidos por [`sanitize`] (espaços de largura zero, BOM).
+const INVISIBLE_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// Acentos combinantes compostos por [`sanitize`]: (acento, letras base, letras acentuadas).
+const COMBINING_MARKS: &[(char, &str, &str)] = &[
+    ('\u{300}', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('\u{301}', "aeiouAEIOU", "áéíóúÁÉÍÓÚ"),
+    ('\u{302}', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('\u{303}', "aonAON", "ãõñÃÕÑ"),
+    ('\u{308}', "aeiouAEIOU", "äëïöüÄËÏÖÜ"),
+    ('\u{327}', "cC", "çÇ"),
+];
+
+/// Entidades nomeadas mais comuns em páginas em português.
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", ' '),
+    ("aacute", 'á'), ("eacute", 'é'), ("iacute", 'í'), ("oacute", 'ó'), ("uacute", 'ú'),
+    ("Aacute", 'Á'), ("Eacute", 'É'), ("Iacute", 'Í'), ("Oacute", 'Ó'), ("Uacute", 'Ú'),
+    ("acirc", 'â'), ("ecirc", 'ê'), ("ocirc", 'ô'), ("Acirc", 'Â'), ("Ecirc", 'Ê'), ("Ocirc", 'Ô'),
+    ("atilde", 'ã'), ("otilde", 'õ'), ("Atilde", 'Ã'), ("Otilde", 'Õ'),
+    ("agrave", 'à'), ("Agrave", 'À'), ("ccedil", 'ç'), ("Ccedil", 'Ç'), ("uuml", 'ü'),
+    ("ordm", 'º'), ("ordf", 'ª'), ("mdash", '—'), ("ndash", '–'), ("hellip", '…'),
+    ("laquo", '«'), ("raquo", '»'), ("ldquo", '“'), ("rdquo", '”'), ("lsquo", '‘'), ("rsquo", '’'),
+];
+
+/// Texto extraído de um documento, com o mapa de volta para a marcação original.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedText {
+    /// Texto visível, pronto para o pipeline.
+    pub text: String,
+    /// Para cada byte de `text`, o intervalo de bytes no documento original do caractere
+    /// que o gerou. Separadores inseridos (quebras de bloco) têm intervalo vazio.
+    pub map: OffsetMap,
+}
+
+impl ExtractedText {
+    /// Acrescenta um caractere vindo do intervalo `src` do documento original.
+    fn push(&mut self, c: char, src: (usize, usize)) {
+        self.text.push(c);
+        self.map.push(c, src);
+    }
+
+    /// Remove o último caractere do texto e do mapa.
+    fn pop(&mut self) -> Option<char> {
+        let c = self.text.pop()?;
+        self.map.pop(c);
+        Some(c)
+    }
+
+    /// Remove o espaço em branco final.
+    fn trim_end(&mut self) {
+        while self.text.ends_with(char::is_whitespace) {
+            self.pop();
+        }
+    }
+
+    /// Aplica mais um estágio de pré-processamento ao texto, compondo os mapas: o
+    /// resultado continua apontando para o documento original.
+    pub fn and_then(self, stage: impl FnOnce(&str) -> ExtractedText) -> ExtractedText {
+        let next = stage(&self.text);
+        ExtractedText { map: self.map.compose(&next.map), text: next.text }
+    }
+
+    /// Acrescenta espaço em branco, colapsando sequências (como o navegador faz).
+    fn push_space(&mut self, c: char, src: (usize, usize)) {
+        match self.text.chars().last() {
+            None => {}
+            // Uma quebra de bloco prevalece sobre um espaço comum
+            Some(last) if last.is_whitespace() && c == '\n' && last != '\n' => {
+                self.pop();
+                self.push('\n', src);
+            }
+            Some(last) if last.is_whitespace() => {}
+            Some(_) => self.push(c, src),
+        }
+    }
+
+    /// Leva o intervalo `[start, end)` do texto extraído para bytes do documento original.
+    ///
+    /// Retorna `None` se o intervalo for vazio ou estiver fora do texto.
+    pub fn project(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        self.map.project(start, end)
+    }
+
+    /// Trechos contínuos do documento original que compõem `[start, end)`.
+    ///
+    /// Uma entidade que atravessa tags ("<b>Petro</b>bras") gera um trecho para cada
+    /// pedaço de texto entre as tags, de modo que a marcação continue bem aninhada.
+    pub fn source_runs(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        self.map.source_runs(start, end)
+    }
+
+    /// Cópia de `entities` (com offsets de [`ExtractedText::text`]) com `start`/`end`
+    /// levados para o documento original.
+    ///
+    /// `text` continua sendo o do texto extraído ("Petrobras", e não "Petro-\nbras") e
+    /// `start_token`/`end_token` continuam relativos aos tokens do texto extraído.
+    /// Entidades que não podem ser projetadas são descartadas (ver [`EntitySpan::project`]).
+    pub fn project_entities(&self, entities: &[EntitySpan]) -> Vec<EntitySpan> {
+        entities.iter().filter_map(|entity| entity.project(&self.map)).collect()
+    }
+
+    /// Reescreve o documento original envolvendo cada entidade em
+    /// `<mark class="ent-org" data-category="ORG">`.
+    ///
+    /// `original` deve ser o mesmo documento passado a [`extract_html`] e as entidades
+    /// devem ter offsets relativos a [`ExtractedText::text`].
+    pub fn highlight(&self, original: &str, entities: &[EntitySpan]) -> String {
+        // (posição, é_abertura, marcação): fechamentos vêm antes de aberturas na mesma posição
+        let mut inserts: Vec<(usize, bool, String)> = Vec::new();
+        for entity in entities {
+            for (s, e) in self.source_runs(entity.start, entity.end) {
+                inserts.push((s, true, mark_open(entity.category)));
+                inserts.push((e, false, "</mark>".to_string()));
+            }
+        }
+        inserts.sort_by_key(|(pos, opening, _)| (*pos, *opening));
+
+        let mut out = String::with_capacity(original.len() + inserts.len() * 32);
+        let mut copied = 0;
+        for (pos, _, markup) in inserts {
+            let pos = pos.min(original.len());
+            out.push_str(&original[copied..pos]);
+            out.push_str(&markup);
+            copied = pos;
+        }
+        out.push_str(&original[copied..]);
+        out
+    }
+}
+
+/// Extrai o texto visível de um documento HTML, preservando o mapa de offsets.
+pub fn extract_html(html: &str) -> ExtractedText {
+    let mut out = ExtractedText::default();
+    let lower = html.to_ascii_lowercase(); // mesmos offsets de byte que `html`
+    let mut i = 0;
+
+    while i < html.len() {
+        let rest = &html[i..];
+
+        if rest.starts_with("<!--") {
+            i = rest.find("-->").map(|p| i + p + 3).unwrap_or(html.len());
+            continue;
+        }
+
+        if let Some(after_lt) = rest.strip_prefix('<') {
+            let Some(close) = rest.find('>') else {
+                // "<" solto (ex: "a < b"): é texto
+                out.push('<', (i, i + 1));
+                i += 1;
+                continue;
+            };
+            let tag_end = i + close + 1;
+            let name = tag_name(&lower[i + 1..tag_end - 1]);
+            let is_closing = after_lt.starts_with('/');
+
+            if !is_closing && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+                // Pula até o fechamento correspondente
+                let closing = format!("</{name}");
+                i = match lower[tag_end..].find(&closing) {
+                    Some(p) => {
+                        let at = tag_end + p;
+                        lower[at..].find('>').map(|q| at + q + 1).unwrap_or(html.len())
+                    }
+                    None => html.len(),
+                };
+                continue;
+            }
+            if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                out.push_space('\n', (tag_end, tag_end));
+            }
+            i = tag_end;
+            continue;
+        }
+
+        if rest.starts_with('&') {
+            if let Some((c, len)) = decode_entity(rest) {
+                if c.is_whitespace() {
+                    out.push_space(' ', (i, i + len));
+                } else {
+                    out.push(c, (i, i + len));
+                }
+                i += len;
+                continue;
+            }
+        }
+
+        let c = rest.chars().next().unwrap_or(' ');
+        let len = c.len_utf8();
+        if c.is_whitespace() {
+            out.push_space(' ', (i, i + len));
+        } else {
+            out.push(c, (i, i + len));
+        }
+        i += len;
+    }
+
+    out.trim_end();
+    out
+}
+
+/// Desfaz as quebras de linha e a hifenização de texto diagramado (ver o módulo).
+///
+/// ```rust
+/// use ner_core::ingest::unwrap_lines;
+///
+/// let original = "A Petro-\nbras e o Banco do\nBrasil.\n\nNovo parágrafo.";
+/// let unwrapped = unwrap_lines(original);
+/// assert_eq!(unwrapped.text, "A Petrobras e o Banco do Brasil.\nNovo parágrafo.");
+///
+/// let start = unwrapped.text.find("Petrobras").unwrap();
+/// let (s, e) = unwrapped.project(start, start + "Petrobras".len()).unwrap();
+/// assert_eq!(&original[s..e], "Petro-\nbras");
+/// ```
+pub fn unwrap_lines(text: &str) -> ExtractedText {
+    let mut out = ExtractedText::default();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\u{AD}' {
+            continue;
+        }
+        if c.is_whitespace() {
+            // Uma sequência de espaços: com quebra de linha vira um separador só
+            let mut end = i + c.len_utf8();
+            let mut breaks = usize::from(c == '\n');
+            while let Some(&(j, w)) = chars.peek().filter(|(_, w)| w.is_whitespace()) {
+                breaks += usize::from(w == '\n');
+                end = j + w.len_utf8();
+                chars.next();
+            }
+            match breaks {
+                0 => {
+                    for (j, w) in text[i..end].char_indices() {
+                        out.push(w, (i + j, i + j + w.len_utf8()));
+                    }
+                }
+                1 => out.push(' ', (i, end)),
+                _ => out.push('\n', (i, end)),
+            }
+            continue;
+        }
+        if c == '-' && out.text.chars().last().is_some_and(char::is_alphabetic) {
+            // Hífen no fim da linha: "Petro-\n  bras"
+            let rest = &text[i + 1..];
+            let gap = rest.len() - rest.trim_start_matches([' ', '\t', '\r']).len();
+            let after_break = rest[gap..].strip_prefix('\n').map(|r| r.trim_start_matches([' ', '\t', '\r']));
+            if let Some(next) = after_break.and_then(|r| r.chars().next()).filter(|n| n.is_alphabetic()) {
+                let skip = rest.len() - after_break.map_or(0, str::len);
+                if next.is_uppercase() {
+                    out.push('-', (i, i + 1));
+                }
+                while chars.peek().is_some_and(|&(j, _)| j <= i + skip) {
+                    chars.next();
+                }
+                continue;
+            }
+        }
+        out.push(c, (i, i + c.len_utf8()));
+    }
+
+    out.trim_end();
+    out
+}
+
+/// Normaliza caracteres que atrapalham o tokenizador, preservando o mapa de offsets.
+///
+/// - remove caracteres de controle (exceto espaços como `\n` e `\t`) e invisíveis;
+/// - troca espaços Unicode (`U+00A0`, `U+2009`...) por espaço comum;
+/// - compõe letra + acento combinante na letra acentuada ("c\u{327}" → "ç").
+///
+/// ```rust
+/// use ner_core::ingest::sanitize;
+///
+/// let original = "Jose\u{301}\u{A0}Sa\u{303}o\u{200B}";
+/// let clean = sanitize(original);
+/// assert_eq!(clean.text, "José São");
+///
+/// let start = clean.text.find("São").unwrap();
+/// let (s, e) = clean.project(start, start + "São".len()).unwrap();
+/// assert_eq!(&original[s..e], "Sa\u{303}o");
+/// ```
+pub fn sanitize(text: &str) -> ExtractedText {
+    let mut out = ExtractedText::default();
+    // Último caractere emitido e onde ele começa no original, para compor acentos
+    let mut last: Option<(char, usize)> = None;
+
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        if let Some(composed) = last.and_then(|(base, _)| compose_mark(base, c)) {
+            let start = last.map_or(i, |(_, start)| start);
+            out.pop();
+            out.push(composed, (start, end));
+            last = Some((composed, start));
+            continue;
+        }
+        if INVISIBLE_CHARS.contains(&c) || (c.is_control() && !c.is_whitespace()) {
+            continue;
+        }
+        let c = if c.is_whitespace() && !c.is_ascii() { ' ' } else { c };
+        out.push(c, (i, end));
+        last = Some((c, i));
+    }
+    out
+}
+
+/// Letra acentuada equivalente a `base` seguida do acento combinante `mark`.
+fn compose_mark(base: char, mark: char) -> Option<char> {
+    let (_, bases, composed) = COMBINING_MARKS.iter().find(|(m, _, _)| *m == mark)?;
+    let at = bases.chars().position(|b| b == base)?;
+    composed.chars().nth(at)
+}
+
+/// Nome da tag em minúsculas a partir do conteúdo entre `<` e `>` ("/P class=x" → "p").
+fn tag_name(inner: &str) -> String {
+    inner
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect()
+}
+
+/// Decodifica uma entidade HTML no início de `s`, retornando o caractere e o tamanho em bytes.
+fn decode_entity(s: &str) -> Option<(char, usize)> {
+    // Entidades são curtas; o limite recua até uma fronteira de caractere ("&T Comunicações")
+    let semi = s[..floor_char_boundary(s, s.len().min(12))].find(';')?;
+    let body = &s[1..semi];
+    let c = if let Some(num) = body.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse::<u32>().ok()?,
+        };
+        char::from_u32(code)?
+    } else {
+        NAMED_ENTITIES.iter().find(|(name, _)| *name == body)?.1
+    };
+    Some((c, semi + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::EntityCategory;
+
+    #[test]
+    fn test_extract_strips_markup_and_decodes_entities() {
+        let html = "<html><head><style>p { color: red }</style><script>var x = '<b>';</script></head>\
+                    <body><!-- menu --><h1>Not&iacute;cias</h1><p>S&atilde;o   Paulo &amp; Rio</p></body></html>";
+        let extracted = extract_html(html);
+        assert_eq!(extracted.text, "Notícias\nSão Paulo & Rio");
+
+        // "ã" veio de "&atilde;"
+        let a = extracted.text.find('ã').unwrap();
+        let (s, e) = extracted.project(a, a + 'ã'.len_utf8()).unwrap();
+        assert_eq!(&html[s..e], "&atilde;");
+    }
+
+    #[test]
+    fn test_raw_ampersand_before_accented_text() {
+        // O byte 12 a partir do "&" cai no meio do "ç"
+        let extracted = extract_html("<p>AT&T Comunicações</p>");
+        assert_eq!(extracted.text, "AT&T Comunicações");
+        assert_eq!(extract_html("<p>&ação;</p>").text, "&ação;");
+    }
+
+    #[test]
+    fn test_project_spans_back_to_html() {
+        let html = "<p>Ontem a <a href=\"/x\">Fiocruz</a> divulgou.</p>";
+        let extracted = extract_html(html);
+        let start = extracted.text.find("Fiocruz").unwrap();
+        let (s, e) = extracted.project(start, start + "Fiocruz".len()).unwrap();
+        assert_eq!(&html[s..e], "Fiocruz");
+        assert_eq!(extracted.project(3, 3), None);
+        assert_eq!(extracted.project(0, 1000), None);
+    }
+
+    #[test]
+    fn test_unwrap_lines_joins_hyphenated_words() {
+        let original = "Reunião na Petro-\r\n  bras com a Norte-\nAmericana e o gover-\nno\u{AD}r de\n\n\nSão Paulo -\n ontem.\n";
+        let unwrapped = unwrap_lines(original);
+        assert_eq!(
+            unwrapped.text,
+            "Reunião na Petrobras com a Norte-Americana e o governor de\nSão Paulo - ontem."
+        );
+        let project = |needle: &str| {
+            let start = unwrapped.text.find(needle).unwrap();
+            let (s, e) = unwrapped.project(start, start + needle.len()).unwrap();
+            &original[s..e]
+        };
+        assert_eq!(project("Petrobras"), "Petro-\r\n  bras");
+        assert_eq!(project("Norte-Americana"), "Norte-\nAmericana");
+        assert_eq!(project("governor"), "gover-\nno\u{AD}r");
+    }
+
+    #[test]
+    fn test_highlight_keeps_markup_nested() {
+        let html = "<p><b>Petro</b>bras e Vale</p>";
+        let extracted = extract_html(html);
+        assert_eq!(extracted.text, "Petrobras e Vale");
+        let entity = |text: &str| EntitySpan::test_at(&extracted.text, text, EntityCategory::Org);
+        let highlighted = extracted.highlight(html, &[entity("Petrobras"), entity("Vale")]);
+        let mark = r#"<mark class="ent-org" data-category="ORG">"#;
+        assert_eq!(
+            highlighted,
+            format!("<p><b>{mark}Petro</mark></b>{mark}bras</mark> e {mark}Vale</mark></p>")
+        );
+    }
+
+    #[test]
+    fn test_sanitize_normalizes_and_keeps_offsets() {
+        // Acentos combinantes, NBSP, espaço fino, BOM, zero-width e controle
+        let original = "\u{FEFF}Joa\u{303}o\u{A0}Conceic\u{327}a\u{303}o foi a\u{2009}Bras\u{ED}lia\u{200B}.\u{7}\n";
+        let clean = sanitize(original);
+        assert_eq!(clean.text, "João Conceição foi a Brasília.\n");
+        assert_eq!(clean.map.len(), clean.text.len());
+
+        let project = |needle: &str| {
+            let start = clean.text.find(needle).unwrap();
+            let (s, e) = clean.project(start, start + needle.len()).unwrap();
+            &original[s..e]
+        };
+        assert_eq!(project("João"), "Joa\u{303}o");
+        assert_eq!(project("ã"), "a\u{303}");
+        assert_eq!(project("João Conceição"), "Joa\u{303}o\u{A0}Conceic\u{327}a\u{303}o");
+        assert_eq!(project("Brasília."), "Bras\u{ED}lia\u{200B}.");
+
+        // Texto já limpo: o mapa é a identidade
+        let plain = sanitize("São Paulo");
+        assert_eq!(plain.text, "São Paulo");
+        assert_eq!(plain.map, OffsetMap::identity("São Paulo"));
+    }
+
+    #[test]
+    fn test_chained_stages_project_to_original() {
+        let html = "<p>O governador de Sa\u{303}o&nbsp;Paulo visitou a Petro-<br>bras em Bras&iacute;lia.</p>";
+        let prepared = extract_html(html).and_then(sanitize).and_then(unwrap_lines);
+        assert_eq!(prepared.text, "O governador de São Paulo visitou a Petrobras em Brasília.");
+        assert_eq!(prepared.map.len(), prepared.text.len());
+
+        let entity = |text: &str, category| EntitySpan::test_at(&prepared.text, text, category);
+        let entities = [
+            entity("São Paulo", EntityCategory::Loc),
+            entity("Petrobras", EntityCategory::Org),
+            entity("Brasília", EntityCategory::Loc),
+        ];
+        let projected: Vec<&str> =
+            prepared.project_entities(&entities).iter().map(|e| &html[e.start..e.end]).collect();
+        assert_eq!(projected, ["Sa\u{303}o&nbsp;Paulo", "Petro-<br>bras", "Bras&iacute;lia"]);
+
+        let highlighted = prepared.highlight(html, &entities[2..]);
+        assert!(highlighted.contains(r#"<mark class="ent-loc" data-category="LOC">Bras&iacute;lia</mark>"#));
+    }
+}
@@ -0,0 +1,230 @@
+//! # Mapeamento de Rótulos entre Padrões de Anotação
+//!
+//! Corpora e modelos de fora usam outros nomes de categoria: o OntoNotes separa `GPE`
+//! (países, cidades) de `LOC` e `FAC`, chama pessoas de `PERSON` e tem `WORK_OF_ART`,
+//! `EVENT`, `NORP`; o CoNLL-2003 não tem `DATE` nem `TIME`. [`LabelMapping`] traduz as
+//! categorias nas duas direções:
+//!
+//! - **Importação** (externo → interno): aplicada às tags BIO de um corpus lido do disco
+//!   ([`LabelMapping::import_annotations`], [`crate::corpus::load_conll_mapped`]), antes
+//!   do treino ou da avaliação.
+//! - **Exportação** (interno → externo): aplicada à saída, como em
+//!   [`crate::render::to_conll_mapped`], para comparar com ferramentas que esperam o
+//!   outro padrão.
+//!
+//! Categorias sem entrada no mapa passam inalteradas. Mapear para `O` descarta a
+//! entidade (ex: `CARDINAL`, `MONEY` do OntoNotes, sem equivalente aqui). Depois da
+//! troca, um `I-X` que deixou de continuar uma entidade `X` vira `B-X`, para a
+//! sequência continuar BIO válida.
+//!
+//! Os mapas prontos ([`LabelMapping::ontonotes`], [`LabelMapping::conll2003`]) podem
+//! ser estendidos com [`LabelMapping::with_import`] e [`LabelMapping::with_export`], ou
+//! lidos de um arquivo JSON (`{"import": {"GPE": "LOC"}, "export": {"LOC": "GPE"}}`).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::label_map::LabelMapping;
+//!
+//! let mapping = LabelMapping::ontonotes();
+//! let mut annotations: Vec<(String, String)> = [("Lula", "B-PERSON"), ("visitou", "O"), ("Recife", "B-GPE"), ("3", "B-CARDINAL")]
+//!     .iter()
+//!     .map(|(w, t)| (w.to_string(), t.to_string()))
+//!     .collect();
+//! mapping.import_annotations(&mut annotations);
+//! let tags: Vec<&str> = annotations.iter().map(|(_, t)| t.as_str()).collect();
+//! assert_eq!(tags, ["B-PER", "O", "B-LOC", "O"]);
+//!
+//! assert_eq!(mapping.export_tag("I-LOC"), "I-GPE");
+//! assert_eq!(mapping.export_tag("B-ORG"), "B-ORG");
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::corpus::{iob1_to_bio, OwnedAnnotatedSentence};
+use crate::format;
+
+/// Destino que descarta a entidade.
+const OUTSIDE: &str = "O";
+
+/// Tradução de categorias entre um padrão externo e as categorias do modelo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelMapping {
+    /// Categoria externa → interna (`"GPE" → "LOC"`), usada ao importar corpora.
+    #[serde(default)]
+    pub import: HashMap<String, String>,
+    /// Categoria interna → externa (`"LOC" → "GPE"`), usada ao exportar resultados.
+    #[serde(default)]
+    pub export: HashMap<String, String>,
+}
+
+impl LabelMapping {
+    /// Mapa vazio: todas as categorias passam inalteradas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// As 18 categorias do OntoNotes 5. Lugares (`GPE`, `LOC`, `FAC`) viram `LOC`;
+    /// `NORP`, `EVENT`, `WORK_OF_ART`, `LAW`, `PRODUCT` e `LANGUAGE` viram `MISC`;
+    /// números e quantias são descartados. Na exportação, `PER` vira `PERSON` e `LOC`
+    /// vira `GPE`; `MISC` não tem equivalente e passa inalterada.
+    pub fn ontonotes() -> Self {
+        let mut mapping = Self::new().with_import("PERSON", "PER").with_export("PER", "PERSON");
+        for place in ["GPE", "LOC", "FAC"] {
+            mapping = mapping.with_import(place, "LOC");
+        }
+        for misc in ["NORP", "EVENT", "WORK_OF_ART", "LAW", "PRODUCT", "LANGUAGE"] {
+            mapping = mapping.with_import(misc, "MISC");
+        }
+        for number in ["CARDINAL", "ORDINAL", "QUANTITY", "MONEY", "PERCENT"] {
+            mapping = mapping.with_import(number, OUTSIDE);
+        }
+        mapping.with_export("LOC", "GPE")
+    }
+
+    /// CoNLL-2003 (`PER`, `ORG`, `LOC`, `MISC`): a importação é a identidade; na
+    /// exportação, `DATE` e `TIME`, que o padrão não anota, são descartadas.
+    pub fn conll2003() -> Self {
+        Self::new().with_export("DATE", OUTSIDE).with_export("TIME", OUTSIDE)
+    }
+
+    /// Mapa pronto pelo nome: `"ontonotes"` ou `"conll2003"`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ontonotes" => Some(Self::ontonotes()),
+            "conll2003" | "conll" => Some(Self::conll2003()),
+            _ => None,
+        }
+    }
+
+    /// Importa a categoria externa `from` como `to` (`"O"` descarta).
+    pub fn with_import(mut self, from: &str, to: &str) -> Self {
+        self.import.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Exporta a categoria interna `from` como `to` (`"O"` descarta).
+    pub fn with_export(mut self, from: &str, to: &str) -> Self {
+        self.export.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Categoria interna correspondente à externa `category`.
+    pub fn import_category<'a>(&'a self, category: &'a str) -> &'a str {
+        self.import.get(category).map_or(category, String::as_str)
+    }
+
+    /// Categoria externa correspondente à interna `category`.
+    pub fn export_category<'a>(&'a self, category: &'a str) -> &'a str {
+        self.export.get(category).map_or(category, String::as_str)
+    }
+
+    /// Tag BIO externa traduzida (`"B-GPE"` → `"B-LOC"`). Não corrige a sequência;
+    /// para isso use [`LabelMapping::import_annotations`].
+    pub fn import_tag<'a>(&'a self, tag: &'a str) -> Cow<'a, str> {
+        map_tag(tag, &self.import)
+    }
+
+    /// Tag BIO interna traduzida para o padrão externo (`"B-LOC"` → `"B-GPE"`).
+    pub fn export_tag<'a>(&'a self, tag: &'a str) -> Cow<'a, str> {
+        map_tag(tag, &self.export)
+    }
+
+    /// Traduz as tags de uma sentença anotada e refaz os `B-` que a troca tornou necessários.
+    pub fn import_annotations(&self, annotations: &mut [(String, String)]) {
+        for (_, tag) in annotations.iter_mut() {
+            let mapped = self.import_tag(tag);
+            if mapped != tag.as_str() {
+                *tag = mapped.into_owned();
+            }
+        }
+        iob1_to_bio(annotations);
+    }
+
+    /// Traduz as tags de todas as sentenças (ver [`LabelMapping::import_annotations`]).
+    pub fn import_corpus(&self, sentences: &mut [OwnedAnnotatedSentence]) {
+        for sentence in sentences {
+            self.import_annotations(&mut sentence.annotations);
+        }
+    }
+
+    /// Lê um mapa salvo em JSON (`{"import": {...}, "export": {...}}`).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+
+    /// Salva o mapa em disco (JSON), no formato lido por [`LabelMapping::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+}
+
+/// Troca a categoria de `tag` segundo `map`; `O` e categorias sem entrada não mudam.
+fn map_tag<'a>(tag: &'a str, map: &'a HashMap<String, String>) -> Cow<'a, str> {
+    let Some((prefix, category)) = tag.split_once('-').filter(|(p, _)| *p == "B" || *p == "I") else {
+        return Cow::Borrowed(tag);
+    };
+    match map.get(category).map(String::as_str) {
+        None => Cow::Borrowed(tag),
+        Some(OUTSIDE) => Cow::Borrowed(OUTSIDE),
+        Some(mapped) => Cow::Owned(format!("{prefix}-{mapped}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotations(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(w, t)| (w.to_string(), t.to_string())).collect()
+    }
+
+    #[test]
+    fn test_import_keeps_bio_valid() {
+        let mapping = LabelMapping::ontonotes();
+        // FAC seguido de GPE: duas entidades LOC, não uma só
+        let mut tagged = annotations(&[
+            ("Maracanã", "B-FAC"),
+            ("Rio", "B-GPE"),
+            ("de", "I-GPE"),
+            ("Janeiro", "I-GPE"),
+            ("R$", "B-MONEY"),
+            ("10", "I-MONEY"),
+            ("Dom", "B-PERSON"),
+            ("Casmurro", "I-WORK_OF_ART"),
+        ]);
+        mapping.import_annotations(&mut tagged);
+        let tags: Vec<&str> = tagged.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(tags, ["B-LOC", "B-LOC", "I-LOC", "I-LOC", "O", "O", "B-PER", "B-MISC"]);
+
+        // Sem entrada no mapa, a tag passa inalterada
+        assert_eq!(mapping.import_tag("B-ORG"), "B-ORG");
+        assert_eq!(LabelMapping::new().import_tag("B-GPE"), "B-GPE");
+    }
+
+    #[test]
+    fn test_export_presets_and_round_trip() {
+        let conll = LabelMapping::preset("CoNLL2003").unwrap();
+        assert_eq!(conll.export_tag("B-DATE"), "O");
+        assert_eq!(conll.export_tag("I-PER"), "I-PER");
+        assert_eq!(LabelMapping::ontonotes().export_category("PER"), "PERSON");
+        assert!(LabelMapping::preset("harem").is_none());
+
+        let mapping = LabelMapping::new().with_import("PESSOA", "PER").with_export("PER", "PESSOA");
+        let path = std::env::temp_dir().join(format!("ner_label_map_{}.json", std::process::id()));
+        mapping.save(&path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["format_version"], crate::format::MODEL_FORMAT_VERSION);
+        assert_eq!(LabelMapping::load(&path).unwrap(), mapping);
+
+        // Mapas gravados antes do versionamento continuam legíveis
+        std::fs::write(&path, r#"{"import": {"PESSOA": "PER"}, "export": {}}"#).unwrap();
+        assert_eq!(LabelMapping::load(&path).unwrap().import_tag("B-PESSOA"), "B-PER");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+//! # Pacotes de Recursos por Variante do Português
+//!
+//! Boa parte do que o sistema "sabe" sobre a língua não está nos pesos, e sim em listas:
+//! abreviações do tokenizador, clíticos do modo `Aggressive`, títulos que precedem nomes,
+//! sufixos societários, sementes dos gazetteers e nomes de meses e dias. Essas listas
+//! eram brasileiras por construção; o [`LanguagePack`] as reúne em um único valor que
+//! pode ser trocado na construção do pipeline.
+//!
+//! Dois pacotes vêm embutidos:
+//!
+//! - [`LanguagePack::pt_br`]: o português do Brasil, o padrão de [`crate::NerPipeline::new`].
+//! - [`LanguagePack::pt_pt`]: o português europeu. Muda o vocabulário (lugares, partidos,
+//!   clubes, "primeiro-ministro", "Lda.") e a colocação dos pronomes: a ênclise ("diz-se",
+//!   "deram-no") é a regra e a mesóclise ("dir-se-á") ainda é corrente, então o modo
+//!   `Aggressive` separa também os clíticos do futuro e do condicional.
+//!
+//! Os modelos estatísticos continuam treinados no corpus embutido (brasileiro); o pacote
+//! afeta a tokenização, as regras e os gazetteers. Pacotes próprios podem partir de um
+//! dos embutidos e ser gravados em JSON ([`LanguagePack::save`]).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::language::LanguagePack;
+//! use ner_core::tokenizer::TokenizerMode;
+//!
+//! let pack = LanguagePack::pt_pt();
+//! let texts = |text: &str| -> Vec<String> {
+//!     pack.tokenizer_config().tokenize(text, TokenizerMode::Aggressive).into_iter().map(|t| t.text).collect()
+//! };
+//! assert_eq!(texts("dir-se-á"), vec!["dir", "-", "se", "-", "á"]);
+//! assert_eq!(texts("deram-no"), vec!["deram", "-", "no"]);
+//!
+//! assert_eq!(LanguagePack::from_code("pt-PT").unwrap().code, "pt-PT");
+//! assert!(LanguagePack::from_code("es").is_none());
+//! ```
+
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::format;
+use crate::tokenizer::{TokenizerConfig, ABBREVIATIONS, CLITICS};
+
+/// Títulos comuns às duas variantes.
+const PORTUGUESE_TITLES: &[&str] = &[
+    "presidente", "ex-presidente", "senador", "senadora", "deputado",
+    "deputada", "ministro", "ministra", "governador", "governadora",
+    "prefeito", "prefeita", "general", "capitão", "dr", "dra", "prof",
+    "profa", "vereador", "vereadora", "secretário", "secretária",
+    "diretor", "diretora", "ceo", "jogador", "jogadora", "técnico",
+    "técnica", "atleta", "ator", "atriz", "cantor", "cantora",
+];
+
+/// Indicadores jurídicos de empresas no Brasil.
+const PT_BR_ORG_SUFFIXES: &[&str] = &[
+    "s.a.", "s/a", "ltda", "eireli", "me", "epp", "sa", "inc",
+    "corp", "holdings", "group", "fc", "esporte", "clube",
+];
+
+/// Meses e dias da semana (iguais nas duas variantes; o Acordo Ortográfico de 1990
+/// tornou os meses minúsculos também em Portugal, e a consulta ignora maiúsculas).
+const PORTUGUESE_DATES: &[&str] = &[
+    "janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho",
+    "agosto", "setembro", "outubro", "novembro", "dezembro",
+    "segunda-feira", "terça-feira", "quarta-feira", "quinta-feira",
+    "sexta-feira", "sábado", "domingo",
+];
+
+/// Marcos horários.
+const PORTUGUESE_TIMES: &[&str] = &["meio-dia", "meia-noite"];
+
+/// Políticos e figuras históricas do Brasil.
+const PT_BR_PERSONS: &[&str] = &[
+    "Getúlio", "Vargas", "Juscelino", "Kubitschek", "Jânio", "Quadros",
+    "Costa", "Silva", "Geisel", "Figueiredo", "Sarney", "Collor", "Itamar",
+    "Franco", "Cardoso", "Rousseff", "Temer", "Bolsonaro", "Haddad",
+    "Mantega", "Meirelles", "Guedes", "Ciro", "Alckmin", "Moro",
+    "Senna", "Pelé", "Ronaldo", "Ronaldinho", "Zico", "Garrincha",
+    "Neymar", "Vini", "Rodrygo", "Casemiro", "Marquinhos",
+    "Gisele", "Bündchen", "Xuxa", "Ivete", "Sangalo", "Anitta",
+    "Caetano", "Veloso", "Gilberto", "Gil", "Chico", "Buarque",
+    "Machado", "Assis", "Guimarães", "Rosa", "Clarice", "Lispector",
+    "Oswald", "Andrade", "Drummond", "Pessoa",
+];
+
+/// Cidades e locais do Brasil (os estados e capitais vêm de [`crate::brazil`]).
+const PT_BR_LOCATIONS: &[&str] = &[
+    "Brasília", "São Paulo", "Rio de Janeiro", "Salvador", "Fortaleza",
+    "Manaus", "Curitiba", "Recife", "Porto Alegre", "Belém", "Goiânia",
+    "Florianópolis", "Maceió", "Natal", "Teresina", "Campo Grande",
+    "João Pessoa", "Aracaju", "Cuiabá", "Macapá", "Porto Velho",
+    "Boa Vista", "Palmas", "Rio Branco", "Vitória", "São Luís",
+    "Amazônia", "Pantanal", "Cerrado", "Caatinga", "Pampa",
+    "Nordeste", "Sudeste", "Norte", "Sul", "Centro-Oeste",
+    "Maracanã", "Itaquerão", "Arena", "Mineirão", "Beira-Rio",
+    "Planalto", "Palácio", "Congresso", "Senado", "Câmara",
+    "Supremo", "STF", "STJ", "TSE", "TRF",
+    "Argentina", "Chile", "Colômbia", "Peru", "Venezuela", "Uruguai",
+    "Paraguai", "Bolívia", "Equador", "Qatar", "Japão", "Coreia",
+    "Alemanha", "França", "Espanha", "Portugal", "Itália", "Inglaterra",
+    "Estados Unidos", "China", "Rússia", "Índia", "África",
+    "Europa", "Ásia", "América", "Latina", "Caribe",
+    "Ipiranga", "Tietê", "São Francisco", "Paraná", "Tocantins",
+    "Xingu", "Negro", "Solimões", "Tapajós",
+];
+
+/// Organizações brasileiras.
+const PT_BR_ORGANIZATIONS: &[&str] = &[
+    "Petrobras", "Vale", "Embraer", "Nubank", "Itaú", "Bradesco", "Santander",
+    "Caixa", "Econômica", "Federal", "BNDES", "IBGE", "INPE", "Fiocruz",
+    "Anvisa", "Anatel", "Aneel", "ANS", "ANP", "CADE",
+    "Partidos", "PT", "PL", "MDB", "PSDB", "PDT", "PSB", "Republicanos",
+    "Podemos", "União", "Brasil", "Solidariedade", "Avante",
+    "Flamengo", "Palmeiras", "Corinthians", "São Paulo", "Grêmio",
+    "Internacional", "Atlético", "Cruzeiro", "Fluminense", "Vasco",
+    "Botafogo", "Santos", "Sport", "Bahia", "Ceará", "Fortaleza",
+    "McLaren", "Ferrari", "Mercedes", "Red Bull", "Alpine",
+    "ONU", "UNESCO", "UNICEF", "OMS", "FMI", "Banco Mundial",
+    "BRICS", "Mercosul", "ALBA", "UNASUL", "CELAC",
+    "FIFA", "CBF", "COI", "COB",
+    "USP", "Unicamp", "UFRJ", "UnB", "UFMG", "UFRGS",
+    "Globo", "Record", "SBT", "Band", "CNN Brasil", "UOL", "Folha",
+    "Estadão", "O Globo", "Veja", "Época", "IstoÉ",
+];
+
+/// Miscelânea brasileira (eventos, produtos, leis, etc.).
+const PT_BR_MISC: &[&str] = &[
+    "Copa do Mundo", "Olimpíadas", "Jogos Olímpicos", "Paralímpicos",
+    "Libertadores", "Copa América", "Europeu", "Champions League",
+    "Fórmula 1", "MotoGP", "Rally Dakar",
+    "Carnaval", "Réveillon", "Natal", "São João", "Festa Junina",
+    "COVID-19", "Dengue", "Febre Amarela", "Zika", "Malária",
+    "PIB", "Selic", "IPCA", "IBOV", "FGTS", "INSS", "SUS",
+    "Constituição", "Marco Civil", "Lei Maria da Penha", "ECA",
+    "Operação Lava Jato", "Mensalão", "Privatizações",
+    "Independência", "República", "Proclamação", "Abolição",
+    "Inconfidência Mineira", "Revolução de 1930", "AI-5",
+    "Amazônia-1", "SGDC", "VLS",
+    "Gabriela Cravo e Canela", "Grande Sertão Veredas",
+];
+
+/// Cidades homônimas de clubes: as cidades são bem mais citadas.
+const PT_BR_ORG_PRIORS: &[(&str, f64)] = &[("São Paulo", 0.6), ("Fortaleza", 0.6)];
+
+/// Abreviações usadas em Portugal, além das embutidas no tokenizador.
+const PT_PT_ABBREVIATIONS: &[&str] = &["Exmo", "Exma", "Lda", "Av", "Sto", "Sta", "Dto", "Esq"];
+
+/// Clíticos da ênclise europeia, além dos embutidos ("deram-no", "dá-mo").
+const PT_PT_CLITICS: &[&str] = &[
+    "-os", "-as", "-lo", "-la", "-no", "-na", "-nas", "-vos",
+    "-lho", "-lha", "-lhos", "-lhas", "-mo", "-ma", "-to", "-ta",
+];
+
+/// Terminações do futuro e do condicional que seguem o clítico na mesóclise.
+const PT_PT_MESOCLITIC_ENDINGS: &[&str] = &["á", "ás", "ão", "ei", "emos", "eis", "ia", "ias", "iam", "íamos", "íeis"];
+
+/// Títulos e cargos usados em Portugal.
+const PT_PT_TITLES: &[&str] = &[
+    "primeiro-ministro", "primeira-ministra", "eurodeputado", "eurodeputada",
+    "autarca", "bastonário", "bastonária", "procurador", "procuradora",
+    "juiz", "juíza", "treinador", "seleccionador", "selecionador",
+    "engenheiro", "eng", "doutor", "doutora", "professor", "professora",
+    "sr", "sra",
+];
+
+/// Formas societárias portuguesas.
+const PT_PT_ORG_SUFFIXES: &[&str] = &[
+    "lda", "lda.", "s.a.", "sa", "sgps", "s.g.p.s.", "epe", "e.p.e.", "crl", "unipessoal",
+];
+
+const PT_PT_PERSONS: &[&str] = &[
+    "Marcelo", "Rebelo de Sousa", "Cavaco", "Sócrates", "Passos Coelho",
+    "Guterres", "Montenegro", "Saramago", "Pessoa", "Camões", "Eça de Queirós",
+    "Eusébio", "Cristiano Ronaldo", "Figo", "Mourinho", "Amália", "Rodrigues",
+];
+
+const PT_PT_LOCATIONS: &[&str] = &[
+    "Portugal", "Lisboa", "Porto", "Coimbra", "Braga", "Faro", "Aveiro",
+    "Évora", "Setúbal", "Funchal", "Madeira", "Açores", "Algarve", "Alentejo",
+    "Minho", "Douro", "Tejo", "Sintra", "Cascais", "Guimarães", "Viseu",
+    "Leiria", "Bragança", "Trás-os-Montes", "Belém", "São Bento",
+    "Espanha", "França", "Brasil", "Angola", "Moçambique", "Cabo Verde",
+    "Europa", "Bruxelas", "Estrasburgo",
+];
+
+const PT_PT_ORGANIZATIONS: &[&str] = &[
+    "Assembleia da República", "PS", "PSD", "CDS", "Chega", "Bloco de Esquerda",
+    "PCP", "Iniciativa Liberal", "Livre", "Caixa Geral de Depósitos", "CGD",
+    "Millennium BCP", "Novo Banco", "Banco de Portugal", "EDP", "Galp", "TAP",
+    "CTT", "RTP", "SIC", "TVI", "Público", "Expresso", "Benfica", "Sporting",
+    "FC Porto", "Porto", "Braga", "INE", "PSP", "GNR", "SNS",
+    "União Europeia", "Comissão Europeia", "NATO", "ONU",
+];
+
+const PT_PT_MISC: &[&str] = &[
+    "Liga Portugal", "Taça de Portugal", "Revolução dos Cravos", "Euro 2004",
+    "Expo 98", "Santos Populares", "Orçamento do Estado", "PRR",
+    "Constituição", "COVID-19", "Natal", "Carnaval",
+];
+
+/// Cidades homônimas de clubes ("o Porto venceu" × "no Porto").
+const PT_PT_ORG_PRIORS: &[(&str, f64)] = &[("Porto", 0.6), ("Braga", 0.6)];
+
+/// Recursos linguísticos de uma variante do português.
+///
+/// Cada campo substitui a lista correspondente do sistema:
+///
+/// | Campo | Usado por |
+/// |-------|-----------|
+/// | `abbreviations`, `clitics`, `mesoclitic_endings` | tokenizador ([`LanguagePack::tokenizer_config`]) |
+/// | `person_titles`, `org_suffixes` | classes `title` e `org_suffix` do motor de regras |
+/// | `persons` … `times`, `org_priors` | gazetteers de features e de regras ([`crate::model::NerModel::build_for`]) |
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguagePack {
+    /// Etiqueta BCP 47 da variante ("pt-BR", "pt-PT").
+    pub code: String,
+    /// Abreviações sem o ponto, diferenciando maiúsculas ("Dr", "art").
+    pub abbreviations: Vec<String>,
+    /// Clíticos separados pelo modo `Aggressive`, com o hífen ("-se").
+    pub clitics: Vec<String>,
+    /// Terminações que seguem o clítico na mesóclise ("á" em "dir-se-á"). Vazio: a
+    /// mesóclise não é separada.
+    pub mesoclitic_endings: Vec<String>,
+    /// Títulos que precedem nomes de pessoa, em minúsculas ("presidente").
+    pub person_titles: Vec<String>,
+    /// Sufixos societários, em minúsculas ("ltda").
+    pub org_suffixes: Vec<String>,
+    pub persons: Vec<String>,
+    pub locations: Vec<String>,
+    pub organizations: Vec<String>,
+    pub misc: Vec<String>,
+    /// Meses e dias da semana.
+    pub dates: Vec<String>,
+    pub times: Vec<String>,
+    /// Priors de ORG dos nomes que também são lugares (ver [`crate::features::Gazetteers::set_prior`]).
+    pub org_priors: Vec<(String, f64)>,
+}
+
+fn owned(words: &[&str]) -> Vec<String> {
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+fn priors(entries: &[(&str, f64)]) -> Vec<(String, f64)> {
+    entries.iter().map(|(name, prior)| (name.to_string(), *prior)).collect()
+}
+
+impl LanguagePack {
+    /// O português do Brasil: as listas que o sistema sempre usou.
+    pub fn pt_br() -> Self {
+        let mut locations = owned(PT_BR_LOCATIONS);
+        locations.extend(crate::brazil::place_names().into_iter().map(String::from));
+        Self {
+            code: "pt-BR".to_string(),
+            abbreviations: owned(ABBREVIATIONS),
+            clitics: owned(CLITICS),
+            mesoclitic_endings: Vec::new(),
+            person_titles: owned(PORTUGUESE_TITLES),
+            org_suffixes: owned(PT_BR_ORG_SUFFIXES),
+            persons: owned(PT_BR_PERSONS),
+            locations,
+            organizations: owned(PT_BR_ORGANIZATIONS),
+            misc: owned(PT_BR_MISC),
+            dates: owned(PORTUGUESE_DATES),
+            times: owned(PORTUGUESE_TIMES),
+            org_priors: priors(PT_BR_ORG_PRIORS),
+        }
+    }
+
+    /// O português europeu: vocabulário de Portugal, ênclise e mesóclise.
+    pub fn pt_pt() -> Self {
+        let chain = |base: &[&str], extra: &[&str]| owned(&[base, extra].concat());
+        Self {
+            code: "pt-PT".to_string(),
+            abbreviations: chain(ABBREVIATIONS, PT_PT_ABBREVIATIONS),
+            clitics: chain(CLITICS, PT_PT_CLITICS),
+            mesoclitic_endings: owned(PT_PT_MESOCLITIC_ENDINGS),
+            person_titles: chain(PORTUGUESE_TITLES, PT_PT_TITLES),
+            org_suffixes: owned(PT_PT_ORG_SUFFIXES),
+            persons: owned(PT_PT_PERSONS),
+            locations: owned(PT_PT_LOCATIONS),
+            organizations: owned(PT_PT_ORGANIZATIONS),
+            misc: owned(PT_PT_MISC),
+            dates: owned(PORTUGUESE_DATES),
+            times: owned(PORTUGUESE_TIMES),
+            org_priors: priors(PT_PT_ORG_PRIORS),
+        }
+    }
+
+    /// Pacote embutido da etiqueta `code` ("pt-BR", "pt_pt"; sem diferenciar maiúsculas).
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().replace('_', "-").as_str() {
+            "pt-br" => Some(Self::pt_br()),
+            "pt-pt" => Some(Self::pt_pt()),
+            _ => None,
+        }
+    }
+
+    /// Configuração do tokenizador com as abreviações e os clíticos do pacote.
+    pub fn tokenizer_config(&self) -> TokenizerConfig {
+        let mut config = TokenizerConfig::new();
+        for word in &self.abbreviations {
+            config.add_abbreviation(word);
+        }
+        for clitic in &self.clitics {
+            config.add_clitic(clitic);
+        }
+        config.mesoclitic_endings.extend(self.mesoclitic_endings.iter().cloned());
+        config
+    }
+
+    /// Salva o pacote em disco (JSON, com a versão do formato; ver [`crate::format`]).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega um pacote salvo com [`LanguagePack::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+}
+
+impl Default for LanguagePack {
+    fn default() -> Self {
+        Self::pt_br()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::TokenizerMode;
+
+    #[test]
+    fn test_pt_br_pack_adds_nothing_to_builtin_tokenizer() {
+        assert_eq!(LanguagePack::pt_br().tokenizer_config(), TokenizerConfig::new());
+        assert_eq!(LanguagePack::from_code("PT_br"), Some(LanguagePack::pt_br()));
+    }
+
+    #[test]
+    fn test_pt_pt_tokenizer_and_round_trip() {
+        let pack = LanguagePack::pt_pt();
+        let config = pack.tokenizer_config();
+        let tokens = config.tokenize("A Exma. Sra. entregou-vos a carta.", TokenizerMode::Aggressive);
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, vec!["A", "Exma.", "Sra.", "entregou", "-", "vos", "a", "carta", "."]);
+        // Sem terminação de futuro, não é mesóclise: "-se" é clítico e "-á" não é
+        let br = LanguagePack::pt_br().tokenizer_config().tokenize("far-se-á", TokenizerMode::Aggressive);
+        assert_eq!(br.len(), 1);
+
+        let path = std::env::temp_dir().join(format!("ner_language_{}.json", std::process::id()));
+        pack.save(&path).unwrap();
+        let loaded = LanguagePack::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, pack);
+    }
+}
@@ -20,52 +20,204 @@
 //! ## Exemplo de Uso
 //!
 //! ```rust
-//! use ner_core::{NerPipeline, AlgorithmMode, TokenizerMode};
+//! use ner_core::prelude::*;
 //!
-//! // 1. Instancia o pipeline (carrega modelos e gazetteers)
+//! // 1. Instancia o pipeline (modelo padrão com pesos heurísticos e gazetteers embutidos)
 //! let pipeline = NerPipeline::new();
 //!
-//! // 2. Texto para análise
-//! let text = "O Supremo Tribunal Federal decidiu ontem.";
+//! // 2. Escolhe um modo que este build oferece, em vez de depender do fallback
+//! let capabilities = pipeline.capabilities();
+//! let mode = if capabilities.supports(AlgorithmMode::Hybrid) { AlgorithmMode::Hybrid } else { AlgorithmMode::RulesOnly };
 //!
-//! // 3. Executa a análise usando modo Híbrido (Regras + CRF)
-//! let (tokens, entities) = pipeline.analyze_with_mode(
-//!     text,
-//!     AlgorithmMode::Hybrid,
-//!     TokenizerMode::Standard
-//! );
+//! // 3. Opções da análise e filtro de saída (validados antes de rodar)
+//! let options = AnalysisOptions { max_tokens: Some(512), ..Default::default() };
+//! let filter = EntityFilter::default().with_min_confidence(0.8);
 //!
-//! // 4. Exibe as entidades encontradas
-//! for entity in entities {
-//!     println!("Entidade: {} ({:?}) - Confiança: {:.2}", entity.text, entity.category, entity.confidence);
-//! }
+//! // 4. Executa a análise
+//! let text = "O presidente Lula visitou a Petrobras em Brasília.";
+//! let report = pipeline.analyze_filtered(text, mode, TokenizerMode::Standard, options, &filter).unwrap();
+//!
+//! // 5. Entidades encontradas, com offsets de byte no texto original
+//! let found: Vec<(&str, EntityCategory)> = report.entities.iter().map(|e| (e.text.as_str(), e.category)).collect();
+//! assert!(found.contains(&("Petrobras", EntityCategory::Org)));
+//! assert!(found.contains(&("Brasília", EntityCategory::Loc)));
+//! assert!(report.entities.iter().all(|e| e.confidence >= 0.8 && text[e.start..e.end] == e.text));
+//!
+//! // Opções inválidas são recusadas em vez de produzir um resultado vazio
+//! let filter = EntityFilter::default().with_categories(Vec::new());
+//! assert!(pipeline.analyze_filtered(text, mode, TokenizerMode::Standard, options, &filter).is_err());
 //! ```
 //!
+//! Para scripts, [`extract_entities`] faz tudo em uma chamada (modo `Hybrid`, tokenizador
+//! `Standard`), e [`prelude`] reúne os imports mais comuns:
+//!
+//! ```rust
+//! use ner_core::prelude::*;
+//!
+//! let entities = extract_entities("Lula visitou Brasília.");
+//! let brasilia = entities.iter().find(|e| e.text == "Brasília").unwrap();
+//! assert_eq!(brasilia.category, EntityCategory::Loc);
+//! ```
+//!
+//! Os exemplos do `README.md` também são compilados e executados como doctests.
+//!
 //! ## Módulos Principais
 //!
+//! - [`prelude`]: Reexportações para `use ner_core::prelude::*`.
 //! - [`pipeline`]: Orquestrador principal que conecta todos os estágios.
+//! - [`shared`]: Pipeline compartilhado entre threads, com atualização de gazetteers e troca de modelo a quente.
 //! - [`tokenizer`]: Responsável pela segmentação do texto.
+//! - [`abbreviations`]: Aprendizado de abreviações do domínio ("proc.", "ref.") para o tokenizador.
+//! - [`brazil`]: Estados, capitais, siglas de UF e gentílicos do Brasil, com o subtipo de cada menção de lugar.
+//! - [`language`]: Recursos de cada variante do português (PT-BR, PT-PT): abreviações, clíticos, títulos e gazetteers.
+//! - [`categories`]: Categorias de entidade personalizadas (`LEGISLACAO`, `DOENCA`) declaradas em tempo de execução.
+//! - [`backend`]: Traits comuns dos algoritmos (`SequenceTagger`, `SpanPredictor`), também para backends próprios.
 //! - [`features`]: Engenharia de características para modelos de ML.
-//! - [`corpus`]: Dados de treinamento e teste anotados (BIO).
+//! - [`morphology`]: Radicais de palavras em português (RSLP enxuto), usados nas features `lemma=`.
+//! - [`clusters`]: Clusters de Brown aprendidos de texto cru, usados nas features `cluster4=`/`cluster6=`.
+//! - [`embeddings`]: Embeddings pré-treinados (word2vec/fastText `.vec`) como features e para expandir gazetteers pelos vizinhos mais próximos.
+//! - [`pos`]: Etiquetador morfossintático (perceptron sobre etiquetas UPOS), treinável com corpora CoNLL-U, usado nas features `pos=`.
+//! - [`corpus`]: Dados de treinamento e teste anotados (BIO), e carga de corpora CoNLL externos.
+//! - [`label_map`]: Tradução de categorias entre padrões de anotação (OntoNotes `GPE` → `LOC`, CoNLL-2003) na importação de corpora e na exportação.
+//! - [`corpus_reader`]: Leitura de corpora CoNLL/JSONL do disco, uma sentença por vez.
+//! - [`chunking`]: Divisão de documentos longos em trechos (RAG) sem partir sentenças nem entidades.
+//! - [`offsets`]: Fatiamento seguro do texto original a partir de offsets de byte e [`offsets::OffsetMap`] entre texto transformado e original.
+//! - [`alias`]: Tabela de siglas e nomes alternativos usada por NED e NEL.
+//! - [`coref`]: Resolução leve de pronomes ("ele", "dela") para a pessoa mencionada mais recentemente.
+//! - [`temporal`]: Datas, horários, valores em dinheiro e percentuais ("13 de maio de 1888", "R$ 50 bilhões") com valor normalizado.
+//! - [`variants`]: Grafias variantes e históricas ("Sam Paulo", "Bahia"/"Baía") casadas com as chaves dos gazetteers.
+//! - [`token_pattern`]: Linguagem de padrões sobre tokens usada pelas regras declarativas.
+//! - [`index`]: Índice invertido de entidades para busca em muitos documentos.
+//! - [`format`]: Versão do formato ([`format::MODEL_FORMAT_VERSION`]) gravada em modelos, pacotes de idioma e tabelas BPE, com erros claros para arquivos incompatíveis.
+//! - [`storage`]: Armazenamento chave-valor (síncrono e assíncrono) em memória ou em arquivos, usado por feedback, cache de linking e índice.
+//! - [`feedback`]: Marcações de revisores (correta, errada, recategorizada) convertidas em anotação ouro.
+//! - [`ingest`]: Extração de texto de HTML e de PDF (quebras de linha, hifenização) e normalização Unicode, com mapa de offsets para o original.
+//! - [`probabilities`]: Tabelas de probabilidade por token (ouro, previsto, todas as tags) exportadas em CSV.
+//! - [`diff`]: Alinhamento entre entidades anotadas à mão e previstas (acertos, erros de rótulo e de fronteira).
+//! - [`samples`]: Textos de demonstração com entidades esperadas, usados pela interface e como smoke tests.
+//! - [`overlay`]: Dicionários e filtros de saída aplicados sobre o modelo compartilhado (um por cliente).
+//! - [`render`]: Resultado como texto CoNLL ou HTML com as entidades destacadas.
+//! - [`annotation`]: Exportação de entidades como W3C Web Annotation ou JSON Patch (INCEpTION, Label Studio).
+//! - [`export`]: Exportação para o JSON do spaCy (`ents`) e o formato standoff do BRAT (`.ann`).
+//! - [`synthetic`]: Gerador de corpora sintéticos grandes para benchmarks e testes de carga.
+//! - [`calibration`]: Calibração da confiança de cada modo (Platt scaling) ajustada em dados reservados.
+//! - [`thresholds`]: Limiares de confiança por categoria escolhidos em dados de desenvolvimento.
+//! - [`self_check`]: Autoavaliação opcional de cada modo numa fatia reservada do corpus embutido, exposta nas capacidades do pipeline.
+//! - [`eval`]: Validação cruzada em k partes dos modelos treináveis (média e desvio do F1); requer a feature `statistical`.
+//! - [`training`]: Ordem das sentenças em cada época de treino (embaralhamento, currículo, sobreamostragem).
+//! - [`active_learning`]: Amostragem por incerteza: as sentenças de um conjunto não anotado que mais valem a anotação.
+//!
+//! ## Features do Cargo
+//!
+//! Todos os subsistemas são compilados por padrão. Implantações mínimas (ex: um serviço
+//! de anonimização só com regras) podem desligar o que não usam:
+//!
+//! | Feature | Módulos | Modos de [`AlgorithmMode`] |
+//! |---------|---------|----------------------------|
+//! | `rules` | `rule_based`, `token_pattern` | `RulesOnly` (e as regras do `Hybrid`) |
+//! | `statistical` | `hmm`, `maxent`, `perceptron`, `span`, `eval` | `Hmm`, `MaxEnt`, `Memm`, `Perceptron`, `SpanBased` |
+//! | `zero-shot` | `sota_2024` | — |
+//! | `linking` | `ned`, `nel` | — |
+//!
+//! ```toml
+//! ner-core = { path = "../ner-core", default-features = false, features = ["rules"] }
+//! ```
+//!
+//! Tokenização, features, CRF e Viterbi fazem parte do núcleo. Os modos de uma feature
+//! desligada continuam existindo no enum (para não quebrar clientes serializados), mas
+//! [`NerPipeline::is_available`] os reporta indisponíveis e o pipeline segue a cadeia
+//! de fallback, emitindo um `PipelineEvent::Warning`.
 
 
+pub mod abbreviations;
+pub mod active_learning;
+pub mod alias;
+pub mod annotation;
+pub mod backend;
+pub mod brazil;
+pub mod calibration;
+pub mod categories;
+pub mod chunking;
+pub mod clusters;
+pub mod coref;
 pub mod corpus;
+pub mod corpus_reader;
 pub mod crf;
+pub mod diff;
+pub mod embeddings;
+pub mod export;
+pub mod feedback;
 pub mod features;
+pub mod format;
+pub mod label_map;
+pub mod language;
 pub mod model;
+pub mod morphology;
+pub mod offsets;
+pub mod overlay;
 pub mod pipeline;
+pub mod pos;
+pub mod prelude;
+pub mod probabilities;
+pub mod render;
+#[cfg(feature = "rules")]
 pub mod rule_based;
+pub mod shared;
+pub mod storage;
 pub mod tagger;
+pub mod temporal;
+pub mod thresholds;
+#[cfg(feature = "rules")]
+pub mod token_pattern;
 pub mod tokenizer;
+pub mod variants;
+#[cfg(feature = "statistical")]
+pub mod eval;
+#[cfg(feature = "statistical")]
 pub mod hmm;
+pub mod index;
+pub mod ingest;
+#[cfg(feature = "statistical")]
 pub mod maxent;
+#[cfg(feature = "statistical")]
 pub mod perceptron;
+pub mod samples;
+pub mod self_check;
+#[cfg(feature = "statistical")]
 pub mod span;
+pub mod synthetic;
+pub mod training;
 pub mod viterbi;
+#[cfg(feature = "linking")]
 pub mod ned;
+#[cfg(feature = "linking")]
 pub mod nel;
+#[cfg(feature = "zero-shot")]
 pub mod sota_2024;
 
-pub use pipeline::{AlgorithmMode, NerPipeline, PipelineEvent};
-pub use tagger::{EntitySpan, Tag, TaggedToken};
+pub use pipeline::{
+    AlgorithmMode, AnalysisOptions, AnalysisReport, AnalysisWarning, Capabilities, FeatureEvents, FusionConfig,
+    FusionPolicy, InvalidOptions, ModeCapability, ModeStages, NerPipeline, PipelineEvent, SentenceConfidence, WarningCode,
+};
+pub use tagger::{EntityOrder, EntitySpan, LabelScore, MultiLabelSpan, ScoreBreakdown, Tag, TaggedToken};
 pub use tokenizer::{Token, TokenizerMode};
+pub use viterbi::{Decoder, ViterbiDetail};
+
+use std::sync::OnceLock;
+
+/// Pipeline compartilhado por [`extract_entities`], criado na primeira chamada.
+static GLOBAL_PIPELINE: OnceLock<NerPipeline> = OnceLock::new();
+
+/// Extrai as entidades de um texto com um pipeline global e as configurações padrão.
+///
+/// O modelo é construído uma única vez, na primeira chamada (que por isso é mais lenta);
+/// as seguintes reutilizam o mesmo pipeline, inclusive entre threads. Para escolher modo,
+/// tokenizador ou receber eventos, use [`NerPipeline`] diretamente.
+pub fn extract_entities(text: &str) -> Vec<EntitySpan> {
+    GLOBAL_PIPELINE.get_or_init(NerPipeline::new).analyze(text).1
+}
+
+/// Roda os blocos `rust` do README como doctests, para que os exemplos não envelheçam.
+#[cfg(doctest)]
+#[doc = include_str!("../../README.md")]
+pub struct ReadmeDoctests;
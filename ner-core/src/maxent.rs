@@ -6,14 +6,28 @@
 //!
 //! ## Algoritmo
 //! - **Treinamento**: Stochastic Gradient Descent (SGD) com regularização L2.
-//! - **Predição**: Classificação local (greedy) ou MEMM (se features de transição forem usadas).
+//! - **Predição**: Classificação local (greedy) ou MEMM (ver [`MaxEntModel::memm`]).
 //!
 //! O modelo calcula: P(tag | features) ~ exp(dot(weights, features))
+//!
+//! ## MEMM (Maximum Entropy Markov Model)
+//!
+//! A decisão gulosa ignora a sequência: nada impede `O I-PER`. No modo MEMM a tag
+//! anterior vira uma feature (`prev_tag=B-PER`): no treino ela vem do ouro, e na
+//! predição o Viterbi percorre todas as histórias possíveis, maximizando
+//!
+//! $$ \prod_i P(t_i \mid x_i, t_{i-1}) $$
+//!
+//! com cada distribuição normalizada localmente, e descartando transições inválidas
+//! no esquema BIO (`I-X` só depois de `B-X` ou `I-X`).
 
 use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
-use crate::corpus::AnnotatedSentence;
-use crate::features::{self, FeatureVector, Gazetteers};
+use crate::backend::SequenceTagger;
+use crate::corpus::{AnnotatedSentence, OwnedAnnotatedSentence};
+use crate::features::{FeatureExtractor, FeatureVector, Gazetteers, SharedExtractor};
+use crate::training::TrainingSchedule;
+use crate::viterbi::forward_backward;
 
 
 /// Modelo de Entropia Máxima (MaxEnt), também conhecido como Regressão Logística Multinomial.
@@ -37,6 +51,12 @@ pub struct MaxEntModel {
     weights: HashMap<(String, String), f64>,
     /// Lista de todas as tags possíveis (labels de classe).
     tags: Vec<String>,
+    /// Modo MEMM: features `prev_tag=` no treino e Viterbi na predição.
+    #[serde(default)]
+    history: bool,
+    /// Extrator de features usado no treino e na predição (não serializado).
+    #[serde(skip)]
+    extractor: SharedExtractor,
 }
 
 impl MaxEntModel {
@@ -44,9 +64,41 @@ impl MaxEntModel {
         Self {
             weights: HashMap::new(),
             tags: Vec::new(),
+            history: false,
+            extractor: SharedExtractor::default(),
         }
     }
 
+    /// Modelo MEMM: treina com a tag anterior como feature e decodifica com Viterbi
+    /// sobre as histórias de tags (modo `AlgorithmMode::Memm`).
+    pub fn memm() -> Self {
+        Self { history: true, ..Self::new() }
+    }
+
+    /// Indica se o modelo é um MEMM (ver [`MaxEntModel::memm`]).
+    pub fn is_memm(&self) -> bool {
+        self.history
+    }
+
+    /// Troca o extrator de features (ver [`FeatureExtractor`]). Treine depois de trocá-lo.
+    pub fn with_extractor(mut self, extractor: impl FeatureExtractor + 'static) -> Self {
+        self.set_extractor(extractor);
+        self
+    }
+
+    pub fn set_extractor(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.extractor = SharedExtractor::new(extractor);
+    }
+
+    pub fn extractor(&self) -> &dyn FeatureExtractor {
+        &*self.extractor
+    }
+
+    /// Indica se o modelo já foi treinado (possui tags conhecidas).
+    pub fn is_trained(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
     /// Treina o modelo usando **Stochastic Gradient Descent (SGD)**.
     ///
     /// Diferente do HMM que conta frequências, o MaxEnt é treinado iterativamente para
@@ -58,126 +110,273 @@ impl MaxEntModel {
     /// * `learning_rate` ($\eta$) - Taxa de aprendizado (tamanho do passo do gradiente).
     /// * `lambda` ($\lambda$) - Fator de regularização L2 (ajuda a evitar overfitting punindo pesos muito grandes).
     pub fn train(&mut self, corpus: &[AnnotatedSentence], iterations: usize, learning_rate: f64, lambda: f64) {
-        // 1. Coleta todas as tags e inicializa estrutura
+        self.train_stream(corpus.iter().map(OwnedAnnotatedSentence::from), iterations, learning_rate, lambda);
+    }
+
+    /// Como [`Self::train`], mas lendo as sentenças de um iterador (ex: um
+    /// [`crate::corpus_reader::CorpusReader`]); cada época percorre um clone de `corpus`.
+    pub fn train_stream(
+        &mut self,
+        corpus: impl Iterator<Item = OwnedAnnotatedSentence> + Clone,
+        iterations: usize,
+        learning_rate: f64,
+        lambda: f64,
+    ) {
+        self.collect_tags(corpus.clone());
+        let gaz = Gazetteers::new(); // Gazetteers vazios por enquanto ou passados como arg
+        for epoch in 0..iterations {
+            let (correct, total) = self.train_epoch(corpus.clone(), &gaz, learning_rate, lambda);
+            Self::log_epoch(epoch, correct, total);
+        }
+    }
+
+    /// Como [`Self::train`], percorrendo cada época na ordem do `schedule`
+    /// (embaralhamento, currículo, sobreamostragem; ver [`crate::training`]).
+    pub fn train_scheduled(
+        &mut self,
+        corpus: &[AnnotatedSentence],
+        iterations: usize,
+        learning_rate: f64,
+        lambda: f64,
+        schedule: &TrainingSchedule,
+    ) {
+        let owned: Vec<OwnedAnnotatedSentence> = corpus.iter().map(OwnedAnnotatedSentence::from).collect();
+        self.collect_tags(owned.iter().cloned());
+        let gaz = Gazetteers::new();
+        for epoch in 0..iterations {
+            let order = schedule.epoch_order(&owned, epoch);
+            let sentences = order.into_iter().map(|i| owned[i].clone());
+            let (correct, total) = self.train_epoch(sentences, &gaz, learning_rate, lambda);
+            Self::log_epoch(epoch, correct, total);
+        }
+    }
+
+    /// Coleta todas as tags e inicializa estrutura
+    fn collect_tags(&mut self, corpus: impl Iterator<Item = OwnedAnnotatedSentence>) {
         let mut tag_set = HashSet::new();
         for s in corpus {
             for (_, tag) in s.annotations {
-                tag_set.insert(tag.to_string());
+                tag_set.insert(tag);
             }
         }
         self.tags = tag_set.into_iter().collect();
         self.tags.sort();
+    }
 
-        let gaz = Gazetteers::new(); // Gazetteers vazios por enquanto ou passados como arg
+    fn log_epoch(epoch: usize, correct: usize, total: usize) {
+        if epoch.is_multiple_of(5) {
+            println!("Epoch {}: Accuracy {:.2}%", epoch, (correct as f64 / total as f64) * 100.0);
+        }
+    }
 
-        for epoch in 0..iterations {
-            let mut correct = 0;
-            let mut total = 0;
-
-            for sentence in corpus {
-                // Tokeniza e extrai features
-                // Em um cenário real, tokenização deve alinhar perfeitamente.
-                // Aqui reconstruímos tokens simples baseados na anotação para garantir alinhamento.
-                let tokens: Vec<crate::tokenizer::Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
-                    crate::tokenizer::Token {
-                        text: text.to_string(),
-                        start: 0, // irrelevante para features de treino simples
-                        end: 0,
-                        index: i,
-                    }
-                }).collect();
+    /// Uma época de SGD; retorna `(acertos, tokens)` para o log de acurácia.
+    fn train_epoch(
+        &mut self,
+        sentences: impl Iterator<Item = OwnedAnnotatedSentence>,
+        gaz: &Gazetteers,
+        learning_rate: f64,
+        lambda: f64,
+    ) -> (usize, usize) {
+        let mut correct = 0;
+        let mut total = 0;
 
-                let feature_vectors = features::extract_features(&tokens, &gaz);
+        for sentence in sentences {
+            // Tokeniza e extrai features
+            // Em um cenário real, tokenização deve alinhar perfeitamente.
+            // Aqui reconstruímos tokens simples baseados na anotação para garantir alinhamento.
+            let tokens: Vec<crate::tokenizer::Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
+                // Offsets irrelevantes para features de treino simples
+                crate::tokenizer::Token::new(text.to_string(), 0, 0, i)
+            }).collect();
 
-                for (i, fv) in feature_vectors.iter().enumerate() {
-                    let true_tag = sentence.annotations[i].1;
+            let feature_vectors = self.extractor.extract(&tokens, gaz);
 
-                    // 1. Predição (Forward step)
-                    let scores = self.compute_scores(fv);
-                    let probs = self.softmax(&scores);
+            for (i, fv) in feature_vectors.iter().enumerate() {
+                let true_tag = sentence.annotations[i].1.as_str();
+                // No MEMM a tag anterior (do ouro) entra como mais uma feature
+                let with_history;
+                let fv = if self.history {
+                    let prev = if i == 0 { START_TAG } else { sentence.annotations[i - 1].1.as_str() };
+                    let mut extended = fv.clone();
+                    extended.insert(prev_tag_feature(prev), 1.0);
+                    with_history = extended;
+                    &with_history
+                } else {
+                    fv
+                };
 
-                    // Apenas para log de acurácia
-                    let (pred_tag, _) = self.predict_best(&scores);
-                    if pred_tag == true_tag {
-                        correct += 1;
-                    }
-                    total += 1;
-
-                    // 2. Atualização (Backward step - SGD)
-                    // Para cada classe, ajustamos os pesos das features ativas.
-                    // Regra de atualização: w = w + rate * (indicador_classe_correta - prob_predita)
-                    
-                    for (tag_idx, tag) in self.tags.iter().enumerate() {
-                        let prob = probs[tag_idx];
-                        let indicator = if tag == true_tag { 1.0 } else { 0.0 };
-                        let error = indicator - prob; // Gradiente do erro
-
-                        // Otimização: só atualiza se o erro for significativo
-                        if error.abs() > 1e-6 {
-                            for (fname, fval) in &fv.features {
-                                let key = (fname.clone(), tag.clone());
-                                let current_w = *self.weights.get(&key).unwrap_or(&0.0);
-                                
-                                // Update com regularização L2 (Ridge)
-                                // w_new = w_old + rate * (error * feature_val - lambda * w_old)
-                                let grad = error * fval;
-                                let reg = lambda * current_w;
-                                let new_w = current_w + learning_rate * (grad - reg);
-                                
-                                // Pruning de pesos muito próximos de zero (sparsity)
-                                if new_w.abs() > 1e-9 {
-                                    self.weights.insert(key, new_w);
-                                } else {
-                                    self.weights.remove(&key);
-                                }
+                // 1. Predição (Forward step)
+                let scores = self.compute_scores(fv);
+                let probs = self.softmax(&scores);
+
+                // Apenas para log de acurácia
+                let (pred_tag, _) = self.predict_best(&scores);
+                if pred_tag == true_tag {
+                    correct += 1;
+                }
+                total += 1;
+
+                // 2. Atualização (Backward step - SGD)
+                // Para cada classe, ajustamos os pesos das features ativas.
+                // Regra de atualização: w = w + rate * (indicador_classe_correta - prob_predita)
+            
+                for (tag_idx, tag) in self.tags.iter().enumerate() {
+                    let prob = probs[tag_idx];
+                    let indicator = if tag == true_tag { 1.0 } else { 0.0 };
+                    let error = indicator - prob; // Gradiente do erro
+
+                    // Otimização: só atualiza se o erro for significativo
+                    if error.abs() > 1e-6 {
+                        for (fname, fval) in &fv.features {
+                            let key = (fname.clone(), tag.clone());
+                            let current_w = *self.weights.get(&key).unwrap_or(&0.0);
+                        
+                            // Update com regularização L2 (Ridge)
+                            // w_new = w_old + rate * (error * feature_val - lambda * w_old)
+                            let grad = error * fval;
+                            let reg = lambda * current_w;
+                            let new_w = current_w + learning_rate * (grad - reg);
+                        
+                            // Pruning de pesos muito próximos de zero (sparsity)
+                            if new_w.abs() > 1e-9 {
+                                self.weights.insert(key, new_w);
+                            } else {
+                                self.weights.remove(&key);
                             }
                         }
                     }
                 }
             }
-            
-            if epoch % 5 == 0 {
-                println!("Epoch {}: Accuracy {:.2}%", epoch, (correct as f64 / total as f64) * 100.0);
-            }
         }
+        (correct, total)
     }
 
-    /// Prediz tags para uma sentença (Greedy Decoding).
+    /// Prediz tags para uma sentença.
     ///
     /// # Nota
-    /// Nesta implementação simplificada, a decisão é **Local** (Greedy):
-    /// Para cada token, escolhemos a tag com maior probabilidade isoladamente.
-    ///
-    /// Em implementações mais avançadas (MEMM), usaríamos Viterbi considerando
-    /// a tag anterior como uma feature.
+    /// No modelo padrão a decisão é **Local** (Greedy): para cada token, escolhemos a
+    /// tag com maior probabilidade isoladamente. No MEMM ([`MaxEntModel::memm`]) o
+    /// Viterbi escolhe a sequência inteira, considerando a tag anterior.
     pub fn predict(&self, tokens: &[String]) -> Vec<String> {
+        self.predict_with_confidence(tokens).into_iter().map(|(tag, _)| tag).collect()
+    }
+
+    /// Como [`MaxEntModel::predict`], com a probabilidade da tag escolhida: o softmax do
+    /// token no MaxEnt e, no MEMM, a posterior por forward-backward sobre as
+    /// distribuições locais `P(t | x_i, prev_tag)`.
+    pub fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
         let gaz = Gazetteers::new();
         // Reconstrói tokens
         let input_tokens: Vec<crate::tokenizer::Token> = tokens.iter().enumerate().map(|(i, text)| {
-             crate::tokenizer::Token {
-                text: text.clone(),
-                start: 0,
-                end: 0,
-                index: i,
-            }
+             crate::tokenizer::Token::new(text.clone(), 0, 0, i)
         }).collect();
 
-        let feature_vectors = features::extract_features(&input_tokens, &gaz);
+        let feature_vectors = self.extractor.extract(&input_tokens, &gaz);
+        if self.history && self.is_trained() {
+            let posteriors = self.memm_marginals(&feature_vectors);
+            return self
+                .decode_memm(&feature_vectors)
+                .into_iter()
+                .enumerate()
+                .map(|(i, tag)| {
+                    let p = self.tags.iter().position(|t| *t == tag).and_then(|t| posteriors.get(i)?.get(t)).copied();
+                    (tag, p.unwrap_or(0.0))
+                })
+                .collect();
+        }
         let mut result = Vec::with_capacity(tokens.len());
 
-        // TODO: Suportar features de transição (prev_tag) passando a tag prevista anterior
-        // Por enquanto, features.rs busca "prev_word" etc, mas "prev_tag" seria um feature extra.
-        // O extract_features padrão não usa prev_tag dinâmico.
-
         for fv in feature_vectors {
             let scores = self.compute_scores(&fv);
             let (best_tag, _) = self.predict_best(&scores);
-            result.push(best_tag);
+            let probability = self.tags.iter().position(|t| *t == best_tag).map_or(0.0, |t| self.softmax(&scores)[t]);
+            result.push((best_tag, probability));
         }
 
         result
     }
 
+    /// `P(y_i = t | x)` do MEMM para cada token e tag (na ordem de `tags`).
+    fn memm_marginals(&self, feature_vectors: &[FeatureVector]) -> Vec<Vec<f64>> {
+        let bases: Vec<Vec<f64>> = feature_vectors
+            .iter()
+            .map(|fv| {
+                let scores = self.compute_scores(fv);
+                self.tags.iter().map(|t| scores[t]).collect()
+            })
+            .collect();
+        let Some(first_base) = bases.first() else {
+            return Vec::new();
+        };
+        let first = self.memm_local(first_base, START_TAG);
+        let steps: Vec<Vec<Vec<f64>>> =
+            bases.iter().skip(1).map(|base| self.tags.iter().map(|prev| self.memm_local(base, prev)).collect()).collect();
+        forward_backward(bases.len(), self.tags.len(), |t| first[t], |i, u, t| steps[i - 1][u][t])
+    }
+
+    /// `log P(t | x_i, prev_tag)` de cada tag a partir da parte do score que não depende
+    /// da tag anterior; transições BIO inválidas ficam com `-inf`.
+    fn memm_local(&self, base: &[f64], prev_label: &str) -> Vec<f64> {
+        let feature = prev_tag_feature(prev_label);
+        let local: Vec<f64> = self
+            .tags
+            .iter()
+            .zip(base)
+            .map(|(tag, b)| b + self.weights.get(&(feature.clone(), tag.clone())).unwrap_or(&0.0))
+            .collect();
+        log_softmax(&local)
+            .into_iter()
+            .zip(&self.tags)
+            .map(|(log_p, tag)| if bio_allows(prev_label, tag) { log_p } else { f64::NEG_INFINITY })
+            .collect()
+    }
+
+    /// Viterbi do MEMM: `best[t]` é o log da probabilidade da melhor história que
+    /// termina em `t`; cada passo soma `log P(t | x_i, prev_tag=t')` para todo `t'`.
+    fn decode_memm(&self, feature_vectors: &[FeatureVector]) -> Vec<String> {
+        let n_tags = self.tags.len();
+        let mut best: Vec<f64> = Vec::new();
+        let mut backpointers: Vec<Vec<usize>> = Vec::with_capacity(feature_vectors.len());
+
+        for (i, fv) in feature_vectors.iter().enumerate() {
+            // Parte do score que não depende da tag anterior
+            let scores = self.compute_scores(fv);
+            let base: Vec<f64> = self.tags.iter().map(|t| scores[t]).collect();
+            let mut next = vec![f64::NEG_INFINITY; n_tags];
+            let mut back = vec![0; n_tags];
+
+            // No primeiro token a única história é o início da sentença
+            let histories: Vec<(Option<usize>, f64)> = if i == 0 {
+                vec![(None, 0.0)]
+            } else {
+                best.iter().enumerate().filter(|(_, s)| s.is_finite()).map(|(p, &s)| (Some(p), s)).collect()
+            };
+            for (prev, prev_score) in histories {
+                let prev_label = prev.map_or(START_TAG, |p| self.tags[p].as_str());
+                for (t, log_p) in self.memm_local(&base, prev_label).into_iter().enumerate() {
+                    let score = prev_score + log_p;
+                    if score > next[t] {
+                        next[t] = score;
+                        back[t] = prev.unwrap_or(0);
+                    }
+                }
+            }
+            best = next;
+            backpointers.push(back);
+        }
+
+        // Backtracking a partir da melhor tag final
+        let Some(mut current) = (0..best.len()).max_by(|&a, &b| best[a].total_cmp(&best[b])) else {
+            return Vec::new();
+        };
+        let mut path = vec![current; backpointers.len()];
+        for i in (1..backpointers.len()).rev() {
+            current = backpointers[i][current];
+            path[i - 1] = current;
+        }
+        path.into_iter().map(|t| self.tags[t].clone()).collect()
+    }
+
     fn compute_scores(&self, fv: &FeatureVector) -> HashMap<String, f64> {
         let mut scores = HashMap::new();
         for tag in &self.tags {
@@ -221,6 +420,59 @@ impl MaxEntModel {
     }
 }
 
+/// Tag "anterior" do primeiro token nas features `prev_tag=`.
+const START_TAG: &str = "<S>";
+
+fn prev_tag_feature(prev: &str) -> String {
+    format!("prev_tag={prev}")
+}
+
+/// `I-X` só continua `B-X` ou `I-X`; as demais tags podem seguir qualquer uma.
+fn bio_allows(prev: &str, next: &str) -> bool {
+    match next.strip_prefix("I-") {
+        Some(category) => prev.strip_prefix("B-").or_else(|| prev.strip_prefix("I-")) == Some(category),
+        None => true,
+    }
+}
+
+fn log_softmax(scores: &[f64]) -> Vec<f64> {
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let log_sum = max + scores.iter().map(|s| (s - max).exp()).sum::<f64>().ln();
+    scores.iter().map(|s| s - log_sum).collect()
+}
+
+impl Default for MaxEntModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceTagger for MaxEntModel {
+    fn name(&self) -> &str {
+        if self.history {
+            "memm"
+        } else {
+            "maxent"
+        }
+    }
+
+    fn predict(&self, tokens: &[String]) -> Vec<String> {
+        MaxEntModel::predict(self, tokens)
+    }
+
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
+        MaxEntModel::predict_with_confidence(self, tokens)
+    }
+
+    fn is_trained(&self) -> bool {
+        MaxEntModel::is_trained(self)
+    }
+
+    fn extractor(&self) -> Option<&dyn FeatureExtractor> {
+        Some(MaxEntModel::extractor(self))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,4 +501,37 @@ mod tests {
 
         assert_eq!(tags[0], "B-PER"); // Deve aprender que Lula é PER
     }
+
+    #[test]
+    fn test_memm_decodes_consistent_bio() {
+        let corpus = crate::corpus::get_corpus();
+        let mut memm = MaxEntModel::memm();
+        memm.train(&corpus, 10, 0.1, 0.01);
+        assert!(memm.is_memm() && memm.weights.keys().any(|(f, _)| f.starts_with("prev_tag=")));
+
+        let tokens: Vec<String> = "O presidente Luiz Inácio Lula da Silva visitou o Rio de Janeiro"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        let tags = memm.predict(&tokens);
+        assert_eq!(tags.len(), tokens.len());
+        // Nenhum I-X sem um B-X/I-X da mesma categoria antes
+        assert!(bio_allows(START_TAG, &tags[0]));
+        assert!(tags.windows(2).all(|w| bio_allows(&w[0], &w[1])), "{tags:?}");
+        assert_eq!(tags[9..], ["B-LOC", "I-LOC", "I-LOC"], "{tags:?}");
+
+        // O modelo serializado continua sendo um MEMM
+        let restored: MaxEntModel = bincode::deserialize(&bincode::serialize(&memm).unwrap()).unwrap();
+        assert_eq!(restored.predict(&tokens), tags);
+    }
+
+    #[test]
+    fn test_bio_allows() {
+        assert!(bio_allows("B-PER", "I-PER"));
+        assert!(bio_allows("I-LOC", "I-LOC"));
+        assert!(!bio_allows("O", "I-PER"));
+        assert!(!bio_allows("B-ORG", "I-PER"));
+        assert!(!bio_allows(START_TAG, "I-MISC"));
+        assert!(bio_allows("O", "B-PER") && bio_allows("I-PER", "O"));
+    }
 }
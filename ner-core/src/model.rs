@@ -4,6 +4,7 @@
 //! - **Pesos CRF** estimados a partir do corpus PT-BR usando frequências de tags
 //! - **Gazetteers** compilados automaticamente do corpus + listas manuais
 //! - **Motor de Regras** configurado com entidades brasileiras conhecidas
+//! - **Tabela de Aliases** com siglas extraídas dos textos embutidos
 //!
 //! ## Como os pesos foram derivados
 //!
@@ -11,17 +12,75 @@
 //! observadas no corpus anotado. Em um sistema real, seriam treinados via
 //! máxima verossimilhança condicional com L-BFGS. Para fins didáticos,
 //! codificamos pesos que refletem os padrões mais fortes do corpus.
-
+//!
+//! ## Persistência
+//!
+//! [`NerModel::build`] não treina os modelos secundários (HMM, MaxEnt, MEMM, Perceptron
+//! e SpanModel): cada um é treinado na primeira vez que o seu modo é usado (ver
+//! [`LazyModel`]), de modo que um pipeline que só roda `Hybrid` sobe em milissegundos.
+//! [`NerModel::train_pending`] antecipa esse treino, se a latência da primeira análise
+//! importar mais que a da inicialização.
+//!
+//! Um modelo construído (ou ajustado) uma vez pode ser gravado com [`NerModel::save`]
+//! (que treina antes o que ainda estiver pendente) e lido com
+//! [`NerModel::load`] (formato binário `bincode`, precedido da versão do formato; ver
+//! [`crate::format`]), e o pipeline montado direto dele com
+//! [`crate::NerPipeline::from_model`]:
+//!
+//! ```rust,no_run
+//! use ner_core::model::NerModel;
+//! use ner_core::NerPipeline;
+//!
+//! NerModel::build().save("modelo.bin")?;
+//! // Em outra execução, sem retreinar:
+//! let pipeline = NerPipeline::from_model(NerModel::load("modelo.bin")?);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Extratores de features customizados ([`crate::features::FeatureExtractor`]) não são
+//! gravados: o modelo lido usa o extrator padrão até que outro seja definido.
+//!
+//! ## Autoavaliação
+//!
+//! [`NerModel::build_with_self_check`] constrói o modelo deixando uma fatia do corpus
+//! fora do treino e mede cada modo nela (ver [`crate::self_check`]). O relatório fica em
+//! [`NerModel::self_check`] e é gravado junto com o modelo.
+
+use std::io;
+use std::path::Path;
+#[cfg(feature = "statistical")]
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "statistical")]
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "statistical")]
+use serde::{Deserializer, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::alias::AliasTable;
+use crate::calibration::ConfidenceCalibration;
 use crate::corpus::extract_gazetteers_from_corpus;
-use crate::corpus::get_corpus;
+use crate::corpus::{get_corpus, AnnotatedSentence};
+use crate::samples::demo_samples;
 use crate::crf::CrfModel;
-use crate::features::Gazetteers;
+use crate::features::{GazetteerKey, Gazetteers};
+use crate::format;
+use crate::language::LanguagePack;
+use crate::pipeline::NerPipeline;
+use crate::self_check::{self, SelfCheckConfig, SelfCheckReport};
+#[cfg(feature = "statistical")]
 use crate::hmm::HmmModel;
+#[cfg(feature = "statistical")]
 use crate::maxent::MaxEntModel;
+#[cfg(feature = "statistical")]
 use crate::perceptron::PerceptronModel;
+#[cfg(feature = "rules")]
 use crate::rule_based::RuleEngine;
+#[cfg(feature = "statistical")]
 use crate::span::SpanModel;
 use crate::tagger::{EntityCategory, Tag};
+use crate::thresholds::CategoryThresholds;
+use crate::tokenizer::BpeMergeTable;
 
 /// O modelo NER completo, agregando todos os sub-modelos e recursos.
 ///
@@ -29,7 +88,10 @@ use crate::tagger::{EntityCategory, Tag};
 /// - **CRF**: O modelo estatístico principal (pesos).
 /// - **Regras**: O motor de regras determinísticas.
 /// - **Gazelleers**: As listas de entidades conhecidas.
-/// - **Outros Modelos**: HMM, MaxEnt, Perceptron, SpanModel (para experimentação).
+/// - **Outros Modelos**: HMM, MaxEnt, MEMM, Perceptron, SpanModel (para experimentação).
+///
+/// Regras e modelos secundários só existem com as features `rules` e `statistical`.
+#[derive(Serialize, Deserialize)]
 pub struct NerModel {
     /// ## Exemplos
     ///
@@ -37,17 +99,44 @@ pub struct NerModel {
     /// usará o conhecimento embutido sobre língua portuguesa (sufixos, prefixos, listas)
     /// para pontuar as tags candidatas.
     pub crf: CrfModel,
-    /// Modelo HMM (Hidden Markov Model)
-    pub hmm: HmmModel,
-    /// Modelo de Maxima Entropia
-    pub maxent: MaxEntModel,
-    /// Modelo Perceptron
-    pub perceptron: PerceptronModel,
-    /// Modelo Span
-    pub span: SpanModel,
+    /// Modelo HMM (Hidden Markov Model), treinado no primeiro uso
+    #[cfg(feature = "statistical")]
+    pub hmm: LazyModel<HmmModel>,
+    /// Modelo de Maxima Entropia, treinado no primeiro uso
+    #[cfg(feature = "statistical")]
+    pub maxent: LazyModel<MaxEntModel>,
+    /// MaxEnt com a tag anterior como feature e decodificação Viterbi (MEMM), treinado no primeiro uso
+    #[cfg(feature = "statistical")]
+    pub memm: LazyModel<MaxEntModel>,
+    /// Modelo Perceptron, treinado no primeiro uso
+    #[cfg(feature = "statistical")]
+    pub perceptron: LazyModel<PerceptronModel>,
+    /// Modelo Span, treinado no primeiro uso
+    #[cfg(feature = "statistical")]
+    pub span: LazyModel<SpanModel>,
     /// Motor de regras para aplicação de dicionários e regex
+    #[cfg(feature = "rules")]
     pub rule_engine: RuleEngine,
+    /// Tabela de aliases ("Fiocruz" → "Fundação Oswaldo Cruz") compartilhada por NED e NEL.
+    ///
+    /// Derivada automaticamente dos textos embutidos; novos aliases podem ser
+    /// adicionados com [`AliasTable::add_alias`].
+    pub aliases: AliasTable,
+    /// Impressão digital da tabela de merges BPE usada no treinamento
+    /// (ver [`BpeMergeTable::fingerprint`]).
+    pub bpe_fingerprint: u64,
+    /// Variante da língua com que o modelo foi construído ([`LanguagePack::code`]).
+    pub language: String,
+    /// Confiança mínima por categoria, aplicada à saída de todos os modos
+    /// (ver [`crate::thresholds::ThresholdTuner`]). Vazio por padrão.
+    pub thresholds: CategoryThresholds,
+    /// Calibração da confiança de cada modo, aplicada antes dos limiares
+    /// (ver [`crate::calibration`]). Vazia por padrão.
+    pub calibration: ConfidenceCalibration,
+    /// Resultado da última autoavaliação, se houve uma (ver [`NerModel::build_with_self_check`]).
+    pub self_check: Option<SelfCheckReport>,
     /// Cache interno de gazetteers para acesso rápido
+    #[serde(rename = "gazetteers")]
     gazetteers_cache: Gazetteers,
 }
 
@@ -57,32 +146,103 @@ impl NerModel {
     /// Em um cenário de produção real, estes pesos seriam aprendidos via treinamento (L-BFGS).
     /// Aqui, eles são definidos manualmente para refletir intuições linguísticas sobre o português.
     pub fn build() -> Self {
-        let crf = build_crf_model();
-        let mut rule_engine = build_rule_engine();
-        // Os gazetteers alimentam tanto o motor de regras quanto a extração de features
-        let gazetteers = build_gazetteers(&mut rule_engine);
-        let corpus = get_corpus();
-
-        // Treinamento rápido dos modelos secundários para demonstração
-        let mut hmm = HmmModel::new();
-        hmm.train(&corpus);
+        Self::build_for(&LanguagePack::pt_br())
+    }
 
-        let mut maxent = MaxEntModel::new();
-        maxent.train(&corpus, 10, 0.1, 0.01);
+    /// Como [`NerModel::build`], com os títulos, sufixos societários e listas manuais
+    /// dos gazetteers de `pack` (ex: [`LanguagePack::pt_pt`]). Os pesos e os modelos
+    /// secundários continuam vindo do corpus embutido.
+    pub fn build_for(pack: &LanguagePack) -> Self {
+        Self::build_on(pack, &get_corpus())
+    }
 
-        let mut perceptron = PerceptronModel::new();
-        perceptron.train(&corpus, 5);
+    /// Como [`NerModel::build_for`], mas os modelos estatísticos treinam sem a fatia do
+    /// corpus reservada por `config`, e cada modo é avaliado nela em seguida. O
+    /// relatório fica em [`NerModel::self_check`] (ver [`crate::self_check`]).
+    pub fn build_with_self_check(pack: &LanguagePack, config: &SelfCheckConfig) -> Self {
+        let corpus = get_corpus();
+        let train: Vec<AnnotatedSentence> = corpus
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !config.is_holdout(*i))
+            .map(|(_, s)| AnnotatedSentence { text: s.text, domain: s.domain, annotations: s.annotations })
+            .collect();
+        let model = Self::build_on(pack, &train);
+
+        let mut pipeline = NerPipeline::from_model(model).with_tokenizer_config(pack.tokenizer_config());
+        let report = self_check::evaluate(&pipeline, &corpus, config);
+        pipeline.model.self_check = Some(report);
+        pipeline.model
+    }
 
-        let mut span = SpanModel::new();
-        span.train(&corpus, 5);
+    /// Constrói o modelo deixando os modelos secundários para treinar em `training` no
+    /// primeiro uso. Gazetteers e aliases vêm sempre do corpus inteiro.
+    fn build_on(pack: &LanguagePack, training: &[AnnotatedSentence<'static>]) -> Self {
+        let crf = build_crf_model();
+        let mut rule_engine = build_rule_engine(pack);
+        // Os gazetteers alimentam tanto o motor de regras quanto a extração de features
+        let gazetteers = build_gazetteers(&mut rule_engine, pack);
+        let aliases = build_alias_table(&get_corpus());
+
+        // Treinamento rápido dos modelos secundários para demonstração, adiado até o
+        // primeiro uso de cada um
+        #[cfg(feature = "statistical")]
+        let (hmm, maxent, memm, perceptron, span) = {
+            let training: Arc<[AnnotatedSentence<'static>]> = training
+                .iter()
+                .map(|s| AnnotatedSentence { text: s.text, domain: s.domain, annotations: s.annotations })
+                .collect();
+            (
+                LazyModel::new(training.clone(), |training| {
+                    let mut hmm = HmmModel::new();
+                    hmm.train(training);
+                    hmm
+                }),
+                LazyModel::new(training.clone(), |training| {
+                    let mut maxent = MaxEntModel::new();
+                    maxent.train(training, 10, 0.1, 0.01);
+                    maxent
+                }),
+                LazyModel::new(training.clone(), |training| {
+                    let mut memm = MaxEntModel::memm();
+                    memm.train(training, 10, 0.1, 0.01);
+                    memm
+                }),
+                LazyModel::new(training.clone(), |training| {
+                    let mut perceptron = PerceptronModel::new();
+                    perceptron.train(training, 5);
+                    perceptron
+                }),
+                LazyModel::new(training, |training| {
+                    let mut span = SpanModel::new();
+                    span.train(training, 5);
+                    span
+                }),
+            )
+        };
+        #[cfg(not(feature = "statistical"))]
+        let _ = training;
 
         Self {
             crf,
+            #[cfg(feature = "statistical")]
             hmm,
+            #[cfg(feature = "statistical")]
             maxent,
+            #[cfg(feature = "statistical")]
+            memm,
+            #[cfg(feature = "statistical")]
             perceptron,
+            #[cfg(feature = "statistical")]
             span,
+            #[cfg(feature = "rules")]
             rule_engine,
+            aliases,
+            bpe_fingerprint: BpeMergeTable::lite().fingerprint(),
+            language: pack.code.clone(),
+            thresholds: CategoryThresholds::default(),
+            calibration: ConfidenceCalibration::default(),
+            self_check: None,
             gazetteers_cache: gazetteers,
         }
     }
@@ -97,6 +257,164 @@ impl NerModel {
     pub fn gazetteers(&self) -> Gazetteers {
         self.gazetteers_cache.clone()
     }
+
+    /// Como [`NerModel::gazetteers`], sem a cópia (usado a cada análise pelo pipeline).
+    pub(crate) fn gazetteers_ref(&self) -> &Gazetteers {
+        &self.gazetteers_cache
+    }
+
+    /// Define o prior de uma entrada de gazetteer tanto nas features quanto nas regras
+    /// (ver [`Gazetteers::set_prior`]).
+    pub fn set_gazetteer_prior(&mut self, category: EntityCategory, name: &str, prior: f64) {
+        self.gazetteers_cache.set_prior(category, name, prior);
+        #[cfg(feature = "rules")]
+        self.rule_engine.set_prior(category, name, prior);
+    }
+
+    /// Adiciona uma entrada de gazetteer tanto nas features quanto nas regras, mantendo
+    /// as duas visões iguais (ver [`crate::shared::SharedPipeline::add_gazetteer_entry`]).
+    pub fn add_gazetteer_entry(&mut self, category: EntityCategory, name: &str) {
+        self.gazetteers_cache.insert(category, name);
+        #[cfg(feature = "rules")]
+        self.rule_engine.add_entry(category, name);
+    }
+
+    /// Grava o modelo inteiro em disco: pesos do CRF, tabelas do HMM, pesos de
+    /// MaxEnt/Perceptron/SpanModel, regras, gazetteers, aliases, limiares e calibração.
+    ///
+    /// O arquivo só pode ser lido por um build com as mesmas features `rules` e
+    /// `statistical` habilitadas.
+    ///
+    /// Modelos secundários ainda não usados são treinados antes da gravação. O arquivo
+    /// começa com a versão do formato ([`crate::format::MODEL_FORMAT_VERSION`]).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_binary(path, self)
+    }
+
+    /// Carrega um modelo salvo com [`NerModel::save`], sem retreinar nada.
+    ///
+    /// Arquivos de uma versão mais nova do crate, ou gravados antes do versionamento,
+    /// são recusados com um [`crate::format::FormatError`] em vez de lidos com pesos trocados.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_binary(path)
+    }
+
+    /// Treina agora os modelos secundários que ainda não foram usados, em vez de
+    /// deixar o custo para a primeira análise de cada modo. Sem a feature
+    /// `statistical`, não faz nada.
+    pub fn train_pending(&self) {
+        #[cfg(feature = "statistical")]
+        {
+            self.hmm.get();
+            self.maxent.get();
+            self.memm.get();
+            self.perceptron.get();
+            self.span.get();
+        }
+    }
+}
+
+/// Modelo secundário treinado sob demanda, na primeira vez que é acessado.
+///
+/// Dá acesso ao modelo por `Deref`/`DerefMut` (o primeiro acesso treina). A
+/// serialização grava só o modelo treinado, no mesmo layout de um `T` comum; um modelo
+/// lido do disco já vem pronto.
+///
+/// ```
+/// use ner_core::hmm::HmmModel;
+/// use ner_core::model::LazyModel;
+///
+/// let hmm = LazyModel::new(ner_core::corpus::get_corpus().into(), |training| {
+///     let mut hmm = HmmModel::new();
+///     hmm.train(training);
+///     hmm
+/// });
+/// assert!(!hmm.is_built());
+/// assert!(hmm.is_trained()); // treina aqui
+/// assert!(hmm.is_built());
+/// ```
+#[cfg(feature = "statistical")]
+pub struct LazyModel<T> {
+    model: OnceLock<T>,
+    /// Sentenças de treino, compartilhadas entre os modelos de um mesmo `NerModel`
+    training: Arc<[AnnotatedSentence<'static>]>,
+    /// Rotina de treino; `None` quando o modelo já foi criado pronto
+    train: Option<fn(&[AnnotatedSentence]) -> T>,
+}
+
+#[cfg(feature = "statistical")]
+impl<T> LazyModel<T> {
+    /// Modelo a ser treinado com `train(&training)` no primeiro acesso.
+    pub fn new(training: Arc<[AnnotatedSentence<'static>]>, train: fn(&[AnnotatedSentence]) -> T) -> Self {
+        Self { model: OnceLock::new(), training, train: Some(train) }
+    }
+
+    /// Modelo já treinado (ou montado à mão), sem treino pendente.
+    pub fn trained(model: T) -> Self {
+        Self { model: OnceLock::from(model), training: Arc::from([]), train: None }
+    }
+
+    /// O modelo, treinando-o se for o primeiro acesso.
+    pub fn get(&self) -> &T {
+        self.model.get_or_init(|| (self.train.expect("modelo sem treino pendente já é criado pronto"))(&self.training))
+    }
+
+    /// Como [`LazyModel::get`], com acesso mutável.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.get();
+        self.model.get_mut().expect("inicializado por get")
+    }
+
+    /// Se o modelo já foi treinado (ou criado pronto).
+    pub fn is_built(&self) -> bool {
+        self.model.get().is_some()
+    }
+
+    /// Se o modelo está (ou ficará, ao ser treinado) utilizável, sem disparar o treino:
+    /// com o treino pendente, basta haver sentenças de treino; senão vale `is_trained`.
+    pub fn is_usable(&self, is_trained: impl FnOnce(&T) -> bool) -> bool {
+        match self.model.get() {
+            Some(model) => is_trained(model),
+            None => !self.training.is_empty(),
+        }
+    }
+}
+
+#[cfg(feature = "statistical")]
+impl<T> Deref for LazyModel<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+#[cfg(feature = "statistical")]
+impl<T> DerefMut for LazyModel<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.get_mut()
+    }
+}
+
+#[cfg(feature = "statistical")]
+impl<T> From<T> for LazyModel<T> {
+    fn from(model: T) -> Self {
+        Self::trained(model)
+    }
+}
+
+#[cfg(feature = "statistical")]
+impl<T: Serialize> Serialize for LazyModel<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "statistical")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LazyModel<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::trained)
+    }
 }
 
 impl Default for NerModel {
@@ -105,6 +423,29 @@ impl Default for NerModel {
     }
 }
 
+/// Constrói a tabela de aliases a partir do corpus e dos textos de demonstração.
+///
+/// Pares "Nome Completo (SIGLA)" são extraídos automaticamente; siglas conhecidas
+/// que aparecem sem a forma expandida nos textos são adicionadas manualmente.
+fn build_alias_table(corpus: &[AnnotatedSentence]) -> AliasTable {
+    let demos = demo_samples();
+    let texts = corpus
+        .iter()
+        .map(|s| s.text)
+        .chain(demos.samples.iter().map(|s| s.text.as_str()));
+    let mut table = AliasTable::from_texts(texts);
+
+    let manual = [
+        ("OMS", "Organização Mundial da Saúde"),
+        ("ONU", "Organização das Nações Unidas"),
+        ("Lula", "Luiz Inácio Lula da Silva"),
+    ];
+    for (alias, canonical) in manual {
+        table.add_alias(alias, canonical);
+    }
+    table
+}
+
 /// Constrói o modelo CRF com pesos heurísticos baseados no corpus.
 ///
 /// Define manualmente a "importância" de cada feature para cada tag.
@@ -136,6 +477,10 @@ fn build_crf_model() -> CrfModel {
     model.set_emission("in_misc_gazetteer", &Tag::Begin(EntityCategory::Misc), 5.0);
     model.set_emission("in_misc_gazetteer", &Tag::Inside(EntityCategory::Misc), 4.5);
 
+    // Grafia estrangeira ("Bündchen", "Kubitschek") → sobrenomes, sobretudo após um primeiro nome
+    model.set_emission("looks_foreign", &Tag::Begin(EntityCategory::Per), 1.2);
+    model.set_emission("looks_foreign", &Tag::Inside(EntityCategory::Per), 2.0);
+
     // Sufixo "-inho", "-inha" → frequentemente apelidos de pessoas
     model.set_emission("suffix3=nho", &Tag::Begin(EntityCategory::Per), 1.0);
     model.set_emission("suffix3=nha", &Tag::Begin(EntityCategory::Per), 1.0);
@@ -226,6 +571,19 @@ fn build_crf_model() -> CrfModel {
     model.set_emission("prev_word=operação", &Tag::Begin(EntityCategory::Misc), 1.5);
     model.set_emission("prev_word=fórmula", &Tag::Begin(EntityCategory::Misc), 2.0);
 
+    // --- DATA (DATE) e HORÁRIO (TIME) ---
+    // Anos de 4 dígitos ("1888", "2023") são quase sempre datas, superando o peso de `is_digit`.
+    model.set_emission("is_year", &Tag::Begin(EntityCategory::Date), 4.0);
+    model.set_emission("is_year", &Tag::Inside(EntityCategory::Date), 3.0);
+    model.set_emission("in_date_gazetteer", &Tag::Begin(EntityCategory::Date), 4.5);
+    model.set_emission("in_date_gazetteer", &Tag::Inside(EntityCategory::Date), 4.0);
+    model.set_emission("prev_word=século", &Tag::Inside(EntityCategory::Date), 3.0);
+    model.set_emission("prev_word=ano", &Tag::Begin(EntityCategory::Date), 1.5);
+    model.set_emission("prev_word=dia", &Tag::Begin(EntityCategory::Date), 1.5);
+    model.set_emission("is_time", &Tag::Begin(EntityCategory::Time), 6.0);
+    model.set_emission("in_time_gazetteer", &Tag::Begin(EntityCategory::Time), 4.5);
+    model.set_emission("prev_word=às", &Tag::Begin(EntityCategory::Time), 1.5);
+
     // Palavra comum → Outside
     model.set_emission("BOS", &Tag::Outside, 0.5);
     model.set_emission("bias", &Tag::Outside, 1.0);
@@ -233,9 +591,25 @@ fn build_crf_model() -> CrfModel {
     // Pontuação → sempre Outside
     model.set_emission("is_punctuation", &Tag::Outside, 5.0);
 
+    // Endereços web e de e-mail são contatos, não nomes ("www.financas.com" não é ORG)
+    model.set_emission("kind=url", &Tag::Outside, 3.0);
+    model.set_emission("kind=email", &Tag::Outside, 3.0);
+
     // Dígito puro → geralmente Outside (anos, números)
     model.set_emission("is_digit", &Tag::Outside, 2.0);
 
+    // Classe gramatical: artigos, pronomes e verbos quase nunca são entidade, mesmo
+    // capitalizados no início da frase ("A", "Ele"); nomes próprios tendem a ser.
+    // Preposições ficam leves: "do" em "Banco do Brasil" é parte do nome.
+    model.set_emission("pos=DET", &Tag::Outside, 1.5);
+    model.set_emission("pos=PRON", &Tag::Outside, 1.5);
+    model.set_emission("pos=VERB", &Tag::Outside, 1.0);
+    model.set_emission("pos=AUX", &Tag::Outside, 1.0);
+    model.set_emission("pos=ADP", &Tag::Outside, 0.5);
+    model.set_emission("pos=PROPN", &Tag::Begin(EntityCategory::Per), 0.5);
+    model.set_emission("pos=PROPN", &Tag::Begin(EntityCategory::Org), 0.5);
+    model.set_emission("pos=PROPN", &Tag::Begin(EntityCategory::Loc), 0.5);
+
     // =====================================================================
     // PESOS DE TRANSIÇÃO
     // Capturam a regularidade das sequências BIO
@@ -253,13 +627,7 @@ fn build_crf_model() -> CrfModel {
     }
 
     // Transições válidas B→I da mesma categoria têm alto peso
-    let categories = [
-        EntityCategory::Per,
-        EntityCategory::Org,
-        EntityCategory::Loc,
-        EntityCategory::Misc,
-    ];
-    for cat in &categories {
+    for cat in &EntityCategory::all() {
         let b = Tag::Begin(*cat);
         let i = Tag::Inside(*cat);
         model.set_transition(&b, &i, 4.0);   // B-PER → I-PER: muito provável
@@ -275,150 +643,221 @@ fn build_crf_model() -> CrfModel {
     model
 }
 
-/// Constrói os gazetteers a partir do corpus e de listas manuais
-fn build_gazetteers(rule_engine: &mut RuleEngine) -> Gazetteers {
-    let (corpus_persons, corpus_locs, corpus_orgs, corpus_misc) =
-        extract_gazetteers_from_corpus();
+/// Constrói os gazetteers a partir do corpus e das listas manuais de `pack`
+fn build_gazetteers(rule_engine: &mut RuleSink, pack: &LanguagePack) -> Gazetteers {
+    let corpus_gaz = extract_gazetteers_from_corpus();
 
     let mut gaz = Gazetteers::new();
 
     // Inclui entidades do corpus
-    for p in &corpus_persons {
-        for word in p.split_whitespace() {
-            if word.len() > 2 {
-                gaz.persons.insert(word.to_lowercase());
-                rule_engine.add_person(word);
-            }
+    // Todas as chaves passam por `GazetteerKey`, a mesma normalização usada nas consultas
+    for p in &corpus_gaz.persons {
+        for word in GazetteerKey::words(p) {
+            rule_engine.add_person(&word);
         }
+        gaz.insert(EntityCategory::Per, p);
         rule_engine.add_person(p);
     }
-    for l in &corpus_locs {
-        for word in l.split_whitespace() {
-            if word.len() > 3 {
-                gaz.locations.insert(word.to_lowercase());
-            }
-        }
+    for l in &corpus_gaz.locations {
+        gaz.insert(EntityCategory::Loc, l);
         rule_engine.add_location(l);
     }
-    for o in &corpus_orgs {
-        for word in o.split_whitespace() {
-            if word.len() > 3 {
-                gaz.organizations.insert(word.to_lowercase());
-            }
-        }
+    for o in &corpus_gaz.orgs {
+        gaz.insert(EntityCategory::Org, o);
         rule_engine.add_org(o);
     }
-    for m in &corpus_misc {
-        for word in m.split_whitespace() {
-            if word.len() > 3 {
-                gaz.misc.insert(word.to_lowercase());
-            }
-        }
+    for m in &corpus_gaz.misc {
+        gaz.insert(EntityCategory::Misc, m);
         rule_engine.add_misc(m);
     }
+    // Expressões temporais: apenas as palavras (não números) alimentam o gazetteer de features,
+    // já que anos e dias são cobertos pelas features de forma (`is_year`, `is_digit`).
+    for d in &corpus_gaz.dates {
+        let words = GazetteerKey::words(d);
+        gaz.dates.extend(words.into_iter().filter(|w| w.chars().any(char::is_alphabetic)));
+        rule_engine.add_date(d);
+    }
+    for t in &corpus_gaz.times {
+        if t.chars().all(|c| c.is_alphabetic() || c == '-') {
+            gaz.times.insert(GazetteerKey::normalize(t));
+            rule_engine.add_time(t);
+        }
+    }
 
-    // Listas manuais estendidas — Políticos e figuras históricas do Brasil
-    let extra_persons = vec![
-        "Getúlio", "Vargas", "Juscelino", "Kubitschek", "Jânio", "Quadros",
-        "Costa", "Silva", "Geisel", "Figueiredo", "Sarney", "Collor", "Itamar",
-        "Franco", "Cardoso", "Rousseff", "Temer", "Bolsonaro", "Haddad",
-        "Mantega", "Meirelles", "Guedes", "Ciro", "Alckmin", "Moro",
-        "Senna", "Pelé", "Ronaldo", "Ronaldinho", "Zico", "Garrincha",
-        "Neymar", "Vini", "Rodrygo", "Casemiro", "Marquinhos",
-        "Gisele", "Bündchen", "Xuxa", "Ivete", "Sangalo", "Anitta",
-        "Caetano", "Veloso", "Gilberto", "Gil", "Chico", "Buarque",
-        "Machado", "Assis", "Guimarães", "Rosa", "Clarice", "Lispector",
-        "Oswald", "Andrade", "Drummond", "Pessoa",
-    ];
-    for p in extra_persons {
-        gaz.persons.insert(p.to_lowercase());
+    // Listas manuais da variante da língua (ver `crate::language`)
+    for p in &pack.persons {
+        gaz.insert(EntityCategory::Per, p);
         rule_engine.add_person(p);
     }
-
-    // Cidades e locais do Brasil
-    let extra_locs = vec![
-        "Brasília", "São Paulo", "Rio de Janeiro", "Salvador", "Fortaleza",
-        "Manaus", "Curitiba", "Recife", "Porto Alegre", "Belém", "Goiânia",
-        "Florianópolis", "Maceió", "Natal", "Teresina", "Campo Grande",
-        "João Pessoa", "Aracaju", "Cuiabá", "Macapá", "Porto Velho",
-        "Boa Vista", "Palmas", "Rio Branco", "Vitória", "São Luís",
-        "Amazônia", "Pantanal", "Cerrado", "Caatinga", "Pampa",
-        "Nordeste", "Sudeste", "Norte", "Sul", "Centro-Oeste",
-        "Maracanã", "Itaquerão", "Arena", "Mineirão", "Beira-Rio",
-        "Planalto", "Palácio", "Congresso", "Senado", "Câmara",
-        "Supremo", "STF", "STJ", "TSE", "TRF",
-        "Argentina", "Chile", "Colômbia", "Peru", "Venezuela", "Uruguai",
-        "Paraguai", "Bolívia", "Equador", "Qatar", "Japão", "Coreia",
-        "Alemanha", "França", "Espanha", "Portugal", "Itália", "Inglaterra",
-        "Estados Unidos", "China", "Rússia", "Índia", "África",
-        "Europa", "Ásia", "América", "Latina", "Caribe",
-        "Ipiranga", "Tietê", "São Francisco", "Paraná", "Tocantins",
-        "Xingu", "Negro", "Solimões", "Tapajós",
-    ];
-    for l in extra_locs {
-        for word in l.split_whitespace() {
-            if word.len() > 3 {
-                gaz.locations.insert(word.to_lowercase());
-            }
-        }
+    for l in &pack.locations {
+        gaz.insert(EntityCategory::Loc, l);
         rule_engine.add_location(l);
     }
-
-    // Organizações brasileiras
-    let extra_orgs = vec![
-        "Petrobras", "Vale", "Embraer", "Nubank", "Itaú", "Bradesco", "Santander",
-        "Caixa", "Econômica", "Federal", "BNDES", "IBGE", "INPE", "Fiocruz",
-        "Anvisa", "Anatel", "Aneel", "ANS", "ANP", "CADE",
-        "Partidos", "PT", "PL", "MDB", "PSDB", "PDT", "PSB", "Republicanos",
-        "Podemos", "União", "Brasil", "Solidariedade", "Avante",
-        "Flamengo", "Palmeiras", "Corinthians", "São Paulo", "Grêmio",
-        "Internacional", "Atlético", "Cruzeiro", "Fluminense", "Vasco",
-        "Botafogo", "Santos", "Sport", "Bahia", "Ceará", "Fortaleza",
-        "McLaren", "Ferrari", "Mercedes", "Red Bull", "Alpine",
-        "ONU", "UNESCO", "UNICEF", "OMS", "FMI", "Banco Mundial",
-        "BRICS", "Mercosul", "ALBA", "UNASUL", "CELAC",
-        "FIFA", "CBF", "COI", "COB",
-        "USP", "Unicamp", "UFRJ", "UnB", "UFMG", "UFRGS",
-        "Globo", "Record", "SBT", "Band", "CNN Brasil", "UOL", "Folha",
-        "Estadão", "O Globo", "Veja", "Época", "IstoÉ",
-    ];
-    for o in extra_orgs {
-        for word in o.split_whitespace() {
-            if word.len() > 2 {
-                gaz.organizations.insert(word.to_lowercase());
-            }
-        }
+    for o in &pack.organizations {
+        gaz.insert(EntityCategory::Org, o);
         rule_engine.add_org(o);
     }
-
-    // Miscelânea (eventos, produtos, leis, etc.)
-    let extra_misc = vec![
-        "Copa do Mundo", "Olimpíadas", "Jogos Olímpicos", "Paralímpicos",
-        "Libertadores", "Copa América", "Europeu", "Champions League",
-        "Fórmula 1", "MotoGP", "Rally Dakar",
-        "Carnaval", "Réveillon", "Natal", "São João", "Festa Junina",
-        "COVID-19", "Dengue", "Febre Amarela", "Zika", "Malária",
-        "PIB", "Selic", "IPCA", "IBOV", "FGTS", "INSS", "SUS",
-        "Constituição", "Marco Civil", "Lei Maria da Penha", "ECA",
-        "Operação Lava Jato", "Mensalão", "Privatizações",
-        "Independência", "República", "Proclamação", "Abolição",
-        "Inconfidência Mineira", "Revolução de 1930", "AI-5",
-        "Amazônia-1", "SGDC", "VLS",
-        "Gabriela Cravo e Canela", "Grande Sertão Veredas",
-    ];
-    for m in extra_misc {
-        for word in m.split_whitespace() {
-            if word.len() > 3 {
-                gaz.misc.insert(word.to_lowercase());
-            }
-        }
+    for m in &pack.misc {
+        gaz.insert(EntityCategory::Misc, m);
         rule_engine.add_misc(m);
     }
+    for d in &pack.dates {
+        gaz.dates.insert(GazetteerKey::normalize(d));
+        rule_engine.add_date(d);
+    }
+    for t in &pack.times {
+        gaz.times.insert(GazetteerKey::normalize(t));
+        rule_engine.add_time(t);
+    }
+    for (name, prior) in &pack.org_priors {
+        gaz.set_prior(EntityCategory::Org, name, *prior);
+        rule_engine.set_prior(EntityCategory::Org, name, *prior);
+    }
 
     gaz
 }
 
+/// Destino dos nomes de gazetteer no motor de regras.
+#[cfg(feature = "rules")]
+type RuleSink = RuleEngine;
+
+/// Sem a feature `rules` não há motor: os nomes alimentam apenas os gazetteers de features.
+#[cfg(not(feature = "rules"))]
+struct RuleSink;
+
+#[cfg(not(feature = "rules"))]
+impl RuleSink {
+    fn add_person(&mut self, _name: &str) {}
+    fn add_location(&mut self, _name: &str) {}
+    fn add_org(&mut self, _name: &str) {}
+    fn add_misc(&mut self, _name: &str) {}
+    fn add_date(&mut self, _name: &str) {}
+    fn add_time(&mut self, _name: &str) {}
+    fn set_prior(&mut self, _category: EntityCategory, _name: &str, _prior: f64) {}
+}
+
 /// Constrói o motor de regras base (sem gazetteers, que são adicionados depois)
-fn build_rule_engine() -> RuleEngine {
-    RuleEngine::new()
+#[cfg(feature = "rules")]
+fn build_rule_engine(pack: &LanguagePack) -> RuleSink {
+    RuleEngine::for_language(pack)
+}
+
+#[cfg(not(feature = "rules"))]
+fn build_rule_engine(_pack: &LanguagePack) -> RuleSink {
+    RuleSink
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{FormatError, MODEL_FORMAT_VERSION, MODEL_MAGIC};
+    use crate::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut model = NerModel::build();
+        model.set_gazetteer_prior(EntityCategory::Loc, "Paraná", 0.3);
+        model.thresholds.set(EntityCategory::Misc, 0.7);
+
+        let path = std::env::temp_dir().join(format!("ner_model_{}.bin", std::process::id()));
+        model.save(&path).unwrap();
+        let loaded = NerModel::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.crf.emission_weights, model.crf.emission_weights);
+        assert_eq!(loaded.thresholds, model.thresholds);
+        assert_eq!(loaded.gazetteers_ref().prior(EntityCategory::Loc, "paraná"), 0.3);
+        assert_eq!(loaded.bpe_fingerprint, model.bpe_fingerprint);
+
+        // O pipeline montado do modelo lido dá o mesmo resultado em todos os modos
+        let text = "O presidente Lula visitou a Petrobras no Rio de Janeiro.";
+        let built = NerPipeline::from_model(model);
+        let restored = NerPipeline::from_model(loaded);
+        let modes = [
+            AlgorithmMode::Hybrid,
+            AlgorithmMode::RulesOnly,
+            AlgorithmMode::CrfOnly,
+            AlgorithmMode::Hmm,
+            AlgorithmMode::MaxEnt,
+            AlgorithmMode::Memm,
+            AlgorithmMode::Perceptron,
+            AlgorithmMode::SpanBased,
+        ];
+        for mode in modes {
+            let key = |p: &NerPipeline| {
+                let (_, entities) = p.analyze_with_mode(text, mode, TokenizerMode::Standard);
+                entities.into_iter().map(|e| (e.start, e.end, e.category)).collect::<Vec<_>>()
+            };
+            assert_eq!(key(&restored), key(&built), "{mode:?}");
+        }
+
+        let garbage = std::env::temp_dir().join(format!("ner_model_garbage_{}.bin", std::process::id()));
+        std::fs::write(&garbage, b"nao e um modelo").unwrap();
+        let err = NerModel::load(&garbage).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.get_ref().unwrap().downcast_ref::<FormatError>(), Some(&FormatError::Unversioned));
+
+        // Cabeçalho de uma versão futura: recusado antes de ler os pesos
+        let mut future = MODEL_MAGIC.to_vec();
+        future.extend_from_slice(&(MODEL_FORMAT_VERSION + 1).to_le_bytes());
+        std::fs::write(&garbage, future).unwrap();
+        let err = NerModel::load(&garbage).err().unwrap();
+        std::fs::remove_file(&garbage).unwrap();
+        assert!(err.to_string().contains("mais novo"), "{err}");
+    }
+
+    #[test]
+    #[cfg(feature = "statistical")]
+    fn test_secondary_models_train_on_first_use() {
+        let pipeline = NerPipeline::new();
+        let model = &pipeline.model;
+        let pending = |m: &NerModel| [m.hmm.is_built(), m.maxent.is_built(), m.memm.is_built(), m.perceptron.is_built(), m.span.is_built()];
+        assert_eq!(pending(model), [false; 5]);
+
+        // Hybrid e as capacidades não treinam nada; cada modo treina só o seu modelo
+        pipeline.analyze_with_mode("Lula visitou a Petrobras.", AlgorithmMode::Hybrid, TokenizerMode::Standard);
+        let lazy_modes = [AlgorithmMode::Hmm, AlgorithmMode::MaxEnt, AlgorithmMode::Memm, AlgorithmMode::Perceptron, AlgorithmMode::SpanBased];
+        let capabilities = pipeline.capabilities();
+        assert!(capabilities.modes.iter().filter(|m| lazy_modes.contains(&m.mode)).all(|m| m.available));
+        assert_eq!(pending(model), [false; 5]);
+        pipeline.analyze_with_mode("Lula visitou a Petrobras.", AlgorithmMode::Hmm, TokenizerMode::Standard);
+        assert_eq!(pending(model), [true, false, false, false, false]);
+
+        model.train_pending();
+        assert_eq!(pending(model), [true; 5]);
+        assert!(model.perceptron.is_trained() && model.span.is_trained());
+
+        // Um modelo vazio, sem sentenças de treino, não fica disponível
+        let empty: LazyModel<HmmModel> = LazyModel::new(Arc::from([]), |training| {
+            let mut hmm = HmmModel::new();
+            hmm.train(training);
+            hmm
+        });
+        assert!(!empty.is_usable(|m| m.is_trained()));
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_build_with_self_check() {
+        let config = SelfCheckConfig::default().with_modes([AlgorithmMode::RulesOnly, AlgorithmMode::Hmm, AlgorithmMode::FeaturesOnly]);
+        let model = NerModel::build_with_self_check(&LanguagePack::pt_br(), &config);
+        let report = model.self_check.clone().unwrap();
+        let corpus = get_corpus();
+        assert_eq!(report.holdout_sentences, corpus.len().div_ceil(5));
+        assert_eq!(report.holdout_sentences + report.train_sentences, corpus.len());
+
+        // FeaturesOnly não produz entidades; os outros acertam parte da fatia reservada
+        let modes: Vec<AlgorithmMode> = report.scores.iter().map(|s| s.mode).collect();
+        assert_eq!(modes[..2], [AlgorithmMode::RulesOnly, AlgorithmMode::Hmm]);
+        for score in report.scores.iter().take(2) {
+            assert!(score.gold > 0 && score.correct > 0 && score.f1 > 0.0 && score.f1 <= 1.0, "{score:?}");
+        }
+
+        // O relatório aparece nas capacidades e pode ser refeito sobre o modelo pronto
+        let mut pipeline = NerPipeline::from_model(model);
+        assert_eq!(pipeline.capabilities().self_check, Some(report.clone()));
+        let rerun = pipeline.run_self_check(&config).clone();
+        assert_eq!(rerun.score(AlgorithmMode::RulesOnly), report.score(AlgorithmMode::RulesOnly));
+        assert!(rerun.regressions(&report, 0.0).is_empty());
+    }
 }
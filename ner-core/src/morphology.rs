@@ -0,0 +1,325 @@
+//! # Morfologia: Radicais do Português
+//!
+//! Num corpus pequeno, a flexão espalha a mesma palavra por várias features:
+//! "governou", "governa", "governava" e "governador" são palavras diferentes para o
+//! modelo, e o peso aprendido para uma não vale para as outras. [`stem`] reduz cada
+//! forma a um radical comum (`govern`), que o extrator de features emite como
+//! `lemma=...` (ver [`crate::features::FeatureTemplate::lemmas`]).
+//!
+//! O algoritmo segue o **RSLP** (*Removedor de Sufixos da Língua Portuguesa*, Orengo &
+//! Huyck, 2001), em versão enxuta: uma sequência de passos, cada um com regras
+//! `sufixo → substituição`, um tamanho mínimo do radical e exceções:
+//!
+//! 1. **Plural** (palavras terminadas em "s"): "ões" → "ão", "ais" → "al", "res" → "r"...
+//! 2. **Feminino** (terminadas em "a"): "ora" → "or", "osa" → "oso", "eira" → "eiro"...
+//! 3. **Grau**: superlativos e diminutivos ("íssimo", "inho", "zinho").
+//! 4. **Advérbio**: "mente".
+//! 5. **Substantivo**: "ação", "mento", "idade", "ador", "ismo"...
+//! 6. **Verbo** (só se o passo 5 não removeu nada): "aram", "ava", "ou", "ar"...
+//! 7. **Vogal temática** (só se nem 5 nem 6 removeram nada): "a", "e" ou "o" finais.
+//! 8. Remoção de acentos do radical.
+//!
+//! O resultado é um radical, não um lema de dicionário ("govern", não "governar"):
+//! basta que formas da mesma palavra se encontrem. As listas de regras e exceções são
+//! um subconjunto das do RSLP original, suficiente para as flexões mais frequentes.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::morphology::stem;
+//!
+//! for word in ["governou", "governa", "governava", "governará", "Governador", "governadores"] {
+//!     assert_eq!(stem(word), "govern");
+//! }
+//! assert_eq!(stem("presidentes"), stem("presidiu"));
+//! assert_eq!(stem("de"), "de"); // palavras curtas ficam como estão
+//! ```
+
+/// Uma regra de um passo: troca `suffix` por `replacement` se sobrarem ao menos
+/// `min_stem` caracteres e a palavra não for uma das `exceptions`.
+struct Rule {
+    suffix: &'static str,
+    min_stem: usize,
+    replacement: &'static str,
+    exceptions: &'static [&'static str],
+}
+
+const fn rule(suffix: &'static str, min_stem: usize, replacement: &'static str) -> Rule {
+    Rule { suffix, min_stem, replacement, exceptions: &[] }
+}
+
+const fn except(suffix: &'static str, min_stem: usize, replacement: &'static str, exceptions: &'static [&'static str]) -> Rule {
+    Rule { suffix, min_stem, replacement, exceptions }
+}
+
+/// Palavras com menos caracteres que isto não são reduzidas.
+const MIN_WORD_CHARS: usize = 3;
+
+const PLURAL: &[Rule] = &[
+    rule("ns", 1, "m"),
+    rule("ões", 3, "ão"),
+    except("ães", 1, "ão", &["mães"]),
+    except("ais", 1, "al", &["cais", "mais"]),
+    rule("éis", 2, "el"),
+    rule("eis", 2, "el"),
+    rule("óis", 2, "ol"),
+    except("is", 2, "il", &["lápis", "cais", "mais", "crúcis", "biquínis", "pois", "depois", "dois", "leis"]),
+    rule("les", 3, "l"),
+    rule("res", 3, "r"),
+    except(
+        "s",
+        2,
+        "",
+        &[
+            "aliás", "pires", "lápis", "cais", "mais", "mas", "menos", "férias", "fezes", "pêsames", "gás", "atrás",
+            "através", "convés", "país", "após", "ambas", "ambos", "messias", "ônibus", "vírus", "tênis",
+        ],
+    ),
+];
+
+const FEMININE: &[Rule] = &[
+    except("ona", 3, "ão", &["abandona", "lona", "iona", "cortisona", "monótona", "maratona", "acetona", "detona", "carona"]),
+    except("ora", 3, "or", &["agora", "embora", "fora", "hora", "senhora"]),
+    except(
+        "na",
+        4,
+        "no",
+        &["carona", "abandona", "lona", "iona", "cortisona", "monótona", "maratona", "acetona", "detona", "guiana",
+          "campana", "grana", "caravana", "banana", "paisana"],
+    ),
+    except("inha", 3, "inho", &["rainha", "linha", "minha"]),
+    except("esa", 3, "ês", &["mesa", "obesa", "princesa", "turquesa", "ilesa", "pesa", "presa", "empresa", "defesa"]),
+    except("osa", 3, "oso", &["mucosa", "prosa"]),
+    rule("íaca", 3, "íaco"),
+    except("ica", 3, "ico", &["dica"]),
+    except("ada", 2, "ado", &["pitada"]),
+    except("ida", 3, "ido", &["vida"]),
+    except("ída", 3, "ido", &["recaída", "saída", "dúvida"]),
+    except("ima", 3, "imo", &["vítima"]),
+    except("iva", 3, "ivo", &["saliva", "oliva"]),
+    except(
+        "eira",
+        3,
+        "eiro",
+        &["beira", "cadeira", "frigideira", "bandeira", "feira", "capoeira", "barreira", "fronteira", "besteira", "poeira"],
+    ),
+];
+
+const DEGREE: &[Rule] = &[
+    rule("abilíssimo", 5, ""),
+    rule("díssimo", 5, ""),
+    rule("íssimo", 3, ""),
+    rule("ésimo", 3, ""),
+    rule("érrimo", 4, ""),
+    rule("zinho", 2, ""),
+    rule("quinho", 4, "c"),
+    rule("uinho", 4, ""),
+    rule("adinho", 3, ""),
+    except("inho", 3, "", &["caminho", "cominho", "vizinho", "sobrinho", "padrinho"]),
+    rule("zão", 2, ""),
+];
+
+const ADVERB: &[Rule] = &[except("mente", 4, "", &["experimente"])];
+
+const NOUN: &[Rule] = &[
+    rule("encialista", 4, ""),
+    rule("alista", 5, ""),
+    except("agem", 3, "", &["coragem", "chantagem", "vantagem", "carruagem"]),
+    rule("iamento", 4, ""),
+    except("amento", 3, "", &["firmamento", "fundamento", "departamento"]),
+    rule("imento", 3, ""),
+    except("mento", 6, "", &["firmamento", "elemento", "complemento", "instrumento", "departamento"]),
+    rule("alização", 4, ""),
+    rule("ização", 4, ""),
+    rule("ação", 3, ""),
+    rule("ição", 3, ""),
+    rule("ução", 3, ""),
+    rule("alizado", 4, ""),
+    rule("izado", 5, ""),
+    except("ativo", 4, "", &["pejorativo", "relativo"]),
+    except("ivo", 4, "", &["passivo", "possessivo", "pejorativo", "positivo"]),
+    except("ado", 2, "", &["grado"]),
+    except("ido", 3, "", &["cândido", "tímido", "marido", "partido"]),
+    rule("ador", 3, ""),
+    rule("edor", 3, ""),
+    except("idor", 4, "", &["ouvidor"]),
+    rule("atoria", 5, ""),
+    except("tor", 3, "", &["benfeitor", "leitor", "editor", "pastor", "produtor", "promotor", "consultor"]),
+    rule("abilidade", 5, ""),
+    rule("ividade", 5, ""),
+    except("idade", 4, "", &["autoridade", "comunidade"]),
+    rule("cionista", 5, ""),
+    rule("ional", 4, ""),
+    rule("ência", 3, ""),
+    except("ância", 4, "", &["ambulância"]),
+    except("eiro", 3, "", &["desfiladeiro", "pioneiro", "mosteiro"]),
+    except("oso", 3, "", &["precioso"]),
+    except("ário", 3, "", &["voluntário", "salário", "aniversário", "diário", "armário"]),
+    rule("eza", 3, ""),
+    except("ante", 2, "", &["gigante", "elefante", "adiante", "possante", "instante", "restante"]),
+    except("tico", 3, "", &["político", "prático", "doméstico", "diagnóstico", "idêntico", "artístico", "autêntico", "crítico"]),
+    except("ico", 4, "", &["público"]),
+    except("ista", 4, "", &["revista"]),
+    except("ente", 4, "", &["alimente", "acrescente", "permanente", "oriente", "aparente"]),
+    rule("ense", 5, ""),
+    except("ável", 2, "", &["afável", "razoável", "potável", "vulnerável"]),
+    except("ível", 3, "", &["possível"]),
+    except("ural", 4, "", &["natural"]),
+    except("al", 4, "", &["afinal", "animal", "estatal", "fiscal", "formal", "pessoal", "liberal", "postal", "virtual", "visual", "pontual"]),
+    rule("alismo", 4, ""),
+    except("ismo", 3, "", &["cinismo"]),
+];
+
+const VERB: &[Rule] = &[
+    rule("aríamo", 2, ""),
+    rule("ássemo", 2, ""),
+    rule("eríamo", 2, ""),
+    rule("êssemo", 2, ""),
+    rule("iríamo", 3, ""),
+    rule("íssemo", 3, ""),
+    rule("áramo", 2, ""),
+    rule("aremo", 2, ""),
+    rule("ariam", 2, ""),
+    rule("assem", 2, ""),
+    rule("ávamo", 2, ""),
+    rule("êramo", 3, ""),
+    rule("eremo", 3, ""),
+    rule("eriam", 3, ""),
+    rule("essem", 3, ""),
+    rule("íramo", 3, ""),
+    rule("iremo", 3, ""),
+    rule("iriam", 3, ""),
+    rule("issem", 3, ""),
+    rule("ando", 2, ""),
+    rule("endo", 3, ""),
+    rule("indo", 3, ""),
+    rule("aram", 2, ""),
+    rule("arão", 2, ""),
+    rule("arei", 2, ""),
+    rule("arem", 2, ""),
+    rule("aria", 3, ""),
+    rule("asse", 2, ""),
+    rule("aste", 2, ""),
+    rule("avam", 2, ""),
+    rule("eram", 3, ""),
+    rule("erão", 3, ""),
+    rule("erei", 3, ""),
+    rule("erem", 3, ""),
+    rule("eria", 3, ""),
+    rule("esse", 3, ""),
+    rule("este", 3, ""),
+    rule("íamo", 3, ""),
+    rule("iram", 3, ""),
+    rule("irão", 3, ""),
+    rule("irei", 3, ""),
+    rule("irem", 3, ""),
+    rule("iria", 3, ""),
+    rule("isse", 3, ""),
+    rule("iste", 4, ""),
+    rule("amo", 2, ""),
+    rule("ará", 2, ""),
+    rule("ava", 2, ""),
+    rule("emo", 2, ""),
+    rule("erá", 3, ""),
+    rule("iam", 3, ""),
+    rule("imo", 3, ""),
+    rule("irá", 3, ""),
+    rule("am", 2, ""),
+    rule("ar", 2, ""),
+    rule("ei", 3, ""),
+    rule("em", 2, ""),
+    rule("er", 2, ""),
+    rule("eu", 3, ""),
+    rule("ia", 3, ""),
+    rule("ir", 3, ""),
+    rule("iu", 3, ""),
+    rule("ou", 3, ""),
+];
+
+const VOWEL: &[Rule] = &[rule("a", 3, ""), rule("e", 3, ""), rule("o", 3, "")];
+
+/// Aplica a primeira regra de `rules` que casar com `word`; `true` se alguma casou.
+fn apply(word: &mut String, rules: &[Rule]) -> bool {
+    for rule in rules {
+        let Some(stem) = word.strip_suffix(rule.suffix) else { continue };
+        if stem.chars().count() < rule.min_stem || rule.exceptions.contains(&word.as_str()) {
+            continue;
+        }
+        *word = format!("{stem}{}", rule.replacement);
+        return true;
+    }
+    false
+}
+
+/// Radical de uma palavra em português, em minúsculas e sem acentos
+/// ("governadores" → "govern"). Palavras com menos de 3 letras voltam em minúsculas.
+pub fn stem(word: &str) -> String {
+    let mut word = word.to_lowercase();
+    if word.chars().count() < MIN_WORD_CHARS {
+        return word;
+    }
+    if word.ends_with('s') {
+        apply(&mut word, PLURAL);
+    }
+    if word.ends_with('a') {
+        apply(&mut word, FEMININE);
+    }
+    apply(&mut word, DEGREE);
+    apply(&mut word, ADVERB);
+    if !apply(&mut word, NOUN) && !apply(&mut word, VERB) {
+        apply(&mut word, VOWEL);
+    }
+    remove_accents(&word)
+}
+
+/// A palavra sem acentos nem cedilha ("ação" → "acao").
+fn remove_accents(word: &str) -> String {
+    word.chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' => 'a',
+            'é' | 'ê' => 'e',
+            'í' => 'i',
+            'ó' | 'ô' | 'õ' => 'o',
+            'ú' | 'ü' => 'u',
+            'ç' => 'c',
+            other => other,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflections_share_stem() {
+        let groups: &[&[&str]] = &[
+            &["governou", "governa", "governava", "governar", "governaram", "governo", "governos", "governador"],
+            &["visitou", "visita", "visitaram", "visitando", "visitas"],
+            &["presidente", "presidentes", "presidiu"],
+            &["brasileiro", "brasileiros", "brasileira", "brasileiras"],
+            &["eleição", "eleições"],
+            &["cidade", "cidades"],
+            &["nacional", "nacionais"],
+        ];
+        for group in groups {
+            let expected = stem(group[0]);
+            for word in *group {
+                assert_eq!(stem(word), expected, "{word} em {group:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_exceptions_and_short_words() {
+        // Exceções não perdem o "sufixo" que não é sufixo
+        assert_eq!(stem("lápis"), "lapis");
+        assert_eq!(stem("mais"), "mais");
+        assert_eq!(stem("caminho"), "caminh"); // não é diminutivo de "cam-"
+        // Curtas e sem flexão
+        assert_eq!(stem("em"), "em");
+        assert_eq!(stem("Brasil"), "brasil");
+        assert_eq!(stem("AÇÃO"), stem("ação"));
+        assert!(!stem("governação").is_empty());
+    }
+}
@@ -5,10 +5,53 @@
 //! (a pessoa, em "Paris Hilton").
 //!
 //! A estratégia básica envolve perfis de contexto esperados para certos tipos de categorias.
+//! Menções que são aliases conhecidos (ex: "STF") também são resolvidas para o nome
+//! canônico via [`AliasTable`].
+//!
+//! ## Janela de Contexto
+//!
+//! As pistas são procuradas em uma janela de tokens ao redor da entidade, configurada
+//! por [`NedConfig`]: o tamanho da janela e se ela pode atravessar a fronteira da
+//! sentença (ver [`sentence_ranges`]). Cada pista pesa `1 / distância`: uma palavra
+//! colada na entidade vale mais do que uma a três tokens dela.
+//!
+//! ```rust
+//! use ner_core::alias::AliasTable;
+//! use ner_core::ned::{disambiguate_with_config, NedConfig};
+//! use ner_core::tagger::{EntityCategory, EntitySpan};
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let tokens = tokenize("Paris recebeu ontem a atriz. A cidade parou.");
+//! let paris = EntitySpan {
+//!     text: "Paris".to_string(),
+//!     category: EntityCategory::Loc,
+//!     start_token: 0,
+//!     end_token: 0,
+//!     start: 0,
+//!     end: 5,
+//!     confidence: 0.9,
+//!     source: "crf".to_string(),
+//!     score_breakdown: None,
+//! };
+//!
+//! // Janela de 4 tokens: "atriz" é a única pista alcançável
+//! let config = NedConfig::default().with_window(4);
+//! let resolved = disambiguate_with_config(&tokens, &[paris.clone()], &AliasTable::new(), &config);
+//! assert_eq!(resolved[0].resolved_tag, "PER");
+//!
+//! // Janela maior alcança "cidade", mas a fronteira da sentença a exclui de novo
+//! let config = config.with_window(8);
+//! assert_eq!(disambiguate_with_config(&tokens, &[paris.clone()], &AliasTable::new(), &config)[0].resolved_tag, "PER");
+//! let unbounded = config.with_sentence_bounded(false);
+//! let resolved = disambiguate_with_config(&tokens, &[paris], &AliasTable::new(), &unbounded);
+//! assert!(resolved[0].context_clues.iter().any(|c| c.contains("'cidade'")));
+//! ```
 
+use crate::alias::AliasTable;
 use crate::tagger::EntitySpan;
-use crate::tokenizer::Token;
+use crate::tokenizer::{sentence_ranges, Token};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 /// Resultado da desambiguação para uma entidade
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,74 +61,269 @@ pub struct DisambiguatedEntity {
     pub resolved_tag: String,
     pub confidence: f32,
     pub context_clues: Vec<String>,
+    /// Nome canônico quando a menção é um alias conhecido (ex: "Fiocruz" → "Fundação Oswaldo Cruz").
+    #[serde(default)]
+    pub canonical_name: Option<String>,
+}
+
+/// Configuração da janela de contexto da desambiguação.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NedConfig {
+    /// Quantos tokens antes e depois da entidade são examinados (padrão: 3).
+    pub window: usize,
+    /// Se `true`, a janela não atravessa a fronteira da sentença da entidade.
+    pub sentence_bounded: bool,
+}
+
+impl Default for NedConfig {
+    fn default() -> Self {
+        Self {
+            window: 3,
+            sentence_bounded: true,
+        }
+    }
+}
+
+impl NedConfig {
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_sentence_bounded(mut self, sentence_bounded: bool) -> Self {
+        self.sentence_bounded = sentence_bounded;
+        self
+    }
+}
+
+/// Perfil de uma menção ambígua: as pistas de cada leitura possível.
+struct AmbiguityProfile {
+    /// Trecho (em minúsculas) que torna a menção ambígua.
+    mention: &'static str,
+    /// Leituras candidatas; em caso de empate vence `default_tag`.
+    readings: &'static [Reading],
+    /// Classe majoritária, usada sem pistas ou em empate.
+    default_tag: &'static str,
+    /// Nome da classe majoritária nas pistas ("Local").
+    default_name: &'static str,
 }
 
+/// Uma leitura da menção e as palavras de contexto que a indicam.
+struct Reading {
+    tag: &'static str,
+    /// Descrição usada nas pistas ("pessoa", "local").
+    kind: &'static str,
+    cues: &'static [&'static str],
+}
+
+const PROFILES: &[AmbiguityProfile] = &[AmbiguityProfile {
+    mention: "paris",
+    readings: &[
+        Reading {
+            tag: "PER",
+            kind: "pessoa",
+            cues: &["hilton", "socialite", "atriz"],
+        },
+        Reading {
+            tag: "LOC",
+            kind: "local",
+            cues: &["frança", "cidade", "capital"],
+        },
+    ],
+    default_tag: "LOC",
+    default_name: "Local",
+}];
+
 /// Analisa os tokens e as entidades extraídas pelo NER para refinar suas categorias.
 pub fn disambiguate(
     tokens: &[Token],
     entities: &[EntitySpan],
+) -> Vec<DisambiguatedEntity> {
+    disambiguate_with_aliases(tokens, entities, &AliasTable::new())
+}
+
+/// Como [`disambiguate`], mas também resolve aliases para o nome canônico.
+pub fn disambiguate_with_aliases(
+    tokens: &[Token],
+    entities: &[EntitySpan],
+    aliases: &AliasTable,
+) -> Vec<DisambiguatedEntity> {
+    disambiguate_with_config(tokens, entities, aliases, &NedConfig::default())
+}
+
+/// Como [`disambiguate_with_aliases`], com a janela de contexto de `config`.
+pub fn disambiguate_with_config(
+    tokens: &[Token],
+    entities: &[EntitySpan],
+    aliases: &AliasTable,
+    config: &NedConfig,
 ) -> Vec<DisambiguatedEntity> {
     let mut results = Vec::new();
+    let sentences = if config.sentence_bounded { sentence_ranges(tokens) } else { Vec::new() };
 
     for entity in entities {
-        let (resolved_tag, confidence, clues) = analyze_context(tokens, entity);
+        let window = context_window(tokens.len(), entity, config, &sentences);
+        let (resolved_tag, confidence, mut clues) = analyze_context(tokens, entity, window);
+        let canonical_name = aliases.canonical(&entity.text).map(str::to_string);
+        if let Some(canonical) = &canonical_name {
+            clues.push(format!("Alias conhecido: '{}' → '{}'", entity.text, canonical));
+        }
         results.push(DisambiguatedEntity {
             entity: entity.clone(),
             original_tag: entity.category.name().to_string(),
             resolved_tag,
             confidence,
             context_clues: clues,
+            canonical_name,
         });
     }
 
     results
 }
 
-fn analyze_context(tokens: &[Token], entity: &EntitySpan) -> (String, f32, Vec<String>) {
-    let mut clues = Vec::new();
-    let mut resolved_tag = entity.category.name().to_string();
-    let confidence;
+/// Intervalo de tokens examinado para `entity`, limitado à sua sentença se configurado.
+fn context_window(
+    n_tokens: usize,
+    entity: &EntitySpan,
+    config: &NedConfig,
+    sentences: &[Range<usize>],
+) -> Range<usize> {
+    let mut start = entity.start_token.saturating_sub(config.window);
+    let mut end = (entity.end_token + config.window + 1).min(n_tokens);
+    if let Some(sentence) = sentences.iter().find(|s| s.contains(&entity.start_token)) {
+        start = start.max(sentence.start);
+        end = end.min(sentence.end.max(entity.end_token + 1));
+    }
+    start..end
+}
 
-    // Obtém janela de contexto de +/- 3 tokens
-    let start_idx = entity.start_token.saturating_sub(3);
-    let end_idx = (entity.end_token + 3).min(tokens.len() - 1);
+/// Peso de uma pista a `distance` tokens da entidade (tokens da própria entidade e
+/// vizinhos imediatos valem 1).
+fn clue_weight(distance: usize) -> f32 {
+    1.0 / distance.max(1) as f32
+}
 
+fn analyze_context(tokens: &[Token], entity: &EntitySpan, window: Range<usize>) -> (String, f32, Vec<String>) {
+    let mut clues = Vec::new();
     let text_lower = entity.text.to_lowercase();
 
-    // Regras Hardcoded simples para propósito educacional:
-    if text_lower.contains("paris") {
-        let mut is_person = false;
-        let mut is_loc = false;
+    // Regras simples para propósito educacional: um perfil por menção ambígua
+    let Some(profile) = PROFILES.iter().find(|p| text_lower.contains(p.mention)) else {
+        // Sem regras específicas, mantém a tag do NER
+        clues.push("Nenhuma regra de desambiguação específica aplicada".to_string());
+        return (entity.category.name().to_string(), 0.80, clues);
+    };
 
-        for i in start_idx..=end_idx {
-            let token_lower = tokens[i].text.to_lowercase();
-            if token_lower == "hilton" || token_lower == "socialite" || token_lower == "atriz" {
-                is_person = true;
-                clues.push(format!("Encontrado indicador de pessoa: '{}'", tokens[i].text));
-            }
-            if token_lower == "frança" || token_lower == "cidade" || token_lower == "capital" {
-                is_loc = true;
-                clues.push(format!("Encontrado indicador de local: '{}'", tokens[i].text));
+    // Soma ponderada das pistas de cada leitura
+    let mut scores = vec![0.0f32; profile.readings.len()];
+    for token in &tokens[window] {
+        let distance = if token.index < entity.start_token {
+            entity.start_token - token.index
+        } else {
+            token.index.saturating_sub(entity.end_token)
+        };
+        let token_lower = token.text.to_lowercase();
+        for (score, reading) in scores.iter_mut().zip(profile.readings) {
+            if reading.cues.contains(&token_lower.as_str()) {
+                let weight = clue_weight(distance);
+                *score += weight;
+                clues.push(format!(
+                    "Encontrado indicador de {}: '{}' (distância {}, peso {:.2})",
+                    reading.kind, token.text, distance, weight
+                ));
             }
         }
+    }
 
-        if is_person {
-            resolved_tag = "PER".to_string();
-            confidence = 0.95;
-        } else if is_loc || entity.category.name().contains("LOC") {
-            resolved_tag = "LOC".to_string();
-            confidence = 0.85;
-        } else {
-            // Se "Paris" não tiver contexto de pessoa, assumimos LOC como padrão estatístico
-            resolved_tag = "LOC".to_string();
-            confidence = 0.60;
-            clues.push("Nenhum contexto forte, assumindo classe majoritária (Local)".to_string());
+    let total: f32 = scores.iter().sum();
+    let best = scores.iter().copied().fold(0.0f32, f32::max);
+    let winners: Vec<&Reading> = profile
+        .readings
+        .iter()
+        .zip(&scores)
+        .filter(|(_, &score)| score > 0.0 && score == best)
+        .map(|(reading, _)| reading)
+        .collect();
+
+    match winners.as_slice() {
+        // Uma leitura claramente mais apoiada pelo contexto
+        [reading] => (reading.tag.to_string(), 0.60 + 0.35 * best / total, clues),
+        [] if entity.category.name() == profile.default_tag => (profile.default_tag.to_string(), 0.85, clues),
+        [] => {
+            // Sem contexto forte, assumimos a classe majoritária como padrão estatístico
+            clues.push(format!(
+                "Nenhum contexto forte, assumindo classe majoritária ({})",
+                profile.default_name
+            ));
+            (profile.default_tag.to_string(), 0.60, clues)
+        }
+        _ => {
+            clues.push(format!("Pistas empatadas, assumindo classe majoritária ({})", profile.default_name));
+            (profile.default_tag.to_string(), 0.60, clues)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::EntityCategory;
+    use crate::tokenizer::tokenize;
+
+    fn entity_at(tokens: &[Token], index: usize, category: EntityCategory) -> EntitySpan {
+        EntitySpan {
+            text: tokens[index].text.clone(),
+            category,
+            start_token: index,
+            end_token: index,
+            start: tokens[index].start,
+            end: tokens[index].end,
+            confidence: 0.9,
+            source: "test".to_string(),
+            score_breakdown: None,
         }
-    } else {
-        // Sem regras específicas, mantém a tag do NER
-        confidence = 0.80;
-        clues.push("Nenhuma regra de desambiguação específica aplicada".to_string());
     }
 
-    (resolved_tag, confidence, clues)
+    #[test]
+    fn test_context_window_and_weighted_clues() {
+        let tokens = tokenize("A atriz gosta de Paris , perto da França");
+        let paris = entity_at(&tokens, 4, EntityCategory::Loc);
+        let aliases = AliasTable::new();
+
+        // Janela padrão (±3): só "atriz" (distância 3) está ao alcance
+        let resolved = disambiguate(&tokens, std::slice::from_ref(&paris));
+        assert_eq!(resolved[0].resolved_tag, "PER");
+        assert!((resolved[0].confidence - 0.95).abs() < 1e-6);
+
+        // Janela ±4 alcança "França" (distância 4), mas "atriz" está mais perto
+        let wider = NedConfig::default().with_window(4);
+        let resolved = disambiguate_with_config(&tokens, &[paris], &aliases, &wider);
+        assert_eq!(resolved[0].resolved_tag, "PER");
+        assert!(resolved[0].confidence < 0.95);
+        assert!(resolved[0].context_clues.iter().any(|c| c.contains("'França'")));
+
+        // Pistas à mesma distância empatam → classe majoritária
+        let tokens = tokenize("A atriz de Paris na França");
+        let paris = entity_at(&tokens, 3, EntityCategory::Per);
+        let resolved = disambiguate(&tokens, &[paris]);
+        assert_eq!(resolved[0].resolved_tag, "LOC");
+        assert!(resolved[0].context_clues.iter().any(|c| c.contains("empatadas")));
+
+        // Pistas mais próximas pesam mais
+        let tokens = tokenize("A atriz visitou a cidade de Paris");
+        let paris = entity_at(&tokens, 6, EntityCategory::Loc);
+        let config = NedConfig::default().with_window(5);
+        let resolved = disambiguate_with_config(&tokens, &[paris], &aliases, &config);
+        assert_eq!(resolved[0].resolved_tag, "LOC");
+        assert!(resolved[0].confidence > 0.60 && resolved[0].confidence < 0.95);
+
+        // A janela limitada à sentença ignora pistas da sentença vizinha
+        let tokens = tokenize("Paris chegou . A atriz sorriu");
+        let paris = entity_at(&tokens, 0, EntityCategory::Per);
+        let resolved = disambiguate_with_config(&tokens, std::slice::from_ref(&paris), &aliases, &config);
+        assert_eq!(resolved[0].resolved_tag, "LOC");
+        let unbounded = config.with_sentence_bounded(false);
+        let resolved = disambiguate_with_config(&tokens, &[paris], &aliases, &unbounded);
+        assert_eq!(resolved[0].resolved_tag, "PER");
+    }
 }
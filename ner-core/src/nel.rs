@@ -3,8 +3,40 @@
 //! Este módulo faz o "Linking" ou "Grounding" de entidades desambiguadas para uma
 //! Base de Conhecimento (Knowledge Base - KB). O NEL é crucial para resolver
 //! sinônimos ou variações ortográficas para a mesma entidade no mundo real.
+//!
+//! ## Tipos e Compatibilidade
+//!
+//! Cada [`KbRecord`] declara seus tipos (`["human"]`, `["city"]`...). Um candidato só é
+//! aceito se algum tipo for compatível com a categoria resolvida pelo NED
+//! ([`compatible_types`]): "Paris" desambiguada como PER nunca é ligada à cidade,
+//! mesmo que o nome case exatamente. Registros sem tipos não sofrem a restrição.
+//!
+//! ## Cache de Linking
+//!
+//! Consultas a uma KB remota são lentas. Com [`KnowledgeBase::with_cache`], cada
+//! resultado fica guardado num [`LinkCache`] indexado por **versão da KB + categoria +
+//! menção canônica normalizada**, de modo que "Fiocruz" e "Fundação Oswaldo Cruz"
+//! compartilham a mesma entrada. Entradas expiram após um TTL, podem ser invalidadas
+//! manualmente e são persistidas em um arquivo JSON quando o cache tem um caminho.
+//!
+//! ```rust
+//! use ner_core::nel::{KnowledgeBase, LinkCache};
+//! use std::time::Duration;
+//!
+//! let kb = KnowledgeBase::new().with_cache(LinkCache::new(Some(Duration::from_secs(3600))));
+//! assert_eq!(kb.cache_len(), 0);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::features::GazetteerKey;
 use crate::ned::DisambiguatedEntity;
+use crate::storage::{get_json, put_json, FileStorage, Storage};
 use serde::{Deserialize, Serialize};
 
 /// Um registro simulado em nossa Base de Conhecimento "Wikidata Mock"
@@ -14,6 +46,32 @@ pub struct KbRecord {
     pub name: String,
     pub description: String,
     pub url: String,
+    /// Tipos do registro (ex: `["human"]`, `["city"]`), comparados com a categoria do NED.
+    #[serde(default)]
+    pub types: Vec<String>,
+}
+
+/// Tipos de registro compatíveis com cada categoria resolvida pelo NED.
+const TAG_TYPES: &[(&str, &[&str])] = &[
+    ("PER", &["human", "fictional_character"]),
+    ("LOC", &["country", "city", "state", "place"]),
+    ("ORG", &["company", "organization", "government_agency"]),
+    ("MISC", &["event", "work", "law", "product"]),
+];
+
+/// Tipos de [`KbRecord`] compatíveis com a categoria `tag` ("PER", "LOC"...).
+///
+/// Categorias sem mapeamento (ex: DATE) retornam uma lista vazia: nenhum registro tipado
+/// é compatível com elas.
+pub fn compatible_types(tag: &str) -> &'static [&'static str] {
+    TAG_TYPES.iter().find(|(t, _)| *t == tag).map_or(&[], |(_, types)| types)
+}
+
+impl KbRecord {
+    /// O registro pode representar uma entidade da categoria `tag`?
+    pub fn is_compatible_with(&self, tag: &str) -> bool {
+        self.types.is_empty() || self.types.iter().any(|t| compatible_types(tag).contains(&t.as_str()))
+    }
 }
 
 /// Entidade após a etapa de Linking
@@ -24,108 +82,324 @@ pub struct LinkedEntity {
     pub match_score: f32,
 }
 
+/// Resultado de linking guardado no cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLink {
+    kb_match: Option<KbRecord>,
+    match_score: f32,
+    /// Momento da inserção (segundos desde a época Unix).
+    stored_at: u64,
+}
+
+/// Cache de resultados de linking, opcionalmente persistido em um [`Storage`].
+///
+/// Chave: `"versão|categoria|menção canônica normalizada"` (ver [`LinkCache::key`]).
+#[derive(Default, Serialize, Deserialize)]
+pub struct LinkCache {
+    entries: HashMap<String, CachedLink>,
+    /// Validade das entradas; `None` = nunca expiram.
+    ttl: Option<Duration>,
+    /// Armazenamento e chave de persistência (definidos por [`LinkCache::open_in`]).
+    #[serde(skip)]
+    backend: Option<(Arc<dyn Storage>, String)>,
+    /// Há alterações ainda não gravadas no armazenamento.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl fmt::Debug for LinkCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkCache")
+            .field("entries", &self.entries)
+            .field("ttl", &self.ttl)
+            .field("key", &self.backend.as_ref().map(|(_, key)| key))
+            .field("dirty", &self.dirty)
+            .finish()
+    }
+}
+
+impl LinkCache {
+    /// Cache apenas em memória.
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self { ttl, ..Self::default() }
+    }
+
+    /// Cache persistido no arquivo `path`, carregando as entradas existentes (se o arquivo existir).
+    pub fn open(path: impl AsRef<Path>, ttl: Option<Duration>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("caminho de cache inválido: {}", path.display())));
+        };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        Self::open_in(Arc::new(FileStorage::new(dir)?), name, ttl)
+    }
+
+    /// Cache persistido sob `key` em `storage`, carregando as entradas existentes.
+    pub fn open_in(storage: Arc<dyn Storage>, key: impl Into<String>, ttl: Option<Duration>) -> io::Result<Self> {
+        let key = key.into();
+        let mut cache: Self = get_json(storage.as_ref(), &key)?.unwrap_or_default();
+        cache.ttl = ttl;
+        cache.backend = Some((storage, key));
+        cache.purge_expired();
+        Ok(cache)
+    }
+
+    /// Chave de uma menção: a forma canônica (quando o NED resolveu um alias) normalizada
+    /// por [`GazetteerKey::normalize`], prefixada pela categoria e pela versão da KB.
+    pub fn key(kb_version: &str, entity: &DisambiguatedEntity) -> String {
+        let mention = entity.canonical_name.as_deref().unwrap_or(&entity.entity.text);
+        format!("{}|{}|{}", kb_version, entity.resolved_tag, GazetteerKey::normalize(mention))
+    }
+
+    fn get(&self, key: &str) -> Option<&CachedLink> {
+        let now = now_secs();
+        self.entries.get(key).filter(|entry| is_fresh(self.ttl, entry, now))
+    }
+
+    fn insert(&mut self, key: String, kb_match: Option<KbRecord>, match_score: f32) {
+        let entry = CachedLink { kb_match, match_score, stored_at: now_secs() };
+        self.entries.insert(key, entry);
+        self.dirty = true;
+    }
+
+    /// Remove as entradas de uma menção (em qualquer versão da KB). Retorna quantas saíram.
+    pub fn invalidate(&mut self, category: &str, mention: &str) -> usize {
+        let suffix = format!("|{}|{}", category, GazetteerKey::normalize(mention));
+        self.remove_where(|key| key.ends_with(&suffix))
+    }
+
+    /// Remove todas as entradas de uma versão da KB (ex: após atualizar a base).
+    pub fn invalidate_version(&mut self, kb_version: &str) -> usize {
+        let prefix = format!("{kb_version}|");
+        self.remove_where(|key| key.starts_with(&prefix))
+    }
+
+    /// Remove as entradas expiradas.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = now_secs();
+        let before = self.entries.len();
+        let ttl = self.ttl;
+        self.entries.retain(|_, entry| is_fresh(ttl, entry, now));
+        self.mark_removed(before)
+    }
+
+    pub fn clear(&mut self) {
+        self.dirty |= !self.entries.is_empty();
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Grava as alterações pendentes no armazenamento (no-op para caches em memória).
+    pub fn flush(&mut self) -> io::Result<()> {
+        let Some((storage, key)) = &self.backend else {
+            return Ok(());
+        };
+        if self.dirty {
+            put_json(storage.as_ref(), key, self)?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    fn remove_where(&mut self, mut matches: impl FnMut(&str) -> bool) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| !matches(key));
+        self.mark_removed(before)
+    }
+
+    fn mark_removed(&mut self, before: usize) -> usize {
+        let removed = before - self.entries.len();
+        self.dirty |= removed > 0;
+        removed
+    }
+}
+
+fn is_fresh(ttl: Option<Duration>, entry: &CachedLink, now: u64) -> bool {
+    ttl.is_none_or(|ttl| now.saturating_sub(entry.stored_at) <= ttl.as_secs())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Versão da KB simulada embutida.
+pub const MOCK_KB_VERSION: &str = "mock-2";
+
 /// Simulated Knowledge Base with predefined entities
 pub struct KnowledgeBase {
     records: Vec<KbRecord>,
+    /// Versão dos registros; faz parte da chave do cache.
+    version: String,
+    /// Cache de resultados (opcional), consultado automaticamente por [`KnowledgeBase::link`].
+    cache: Option<Mutex<LinkCache>>,
 }
 
 impl KnowledgeBase {
     pub fn new() -> Self {
         Self {
+            version: MOCK_KB_VERSION.to_string(),
+            cache: None,
             records: vec![
                 KbRecord {
                     id: "Q36098".to_string(),
                     name: "Luiz Inácio Lula da Silva".to_string(),
                     description: "39º presidente do Brasil".to_string(),
                     url: "https://www.wikidata.org/wiki/Q36098".to_string(),
+                    types: vec!["human".to_string()],
                 },
                 KbRecord {
                     id: "Q155".to_string(),
                     name: "Brasil".to_string(),
                     description: "República Federativa do Brasil, país na América do Sul".to_string(),
                     url: "https://www.wikidata.org/wiki/Q155".to_string(),
+                    types: vec!["country".to_string()],
                 },
                 KbRecord {
                     id: "Q47454".to_string(),
                     name: "Paris Hilton".to_string(),
                     description: "Personalidade de televisão, empresária e socialite americana".to_string(),
                     url: "https://www.wikidata.org/wiki/Q47454".to_string(),
+                    types: vec!["human".to_string()],
                 },
                 KbRecord {
                     id: "Q90".to_string(),
                     name: "Paris".to_string(),
                     description: "Capital e a cidade mais populosa da França".to_string(),
                     url: "https://www.wikidata.org/wiki/Q90".to_string(),
+                    types: vec!["city".to_string()],
                 },
                 KbRecord {
                     id: "Q312".to_string(),
                     name: "Apple Inc.".to_string(),
                     description: "Empresa multinacional norte-americana de eletrônicos e software".to_string(),
                     url: "https://www.wikidata.org/wiki/Q312".to_string(),
+                    types: vec!["company".to_string()],
                 },
             ],
         }
     }
 
-    /// Realiza a busca ingênua (naive) na base de conhecimento usando match parcial
+    /// Ativa o cache de resultados de linking.
+    pub fn with_cache(mut self, cache: LinkCache) -> Self {
+        self.cache = Some(Mutex::new(cache));
+        self
+    }
+
+    /// Define a versão da KB (mudar a versão torna as entradas antigas do cache inalcançáveis).
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Número de entradas no cache (0 sem cache).
+    pub fn cache_len(&self) -> usize {
+        self.with_cache_mut(|cache| cache.len()).unwrap_or(0)
+    }
+
+    /// Executa `f` sobre o cache, se houver (ex: invalidação manual).
+    pub fn with_cache_mut<R>(&self, f: impl FnOnce(&mut LinkCache) -> R) -> Option<R> {
+        self.cache
+            .as_ref()
+            .map(|cache| f(&mut cache.lock().unwrap_or_else(|e| e.into_inner())))
+    }
+
+    /// Liga cada entidade a um registro da KB, consultando o cache antes da busca.
+    ///
+    /// Com cache persistido, as novas entradas são gravadas ao final da chamada;
+    /// uma falha de escrita não interrompe o linking.
     pub fn link(&self, entities: &[DisambiguatedEntity]) -> Vec<LinkedEntity> {
-        let mut results = Vec::new();
-
-        for ent in entities {
-            let mut best_match = None;
-            let mut best_score = 0.0;
-            let query = ent.entity.text.to_lowercase();
-
-            for record in &self.records {
-                let name_lower = record.name.to_lowercase();
-                
-                // Métrica muito simples:
-                // Se a busca é exata ou uma contém a outra, e o tipo sugerido do NED faz sentido:
-                // Ex: Se o NED diz PER e o record id="Q47454" (Paris Hilton), pontuação sobe.
-                let mut score = 0.0;
-                
-                if name_lower == query {
-                    score += 0.8;
-                } else if name_lower.contains(&query) || query.contains(&name_lower) {
-                    score += 0.5;
-                }
-                
-                // Refinamento baseado na tag do NED (hardcoded simulation):
-                if score > 0.0 {
-                    if ent.resolved_tag == "PER" && (record.id == "Q36098" || record.id == "Q47454") {
-                        score += 0.15;
-                    }
-                    if ent.resolved_tag == "LOC" && (record.id == "Q155" || record.id == "Q90") {
-                        score += 0.15;
-                    }
-                    if ent.resolved_tag == "ORG" && record.id == "Q312" {
-                        score += 0.15;
-                    }
+        let Some(cache) = &self.cache else {
+            return entities.iter().map(|ent| self.link_uncached(ent)).collect();
+        };
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        let results = entities
+            .iter()
+            .map(|ent| {
+                let key = LinkCache::key(&self.version, ent);
+                if let Some(hit) = cache.get(&key) {
+                    return LinkedEntity {
+                        disambiguated: ent.clone(),
+                        kb_match: hit.kb_match.clone(),
+                        match_score: hit.match_score,
+                    };
                 }
+                let linked = self.link_uncached(ent);
+                cache.insert(key, linked.kb_match.clone(), linked.match_score);
+                linked
+            })
+            .collect();
+        let _ = cache.flush();
+        results
+    }
 
-                if score > best_score {
-                    best_score = score;
-                    best_match = Some(record.clone());
+    /// Realiza a busca ingênua (naive) na base de conhecimento usando match parcial
+    ///
+    /// Quando o NED resolveu a menção para um nome canônico (alias), a busca usa
+    /// tanto a menção original quanto o nome canônico, ficando com o melhor score.
+    fn link_uncached(&self, ent: &DisambiguatedEntity) -> LinkedEntity {
+        let mut best_match = None;
+        let mut best_score = 0.0;
+        let mut queries = vec![ent.entity.text.to_lowercase()];
+        if let Some(canonical) = &ent.canonical_name {
+            queries.push(canonical.to_lowercase());
+        }
+
+        for record in &self.records {
+            let name_lower = record.name.to_lowercase();
+            
+            // Restrição de tipo: "Paris" como PER nunca é a cidade
+            if !record.is_compatible_with(&ent.resolved_tag) {
+                continue;
+            }
+
+            // Métrica muito simples:
+            // Se a busca é exata ou uma contém a outra, e o tipo do registro confirma a
+            // categoria do NED (ex: PER e "human" em Paris Hilton), a pontuação sobe.
+            let mut score: f32 = 0.0;
+
+            for query in &queries {
+                if name_lower == *query {
+                    score = score.max(0.8);
+                } else if name_lower.contains(query.as_str()) || query.contains(&name_lower) {
+                    score = score.max(0.5);
                 }
             }
+            
+            if score > 0.0 && !record.types.is_empty() {
+                score += 0.15;
+            }
 
-            // Apenas ligamos se o score for aceitável
-            if best_score >= 0.5 {
-                results.push(LinkedEntity {
-                    disambiguated: ent.clone(),
-                    kb_match: best_match,
-                    match_score: best_score,
-                });
-            } else {
-                results.push(LinkedEntity {
-                    disambiguated: ent.clone(),
-                    kb_match: None,
-                    match_score: 0.0,
-                });
+            if score > best_score {
+                best_score = score;
+                best_match = Some(record.clone());
             }
         }
 
-        results
+        // Apenas ligamos se o score for aceitável
+        if best_score >= 0.5 {
+            LinkedEntity {
+                disambiguated: ent.clone(),
+                kb_match: best_match,
+                match_score: best_score,
+            }
+        } else {
+            LinkedEntity {
+                disambiguated: ent.clone(),
+                kb_match: None,
+                match_score: 0.0,
+            }
+        }
     }
 }
 
@@ -134,3 +408,108 @@ impl Default for KnowledgeBase {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::{EntityCategory, EntitySpan};
+
+    fn mention(text: &str, tag: &str, canonical: Option<&str>) -> DisambiguatedEntity {
+        DisambiguatedEntity {
+            entity: EntitySpan {
+                text: text.to_string(),
+                category: EntityCategory::Per,
+                start_token: 0,
+                end_token: 0,
+                start: 0,
+                end: text.len(),
+                confidence: 1.0,
+                source: "test".to_string(),
+                score_breakdown: None,
+            },
+            original_tag: tag.to_string(),
+            resolved_tag: tag.to_string(),
+            confidence: 1.0,
+            context_clues: vec![],
+            canonical_name: canonical.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_cache_key_uses_canonical_mention() {
+        let alias = mention("Lula", "PER", Some("Luiz Inácio  Lula da Silva"));
+        let full = mention("luiz inácio lula da silva", "PER", None);
+        assert_eq!(LinkCache::key("v1", &alias), LinkCache::key("v1", &full));
+        assert_ne!(LinkCache::key("v1", &full), LinkCache::key("v2", &full));
+        assert_ne!(LinkCache::key("v1", &full), LinkCache::key("v1", &mention("luiz inácio lula da silva", "LOC", None)));
+    }
+
+    #[test]
+    fn test_link_uses_and_invalidates_cache() {
+        let kb = KnowledgeBase::new().with_cache(LinkCache::new(None));
+        let entities = [mention("Paris", "LOC", None), mention("Paris", "LOC", None)];
+        let linked = kb.link(&entities);
+        assert_eq!(linked[0].kb_match.as_ref().unwrap().id, "Q90");
+        assert_eq!(kb.cache_len(), 1);
+
+        // Uma entrada adulterada mostra que a segunda chamada vem do cache
+        kb.with_cache_mut(|cache| {
+            cache.entries.values_mut().for_each(|e| e.match_score = 0.42);
+        });
+        assert_eq!(kb.link(&entities[..1])[0].match_score, 0.42);
+
+        assert_eq!(kb.with_cache_mut(|c| c.invalidate("LOC", " PARIS ")), Some(1));
+        assert!(kb.link(&entities[..1])[0].match_score > 0.5);
+        assert_eq!(kb.with_cache_mut(|c| c.invalidate_version(MOCK_KB_VERSION)), Some(1));
+    }
+
+    #[test]
+    fn test_link_enforces_type_compatibility() {
+        let kb = KnowledgeBase::new();
+        let linked = kb.link(&[mention("Paris", "PER", None), mention("Paris", "LOC", None), mention("Brasil", "DATE", None)]);
+        // A cidade casa exatamente, mas não é "human": vence Paris Hilton
+        assert_eq!(linked[0].kb_match.as_ref().unwrap().id, "Q47454");
+        assert_eq!(linked[1].kb_match.as_ref().unwrap().id, "Q90");
+        assert!(linked[2].kb_match.is_none());
+
+        // Registros sem tipos (ex: de um cache antigo) não sofrem a restrição
+        let untyped: KbRecord = serde_json::from_str(r#"{"id":"X","name":"X","description":"","url":""}"#).unwrap();
+        assert!(untyped.is_compatible_with("DATE"));
+        assert_eq!(compatible_types("LOC"), &["country", "city", "state", "place"]);
+    }
+
+    #[test]
+    fn test_cache_ttl_and_persistence() {
+        let path = std::env::temp_dir().join(format!("ner_link_cache_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let ttl = Some(Duration::from_secs(60));
+
+        let kb = KnowledgeBase::new().with_cache(LinkCache::open(&path, ttl).unwrap());
+        kb.link(&[mention("Brasil", "LOC", None), mention("Apple Inc.", "ORG", None)]);
+        kb.with_cache_mut(|cache| {
+            // Envelhece uma das entradas além do TTL
+            let old = cache.entries.get_mut(&format!("{MOCK_KB_VERSION}|ORG|apple inc.")).unwrap();
+            old.stored_at -= 120;
+            cache.dirty = true;
+            cache.flush().unwrap();
+        });
+
+        let reopened = LinkCache::open(&path, ttl).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert!(reopened.get(&format!("{MOCK_KB_VERSION}|LOC|brasil")).is_some());
+    }
+
+    #[test]
+    fn test_cache_in_shared_storage() {
+        use crate::storage::MemoryStorage;
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+        let kb = KnowledgeBase::new().with_cache(LinkCache::open_in(storage.clone(), "cache/links", None).unwrap());
+        kb.link(&[mention("Brasil", "LOC", None)]);
+        kb.with_cache_mut(|cache| cache.flush().unwrap());
+
+        assert_eq!(storage.keys("cache/").unwrap(), vec!["cache/links"]);
+        assert_eq!(LinkCache::open_in(storage, "cache/links", None).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,308 @@
+//! # Fatiamento Seguro de Texto por Offsets
+//!
+//! Tokens e entidades guardam offsets de **byte** (`start`, `end`) no texto original.
+//! Em Rust, `text[start..end]` entra em pânico se algum offset estiver fora do texto
+//! ou cair no meio de um caractere UTF-8 multibyte (ex: o "ã" de "São" ocupa 2 bytes).
+//!
+//! Um único offset inválido derrubaria o servidor web. Este módulo oferece duas formas
+//! de fatiar o texto sem pânico:
+//!
+//! - [`slice_checked`]: valida os offsets e retorna um erro descritivo ([`SliceError`]).
+//! - [`slice_lossy`]: "encaixa" os offsets na fronteira de caractere mais próxima
+//!   (expandindo o intervalo) e nunca falha.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::offsets::{slice_checked, slice_lossy};
+//!
+//! let text = "São Paulo";
+//! // Byte 2 está no meio do "ã" (bytes 1..3)
+//! assert!(slice_checked(text, 0, 2).is_err());
+//! assert_eq!(slice_lossy(text, 0, 2), "Sã");
+//! assert_eq!(slice_lossy(text, 0, 100), "São Paulo");
+//! ```
+//!
+//! ## Texto transformado
+//!
+//! Quando o texto passa por pré-processamento antes do pipeline (remoção de HTML,
+//! limpeza de caracteres invisíveis, desfazer hifenização), os offsets das entidades
+//! deixam de valer para a string do usuário. Cada estágio produz um [`OffsetMap`] do
+//! texto novo para o anterior; os mapas são encadeados com [`OffsetMap::compose`] e
+//! [`crate::EntitySpan::project`] leva uma entidade de volta ao original (ver
+//! [`crate::ingest::ExtractedText::and_then`]).
+
+use std::fmt;
+
+/// Motivo pelo qual um par de offsets não pode indexar o texto diretamente.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceError {
+    /// Algum offset ultrapassa o tamanho do texto (em bytes).
+    OutOfBounds { start: usize, end: usize, len: usize },
+    /// O offset inicial é maior que o final.
+    InvertedRange { start: usize, end: usize },
+    /// O offset cai no meio de um caractere UTF-8 multibyte.
+    NotCharBoundary { index: usize },
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SliceError::OutOfBounds { start, end, len } => {
+                write!(f, "offsets {start}..{end} fora do texto (tamanho {len} bytes)")
+            }
+            SliceError::InvertedRange { start, end } => {
+                write!(f, "offsets invertidos: início {start} maior que fim {end}")
+            }
+            SliceError::NotCharBoundary { index } => {
+                write!(f, "offset {index} não está em uma fronteira de caractere UTF-8")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+/// Fatia o texto validando os offsets, sem entrar em pânico.
+///
+/// Retorna o mesmo resultado que `&text[start..end]` quando os offsets são válidos.
+pub fn slice_checked(text: &str, start: usize, end: usize) -> Result<&str, SliceError> {
+    if start > end {
+        return Err(SliceError::InvertedRange { start, end });
+    }
+    if end > text.len() {
+        return Err(SliceError::OutOfBounds { start, end, len: text.len() });
+    }
+    if !text.is_char_boundary(start) {
+        return Err(SliceError::NotCharBoundary { index: start });
+    }
+    if !text.is_char_boundary(end) {
+        return Err(SliceError::NotCharBoundary { index: end });
+    }
+    Ok(&text[start..end])
+}
+
+/// Fatia o texto de forma tolerante, ajustando offsets inválidos.
+///
+/// # Ajustes aplicados
+/// 1. Offsets além do fim do texto são limitados a `text.len()`.
+/// 2. `start` recua até a fronteira de caractere anterior; `end` avança até a próxima.
+///    Assim o caractere parcialmente coberto é incluído por inteiro.
+/// 3. Intervalos invertidos (`start > end`) resultam em string vazia.
+pub fn slice_lossy(text: &str, start: usize, end: usize) -> &str {
+    let start = floor_char_boundary(text, start.min(text.len()));
+    let end = ceil_char_boundary(text, end.min(text.len()));
+    if start >= end {
+        return "";
+    }
+    &text[start..end]
+}
+
+/// Converte um offset de byte em offset de caractere (ponto de código Unicode).
+///
+/// Plataformas de anotação (e o seletor `TextPositionSelector` do W3C) contam caracteres,
+/// não bytes: em "São Paulo", "Paulo" começa no byte 5, mas no caractere 4.
+/// Offsets no meio de um caractere contam o caractere parcial; offsets além do fim
+/// são limitados ao tamanho do texto.
+pub fn char_offset(text: &str, byte: usize) -> usize {
+    let byte = ceil_char_boundary(text, byte.min(text.len()));
+    text[..byte].chars().count()
+}
+
+/// Maior fronteira de caractere `<= index` (assume `index <= text.len()`).
+pub fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while index > 0 && !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Menor fronteira de caractere `>= index` (assume `index <= text.len()`).
+pub fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while index < text.len() && !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Mapa de um texto transformado de volta para o texto de origem.
+///
+/// Para cada byte do texto transformado guarda o intervalo de bytes, na origem, do
+/// caractere que o gerou. Um caractere pode vir de vários ("&atilde;" → "ã", "a\u{303}" → "ã")
+/// ou de nenhum: separadores inseridos têm intervalo vazio, na posição em que entraram.
+///
+/// ```rust
+/// use ner_core::offsets::OffsetMap;
+///
+/// // "São" escrito com til combinante (4 bytes) virou "São" pré-composto (4 bytes)
+/// let mut map = OffsetMap::new();
+/// map.push('S', (0, 1));
+/// map.push('ã', (1, 4));
+/// map.push('o', (4, 5));
+/// assert_eq!(map.project(0, 4), Some((0, 5)));
+/// assert_eq!(map.project(1, 3), Some((1, 4)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    source: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mapa de um texto que não foi transformado: cada caractere vem de si mesmo.
+    pub fn identity(text: &str) -> Self {
+        let mut map = Self::new();
+        for (i, c) in text.char_indices() {
+            map.push(c, (i, i + c.len_utf8()));
+        }
+        map
+    }
+
+    /// Registra o próximo caractere do texto transformado, vindo de `src` na origem.
+    pub fn push(&mut self, c: char, src: (usize, usize)) {
+        self.source.extend(std::iter::repeat_n(src, c.len_utf8()));
+    }
+
+    /// Esquece o último caractere registrado (`c` é o caractere removido do texto).
+    pub fn pop(&mut self, c: char) {
+        self.source.truncate(self.source.len().saturating_sub(c.len_utf8()));
+    }
+
+    /// Tamanho em bytes do texto transformado.
+    pub fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    /// Leva o intervalo `[start, end)` do texto transformado para bytes da origem.
+    ///
+    /// Retorna `None` se o intervalo for vazio ou estiver fora do texto.
+    pub fn project(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        if start >= end || end > self.source.len() {
+            return None;
+        }
+        Some((self.source[start].0, self.source[end - 1].1))
+    }
+
+    /// Trechos contínuos da origem que compõem `[start, end)`, sem os separadores inseridos.
+    pub fn source_runs(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        let end = end.min(self.source.len());
+        for &(s, e) in self.source.get(start..end).unwrap_or_default() {
+            if s == e {
+                continue; // separador inserido, não existe na origem
+            }
+            match runs.last_mut() {
+                Some(run) if run.1 == s => run.1 = e,
+                Some(run) if run.0 <= s && e <= run.1 => {} // outro byte do mesmo caractere
+                _ => runs.push((s, e)),
+            }
+        }
+        runs
+    }
+
+    /// Encadeia dois estágios: `self` leva o texto A à origem e `next` leva o texto B
+    /// (produzido a partir de A) a A. O resultado leva B direto à origem.
+    pub fn compose(&self, next: &OffsetMap) -> OffsetMap {
+        let point = |at: usize| match self.source.get(at) {
+            Some(&(s, _)) => s,
+            None => self.source.last().map_or(0, |&(_, e)| e),
+        };
+        let source = next
+            .source
+            .iter()
+            .map(|&(s, e)| self.project(s, e).unwrap_or_else(|| (point(s), point(s))))
+            .collect();
+        OffsetMap { source }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_checked_valid() {
+        assert_eq!(slice_checked("São Paulo", 0, 4), Ok("São"));
+        assert_eq!(slice_checked("abc", 3, 3), Ok(""));
+    }
+
+    #[test]
+    fn test_slice_checked_errors() {
+        let text = "São";
+        assert_eq!(slice_checked(text, 0, 2), Err(SliceError::NotCharBoundary { index: 2 }));
+        assert_eq!(slice_checked(text, 2, 1), Err(SliceError::InvertedRange { start: 2, end: 1 }));
+        assert_eq!(
+            slice_checked(text, 0, 10),
+            Err(SliceError::OutOfBounds { start: 0, end: 10, len: 4 })
+        );
+    }
+
+    #[test]
+    fn test_slice_lossy_snaps_to_boundaries() {
+        let text = "Ação já";
+        // "ç" ocupa os bytes 1..3 e "ã" os bytes 3..5
+        assert_eq!(slice_lossy(text, 2, 4), "çã");
+        assert_eq!(slice_lossy(text, 5, 100), "o já");
+        assert_eq!(slice_lossy(text, 6, 2), "");
+    }
+
+    #[test]
+    fn test_char_offset() {
+        let text = "São Paulo";
+        assert_eq!(char_offset(text, 5), 4);
+        assert_eq!(char_offset(text, 2), 2);
+        assert_eq!(char_offset(text, 100), 9);
+    }
+
+    #[test]
+    fn test_offset_map_compose_multibyte() {
+        // Origem → A: "&Aacute;gua" vira "Água" (o "Á" veio de 8 bytes)
+        let original = "&Aacute;gua  ão";
+        let mut first = OffsetMap::new();
+        first.push('Á', (0, 8));
+        for (i, c) in original[8..].char_indices() {
+            first.push(c, (8 + i, 8 + i + c.len_utf8()));
+        }
+        let a = "Água  ão";
+        assert_eq!(first.len(), a.len());
+
+        // A → B: espaços colapsados e um separador inserido no início
+        let mut second = OffsetMap::new();
+        second.push('\n', (0, 0));
+        for (i, c) in "Água".char_indices() {
+            second.push(c, (i, i + c.len_utf8()));
+        }
+        second.push(' ', (5, 7));
+        second.push('ã', (7, 9));
+        second.push('o', (9, 10));
+        let b = "\nÁgua ão";
+        assert_eq!(second.len(), b.len());
+
+        let composed = first.compose(&second);
+        assert_eq!(composed.len(), b.len());
+        let project = |needle: &str| {
+            let start = b.find(needle).unwrap();
+            let (s, e) = composed.project(start, start + needle.len()).unwrap();
+            &original[s..e]
+        };
+        assert_eq!(project("Água"), "&Aacute;gua");
+        assert_eq!(project("Á"), "&Aacute;");
+        assert_eq!(project("ão"), "ão");
+        assert_eq!(project("a ã"), "a  ã");
+        // O separador inserido não existe na origem
+        assert_eq!(composed.project(0, 1), Some((0, 0)));
+        assert_eq!(composed.source_runs(0, 3), vec![(0, 8)]);
+
+        let identity = OffsetMap::identity(original);
+        assert_eq!(identity.compose(&composed), composed);
+        assert_eq!(identity.project(8, 11), Some((8, 11)));
+        assert_eq!(identity.project(2, 2), None);
+    }
+}
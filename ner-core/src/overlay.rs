@@ -0,0 +1,221 @@
+//! # Camadas sobre o Modelo Base (Dicionários e Filtros de Saída)
+//!
+//! Um mesmo servidor pode atender clientes com vocabulários diferentes: uma empresa
+//! quer que os nomes dos seus produtos sejam ORG, outra só se interessa por pessoas.
+//! Retreinar (ou mesmo clonar) o modelo para cada cliente seria caro; em vez disso,
+//! cada cliente ganha **camadas** leves aplicadas sobre o resultado do modelo compartilhado:
+//!
+//! - [`DictionaryOverlay`]: frases do dicionário do cliente. Cada ocorrência vira uma
+//!   entidade e **substitui** as entidades do modelo que se sobrepõem a ela.
+//! - [`EntityFilter`]: filtro de saída por confiança mínima e categorias permitidas.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::overlay::{DictionaryOverlay, EntityFilter};
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let overlay = DictionaryOverlay::new().with_entry(EntityCategory::Org, "Zubrex Pay");
+//! let text = "O app zubrex pay chegou ao Recife.";
+//! let entities = overlay.apply(text, &tokenize(text), Vec::new());
+//! assert_eq!(entities[0].text, "zubrex pay");
+//!
+//! let filter = EntityFilter::default().with_categories(vec![EntityCategory::Per]);
+//! assert!(filter.apply(entities).is_empty());
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::features::GazetteerKey;
+use crate::offsets::slice_lossy;
+use crate::pipeline::InvalidOptions;
+use crate::tagger::{sort_entities, EntityCategory, EntityOrder, EntitySpan};
+use crate::tokenizer::{tokenize, Token};
+
+/// Confiança atribuída às entidades vindas de um dicionário do cliente.
+pub const OVERLAY_CONFIDENCE: f64 = 0.95;
+
+/// Fonte (`EntitySpan::source`) das entidades do dicionário.
+pub const OVERLAY_SOURCE: &str = "dictionary_overlay";
+
+/// Uma frase do dicionário, já tokenizada e normalizada.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct OverlayEntry {
+    category: EntityCategory,
+    words: Vec<String>,
+}
+
+/// Dicionário de frases aplicado sobre as entidades do modelo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictionaryOverlay {
+    entries: Vec<OverlayEntry>,
+}
+
+impl DictionaryOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acrescenta uma frase. Ela é tokenizada como o texto analisado, então
+    /// "Zubrex S.A." casa com os mesmos tokens que o pipeline produz.
+    pub fn add(&mut self, category: EntityCategory, phrase: &str) {
+        let words: Vec<String> = tokenize(phrase).iter().map(|t| GazetteerKey::normalize(&t.text)).collect();
+        if !words.is_empty() {
+            self.entries.push(OverlayEntry { category, words });
+        }
+    }
+
+    pub fn with_entry(mut self, category: EntityCategory, phrase: &str) -> Self {
+        self.add(category, phrase);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Ocorrências das frases nos `tokens` de `text`, sem maiúsculas/minúsculas.
+    ///
+    /// A varredura é da esquerda para a direita e, em cada posição, vence a frase mais
+    /// longa; as ocorrências retornadas nunca se sobrepõem.
+    pub fn matches(&self, text: &str, tokens: &[Token]) -> Vec<EntitySpan> {
+        let words: Vec<String> = tokens.iter().map(|t| GazetteerKey::normalize(&t.text)).collect();
+        let mut spans = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let best = self
+                .entries
+                .iter()
+                .filter(|e| words[i..].starts_with(&e.words))
+                .max_by_key(|e| e.words.len());
+            match best {
+                Some(entry) => {
+                    let end_token = i + entry.words.len() - 1;
+                    let (start, end) = (tokens[i].start, tokens[end_token].end);
+                    spans.push(EntitySpan {
+                        text: slice_lossy(text, start, end).to_string(),
+                        category: entry.category,
+                        start_token: i,
+                        end_token,
+                        start,
+                        end,
+                        confidence: OVERLAY_CONFIDENCE,
+                        source: OVERLAY_SOURCE.to_string(),
+                        score_breakdown: None,
+                    });
+                    i = end_token + 1;
+                }
+                None => i += 1,
+            }
+        }
+        spans
+    }
+
+    /// Combina as ocorrências do dicionário com as `entities` do modelo: entidades que
+    /// se sobrepõem a uma ocorrência são descartadas. O resultado vem em ordem de posição.
+    pub fn apply(&self, text: &str, tokens: &[Token], entities: Vec<EntitySpan>) -> Vec<EntitySpan> {
+        if self.is_empty() {
+            return entities;
+        }
+        let overlay = self.matches(text, tokens);
+        let mut merged: Vec<EntitySpan> = entities
+            .into_iter()
+            .filter(|e| !overlay.iter().any(|o| o.start < e.end && e.start < o.end))
+            .collect();
+        merged.extend(overlay);
+        sort_entities(&mut merged, EntityOrder::Position);
+        merged
+    }
+}
+
+/// Filtro de saída: confiança mínima e categorias permitidas.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityFilter {
+    /// Entidades com confiança abaixo deste valor são descartadas.
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Se presente, apenas estas categorias são mantidas.
+    #[serde(default)]
+    pub categories: Option<Vec<EntityCategory>>,
+}
+
+impl EntityFilter {
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    pub fn with_categories(mut self, categories: Vec<EntityCategory>) -> Self {
+        self.categories = Some(categories);
+        self
+    }
+
+    /// Rejeita confiança fora de `[0, 1]` e lista de categorias vazia.
+    pub fn validate(&self) -> Result<(), InvalidOptions> {
+        if let Some(min) = self.min_confidence {
+            if !(0.0..=1.0).contains(&min) {
+                return Err(InvalidOptions::MinConfidence(min));
+            }
+        }
+        if self.categories.as_ref().is_some_and(|cats| cats.is_empty()) {
+            return Err(InvalidOptions::EmptyCategories);
+        }
+        Ok(())
+    }
+
+    /// A entidade passa pelo filtro?
+    pub fn keeps(&self, entity: &EntitySpan) -> bool {
+        self.min_confidence.is_none_or(|min| entity.confidence >= min)
+            && self.categories.as_ref().is_none_or(|cats| cats.contains(&entity.category))
+    }
+
+    pub fn apply(&self, entities: Vec<EntitySpan>) -> Vec<EntitySpan> {
+        entities.into_iter().filter(|e| self.keeps(e)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_replaces_overlapping_entities() {
+        let overlay = DictionaryOverlay::new()
+            .with_entry(EntityCategory::Org, "Zubrex")
+            .with_entry(EntityCategory::Misc, "Zubrex Pay")
+            .with_entry(EntityCategory::Loc, "  ");
+        assert_eq!(overlay.len(), 2);
+
+        let text = "Ana usa o ZUBREX PAY e a Zubrex no Recife.";
+        let tokens = tokenize(text);
+        let entities = vec![
+            EntitySpan { confidence: 0.9, ..EntitySpan::test_at(text, "Ana", EntityCategory::Per) },
+            EntitySpan { confidence: 0.6, ..EntitySpan::test_at(text, "ZUBREX", EntityCategory::Per) },
+            EntitySpan { confidence: 0.4, ..EntitySpan::test_at(text, "Recife", EntityCategory::Loc) },
+        ];
+        let merged = overlay.apply(text, &tokens, entities);
+        let summary: Vec<(&str, EntityCategory, &str)> =
+            merged.iter().map(|e| (e.text.as_str(), e.category, e.source.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Ana", EntityCategory::Per, "crf"),
+                // A frase mais longa vence e substitui a entidade do modelo
+                ("ZUBREX PAY", EntityCategory::Misc, OVERLAY_SOURCE),
+                ("Zubrex", EntityCategory::Org, OVERLAY_SOURCE),
+                ("Recife", EntityCategory::Loc, "crf"),
+            ]
+        );
+
+        let filter = EntityFilter::default()
+            .with_min_confidence(0.5)
+            .with_categories(vec![EntityCategory::Org, EntityCategory::Loc, EntityCategory::Misc]);
+        let kept: Vec<String> = filter.apply(merged).into_iter().map(|e| e.text).collect();
+        assert_eq!(kept, vec!["ZUBREX PAY", "Zubrex"]);
+    }
+}
@@ -5,8 +5,10 @@
 
 use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
-use crate::corpus::AnnotatedSentence;
-use crate::features::{self, FeatureVector, Gazetteers};
+use crate::backend::SequenceTagger;
+use crate::corpus::{AnnotatedSentence, OwnedAnnotatedSentence};
+use crate::features::{FeatureExtractor, FeatureVector, Gazetteers, SharedExtractor};
+use crate::training::TrainingSchedule;
 
 /// Modelo Perceptron Médio (Averaged Perceptron).
 ///
@@ -39,6 +41,9 @@ pub struct PerceptronModel {
     steps: usize,
     /// Tags conhecidas.
     tags: Vec<String>,
+    /// Extrator de features usado no treino e na predição (não serializado).
+    #[serde(skip)]
+    extractor: SharedExtractor,
 }
 
 impl PerceptronModel {
@@ -49,9 +54,29 @@ impl PerceptronModel {
             last_update: HashMap::new(),
             steps: 0,
             tags: Vec::new(),
+            extractor: SharedExtractor::default(),
         }
     }
 
+    /// Troca o extrator de features (ver [`FeatureExtractor`]). Treine depois de trocá-lo.
+    pub fn with_extractor(mut self, extractor: impl FeatureExtractor + 'static) -> Self {
+        self.set_extractor(extractor);
+        self
+    }
+
+    pub fn set_extractor(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.extractor = SharedExtractor::new(extractor);
+    }
+
+    pub fn extractor(&self) -> &dyn FeatureExtractor {
+        &*self.extractor
+    }
+
+    /// Indica se o modelo já foi treinado (possui tags conhecidas).
+    pub fn is_trained(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
     /// Treina o modelo (Online Learning).
     ///
     /// O algoritmo itera pelo corpus várias vezes (`iterations`). Para cada sentença:
@@ -60,50 +85,71 @@ impl PerceptronModel {
     ///
     /// Ao final, calcula a média dos pesos (finalize_weights) para obter o modelo robusto.
     pub fn train(&mut self, corpus: &[AnnotatedSentence], iterations: usize) {
-        // Coleta tags
+        self.train_stream(corpus.iter().map(OwnedAnnotatedSentence::from), iterations);
+    }
+
+    /// Como [`Self::train`], mas lendo as sentenças de um iterador (ex: um
+    /// [`crate::corpus_reader::CorpusReader`]), sem manter o corpus em memória.
+    ///
+    /// Cada época (e a coleta inicial de tags) percorre um clone de `corpus`.
+    pub fn train_stream(&mut self, corpus: impl Iterator<Item = OwnedAnnotatedSentence> + Clone, iterations: usize) {
+        self.collect_tags(corpus.clone());
+        let gaz = Gazetteers::new();
+        for _ in 0..iterations {
+            self.train_epoch(corpus.clone(), &gaz);
+        }
+        // Finaliza: Atualiza total de todos os pesos até o passo final e calcula média
+        self.finalize_weights();
+    }
+
+    /// Como [`Self::train`], percorrendo cada época na ordem do `schedule`
+    /// (embaralhamento, currículo, sobreamostragem; ver [`crate::training`]).
+    pub fn train_scheduled(&mut self, corpus: &[AnnotatedSentence], iterations: usize, schedule: &TrainingSchedule) {
+        let owned: Vec<OwnedAnnotatedSentence> = corpus.iter().map(OwnedAnnotatedSentence::from).collect();
+        self.collect_tags(owned.iter().cloned());
+        let gaz = Gazetteers::new();
+        for epoch in 0..iterations {
+            let order = schedule.epoch_order(&owned, epoch);
+            self.train_epoch(order.into_iter().map(|i| owned[i].clone()), &gaz);
+        }
+        self.finalize_weights();
+    }
+
+    fn collect_tags(&mut self, corpus: impl Iterator<Item = OwnedAnnotatedSentence>) {
         let mut tag_set = HashSet::new();
         for s in corpus {
             for (_, tag) in s.annotations {
-                tag_set.insert(tag.to_string());
+                tag_set.insert(tag);
             }
         }
         self.tags = tag_set.into_iter().collect();
         self.tags.sort();
+    }
 
-        let gaz = Gazetteers::new();
+    /// Uma passada pelas sentenças, atualizando os pesos a cada erro.
+    fn train_epoch(&mut self, sentences: impl Iterator<Item = OwnedAnnotatedSentence>, gaz: &Gazetteers) {
+        for sentence in sentences {
+            // Reconstrói tokens (simplificação)
+            let tokens: Vec<crate::tokenizer::Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
+                crate::tokenizer::Token::new(text.to_string(), 0, 0, i)
+            }).collect();
 
-        for _ in 0..iterations {
-            for sentence in corpus {
-                // Reconstrói tokens (simplificação)
-                let tokens: Vec<crate::tokenizer::Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
-                    crate::tokenizer::Token {
-                        text: text.to_string(),
-                        start: 0,
-                        end: 0,
-                        index: i,
-                    }
-                }).collect();
-
-                let feature_vectors = features::extract_features(&tokens, &gaz);
-
-                for (i, fv) in feature_vectors.iter().enumerate() {
-                    let true_tag = sentence.annotations[i].1;
-                    
-                    // Predição usando pesos REAIS (não averaged durante treino)
-                    let pred_tag = self.predict_single(fv, false);
-
-                    // Atualiza apenas em caso de erro (mistake-driven)
-                    if pred_tag != true_tag {
-                        self.update(fv, true_tag, &pred_tag);
-                    }
-                    
-                    self.steps += 1;
+            let feature_vectors = self.extractor.extract(&tokens, gaz);
+
+            for (i, fv) in feature_vectors.iter().enumerate() {
+                let true_tag = sentence.annotations[i].1.as_str();
+                
+                // Predição usando pesos REAIS (não averaged durante treino)
+                let pred_tag = self.predict_single(fv, false);
+
+                // Atualiza apenas em caso de erro (mistake-driven)
+                if pred_tag != true_tag {
+                    self.update(fv, true_tag, &pred_tag);
                 }
+                
+                self.steps += 1;
             }
         }
-        
-        // Finaliza: Atualiza total de todos os pesos até o passo final e calcula média
-        self.finalize_weights();
     }
 
     fn predict_single(&self, fv: &FeatureVector, use_averaged: bool) -> String {
@@ -139,7 +185,7 @@ impl PerceptronModel {
     /// $w_{errado} \leftarrow w_{errado} - \phi(x)$
     fn update(&mut self, fv: &FeatureVector, true_tag: &str, pred_tag: &str) {
         // Para cada feature ativa
-        for (fname, _fval) in &fv.features {
+        for fname in fv.features.keys() {
             // Nota: Perceptron binário assume fval=1.0 geralmente, mas aqui usamos generalizado.
             // Para simplificar, assumimos features binárias ou multiplicamos pelo valor.
             
@@ -195,27 +241,75 @@ impl PerceptronModel {
 
     /// Predição final (usando pesos médios)
     pub fn predict(&self, tokens: &[String]) -> Vec<String> {
+        self.predict_with_confidence(tokens).into_iter().map(|(tag, _)| tag).collect()
+    }
+
+    /// Como [`PerceptronModel::predict`], com o softmax dos scores do token na tag
+    /// escolhida. Os scores do perceptron não são probabilidades: o valor só ordena as
+    /// decisões, e a calibração ([`crate::calibration`]) o leva a uma probabilidade.
+    pub fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
         let gaz = Gazetteers::new();
         let input_tokens: Vec<crate::tokenizer::Token> = tokens.iter().enumerate().map(|(i, text)| {
-             crate::tokenizer::Token {
-                text: text.clone(),
-                start: 0,
-                end: 0,
-                index: i,
-            }
+             crate::tokenizer::Token::new(text.clone(), 0, 0, i)
         }).collect();
 
-        let feature_vectors = features::extract_features(&input_tokens, &gaz);
+        let feature_vectors = self.extractor.extract(&input_tokens, &gaz);
         let mut result = Vec::with_capacity(tokens.len());
-
         for fv in feature_vectors {
             // Usa weights (que agora são averages)
-            result.push(self.predict_single(&fv, true));
+            let best_tag = self.predict_single(&fv, true);
+            // Soma em ordem fixa: a ordem do HashMap mudaria a probabilidade na última casa
+            let mut features: Vec<(&String, &f64)> = fv.features.iter().collect();
+            features.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            let scores: Vec<f64> = self
+                .tags
+                .iter()
+                .map(|tag| {
+                    features
+                        .iter()
+                        .filter_map(|(name, value)| self.weights.get(&((*name).clone(), tag.clone())).map(|w| w * *value))
+                        .sum()
+                })
+                .collect();
+            let probability = self
+                .tags
+                .iter()
+                .position(|t| *t == best_tag)
+                .map_or(0.0, |t| crate::viterbi::scores_to_probs(&scores)[t]);
+            result.push((best_tag, probability));
         }
         result
     }
 }
 
+impl Default for PerceptronModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceTagger for PerceptronModel {
+    fn name(&self) -> &str {
+        "perceptron"
+    }
+
+    fn predict(&self, tokens: &[String]) -> Vec<String> {
+        PerceptronModel::predict(self, tokens)
+    }
+
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(String, f64)> {
+        PerceptronModel::predict_with_confidence(self, tokens)
+    }
+
+    fn is_trained(&self) -> bool {
+        PerceptronModel::is_trained(self)
+    }
+
+    fn extractor(&self) -> Option<&dyn FeatureExtractor> {
+        Some(PerceptronModel::extractor(self))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,4 +332,94 @@ mod tests {
 
         assert_eq!(tags[0], "B-PER");
     }
+
+    #[test]
+    fn test_custom_extractor_is_used_in_training_and_prediction() {
+        /// Só a palavra, sem contexto nem forma: "Lula" em outra frase continua PER,
+        /// mas uma palavra nunca vista não tem nenhuma feature com peso.
+        #[derive(Debug)]
+        struct WordOnly;
+
+        impl FeatureExtractor for WordOnly {
+            fn extract(
+                &self,
+                tokens: &[crate::tokenizer::Token],
+                _: &Gazetteers,
+            ) -> Vec<FeatureVector> {
+                tokens
+                    .iter()
+                    .map(|t| {
+                        let mut fv = FeatureVector::new(t.index);
+                        fv.insert(format!("word={}", t.text), 1.0);
+                        fv
+                    })
+                    .collect()
+            }
+        }
+
+        let corpus = vec![AnnotatedSentence {
+            text: "O Lula viajou",
+            domain: "test",
+            annotations: &[("O", "O"), ("Lula", "B-PER"), ("viajou", "O")],
+        }];
+        let mut model = PerceptronModel::new().with_extractor(WordOnly);
+        model.train(&corpus, 3);
+        assert!(model.weights.keys().all(|(f, _)| f.starts_with("word=")));
+
+        let tokens: Vec<String> = ["Lula", "Dilma"].iter().map(|w| w.to_string()).collect();
+        // Sem features ativas, todas as tags empatam em 0 e vence a primeira ("B-PER")
+        assert_eq!(model.predict(&tokens), vec!["B-PER", "B-PER"]);
+        let tokens: Vec<String> = ["viajou"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(model.predict(&tokens), vec!["O"]);
+    }
+
+    #[test]
+    fn test_train_scheduled() {
+        use crate::corpus::get_corpus;
+        use crate::tagger::EntityCategory;
+
+        let corpus = get_corpus();
+        let mut plain = PerceptronModel::new();
+        plain.train(&corpus, 2);
+        // Sem opções, a agenda percorre o corpus na ordem original
+        let mut scheduled = PerceptronModel::new();
+        scheduled.train_scheduled(&corpus, 2, &TrainingSchedule::new());
+        assert_eq!(plain.weights, scheduled.weights);
+
+        let schedule = TrainingSchedule::new()
+            .with_shuffle(3)
+            .with_curriculum()
+            .with_oversampling(EntityCategory::Misc, 3);
+        let mut a = PerceptronModel::new();
+        a.train_scheduled(&corpus, 2, &schedule);
+        let mut b = PerceptronModel::new();
+        b.train_scheduled(&corpus, 2, &schedule);
+        assert_eq!(a.weights, b.weights);
+        assert_ne!(a.weights, plain.weights);
+        assert_eq!(a.tags, plain.tags);
+    }
+
+    #[test]
+    fn test_train_stream_from_disk_matches_in_memory() {
+        use crate::corpus::get_corpus;
+        use crate::corpus_reader::{CorpusFormat, CorpusReader};
+
+        let corpus: Vec<AnnotatedSentence> = get_corpus().into_iter().take(12).collect();
+        let jsonl: String = corpus
+            .iter()
+            .map(|s| serde_json::to_string(&OwnedAnnotatedSentence::from(s)).unwrap() + "\n")
+            .collect();
+        let path = std::env::temp_dir().join(format!("ner_perceptron_stream_{}.jsonl", std::process::id()));
+        std::fs::write(&path, jsonl).unwrap();
+
+        let mut in_memory = PerceptronModel::new();
+        in_memory.train(&corpus, 3);
+        let mut streamed = PerceptronModel::new();
+        streamed.train_stream(CorpusReader::open(&path, CorpusFormat::Jsonl).unwrap(), 3);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(streamed.steps, in_memory.steps);
+        let tokens: Vec<String> = corpus[3].annotations.iter().map(|(w, _)| w.to_string()).collect();
+        assert_eq!(streamed.predict(&tokens), in_memory.predict(&tokens));
+    }
 }
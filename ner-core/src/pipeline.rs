@@ -3,28 +3,79 @@
 //! O pipeline coordena todos os módulos (tokenizador, features, regras, CRF/Viterbi)
 //! e emite eventos em cada passo via um canal Rust (`mpsc`), permitindo que
 //! o servidor WebSocket transmita o progresso em tempo real para o cliente.
+//!
+//! ## Saída Plana vs Sobreposta
+//!
+//! | Modo | `analyze` / `Done` | `analyze_multilabel` |
+//! |------|--------------------|----------------------|
+//! | Hybrid, RulesOnly, CrfOnly, FeaturesOnly | plana (BIO) | plana, 1 rótulo por trecho |
+//! | Hmm, MaxEnt, Memm, Perceptron | plana (BIO) | plana, 1 rótulo por trecho |
+//! | SpanBased | **pode sobrepor** | sobreposta, vários rótulos com probabilidade |
+//!
+//! Os modos de sequência (BIO) atribuem exatamente uma tag por token, então nunca
+//! produzem sobreposição. O zero-shot (`sota_2024`) tem as duas variantes:
+//! `simulate_gliner` (com NMS, plana) e `simulate_gliner_overlapping`.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
 use std::sync::mpsc;
+use std::sync::Arc;
+#[cfg(feature = "rules")]
+use std::sync::Mutex;
 
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::features::{extract_features, FeatureVector};
+use crate::backend::{SequenceTagger, SpanPredictor};
+use crate::calibration::{calibration_samples, PlattScaling};
+use crate::chunking::{split_preserving_entities, TextChunk};
+use crate::diff::{diff_entities, GoldEntity, SpanDiff};
+use crate::features::{FeatureExtractor, FeatureTemplate, FeatureVector, GazetteerLookups, Gazetteers};
+use crate::ingest::{extract_html, unwrap_lines, ExtractedText};
+use crate::language::LanguagePack;
+use crate::crf::CrfModel;
 use crate::model::NerModel;
-use crate::tagger::{tokens_to_spans, EntitySpan, Tag, TaggedToken};
-use crate::tokenizer::{tokenize_with_mode, Token, TokenizerMode};
-use crate::viterbi::{viterbi_decode, ViterbiStep};
+use crate::corpus::get_corpus;
+use crate::offsets::{slice_checked, slice_lossy};
+use crate::overlay::EntityFilter;
+use crate::probabilities::{token_probabilities, TokenProbabilities};
+use crate::render::to_conll_gold;
+/// Tokens etiquetados em CoNLL (`token<TAB>tag`), como saem de
+/// [`NerPipeline::analyze_with_mode`]; o formato de três colunas com o ouro fica em
+/// [`NerPipeline::conll_against_gold`].
+pub use crate::render::to_conll;
+#[cfg(feature = "rules")]
+use crate::rule_based::{RuleGroups, RuleStats};
+use crate::self_check::{self, SelfCheckConfig, SelfCheckReport};
+use crate::tagger::{
+    sort_entities, tokens_to_spans, EntityCategory, EntityOrder, EntitySpan, LabelScore, MultiLabelSpan,
+    ScoreBreakdown, Tag, TaggedToken,
+};
+use crate::temporal::{find_expressions, Expression};
+use crate::thresholds::{CategoryThresholds, ThresholdTuner};
+use crate::tokenizer::{
+    sentence_ranges, BpeMergeTable, BpeMismatch, BpeModel, Token, TokenizerConfig, TokenizerMode,
+};
+use crate::variants::SpellingVariants;
+use crate::viterbi::{
+    marginals, summarize_sentences, Decoder, ViterbiDetail, ViterbiScratch, ViterbiSentenceSummary, ViterbiStep,
+    COMPACT_TOP_K,
+};
 
 /// Modo de operação do algoritmo NER.
 ///
 /// O usuário pode escolher qual combinação de algoritmos usar para analisar o texto.
 /// Cada modo oferece um balanço diferente entre precisão e explicabilidade.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AlgorithmMode {
     /// **Híbrido (Recomendado)**: Combina Regras + CRF + Viterbi.
     /// - Primeiro aplica regras determinísticas (gazetteers, regex).
     /// - Onde as regras não cobrem, usa o modelo estatístico (CRF).
     /// - Produz os melhores resultados gerais.
+    #[default]
     Hybrid,
     /// **Apenas Regras**: Usa somente gazetteers e padrões.
     /// Ítil para debugging ou quando se quer controle total sobre a saída.
@@ -43,6 +94,9 @@ pub enum AlgorithmMode {
     /// **MaxEnt (Entropia Máxima)**: Classificador logístico que não considera a sequência (histórico).
     /// Classifica cada token independentemente.
     MaxEnt,
+    /// **MEMM**: MaxEnt com a tag anterior como feature, decodificado com Viterbi.
+    /// Respeita a consistência BIO (nunca produz `I-X` sem um `B-X` antes).
+    Memm,
     /// **Perceptron Médio**: Algoritmo online simples e eficaz.
     /// Aprende iterativamente a separar as classes.
     Perceptron,
@@ -50,8 +104,359 @@ pub enum AlgorithmMode {
     SpanBased,
 }
 
-impl Default for AlgorithmMode {
-    fn default() -> Self { AlgorithmMode::Hybrid }
+/// Estágios opcionais que um modo executa. Tokenização e montagem das entidades
+/// sempre rodam; os demais são pulados quando o modo não precisa deles.
+///
+/// Pular estágios é o que torna `RulesOnly` barato: sem extração de features nem
+/// Viterbi, o custo fica dominado pela varredura dos gazetteers (útil para anonimização
+/// de grandes volumes com dicionários).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeStages {
+    /// Extração de features (eventos `FeaturesComputed`).
+    pub features: bool,
+    /// Motor de regras (eventos `RuleApplied`).
+    pub rules: bool,
+    /// Decodificação Viterbi do CRF (eventos `ViterbiStep` / `ViterbiSummary`).
+    pub viterbi: bool,
+}
+
+impl AlgorithmMode {
+    /// Estágios de que o modo precisa.
+    ///
+    /// ```rust
+    /// use ner_core::AlgorithmMode;
+    /// let stages = AlgorithmMode::RulesOnly.stages();
+    /// assert!(stages.rules && !stages.features && !stages.viterbi);
+    /// ```
+    pub fn stages(self) -> ModeStages {
+        let (features, rules, viterbi) = match self {
+            AlgorithmMode::Hybrid => (true, true, true),
+            AlgorithmMode::RulesOnly => (false, true, false),
+            AlgorithmMode::CrfOnly => (true, false, true),
+            AlgorithmMode::FeaturesOnly => (true, false, false),
+            // MaxEnt e Perceptron calculam as próprias features; as do CRF são só visualização
+            AlgorithmMode::MaxEnt | AlgorithmMode::Memm | AlgorithmMode::Perceptron => (true, false, false),
+            AlgorithmMode::Hmm | AlgorithmMode::SpanBased => (false, false, false),
+        };
+        ModeStages { features, rules, viterbi }
+    }
+
+    /// Todos os modos, na ordem de declaração (para iteração).
+    pub fn all() -> [AlgorithmMode; 9] {
+        [
+            AlgorithmMode::Hybrid,
+            AlgorithmMode::RulesOnly,
+            AlgorithmMode::CrfOnly,
+            AlgorithmMode::FeaturesOnly,
+            AlgorithmMode::Hmm,
+            AlgorithmMode::MaxEnt,
+            AlgorithmMode::Memm,
+            AlgorithmMode::Perceptron,
+            AlgorithmMode::SpanBased,
+        ]
+    }
+}
+
+/// Como o modo `Hybrid` decide quando regra e CRF discordam sobre um trecho.
+///
+/// Regras vencerem sempre é seguro para nomes inequívocos, mas erra em palavras
+/// ambíguas: "Vale" é ORG em "a Vale exportou minério", mas não em "o vale do rio".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionPolicy {
+    /// A regra sempre vence (comportamento padrão).
+    #[default]
+    RulePriority,
+    /// O CRF sempre vence; a regra só prevalece quando concorda com ele.
+    ModelPriority,
+    /// Vence a maior confiança: a da regra ou a probabilidade média do CRF no trecho.
+    WeightedByConfidence,
+}
+
+impl FusionPolicy {
+    /// Nome usado em `EntitySpan::source` (ex: `"org_gazetteer:weighted_by_confidence"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            FusionPolicy::RulePriority => "rule_priority",
+            FusionPolicy::ModelPriority => "model_priority",
+            FusionPolicy::WeightedByConfidence => "weighted_by_confidence",
+        }
+    }
+}
+
+/// Políticas de fusão do modo `Hybrid`: uma padrão e exceções por categoria.
+///
+/// A categoria considerada é a da regra que disparou no trecho.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FusionConfig {
+    pub default_policy: FusionPolicy,
+    pub per_category: HashMap<EntityCategory, FusionPolicy>,
+}
+
+impl FusionConfig {
+    pub fn new(default_policy: FusionPolicy) -> Self {
+        Self { default_policy, per_category: HashMap::new() }
+    }
+
+    /// Define a política de uma categoria (ex: ORG → `ModelPriority`).
+    pub fn with_category(mut self, category: EntityCategory, policy: FusionPolicy) -> Self {
+        self.per_category.insert(category, policy);
+        self
+    }
+
+    pub fn policy_for(&self, category: EntityCategory) -> FusionPolicy {
+        self.per_category.get(&category).copied().unwrap_or(self.default_policy)
+    }
+}
+
+/// Probabilidade mínima para um rótulo secundário entrar na saída multi-label.
+#[cfg(feature = "statistical")]
+const MULTILABEL_MIN_SCORE: f64 = 0.1;
+
+/// Quais eventos `FeaturesComputed` uma análise emite.
+///
+/// Um evento por token inunda o cliente em documentos de milhares de tokens; as outras
+/// políticas reduzem o volume sem mudar o resultado da análise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureEvents {
+    /// Um evento por token.
+    #[default]
+    All,
+    /// Um evento a cada `n` tokens (os de índice 0, n, 2n...).
+    EveryNth(usize),
+    /// Só os tokens que terminam dentro de uma entidade, emitidos após a decodificação.
+    EntityTokens,
+    /// Um `SentenceFeatures` por sentença, com as features somadas sobre seus tokens.
+    PerSentence,
+}
+
+/// Opções de uma análise: o que é reportado e, com `rule_groups`, quais regras rodam.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisOptions {
+    /// Nível de detalhe dos eventos do Viterbi.
+    pub viterbi_detail: ViterbiDetail,
+    /// Quais tokens geram eventos `FeaturesComputed`.
+    pub feature_events: FeatureEvents,
+    /// Preenche `EntitySpan::score_breakdown` (emissão, transição, regra, calibração).
+    ///
+    /// Desligado por padrão: recalcular as contribuições do CRF por trecho tem custo.
+    /// Só os modos `Hybrid`, `CrfOnly` e `RulesOnly` produzem a decomposição.
+    pub explain: bool,
+    /// Abstenção: sentenças com [`SentenceConfidence::confidence`] abaixo deste limiar são
+    /// marcadas como `needs_review` e suas entidades são omitidas da saída.
+    ///
+    /// As tags dos tokens (`TagAssigned`, `tagged_tokens`) não mudam, para que o revisor
+    /// veja o que o modelo propôs. `None` (padrão) nunca se abstém.
+    pub abstain_below: Option<f64>,
+    /// Analisa no máximo este número de tokens; o restante do texto é ignorado e
+    /// reportado com um aviso [`WarningCode::TruncatedInput`]. `None` (padrão) não limita.
+    pub max_tokens: Option<usize>,
+    /// Grupos de regras ativos nos modos `Hybrid` e `RulesOnly` (padrão: todos), para
+    /// dispensar um gazetteer ruidoso ou medir o efeito de cada grupo sem refazer o motor.
+    #[cfg(feature = "rules")]
+    pub rule_groups: RuleGroups,
+}
+
+impl AnalysisOptions {
+    /// Rejeita combinações sem sentido antes de analisar (ex: vindas de um cliente HTTP).
+    ///
+    /// `abstain_below` acima de 1 é aceito: é a forma de mandar tudo para revisão.
+    ///
+    /// ```
+    /// use ner_core::{AnalysisOptions, InvalidOptions};
+    /// assert!(AnalysisOptions::default().validate().is_ok());
+    /// let options = AnalysisOptions { max_tokens: Some(0), ..Default::default() };
+    /// assert_eq!(options.validate(), Err(InvalidOptions::ZeroMaxTokens));
+    /// ```
+    pub fn validate(&self) -> Result<(), InvalidOptions> {
+        if let Some(threshold) = self.abstain_below {
+            if !threshold.is_finite() || threshold < 0.0 {
+                return Err(InvalidOptions::AbstainThreshold(threshold));
+            }
+        }
+        if self.max_tokens == Some(0) {
+            return Err(InvalidOptions::ZeroMaxTokens);
+        }
+        if self.feature_events == FeatureEvents::EveryNth(0) {
+            return Err(InvalidOptions::ZeroFeatureStride);
+        }
+        Ok(())
+    }
+}
+
+/// Opção de análise ou filtro de saída inválido (ver [`AnalysisOptions::validate`] e
+/// [`EntityFilter::validate`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidOptions {
+    /// `abstain_below` negativo ou não finito.
+    AbstainThreshold(f64),
+    /// `max_tokens = Some(0)` descartaria o texto inteiro.
+    ZeroMaxTokens,
+    /// `feature_events = EveryNth(0)`.
+    ZeroFeatureStride,
+    /// `min_confidence` fora de `[0, 1]`.
+    MinConfidence(f64),
+    /// Lista de categorias permitidas vazia: nenhuma entidade passaria.
+    EmptyCategories,
+}
+
+impl fmt::Display for InvalidOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AbstainThreshold(t) => write!(f, "abstain_below = {t} inválido (use um valor finito ≥ 0)"),
+            Self::ZeroMaxTokens => write!(f, "max_tokens = 0 descartaria o texto inteiro"),
+            Self::ZeroFeatureStride => write!(f, "feature_events = every_nth(0) inválido (use n ≥ 1)"),
+            Self::MinConfidence(c) => write!(f, "min_confidence = {c} fora de [0, 1]"),
+            Self::EmptyCategories => write!(f, "a lista de categorias permitidas está vazia"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidOptions {}
+
+/// Disponibilidade de um modo neste pipeline (ver [`NerPipeline::capabilities`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ModeCapability {
+    pub mode: AlgorithmMode,
+    pub available: bool,
+    /// Modo que roda de fato quando `mode` é pedido (ele mesmo, se disponível).
+    pub resolves_to: AlgorithmMode,
+}
+
+/// O que este build e este pipeline sabem fazer: modos, features do Cargo e categorias.
+///
+/// Permite a clientes (e exemplos) escolher um modo disponível em vez de depender do
+/// fallback silencioso, e a servidores exporem a lista para a interface.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Capabilities {
+    pub modes: Vec<ModeCapability>,
+    /// Features do Cargo compiladas (`rules`, `statistical`, `zero-shot`, `linking`).
+    pub features: Vec<&'static str>,
+    pub categories: Vec<EntityCategory>,
+    /// Notas de cada modo na autoavaliação do modelo, se ela rodou (ver [`crate::self_check`]).
+    pub self_check: Option<SelfCheckReport>,
+}
+
+impl Capabilities {
+    /// Modos que rodam sem fallback.
+    pub fn available_modes(&self) -> Vec<AlgorithmMode> {
+        self.modes.iter().filter(|m| m.available).map(|m| m.mode).collect()
+    }
+
+    pub fn supports(&self, mode: AlgorithmMode) -> bool {
+        self.modes.iter().any(|m| m.mode == mode && m.available)
+    }
+}
+
+/// Features do Cargo com que o crate foi compilado.
+fn compiled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "rules") {
+        features.push("rules");
+    }
+    if cfg!(feature = "statistical") {
+        features.push("statistical");
+    }
+    if cfg!(feature = "zero-shot") {
+        features.push("zero-shot");
+    }
+    if cfg!(feature = "linking") {
+        features.push("linking");
+    }
+    features
+}
+
+/// Entidades com mais tokens que isto geram o aviso [`WarningCode::LongEntity`].
+pub const LONG_ENTITY_TOKENS: usize = 10;
+
+/// Letras ou dígitos mínimos para uma entrada passar pelos estágios do pipeline.
+pub const MIN_ALPHANUMERIC_CHARS: usize = 2;
+
+/// A entrada não tem o que analisar: menos de [`MIN_ALPHANUMERIC_CHARS`] letras ou dígitos
+/// (ex: `"😀"`, `"a"`, `"  \n"`, caracteres de controle, só pontuação).
+///
+/// Essas entradas não contêm entidades; rodá-las pelos estágios só produziria eventos de
+/// ruído (tokens soltos, features, passos do Viterbi).
+pub fn is_degenerate_input(text: &str) -> bool {
+    text.chars().filter(|c| c.is_alphanumeric()).take(MIN_ALPHANUMERIC_CHARS).count() < MIN_ALPHANUMERIC_CHARS
+}
+
+/// Tipo de um [`PipelineEvent::Warning`]: estável, para que clientes filtrem por ele
+/// (a `message` é texto livre para humanos).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// O modo pedido não está disponível; um modo de fallback foi usado.
+    ModeFallback,
+    /// Tabela BPE incompatível com a do treinamento; o tokenizador Standard foi usado.
+    BpeMismatch,
+    /// `TokenizerMode::Bpe` sem modelo BPE no pipeline; o tokenizador Standard foi usado.
+    BpeModelMissing,
+    /// A entrada excedeu [`AnalysisOptions::max_tokens`] e foi cortada.
+    TruncatedInput,
+    /// Um `I-X` órfão na fronteira de uma regra foi convertido em `B-X` pela fusão.
+    BioRepaired,
+    /// Entidade com mais de [`LONG_ENTITY_TOKENS`] tokens: provável fusão indevida de trechos.
+    LongEntity,
+    /// O texto da entidade está no gazetteer de outra categoria, mas não no da sua.
+    GazetteerConflict,
+    /// Entrada degenerada (só emoji, espaços, controle ou um caractere; ver
+    /// [`is_degenerate_input`]): nenhum estágio rodou e o resultado é vazio.
+    DegenerateInput,
+}
+
+/// Um aviso estruturado da análise (ver [`PipelineEvent::Warning`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalysisWarning {
+    pub code: WarningCode,
+    pub message: String,
+    /// Trecho do texto (offsets de byte) a que o aviso se refere, se houver.
+    pub span: Option<Range<usize>>,
+}
+
+/// Confiança agregada de uma sentença (evento [`PipelineEvent::SentencesScored`]).
+///
+/// Uma entidade isolada com baixa confiança é fácil de descartar; uma sentença inteira
+/// em que o modelo "chutou" (ex: texto fora do domínio, sem pontuação, tudo em
+/// maiúsculas) é melhor enviada para revisão humana do que anotada com ruído.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceConfidence {
+    /// Índice do primeiro token da sentença.
+    pub start_token: usize,
+    /// Índice exclusivo do último token da sentença.
+    pub end_token: usize,
+    /// Probabilidade normalizada do caminho: média geométrica das confianças das tags
+    /// escolhidas. Ao contrário do produto, não penaliza sentenças longas.
+    pub confidence: f64,
+    /// Margem média entre a probabilidade da tag escolhida e a da segunda melhor em cada
+    /// token. Só existe quando o Viterbi (CRF) rodou.
+    pub mean_margin: Option<f64>,
+    /// Confiança abaixo de [`AnalysisOptions::abstain_below`]: precisa de revisão humana.
+    pub needs_review: bool,
+}
+
+/// Resultado de [`NerPipeline::analyze_report`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    pub tagged_tokens: Vec<TaggedToken>,
+    /// Entidades, sem as das sentenças marcadas para revisão.
+    pub entities: Vec<EntitySpan>,
+    pub sentences: Vec<SentenceConfidence>,
+    /// Avisos emitidos durante a análise, na ordem em que ocorreram.
+    pub warnings: Vec<AnalysisWarning>,
+    /// Datas, horários, valores e percentuais do texto analisado (ver [`crate::temporal`]),
+    /// independentes do modo.
+    #[serde(default)]
+    pub expressions: Vec<Expression>,
+}
+
+impl AnalysisReport {
+    /// Alguma sentença ficou abaixo do limiar de abstenção.
+    pub fn needs_review(&self) -> bool {
+        self.sentences.iter().any(|s| s.needs_review)
+    }
 }
 
 /// Eventos emitidos pelo pipeline durante o processamento.
@@ -75,6 +480,16 @@ pub enum PipelineEvent {
         /// Lista das 10 features com maiores pesos para visualização.
         top_features: Vec<(String, f64)>,
     },
+    /// **Passo 2 (agregado)**: Features somadas sobre os tokens de uma sentença.
+    /// Substitui os `FeaturesComputed` quando o cliente pede [`FeatureEvents::PerSentence`].
+    SentenceFeatures {
+        /// Índice do primeiro token da sentença.
+        start_token: usize,
+        /// Índice logo após o último token.
+        end_token: usize,
+        /// As 10 features de maior soma na sentença.
+        top_features: Vec<(String, f64)>,
+    },
     /// **Passo 3 (Opcional)**: Uma regra manual foi aplicada com sucesso.
     /// Indica que o sistema "cortou caminho" usando conhecimento prévio.
     RuleApplied {
@@ -83,6 +498,15 @@ pub enum PipelineEvent {
         tag: String,
         rule_name: String,
         confidence: f64,
+        /// Chave do gazetteer casada por grafia variante (ver [`NerPipeline::with_spelling_variants`]).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variant_of: Option<String>,
+    },
+    /// **Passo 3b**: Estatísticas das regras nesta análise (modos Hybrid e RulesOnly).
+    /// Quantas vezes cada regra disparou e quantas foram sobrescritas ou contestadas pelo CRF.
+    #[cfg(feature = "rules")]
+    RuleStatsComputed {
+        stats: RuleStats,
     },
     /// **Passo 4**: Um passo do algoritmo de decodificação Viterbi.
     /// Mostra as probabilidades acumuladas para cada tag naquele ponto da frase.
@@ -90,6 +514,16 @@ pub enum PipelineEvent {
         step: ViterbiStep,
         token_text: String,
     },
+    /// **Passo 4 (resumido)**: Resumo do Viterbi para uma sentença inteira.
+    /// Substitui os `ViterbiStep` quando o cliente pede `ViterbiDetail::Summary`.
+    ViterbiSummary {
+        summary: ViterbiSentenceSummary,
+        sentence_text: String,
+    },
+    /// **Passo 5b**: Confiança de cada sentença e decisão de abstenção, logo antes do `Done`.
+    SentencesScored {
+        sentences: Vec<SentenceConfidence>,
+    },
     /// **Passo Final**: Tag definitiva atribuída a um token.
     /// Pode vir de uma regra ou do cálculo do Viterbi/CRF.
     TagAssigned {
@@ -99,6 +533,12 @@ pub enum PipelineEvent {
         confidence: f64,
         source: String, // "rule" ou "crf"
     },
+    /// **Expressões**: Datas, horários, valores e percentuais encontrados por
+    /// [`crate::temporal::find_expressions`], logo após a tokenização. Paralelas às
+    /// entidades e iguais em todos os modos; emitido só se houver alguma.
+    ExpressionsFound {
+        expressions: Vec<Expression>,
+    },
     /// **Conclusão**: O processo terminou com sucesso.
     /// Retorna todas as entidades estruturadas e estatísticas de tempo.
     Done {
@@ -107,7 +547,17 @@ pub enum PipelineEvent {
         total_tokens: usize,
         processing_ms: u64,
     },
-    /// **Falha**: Ocorreu um erro irrecuperável.
+    /// **Aviso**: Algo foi ajustado ou parece suspeito, sem interromper a análise.
+    /// Ex: o modo solicitado estava indisponível e foi substituído por um modo de fallback,
+    /// ou uma entidade ficou longa demais. `span` aponta o trecho (bytes), se houver.
+    Warning {
+        code: WarningCode,
+        message: String,
+        span: Option<Range<usize>>,
+    },
+    /// **Falha**: Ocorreu um erro durante a análise.
+    /// Pode ser irrecuperável ou apenas reportar um problema contornado
+    /// (ex: offsets de token inválidos, que são ajustados automaticamente).
     Error {
         message: String,
     },
@@ -124,19 +574,393 @@ pub enum PipelineEvent {
 ///
 /// # Modos de Uso
 /// - **Sync**: Método `analyze` para scripts e chamadas diretas.
-/// - **Streaming**: Método `analyze_streaming` para UIs reativas (via WebSocket).
+/// - **Streaming**: Métodos `analyze_streaming` (canal) e `analyze_with_callback` para UIs reativas (via WebSocket).
+///
+/// # Ordem das Entidades
+/// Independentemente do modo, as entidades do evento `Done` são ordenadas por
+/// [`sort_entities`] segundo `entity_order` (padrão: posição no texto).
+///
+/// # Degradação Graciosa
+/// Se o modelo de um modo não estiver disponível (ex: não foi treinado ou falhou ao
+/// carregar), o pipeline percorre `fallback_chain` e usa o primeiro modo disponível,
+/// emitindo um `PipelineEvent::Warning` com a substituição. `RulesOnly` é o último recurso
+/// (ou `FeaturesOnly`, sem a feature `rules`). Modos de features do Cargo desligadas
+/// ([`NerPipeline::is_available`]) também seguem esse caminho.
+///
+/// # Fusão Regras × CRF
+/// No modo `Hybrid`, quando regra e CRF discordam sobre um trecho, `fusion` define quem
+/// vence ([`FusionPolicy`], configurável por categoria). A política aplicada fica
+/// registrada em `EntitySpan::source` (ex: `"crf:model_priority"`).
+///
+/// # Backends
+/// `Hmm`, `MaxEnt`, `Perceptron` e `SpanBased` são despachados pelos traits de
+/// [`crate::backend`]. [`NerPipeline::with_sequence_tagger`] e
+/// [`NerPipeline::with_span_predictor`] trocam o backend de um modo por um próprio.
+///
+/// # Tabela BPE
+/// `TokenizerMode::BpeLite` usa `bpe_merges`. Se ela não for a tabela com que o modelo
+/// foi treinado ([`NerModel::bpe_fingerprint`]), a análise cai para `Standard` e emite
+/// um `Warning`; [`NerPipeline::check_bpe_merges`] permite falhar antes de analisar.
+/// `TokenizerMode::Bpe` usa o modelo treinado de [`NerPipeline::with_bpe_model`]; sem
+/// ele, também cai para `Standard` com um `Warning`.
+///
+/// # Concorrência
+/// `NerPipeline` é `Send + Sync` sem nenhum `unsafe`: o modelo é só dados, os backends
+/// próprios exigem `Send + Sync` ([`SequenceTagger`], [`SpanPredictor`]) e o estado de
+/// uma análise (eventos, relatório) vive na pilha da chamada. Um `&NerPipeline` pode ser
+/// compartilhado entre threads (ex: `rayon`); para alterá-lo enquanto é usado, veja
+/// [`crate::shared::SharedPipeline`].
 pub struct NerPipeline {
     pub model: NerModel,
+    /// Critério de ordenação das entidades na saída.
+    pub entity_order: EntityOrder,
+    /// Ordem de fallback quando o modo solicitado está indisponível.
+    pub fallback_chain: Vec<AlgorithmMode>,
+    /// Como o modo `Hybrid` resolve conflitos entre regras e CRF.
+    pub fusion: FusionConfig,
+    /// Tabela de merges do modo `BpeLite`.
+    pub bpe_merges: BpeMergeTable,
+    /// Modelo BPE treinado do modo `Bpe` (ver [`BpeModel::train`]).
+    pub bpe_model: Option<BpeModel>,
+    /// Abreviações do domínio usadas pelos modos derivados do `Standard`
+    /// (ver [`crate::abbreviations::AbbreviationLearner`]).
+    pub tokenizer_config: TokenizerConfig,
+    /// No modo `Aggressive`, não divide sufixos e clíticos de palavras capitalizadas ou
+    /// presentes nos gazetteers ("Consolidação" continua inteira). Padrão: `true`.
+    pub entity_safe_aggressive: bool,
+    /// Decodificador do CRF nos modos `Hybrid` e `CrfOnly`: Viterbi exato (padrão) ou
+    /// busca em feixe.
+    pub decoder: Decoder,
+    /// Casamento de grafias variantes com os gazetteers ("Sam Paulo", "Baía"); desligado
+    /// por padrão (ver [`crate::variants`]).
+    pub spelling_variants: Option<SpellingVariants>,
+    /// Estatísticas de regras acumuladas desde a criação (ou último reset).
+    #[cfg(feature = "rules")]
+    rule_stats: Mutex<RuleStats>,
+    /// Backends registrados pelo usuário, no lugar dos modelos embutidos.
+    sequence_taggers: HashMap<AlgorithmMode, Arc<dyn SequenceTagger>>,
+    span_predictor: Option<Arc<dyn SpanPredictor>>,
 }
 
 impl NerPipeline {
     /// Cria o pipeline carregando o modelo padrão com pesos heurísticos.
     pub fn new() -> Self {
+        Self::from_model(NerModel::default())
+    }
+
+    /// Cria o pipeline para outra variante do português (ex: [`LanguagePack::pt_pt`]):
+    /// modelo construído com [`NerModel::build_for`] e tokenizador com as abreviações e
+    /// os clíticos do pacote.
+    pub fn for_language(pack: &LanguagePack) -> Self {
+        Self::from_model(NerModel::build_for(pack)).with_tokenizer_config(pack.tokenizer_config())
+    }
+
+    /// Cria o pipeline sobre um modelo já construído (ex: lido com [`NerModel::load`]).
+    pub fn from_model(model: NerModel) -> Self {
         Self {
-            model: NerModel::default(),
+            model,
+            entity_order: EntityOrder::default(),
+            fallback_chain: vec![
+                AlgorithmMode::Hybrid,
+                AlgorithmMode::CrfOnly,
+                AlgorithmMode::Perceptron,
+                AlgorithmMode::Memm,
+                AlgorithmMode::MaxEnt,
+                AlgorithmMode::Hmm,
+                AlgorithmMode::RulesOnly,
+            ],
+            fusion: FusionConfig::default(),
+            bpe_merges: BpeMergeTable::lite(),
+            bpe_model: None,
+            tokenizer_config: TokenizerConfig::new(),
+            entity_safe_aggressive: true,
+            decoder: Decoder::default(),
+            spelling_variants: None,
+            #[cfg(feature = "rules")]
+            rule_stats: Mutex::new(RuleStats::new()),
+            sequence_taggers: HashMap::new(),
+            span_predictor: None,
+        }
+    }
+
+    /// Cópia das estatísticas de regras acumuladas em todas as análises.
+    #[cfg(feature = "rules")]
+    pub fn rule_stats(&self) -> RuleStats {
+        self.rule_stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Zera as estatísticas de regras acumuladas.
+    #[cfg(feature = "rules")]
+    pub fn reset_rule_stats(&self) {
+        *self.rule_stats.lock().unwrap_or_else(|e| e.into_inner()) = RuleStats::new();
+    }
+
+    /// Define o critério de ordenação das entidades (ex: por confiança).
+    pub fn with_entity_order(mut self, order: EntityOrder) -> Self {
+        self.entity_order = order;
+        self
+    }
+
+    /// Define a cadeia de fallback (ex: `[CrfOnly, Perceptron, RulesOnly]`).
+    pub fn with_fallback_chain(mut self, chain: Vec<AlgorithmMode>) -> Self {
+        self.fallback_chain = chain;
+        self
+    }
+
+    /// Usa `tagger` para o modo `mode` (em qualquer modo, inclusive os que já têm um
+    /// modelo embutido). Ver [`crate::backend`].
+    pub fn with_sequence_tagger(mut self, mode: AlgorithmMode, tagger: impl SequenceTagger + 'static) -> Self {
+        self.sequence_taggers.insert(mode, Arc::new(tagger));
+        self
+    }
+
+    /// Usa `predictor` no modo `SpanBased`.
+    pub fn with_span_predictor(mut self, predictor: impl SpanPredictor + 'static) -> Self {
+        self.span_predictor = Some(Arc::new(predictor));
+        self
+    }
+
+    /// Backend que etiqueta tokens no modo: o registrado pelo usuário ou o modelo
+    /// embutido de `Hmm`, `MaxEnt`, `Memm` e `Perceptron`.
+    pub fn sequence_tagger(&self, mode: AlgorithmMode) -> Option<&dyn SequenceTagger> {
+        if let Some(tagger) = self.sequence_taggers.get(&mode) {
+            return Some(tagger.as_ref());
+        }
+        match mode {
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Hmm => Some(self.model.hmm.get()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::MaxEnt => Some(self.model.maxent.get()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Memm => Some(self.model.memm.get()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Perceptron => Some(self.model.perceptron.get()),
+            _ => None,
         }
     }
 
+    /// Backend do modo `SpanBased`: o registrado pelo usuário ou o `SpanModel` embutido.
+    pub fn span_predictor(&self) -> Option<&dyn SpanPredictor> {
+        if let Some(predictor) = &self.span_predictor {
+            return Some(predictor.as_ref());
+        }
+        #[cfg(feature = "statistical")]
+        return Some(self.model.span.get());
+        #[cfg(not(feature = "statistical"))]
+        None
+    }
+
+    /// Define a janela de contexto das features do CRF (ex: `window: 4` para nomes longos).
+    ///
+    /// Atalho para `model.crf.set_extractor(template)`: cada modelo tem o seu extrator
+    /// (ver [`crate::features::FeatureExtractor`]) e o pipeline usa o do modelo que roda.
+    pub fn with_feature_template(mut self, template: FeatureTemplate) -> Self {
+        self.model.crf.set_extractor(template);
+        self
+    }
+
+    /// Define as políticas de fusão entre regras e CRF do modo `Hybrid`.
+    pub fn with_fusion(mut self, fusion: FusionConfig) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Define a tabela de merges do modo `BpeLite` (ex: carregada com [`BpeMergeTable::load`]).
+    pub fn with_bpe_merges(mut self, merges: BpeMergeTable) -> Self {
+        self.bpe_merges = merges;
+        self
+    }
+
+    /// Define o modelo BPE do modo `Bpe` (treinado com [`BpeModel::train`] ou lido com
+    /// [`BpeModel::load`]).
+    pub fn with_bpe_model(mut self, model: BpeModel) -> Self {
+        self.bpe_model = Some(model);
+        self
+    }
+
+    /// Define a configuração do tokenizador (ex: abreviações aprendidas do corpus).
+    pub fn with_tokenizer_config(mut self, config: TokenizerConfig) -> Self {
+        self.tokenizer_config = config;
+        self
+    }
+
+    /// Liga ou desliga a proteção de entidades no modo `Aggressive`
+    /// (desligada, reproduz [`crate::tokenizer::tokenize_with_mode`] exatamente).
+    pub fn with_entity_safe_aggressive(mut self, enabled: bool) -> Self {
+        self.entity_safe_aggressive = enabled;
+        self
+    }
+
+    /// Define o decodificador do CRF (ex: `Decoder::Beam { width: 4 }` para muitas
+    /// categorias personalizadas, trocando exatidão por latência).
+    pub fn with_decoder(mut self, decoder: Decoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    /// Liga o casamento de grafias variantes e históricas com os gazetteers.
+    ///
+    /// Tokens cuja chave não está em nenhum gazetteer são consultados pela chave de que
+    /// são grafia variante; as regras que casam assim registram a chave em
+    /// [`crate::rule_based::RuleMatch::variant_of`] (e no evento `RuleApplied`).
+    ///
+    /// ```rust
+    /// use ner_core::tagger::EntityCategory;
+    /// use ner_core::variants::SpellingVariants;
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    ///
+    /// let pipeline = NerPipeline::new().with_spelling_variants(SpellingVariants::new());
+    /// let (_, entities) = pipeline.analyze_with_mode("Chegou ao Brazil em 1850.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    /// assert!(entities.iter().any(|e| e.text == "Brazil" && e.category == EntityCategory::Loc));
+    /// ```
+    pub fn with_spelling_variants(mut self, variants: SpellingVariants) -> Self {
+        self.spelling_variants = Some(variants);
+        self
+    }
+
+    /// Consultas aos gazetteers do modelo, com as grafias variantes se ligadas.
+    fn gazetteer_lookups(&self, tokens: &[Token]) -> GazetteerLookups {
+        match &self.spelling_variants {
+            Some(variants) => GazetteerLookups::with_variants(tokens, self.model.gazetteers_ref(), variants),
+            None => GazetteerLookups::new(tokens, self.model.gazetteers_ref()),
+        }
+    }
+
+    /// Confere se `bpe_merges` é a tabela usada no treinamento do modelo.
+    pub fn check_bpe_merges(&self) -> Result<(), BpeMismatch> {
+        let (expected, found) = (self.model.bpe_fingerprint, self.bpe_merges.fingerprint());
+        if expected == found {
+            Ok(())
+        } else {
+            Err(BpeMismatch { expected, found })
+        }
+    }
+
+    /// Tokeniza com o modo pedido. `BpeLite` com tabela incompatível e `Bpe` sem modelo
+    /// caem para `Standard`, retornando o aviso para que o chamador o emita.
+    fn tokenize(&self, text: &str, mode: TokenizerMode) -> (Vec<Token>, Option<AnalysisWarning>) {
+        if mode == TokenizerMode::Aggressive && self.entity_safe_aggressive {
+            let gazetteers = self.model.gazetteers_ref();
+            let protect = |token: &Token| {
+                token.is_capitalized()
+                    || gazetteers.categories().into_iter().any(|c| gazetteers.contains_phrase(c, &token.text))
+            };
+            return (self.tokenizer_config.tokenize_aggressive_with(text, protect), None);
+        }
+        let warning = match mode {
+            TokenizerMode::BpeLite => match self.check_bpe_merges() {
+                Ok(()) => return (self.bpe_merges.tokenize(text), None),
+                Err(mismatch) => AnalysisWarning {
+                    code: WarningCode::BpeMismatch,
+                    message: format!("{mismatch}; usando o tokenizador Standard"),
+                    span: None,
+                },
+            },
+            TokenizerMode::Bpe => match &self.bpe_model {
+                Some(model) => return (model.tokenize(text), None),
+                None => AnalysisWarning {
+                    code: WarningCode::BpeModelMissing,
+                    message: "nenhum modelo BPE definido (with_bpe_model); usando o tokenizador Standard".to_string(),
+                    span: None,
+                },
+            },
+            _ => return (self.tokenizer_config.tokenize(text, mode), None),
+        };
+        (self.tokenizer_config.tokenize(text, TokenizerMode::Standard), Some(warning))
+    }
+
+    /// Indica se o(s) modelo(s) exigido(s) por um modo estão prontos para uso.
+    ///
+    /// Modos cuja feature do Cargo foi desligada (`rules`, `statistical`) nunca estão disponíveis.
+    pub fn is_available(&self, mode: AlgorithmMode) -> bool {
+        if let Some(tagger) = self.sequence_taggers.get(&mode) {
+            return tagger.is_trained();
+        }
+        // Os modelos embutidos com treino pendente não são treinados só para responder
+        match mode {
+            #[cfg(feature = "rules")]
+            AlgorithmMode::Hybrid => self.model.crf.is_trained(),
+            AlgorithmMode::CrfOnly => self.model.crf.is_trained(),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Hmm => self.model.hmm.is_usable(|m| m.is_trained()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::MaxEnt => self.model.maxent.is_usable(|m| m.is_trained()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Memm => self.model.memm.is_usable(|m| m.is_trained()),
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::Perceptron => self.model.perceptron.is_usable(|m| m.is_trained()),
+            AlgorithmMode::SpanBased => match &self.span_predictor {
+                Some(predictor) => predictor.is_trained(),
+                #[cfg(feature = "statistical")]
+                None => self.model.span.is_usable(|m| m.is_trained()),
+                #[cfg(not(feature = "statistical"))]
+                None => false,
+            },
+            #[cfg(feature = "rules")]
+            AlgorithmMode::RulesOnly => true,
+            AlgorithmMode::FeaturesOnly => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Modos disponíveis (e para onde cada um cai), features compiladas e categorias.
+    ///
+    /// ```
+    /// use ner_core::{AlgorithmMode, NerPipeline};
+    /// let capabilities = NerPipeline::new().capabilities();
+    /// assert!(capabilities.supports(AlgorithmMode::RulesOnly));
+    /// assert!(capabilities.features.contains(&"rules"));
+    /// ```
+    pub fn capabilities(&self) -> Capabilities {
+        let modes = AlgorithmMode::all()
+            .into_iter()
+            .map(|mode| ModeCapability { mode, available: self.is_available(mode), resolves_to: self.resolve_mode(mode) })
+            .collect();
+        Capabilities {
+            modes,
+            features: compiled_features(),
+            categories: EntityCategory::all().to_vec(),
+            self_check: self.model.self_check.clone(),
+        }
+    }
+
+    /// Avalia os modos disponíveis na fatia do corpus embutido reservada por `config` e
+    /// guarda o relatório no modelo (e em [`Capabilities::self_check`]).
+    ///
+    /// Útil para um modelo lido com [`NerModel::load`] ou ajustado depois de construído.
+    /// Como os modelos estatísticos dele podem ter treinado com a fatia inteira, as
+    /// notas só são comparáveis entre modelos construídos da mesma forma (ver
+    /// [`NerModel::build_with_self_check`]).
+    pub fn run_self_check(&mut self, config: &SelfCheckConfig) -> &SelfCheckReport {
+        let report = self_check::evaluate(self, &get_corpus(), config);
+        self.model.self_check.insert(report)
+    }
+
+    /// Escolhe o modo efetivamente usado para `requested`.
+    ///
+    /// Se `requested` estiver indisponível, segue a cadeia a partir da posição dele
+    /// (ou do início, se ele não fizer parte da cadeia) até achar um modo disponível.
+    pub fn resolve_mode(&self, requested: AlgorithmMode) -> AlgorithmMode {
+        if self.is_available(requested) {
+            return requested;
+        }
+        let from = self
+            .fallback_chain
+            .iter()
+            .position(|&m| m == requested)
+            .map_or(0, |pos| pos + 1);
+        self.fallback_chain[from..]
+            .iter()
+            .copied()
+            .find(|&m| self.is_available(m))
+            .unwrap_or(if self.is_available(AlgorithmMode::RulesOnly) {
+                AlgorithmMode::RulesOnly
+            } else {
+                AlgorithmMode::FeaturesOnly
+            })
+    }
+
     /// Processa o texto de forma síncrona e retorna o resultado final.
     ///
     /// Ideal para processamento em lote ou validação rápida quando não há necessidade
@@ -157,124 +981,592 @@ impl NerPipeline {
     ///
     /// Útil para debugging ou comparações de performance entre modos.
     pub fn analyze_with_mode(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode) -> (Vec<TaggedToken>, Vec<EntitySpan>) {
-        let (tx, rx) = mpsc::channel();
-        self.analyze_streaming(text, mode, tokenizer_mode, tx);
-        let mut tagged = vec![];
-        let mut entities = vec![];
-        
-        // Consome todos os eventos até o fim
-        while let Ok(event) = rx.recv() {
-            if let PipelineEvent::Done {
-                tagged_tokens,
-                entities: ents,
-                ..
-            } = event
-            {
-                tagged = tagged_tokens;
-                entities = ents;
-            }
-        }
-        (tagged, entities)
+        self.analyze_with_options(text, mode, tokenizer_mode, AnalysisOptions::default())
     }
 
-    /// Executa o pipeline enviando eventos de progresso em tempo real.
+    /// Analisa vários textos em paralelo (um por thread do pool do rayon).
     ///
-    /// Este método é o coração da interface visual (ner-web). Ele não retorna valores diretamente,
-    /// mas sim "empurra" `PipelineEvent`s pelo canal `tx`. Isso permite que o frontend
-    /// renderize animações mostrando o modelo "lendo", "pensando" (extraindo features),
-    /// "aplicando regras" e "decidindo" (Viterbi).
+    /// O pipeline só é lido depois de construído, então as análises não disputam travas
+    /// e o ganho cresce com o número de núcleos. O resultado segue a ordem de `texts` e é
+    /// igual ao de chamar [`NerPipeline::analyze_with_mode`] em cada um.
     ///
-    /// # Fluxo de Eventos
-    /// 1. `TokenizationDone`: Tokens gerados.
-    /// 2. `FeaturesComputed` (Loop): Features de cada token (se aplicável), mostrando o que o modelo "vê".
-    /// 3. `RuleApplied` (Loop): Regras que "bateram" (se modo híbrido), mostrando conhecimento explícito.
-    /// 4. `ViterbiStep` (Loop): Passos do algoritmo de decodificação, mostrando a incerteza probabilística.
-    /// 5. `TagAssigned` (Loop): Decisão final para cada token.
-    /// 6. `Done`: Resultado final consolidado com métricas de tempo.
-    pub fn analyze_streaming(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode, tx: mpsc::Sender<PipelineEvent>) {
-        let start = std::time::Instant::now();
+    /// ```
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    /// let texts = vec!["o Brasil venceu.".to_string(), "a Petrobras lucrou.".to_string()];
+    /// let results = NerPipeline::new().analyze_batch(&texts, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    /// assert_eq!(results[0].1[0].text, "Brasil");
+    /// assert_eq!(results[1].1[0].text, "Petrobras");
+    /// ```
+    pub fn analyze_batch(
+        &self,
+        texts: &[String],
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+    ) -> Vec<(Vec<TaggedToken>, Vec<EntitySpan>)> {
+        texts
+            .par_iter()
+            .map(|text| self.analyze_with_mode(text, mode, tokenizer_mode))
+            .collect()
+    }
 
-        // === Passo 1: Tokenização ===
-        let tokens = tokenize_with_mode(text, tokenizer_mode);
-        let total = tokens.len();
-        let _ = tx.send(PipelineEvent::TokenizationDone {
-            tokens: tokens.clone(),
-            total,
-        });
+    /// Analisa um documento sentença por sentença (ver [`crate::tokenizer::split_sentences`]).
+    ///
+    /// Cada sentença é uma sequência independente para o Viterbi, o que mantém a
+    /// decodificação rápida e evita que uma sentença influencie as tags da outra em
+    /// documentos longos. Tokens e entidades voltam com offsets e índices do documento.
+    ///
+    /// ```
+    /// use ner_core::NerPipeline;
+    /// let text = "O Dr. Silva visitou a Petrobras. Depois foi ao Rio de Janeiro.";
+    /// let (tokens, entities) = NerPipeline::new().analyze_document(text);
+    /// assert_eq!(tokens.last().unwrap().token.index, tokens.len() - 1);
+    /// assert!(entities.iter().all(|e| text[e.start..e.end] == e.text));
+    /// ```
+    pub fn analyze_document(&self, text: &str) -> (Vec<TaggedToken>, Vec<EntitySpan>) {
+        self.analyze_document_with_mode(text, AlgorithmMode::Hybrid, TokenizerMode::Standard)
+    }
 
-        if tokens.is_empty() {
-            let _ = tx.send(PipelineEvent::Done {
-                entities: vec![],
-                tagged_tokens: vec![],
-                total_tokens: 0,
-                processing_ms: start.elapsed().as_millis() as u64,
-            });
-            return;
+    /// Igual a [`NerPipeline::analyze_document`], configurando o algoritmo e tokenizador.
+    pub fn analyze_document_with_mode(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+    ) -> (Vec<TaggedToken>, Vec<EntitySpan>) {
+        let mut tagged: Vec<TaggedToken> = Vec::new();
+        let mut entities = Vec::new();
+        for range in self.tokenizer_config.split_sentences(text) {
+            let (offset, first) = (range.start, tagged.len());
+            let (sentence_tokens, sentence_entities) = self.analyze_with_mode(&text[range], mode, tokenizer_mode);
+            tagged.extend(sentence_tokens.into_iter().map(|mut t| {
+                t.token.start += offset;
+                t.token.end += offset;
+                t.token.index += first;
+                t
+            }));
+            entities.extend(sentence_entities.into_iter().map(|e| EntitySpan {
+                start: e.start + offset,
+                end: e.end + offset,
+                start_token: e.start_token + first,
+                end_token: e.end_token + first,
+                ..e
+            }));
         }
+        (tagged, entities)
+    }
 
-        match mode {
-            AlgorithmMode::Hybrid | AlgorithmMode::RulesOnly | AlgorithmMode::CrfOnly | AlgorithmMode::FeaturesOnly => {
-                 self.analyze_streaming_standard(text, &tokens, mode, &tx, start);
-            }
-            AlgorithmMode::Hmm | AlgorithmMode::MaxEnt | AlgorithmMode::Perceptron => {
-                 self.analyze_streaming_ml(text, &tokens, mode, &tx, start);
-            }
-             AlgorithmMode::SpanBased => {
-                 self.analyze_streaming_span(text, &tokens, &tx, start);
-             }
+    /// Igual a [`NerPipeline::analyze_with_mode`], com [`AnalysisOptions`] (ex: `explain`).
+    ///
+    /// ```
+    /// use ner_core::{AlgorithmMode, AnalysisOptions, NerPipeline, TokenizerMode};
+    /// let pipeline = NerPipeline::new();
+    /// let options = AnalysisOptions { explain: true, ..Default::default() };
+    /// let (_, entities) = pipeline.analyze_with_options("o Brasil venceu.", AlgorithmMode::RulesOnly, TokenizerMode::Standard, options);
+    /// let why = entities[0].score_breakdown.as_ref().unwrap();
+    /// assert_eq!(why.rule_confidence, Some(entities[0].confidence));
+    /// assert_eq!(why.emission, None); // o CRF não roda em RulesOnly
+    /// ```
+    pub fn analyze_with_options(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        options: AnalysisOptions,
+    ) -> (Vec<TaggedToken>, Vec<EntitySpan>) {
+        let report = self.analyze_report(text, mode, tokenizer_mode, options);
+        (report.tagged_tokens, report.entities)
+    }
+
+    /// Igual a [`NerPipeline::analyze_with_options`], incluindo a confiança de cada sentença.
+    ///
+    /// Com `abstain_below`, sentenças incertas ficam sem entidades e marcadas para revisão:
+    ///
+    /// ```
+    /// use ner_core::{AlgorithmMode, AnalysisOptions, NerPipeline, TokenizerMode};
+    /// let pipeline = NerPipeline::new();
+    /// let options = AnalysisOptions { abstain_below: Some(1.01), ..Default::default() };
+    /// let report = pipeline.analyze_report("o Brasil venceu.", AlgorithmMode::Hybrid, TokenizerMode::Standard, options);
+    /// assert!(report.needs_review());
+    /// assert!(report.entities.is_empty());
+    /// ```
+    pub fn analyze_report(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        options: AnalysisOptions,
+    ) -> AnalysisReport {
+        // Ninguém escuta os eventos de progresso: eles nem são construídos (ver `EventSink`)
+        let sink = EventSink::Report(RefCell::default());
+        self.run(text, mode, tokenizer_mode, options, &sink);
+        match sink {
+            EventSink::Report(report) => report.into_inner(),
+            EventSink::Callback(_) => unreachable!(),
         }
     }
 
-    fn analyze_streaming_standard(&self, text: &str, tokens: &[Token], mode: AlgorithmMode, tx: &mpsc::Sender<PipelineEvent>, start: std::time::Instant) {
-         // === Passo 2: Extração de Features ===
-        let gazetteers = self.model.gazetteers();
-        let feature_vectors: Vec<FeatureVector> =
-            extract_features(tokens, &gazetteers);
+    /// Valida `options` e `filter`, analisa e aplica o filtro de saída às entidades.
+    ///
+    /// As tags dos tokens e a confiança das sentenças não são filtradas.
+    ///
+    /// ```
+    /// use ner_core::overlay::EntityFilter;
+    /// use ner_core::tagger::EntityCategory;
+    /// use ner_core::{AlgorithmMode, AnalysisOptions, NerPipeline, TokenizerMode};
+    /// let pipeline = NerPipeline::new();
+    /// let text = "A Petrobras abriu uma sede no Brasil.";
+    /// let filter = EntityFilter::default().with_categories(vec![EntityCategory::Loc]);
+    /// let report = pipeline
+    ///     .analyze_filtered(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, AnalysisOptions::default(), &filter)
+    ///     .unwrap();
+    /// let found: Vec<&str> = report.entities.iter().map(|e| e.text.as_str()).collect();
+    /// assert_eq!(found, ["Brasil"]);
+    ///
+    /// let filter = EntityFilter::default().with_min_confidence(1.5);
+    /// let options = AnalysisOptions::default();
+    /// assert!(pipeline.analyze_filtered(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, options, &filter).is_err());
+    /// ```
+    pub fn analyze_filtered(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        options: AnalysisOptions,
+        filter: &EntityFilter,
+    ) -> Result<AnalysisReport, InvalidOptions> {
+        options.validate()?;
+        filter.validate()?;
+        let mut report = self.analyze_report(text, mode, tokenizer_mode, options);
+        report.entities = filter.apply(report.entities);
+        Ok(report)
+    }
 
-        for (i, fv) in feature_vectors.iter().enumerate() {
-            // Envia as top 10 features por importância
-            let mut sorted: Vec<(String, f64)> = fv
-                .features
-                .iter()
-                .map(|(k, v)| (k.clone(), *v))
-                .collect();
-            sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-            sorted.truncate(10);
+    /// Analisa um documento HTML: extrai o texto visível (ver [`crate::ingest`]) e o processa.
+    ///
+    /// Os offsets das entidades são relativos a `ExtractedText::text`; use
+    /// [`ExtractedText::project`] ou [`ExtractedText::highlight`] para voltar ao HTML.
+    pub fn analyze_html(&self, html: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode) -> (ExtractedText, Vec<EntitySpan>) {
+        let extracted = extract_html(html);
+        let (_, entities) = self.analyze_with_mode(&extracted.text, mode, tokenizer_mode);
+        (extracted, entities)
+    }
 
-            let _ = tx.send(PipelineEvent::FeaturesComputed {
-                token_index: i,
-                token_text: tokens[i].text.clone(),
-                top_features: sorted,
-            });
-        }
+    /// Analisa texto diagramado (extraído de PDF) depois de desfazer quebras de linha e
+    /// hifenização (ver [`crate::ingest::unwrap_lines`]).
+    ///
+    /// As entidades quebradas pela diagramação voltam inteiras, com offsets do `text`
+    /// original:
+    ///
+    /// ```
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    /// let text = "O lucro da Petro-\nbras subiu.";
+    /// let entities = NerPipeline::new().analyze_wrapped(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    /// assert_eq!(entities[0].text, "Petrobras");
+    /// assert_eq!(&text[entities[0].start..entities[0].end], "Petro-\nbras");
+    /// ```
+    pub fn analyze_wrapped(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode) -> Vec<EntitySpan> {
+        self.analyze_prepared(&unwrap_lines(text), mode, tokenizer_mode)
+    }
 
-        // === Passo 3: Motor de Regras (pula se CrfOnly ou FeaturesOnly) ===
+    /// Analisa um texto pré-processado (ver [`crate::ingest::ExtractedText::and_then`]) e
+    /// devolve as entidades com offsets do documento de origem.
+    ///
+    /// ```
+    /// use ner_core::ingest::{sanitize, unwrap_lines};
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    /// let text = "Reunião em Sa\u{303}o\u{A0}Paulo com a Petro-\nbras.";
+    /// let prepared = sanitize(text).and_then(unwrap_lines);
+    /// let entities = NerPipeline::new().analyze_prepared(&prepared, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    /// let sp = entities.iter().find(|e| e.text == "São Paulo").unwrap();
+    /// assert_eq!(&text[sp.start..sp.end], "Sa\u{303}o\u{A0}Paulo");
+    /// ```
+    pub fn analyze_prepared(&self, prepared: &ExtractedText, mode: AlgorithmMode, tokenizer_mode: TokenizerMode) -> Vec<EntitySpan> {
+        let (_, entities) = self.analyze_with_mode(&prepared.text, mode, tokenizer_mode);
+        prepared.project_entities(&entities)
+    }
+
+    /// Divide `text` em trechos de até `max_chars` caracteres para RAG/LLMs, sem partir
+    /// sentenças (quando possível) nem as entidades detectadas (ver [`crate::chunking`]).
+    pub fn split_preserving_entities(&self, text: &str, max_chars: usize) -> Vec<TextChunk> {
+        let (_, entities) = self.analyze(text);
+        split_preserving_entities(text, &entities, max_chars)
+    }
+
+    /// Analisa `text` e compara as entidades com as anotações ouro (ver [`crate::diff`]).
+    pub fn diff_against_gold(
+        &self,
+        text: &str,
+        gold: &[GoldEntity],
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+    ) -> Vec<SpanDiff> {
+        let (_, predicted) = self.analyze_with_mode(text, mode, tokenizer_mode);
+        diff_entities(gold, &predicted)
+    }
+
+    /// Analisa `text` e escreve o resultado em CoNLL de três colunas,
+    /// `token<TAB>ouro<TAB>predito`, para o `conlleval` ou outras ferramentas de avaliação
+    /// (ver [`crate::render::to_conll_gold`]).
+    ///
+    /// ```rust
+    /// use ner_core::diff::GoldEntity;
+    /// use ner_core::tagger::EntityCategory;
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    ///
+    /// let text = "A Petrobras abriu vagas.";
+    /// let gold = [GoldEntity::find(text, "Petrobras", EntityCategory::Org).unwrap()];
+    /// let conll = NerPipeline::new().conll_against_gold(text, &gold, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+    /// assert!(conll.starts_with("A\tO\tO\nPetrobras\tB-ORG\tB-ORG\n"));
+    /// ```
+    pub fn conll_against_gold(
+        &self,
+        text: &str,
+        gold: &[GoldEntity],
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+    ) -> String {
+        let (tagged, _) = self.analyze_with_mode(text, mode, tokenizer_mode);
+        to_conll_gold(&tagged, gold)
+    }
+
+    /// Escolhe limiares de confiança por categoria em `dev` (pares texto/ouro) e os guarda
+    /// em `model.thresholds`, de onde passam a valer para todas as análises.
+    ///
+    /// As previsões usadas na escolha são feitas sem os limiares anteriores.
+    pub fn tune_thresholds(
+        &mut self,
+        dev: &[(String, Vec<GoldEntity>)],
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        tuner: &ThresholdTuner,
+    ) -> &CategoryThresholds {
+        self.model.thresholds = CategoryThresholds::default();
+        let predictions: Vec<(Vec<GoldEntity>, Vec<EntitySpan>)> = dev
+            .iter()
+            .map(|(text, gold)| (gold.clone(), self.analyze_with_mode(text, mode, tokenizer_mode).1))
+            .collect();
+        self.model.thresholds = tuner.tune(&predictions);
+        &self.model.thresholds
+    }
+
+    /// Ajusta em `dev` (pares texto/ouro) a calibração de confiança de `mode` e a guarda
+    /// em `model.calibration` (ver [`crate::calibration`]). `None` se o modo não
+    /// encontrou nenhuma entidade em `dev`, caso em que a calibração anterior é descartada.
+    ///
+    /// As previsões usadas no ajuste são feitas sem a calibração anterior e sem os
+    /// limiares por categoria. `dev` não deve ter sido usado no treino, como a fatia de
+    /// [`SelfCheckConfig::holdout_gold`] num modelo de [`NerModel::build_with_self_check`].
+    ///
+    /// ```rust,no_run
+    /// use ner_core::corpus::get_corpus;
+    /// use ner_core::language::LanguagePack;
+    /// use ner_core::model::NerModel;
+    /// use ner_core::self_check::SelfCheckConfig;
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    ///
+    /// let config = SelfCheckConfig::default();
+    /// let mut pipeline = NerPipeline::from_model(NerModel::build_with_self_check(&LanguagePack::pt_br(), &config));
+    /// let dev = config.holdout_gold(&get_corpus());
+    /// for mode in [AlgorithmMode::Hybrid, AlgorithmMode::Hmm, AlgorithmMode::Perceptron] {
+    ///     pipeline.calibrate_confidence(&dev, mode, TokenizerMode::Standard);
+    /// }
+    /// ```
+    pub fn calibrate_confidence(
+        &mut self,
+        dev: &[(String, Vec<GoldEntity>)],
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+    ) -> Option<PlattScaling> {
+        self.model.calibration.remove(mode);
+        let thresholds = std::mem::take(&mut self.model.thresholds);
+        let predictions: Vec<(Vec<GoldEntity>, Vec<EntitySpan>)> = dev
+            .iter()
+            .map(|(text, gold)| (gold.clone(), self.analyze_with_mode(text, mode, tokenizer_mode).1))
+            .collect();
+        self.model.thresholds = thresholds;
+
+        let scaling = PlattScaling::fit(&calibration_samples(&predictions))?;
+        self.model.calibration.set(mode, scaling);
+        Some(scaling)
+    }
+
+    /// Tabela de probabilidades do CRF por token, para exportar com
+    /// [`crate::probabilities::write_csv`] (ver [`crate::probabilities`]).
+    pub fn token_probabilities(&self, text: &str, tokenizer_mode: TokenizerMode) -> Vec<TokenProbabilities> {
+        let (tokens, _) = self.tokenize(text, tokenizer_mode);
+        let features = self.model.crf.extractor().extract(&tokens, self.model.gazetteers_ref());
+        token_probabilities(&self.model.crf, &tokens, &features)
+    }
+
+    /// Retorna trechos possivelmente sobrepostos, cada um com todos os rótulos plausíveis.
+    ///
+    /// Apenas `SpanBased` produz sobreposição e múltiplos rótulos (com probabilidade
+    /// mínima de 10% para rótulos secundários). Os demais modos são planos: cada entidade
+    /// de [`NerPipeline::analyze_with_mode`] vira um trecho com um único rótulo.
+    /// A saída segue a ordem de leitura (ver [`EntityOrder::Position`]).
+    pub fn analyze_multilabel(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode) -> Vec<MultiLabelSpan> {
+        let mut spans: Vec<MultiLabelSpan> = match mode {
+            #[cfg(feature = "statistical")]
+            AlgorithmMode::SpanBased => self.span_multilabel(text, tokenizer_mode),
+            _ => {
+                let (_, entities) = self.analyze_with_mode(text, mode, tokenizer_mode);
+                entities
+                    .into_iter()
+                    .map(|e| MultiLabelSpan {
+                        labels: vec![LabelScore { category: e.category, score: e.confidence }],
+                        text: e.text,
+                        start_token: e.start_token,
+                        end_token: e.end_token,
+                        start: e.start,
+                        end: e.end,
+                        source: e.source,
+                    })
+                    .collect()
+            }
+        };
+
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| b.end.cmp(&a.end)));
+        spans
+    }
+
+    /// Trechos sobrepostos do `SpanModel`, com os rótulos acima de [`MULTILABEL_MIN_SCORE`].
+    #[cfg(feature = "statistical")]
+    fn span_multilabel(&self, text: &str, tokenizer_mode: TokenizerMode) -> Vec<MultiLabelSpan> {
+        let (tokens, _) = self.tokenize(text, tokenizer_mode);
+        let token_strs: Vec<String> = tokens.iter().map(|t| t.text.clone()).collect();
+        self.model
+            .span
+            .predict_scored(&token_strs)
+            .into_iter()
+            .filter_map(|scored| {
+                let labels: Vec<LabelScore> = scored
+                    .scores
+                    .iter()
+                    .filter(|(label, score)| label != "O" && *score >= MULTILABEL_MIN_SCORE)
+                    .filter_map(|(label, score)| {
+                        EntityCategory::from_str(label).map(|category| LabelScore { category, score: *score })
+                    })
+                    .collect();
+                if labels.is_empty() {
+                    return None;
+                }
+                let (start, end) = (tokens[scored.start].start, tokens[scored.end - 1].end);
+                Some(MultiLabelSpan {
+                    text: slice_lossy(text, start, end).to_string(),
+                    start_token: scored.start,
+                    end_token: scored.end - 1,
+                    start,
+                    end,
+                    labels,
+                    source: "span_model".to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Executa o pipeline enviando eventos de progresso em tempo real.
+    ///
+    /// Este método é o coração da interface visual (ner-web). Ele não retorna valores diretamente,
+    /// mas sim "empurra" `PipelineEvent`s pelo canal `tx`. Isso permite que o frontend
+    /// renderize animações mostrando o modelo "lendo", "pensando" (extraindo features),
+    /// "aplicando regras" e "decidindo" (Viterbi).
+    ///
+    /// # Fluxo de Eventos
+    /// 1. `TokenizationDone`: Tokens gerados.
+    /// 2. `FeaturesComputed` (Loop): Features de cada token (se o modo precisar, ver
+    ///    [`AlgorithmMode::stages`]), mostrando o que o modelo "vê". Em textos longos,
+    ///    [`AnalysisOptions::feature_events`] limita os tokens ou agrega por sentença.
+    /// 3. `RuleApplied` (Loop): Regras que "bateram" (se modo híbrido), mostrando conhecimento explícito.
+    /// 4. `ViterbiStep` (Loop): Passos do algoritmo de decodificação, mostrando a incerteza probabilística.
+    /// 5. `TagAssigned` (Loop): Decisão final para cada token.
+    /// 6. `Done`: Resultado final consolidado com métricas de tempo.
+    pub fn analyze_streaming(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode, tx: mpsc::Sender<PipelineEvent>) {
+        self.analyze_streaming_with_detail(text, mode, tokenizer_mode, ViterbiDetail::Full, tx);
+    }
+
+    /// Igual a [`NerPipeline::analyze_streaming`], escolhendo o nível de detalhe dos eventos
+    /// do Viterbi: tabela completa, apenas as melhores tags (`Compact`) ou um
+    /// `ViterbiSummary` por sentença (`Summary`), útil para textos longos.
+    pub fn analyze_streaming_with_detail(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        viterbi_detail: ViterbiDetail,
+        tx: mpsc::Sender<PipelineEvent>,
+    ) {
+        let options = AnalysisOptions { viterbi_detail, ..Default::default() };
+        self.analyze_streaming_with_options(text, mode, tokenizer_mode, options, tx);
+    }
+
+    /// Versão mais geral de [`NerPipeline::analyze_streaming`], com todas as [`AnalysisOptions`].
+    pub fn analyze_streaming_with_options(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        options: AnalysisOptions,
+        tx: mpsc::Sender<PipelineEvent>,
+    ) {
+        self.analyze_with_callback(text, mode, tokenizer_mode, options, |event| {
+            let _ = tx.send(event);
+        });
+    }
+
+    /// Como [`NerPipeline::analyze_streaming_with_options`], entregando cada evento a
+    /// `on_event` assim que ele é produzido, na thread que analisa.
+    ///
+    /// É a ponte para canais de outros runtimes: um servidor assíncrono roda a análise em
+    /// uma thread de bloqueio e repassa os eventos por um canal `tokio`, enviando-os ao
+    /// cliente enquanto o pipeline ainda trabalha.
+    ///
+    /// ```rust
+    /// use ner_core::pipeline::{AnalysisOptions, PipelineEvent};
+    /// use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+    ///
+    /// let pipeline = NerPipeline::new();
+    /// let mut kinds = Vec::new();
+    /// pipeline.analyze_with_callback("Lula visitou Brasília.", AlgorithmMode::Hybrid, TokenizerMode::Standard, AnalysisOptions::default(), |event| {
+    ///     kinds.push(matches!(event, PipelineEvent::Done { .. }));
+    /// });
+    /// // O `Done` é sempre o último evento
+    /// assert_eq!(kinds.iter().position(|&done| done), Some(kinds.len() - 1));
+    /// ```
+    pub fn analyze_with_callback(
+        &self,
+        text: &str,
+        mode: AlgorithmMode,
+        tokenizer_mode: TokenizerMode,
+        options: AnalysisOptions,
+        mut on_event: impl FnMut(PipelineEvent),
+    ) {
+        self.run(text, mode, tokenizer_mode, options, &EventSink::Callback(RefCell::new(&mut on_event)));
+    }
+
+    /// A análise em si, comum ao streaming e à análise síncrona.
+    fn run(&self, text: &str, mode: AlgorithmMode, tokenizer_mode: TokenizerMode, options: AnalysisOptions, sink: &EventSink) {
+        let start = std::time::Instant::now();
+
+        // Atalho: entrada sem conteúdo gera um resultado vazio, sem passar pelos estágios
+        if is_degenerate_input(text) {
+            sink.progress(|| PipelineEvent::TokenizationDone { tokens: Vec::new(), total: 0 });
+            send_warning(
+                sink,
+                WarningCode::DegenerateInput,
+                format!("Entrada sem conteúdo analisável (menos de {MIN_ALPHANUMERIC_CHARS} letras ou dígitos)"),
+                (!text.is_empty()).then_some(0..text.len()),
+            );
+            self.send_done(sink, vec![], vec![], &options, None, start);
+            return;
+        }
+
+        // === Passo 1: Tokenização ===
+        let (mut tokens, tokenizer_warning) = self.tokenize(text, tokenizer_mode);
+        if let Some(warning) = tokenizer_warning {
+            send_warning(sink, warning.code, warning.message, warning.span);
+        }
+        if let Some(max) = options.max_tokens.filter(|&max| tokens.len() > max) {
+            let cut = tokens.get(max).map_or(text.len(), |t| t.start);
+            send_warning(
+                sink,
+                WarningCode::TruncatedInput,
+                format!("Entrada com {} tokens cortada em {max}; o restante não foi analisado", tokens.len()),
+                Some(cut..text.len()),
+            );
+            tokens.truncate(max);
+        }
+        let total = tokens.len();
+        sink.progress(|| PipelineEvent::TokenizationDone {
+            tokens: tokens.clone(),
+            total,
+        });
+        // Com a entrada cortada, só o trecho analisado
+        let analyzed = tokens.last().map_or(0, |t| t.end);
+        let expressions = find_expressions(text.get(..analyzed).unwrap_or(text));
+        if !expressions.is_empty() {
+            sink.send(PipelineEvent::ExpressionsFound { expressions });
+        }
+        report_invalid_offsets(text, &tokens, sink);
+
+        let requested = mode;
+        let mode = self.resolve_mode(requested);
+        if mode != requested {
+            send_warning(
+                sink,
+                WarningCode::ModeFallback,
+                format!("Modo {:?} indisponível; usando {:?} como fallback", requested, mode),
+                None,
+            );
+        }
+
+        if tokens.is_empty() {
+            self.send_done(sink, vec![], vec![], &options, None, start);
+            return;
+        }
+
+        if self.sequence_tagger(mode).is_some() {
+            self.analyze_streaming_tagger(text, &tokens, mode, &options, sink, start);
+            return;
+        }
+        match mode {
+            AlgorithmMode::Hybrid | AlgorithmMode::RulesOnly | AlgorithmMode::CrfOnly | AlgorithmMode::FeaturesOnly => {
+                 self.analyze_streaming_standard(text, &tokens, mode, options, sink, start);
+            }
+            AlgorithmMode::SpanBased => {
+                // `resolve_mode` só escolhe SpanBased se houver um backend de spans
+                let predictor = self.span_predictor().expect("SpanBased sem backend");
+                self.analyze_streaming_span(text, &tokens, predictor, &options, sink, start);
+            }
+            // `resolve_mode` nunca escolhe um modo sem backend
+            _ => unreachable!("modo {mode:?} indisponível nesta compilação"),
+        }
+    }
+
+    fn analyze_streaming_standard(&self, text: &str, tokens: &[Token], mode: AlgorithmMode, options: AnalysisOptions, sink: &EventSink, start: std::time::Instant) {
+        let stages = mode.stages();
+        // Consultas aos gazetteers compartilhadas entre features e regras
+        let lookups = self.gazetteer_lookups(tokens);
+
+        // === Passo 2: Extração de Features (pula se RulesOnly) ===
+        let feature_vectors: Vec<FeatureVector> = if stages.features {
+            self.compute_features(tokens, self.model.crf.extractor(), &lookups, options.feature_events, sink)
+        } else {
+            Vec::new()
+        };
+
+        // === Passo 3: Motor de Regras (pula se CrfOnly ou FeaturesOnly) ===
+        #[cfg_attr(not(feature = "rules"), allow(unused_mut))]
         let mut rule_tags: Vec<Option<(Tag, String, f64)>> = vec![None; tokens.len()];
 
-        if mode != AlgorithmMode::CrfOnly && mode != AlgorithmMode::FeaturesOnly {
-            let rule_results = self.model.rule_engine.apply(tokens);
+        #[cfg(feature = "rules")]
+        if stages.rules {
+            let rule_results = self.model.rule_engine.apply_selected(tokens, &lookups, options.rule_groups);
             for (i, maybe_match) in rule_results.iter().enumerate() {
                 if let Some(rm) = maybe_match {
-                    let _ = tx.send(PipelineEvent::RuleApplied {
+                    sink.progress(|| PipelineEvent::RuleApplied {
                         token_index: i,
                         token_text: tokens[i].text.clone(),
                         tag: rm.tag.label(),
                         rule_name: rm.rule_name.clone(),
                         confidence: rm.confidence,
+                        variant_of: rm.variant_of.clone(),
                     });
                     rule_tags[i] = Some((rm.tag.clone(), rm.rule_name.clone(), rm.confidence));
                 }
             }
         }
 
-        // Se RulesOnly: aplica apenas as regras e conclui
-        if mode == AlgorithmMode::RulesOnly || mode == AlgorithmMode::FeaturesOnly {
+        // Sem Viterbi (RulesOnly, FeaturesOnly): aplica apenas as regras e conclui
+        if !stages.viterbi {
+            if options.feature_events == FeatureEvents::EntityTokens {
+                send_token_features(tokens, &feature_vectors, |i| rule_tags[i].is_some(), sink);
+            }
             let tagged_tokens: Vec<TaggedToken> = tokens
                 .iter()
                 .enumerate()
                 .map(|(i, token)| {
                     if let Some((rule_tag, rule_name, rule_conf)) = &rule_tags[i] {
-                        let _ = tx.send(PipelineEvent::TagAssigned {
+                        sink.progress(|| PipelineEvent::TagAssigned {
                             token_index: i,
                             token_text: token.text.clone(),
                             tag: rule_tag.label(),
@@ -283,7 +1575,7 @@ impl NerPipeline {
                         });
                         TaggedToken { token: token.clone(), tag: rule_tag.clone(), confidence: *rule_conf }
                     } else {
-                        let _ = tx.send(PipelineEvent::TagAssigned {
+                        sink.progress(|| PipelineEvent::TagAssigned {
                             token_index: i,
                             token_text: token.text.clone(),
                             tag: Tag::Outside.label(),
@@ -295,151 +1587,157 @@ impl NerPipeline {
                 })
                 .collect();
 
-            let entities = tokens_to_spans(&tagged_tokens, text);
-            let _ = tx.send(PipelineEvent::Done {
-                entities,
-                tagged_tokens,
-                total_tokens: tokens.len(),
-                processing_ms: start.elapsed().as_millis() as u64,
-            });
+            #[cfg(feature = "rules")]
+            if mode == AlgorithmMode::RulesOnly {
+                self.publish_rule_stats(collect_rule_stats(&rule_tags, &tagged_tokens, None), sink);
+            }
+            let mut entities = tokens_to_spans(&tagged_tokens, text);
+            if options.explain {
+                explain_spans(&mut entities, &tagged_tokens, &rule_tags, None);
+            }
+            self.calibrate_entities(mode, &mut entities);
+            self.send_done(sink, entities, tagged_tokens, &options, None, start);
             return;
         }
 
         // === Passo 4: Viterbi (CRF) — pula se RulesOnly ===
-        let viterbi_result = viterbi_decode(&self.model.crf, &feature_vectors);
-
-        for (i, step) in viterbi_result.steps.iter().enumerate() {
-            let _ = tx.send(PipelineEvent::ViterbiStep {
-                step: step.clone(),
-                token_text: tokens[i].text.clone(),
-            });
-        }
+        // Sem ouvinte, a tabela de visualização não é montada
+        let best_sequence = if sink.listening() {
+            let viterbi_result = self.decoder.decode(&self.model.crf, &feature_vectors);
+            send_viterbi_events(text, tokens, &viterbi_result, options.viterbi_detail, sink);
+            viterbi_result.best_sequence
+        } else {
+            VITERBI_SCRATCH.with(|scratch| {
+                self.decoder.best_path(&self.model.crf, &feature_vectors, &mut scratch.borrow_mut()).0
+            })
+        };
 
         // === Passo 5: Fusão de Resultados ===
-        // No modo Hybrid: Regras prevalecem; no CrfOnly: apenas CRF
-        let tag_probs: Vec<Vec<f64>> = viterbi_result.steps.iter().map(|step| {
-            let scores: Vec<f64> = step.scores.iter().map(|s| s.score).collect();
-            crate::viterbi::scores_to_probs(&scores)
-        }).collect();
+        // No modo Hybrid: conflitos resolvidos por `self.fusion`; no CrfOnly: apenas CRF.
+        // A confiança do CRF é a posterior de cada tag (forward-backward)
+        let tag_probs = marginals(&self.model.crf, &feature_vectors);
 
-        let tagged_tokens: Vec<TaggedToken> = tokens
-            .iter()
-            .enumerate()
-            .map(|(i, token)| {
-                let crf_tag = viterbi_result
-                    .best_sequence
+        let categories = self.model.crf.categories();
+        let model_tags: Vec<(Tag, f64)> = (0..tokens.len())
+            .map(|i| {
+                let crf_tag = best_sequence
                     .get(i)
                     .cloned()
                     .unwrap_or(Tag::Outside);
                 let crf_confidence = tag_probs
                     .get(i)
-                    .and_then(|probs| probs.get(crf_tag.index()))
+                    .zip(categories.tag_index(&crf_tag))
+                    .and_then(|(probs, t)| probs.get(t))
                     .copied()
                     .unwrap_or(0.5);
+                (crf_tag, crf_confidence)
+            })
+            .collect();
 
-                // Modo Hybrid: regra vence se disponível; CrfOnly: ignora regras
-                if mode == AlgorithmMode::Hybrid {
-                    if let Some((rule_tag, rule_name, rule_conf)) = &rule_tags[i] {
-                        let _ = tx.send(PipelineEvent::TagAssigned {
-                            token_index: i,
-                            token_text: token.text.clone(),
-                            tag: rule_tag.label(),
-                            confidence: *rule_conf,
-                            source: rule_name.clone(),
-                        });
-                        return TaggedToken {
-                            token: token.clone(),
-                            tag: rule_tag.clone(),
-                            confidence: *rule_conf,
-                        };
-                    }
-                }
+        // Em CrfOnly as regras não rodam, então `rule_tags` está vazio e o CRF decide sozinho
+        let fused = fuse_rules_and_model(&rule_tags, &model_tags, &self.fusion);
+        for (token, decision) in tokens.iter().zip(&fused).filter(|(_, d)| d.repaired) {
+            send_warning(
+                sink,
+                WarningCode::BioRepaired,
+                format!("Tag I- órfã em \"{}\" reparada para {}", token.text, decision.tag.label()),
+                Some(token.start..token.end),
+            );
+        }
+
+        if options.feature_events == FeatureEvents::EntityTokens {
+            send_token_features(tokens, &feature_vectors, |i| fused[i].tag != Tag::Outside, sink);
+        }
 
-                let _ = tx.send(PipelineEvent::TagAssigned {
+        let tagged_tokens: Vec<TaggedToken> = tokens
+            .iter()
+            .zip(&fused)
+            .enumerate()
+            .map(|(i, (token, decision))| {
+                sink.progress(|| PipelineEvent::TagAssigned {
                     token_index: i,
                     token_text: token.text.clone(),
-                    tag: crf_tag.label(),
-                    confidence: crf_confidence,
-                    source: "crf".to_string(),
+                    tag: decision.tag.label(),
+                    confidence: decision.confidence,
+                    source: decision.source(),
                 });
                 TaggedToken {
                     token: token.clone(),
-                    tag: crf_tag,
-                    confidence: crf_confidence,
+                    tag: decision.tag.clone(),
+                    confidence: decision.confidence,
                 }
             })
             .collect();
 
+        #[cfg(feature = "rules")]
+        if mode == AlgorithmMode::Hybrid {
+            let stats = collect_rule_stats(&rule_tags, &tagged_tokens, Some(&best_sequence));
+            self.publish_rule_stats(stats, sink);
+        }
+
         // === Passo 6: Agrupamento de Entidades ===
+        // A origem do trecho é a do seu primeiro token (regra, "crf" e a política, se houve conflito)
         let mut entities = tokens_to_spans(&tagged_tokens, text);
         for span in &mut entities {
-            if mode == AlgorithmMode::Hybrid {
-                if let Some(Some((_, rule_name, _))) = rule_tags.get(span.start_token) {
-                    span.source = rule_name.clone();
-                }
+            if let Some(decision) = fused.get(span.start_token) {
+                span.source = decision.source();
             }
         }
+        if options.explain {
+            let crf = CrfEvidence {
+                model: &self.model.crf,
+                features: &feature_vectors,
+                model_tags: &model_tags,
+            };
+            explain_spans(&mut entities, &tagged_tokens, &rule_tags, Some(crf));
+        }
+        self.calibrate_entities(mode, &mut entities);
 
-        let elapsed = start.elapsed().as_millis() as u64;
-
-        let _ = tx.send(PipelineEvent::Done {
-            entities: entities.clone(),
-            tagged_tokens: tagged_tokens.clone(),
-            total_tokens: tokens.len(),
-            processing_ms: elapsed,
-        });
+        let margins: Vec<f64> = model_tags
+            .iter()
+            .zip(&tag_probs)
+            .map(|((tag, _), probs)| categories.tag_index(tag).map_or(0.0, |t| tag_margin(probs, t)))
+            .collect();
+        self.send_done(sink, entities, tagged_tokens, &options, Some(&margins), start);
     }
 
-    fn analyze_streaming_ml(&self, text: &str, tokens: &[Token], mode: AlgorithmMode, tx: &mpsc::Sender<PipelineEvent>, start: std::time::Instant) {
-        // Envia features se for MaxEnt ou Perceptron
-        if mode == AlgorithmMode::MaxEnt || mode == AlgorithmMode::Perceptron {
-             let gazetteers = self.model.gazetteers();
-             let feature_vectors = extract_features(tokens, &gazetteers);
-             for (i, fv) in feature_vectors.iter().enumerate() {
-                // Top features logic clone from standard
-                let mut sorted: Vec<(String, f64)> = fv.features.iter().map(|(k, v)| (k.clone(), *v)).collect();
-                sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                sorted.truncate(10);
-                let _ = tx.send(PipelineEvent::FeaturesComputed {
-                    token_index: i,
-                    token_text: tokens[i].text.clone(),
-                    top_features: sorted,
-                });
-            }
-        }
+    fn analyze_streaming_tagger(&self, text: &str, tokens: &[Token], mode: AlgorithmMode, options: &AnalysisOptions, sink: &EventSink, start: std::time::Instant) {
+        let tagger = self.sequence_tagger(mode).expect("modo sem backend de tags");
+        // Envia features se o backend tiver extrator (MaxEnt, Perceptron), com o extrator do próprio modelo
+        let feature_vectors = tagger.extractor().map(|extractor| {
+            let lookups = self.gazetteer_lookups(tokens);
+            self.compute_features(tokens, extractor, &lookups, options.feature_events, sink)
+        });
 
         let token_strs: Vec<String> = tokens.iter().map(|t| t.text.clone()).collect();
-        let pred_tags = match mode {
-            AlgorithmMode::Hmm => self.model.hmm.predict(&token_strs),
-            AlgorithmMode::MaxEnt => self.model.maxent.predict(&token_strs),
-            AlgorithmMode::Perceptron => self.model.perceptron.predict(&token_strs),
-            _ => unreachable!(),
-        };
+        let pred_tags = tagger.predict_with_confidence(&token_strs);
+        if let Some(feature_vectors) = feature_vectors.filter(|_| options.feature_events == FeatureEvents::EntityTokens) {
+            send_token_features(tokens, &feature_vectors, |i| pred_tags.get(i).is_some_and(|(tag, _)| tag != "O"), sink);
+        }
 
-        let tagged_tokens: Vec<TaggedToken> = tokens.iter().zip(pred_tags.iter()).enumerate().map(|(i, (token, tag_str))| {
+        let tagged_tokens: Vec<TaggedToken> = tokens.iter().zip(pred_tags.iter()).enumerate().map(|(i, (token, (tag_str, confidence)))| {
             let tag = Tag::from_label(tag_str).unwrap_or(Tag::Outside);
-            let _ = tx.send(PipelineEvent::TagAssigned {
+            sink.progress(|| PipelineEvent::TagAssigned {
                 token_index: i,
                 token_text: token.text.clone(),
                 tag: tag.label(),
-                confidence: 1.0, 
-                source: format!("{:?}", mode).to_lowercase(),
+                confidence: *confidence,
+                source: tagger.name().to_string(),
             });
-            TaggedToken { token: token.clone(), tag, confidence: 1.0 }
+            TaggedToken { token: token.clone(), tag, confidence: *confidence }
         }).collect();
 
-        let entities = tokens_to_spans(&tagged_tokens, text);
-        let _ = tx.send(PipelineEvent::Done {
-            entities,
-            tagged_tokens,
-            total_tokens: tokens.len(),
-            processing_ms: start.elapsed().as_millis() as u64,
-        });
+        let mut entities = tokens_to_spans(&tagged_tokens, text);
+        for entity in &mut entities {
+            entity.source = tagger.name().to_string();
+        }
+        self.calibrate_entities(mode, &mut entities);
+        self.send_done(sink, entities, tagged_tokens, options, None, start);
     }
 
-    fn analyze_streaming_span(&self, text: &str, tokens: &[Token], tx: &mpsc::Sender<PipelineEvent>, start: std::time::Instant) {
+    fn analyze_streaming_span(&self, text: &str, tokens: &[Token], predictor: &dyn SpanPredictor, options: &AnalysisOptions, sink: &EventSink, start: std::time::Instant) {
         let token_strs: Vec<String> = tokens.iter().map(|t| t.text.clone()).collect();
-        let spans = self.model.span.predict(&token_strs);
+        let spans = predictor.predict_with_confidence(&token_strs);
 
         // Dummy tagged tokens (converte spans de volta para BIO para visualização seria ideal, mas complexo com overlaps)
         // Para simplificar, gera tudo como O, exceto se eu quiser reconstruir BIO sem overlap.
@@ -451,7 +1749,7 @@ impl NerPipeline {
 
         // Tenta marcar BIO para o primeiro layer de spans
         let mut occupied = vec![false; tokens.len()];
-        for span in &spans {
+        for (span, confidence) in &spans {
             // Ignora spans que colidem
              let range = span.start..span.end;
              if range.clone().any(|i| i < occupied.len() && occupied[i]) {
@@ -461,10 +1759,12 @@ impl NerPipeline {
              if let Some(cat) = crate::tagger::EntityCategory::from_str(&span.label) {
                  if span.start < tagged_tokens.len() {
                     tagged_tokens[span.start].tag = Tag::Begin(cat);
+                    tagged_tokens[span.start].confidence = *confidence;
                     occupied[span.start] = true;
                     for i in (span.start + 1)..span.end {
                         if i < tagged_tokens.len() {
                             tagged_tokens[i].tag = Tag::Inside(cat);
+                            tagged_tokens[i].confidence = *confidence;
                             occupied[i] = true;
                         }
                     }
@@ -474,17 +1774,17 @@ impl NerPipeline {
 
         // For Done event, TagAssigned events
         for (i, tt) in tagged_tokens.iter().enumerate() {
-             let _ = tx.send(PipelineEvent::TagAssigned {
+             sink.progress(|| PipelineEvent::TagAssigned {
                 token_index: i,
                 token_text: tt.token.text.clone(),
                 tag: tt.tag.label(),
-                confidence: 1.0, 
-                source: "span_based".to_string(),
+                confidence: tt.confidence,
+                source: predictor.name().to_string(),
             });
         }
 
         let mut entities_vec = Vec::new();
-        for span in spans {
+        for (span, confidence) in spans {
              if span.start < tokens.len() && span.end <= tokens.len() {
                 let start_char = tokens[span.start].start;
                 let end_char = tokens[span.end - 1].end;
@@ -492,76 +1792,1631 @@ impl NerPipeline {
                 let cat = crate::tagger::EntityCategory::from_str(&span.label).unwrap_or(crate::tagger::EntityCategory::Misc);
                 
                 entities_vec.push(EntitySpan {
-                    text: text[start_char..end_char].to_string(),
+                    text: slice_lossy(text, start_char, end_char).to_string(),
                     category: cat,
                     start_token: span.start,
                     end_token: span.end - 1,
                     start: start_char,
                     end: end_char,
-                    confidence: 1.0,
-                    source: "span_model".to_string(),
+                    confidence,
+                    source: predictor.name().to_string(),
+                    score_breakdown: None,
                 });
             }
         }
 
-        let _ = tx.send(PipelineEvent::Done {
-            entities: entities_vec,
+        self.calibrate_entities(AlgorithmMode::SpanBased, &mut entities_vec);
+        self.send_done(sink, entities_vec, tagged_tokens, options, None, start);
+    }
+
+    /// Extrai as features de cada token e emite as 10 de maior peso como `FeaturesComputed`,
+    /// segundo a política `emission`. Com `EntityTokens`, nada é emitido aqui: quem decodifica
+    /// chama [`send_token_features`] com os tokens que ficaram em entidades.
+    fn compute_features(
+        &self,
+        tokens: &[Token],
+        extractor: &dyn FeatureExtractor,
+        lookups: &GazetteerLookups,
+        emission: FeatureEvents,
+        sink: &EventSink,
+    ) -> Vec<FeatureVector> {
+        let feature_vectors = extractor.extract_with_lookups(tokens, self.model.gazetteers_ref(), lookups);
+        if !sink.listening() {
+            return feature_vectors;
+        }
+        match emission {
+            FeatureEvents::All => send_token_features(tokens, &feature_vectors, |_| true, sink),
+            FeatureEvents::EveryNth(n) => send_token_features(tokens, &feature_vectors, |i| i.is_multiple_of(n.max(1)), sink),
+            FeatureEvents::EntityTokens => {}
+            FeatureEvents::PerSentence => {
+                for range in sentence_ranges(tokens).into_iter().filter(|r| !r.is_empty()) {
+                    let mut totals: HashMap<&str, f64> = HashMap::new();
+                    for fv in &feature_vectors[range.clone()] {
+                        for (name, value) in &fv.features {
+                            *totals.entry(name.as_str()).or_insert(0.0) += value;
+                        }
+                    }
+                    sink.progress(|| PipelineEvent::SentenceFeatures {
+                        start_token: range.start,
+                        end_token: range.end,
+                        top_features: top_features(totals.into_iter().map(|(k, v)| (k.to_string(), v))),
+                    });
+                }
+            }
+        }
+        feature_vectors
+    }
+
+    /// Acumula as estatísticas da análise e as emite como evento.
+    #[cfg(feature = "rules")]
+    fn publish_rule_stats(&self, stats: RuleStats, sink: &EventSink) {
+        self.rule_stats.lock().unwrap_or_else(|e| e.into_inner()).merge(&stats);
+        sink.progress(|| PipelineEvent::RuleStatsComputed { stats });
+    }
+
+    /// Leva a confiança das entidades de `mode` à escala calibrada
+    /// ([`NerModel::calibration`]); `score_breakdown.calibration` guarda o ajuste.
+    fn calibrate_entities(&self, mode: AlgorithmMode, entities: &mut [EntitySpan]) {
+        let Some(scaling) = self.model.calibration.get(mode) else {
+            return;
+        };
+        for entity in entities {
+            let calibrated = scaling.apply(entity.confidence);
+            if let Some(breakdown) = entity.score_breakdown.as_mut() {
+                breakdown.calibration = calibrated - entity.confidence;
+            }
+            entity.confidence = calibrated;
+        }
+    }
+
+    /// Ponto único de emissão do evento `Done`: pontua as sentenças (aplicando a
+    /// abstenção pedida em `options`), descarta entidades abaixo do limiar da sua
+    /// categoria (`model.thresholds`), avisa sobre entidades suspeitas e ordena as
+    /// entidades conforme `entity_order`, para que todos os modos produzam a mesma ordem.
+    ///
+    /// `margins` traz a margem da tag escolhida em cada token, quando o Viterbi rodou.
+    fn send_done(
+        &self,
+        sink: &EventSink,
+        mut entities: Vec<EntitySpan>,
+        tagged_tokens: Vec<TaggedToken>,
+        options: &AnalysisOptions,
+        margins: Option<&[f64]>,
+        start: std::time::Instant,
+    ) {
+        if !tagged_tokens.is_empty() {
+            let sentences = score_sentences(&tagged_tokens, margins, options.abstain_below);
+            entities.retain(|entity| {
+                !sentences
+                    .iter()
+                    .any(|s| s.needs_review && (s.start_token..s.end_token).contains(&entity.start_token))
+            });
+            sink.send(PipelineEvent::SentencesScored { sentences });
+        }
+        entities.retain(|entity| self.model.thresholds.keeps(entity));
+        sort_entities(&mut entities, self.entity_order);
+        warn_suspicious_entities(&entities, self.model.gazetteers_ref(), sink);
+        sink.send(PipelineEvent::Done {
+            entities,
+            total_tokens: tagged_tokens.len(),
             tagged_tokens,
-            total_tokens: tokens.len(),
             processing_ms: start.elapsed().as_millis() as u64,
         });
     }
 }
 
-impl Default for NerPipeline {
-    fn default() -> Self {
-        Self::new()
+/// Para onde vão os eventos de uma análise.
+///
+/// No streaming todos os eventos vão para o callback (o canal de
+/// [`NerPipeline::analyze_streaming`] é só um callback que envia). Na análise síncrona
+/// ([`NerPipeline::analyze_report`] e derivados) ninguém os escuta: os eventos de
+/// progresso (tokens, features, regras, Viterbi, tags) nem chegam a ser construídos, e só
+/// o que compõe o [`AnalysisReport`] (avisos, sentenças e o resultado final) é guardado.
+enum EventSink<'a> {
+    Callback(RefCell<&'a mut dyn FnMut(PipelineEvent)>),
+    Report(RefCell<AnalysisReport>),
+}
+
+impl EventSink<'_> {
+    /// Há alguém ouvindo os eventos de progresso?
+    fn listening(&self) -> bool {
+        matches!(self, Self::Callback(_))
+    }
+
+    /// Evento de progresso, construído só se houver quem o escute.
+    fn progress(&self, event: impl FnOnce() -> PipelineEvent) {
+        if let Self::Callback(on_event) = self {
+            (on_event.borrow_mut())(event());
+        }
+    }
+
+    /// Evento que faz parte do resultado.
+    fn send(&self, event: PipelineEvent) {
+        match self {
+            Self::Callback(on_event) => (on_event.borrow_mut())(event),
+            Self::Report(report) => {
+                let mut report = report.borrow_mut();
+                match event {
+                    PipelineEvent::SentencesScored { sentences } => report.sentences = sentences,
+                    PipelineEvent::ExpressionsFound { expressions } => report.expressions = expressions,
+                    PipelineEvent::Warning { code, message, span } => {
+                        report.warnings.push(AnalysisWarning { code, message, span });
+                    }
+                    PipelineEvent::Done { tagged_tokens, entities, .. } => {
+                        report.tagged_tokens = tagged_tokens;
+                        report.entities = entities;
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn send_warning(sink: &EventSink, code: WarningCode, message: String, span: Option<Range<usize>>) {
+    sink.send(PipelineEvent::Warning { code, message, span });
+}
 
-    #[test]
-    fn test_pipeline_basic() {
-        let pipeline = NerPipeline::new();
-        let (tagged, entities) = pipeline.analyze(
-            "Lula foi eleito presidente do Brasil em 2002 com apoio da Petrobras.",
-        );
-        assert!(!tagged.is_empty());
-        // Deve encontrar pelo menos uma entidade
-        assert!(!entities.is_empty());
+/// Avisos [`WarningCode::LongEntity`] e [`WarningCode::GazetteerConflict`].
+fn warn_suspicious_entities(entities: &[EntitySpan], gazetteers: &Gazetteers, sink: &EventSink) {
+    for entity in entities {
+        let span = Some(entity.start..entity.end);
+        let n_tokens = entity.end_token + 1 - entity.start_token;
+        if n_tokens > LONG_ENTITY_TOKENS {
+            send_warning(
+                sink,
+                WarningCode::LongEntity,
+                format!("Entidade {} \"{}\" tem {n_tokens} tokens", entity.category.name(), entity.text),
+                span.clone(),
+            );
+        }
+        if gazetteers.contains_phrase(entity.category, &entity.text) {
+            continue;
+        }
+        let others: Vec<&str> = EntityCategory::all()
+            .into_iter()
+            .filter(|&c| c != entity.category && gazetteers.contains_phrase(c, &entity.text))
+            .map(|c| c.name())
+            .collect();
+        if !others.is_empty() {
+            send_warning(
+                sink,
+                WarningCode::GazetteerConflict,
+                format!(
+                    "\"{}\" foi marcada como {}, mas consta no gazetteer de {}",
+                    entity.text,
+                    entity.category.name(),
+                    others.join(", ")
+                ),
+                span,
+            );
+        }
     }
+}
 
-    #[test]
-    fn test_pipeline_empty() {
-        let pipeline = NerPipeline::new();
-        let (tagged, entities) = pipeline.analyze("");
-        assert!(tagged.is_empty());
-        assert!(entities.is_empty());
+/// Diferença entre a probabilidade da tag `chosen` e a maior probabilidade entre as demais.
+fn tag_margin(probs: &[f64], chosen: usize) -> f64 {
+    let Some(&p) = probs.get(chosen) else {
+        return 0.0;
+    };
+    let runner_up = probs
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != chosen)
+        .map(|(_, &q)| q)
+        .fold(0.0, f64::max);
+    p - runner_up
+}
+
+/// As 10 features de maior valor, em ordem decrescente (empates pelo nome).
+fn top_features(features: impl IntoIterator<Item = (String, f64)>) -> Vec<(String, f64)> {
+    let mut sorted: Vec<(String, f64)> = features.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+    sorted.truncate(10);
+    sorted
+}
+
+/// Emite `FeaturesComputed` para os tokens de índice aceito por `keep`.
+fn send_token_features(tokens: &[Token], feature_vectors: &[FeatureVector], keep: impl Fn(usize) -> bool, sink: &EventSink) {
+    for (i, fv) in feature_vectors.iter().enumerate().filter(|(i, _)| keep(*i)) {
+        sink.progress(|| PipelineEvent::FeaturesComputed {
+            token_index: i,
+            token_text: tokens[i].text.clone(),
+            top_features: top_features(fv.features.iter().map(|(k, v)| (k.clone(), *v))),
+        });
     }
+}
 
-    #[test]
-    fn test_pipeline_events_streaming() {
-        let pipeline = NerPipeline::new();
-        let (tx, rx) = mpsc::channel();
-        pipeline.analyze_streaming("São Paulo é a maior cidade do Brasil.", AlgorithmMode::Hybrid, TokenizerMode::Standard, tx);
+/// Pontua cada sentença pelas confianças finais dos tokens (e margens do Viterbi, se houver).
+fn score_sentences(
+    tagged_tokens: &[TaggedToken],
+    margins: Option<&[f64]>,
+    abstain_below: Option<f64>,
+) -> Vec<SentenceConfidence> {
+    let tokens: Vec<Token> = tagged_tokens.iter().map(|t| t.token.clone()).collect();
+    sentence_ranges(&tokens)
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let len = range.len() as f64;
+            let log_sum: f64 = tagged_tokens[range.clone()].iter().map(|t| t.confidence.ln()).sum();
+            let confidence = (log_sum / len).exp();
+            let mean_margin = margins
+                .and_then(|m| m.get(range.clone()))
+                .map(|m| m.iter().sum::<f64>() / len);
+            SentenceConfidence {
+                start_token: range.start,
+                end_token: range.end,
+                confidence,
+                mean_margin,
+                needs_review: abstain_below.is_some_and(|threshold| confidence < threshold),
+            }
+        })
+        .collect()
+}
 
-        let events: Vec<PipelineEvent> = rx.try_iter().collect();
-        assert!(!events.is_empty());
+thread_local! {
+    /// Buffers do Viterbi reaproveitados entre as análises da mesma thread (o pipeline é
+    /// compartilhado entre threads; cada uma decodifica no seu). Crescem até o maior
+    /// documento que a thread já decodificou.
+    static VITERBI_SCRATCH: RefCell<ViterbiScratch> = RefCell::new(ViterbiScratch::new());
+}
 
-        // Deve ter TokenizationDone como primeiro evento
-        assert!(
-            matches!(&events[0], PipelineEvent::TokenizationDone { .. }),
-            "Primeiro evento deve ser TokenizationDone"
-        );
+/// Emite os eventos do Viterbi no nível de detalhe pedido pelo cliente.
+fn send_viterbi_events(
+    text: &str,
+    tokens: &[Token],
+    result: &crate::viterbi::ViterbiResult,
+    detail: ViterbiDetail,
+    sink: &EventSink,
+) {
+    match detail {
+        ViterbiDetail::Full | ViterbiDetail::Compact => {
+            for (i, step) in result.steps.iter().enumerate() {
+                let step = match detail {
+                    ViterbiDetail::Compact => step.top_k(COMPACT_TOP_K),
+                    _ => step.clone(),
+                };
+                sink.progress(|| PipelineEvent::ViterbiStep {
+                    step,
+                    token_text: tokens[i].text.clone(),
+                });
+            }
+        }
+        ViterbiDetail::Summary => {
+            for summary in summarize_sentences(result, &sentence_ranges(tokens)) {
+                let sentence_text = slice_lossy(
+                    text,
+                    tokens[summary.start_token].start,
+                    tokens[summary.end_token - 1].end,
+                )
+                .to_string();
+                sink.progress(|| PipelineEvent::ViterbiSummary { summary, sentence_text });
+            }
+        }
+    }
+}
 
-        // Deve ter Done como último evento
-        let last = events.last().unwrap();
-        assert!(
-            matches!(last, PipelineEvent::Done { .. }),
+/// Decisão da fusão regra × CRF para um token.
+struct FusedTag {
+    tag: Tag,
+    confidence: f64,
+    /// Nome da regra vencedora ou `"crf"`.
+    origin: String,
+    /// Política aplicada, quando regra e CRF discordaram.
+    policy: Option<FusionPolicy>,
+    /// Um `I-X` órfão que o reparo BIO converteu em `B-X`.
+    repaired: bool,
+}
+
+impl FusedTag {
+    /// Origem registrada em eventos e entidades: `"org_gazetteer"`, `"crf"` ou,
+    /// após um conflito, `"crf:model_priority"`.
+    fn source(&self) -> String {
+        match self.policy {
+            Some(policy) => format!("{}:{}", self.origin, policy.name()),
+            None => self.origin.clone(),
+        }
+    }
+}
+
+/// Combina as tags das regras com as do CRF, trecho a trecho.
+///
+/// Um trecho de regra é um `B-X` (ou `I-X`) seguido de `I-X` consecutivos. Se o CRF
+/// concorda em todos os tokens, a regra é mantida; senão, a política da categoria
+/// decide quem vence o trecho inteiro. No fim, um `I-X` que ficou órfão na fronteira
+/// de um trecho decidido vira `B-X`, para não ser descartado ao agrupar entidades.
+fn fuse_rules_and_model(
+    rule_tags: &[Option<(Tag, String, f64)>],
+    model_tags: &[(Tag, f64)],
+    config: &FusionConfig,
+) -> Vec<FusedTag> {
+    let n = model_tags.len();
+    let mut fused: Vec<FusedTag> = model_tags
+        .iter()
+        .map(|(tag, confidence)| FusedTag {
+            tag: tag.clone(),
+            confidence: *confidence,
+            origin: "crf".to_string(),
+            policy: None,
+            repaired: false,
+        })
+        .collect();
+
+    let mut i = 0;
+    while i < n {
+        let Some(category) = rule_tags[i].as_ref().and_then(|(tag, _, _)| tag.category()) else {
+            i += 1;
+            continue;
+        };
+        let mut end = i + 1;
+        while end < n && matches!(&rule_tags[end], Some((Tag::Inside(c), _, _)) if *c == category) {
+            end += 1;
+        }
+
+        let rules: Vec<&(Tag, String, f64)> = rule_tags[i..end].iter().flatten().collect();
+        let agree = rules.iter().zip(&model_tags[i..end]).all(|(rule, (tag, _))| rule.0 == *tag);
+        let policy = config.policy_for(category);
+        let rule_wins = agree
+            || match policy {
+                FusionPolicy::RulePriority => true,
+                FusionPolicy::ModelPriority => false,
+                FusionPolicy::WeightedByConfidence => {
+                    let len = (end - i) as f64;
+                    let rule_confidence = rules.iter().map(|r| r.2).sum::<f64>() / len;
+                    let model_confidence = model_tags[i..end].iter().map(|(_, c)| c).sum::<f64>() / len;
+                    rule_confidence >= model_confidence
+                }
+            };
+
+        let conflict_policy = (!agree).then_some(policy);
+        for (k, rule) in (i..end).zip(&rules) {
+            if rule_wins {
+                fused[k] = FusedTag {
+                    tag: rule.0.clone(),
+                    confidence: rule.2,
+                    origin: rule.1.clone(),
+                    policy: conflict_policy,
+                    repaired: false,
+                };
+            } else {
+                fused[k].policy = conflict_policy;
+            }
+        }
+        i = end;
+    }
+
+    // Reparo BIO nas fronteiras dos trechos de regra
+    for k in 0..n {
+        let touched = rule_tags[k].is_some() || (k > 0 && rule_tags[k - 1].is_some());
+        if let Tag::Inside(category) = fused[k].tag {
+            let continues = k > 0 && fused[k - 1].tag.category() == Some(category);
+            if touched && !continues {
+                fused[k].tag = Tag::Begin(category);
+                fused[k].repaired = true;
+            }
+        }
+    }
+    fused
+}
+
+/// O que o CRF produziu numa análise, para explicar os scores.
+struct CrfEvidence<'a> {
+    model: &'a CrfModel,
+    features: &'a [FeatureVector],
+    /// `(tag, probabilidade)` do CRF por token.
+    model_tags: &'a [(Tag, f64)],
+}
+
+/// Preenche `score_breakdown` de cada trecho (ver [`ScoreBreakdown`]).
+///
+/// `crf` é `None` quando o CRF não rodou. As contribuições usam as tags **finais** do trecho.
+fn explain_spans(
+    spans: &mut [EntitySpan],
+    tagged: &[TaggedToken],
+    rule_tags: &[Option<(Tag, String, f64)>],
+    crf: Option<CrfEvidence>,
+) {
+    let mean = |values: Vec<f64>| (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+
+    for span in spans {
+        let range = span.start_token..span.end_token + 1;
+        let rule_confidence = mean(
+            rule_tags[range.clone()].iter().flatten().map(|(_, _, conf)| *conf).collect(),
+        );
+        let (emission, transition, model_confidence) = match &crf {
+            Some(CrfEvidence { model, features, model_tags }) => {
+                let emission = range.clone().map(|i| model.emission_score(&features[i], &tagged[i].tag)).sum();
+                let transition = range
+                    .clone()
+                    .filter(|&i| i > 0)
+                    .map(|i| model.transition_score(&tagged[i - 1].tag, &tagged[i].tag))
+                    .sum();
+                let probs = mean(model_tags[range.clone()].iter().map(|(_, p)| *p).collect());
+                (Some(emission), Some(transition), probs)
+            }
+            None => (None, None, None),
+        };
+        span.score_breakdown = Some(ScoreBreakdown {
+            emission,
+            transition,
+            model_confidence,
+            rule_confidence,
+            calibration: 0.0,
+        });
+    }
+}
+
+/// Compara o que cada regra marcou com a tag final e com a predição do modelo.
+#[cfg(feature = "rules")]
+fn collect_rule_stats(
+    rule_tags: &[Option<(Tag, String, f64)>],
+    tagged_tokens: &[TaggedToken],
+    model_tags: Option<&[Tag]>,
+) -> RuleStats {
+    let mut stats = RuleStats { analyses: 1, ..RuleStats::default() };
+    for (i, rule) in rule_tags.iter().enumerate() {
+        if let Some((rule_tag, rule_name, _)) = rule {
+            let overridden = tagged_tokens.get(i).is_some_and(|t| t.tag != *rule_tag);
+            let disagreed = model_tags.and_then(|tags| tags.get(i)).is_some_and(|t| t != rule_tag);
+            stats.record(rule_name, overridden, disagreed);
+        }
+    }
+    stats
+}
+
+/// Valida os offsets de cada token contra o texto original.
+///
+/// Offsets inválidos não interrompem a análise (todo fatiamento usa [`slice_lossy`]),
+/// mas são reportados via `PipelineEvent::Error` para facilitar o diagnóstico do tokenizador.
+fn report_invalid_offsets(text: &str, tokens: &[Token], sink: &EventSink) {
+    for token in tokens {
+        if let Err(err) = slice_checked(text, token.start, token.end) {
+            sink.progress(|| PipelineEvent::Error {
+                message: format!("Token {} (\"{}\"): {}", token.index, token.text, err),
+            });
+        }
+    }
+}
+
+impl Default for NerPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::get_corpus;
+    use crate::tokenizer::tokenize_with_mode;
+
+    #[test]
+    fn test_pipeline_basic() {
+        let pipeline = NerPipeline::new();
+        let (tagged, entities) = pipeline.analyze(
+            "Lula foi eleito presidente do Brasil em 2002 com apoio da Petrobras.",
+        );
+        assert!(!tagged.is_empty());
+        // Deve encontrar pelo menos uma entidade
+        assert!(!entities.is_empty());
+    }
+
+    #[test]
+    fn test_report_includes_expressions() {
+        use crate::temporal::ExpressionKind;
+
+        let pipeline = NerPipeline::new();
+        let text = "Em 13 de maio de 1888, o Brasil gastou R$ 50 bilhões. A taxa subiu 10,5% às 14h30.";
+        for mode in [AlgorithmMode::RulesOnly, AlgorithmMode::CrfOnly] {
+            let report = pipeline.analyze_report(text, mode, TokenizerMode::Standard, AnalysisOptions::default());
+            let kinds: Vec<ExpressionKind> = report.expressions.iter().map(|e| e.kind).collect();
+            assert_eq!(
+                kinds,
+                [ExpressionKind::Date, ExpressionKind::Money, ExpressionKind::Percent, ExpressionKind::Time],
+                "{mode:?}"
+            );
+        }
+
+        // Entrada cortada: só as expressões do trecho analisado
+        let options = AnalysisOptions { max_tokens: Some(6), ..Default::default() };
+        let report = pipeline.analyze_report(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, options);
+        assert_eq!(report.expressions.len(), 1);
+        assert_eq!(report.expressions[0].value, "1888-05-13");
+
+        let mut events = Vec::new();
+        pipeline.analyze_with_callback(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, AnalysisOptions::default(), |e| {
+            events.push(e)
+        });
+        assert!(events.iter().any(|e| matches!(e, PipelineEvent::ExpressionsFound { expressions } if expressions.len() == 4)));
+    }
+
+    #[test]
+    fn test_pipeline_is_send_and_sync() {
+        // Verificado em tempo de compilação: um membro `!Sync` quebraria o build aqui
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<NerPipeline>();
+        assert_send_sync::<NerModel>();
+        assert_send_sync::<crate::shared::SharedPipeline>();
+    }
+
+    #[test]
+    fn test_pipeline_empty() {
+        let pipeline = NerPipeline::new();
+        let (tagged, entities) = pipeline.analyze("");
+        assert!(tagged.is_empty());
+        assert!(entities.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_events_streaming() {
+        let pipeline = NerPipeline::new();
+        let (tx, rx) = mpsc::channel();
+        pipeline.analyze_streaming("São Paulo é a maior cidade do Brasil.", AlgorithmMode::Hybrid, TokenizerMode::Standard, tx);
+
+        let events: Vec<PipelineEvent> = rx.try_iter().collect();
+        assert!(!events.is_empty());
+
+        // Deve ter TokenizationDone como primeiro evento
+        assert!(
+            matches!(&events[0], PipelineEvent::TokenizationDone { .. }),
+            "Primeiro evento deve ser TokenizationDone"
+        );
+
+        // Deve ter Done como último evento
+        let last = events.last().unwrap();
+        assert!(
+            matches!(last, PipelineEvent::Done { .. }),
             "Último evento deve ser Done"
         );
     }
+
+    #[test]
+    fn test_callback_receives_events_while_running() {
+        let pipeline = NerPipeline::new();
+        let (events_tx, events_rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel::<()>();
+
+        std::thread::scope(|scope| {
+            let pipeline = &pipeline;
+            scope.spawn(move || {
+                let mut first = true;
+                pipeline.analyze_with_callback("Lula visitou Brasília.", AlgorithmMode::Hybrid, TokenizerMode::Standard, AnalysisOptions::default(), |event| {
+                    events_tx.send(event).unwrap();
+                    // Segura a análise no primeiro evento até o teste conferi-lo
+                    if std::mem::take(&mut first) {
+                        resume_rx.recv().unwrap();
+                    }
+                });
+            });
+
+            // O primeiro evento chega com a análise ainda parada, antes de qualquer outro
+            let first = events_rx.recv().unwrap();
+            assert!(matches!(first, PipelineEvent::TokenizationDone { total: 4, .. }));
+            assert!(events_rx.try_recv().is_err());
+            resume_tx.send(()).unwrap();
+        });
+
+        let rest: Vec<PipelineEvent> = events_rx.try_iter().collect();
+        assert!(matches!(rest.last(), Some(PipelineEvent::Done { .. })));
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_gazetteer_hits_agree_between_rules_and_features() {
+        let pipeline = NerPipeline::new();
+        let gazetteers = pipeline.model.gazetteers();
+        // Mesma entrada em grafias diferentes: regras e features devem concordar
+        let tokens = tokenize_with_mode("BRASÍLIA brasília Brasília Curitiba Petrobras", TokenizerMode::Standard);
+        let rules = pipeline.model.rule_engine.apply(&tokens);
+        let features = crate::features::extract_features_with_template(&tokens, &gazetteers, &FeatureTemplate::default());
+        for (i, token) in tokens.iter().enumerate() {
+            let rule = rules[i].as_ref().map(|r| r.rule_name.as_str());
+            let fv = &features[i].features;
+            assert_eq!(
+                rule == Some("location_gazetteer"),
+                fv.contains_key("in_location_gazetteer"),
+                "divergência em {:?}",
+                token.text
+            );
+        }
+        assert!(features[4].features.contains_key("in_org_gazetteer"));
+        // Palavras de 3 letras de nomes compostos também são indexadas
+        assert!(gazetteers.locations.contains("rio"));
+        assert!(!gazetteers.locations.contains("de"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "rules", feature = "linking"))]
+    fn test_model_alias_table() {
+        let pipeline = NerPipeline::new();
+        let aliases = &pipeline.model.aliases;
+        // Derivados automaticamente dos textos de demonstração
+        assert_eq!(aliases.canonical("Fiocruz"), Some("Fundação Oswaldo Cruz"));
+        assert_eq!(aliases.canonical("STF"), Some("Supremo Tribunal Federal"));
+
+        // NED anota o nome canônico da menção
+        let (tagged, entities) = pipeline.analyze("O STF decidiu.");
+        let tokens: Vec<_> = tagged.into_iter().map(|t| t.token).collect();
+        let resolved = crate::ned::disambiguate_with_aliases(&tokens, &entities, aliases);
+        let stf = resolved.iter().find(|d| d.entity.text == "STF").expect("STF deve ser entidade");
+        assert_eq!(stf.canonical_name.as_deref(), Some("Supremo Tribunal Federal"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "rules", feature = "statistical"))]
+    fn test_entity_order_is_stable_across_modes() {
+        let text = "Lula visitou a Petrobras no Rio de Janeiro.";
+        let mut pipeline = NerPipeline::new();
+        for mode in [AlgorithmMode::Hybrid, AlgorithmMode::RulesOnly, AlgorithmMode::SpanBased] {
+            let (_, entities) = pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard);
+            assert!(entities.windows(2).all(|w| w[0].start <= w[1].start), "{:?} fora de ordem", mode);
+        }
+
+        pipeline.entity_order = EntityOrder::Confidence;
+        let (_, entities) = pipeline.analyze(text);
+        assert!(entities.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+    }
+
+    #[test]
+    #[cfg(all(feature = "rules", feature = "statistical"))]
+    fn test_analyze_multilabel() {
+        let pipeline = NerPipeline::new();
+        let text = "Lula visitou a Petrobras no Rio de Janeiro.";
+
+        // Modos BIO são planos: nenhum trecho se sobrepõe e há um rótulo por trecho
+        let flat = pipeline.analyze_multilabel(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        assert!(flat.iter().all(|s| s.labels.len() == 1));
+        assert!(flat.windows(2).all(|w| w[0].end <= w[1].start));
+
+        // SpanBased: scores em ordem decrescente e acima do mínimo
+        let spans = pipeline.analyze_multilabel(text, AlgorithmMode::SpanBased, TokenizerMode::Standard);
+        assert!(!spans.is_empty());
+        for span in &spans {
+            assert!(span.labels.windows(2).all(|w| w[0].score >= w[1].score));
+            assert!(span.labels.iter().all(|l| l.score >= MULTILABEL_MIN_SCORE));
+            assert_eq!(&text[span.start..span.end], span.text);
+        }
+    }
+
+    #[test]
+    fn test_fusion_policies() {
+        let org = EntityCategory::Org;
+        // "a Vale do Rio": regra marca "Vale do Rio" como ORG; o CRF discorda no trecho
+        let rule = |tag: Tag| Some((tag, "org_gazetteer".to_string(), 0.9));
+        let rule_tags = vec![None, rule(Tag::Begin(org)), rule(Tag::Inside(org)), rule(Tag::Inside(org))];
+        let model_tags = vec![
+            (Tag::Outside, 0.9),
+            (Tag::Outside, 0.95),
+            (Tag::Outside, 0.99),
+            (Tag::Begin(EntityCategory::Loc), 0.97),
+        ];
+        let tags = |config: &FusionConfig| -> Vec<(String, String)> {
+            fuse_rules_and_model(&rule_tags, &model_tags, config)
+                .iter()
+                .map(|f| (f.tag.label(), f.source()))
+                .collect()
+        };
+
+        let rule_first = tags(&FusionConfig::default());
+        assert_eq!(rule_first[1], ("B-ORG".to_string(), "org_gazetteer:rule_priority".to_string()));
+        assert_eq!(rule_first[3].0, "I-ORG");
+
+        let model_first = tags(&FusionConfig::default().with_category(org, FusionPolicy::ModelPriority));
+        assert_eq!(model_first[1], ("O".to_string(), "crf:model_priority".to_string()));
+        assert_eq!(model_first[3].0, "B-LOC");
+        // A política vale apenas para a categoria configurada
+        assert_eq!(tags(&FusionConfig::default().with_category(EntityCategory::Per, FusionPolicy::ModelPriority)), rule_first);
+
+        // Confiança média do CRF (0.97) supera a da regra (0.9)
+        let weighted = tags(&FusionConfig::new(FusionPolicy::WeightedByConfidence));
+        assert_eq!(weighted[1], ("O".to_string(), "crf:weighted_by_confidence".to_string()));
+
+        // Sem conflito, a regra é mantida sem registrar política
+        let agreeing = vec![(Tag::Outside, 0.9), (Tag::Begin(org), 0.5), (Tag::Inside(org), 0.5), (Tag::Inside(org), 0.5)];
+        let fused = fuse_rules_and_model(&rule_tags, &agreeing, &FusionConfig::new(FusionPolicy::ModelPriority));
+        assert_eq!(fused[1].source(), "org_gazetteer");
+    }
+
+    #[test]
+    fn test_fusion_repairs_orphan_inside_tags() {
+        let per = EntityCategory::Per;
+        // A regra (vencedora) cobre só o primeiro token; o CRF continua o nome com I-PER
+        let rule_tags = vec![Some((Tag::Begin(EntityCategory::Loc), "location_gazetteer".to_string(), 0.9)), None];
+        let model_tags = vec![(Tag::Begin(per), 0.6), (Tag::Inside(per), 0.6)];
+        let fused = fuse_rules_and_model(&rule_tags, &model_tags, &FusionConfig::default());
+        assert_eq!(fused[0].tag, Tag::Begin(EntityCategory::Loc));
+        assert_eq!(fused[1].tag, Tag::Begin(per));
+        assert!(!fused[0].repaired && fused[1].repaired);
+    }
+
+    #[test]
+    fn test_structured_warnings() {
+        let pipeline = NerPipeline::new();
+        let text = "O Brasil venceu. A Petrobras anunciou lucro.";
+        let options = AnalysisOptions { max_tokens: Some(4), ..Default::default() };
+        let report = pipeline.analyze_report(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, options);
+        assert_eq!(report.tagged_tokens.len(), 4);
+        let truncated = report.warnings.iter().find(|w| w.code == WarningCode::TruncatedInput).unwrap();
+        assert_eq!(truncated.span, Some(text.find(" A").unwrap() + 1..text.len()));
+
+        let mut gazetteers = Gazetteers::new();
+        gazetteers.locations.insert("paris".to_string());
+        let paris = EntitySpan {
+            text: "Paris".to_string(),
+            category: EntityCategory::Per,
+            start_token: 0,
+            end_token: 0,
+            start: 0,
+            end: 5,
+            confidence: 0.9,
+            source: "crf".to_string(),
+            score_breakdown: None,
+        };
+        let long = EntitySpan { category: EntityCategory::Loc, end_token: LONG_ENTITY_TOKENS, ..paris.clone() };
+        let mut events = Vec::new();
+        let mut collect = |event| events.push(event);
+        warn_suspicious_entities(&[paris, long], &gazetteers, &EventSink::Callback(RefCell::new(&mut collect)));
+        let codes: Vec<WarningCode> = events
+            .into_iter()
+            .filter_map(|e| match e {
+                PipelineEvent::Warning { code, span, .. } => {
+                    assert_eq!(span, Some(0..5));
+                    Some(code)
+                }
+                _ => None,
+            })
+            .collect();
+        // "Paris" como LOC está no gazetteer da própria categoria: só o tamanho é suspeito
+        assert_eq!(codes, vec![WarningCode::GazetteerConflict, WarningCode::LongEntity]);
+        let json = serde_json::to_value(WarningCode::GazetteerConflict).unwrap();
+        assert_eq!(json, "gazetteer_conflict");
+    }
+
+    #[test]
+    fn test_viterbi_detail_levels() {
+        let pipeline = NerPipeline::new();
+        let text = "O Brasil venceu. A Petrobras anunciou lucro.";
+        let run = |detail| {
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming_with_detail(text, AlgorithmMode::CrfOnly, TokenizerMode::Standard, detail, tx);
+            rx.try_iter().collect::<Vec<PipelineEvent>>()
+        };
+
+        let compact = run(ViterbiDetail::Compact);
+        let steps: Vec<&ViterbiStep> = compact
+            .iter()
+            .filter_map(|e| match e {
+                PipelineEvent::ViterbiStep { step, .. } => Some(step),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(steps.len(), 9);
+        assert!(steps.iter().all(|s| s.scores.len() == COMPACT_TOP_K));
+
+        let summary = run(ViterbiDetail::Summary);
+        assert!(!summary.iter().any(|e| matches!(e, PipelineEvent::ViterbiStep { .. })));
+        let sentences: Vec<&str> = summary
+            .iter()
+            .filter_map(|e| match e {
+                PipelineEvent::ViterbiSummary { sentence_text, .. } => Some(sentence_text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sentences, vec!["O Brasil venceu.", "A Petrobras anunciou lucro."]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "rules", feature = "statistical"))]
+    fn test_fallback_when_mode_unavailable() {
+        let mut pipeline = NerPipeline::new().with_fallback_chain(vec![
+            AlgorithmMode::CrfOnly,
+            AlgorithmMode::Perceptron,
+            AlgorithmMode::RulesOnly,
+        ]);
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::CrfOnly), AlgorithmMode::CrfOnly);
+
+        // Simula falha de carregamento do CRF e do Perceptron
+        pipeline.model.crf = crate::crf::CrfModel::new();
+        pipeline.model.perceptron = crate::perceptron::PerceptronModel::new().into();
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::CrfOnly), AlgorithmMode::RulesOnly);
+        // Fora da cadeia: começa do início
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::Hybrid), AlgorithmMode::RulesOnly);
+
+        let (tx, rx) = mpsc::channel();
+        pipeline.analyze_streaming("o Brasil venceu.", AlgorithmMode::CrfOnly, TokenizerMode::Standard, tx);
+        let events: Vec<PipelineEvent> = rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(e, PipelineEvent::Warning { message, .. } if message.contains("RulesOnly"))));
+        match events.last() {
+            Some(PipelineEvent::Done { entities, .. }) => assert_eq!(entities[0].text, "Brasil"),
+            other => panic!("esperado Done, obtido {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_rule_stats_per_analysis_and_cumulative() {
+        let pipeline = NerPipeline::new();
+        let (tx, rx) = mpsc::channel();
+        pipeline.analyze_streaming("O presidente Lula visitou o Brasil.", AlgorithmMode::Hybrid, TokenizerMode::Standard, tx);
+        let per_analysis = rx
+            .try_iter()
+            .find_map(|e| match e {
+                PipelineEvent::RuleStatsComputed { stats } => Some(stats),
+                _ => None,
+            })
+            .expect("Hybrid deve emitir RuleStatsComputed");
+        assert_eq!(per_analysis.analyses, 1);
+        assert!(per_analysis.total_fired() >= 2);
+
+        pipeline.analyze_with_mode("O presidente Lula visitou o Brasil.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        pipeline.analyze_with_mode("O presidente Lula visitou o Brasil.", AlgorithmMode::CrfOnly, TokenizerMode::Standard);
+        let total = pipeline.rule_stats();
+        assert_eq!(total.analyses, 2, "CrfOnly não aplica regras");
+        assert_eq!(total.total_fired(), 2 * per_analysis.total_fired());
+
+        pipeline.reset_rule_stats();
+        assert_eq!(pipeline.rule_stats(), RuleStats::new());
+    }
+
+    #[test]
+    fn test_entity_safe_aggressive_tokenization() {
+        let text = "a Consolidação das Leis do Trabalho entrou em vigor rapidamente";
+        let texts = |pipeline: &NerPipeline| -> Vec<String> {
+            pipeline.tokenize(text, TokenizerMode::Aggressive).0.into_iter().map(|t| t.text).collect()
+        };
+
+        let safe = texts(&NerPipeline::new());
+        assert!(safe.contains(&"Consolidação".to_string()));
+        // Palavras comuns continuam sendo divididas
+        assert!(safe.contains(&"rapida".to_string()) && safe.contains(&"mente".to_string()));
+
+        let raw = texts(&NerPipeline::new().with_entity_safe_aggressive(false));
+        assert!(raw.contains(&"Consolida".to_string()));
+        assert_eq!(raw, tokenize_with_mode(text, TokenizerMode::Aggressive).into_iter().map(|t| t.text).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_wrapped_entities_recovered_with_original_offsets() {
+        let text = "A presidente da Petro-\nbras visitou o Banco do\nBrasil em São\n  Paulo.";
+        let pipeline = NerPipeline::new();
+        let entities = pipeline.analyze_wrapped(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        let found: Vec<(&str, &str)> =
+            entities.iter().map(|e| (e.text.as_str(), &text[e.start..e.end])).collect();
+        assert!(found.contains(&("Petrobras", "Petro-\nbras")));
+        assert!(found.contains(&("Banco do Brasil", "Banco do\nBrasil")));
+        assert!(found.contains(&("São Paulo", "São\n  Paulo")));
+
+        // Sem o pré-processamento, "Petro-" e "bras" nunca formam a entidade
+        let (_, raw) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        assert!(raw.iter().all(|e| !e.text.contains("bras")));
+    }
+
+    #[test]
+    fn test_sync_analysis_matches_streaming_done() {
+        let pipeline = NerPipeline::new();
+        let text = "O presidente Lula visitou a Petrobras no Rio de Janeiro em 12 de maio às 14h30.";
+        for mode in [AlgorithmMode::Hybrid, AlgorithmMode::RulesOnly, AlgorithmMode::CrfOnly, AlgorithmMode::Perceptron] {
+            let report = pipeline.analyze_report(text, mode, TokenizerMode::Standard, AnalysisOptions::default());
+
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming(text, mode, TokenizerMode::Standard, tx);
+            let events: Vec<PipelineEvent> = rx.iter().collect();
+            assert!(events.iter().any(|e| matches!(e, PipelineEvent::TagAssigned { .. })));
+            let Some(PipelineEvent::Done { tagged_tokens, entities, .. }) = events.last() else {
+                panic!("o último evento deve ser Done");
+            };
+            let warnings = events.iter().filter(|e| matches!(e, PipelineEvent::Warning { .. })).count();
+
+            let tags = |tokens: &[TaggedToken]| tokens.iter().map(|t| (t.tag.clone(), t.confidence)).collect::<Vec<_>>();
+            assert_eq!(tags(&report.tagged_tokens), tags(tagged_tokens), "{mode:?}");
+            assert_eq!(
+                report.entities.iter().map(|e| (e.start, e.end, e.category, e.source.clone())).collect::<Vec<_>>(),
+                entities.iter().map(|e| (e.start, e.end, e.category, e.source.clone())).collect::<Vec<_>>()
+            );
+            assert_eq!(report.warnings.len(), warnings);
+        }
+    }
+
+    #[test]
+    fn test_analyze_batch_matches_sequential() {
+        let pipeline = NerPipeline::new();
+        let texts: Vec<String> = get_corpus().iter().take(12).map(|s| s.text.to_string()).collect();
+        let batch = pipeline.analyze_batch(&texts, AlgorithmMode::Hybrid, TokenizerMode::Standard);
+        assert_eq!(batch.len(), texts.len());
+        for (text, (tokens, entities)) in texts.iter().zip(&batch) {
+            let (expected_tokens, expected_entities) = pipeline.analyze_with_mode(text, AlgorithmMode::Hybrid, TokenizerMode::Standard);
+            assert_eq!(tokens.iter().map(|t| &t.tag).collect::<Vec<_>>(), expected_tokens.iter().map(|t| &t.tag).collect::<Vec<_>>());
+            assert_eq!(
+                entities.iter().map(|e| (e.start, e.end, e.category)).collect::<Vec<_>>(),
+                expected_entities.iter().map(|e| (e.start, e.end, e.category)).collect::<Vec<_>>()
+            );
+        }
+        assert!(pipeline.analyze_batch(&[], AlgorithmMode::Hybrid, TokenizerMode::Standard).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_brazilian_places_carry_subtype() {
+        use crate::brazil::LocSubtype;
+        let pipeline = NerPipeline::new();
+        let text = "Obras em Uberlândia (MG), perto de Goiânia e de Florianópolis.";
+        let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        let places: Vec<(&str, &str, LocSubtype)> = entities
+            .iter()
+            .filter_map(|e| e.place().map(|p| (e.text.as_str(), p.state.uf, p.subtype)))
+            .collect();
+        assert_eq!(
+            places,
+            [("MG", "MG", LocSubtype::UfCode), ("Goiânia", "GO", LocSubtype::Capital), ("Florianópolis", "SC", LocSubtype::Capital)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_analyze_document_uses_document_offsets() {
+        let pipeline = NerPipeline::new();
+        let text = "A Sra. Maria visitou a Petrobras.\n\nEm São Paulo, o Banco do Brasil abriu. Lula comentou!";
+        let (tokens, entities) = pipeline.analyze_document_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+
+        assert!(tokens.iter().enumerate().all(|(i, t)| t.token.index == i && text[t.token.start..t.token.end] == t.token.text));
+        for entity in &entities {
+            assert_eq!(&text[entity.start..entity.end], entity.text);
+            assert_eq!(tokens[entity.start_token].token.start, entity.start);
+            assert_eq!(tokens[entity.end_token].token.end, entity.end);
+        }
+        let found: Vec<&str> = entities.iter().map(|e| e.text.as_str()).collect();
+        for expected in ["Petrobras", "São Paulo", "Banco do Brasil"] {
+            assert!(found.contains(&expected), "{expected} em {found:?}");
+        }
+
+        // Mesmas entidades que analisar cada sentença isoladamente
+        let per_sentence: usize = crate::tokenizer::split_sentences(text)
+            .into_iter()
+            .map(|r| pipeline.analyze_with_mode(&text[r], AlgorithmMode::RulesOnly, TokenizerMode::Standard).1.len())
+            .sum();
+        assert_eq!(entities.len(), per_sentence);
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_sentence_confidence_and_abstention() {
+        let pipeline = NerPipeline::new();
+        let text = "O Brasil venceu. Xyzw qwrt plmk vbnx.";
+        let report = pipeline.analyze_report(text, AlgorithmMode::Hybrid, TokenizerMode::Standard, AnalysisOptions::default());
+        assert_eq!(report.sentences.len(), 2);
+        assert!(report.sentences.iter().all(|s| (0.0..=1.0).contains(&s.confidence) && s.mean_margin.is_some()));
+        assert!(!report.needs_review());
+        assert!(report.entities.iter().any(|e| e.text == "Brasil"));
+
+        // Limiar entre as duas sentenças: só a menos confiante é retida para revisão
+        let (first, second) = (report.sentences[0].confidence, report.sentences[1].confidence);
+        let threshold = (first + second) / 2.0;
+        let options = AnalysisOptions { abstain_below: Some(threshold), ..Default::default() };
+        let reviewed = pipeline.analyze_report(text, AlgorithmMode::Hybrid, TokenizerMode::Standard, options);
+        let flagged: Vec<bool> = reviewed.sentences.iter().map(|s| s.needs_review).collect();
+        assert_eq!(flagged, vec![first < threshold, second < threshold]);
+        assert_eq!(reviewed.tagged_tokens.len(), report.tagged_tokens.len());
+        assert_eq!(reviewed.entities.iter().any(|e| e.text == "Brasil"), !flagged[0]);
+
+        // RulesOnly não tem Viterbi: sem margem
+        let rules = pipeline.analyze_report(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, AnalysisOptions::default());
+        assert!(rules.sentences.iter().all(|s| s.mean_margin.is_none()));
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_category_thresholds_filter_output() {
+        let mut pipeline = NerPipeline::new();
+        let text = "A presidente Dilma Rousseff recebeu o ex-presidente Lula no Brasil.";
+        let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        let find = |name: &str| entities.iter().find(|e| e.text == name).unwrap().clone();
+        let (lula, brasil) = (find("Lula"), find("Brasil"));
+
+        // Só "Lula" é PER no ouro: o limiar de PER corta o que estiver abaixo dele
+        let dev = vec![(
+            text.to_string(),
+            vec![
+                GoldEntity::new(lula.start, lula.end, EntityCategory::Per),
+                GoldEntity::new(brasil.start, brasil.end, EntityCategory::Loc),
+            ],
+        )];
+        let thresholds = pipeline
+            .tune_thresholds(&dev, AlgorithmMode::RulesOnly, TokenizerMode::Standard, &ThresholdTuner::new())
+            .clone();
+        assert_eq!(thresholds.get(EntityCategory::Per), Some(lula.confidence));
+        assert_eq!(thresholds.get(EntityCategory::Loc), Some(brasil.confidence));
+
+        // Os limiares valem para as análises seguintes
+        pipeline.model.thresholds.set(EntityCategory::Per, lula.confidence + 0.01);
+        let (_, filtered) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        assert!(filtered.iter().all(|e| e.category != EntityCategory::Per || e.confidence > lula.confidence));
+        assert!(!filtered.iter().any(|e| e.text == "Lula"));
+        assert!(filtered.iter().any(|e| e.text == "Brasil"));
+
+        // Retunar ignora os limiares anteriores
+        pipeline.tune_thresholds(&dev, AlgorithmMode::RulesOnly, TokenizerMode::Standard, &ThresholdTuner::new());
+        assert_eq!(pipeline.model.thresholds, thresholds);
+    }
+
+    #[test]
+    fn test_user_backends_replace_builtin_models() {
+        use crate::backend::Span;
+
+        /// Toda palavra capitalizada é um LOC de um token.
+        struct Capitalized;
+
+        impl SequenceTagger for Capitalized {
+            fn name(&self) -> &str {
+                "capitalized"
+            }
+
+            fn predict(&self, tokens: &[String]) -> Vec<String> {
+                tokens
+                    .iter()
+                    .map(|t| if t.starts_with(char::is_uppercase) { "B-LOC" } else { "O" }.to_string())
+                    .collect()
+            }
+        }
+
+        /// Backend ainda sem treino: o pipeline deve seguir a cadeia de fallback.
+        struct Untrained;
+
+        impl SequenceTagger for Untrained {
+            fn name(&self) -> &str {
+                "untrained"
+            }
+
+            fn predict(&self, tokens: &[String]) -> Vec<String> {
+                vec!["O".to_string(); tokens.len()]
+            }
+
+            fn is_trained(&self) -> bool {
+                false
+            }
+        }
+
+        /// O texto inteiro como um único trecho MISC.
+        struct WholeText;
+
+        impl SpanPredictor for WholeText {
+            fn name(&self) -> &str {
+                "whole_text"
+            }
+
+            fn predict(&self, tokens: &[String]) -> Vec<Span> {
+                vec![Span { start: 0, end: tokens.len(), label: "MISC".to_string() }]
+            }
+        }
+
+        let pipeline = NerPipeline::new()
+            .with_sequence_tagger(AlgorithmMode::MaxEnt, Capitalized)
+            .with_sequence_tagger(AlgorithmMode::Hmm, Untrained)
+            .with_span_predictor(WholeText)
+            .with_fallback_chain(vec![AlgorithmMode::MaxEnt]);
+        let text = "Ana viu Recife";
+
+        let (tagged, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::MaxEnt, TokenizerMode::Standard);
+        assert_eq!(tagged.iter().map(|t| t.tag.label()).collect::<Vec<_>>(), vec!["B-LOC", "O", "B-LOC"]);
+        assert!(entities.iter().all(|e| e.source == "capitalized" && e.category == EntityCategory::Loc));
+
+        // Sem extrator, o backend próprio não emite FeaturesComputed
+        let (tx, rx) = mpsc::channel();
+        pipeline.analyze_streaming(text, AlgorithmMode::MaxEnt, TokenizerMode::Standard, tx);
+        assert!(!rx.try_iter().any(|e| matches!(e, PipelineEvent::FeaturesComputed { .. })));
+
+        assert!(!pipeline.is_available(AlgorithmMode::Hmm));
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::Hmm), AlgorithmMode::MaxEnt);
+
+        assert!(pipeline.is_available(AlgorithmMode::SpanBased));
+        let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::SpanBased, TokenizerMode::Standard);
+        assert_eq!(entities.len(), 1);
+        assert_eq!((entities[0].text.as_str(), entities[0].source.as_str()), (text, "whole_text"));
+
+        // Os modelos embutidos também são backends
+        #[cfg(feature = "statistical")]
+        {
+            let builtin = NerPipeline::new();
+            assert_eq!(builtin.sequence_tagger(AlgorithmMode::Perceptron).unwrap().name(), "perceptron");
+            assert!(builtin.sequence_tagger(AlgorithmMode::Perceptron).unwrap().extractor().is_some());
+            assert!(builtin.sequence_tagger(AlgorithmMode::Hmm).unwrap().extractor().is_none());
+            assert!(builtin.sequence_tagger(AlgorithmMode::CrfOnly).is_none());
+            assert_eq!(builtin.span_predictor().unwrap().name(), "span_model");
+        }
+    }
+
+    #[test]
+    fn test_degenerate_inputs_take_fast_path() {
+        let pipeline = NerPipeline::new();
+        for text in ["😀", "😀🎉 👍🏽", "a", "   \n\t ", "\u{0}\u{7}\u{1b}", "?!", "\u{200b}"] {
+            assert!(is_degenerate_input(text), "{text:?}");
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming(text, AlgorithmMode::Hybrid, TokenizerMode::Standard, tx);
+            let events: Vec<PipelineEvent> = rx.try_iter().collect();
+            assert_eq!(events.len(), 3, "{text:?}: {events:?}");
+            assert!(matches!(&events[0], PipelineEvent::TokenizationDone { tokens, total: 0 } if tokens.is_empty()));
+            assert!(matches!(
+                &events[1],
+                PipelineEvent::Warning { code: WarningCode::DegenerateInput, span: Some(span), .. } if *span == (0..text.len())
+            ));
+            assert!(matches!(
+                &events[2],
+                PipelineEvent::Done { entities, tagged_tokens, total_tokens: 0, .. } if entities.is_empty() && tagged_tokens.is_empty()
+            ));
+
+            let report = pipeline.analyze_report(text, AlgorithmMode::CrfOnly, TokenizerMode::Standard, AnalysisOptions::default());
+            assert!(report.entities.is_empty() && report.tagged_tokens.is_empty() && report.sentences.is_empty());
+            assert_eq!(report.warnings.len(), 1);
+        }
+
+        // Duas letras já passam pelos estágios
+        for text in ["Oi", "RJ", "😀 Lula"] {
+            assert!(!is_degenerate_input(text));
+        }
+        let (tagged, _) = pipeline.analyze_with_mode("Oi", AlgorithmMode::Hybrid, TokenizerMode::Standard);
+        assert_eq!(tagged.len(), 1);
+    }
+
+    #[test]
+    fn test_modes_skip_unneeded_stages() {
+        let pipeline = NerPipeline::new();
+        let text = "Lula visitou a Petrobras no Rio de Janeiro.";
+        let run = |mode| {
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming(text, mode, TokenizerMode::Standard, tx);
+            rx.try_iter().collect::<Vec<PipelineEvent>>()
+        };
+        let count = |events: &[PipelineEvent], pred: fn(&PipelineEvent) -> bool| events.iter().filter(|e| pred(e)).count();
+        let features = |e: &PipelineEvent| matches!(e, PipelineEvent::FeaturesComputed { .. });
+        let viterbi = |e: &PipelineEvent| matches!(e, PipelineEvent::ViterbiStep { .. });
+
+        let features_only = run(AlgorithmMode::FeaturesOnly);
+        assert!(count(&features_only, features) > 0);
+        assert_eq!(count(&features_only, viterbi), 0);
+
+        #[cfg(feature = "rules")]
+        {
+            let rules_only = run(AlgorithmMode::RulesOnly);
+            assert_eq!(count(&rules_only, features), 0);
+            assert_eq!(count(&rules_only, viterbi), 0);
+            assert!(rules_only.iter().any(|e| matches!(e, PipelineEvent::RuleApplied { .. })));
+            let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+            assert!(entities.iter().any(|e| e.text == "Petrobras"));
+        }
+    }
+
+    #[test]
+    fn test_feature_event_policies() {
+        let pipeline = NerPipeline::new();
+        let text = "Lula visitou a Petrobras. Depois voltou ao Rio de Janeiro com a comitiva.";
+        let run = |mode, feature_events| {
+            let (tx, rx) = mpsc::channel();
+            let options = AnalysisOptions { feature_events, ..Default::default() };
+            pipeline.analyze_streaming_with_options(text, mode, TokenizerMode::Standard, options, tx);
+            rx.try_iter().collect::<Vec<PipelineEvent>>()
+        };
+        let token_indices = |events: &[PipelineEvent]| -> Vec<usize> {
+            events
+                .iter()
+                .filter_map(|e| match e {
+                    PipelineEvent::FeaturesComputed { token_index, .. } => Some(*token_index),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let all = run(AlgorithmMode::CrfOnly, FeatureEvents::All);
+        let total = token_indices(&all).len();
+        assert!(total > 10);
+        assert_eq!(token_indices(&run(AlgorithmMode::CrfOnly, FeatureEvents::EveryNth(5))), (0..total).step_by(5).collect::<Vec<_>>());
+
+        // Só os tokens das entidades, antes das tags
+        let done = |events: &[PipelineEvent]| -> Vec<Tag> {
+            match events.last() {
+                Some(PipelineEvent::Done { tagged_tokens, .. }) => tagged_tokens.iter().map(|t| t.tag.clone()).collect(),
+                _ => panic!("o último evento deve ser Done"),
+            }
+        };
+        #[cfg(feature = "statistical")]
+        let modes = [AlgorithmMode::Hybrid, AlgorithmMode::Perceptron];
+        #[cfg(not(feature = "statistical"))]
+        let modes = [AlgorithmMode::Hybrid];
+        for mode in modes {
+            let events = run(mode, FeatureEvents::EntityTokens);
+            let in_entities: Vec<usize> = done(&events).iter().enumerate().filter(|(_, tag)| **tag != Tag::Outside).map(|(i, _)| i).collect();
+            assert!(!in_entities.is_empty());
+            assert_eq!(token_indices(&events), in_entities, "{mode:?}");
+            let first_tag = events.iter().position(|e| matches!(e, PipelineEvent::TagAssigned { .. })).unwrap();
+            assert!(events[..first_tag].iter().any(|e| matches!(e, PipelineEvent::FeaturesComputed { .. })));
+        }
+
+        // Um agregado por sentença, sem eventos por token
+        let per_sentence = run(AlgorithmMode::CrfOnly, FeatureEvents::PerSentence);
+        assert!(token_indices(&per_sentence).is_empty());
+        let sentences: Vec<_> = per_sentence
+            .iter()
+            .filter_map(|e| match e {
+                PipelineEvent::SentenceFeatures { start_token, end_token, top_features } => Some((*start_token, *end_token, top_features)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!((sentences[0].0, sentences[1].1), (0, total));
+        assert!(sentences.iter().all(|(_, _, top)| top.len() == 10 && top.windows(2).all(|w| w[0].1 >= w[1].1)));
+
+        // O resultado não muda com a política
+        assert_eq!(done(&per_sentence), done(&all));
+        let invalid = AnalysisOptions { feature_events: FeatureEvents::EveryNth(0), ..Default::default() };
+        assert_eq!(invalid.validate(), Err(InvalidOptions::ZeroFeatureStride));
+        let parsed: FeatureEvents = serde_json::from_str(r#"{"every_nth": 50}"#).unwrap();
+        assert_eq!(parsed, FeatureEvents::EveryNth(50));
+    }
+
+    #[test]
+    fn test_each_model_uses_its_own_feature_extractor() {
+        /// Emite uma única feature com o nome do modelo.
+        #[derive(Debug)]
+        struct Marker(&'static str);
+
+        impl FeatureExtractor for Marker {
+            fn extract(&self, tokens: &[Token], _gazetteers: &Gazetteers) -> Vec<FeatureVector> {
+                (0..tokens.len())
+                    .map(|i| {
+                        let mut fv = FeatureVector::new(i);
+                        fv.insert(format!("marker={}", self.0), 1.0);
+                        fv
+                    })
+                    .collect()
+            }
+        }
+
+        let mut pipeline = NerPipeline::new();
+        pipeline.model.crf.set_extractor(Marker("crf"));
+        #[cfg(feature = "statistical")]
+        pipeline.model.perceptron.set_extractor(Marker("perceptron"));
+
+        let top_features = |mode| -> Vec<String> {
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming("Lula visitou Recife.", mode, TokenizerMode::Standard, tx);
+            rx.try_iter()
+                .filter_map(|e| match e {
+                    PipelineEvent::FeaturesComputed { top_features, .. } => Some(top_features),
+                    _ => None,
+                })
+                .flatten()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        let crf = top_features(AlgorithmMode::CrfOnly);
+        assert_eq!(crf.len(), 4);
+        assert!(crf.iter().all(|f| f == "marker=crf"));
+        #[cfg(feature = "statistical")]
+        assert!(top_features(AlgorithmMode::Perceptron).iter().all(|f| f == "marker=perceptron"));
+
+        // O atalho do template troca o extrator do CRF
+        let wide = NerPipeline::new().with_feature_template(FeatureTemplate { window: 4, ..FeatureTemplate::default() });
+        assert!(format!("{:?}", wide.model.crf.extractor()).contains("window: 4"));
+    }
+
+    #[test]
+    #[cfg(feature = "statistical")]
+    fn test_memm_mode_decodes_valid_bio() {
+        let pipeline = NerPipeline::new();
+        assert!(pipeline.model.memm.is_memm() && !pipeline.model.maxent.is_memm());
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::Memm), AlgorithmMode::Memm);
+
+        let text = "O presidente Lula visitou a Petrobras no Rio de Janeiro em março.";
+        let (tagged, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::Memm, TokenizerMode::Standard);
+        assert!(Tag::is_valid_transition(&Tag::Outside, &tagged[0].tag));
+        assert!(tagged.windows(2).all(|w| Tag::is_valid_transition(&w[0].tag, &w[1].tag)));
+        assert!(entities.iter().all(|e| e.source == "memm"));
+        assert!(entities.iter().any(|e| e.text == "Rio de Janeiro" && e.category == EntityCategory::Loc), "{entities:?}");
+        assert_eq!(serde_json::to_string(&AlgorithmMode::Memm).unwrap(), "\"memm\"");
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_rule_groups_per_analysis() {
+        use crate::rule_based::RuleGroup;
+
+        let pipeline = NerPipeline::new();
+        let text = "O presidente Lula visitou Campinas, SP, às 14h30.";
+        let rules = |groups: RuleGroups| -> Vec<(String, String)> {
+            let options = AnalysisOptions { rule_groups: groups, ..Default::default() };
+            let mut found = Vec::new();
+            pipeline.analyze_with_callback(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, options, |e| {
+                if let PipelineEvent::RuleApplied { token_text, rule_name, .. } = e {
+                    found.push((token_text, rule_name));
+                }
+            });
+            found
+        };
+        let fired = |found: &[(String, String)], token: &str| found.iter().find(|(t, _)| t == token).map(|(_, r)| r.clone());
+
+        let all = rules(RuleGroups::default());
+        assert_eq!(fired(&all, "SP").as_deref(), Some("uf_code"));
+        assert_eq!(fired(&all, "14h30").as_deref(), Some("time_pattern"));
+        assert!(rules(RuleGroups::none()).is_empty());
+
+        let without = rules(RuleGroups::all().without(RuleGroup::UfCodes).without(RuleGroup::RegexPatterns));
+        assert_eq!(fired(&without, "SP"), None);
+        assert_eq!(fired(&without, "14h30"), None);
+        assert_eq!(fired(&without, "Lula"), fired(&all, "Lula"));
+
+        // Só o padrão de título: "Lula" continua PER, pela regra do título
+        let titles = rules(RuleGroups::only(&[RuleGroup::TitlePatterns]));
+        assert_eq!(titles, [("Lula".to_string(), "title_pattern".to_string())]);
+
+        let json = serde_json::to_string(&RuleGroups::only(&[RuleGroup::MiscGazetteer, RuleGroup::UfCodes])).unwrap();
+        assert_eq!(json, r#"["misc_gazetteer","uf_codes"]"#);
+        let options: AnalysisOptions = serde_json::from_str(r#"{"rule_groups": ["title_patterns"]}"#).unwrap();
+        assert_eq!(options.rule_groups, RuleGroups::only(&[RuleGroup::TitlePatterns]));
+        assert_eq!(serde_json::from_str::<AnalysisOptions>("{}").unwrap().rule_groups, RuleGroups::all());
+    }
+
+    #[test]
+    fn test_beam_decoder_option() {
+        let text = "O presidente Lula visitou a Petrobras no Rio de Janeiro em março.";
+        let entities = |pipeline: &NerPipeline| {
+            let (tagged, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::CrfOnly, TokenizerMode::Standard);
+            (tagged.into_iter().map(|t| t.tag).collect::<Vec<_>>(), entities)
+        };
+        let (exact_tags, _) = entities(&NerPipeline::new());
+        // Sem ouvinte, o Viterbi decodifica nos buffers da thread, que ficam para a próxima análise
+        let reserved = VITERBI_SCRATCH.with(|scratch| scratch.borrow().backpointer_bytes());
+        assert!(reserved > 0);
+        entities(&NerPipeline::new());
+        assert_eq!(VITERBI_SCRATCH.with(|scratch| scratch.borrow().backpointer_bytes()), reserved);
+        let n_tags = NerPipeline::new().model.crf.tags().len();
+        let wide = NerPipeline::new().with_decoder(Decoder::Beam { width: n_tags });
+        assert_eq!(entities(&wide).0, exact_tags);
+
+        // Feixe guloso: sequência completa, offsets válidos, confiança em [0, 1]
+        let greedy = NerPipeline::new().with_decoder(Decoder::Beam { width: 1 });
+        let (tags, spans) = entities(&greedy);
+        assert_eq!(tags.len(), exact_tags.len());
+        assert!(spans.iter().all(|e| text[e.start..e.end] == e.text && (0.0..=1.0).contains(&e.confidence)));
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_spelling_variants_match_gazetteers() {
+        let text = "Partiu de Sam Paulo para a Baía e depois para o Piauhy.";
+        let found = |pipeline: &NerPipeline| -> Vec<String> {
+            let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+            entities.iter().filter(|e| e.category == EntityCategory::Loc).map(|e| e.text.clone()).collect()
+        };
+        assert!(!found(&NerPipeline::new()).contains(&"Piauhy".to_string()));
+
+        let pipeline = NerPipeline::new().with_spelling_variants(SpellingVariants::new());
+        let locations = found(&pipeline);
+        assert_eq!(locations, ["Sam Paulo", "Baía", "Piauhy"]);
+
+        // A grafia casada fica registrada na regra e no evento
+        let mut events = Vec::new();
+        pipeline.analyze_with_callback(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard, AnalysisOptions::default(), |e| {
+            events.push(e)
+        });
+        let variant_of = |token: &str| {
+            events.iter().find_map(|e| match e {
+                PipelineEvent::RuleApplied { token_text, variant_of, .. } if token_text == token => Some(variant_of.clone()),
+                _ => None,
+            })
+        };
+        assert_eq!(variant_of("Sam"), Some(Some("são".to_string())));
+        assert_eq!(variant_of("Piauhy"), Some(Some("piauí".to_string())));
+        assert_eq!(variant_of("Paulo"), Some(None));
+    }
+
+    #[test]
+    fn test_modes_follow_cargo_features() {
+        let pipeline = NerPipeline::new();
+        assert_eq!(pipeline.is_available(AlgorithmMode::RulesOnly), cfg!(feature = "rules"));
+        assert_eq!(pipeline.is_available(AlgorithmMode::Hybrid), cfg!(feature = "rules"));
+        assert_eq!(pipeline.is_available(AlgorithmMode::Hmm), cfg!(feature = "statistical"));
+        assert_eq!(pipeline.is_available(AlgorithmMode::SpanBased), cfg!(feature = "statistical"));
+        assert!(pipeline.is_available(AlgorithmMode::CrfOnly));
+
+        // Sem regras, Hybrid cai para CrfOnly e RulesOnly para FeaturesOnly
+        let hybrid = if cfg!(feature = "rules") { AlgorithmMode::Hybrid } else { AlgorithmMode::CrfOnly };
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::Hybrid), hybrid);
+        let rules = if cfg!(feature = "rules") { AlgorithmMode::RulesOnly } else { AlgorithmMode::FeaturesOnly };
+        assert_eq!(pipeline.resolve_mode(AlgorithmMode::RulesOnly), rules);
+    }
+
+    #[test]
+    fn test_bpe_mismatch_falls_back_to_standard() {
+        let pipeline = NerPipeline::new();
+        assert!(pipeline.check_bpe_merges().is_ok());
+
+        let other = BpeMergeTable::new("outro", vec![("B".into(), "r".into())]);
+        let pipeline = pipeline.with_bpe_merges(other);
+        assert!(pipeline.check_bpe_merges().is_err());
+
+        let (tx, rx) = mpsc::channel();
+        pipeline.analyze_streaming("o Brasil venceu.", AlgorithmMode::RulesOnly, TokenizerMode::BpeLite, tx);
+        let events: Vec<PipelineEvent> = rx.iter().collect();
+        assert!(events.iter().any(|e| matches!(e, PipelineEvent::Warning { message, .. } if message.contains("BPE"))));
+        let tokens = events.iter().find_map(|e| match e {
+            PipelineEvent::TokenizationDone { tokens, .. } => Some(tokens),
+            _ => None,
+        });
+        assert_eq!(tokens.unwrap()[1].text, "Brasil");
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_trained_bpe_mode() {
+        let text = "o Brasil venceu.";
+        let warning_codes = |pipeline: &NerPipeline| {
+            let (tx, rx) = mpsc::channel();
+            pipeline.analyze_streaming(text, AlgorithmMode::RulesOnly, TokenizerMode::Bpe, tx);
+            rx.iter()
+                .filter_map(|e| match e {
+                    PipelineEvent::Warning { code, .. } => Some(code),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // Sem modelo: Standard, com aviso
+        let pipeline = NerPipeline::new();
+        assert!(warning_codes(&pipeline).contains(&WarningCode::BpeModelMissing));
+        let (tokens, _) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Bpe);
+        assert_eq!(tokens[1].token.text, "Brasil");
+
+        // Com modelo: os sub-words aprendidos, com offsets do texto original
+        let bpe = BpeModel::train(["o Brasil e a Bahia", "o Brasil venceu a Bolívia"], 25);
+        let pipeline = NerPipeline::new().with_bpe_model(bpe.clone());
+        assert!(warning_codes(&pipeline).is_empty());
+        let (tokens, _) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Bpe);
+        let texts: Vec<&str> = tokens.iter().map(|t| t.token.text.as_str()).collect();
+        let expected: Vec<String> = bpe.tokenize(text).into_iter().map(|t| t.text).collect();
+        assert_eq!(texts, expected);
+        assert!(tokens.iter().all(|t| text[t.token.start..t.token.end] == t.token.text));
+    }
+
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_explain_score_breakdown() {
+        let pipeline = NerPipeline::new();
+        let text = "O presidente Lula visitou o Brasil.";
+        let (_, plain) = pipeline.analyze_with_mode(text, AlgorithmMode::Hybrid, TokenizerMode::Standard);
+        assert!(plain.iter().all(|e| e.score_breakdown.is_none()));
+
+        let options = AnalysisOptions { explain: true, ..Default::default() };
+        let (_, explained) = pipeline.analyze_with_options(text, AlgorithmMode::Hybrid, TokenizerMode::Standard, options);
+        assert_eq!(explained.len(), plain.len());
+        let brasil = explained.iter().find(|e| e.text == "Brasil").unwrap();
+        let why = brasil.score_breakdown.as_ref().unwrap();
+        assert!(why.emission.is_some() && why.transition.is_some());
+        assert!(why.model_confidence.is_some_and(|p| (0.0..=1.0).contains(&p)));
+        assert_eq!(why.rule_confidence, Some(brasil.confidence)); // a regra venceu o trecho
+        assert_eq!(why.calibration, 0.0);
+
+        // Serializado apenas quando presente
+        let json = serde_json::to_value(&plain[0]).unwrap();
+        assert!(json.get("score_breakdown").is_none());
+
+        // Com calibração, o ajuste aparece na decomposição
+        let mut pipeline = pipeline;
+        let scaling = PlattScaling { a: -1.0, b: 0.5 };
+        pipeline.model.calibration.set(AlgorithmMode::Hybrid, scaling);
+        let (_, calibrated) = pipeline.analyze_with_options(text, AlgorithmMode::Hybrid, TokenizerMode::Standard, options);
+        let calibrated = calibrated.iter().find(|e| e.text == "Brasil").unwrap();
+        assert!((calibrated.confidence - scaling.apply(brasil.confidence)).abs() < 1e-12);
+        let adjustment = calibrated.score_breakdown.as_ref().unwrap().calibration;
+        assert!((adjustment - (calibrated.confidence - brasil.confidence)).abs() < 1e-12 && adjustment < 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "statistical")]
+    fn test_model_confidences_and_calibration() {
+        let mut pipeline = NerPipeline::new();
+        let text = "O presidente Lula visitou a Petrobras no Rio de Janeiro.";
+        let modes = [
+            AlgorithmMode::CrfOnly,
+            AlgorithmMode::Hmm,
+            AlgorithmMode::MaxEnt,
+            AlgorithmMode::Memm,
+            AlgorithmMode::Perceptron,
+            AlgorithmMode::SpanBased,
+        ];
+        for mode in modes {
+            let (tokens, entities) = pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard);
+            assert!(tokens.iter().all(|t| (0.0..=1.0).contains(&t.confidence)), "{mode:?}");
+            assert!(!entities.is_empty(), "{mode:?}");
+            // Probabilidades de verdade, não mais 1.0 fixo
+            assert!(entities.iter().all(|e| e.confidence > 0.0 && e.confidence < 1.0), "{mode:?}: {entities:?}");
+        }
+
+        // Platt ajustado na fatia reservada do corpus, aplicado só ao modo calibrado
+        let dev = SelfCheckConfig::default().holdout_gold(&get_corpus());
+        let (_, raw) = pipeline.analyze_with_mode(text, AlgorithmMode::Hmm, TokenizerMode::Standard);
+        let (_, perceptron) = pipeline.analyze_with_mode(text, AlgorithmMode::Perceptron, TokenizerMode::Standard);
+        let scaling = pipeline.calibrate_confidence(&dev, AlgorithmMode::Hmm, TokenizerMode::Standard).unwrap();
+        assert_ne!(scaling, PlattScaling::IDENTITY);
+        let (_, calibrated) = pipeline.analyze_with_mode(text, AlgorithmMode::Hmm, TokenizerMode::Standard);
+        assert_eq!(calibrated.len(), raw.len());
+        for (raw, calibrated) in raw.iter().zip(&calibrated) {
+            assert!((calibrated.confidence - scaling.apply(raw.confidence)).abs() < 1e-12);
+        }
+        let (_, unchanged) = pipeline.analyze_with_mode(text, AlgorithmMode::Perceptron, TokenizerMode::Standard);
+        let confidences = |entities: &[EntitySpan]| entities.iter().map(|e| e.confidence).collect::<Vec<_>>();
+        assert_eq!(confidences(&unchanged), confidences(&perceptron));
+
+        // Recalibrar parte da confiança bruta, não da já calibrada
+        assert_eq!(pipeline.calibrate_confidence(&dev, AlgorithmMode::Hmm, TokenizerMode::Standard), Some(scaling));
+        assert_eq!(pipeline.calibrate_confidence(&[], AlgorithmMode::Hmm, TokenizerMode::Standard), None);
+        assert!(pipeline.model.calibration.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "statistical")]
+    fn test_capabilities_report_fallbacks() {
+        let pipeline = NerPipeline::new().with_sequence_tagger(AlgorithmMode::Hmm, crate::hmm::HmmModel::new());
+        let capabilities = pipeline.capabilities();
+        assert_eq!(capabilities.modes.len(), AlgorithmMode::all().len());
+        let hmm = capabilities.modes.iter().find(|m| m.mode == AlgorithmMode::Hmm).unwrap();
+        assert!(!hmm.available);
+        assert_eq!(hmm.resolves_to, pipeline.resolve_mode(AlgorithmMode::Hmm));
+        assert!(!capabilities.available_modes().contains(&AlgorithmMode::Hmm));
+        assert!(capabilities.supports(AlgorithmMode::CrfOnly));
+    }
+
+    #[test]
+    fn test_invalid_options_are_rejected() {
+        let pipeline = NerPipeline::new();
+        let run = |options: AnalysisOptions, filter: &EntityFilter| {
+            pipeline.analyze_filtered("o Brasil venceu.", AlgorithmMode::CrfOnly, TokenizerMode::Standard, options, filter)
+        };
+        let filter = EntityFilter::default();
+        let nan = AnalysisOptions { abstain_below: Some(f64::NAN), ..Default::default() };
+        assert!(matches!(run(nan, &filter), Err(InvalidOptions::AbstainThreshold(_))));
+        let negative = AnalysisOptions { abstain_below: Some(-0.1), ..Default::default() };
+        assert_eq!(run(negative, &filter).unwrap_err(), InvalidOptions::AbstainThreshold(-0.1));
+        let empty = EntityFilter::default().with_categories(Vec::new());
+        assert_eq!(run(AnalysisOptions::default(), &empty).unwrap_err(), InvalidOptions::EmptyCategories);
+        let too_high = EntityFilter::default().with_min_confidence(1.2);
+        assert_eq!(run(AnalysisOptions::default(), &too_high).unwrap_err(), InvalidOptions::MinConfidence(1.2));
+
+        // O filtro vale só para as entidades; as tags dos tokens ficam intactas
+        let report = run(AnalysisOptions::default(), &EntityFilter::default().with_min_confidence(1.0)).unwrap();
+        assert!(report.entities.is_empty());
+        assert_eq!(report.tagged_tokens.len(), 4);
+    }
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_custom_category_from_rule_gazetteer() {
+        let doenca = EntityCategory::Custom(crate::categories::CustomCategory::new("DOENCA").unwrap());
+        let mut pipeline = NerPipeline::new();
+        pipeline.model.rule_engine.add_entry(doenca, "leptospirose");
+        // "Febre Amarela" já é MISC embutido, que roda antes dos gazetteers personalizados
+        pipeline.model.rule_engine.add_entry(doenca, "febre amarela");
+        let (_, entities) =
+            pipeline.analyze_with_mode("Casos de leptospirose e febre amarela preocupam o SUS.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        let entity = entities.iter().find(|e| e.category == doenca).expect("a regra de gazetteer deve marcar DOENCA");
+        assert_eq!(entity.text, "leptospirose");
+        assert!(entities.iter().any(|e| e.text == "febre amarela" && e.category == EntityCategory::Misc));
+
+        let json = serde_json::to_value(entity).unwrap();
+        assert_eq!(json["category"], "DOENCA");
+    }
+    #[test]
+    #[cfg(feature = "rules")]
+    fn test_european_portuguese_pack() {
+        let pipeline = NerPipeline::for_language(&LanguagePack::pt_pt());
+        assert_eq!(pipeline.model.language, "pt-PT");
+        let text = "O primeiro-ministro Albuquerque reuniu-se com a Galp em Coimbra.";
+        let (_, entities) = pipeline.analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        let category = |name: &str| entities.iter().find(|e| e.text == name).map(|e| e.category);
+        // "primeiro-ministro" é título só no pacote europeu
+        assert_eq!(category("Albuquerque"), Some(EntityCategory::Per));
+        assert_eq!(category("Galp"), Some(EntityCategory::Org));
+        assert_eq!(category("Coimbra"), Some(EntityCategory::Loc));
+
+        let tokens = pipeline.tokenizer_config.tokenize("Dar-lhe-emos a resposta.", TokenizerMode::Aggressive);
+        assert_eq!(tokens[2].text, "lhe");
+        assert_eq!((tokens[4].text.as_str(), tokens[4].start, tokens[4].end), ("emos", 8, 12));
+    }
 }
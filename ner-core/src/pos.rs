@@ -0,0 +1,584 @@
+//! # Etiquetador Morfossintático (POS)
+//!
+//! Classe gramatical de cada token, no conjunto de 17 etiquetas do Universal
+//! Dependencies ([`PosTag`]: `NOUN`, `PROPN`, `VERB`, `ADP`, ...). Para o NER ela é
+//! uma pista forte: entidades são quase sempre `PROPN`, e preposições, artigos e
+//! verbos praticamente nunca abrem uma entidade. As features `pos=` e `prev_pos=`
+//! (ver [`crate::features::FeatureTemplate::pos`]) levam essa informação ao CRF.
+//!
+//! ## Modelo
+//!
+//! [`PosTagger`] é um perceptron médio guloso (da esquerda para a direita): cada token
+//! recebe a etiqueta de maior pontuação dadas a palavra, seus sufixos, a forma, as
+//! palavras vizinhas e as duas etiquetas anteriores já decididas. Palavras que no
+//! treino sempre tiveram a mesma etiqueta ("o", "de", ",") vão direto por um
+//! dicionário, sem pontuar.
+//!
+//! ## Treino
+//!
+//! [`read_conllu`] lê um corpus no formato CoNLL-U do Universal Dependencies (por
+//! exemplo o treebank Bosque) e [`PosTagger::train`] treina sobre as sentenças lidas.
+//! Sem corpus externo, [`builtin_tagger`] treina (uma vez, sob demanda) sobre um
+//! pequeno corpus anotado embutido, suficiente para as classes fechadas e os padrões
+//! mais comuns de notícias.
+//!
+//! ```rust
+//! use ner_core::pos::{builtin_tagger, PosTag};
+//!
+//! let tags = builtin_tagger().tag(&["O", "ministro", "visitou", "a", "Bahia", "."]);
+//! assert_eq!(tags[0], PosTag::Det);
+//! assert_eq!(tags[5], PosTag::Punct);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::format;
+use crate::tokenizer::Token;
+
+/// Etiqueta morfossintática universal (UPOS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PosTag {
+    Adj,
+    Adp,
+    Adv,
+    Aux,
+    Cconj,
+    Det,
+    Intj,
+    Noun,
+    Num,
+    Part,
+    Pron,
+    Propn,
+    Punct,
+    Sconj,
+    Sym,
+    Verb,
+    X,
+}
+
+/// Quantidade de etiquetas em [`PosTag::ALL`].
+const TAG_COUNT: usize = 17;
+
+impl PosTag {
+    /// Todas as etiquetas, na ordem usada nos vetores de pesos.
+    pub const ALL: [PosTag; TAG_COUNT] = [
+        Self::Adj,
+        Self::Adp,
+        Self::Adv,
+        Self::Aux,
+        Self::Cconj,
+        Self::Det,
+        Self::Intj,
+        Self::Noun,
+        Self::Num,
+        Self::Part,
+        Self::Pron,
+        Self::Propn,
+        Self::Punct,
+        Self::Sconj,
+        Self::Sym,
+        Self::Verb,
+        Self::X,
+    ];
+
+    /// Nome UPOS ("PROPN", "ADP", ...).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Adj => "ADJ",
+            Self::Adp => "ADP",
+            Self::Adv => "ADV",
+            Self::Aux => "AUX",
+            Self::Cconj => "CCONJ",
+            Self::Det => "DET",
+            Self::Intj => "INTJ",
+            Self::Noun => "NOUN",
+            Self::Num => "NUM",
+            Self::Part => "PART",
+            Self::Pron => "PRON",
+            Self::Propn => "PROPN",
+            Self::Punct => "PUNCT",
+            Self::Sconj => "SCONJ",
+            Self::Sym => "SYM",
+            Self::Verb => "VERB",
+            Self::X => "X",
+        }
+    }
+
+    /// Etiqueta pelo nome UPOS, sem diferenciar maiúsculas ("propn" → `Propn`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str().eq_ignore_ascii_case(name))
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for PosTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Sentença anotada com classes gramaticais (`words[i]` tem a etiqueta `tags[i]`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PosSentence {
+    pub words: Vec<String>,
+    pub tags: Vec<PosTag>,
+}
+
+/// Erro ao ler um corpus CoNLL-U.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConlluError {
+    /// Linha malformada, numerada a partir de 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConlluError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linha {} do CoNLL-U malformada: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConlluError {}
+
+/// Lê sentenças de um texto CoNLL-U (colunas `ID FORM LEMMA UPOS ...` separadas por tab).
+///
+/// Usa a forma (coluna 2) e a etiqueta UPOS (coluna 4). Comentários (`# text = ...`) e
+/// nós vazios (`8.1`) são ignorados. Contrações como "do" aparecem no CoNLL-U como uma
+/// faixa (`3-4 do`) seguida das partes (`de`, `o`); como o tokenizador não separa
+/// contrações, a sentença lida mantém a forma de superfície, com a etiqueta da primeira
+/// parte (`do` → `ADP`).
+///
+/// ```rust
+/// use ner_core::pos::{read_conllu, PosTag};
+///
+/// let conllu = "# text = Saiu do Rio\n\
+///               1\tSaiu\tsair\tVERB\t_\t_\t0\troot\t_\t_\n\
+///               2-3\tdo\t_\t_\t_\t_\t_\t_\t_\t_\n\
+///               2\tde\tde\tADP\t_\t_\t4\tcase\t_\t_\n\
+///               3\to\to\tDET\t_\t_\t4\tdet\t_\t_\n\
+///               4\tRio\tRio\tPROPN\t_\t_\t1\tobl\t_\t_\n";
+/// let sentences = read_conllu(conllu).unwrap();
+/// assert_eq!(sentences[0].words, vec!["Saiu", "do", "Rio"]);
+/// assert_eq!(sentences[0].tags, vec![PosTag::Verb, PosTag::Adp, PosTag::Propn]);
+/// ```
+pub fn read_conllu(text: &str) -> Result<Vec<PosSentence>, ConlluError> {
+    let mut sentences = Vec::new();
+    let mut current = PosSentence::default();
+    // Faixa (inclusiva) da contração em curso: as partes dela são puladas.
+    let mut range = (0usize, 0usize);
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            if !current.words.is_empty() {
+                sentences.push(std::mem::take(&mut current));
+            }
+            range = (0, 0);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let malformed = |message: String| ConlluError { line: n + 1, message };
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() < 4 {
+            return Err(malformed(format!("esperadas ao menos 4 colunas, encontradas {}", columns.len())));
+        }
+        let (id, form) = (columns[0], columns[1]);
+
+        if id.contains('.') {
+            continue; // nó vazio
+        }
+        if let Some((start, end)) = id.split_once('-') {
+            let bound = |s: &str| s.parse::<usize>().map_err(|_| malformed(format!("faixa inválida '{id}'")));
+            range = (bound(start)?, bound(end)?);
+            current.words.push(form.to_string());
+            current.tags.push(PosTag::X); // etiqueta da primeira parte, logo abaixo
+            continue;
+        }
+
+        let index: usize = id.parse().map_err(|_| malformed(format!("ID inválido '{id}'")))?;
+        let tag = PosTag::from_name(columns[3])
+            .ok_or_else(|| malformed(format!("etiqueta UPOS desconhecida '{}'", columns[3])))?;
+        if index <= range.1 {
+            // Parte de contração: só a primeira define a etiqueta da forma de superfície.
+            if index == range.0 {
+                if let Some(last) = current.tags.last_mut() {
+                    *last = tag;
+                }
+            }
+            continue;
+        }
+        current.words.push(form.to_string());
+        current.tags.push(tag);
+    }
+    if !current.words.is_empty() {
+        sentences.push(current);
+    }
+    Ok(sentences)
+}
+
+/// Etiquetador POS por perceptron médio (ver a documentação do módulo).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PosTagger {
+    weights: HashMap<String, [f64; TAG_COUNT]>,
+    /// Palavras (em minúsculas) que no treino tiveram sempre a mesma etiqueta.
+    tagdict: HashMap<String, PosTag>,
+}
+
+/// Ocorrências mínimas para uma palavra entrar no dicionário de etiquetas.
+const TAGDICT_MIN_COUNT: usize = 3;
+
+/// Épocas de treino do etiquetador embutido.
+const BUILTIN_EPOCHS: usize = 8;
+
+impl PosTagger {
+    /// Treina um etiquetador sobre `sentences` por `epochs` passadas.
+    pub fn train(sentences: &[PosSentence], epochs: usize) -> Self {
+        let mut tagger = Self { weights: HashMap::new(), tagdict: build_tagdict(sentences) };
+        let mut averager = Averager::default();
+
+        for _ in 0..epochs {
+            for sentence in sentences {
+                let lower: Vec<String> = sentence.words.iter().map(|w| w.to_lowercase()).collect();
+                let (mut prev, mut prev2) = (None, None);
+                for i in 0..sentence.words.len() {
+                    let truth = sentence.tags[i];
+                    if tagger.tagdict.contains_key(&lower[i]) {
+                        (prev2, prev) = (prev, Some(truth));
+                        continue;
+                    }
+                    let features = tagger_features(&sentence.words, &lower, i, prev, prev2);
+                    let guess = tagger.best_tag(&features);
+                    averager.tick();
+                    if guess != truth {
+                        for feature in &features {
+                            averager.update(&mut tagger.weights, feature, truth, 1.0);
+                            averager.update(&mut tagger.weights, feature, guess, -1.0);
+                        }
+                    }
+                    // O contexto de treino usa a etiqueta correta (teacher forcing).
+                    (prev2, prev) = (prev, Some(truth));
+                }
+            }
+        }
+        averager.average(&mut tagger.weights);
+        tagger
+    }
+
+    /// Etiqueta uma sentença já dividida em palavras.
+    pub fn tag<S: AsRef<str>>(&self, words: &[S]) -> Vec<PosTag> {
+        let words: Vec<String> = words.iter().map(|w| w.as_ref().to_string()).collect();
+        let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+        let mut tags: Vec<PosTag> = Vec::with_capacity(words.len());
+        for i in 0..words.len() {
+            let prev = i.checked_sub(1).map(|j| tags[j]);
+            let prev2 = i.checked_sub(2).map(|j| tags[j]);
+            let tag = match self.tagdict.get(&lower[i]) {
+                Some(&tag) => tag,
+                None => self.best_tag(&tagger_features(&words, &lower, i, prev, prev2)),
+            };
+            tags.push(tag);
+        }
+        tags
+    }
+
+    /// Etiqueta os tokens do tokenizador.
+    pub fn tag_tokens(&self, tokens: &[Token]) -> Vec<PosTag> {
+        let words: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        self.tag(&words)
+    }
+
+    /// Salva o etiquetador em JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega um etiquetador salvo com [`PosTagger::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+
+    fn best_tag(&self, features: &[String]) -> PosTag {
+        let mut scores = [0.0; TAG_COUNT];
+        for feature in features {
+            if let Some(weights) = self.weights.get(feature) {
+                for (score, w) in scores.iter_mut().zip(weights) {
+                    *score += w;
+                }
+            }
+        }
+        // Empate (inclusive sem nenhum peso): substantivo, a classe aberta mais comum.
+        let mut best = PosTag::Noun;
+        for tag in PosTag::ALL {
+            if scores[tag.index()] > scores[best.index()] {
+                best = tag;
+            }
+        }
+        best
+    }
+}
+
+/// Palavras frequentes que tiveram sempre a mesma etiqueta no treino.
+fn build_tagdict(sentences: &[PosSentence]) -> HashMap<String, PosTag> {
+    let mut counts: HashMap<String, HashMap<PosTag, usize>> = HashMap::new();
+    for sentence in sentences {
+        for (word, &tag) in sentence.words.iter().zip(&sentence.tags) {
+            *counts.entry(word.to_lowercase()).or_default().entry(tag).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .filter_map(|(word, tags)| {
+            let total: usize = tags.values().sum();
+            (tags.len() == 1 && total >= TAGDICT_MIN_COUNT).then(|| (word, *tags.keys().next().unwrap()))
+        })
+        .collect()
+}
+
+/// Features do token `i` para o etiquetador.
+fn tagger_features(
+    words: &[String],
+    lower: &[String],
+    i: usize,
+    prev: Option<PosTag>,
+    prev2: Option<PosTag>,
+) -> Vec<String> {
+    let word = &words[i];
+    let w = &lower[i];
+    let chars: Vec<char> = w.chars().collect();
+    let suffix = |n: usize| -> String { chars[chars.len().saturating_sub(n)..].iter().collect() };
+    let tag_name = |t: Option<PosTag>| t.map_or("BOS", |t| t.as_str());
+
+    let mut features = vec![
+        "bias".to_string(),
+        format!("w={w}"),
+        format!("suf1={}", suffix(1)),
+        format!("suf2={}", suffix(2)),
+        format!("suf3={}", suffix(3)),
+        format!("pre1={}", chars.first().map(|c| c.to_string()).unwrap_or_default()),
+        format!("p1={}", tag_name(prev)),
+        format!("p1p2={}_{}", tag_name(prev), tag_name(prev2)),
+        format!("p1w={}_{w}", tag_name(prev)),
+    ];
+
+    let capitalized = word.chars().next().is_some_and(|c| c.is_uppercase());
+    if capitalized {
+        features.push(if i == 0 { "cap_first".to_string() } else { "cap".to_string() });
+    }
+    if word.chars().filter(|c| c.is_alphabetic()).count() > 1 && word.chars().all(|c| !c.is_lowercase()) {
+        features.push("all_caps".to_string());
+    }
+    if word.chars().any(|c| c.is_ascii_digit()) {
+        features.push("digit".to_string());
+    }
+    if !word.chars().any(char::is_alphanumeric) {
+        features.push("no_alnum".to_string());
+    }
+
+    match i.checked_sub(1) {
+        Some(j) => features.push(format!("w-1={}", lower[j])),
+        None => features.push("w-1=BOS".to_string()),
+    }
+    match lower.get(i + 1) {
+        Some(next) => {
+            features.push(format!("w+1={next}"));
+            let next_chars: Vec<char> = next.chars().collect();
+            let next_suffix: String = next_chars[next_chars.len().saturating_sub(3)..].iter().collect();
+            features.push(format!("suf3+1={next_suffix}"));
+            if words[i + 1].chars().next().is_some_and(|c| c.is_uppercase()) {
+                features.push("cap+1".to_string());
+            }
+        }
+        None => features.push("w+1=EOS".to_string()),
+    }
+    features
+}
+
+/// Acumulador da média dos pesos do perceptron (atualização preguiçosa por carimbo).
+#[derive(Default)]
+struct Averager {
+    step: u64,
+    totals: HashMap<String, [f64; TAG_COUNT]>,
+    stamps: HashMap<String, [u64; TAG_COUNT]>,
+}
+
+impl Averager {
+    fn tick(&mut self) {
+        self.step += 1;
+    }
+
+    fn update(&mut self, weights: &mut HashMap<String, [f64; TAG_COUNT]>, feature: &str, tag: PosTag, delta: f64) {
+        let t = tag.index();
+        let w = weights.entry(feature.to_string()).or_insert([0.0; TAG_COUNT]);
+        let total = self.totals.entry(feature.to_string()).or_insert([0.0; TAG_COUNT]);
+        let stamp = self.stamps.entry(feature.to_string()).or_insert([0; TAG_COUNT]);
+        total[t] += (self.step - stamp[t]) as f64 * w[t];
+        stamp[t] = self.step;
+        w[t] += delta;
+    }
+
+    fn average(self, weights: &mut HashMap<String, [f64; TAG_COUNT]>) {
+        let steps = self.step.max(1) as f64;
+        for (feature, w) in weights.iter_mut() {
+            let (Some(total), Some(stamp)) = (self.totals.get(feature), self.stamps.get(feature)) else {
+                continue;
+            };
+            for t in 0..TAG_COUNT {
+                let sum = total[t] + (self.step - stamp[t]) as f64 * w[t];
+                w[t] = sum / steps;
+            }
+        }
+        weights.retain(|_, w| w.iter().any(|&x| x != 0.0));
+    }
+}
+
+/// Etiquetador treinado sobre o corpus embutido (treinado uma única vez, no primeiro uso).
+pub fn builtin_tagger() -> &'static PosTagger {
+    static TAGGER: OnceLock<PosTagger> = OnceLock::new();
+    TAGGER.get_or_init(|| PosTagger::train(&builtin_corpus(), BUILTIN_EPOCHS))
+}
+
+/// Corpus embutido, no formato `palavra/ETIQUETA` separado por espaços.
+pub fn builtin_corpus() -> Vec<PosSentence> {
+    BUILTIN_CORPUS
+        .iter()
+        .map(|line| {
+            let mut sentence = PosSentence::default();
+            for item in line.split_whitespace() {
+                let (word, tag) = item.rsplit_once('/').expect("item do corpus POS sem etiqueta");
+                sentence.words.push(word.to_string());
+                sentence.tags.push(PosTag::from_name(tag).expect("etiqueta UPOS inválida no corpus POS"));
+            }
+            sentence
+        })
+        .collect()
+}
+
+/// Sentenças anotadas à mão seguindo as convenções do UD Portuguese-Bosque
+/// (cópula e auxiliares como `AUX`, contrações com a etiqueta da preposição).
+const BUILTIN_CORPUS: &[&str] = &[
+    "O/DET presidente/NOUN Lula/PROPN visitou/VERB a/DET Petrobras/PROPN em/ADP Brasília/PROPN ./PUNCT",
+    "A/DET ministra/NOUN da/ADP Saúde/PROPN anunciou/VERB novas/ADJ medidas/NOUN contra/ADP a/DET dengue/NOUN ./PUNCT",
+    "Ele/PRON disse/VERB que/SCONJ o/DET governo/NOUN vai/AUX investir/VERB R$/SYM 50/NUM bilhões/NUM em/ADP escolas/NOUN ./PUNCT",
+    "Santos/PROPN Dumont/PROPN voou/VERB em/ADP Paris/PROPN em/ADP 1906/NUM ./PUNCT",
+    "O/DET Flamengo/PROPN venceu/VERB o/DET Palmeiras/PROPN por/ADP dois/NUM a/ADP um/NUM no/ADP Maracanã/PROPN ./PUNCT",
+    "A/DET Lei/PROPN Áurea/PROPN foi/AUX assinada/VERB pela/ADP princesa/NOUN Isabel/PROPN em/ADP 13/NUM de/ADP maio/NOUN de/ADP 1888/NUM ./PUNCT",
+    "Os/DET pesquisadores/NOUN da/ADP Fiocruz/PROPN desenvolveram/VERB uma/DET vacina/NOUN eficaz/ADJ ./PUNCT",
+    "Ela/PRON trabalha/VERB no/ADP Banco/PROPN do/ADP Brasil/PROPN desde/ADP 2010/NUM ./PUNCT",
+    "Machado/PROPN de/ADP Assis/PROPN escreveu/VERB romances/NOUN importantes/ADJ no/ADP século/NOUN XIX/NUM ./PUNCT",
+    "O/DET Supremo/PROPN Tribunal/PROPN Federal/PROPN julgou/VERB o/DET caso/NOUN ontem/ADV ./PUNCT",
+    "Não/ADV sabemos/VERB quando/SCONJ a/DET obra/NOUN será/AUX concluída/VERB ./PUNCT",
+    "A/DET empresa/NOUN cresceu/VERB muito/ADV e/CCONJ contratou/VERB mil/NUM funcionários/NOUN ./PUNCT",
+    "O/DET governador/NOUN de/ADP São/PROPN Paulo/PROPN se/PRON reuniu/VERB com/ADP prefeitos/NOUN do/ADP interior/NOUN ./PUNCT",
+    "Eles/PRON chegaram/VERB cedo/ADV ,/PUNCT mas/CCONJ a/DET reunião/NOUN já/ADV tinha/AUX começado/VERB ./PUNCT",
+    "O/DET rio/NOUN Amazonas/PROPN é/AUX o/DET maior/ADJ do/ADP mundo/NOUN ./PUNCT",
+    "A/DET inflação/NOUN subiu/VERB 10,5/NUM %/SYM no/ADP ano/NOUN passado/ADJ ./PUNCT",
+    "Segundo/ADP o/DET IBGE/PROPN ,/PUNCT o/DET desemprego/NOUN caiu/VERB em/ADP março/NOUN ./PUNCT",
+    "Os/DET alunos/NOUN estudam/VERB na/ADP Universidade/PROPN de/ADP São/PROPN Paulo/PROPN ./PUNCT",
+    "O/DET técnico/NOUN convocou/VERB Neymar/PROPN para/ADP a/DET seleção/NOUN brasileira/ADJ ./PUNCT",
+    "Ontem/ADV ,/PUNCT a/DET polícia/NOUN prendeu/VERB dois/NUM suspeitos/NOUN no/ADP Rio/PROPN de/ADP Janeiro/PROPN ./PUNCT",
+    "Este/DET livro/NOUN conta/VERB a/DET história/NOUN de/ADP Tiradentes/PROPN ./PUNCT",
+    "Nós/PRON precisamos/VERB de/ADP mais/DET recursos/NOUN para/ADP a/DET saúde/NOUN pública/ADJ ./PUNCT",
+    "A/DET Vale/PROPN anunciou/VERB lucro/NOUN recorde/ADJ no/ADP primeiro/ADJ trimestre/NOUN ./PUNCT",
+    "O/DET deputado/NOUN federal/ADJ apresentou/VERB um/DET projeto/NOUN sobre/ADP educação/NOUN ./PUNCT",
+    "Se/SCONJ chover/VERB ,/PUNCT o/DET jogo/NOUN será/AUX adiado/VERB ./PUNCT",
+    "A/DET cantora/NOUN Anitta/PROPN lançou/VERB uma/DET nova/ADJ música/NOUN hoje/ADV ./PUNCT",
+    "O/DET Senado/PROPN aprovou/VERB a/DET reforma/NOUN tributária/ADJ por/ADP ampla/ADJ maioria/NOUN ./PUNCT",
+    "Muitos/DET turistas/NOUN visitam/VERB Salvador/PROPN durante/ADP o/DET Carnaval/PROPN ./PUNCT",
+    "A/DET reunião/NOUN começa/VERB às/ADP 14h30/NUM na/ADP sede/NOUN do/ADP partido/NOUN ./PUNCT",
+    "Ele/PRON também/ADV foi/AUX eleito/VERB senador/NOUN pelo/ADP Ceará/PROPN ./PUNCT",
+    "O/DET Brasil/PROPN exporta/VERB soja/NOUN e/CCONJ minério/NOUN para/ADP a/DET China/PROPN ./PUNCT",
+    "A/DET professora/NOUN explicou/VERB que/SCONJ os/DET resultados/NOUN eram/AUX bons/ADJ ./PUNCT",
+    "Quem/PRON venceu/VERB a/DET eleição/NOUN em/ADP Minas/PROPN Gerais/PROPN ?/PUNCT",
+    "O/DET hospital/NOUN atendeu/VERB centenas/NOUN de/ADP pacientes/NOUN com/ADP sintomas/NOUN graves/ADJ ./PUNCT",
+    "Dilma/PROPN Rousseff/PROPN foi/AUX a/DET primeira/ADJ presidente/NOUN do/ADP país/NOUN ./PUNCT",
+    "As/DET chuvas/NOUN fortes/ADJ causaram/VERB enchentes/NOUN em/ADP Recife/PROPN e/CCONJ Olinda/PROPN ./PUNCT",
+    "O/DET ministro/NOUN Fernando/PROPN Haddad/PROPN defendeu/VERB o/DET novo/ADJ arcabouço/NOUN fiscal/ADJ ./PUNCT",
+    "Nunca/ADV vi/VERB um/DET jogo/NOUN tão/ADV emocionante/ADJ ./PUNCT",
+    "A/DET Anvisa/PROPN aprovou/VERB o/DET medicamento/NOUN em/ADP 2023/NUM ./PUNCT",
+    "Eu/PRON moro/VERB em/ADP Curitiba/PROPN há/VERB cinco/NUM anos/NOUN ./PUNCT",
+    "O/DET Itaú/PROPN e/CCONJ o/DET Bradesco/PROPN divulgaram/VERB seus/DET balanços/NOUN ./PUNCT",
+    "Ai/INTJ ,/PUNCT que/DET susto/NOUN !/PUNCT",
+    "A/DET seleção/NOUN está/AUX treinando/VERB na/ADP Granja/PROPN Comary/PROPN ./PUNCT",
+    "Os/DET índios/NOUN ianomâmis/ADJ vivem/VERB na/ADP fronteira/NOUN com/ADP a/DET Venezuela/PROPN ./PUNCT",
+    "Ela/PRON comprou/VERB o/DET carro/NOUN por/ADP R$/SYM 80/NUM mil/NUM ./PUNCT",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_conllu_contractions_and_empty_nodes() {
+        let conllu = "# sent_id = 1\n\
+                      1\tEla\tele\tPRON\t_\t_\t2\tnsubj\t_\t_\n\
+                      2\tmora\tmorar\tVERB\t_\t_\t0\troot\t_\t_\n\
+                      2.1\tmora\tmorar\tVERB\t_\t_\t_\t_\t_\t_\n\
+                      3-4\tna\t_\t_\t_\t_\t_\t_\t_\t_\n\
+                      3\tem\tem\tADP\t_\t_\t5\tcase\t_\t_\n\
+                      4\ta\to\tDET\t_\t_\t5\tdet\t_\t_\n\
+                      5\tBahia\tBahia\tPROPN\t_\t_\t2\tobl\t_\t_\n\
+                      \n\
+                      1\tSim\tsim\tINTJ\t_\t_\t0\troot\t_\t_\n";
+        let sentences = read_conllu(conllu).unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[0].words, vec!["Ela", "mora", "na", "Bahia"]);
+        assert_eq!(sentences[0].tags, vec![PosTag::Pron, PosTag::Verb, PosTag::Adp, PosTag::Propn]);
+        assert_eq!(sentences[1].tags, vec![PosTag::Intj]);
+    }
+
+    #[test]
+    fn test_read_conllu_rejects_unknown_tag() {
+        let err = read_conllu("1\tcasa\tcasa\tSUBST\t_\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("SUBST"));
+    }
+
+    #[test]
+    fn test_builtin_tagger_closed_classes_and_names() {
+        let words = ["A", "polícia", "prendeu", "o", "suspeito", "em", "Manaus", "."];
+        let tags = builtin_tagger().tag(&words);
+        assert_eq!(tags[0], PosTag::Det);
+        assert_eq!(tags[3], PosTag::Det);
+        assert_eq!(tags[5], PosTag::Adp);
+        assert_eq!(tags[6], PosTag::Propn);
+        assert_eq!(tags[7], PosTag::Punct);
+    }
+
+    #[test]
+    fn test_tagger_fits_training_corpus() {
+        let corpus = builtin_corpus();
+        let tagger = builtin_tagger();
+        let (mut correct, mut total) = (0, 0);
+        for sentence in &corpus {
+            let tags = tagger.tag(&sentence.words);
+            correct += tags.iter().zip(&sentence.tags).filter(|(a, b)| a == b).count();
+            total += tags.len();
+        }
+        assert!(correct as f64 / total as f64 > 0.95, "acurácia de treino {correct}/{total}");
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = std::env::temp_dir().join(format!("ner_pos_{}.json", std::process::id()));
+        let tagger = builtin_tagger();
+        tagger.save(&path).unwrap();
+        let loaded = PosTagger::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let words = ["O", "Senado", "aprovou", "a", "lei", "."];
+        assert_eq!(loaded.tag(&words), tagger.tag(&words));
+    }
+}
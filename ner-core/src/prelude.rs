@@ -0,0 +1,19 @@
+//! # Prelúdio
+//!
+//! Reexporta os tipos usados no dia a dia, para que scripts e exemplos precisem de uma
+//! única linha de import:
+//!
+//! ```rust
+//! use ner_core::prelude::*;
+//!
+//! let pipeline = NerPipeline::new();
+//! let (_, entities) = pipeline.analyze_with_mode("o Brasil venceu.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//! assert_eq!(entities[0].category, EntityCategory::Loc);
+//! ```
+
+pub use crate::extract_entities;
+pub use crate::language::LanguagePack;
+pub use crate::overlay::EntityFilter;
+pub use crate::pipeline::{AlgorithmMode, AnalysisOptions, NerPipeline, PipelineEvent};
+pub use crate::tagger::{EntityCategory, EntitySpan, Tag, TaggedToken};
+pub use crate::tokenizer::{Token, TokenizerMode};
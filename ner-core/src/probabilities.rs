@@ -0,0 +1,211 @@
+//! # Tabelas de Probabilidade por Token
+//!
+//! Para analisar erros e calibração fora do Rust (pandas, R, planilhas) é mais
+//! prático ter uma tabela plana do que eventos de streaming. [`token_probabilities`]
+//! gera uma linha por token com:
+//!
+//! - o token e seus offsets de byte;
+//! - a tag ouro, se conhecida ([`attach_gold`]);
+//! - a tag prevista pelo CRF (melhor sequência do Viterbi);
+//! - a probabilidade de **cada uma** das tags do CRF (as 13 embutidas e as das categorias
+//!   personalizadas do modelo), a posterior por forward-backward
+//!   ([`crate::viterbi::marginals`], a mesma "confiança" que o pipeline usa).
+//!
+//! [`write_csv`] grava a tabela em CSV (RFC 4180), com uma coluna `p_<TAG>` por tag:
+//!
+//! ```text
+//! token_index,token,start,end,gold,predicted,p_O,p_B-PER,p_I-PER,...
+//! 0,Lula,0,4,B-PER,B-PER,0.012,0.941,0.003,...
+//! ```
+//!
+//! No pandas: `pd.read_csv("tokens.csv", keep_default_na=False)` (a coluna `gold`
+//! vem vazia quando não há anotação). Não há exportação para Parquet: o formato
+//! exige dependências (`arrow`/`parquet`) que o crate não traz; converta o CSV com
+//! `df.to_parquet(...)` se precisar.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::probabilities::{attach_gold, write_csv};
+//! use ner_core::tagger::{EntityCategory, Tag};
+//! use ner_core::{NerPipeline, TokenizerMode};
+//!
+//! let pipeline = NerPipeline::new();
+//! let mut rows = pipeline.token_probabilities("Lula visitou Recife.", TokenizerMode::Standard);
+//! let gold = vec![Tag::Begin(EntityCategory::Per), Tag::Outside, Tag::Begin(EntityCategory::Loc), Tag::Outside];
+//! attach_gold(&mut rows, &gold).unwrap();
+//!
+//! let mut csv = Vec::new();
+//! write_csv(&mut csv, &rows).unwrap();
+//! let csv = String::from_utf8(csv).unwrap();
+//! assert!(csv.starts_with("token_index,token,start,end,gold,predicted,p_O,p_B-PER"));
+//! assert_eq!(csv.lines().count(), 1 + rows.len());
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crf::CrfModel;
+use crate::features::FeatureVector;
+use crate::tagger::Tag;
+use crate::tokenizer::Token;
+use crate::viterbi::{marginals, viterbi_decode};
+
+/// Uma linha da tabela: um token com a distribuição de probabilidade sobre as tags.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenProbabilities {
+    pub token_index: usize,
+    pub token: String,
+    /// Offsets de byte do token, `end` exclusivo.
+    pub start: usize,
+    pub end: usize,
+    pub gold: Option<Tag>,
+    pub predicted: Tag,
+    /// Probabilidade de cada tag, na ordem de `tags` (soma 1).
+    pub probabilities: Vec<f64>,
+    /// Tags do CRF que gerou a linha ([`CrfModel::tags`]).
+    pub tags: Vec<Tag>,
+}
+
+impl TokenProbabilities {
+    /// Probabilidade atribuída a `tag` (0 se o modelo não conhece a tag).
+    pub fn probability(&self, tag: &Tag) -> f64 {
+        self.tags.iter().position(|t| t == tag).and_then(|i| self.probabilities.get(i)).copied().unwrap_or(0.0)
+    }
+}
+
+/// A sequência ouro não tem uma tag por token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldLengthMismatch {
+    pub tokens: usize,
+    pub gold: usize,
+}
+
+impl fmt::Display for GoldLengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tags ouro para {} tokens", self.gold, self.tokens)
+    }
+}
+
+impl std::error::Error for GoldLengthMismatch {}
+
+/// Decodifica `features` com o CRF e monta uma linha por token.
+///
+/// `features` deve ter um vetor por token (ver [`crate::features::extract_features`]).
+pub fn token_probabilities(model: &CrfModel, tokens: &[Token], features: &[FeatureVector]) -> Vec<TokenProbabilities> {
+    let result = viterbi_decode(model, features);
+    let posteriors = marginals(model, features);
+    let tags = model.tags();
+    tokens
+        .iter()
+        .zip(posteriors)
+        .zip(&result.best_sequence)
+        .enumerate()
+        .map(|(i, ((token, probabilities), predicted))| {
+            TokenProbabilities {
+                token_index: i,
+                token: token.text.clone(),
+                start: token.start,
+                end: token.end,
+                gold: None,
+                predicted: predicted.clone(),
+                probabilities,
+                tags: tags.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Preenche a tag ouro de cada linha; `gold` precisa ter exatamente uma tag por linha.
+pub fn attach_gold(rows: &mut [TokenProbabilities], gold: &[Tag]) -> Result<(), GoldLengthMismatch> {
+    if rows.len() != gold.len() {
+        return Err(GoldLengthMismatch { tokens: rows.len(), gold: gold.len() });
+    }
+    for (row, tag) in rows.iter_mut().zip(gold) {
+        row.gold = Some(tag.clone());
+    }
+    Ok(())
+}
+
+/// Grava `rows` como CSV com cabeçalho (ver o módulo). Sem ouro, a coluna `gold` fica vazia.
+///
+/// As colunas `p_<TAG>` são as tags da primeira linha: todas as linhas devem vir do mesmo modelo.
+pub fn write_csv<W: Write>(mut writer: W, rows: &[TokenProbabilities]) -> io::Result<()> {
+    let tags = rows.first().map_or_else(|| Tag::all().to_vec(), |row| row.tags.clone());
+    let mut header = String::from("token_index,token,start,end,gold,predicted");
+    for tag in &tags {
+        header.push_str(",p_");
+        header.push_str(&tag.label());
+    }
+    writeln!(writer, "{header}")?;
+
+    for row in rows {
+        let gold = row.gold.as_ref().map(Tag::label).unwrap_or_default();
+        write!(
+            writer,
+            "{},{},{},{},{},{}",
+            row.token_index,
+            csv_field(&row.token),
+            row.start,
+            row.end,
+            gold,
+            row.predicted.label()
+        )?;
+        for tag in &tags {
+            write!(writer, ",{:.6}", row.probability(tag))?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Campo CSV: entre aspas (com aspas dobradas) se tiver vírgula, aspas ou quebra de linha.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::extract_features;
+    use crate::model::NerModel;
+    use crate::tagger::EntityCategory;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_probability_table_and_csv() {
+        let text = "Ana disse \"oi\", e saiu.";
+        let tokens = tokenize(text);
+        let model = NerModel::default();
+        let features = extract_features(&tokens, model.gazetteers_ref());
+        let mut rows = token_probabilities(&model.crf, &tokens, &features);
+
+        assert_eq!(rows.len(), tokens.len());
+        for row in &rows {
+            assert_eq!(row.probabilities.len(), Tag::all().len());
+            assert!((row.probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert_eq!(&text[row.start..row.end], row.token);
+        }
+
+        let mut gold = vec![Tag::Outside; tokens.len()];
+        gold[0] = Tag::Begin(EntityCategory::Per);
+        assert_eq!(attach_gold(&mut rows, &gold[1..]), Err(GoldLengthMismatch { tokens: tokens.len(), gold: tokens.len() - 1 }));
+        attach_gold(&mut rows, &gold).unwrap();
+
+        let mut out = Vec::new();
+        write_csv(&mut out, &rows).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + tokens.len());
+        assert_eq!(lines[0].split(',').count(), 6 + 13);
+        assert!(lines[1].starts_with(&format!("0,Ana,0,3,B-PER,{},", rows[0].predicted.label())));
+        // Aspas e vírgulas no token são escapadas
+        assert!(csv.contains(",\"\"\"\",") && csv.contains(",\",\","));
+    }
+}
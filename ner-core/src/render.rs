@@ -0,0 +1,265 @@
+//! # Renderização do Resultado em Texto
+//!
+//! Nem todo cliente quer JSON: uma planilha importa colunas separadas por tabulação,
+//! um navegador mostra HTML direto. Este módulo transforma o resultado do pipeline em:
+//!
+//! - **CoNLL** ([`to_conll`]): um token por linha, `token<TAB>tag`, com uma linha em
+//!   branco entre sentenças — o mesmo formato lido por [`crate::corpus_reader`]. Com
+//!   [`to_conll_mapped`], as tags saem em outro padrão (OntoNotes, CoNLL-2003).
+//! - **CoNLL para avaliação** ([`to_conll_eval`], [`to_conll_gold`]): `token<TAB>ouro<TAB>predito`,
+//!   a entrada do `conlleval` e de outras ferramentas de avaliação padrão. Entidades por
+//!   offsets (as do pipeline, ou de outro sistema) viram tags BIO com [`span_tags`].
+//! - **HTML** ([`to_html`]): o texto (escapado) com cada entidade envolvida em
+//!   `<mark class="ent-org" data-category="ORG">`, as mesmas classes usadas pela interface.
+//! - **Texto anotado** ([`to_inline`]): o texto original com as entidades entre
+//!   colchetes seguidas da categoria, `[Brasil]{LOC}`, legível no terminal e fácil de
+//!   filtrar com `grep`.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::render::{to_conll, to_html};
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let text = "O Brasil venceu.";
+//! let (tagged, entities) = NerPipeline::new().analyze_with_mode(text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!
+//! assert_eq!(to_conll(&tagged), "O\tO\nBrasil\tB-LOC\nvenceu\tO\n.\tO\n");
+//! assert_eq!(
+//!     to_html(text, &entities),
+//!     r#"O <mark class="ent-loc" data-category="LOC">Brasil</mark> venceu."#
+//! );
+//! assert_eq!(ner_core::render::to_inline(text, &entities), "O [Brasil]{LOC} venceu.");
+//! ```
+
+use crate::diff::GoldEntity;
+use crate::label_map::LabelMapping;
+use crate::offsets::slice_lossy;
+use crate::tagger::{EntityCategory, EntitySpan, Tag, TaggedToken};
+use crate::tokenizer::{sentence_ranges, Token};
+
+/// Marcação de abertura do destaque de uma entidade da categoria.
+pub fn mark_open(category: EntityCategory) -> String {
+    let name = category.name();
+    format!(r#"<mark class="ent-{}" data-category="{}">"#, name.to_lowercase(), name)
+}
+
+/// Tokens e tags em CoNLL (`token<TAB>tag`), sentenças separadas por linha em branco.
+pub fn to_conll(tagged: &[TaggedToken]) -> String {
+    to_conll_mapped(tagged, &LabelMapping::new())
+}
+
+/// Como [`to_conll`], com as tags traduzidas para outro padrão de anotação pelo `mapping`
+/// (ex: `B-LOC` → `B-GPE` com [`LabelMapping::ontonotes`]).
+pub fn to_conll_mapped(tagged: &[TaggedToken], mapping: &LabelMapping) -> String {
+    write_conll(tagged, |_, t, out| out.push_str(&mapping.export_tag(&t.tag.label())))
+}
+
+/// Três colunas, `token<TAB>ouro<TAB>predito`, como espera o `conlleval`: `gold` traz a
+/// tag correta de cada token de `tagged` (alinhada por posição; o que faltar conta como `O`).
+///
+/// ```rust
+/// use ner_core::render::to_conll_eval;
+/// use ner_core::{AlgorithmMode, NerPipeline, Tag, TokenizerMode};
+/// use ner_core::tagger::EntityCategory;
+///
+/// let (tagged, _) = NerPipeline::new().analyze_with_mode("O Brasil venceu.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+/// let gold = [Tag::Outside, Tag::Begin(EntityCategory::Loc), Tag::Outside, Tag::Outside];
+/// assert_eq!(to_conll_eval(&tagged, &gold), "O\tO\tO\nBrasil\tB-LOC\tB-LOC\nvenceu\tO\tO\n.\tO\tO\n");
+/// ```
+pub fn to_conll_eval(tagged: &[TaggedToken], gold: &[Tag]) -> String {
+    write_conll(tagged, |i, t, out| {
+        out.push_str(&gold.get(i).unwrap_or(&Tag::Outside).label());
+        out.push('\t');
+        out.push_str(&t.tag.label());
+    })
+}
+
+/// Como [`to_conll_eval`], com o ouro dado por entidades anotadas (offsets de byte no
+/// texto analisado) em vez de tags por token.
+pub fn to_conll_gold(tagged: &[TaggedToken], gold: &[GoldEntity]) -> String {
+    let tokens: Vec<Token> = tagged.iter().map(|t| t.token.clone()).collect();
+    let gold_tags = span_tags(&tokens, gold.iter().map(|g| (g.start, g.end, g.category)));
+    to_conll_eval(tagged, &gold_tags)
+}
+
+/// Tags BIO de `tokens` para entidades dadas por offsets de byte (`start`, `end`
+/// exclusivo, categoria): `B-` no primeiro token que começa dentro do trecho, `I-` nos
+/// seguintes e `O` fora de todos. Aceita tanto [`EntitySpan`] quanto [`GoldEntity`] ou
+/// entidades de outro sistema.
+pub fn span_tags(tokens: &[Token], spans: impl IntoIterator<Item = (usize, usize, EntityCategory)>) -> Vec<Tag> {
+    let mut tags = vec![Tag::Outside; tokens.len()];
+    for (start, end, category) in spans {
+        let mut inside = tokens.iter().enumerate().filter(|(_, t)| t.start >= start && t.start < end);
+        if let Some((first, _)) = inside.next() {
+            tags[first] = Tag::Begin(category);
+            for (i, _) in inside {
+                tags[i] = Tag::Inside(category);
+            }
+        }
+    }
+    tags
+}
+
+/// Uma linha por token (`token<TAB>colunas`), sentenças separadas por linha em branco;
+/// `columns` escreve o resto da linha do token de índice `i`.
+fn write_conll(tagged: &[TaggedToken], mut columns: impl FnMut(usize, &TaggedToken, &mut String)) -> String {
+    let tokens: Vec<Token> = tagged.iter().map(|t| t.token.clone()).collect();
+    let mut out = String::new();
+    for (i, range) in sentence_ranges(&tokens).into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for j in range {
+            let t = &tagged[j];
+            // Espaços dentro do token (modo sem tokenização) quebrariam as colunas
+            let word: String = t.token.text.chars().map(|c| if c.is_whitespace() { '_' } else { c }).collect();
+            out.push_str(&word);
+            out.push('\t');
+            columns(j, t, &mut out);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `text` escapado para HTML, com as `entities` destacadas (ver [`mark_open`]).
+///
+/// Entidades sobrepostas a uma anterior são ignoradas, para a marcação continuar bem aninhada.
+pub fn to_html(text: &str, entities: &[EntitySpan]) -> String {
+    let mut out = String::with_capacity(text.len() + entities.len() * 64);
+    let mut copied = 0;
+    for entity in nested(text, entities) {
+        out.push_str(&escape_html(slice_lossy(text, copied, entity.start)));
+        out.push_str(&mark_open(entity.category));
+        out.push_str(&escape_html(slice_lossy(text, entity.start, entity.end)));
+        out.push_str("</mark>");
+        copied = entity.end;
+    }
+    out.push_str(&escape_html(slice_lossy(text, copied, text.len())));
+    out
+}
+
+/// `text` com as `entities` marcadas como `[trecho]{CAT}`, sem escapar nada.
+///
+/// Entidades sobrepostas a uma anterior são ignoradas, como em [`to_html`].
+pub fn to_inline(text: &str, entities: &[EntitySpan]) -> String {
+    let mut out = String::with_capacity(text.len() + entities.len() * 8);
+    let mut copied = 0;
+    for entity in nested(text, entities) {
+        out.push_str(slice_lossy(text, copied, entity.start));
+        out.push('[');
+        out.push_str(slice_lossy(text, entity.start, entity.end));
+        out.push_str("]{");
+        out.push_str(entity.category.name());
+        out.push('}');
+        copied = entity.end;
+    }
+    out.push_str(slice_lossy(text, copied, text.len()));
+    out
+}
+
+/// Entidades válidas em `text`, na ordem do texto, sem as que se sobrepõem a uma anterior.
+pub(crate) fn nested<'a>(text: &str, entities: &'a [EntitySpan]) -> Vec<&'a EntitySpan> {
+    let mut sorted: Vec<&EntitySpan> = entities.iter().collect();
+    sorted.sort_by_key(|e| (e.start, std::cmp::Reverse(e.end)));
+    let mut kept = Vec::with_capacity(sorted.len());
+    let mut end = 0;
+    for entity in sorted {
+        if entity.start < end || entity.end > text.len() || entity.start >= entity.end {
+            continue;
+        }
+        end = entity.end;
+        kept.push(entity);
+    }
+    kept
+}
+
+/// Escapa `& < > " '` para HTML.
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagger::{tokens_to_spans, Tag};
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_conll_and_html_rendering() {
+        let text = "Ana <b> viu a Petrobras. Depois saiu & voltou.";
+        let tagged: Vec<TaggedToken> = tokenize(text)
+            .into_iter()
+            .map(|token| {
+                let tag = match token.text.as_str() {
+                    "Ana" => Tag::Begin(EntityCategory::Per),
+                    "Petrobras" => Tag::Begin(EntityCategory::Org),
+                    _ => Tag::Outside,
+                };
+                TaggedToken { token, tag, confidence: 1.0 }
+            })
+            .collect();
+
+        let conll = to_conll(&tagged);
+        let sentences: Vec<&str> = conll.split("\n\n").collect();
+        assert_eq!(sentences.len(), 2);
+        assert!(sentences[0].starts_with("Ana\tB-PER\n"));
+        assert!(sentences[0].ends_with("Petrobras\tB-ORG\n.\tO"));
+        let onto = to_conll_mapped(&tagged, &LabelMapping::ontonotes().with_export("ORG", "O"));
+        assert!(onto.starts_with("Ana\tB-PERSON\n") && onto.contains("Petrobras\tO\n"));
+
+        let entities = tokens_to_spans(&tagged, text);
+        let html = to_html(text, &entities);
+        assert!(html.starts_with(r#"<mark class="ent-per" data-category="PER">Ana</mark> &lt;b&gt;"#));
+        assert!(html.contains(r#"<mark class="ent-org" data-category="ORG">Petrobras</mark>."#));
+        assert!(html.ends_with("saiu &amp; voltou."));
+        let inline = to_inline(text, &entities);
+        assert_eq!(inline, "[Ana]{PER} <b> viu a [Petrobras]{ORG}. Depois saiu & voltou.");
+
+        // Sobreposta à primeira: ignorada
+        let mut overlapping = entities.clone();
+        overlapping.push(EntitySpan { start: 1, end: 8, ..entities[0].clone() });
+        assert_eq!(to_html(text, &overlapping), html);
+        assert_eq!(to_inline(text, &overlapping), inline);
+    }
+
+    #[test]
+    fn test_conll_eval_columns() {
+        let text = "Ana mora em São Paulo. Saiu.";
+        let tokens = tokenize(text);
+        // Predição erra a categoria de Ana e só pega "São"
+        let predicted = span_tags(&tokens, [(0, 3, EntityCategory::Org), (12, 16, EntityCategory::Loc)]);
+        let tagged: Vec<TaggedToken> = tokens
+            .into_iter()
+            .zip(predicted)
+            .map(|(token, tag)| TaggedToken { token, tag, confidence: 1.0 })
+            .collect();
+
+        let gold = [
+            GoldEntity::find(text, "Ana", EntityCategory::Per).unwrap(),
+            GoldEntity::find(text, "São Paulo", EntityCategory::Loc).unwrap(),
+        ];
+        let conll = to_conll_gold(&tagged, &gold);
+        assert_eq!(
+            conll,
+            "Ana\tB-PER\tB-ORG\nmora\tO\tO\nem\tO\tO\nSão\tB-LOC\tB-LOC\nPaulo\tI-LOC\tO\n.\tO\tO\n\nSaiu\tO\tO\n.\tO\tO\n"
+        );
+        // Cada linha tem exatamente três colunas
+        assert!(conll.lines().filter(|l| !l.is_empty()).all(|l| l.split('\t').count() == 3));
+
+        // Ouro mais curto que os tokens: o resto conta como `O`
+        assert!(to_conll_eval(&tagged, &[Tag::Begin(EntityCategory::Per)]).ends_with("Saiu\tO\tO\n.\tO\tO\n"));
+    }
+}
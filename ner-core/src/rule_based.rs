@@ -2,17 +2,86 @@
 //!
 //! Um motor de regras complementa o CRF com conhecimento explícito:
 //! listas de entidades conhecidas (gazetteers) e expressões regulares
-//! para padrões como CPF, CNPJ, datas e siglas.
+//! para padrões como CPF, CNPJ, horários e siglas.
 //!
 //! ## Por que combinar regras e CRF?
 //!
 //! O CRF aprende padrões estatísticos do corpus, mas pode ter dificuldade
 //! com entidades raras ou novas. As regras garantem alta precisão para
 //! padrões bem definidos (ex: "CNPJ 12.345.678/0001-90" sempre é ORG).
+//!
+//! Citações legais ("art. 5º, §2º, da Lei nº 8.078/1990") são reconhecidas pela regra
+//! embutida `law_ref`. Como [`EntityCategory`] é fechada, essas referências (LAW_REF)
+//! são emitidas como MISC; filtre por `rule_name == "law_ref"` para isolá-las.
+//!
+//! ## Priors e homógrafos
+//!
+//! Uma mesma frase pode estar em mais de um gazetteer ("São Paulo" cidade e clube).
+//! Com [`RuleEngine::set_prior`], cada entrada ganha um peso em `[0, 1]`: a confiança da
+//! regra é multiplicada por ele e, entre as leituras possíveis de uma frase, vence a de
+//! maior prior. Sem priors (todos iguais), vale a ordem de prioridade de [`RuleEngine::apply`].
+//!
+//! ## Nomes de organização com intercalações
+//!
+//! "Banco Central (BC) do Brasil" não casa exatamente com a entrada "Banco Central do
+//! Brasil". Com [`RuleEngine::set_gap_tolerance`], o gazetteer de organizações aceita até
+//! `max_gap` tokens intercalados (por padrão, só entre parênteses); a entidade cobre o
+//! trecho inteiro e a confiança cai por token intercalado ([`GapTolerance`]):
+//!
+//! ```rust
+//! use ner_core::rule_based::{GapTolerance, RuleEngine};
+//! use ner_core::tagger::{EntityCategory, Tag};
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let mut engine = RuleEngine::new();
+//! engine.add_org("Banco Central do Brasil");
+//! let tokens = tokenize("O Banco Central (BC) do Brasil manteve a taxa");
+//! assert!(engine.apply(&tokens)[1].is_none());
+//!
+//! engine.set_gap_tolerance(GapTolerance::parenthetical(1));
+//! let matches = engine.apply(&tokens);
+//! assert_eq!(matches[1].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Org));
+//! assert!(matches[2..=7].iter().all(|m| m.as_ref().unwrap().tag == Tag::Inside(EntityCategory::Org)));
+//! assert!((matches[1].as_ref().unwrap().confidence - 0.93 * 0.9).abs() < 1e-9);
+//! ```
+//!
+//! Para tokens que chegam aos poucos (ex: transcrições ao vivo), [`RuleEngine::session`]
+//! avalia as regras incrementalmente (ver [`RuleEngineSession`]).
+//!
+//! ## Regras declarativas
+//!
+//! Formatos fixos (processos judiciais, telefones, e-mails, valores) são declarados
+//! como expressões regulares com [`RuleEngine::add_regex_rule`]. Regras de múltiplos
+//! tokens baseadas em palavras são escritas como padrões ([`crate::token_pattern`]):
+//!
+//! ```rust
+//! use ner_core::rule_based::RuleEngine;
+//! use ner_core::tagger::{EntityCategory, Tag};
+//! use ner_core::tokenizer::tokenize;
+//!
+//! let mut engine = RuleEngine::new();
+//! engine.add_word_class("cargo", &["reitor", "reitora"]);
+//! engine.add_pattern("reitor_pattern", "[cargo] ([Cap]+)", EntityCategory::Per, 0.8).unwrap();
+//!
+//! let matches = engine.apply(&tokenize("a reitora Ana Souza discursou"));
+//! assert_eq!(matches[2].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Per));
+//! assert_eq!(matches[3].as_ref().unwrap().tag, Tag::Inside(EntityCategory::Per));
+//! ```
 
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use regex::Regex;
+use regex_automata::hybrid::dfa::DFA as LazyDfa;
+use regex_automata::{Anchored, Input};
 use serde::{Deserialize, Serialize};
 
+use crate::brazil::is_uf_code;
+use crate::categories::CustomCategory;
+use crate::features::{is_time_expression, GazetteerKey, GazetteerLookups, DEFAULT_GAZETTEER_PRIOR};
+use crate::language::LanguagePack;
 use crate::tagger::{EntityCategory, Tag};
+use crate::token_pattern::{PatternError, TokenPattern};
 use crate::tokenizer::Token;
 
 /// Uma correspondência de regra: qual token foi marcado e com qual tag
@@ -22,6 +91,419 @@ pub struct RuleMatch {
     pub tag: Tag,
     pub rule_name: String,
     pub confidence: f64,
+    /// Chave do gazetteer casada quando o token traz uma grafia variante dela
+    /// ("Sam" → `Some("são")`, ver [`crate::variants`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_of: Option<String>,
+}
+
+/// Contadores de uma regra (em tokens).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCounter {
+    /// Tokens marcados pela regra.
+    pub fired: u64,
+    /// Tokens em que a tag final do pipeline difere da tag da regra.
+    pub overridden: u64,
+    /// Tokens em que o modelo estatístico (CRF) previa outra tag.
+    pub disagreed: u64,
+}
+
+/// Estatísticas de disparo das regras, por análise ou acumuladas.
+///
+/// Regras com muitos disparos sobrescritos ou em desacordo com o modelo são
+/// candidatas a poda ou a ter sua confiança recalibrada.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleStats {
+    /// Número de análises agregadas.
+    pub analyses: u64,
+    /// Contadores por nome de regra (ordem alfabética).
+    pub rules: BTreeMap<String, RuleCounter>,
+}
+
+impl RuleStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra um disparo de regra e se ele foi sobrescrito / contestado pelo modelo.
+    pub fn record(&mut self, rule_name: &str, overridden: bool, disagreed: bool) {
+        let counter = self.rules.entry(rule_name.to_string()).or_default();
+        counter.fired += 1;
+        counter.overridden += overridden as u64;
+        counter.disagreed += disagreed as u64;
+    }
+
+    /// Soma outras estatísticas a estas (agregação entre análises).
+    pub fn merge(&mut self, other: &RuleStats) {
+        self.analyses += other.analyses;
+        for (name, c) in &other.rules {
+            let counter = self.rules.entry(name.clone()).or_default();
+            counter.fired += c.fired;
+            counter.overridden += c.overridden;
+            counter.disagreed += c.disagreed;
+        }
+    }
+
+    /// Total de disparos de todas as regras.
+    pub fn total_fired(&self) -> u64 {
+        self.rules.values().map(|c| c.fired).sum()
+    }
+
+    /// Fração dos disparos de uma regra que foram sobrescritos (0 se nunca disparou).
+    pub fn override_rate(&self, rule_name: &str) -> f64 {
+        match self.rules.get(rule_name) {
+            Some(c) if c.fired > 0 => c.overridden as f64 / c.fired as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Grupo de regras que pode ser ligado ou desligado por análise (ver [`RuleGroups`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleGroup {
+    PersonGazetteer,
+    LocationGazetteer,
+    OrgGazetteer,
+    MiscGazetteer,
+    DateGazetteer,
+    TimeGazetteer,
+    /// Gazetteers de todas as categorias personalizadas.
+    CustomGazetteers,
+    /// Citações legais (regra `law_ref`).
+    LawReferences,
+    /// Siglas de UF após um lugar (regra `uf_code`).
+    UfCodes,
+    /// Padrão embutido `title_pattern` ("presidente Lula").
+    TitlePatterns,
+    /// Padrão embutido `org_suffix_pattern` ("Acme Ltda").
+    OrgSuffixes,
+    /// Padrões de tokens do usuário ([`RuleEngine::add_pattern`]).
+    TokenPatterns,
+    /// Regex embutidas (CNPJ, horários) e do usuário ([`RuleEngine::add_regex_rule`]).
+    RegexPatterns,
+}
+
+impl RuleGroup {
+    pub const ALL: [RuleGroup; 13] = [
+        RuleGroup::PersonGazetteer,
+        RuleGroup::LocationGazetteer,
+        RuleGroup::OrgGazetteer,
+        RuleGroup::MiscGazetteer,
+        RuleGroup::DateGazetteer,
+        RuleGroup::TimeGazetteer,
+        RuleGroup::CustomGazetteers,
+        RuleGroup::LawReferences,
+        RuleGroup::UfCodes,
+        RuleGroup::TitlePatterns,
+        RuleGroup::OrgSuffixes,
+        RuleGroup::TokenPatterns,
+        RuleGroup::RegexPatterns,
+    ];
+
+    /// Grupo do gazetteer da categoria.
+    pub fn gazetteer(category: EntityCategory) -> RuleGroup {
+        match category {
+            EntityCategory::Per => RuleGroup::PersonGazetteer,
+            EntityCategory::Loc => RuleGroup::LocationGazetteer,
+            EntityCategory::Org => RuleGroup::OrgGazetteer,
+            EntityCategory::Misc => RuleGroup::MiscGazetteer,
+            EntityCategory::Date => RuleGroup::DateGazetteer,
+            EntityCategory::Time => RuleGroup::TimeGazetteer,
+            EntityCategory::Custom(_) => RuleGroup::CustomGazetteers,
+        }
+    }
+
+    /// Grupo de uma regra de padrão de tokens, pelo nome.
+    fn of_pattern(name: &str) -> RuleGroup {
+        match name {
+            "title_pattern" => RuleGroup::TitlePatterns,
+            "org_suffix_pattern" => RuleGroup::OrgSuffixes,
+            _ => RuleGroup::TokenPatterns,
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Conjunto de grupos de regras ativos em uma análise. O padrão liga todos.
+///
+/// Serializado como a lista dos grupos ativos (`["person_gazetteer", "uf_code", ...]`).
+///
+/// ```rust
+/// use ner_core::rule_based::{RuleEngine, RuleGroup, RuleGroups};
+/// use ner_core::features::GazetteerLookups;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let mut engine = RuleEngine::new();
+/// engine.add_misc("Copa do Mundo");
+/// let tokens = tokenize("o presidente Lula viu a Copa do Mundo");
+/// let lookups = GazetteerLookups::keys_only(&tokens);
+///
+/// let all = engine.apply_selected(&tokens, &lookups, RuleGroups::all());
+/// assert!(all[2].is_some() && all[5].is_some());
+///
+/// // Sem o gazetteer de MISC, só o padrão de título dispara
+/// let groups = RuleGroups::all().without(RuleGroup::MiscGazetteer);
+/// let matches = engine.apply_selected(&tokens, &lookups, groups);
+/// assert_eq!(matches[2].as_ref().unwrap().rule_name, "title_pattern");
+/// assert!(matches[5].is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "Vec<RuleGroup>", into = "Vec<RuleGroup>")]
+pub struct RuleGroups(u16);
+
+impl RuleGroups {
+    pub fn all() -> Self {
+        RuleGroup::ALL.into_iter().collect()
+    }
+
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    /// Apenas os grupos dados (ex: medir o efeito isolado de cada um).
+    pub fn only(groups: &[RuleGroup]) -> Self {
+        groups.iter().copied().collect()
+    }
+
+    pub fn with(self, group: RuleGroup) -> Self {
+        Self(self.0 | group.bit())
+    }
+
+    pub fn without(self, group: RuleGroup) -> Self {
+        Self(self.0 & !group.bit())
+    }
+
+    pub fn contains(&self, group: RuleGroup) -> bool {
+        self.0 & group.bit() != 0
+    }
+
+    /// Grupos ativos, na ordem de [`RuleGroup::ALL`].
+    pub fn iter(&self) -> impl Iterator<Item = RuleGroup> + '_ {
+        RuleGroup::ALL.into_iter().filter(|&group| self.contains(group))
+    }
+}
+
+impl Default for RuleGroups {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl FromIterator<RuleGroup> for RuleGroups {
+    fn from_iter<I: IntoIterator<Item = RuleGroup>>(groups: I) -> Self {
+        groups.into_iter().fold(Self::none(), Self::with)
+    }
+}
+
+impl From<Vec<RuleGroup>> for RuleGroups {
+    fn from(groups: Vec<RuleGroup>) -> Self {
+        groups.into_iter().collect()
+    }
+}
+
+impl From<RuleGroups> for Vec<RuleGroup> {
+    fn from(groups: RuleGroups) -> Self {
+        groups.iter().collect()
+    }
+}
+
+/// Uma regra declarativa: padrão de tokens + categoria atribuída ao grupo capturado.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternRule {
+    pub name: String,
+    pub pattern: TokenPattern,
+    pub category: EntityCategory,
+    pub confidence: f64,
+}
+
+/// Uma regra de expressão regular: trechos do texto que casam com ela viram entidades da
+/// categoria (ver [`RuleEngine::add_regex_rule`]).
+///
+/// Gravada como o texto do padrão e recompilada ao ser lida.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RegexRuleSpec", into = "RegexRuleSpec")]
+pub struct RegexRule {
+    pub name: String,
+    pub category: EntityCategory,
+    pub confidence: f64,
+    regex: Regex,
+    /// DFA preguiçoso do mesmo padrão, para saber se um prefixo ainda pode casar.
+    prefix_dfa: LazyDfa,
+}
+
+/// Forma serializada de [`RegexRule`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "RegexRule")]
+struct RegexRuleSpec {
+    name: String,
+    pattern: String,
+    category: EntityCategory,
+    confidence: f64,
+}
+
+impl TryFrom<RegexRuleSpec> for RegexRule {
+    type Error = regex::Error;
+
+    fn try_from(spec: RegexRuleSpec) -> Result<Self, Self::Error> {
+        Self::compile(&spec.name, &spec.pattern, spec.category, spec.confidence)
+    }
+}
+
+impl From<RegexRule> for RegexRuleSpec {
+    fn from(rule: RegexRule) -> Self {
+        Self { pattern: rule.regex.as_str().to_string(), name: rule.name, category: rule.category, confidence: rule.confidence }
+    }
+}
+
+impl RegexRule {
+    fn compile(name: &str, pattern: &str, category: EntityCategory, confidence: f64) -> Result<Self, regex::Error> {
+        let regex = Regex::new(pattern)?;
+        // O `regex` já validou o padrão; o DFA só falha por limites de tamanho
+        let prefix_dfa = LazyDfa::new(pattern).map_err(|e| regex::Error::Syntax(e.to_string()))?;
+        Ok(Self { name: name.to_string(), category, confidence, regex, prefix_dfa })
+    }
+
+    /// Um casamento começando em `start` (byte de `text`) ainda é possível se o texto
+    /// continuar? Verdadeiro também para casamentos completos que podem crescer.
+    fn may_continue(&self, text: &str, start: usize) -> bool {
+        let dfa = &self.prefix_dfa;
+        let mut cache = dfa.create_cache();
+        let input = Input::new(&text[start..]).anchored(Anchored::Yes);
+        let Ok(mut state) = dfa.start_state_forward(&mut cache, &input) else {
+            return true;
+        };
+        for &byte in &text.as_bytes()[start..] {
+            match dfa.next_state(&mut cache, state, byte) {
+                Ok(next) if next.is_dead() => return false,
+                Ok(next) if next.is_quit() => return true,
+                Ok(next) => state = next,
+                // Na dúvida, o token fica pendente
+                Err(_) => return true,
+            }
+        }
+        true
+    }
+
+    /// O texto da expressão regular.
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Faixas de tokens `[início, fim)` casadas em `surface`. Com grupo de captura, vale o
+    /// grupo 1. Casamentos vazios ou que não começam e terminam em fronteiras de token
+    /// são descartados.
+    fn token_spans(&self, surface: &Surface) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        for captures in self.regex.captures_iter(&surface.text) {
+            let Some(m) = captures.get(1).or_else(|| captures.get(0)) else {
+                continue;
+            };
+            if let Some(span) = surface.token_range(m.start(), m.end()) {
+                spans.push(span);
+            }
+        }
+        spans
+    }
+}
+
+/// Texto reconstituído a partir dos tokens, para as regras de expressão regular.
+///
+/// Tokens colados no original ("R$" e "1.500") continuam colados; os demais ficam
+/// separados por um espaço, qualquer que fosse o espaçamento original.
+struct Surface {
+    text: String,
+    /// Faixa de cada token em `text`.
+    ranges: Vec<Range<usize>>,
+}
+
+impl Surface {
+    fn new(tokens: &[Token]) -> Self {
+        let mut text = String::new();
+        let mut ranges = Vec::with_capacity(tokens.len());
+        for (i, token) in tokens.iter().enumerate() {
+            let adjacent = i > 0 && {
+                let prev = &tokens[i - 1];
+                prev.end == token.start && prev.end > prev.start
+            };
+            if i > 0 && !adjacent {
+                text.push(' ');
+            }
+            let start = text.len();
+            text.push_str(&token.text);
+            ranges.push(start..text.len());
+        }
+        Self { text, ranges }
+    }
+
+    /// Tokens cobertos exatamente pelos bytes `[start, end)` de `text`.
+    fn token_range(&self, start: usize, end: usize) -> Option<Range<usize>> {
+        if start == end {
+            return None;
+        }
+        let first = self.ranges.binary_search_by_key(&start, |r| r.start).ok()?;
+        let last = self.ranges.binary_search_by_key(&end, |r| r.end).ok()?;
+        (first <= last).then_some(first..last + 1)
+    }
+}
+
+/// Regra de cada gazetteer: categoria, nome da regra e confiança base (antes do prior).
+const GAZETTEER_RULES: [(EntityCategory, &str, f64); 6] = [
+    (EntityCategory::Per, "person_gazetteer", 0.92),
+    (EntityCategory::Loc, "location_gazetteer", 0.90),
+    (EntityCategory::Org, "org_gazetteer", 0.93),
+    (EntityCategory::Misc, "misc_gazetteer", 0.88),
+    (EntityCategory::Date, "date_gazetteer", 0.85),
+    (EntityCategory::Time, "time_gazetteer", 0.85),
+];
+
+/// Nome e confiança base da regra dos gazetteers de categorias personalizadas.
+const CUSTOM_GAZETTEER_RULE: (&str, f64) = ("custom_gazetteer", 0.88);
+
+/// Tolerância a tokens intercalados nos nomes do gazetteer de organizações
+/// (ver [`RuleEngine::set_gap_tolerance`]).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GapTolerance {
+    /// Máximo de tokens intercalados num casamento; 0 desliga (o padrão).
+    pub max_gap: usize,
+    /// Só aceita intercalações entre parênteses ("(BC)"); os parênteses não contam.
+    pub parenthetical_only: bool,
+    /// Fator aplicado à confiança da regra por token intercalado.
+    pub discount: f64,
+}
+
+impl Default for GapTolerance {
+    fn default() -> Self {
+        Self { max_gap: 0, parenthetical_only: true, discount: 0.9 }
+    }
+}
+
+impl GapTolerance {
+    /// Até `max_gap` tokens entre parênteses ("Banco Central (BC) do Brasil").
+    pub fn parenthetical(max_gap: usize) -> Self {
+        Self { max_gap, ..Self::default() }
+    }
+
+    /// Até `max_gap` tokens quaisquer ("Banco Central **do** do Brasil"); mais sujeito a
+    /// falsos positivos que [`GapTolerance::parenthetical`].
+    pub fn any(max_gap: usize) -> Self {
+        Self { max_gap, parenthetical_only: false, ..Self::default() }
+    }
+
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        self.discount = discount.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Leitura escolhida para uma frase de gazetteer (ver [`RuleEngine::set_prior`]).
+struct GazetteerReading {
+    category: EntityCategory,
+    rule_name: &'static str,
+    confidence: f64,
 }
 
 /// Motor de regras com gazetteers e padrões regex.
@@ -29,6 +511,7 @@ pub struct RuleMatch {
 /// Mantém listas de entidades conhecidas e padrões léxicos.
 /// É utilizado tanto para gerar features (no modelo estatístico) quanto para
 /// fazer predições diretas (no modo híbrido).
+#[derive(Serialize, Deserialize)]
 pub struct RuleEngine {
     /// Nomes de pessoas conhecidas (lowercase). Ex: "lula", "pelé".
     person_names: Vec<String>,
@@ -38,58 +521,351 @@ pub struct RuleEngine {
     org_names: Vec<Vec<String>>,
     /// Entidades miscelâneas (eventos, leis). Ex: "copa do mundo".
     misc_names: Vec<Vec<String>>,
+    /// Expressões de data (lowercase, n-gramas). Ex: "maio", "século xx".
+    date_names: Vec<Vec<String>>,
+    /// Expressões de horário (lowercase, n-gramas). Ex: "meia-noite".
+    time_names: Vec<Vec<String>>,
+    /// Gazetteers (n-gramas) das categorias personalizadas, na ordem de registro.
+    #[serde(default)]
+    custom_names: Vec<(CustomCategory, Vec<Vec<String>>)>,
     /// Títulos que frequentemente precedem nomes de pessoas. Ex: "presidente", "doutor".
     person_titles: Vec<String>,
     /// Palavras que indicam organização ao redor. Ex: "s.a.", "ltda".
     org_indicators: Vec<String>,
+    /// Classes de palavras definidas pelo usuário, usadas nos padrões (`[classe]`).
+    word_classes: HashMap<String, Vec<String>>,
+    /// Regras de padrões de tokens, aplicadas na ordem de registro.
+    patterns: Vec<PatternRule>,
+    /// Regras de expressões regulares do usuário, aplicadas na ordem de registro.
+    #[serde(default)]
+    regex_rules: Vec<RegexRule>,
+    /// Priors explícitos por categoria e chave normalizada (ver [`RuleEngine::set_prior`]).
+    priors: HashMap<EntityCategory, HashMap<String, f64>>,
+    /// Intercalações aceitas nos nomes de organização (ver [`RuleEngine::set_gap_tolerance`]).
+    #[serde(default)]
+    gap_tolerance: GapTolerance,
 }
 
 impl RuleEngine {
+    /// Motor com os títulos e sufixos societários do português do Brasil.
     pub fn new() -> Self {
-        Self {
+        Self::for_language(&LanguagePack::pt_br())
+    }
+
+    /// Motor com os títulos e sufixos societários de `pack` (sem gazetteers, que são
+    /// adicionados depois, ex: por [`crate::model::NerModel::build_for`]).
+    pub fn for_language(pack: &LanguagePack) -> Self {
+        let mut engine = Self {
             person_names: vec![],
             location_names: vec![],
             org_names: vec![],
             misc_names: vec![],
-            // Lista expandida de títulos comuns em PT-BR
-            person_titles: [
-                "presidente", "ex-presidente", "senador", "senadora", "deputado",
-                "deputada", "ministro", "ministra", "governador", "governadora",
-                "prefeito", "prefeita", "general", "capitão", "dr", "dra", "prof",
-                "profa", "vereador", "vereadora", "secretário", "secretária",
-                "diretor", "diretora", "ceo", "jogador", "jogadora", "técnico",
-                "técnica", "atleta", "ator", "atriz", "cantor", "cantora",
-            ].iter().map(|s| s.to_string()).collect(),
-            // Indicadores jurídicos de empresas
-            org_indicators: [
-                "s.a.", "s/a", "ltda", "eireli", "me", "epp", "sa", "inc",
-                "corp", "holdings", "group", "fc", "esporte", "clube",
-            ].iter().map(|s| s.to_string()).collect(),
-        }
+            date_names: vec![],
+            time_names: vec![],
+            custom_names: vec![],
+            person_titles: pack.person_titles.clone(),
+            org_indicators: pack.org_suffixes.clone(),
+            word_classes: HashMap::new(),
+            patterns: vec![],
+            regex_rules: vec![],
+            priors: HashMap::new(),
+            gap_tolerance: GapTolerance::default(),
+        };
+
+        // Padrões embutidos
+        // "Presidente X" → X é PER
+        engine
+            .add_pattern("title_pattern", "[title] ([Cap])", EntityCategory::Per, 0.80)
+            .expect("padrão embutido válido");
+        // "X S.A." → "X S.A." é ORG
+        engine
+            .add_pattern("org_suffix_pattern", "[Cap] [org_suffix]", EntityCategory::Org, 0.85)
+            .expect("padrão embutido válido");
+        engine
     }
 
     pub fn add_person(&mut self, name: &str) {
-        self.person_names.push(name.to_lowercase());
+        self.person_names.push(GazetteerKey::normalize(name));
     }
 
     pub fn add_location(&mut self, name: &str) {
-        self.location_names.push(name.to_lowercase());
+        self.location_names.push(GazetteerKey::normalize(name));
     }
 
     pub fn add_org(&mut self, name: &str) {
-        let parts: Vec<String> = name.split_whitespace().map(|p| p.to_lowercase()).collect();
+        let parts = ngram_key(name);
         if !parts.is_empty() {
             self.org_names.push(parts);
         }
     }
 
     pub fn add_misc(&mut self, name: &str) {
-        let parts: Vec<String> = name.split_whitespace().map(|p| p.to_lowercase()).collect();
+        let parts = ngram_key(name);
         if !parts.is_empty() {
             self.misc_names.push(parts);
         }
     }
 
+    pub fn add_date(&mut self, name: &str) {
+        let parts = ngram_key(name);
+        if !parts.is_empty() {
+            self.date_names.push(parts);
+        }
+    }
+
+    pub fn add_time(&mut self, name: &str) {
+        let parts = ngram_key(name);
+        if !parts.is_empty() {
+            self.time_names.push(parts);
+        }
+    }
+
+    /// Acrescenta um nome ao gazetteer de qualquer categoria, inclusive personalizada.
+    ///
+    /// Os gazetteers personalizados são de n-gramas e rodam depois dos embutidos, com a
+    /// regra `custom_gazetteer`:
+    ///
+    /// ```rust
+    /// use ner_core::categories::CategorySet;
+    /// use ner_core::rule_based::RuleEngine;
+    /// use ner_core::tagger::Tag;
+    /// use ner_core::tokenizer::tokenize;
+    ///
+    /// let legislacao = CategorySet::new().add("LEGISLACAO").unwrap();
+    /// let mut engine = RuleEngine::new();
+    /// engine.add_entry(legislacao, "Código de Defesa do Consumidor");
+    ///
+    /// let matches = engine.apply(&tokenize("Violou o código de defesa do consumidor"));
+    /// assert_eq!(matches[2].as_ref().unwrap().tag, Tag::Begin(legislacao));
+    /// assert_eq!(matches[6].as_ref().unwrap().tag, Tag::Inside(legislacao));
+    /// ```
+    pub fn add_entry(&mut self, category: EntityCategory, name: &str) {
+        match category {
+            EntityCategory::Per => self.add_person(name),
+            EntityCategory::Loc => self.add_location(name),
+            EntityCategory::Org => self.add_org(name),
+            EntityCategory::Misc => self.add_misc(name),
+            EntityCategory::Date => self.add_date(name),
+            EntityCategory::Time => self.add_time(name),
+            EntityCategory::Custom(c) => {
+                let parts = ngram_key(name);
+                if parts.is_empty() {
+                    return;
+                }
+                match self.custom_names.iter_mut().find(|(other, _)| *other == c) {
+                    Some((_, names)) => names.push(parts),
+                    None => self.custom_names.push((c, vec![parts])),
+                }
+            }
+        }
+    }
+
+    /// Categorias personalizadas com gazetteer, na ordem de registro.
+    pub fn custom_categories(&self) -> Vec<CustomCategory> {
+        self.custom_names.iter().map(|(c, _)| *c).collect()
+    }
+
+    /// Define o prior (peso em `[0, 1]`) de uma entrada de gazetteer da categoria.
+    ///
+    /// A confiança das regras de gazetteer é multiplicada pelo prior, e uma frase
+    /// presente em mais de um gazetteer recebe a categoria de maior prior:
+    ///
+    /// ```rust
+    /// use ner_core::rule_based::RuleEngine;
+    /// use ner_core::tagger::{EntityCategory, Tag};
+    /// use ner_core::tokenizer::tokenize;
+    ///
+    /// let mut engine = RuleEngine::new();
+    /// engine.add_location("São Paulo");
+    /// engine.add_org("São Paulo");
+    /// engine.set_prior(EntityCategory::Org, "São Paulo", 0.5);
+    ///
+    /// let matches = engine.apply(&tokenize("Ela mora em São Paulo"));
+    /// let first = matches[3].as_ref().unwrap();
+    /// assert_eq!(first.tag, Tag::Begin(EntityCategory::Loc));
+    /// assert_eq!(first.rule_name, "location_gazetteer");
+    /// ```
+    pub fn set_prior(&mut self, category: EntityCategory, name: &str, prior: f64) {
+        let key = GazetteerKey::normalize(name);
+        if !key.is_empty() {
+            self.priors.entry(category).or_default().insert(key, prior.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Aceita tokens intercalados nos nomes do gazetteer de organizações (ver o módulo).
+    ///
+    /// Um casamento exato sempre tem precedência. Numa [`RuleEngineSession`], um nome com
+    /// intercalação que atravessa o fim do trecho recebido não é reconhecido.
+    pub fn set_gap_tolerance(&mut self, tolerance: GapTolerance) {
+        self.gap_tolerance = tolerance;
+    }
+
+    pub fn gap_tolerance(&self) -> GapTolerance {
+        self.gap_tolerance
+    }
+
+    /// Prior de uma chave normalizada ([`DEFAULT_GAZETTEER_PRIOR`] se não definido).
+    pub fn prior(&self, category: EntityCategory, key: &str) -> f64 {
+        self.priors
+            .get(&category)
+            .and_then(|priors| priors.get(key))
+            .copied()
+            .unwrap_or(DEFAULT_GAZETTEER_PRIOR)
+    }
+
+    /// A chave normalizada é uma entrada do gazetteer da categoria?
+    fn gazetteer_contains(&self, category: EntityCategory, key: &str) -> bool {
+        let ngrams = match category {
+            EntityCategory::Per => return self.person_names.iter().any(|n| n == key),
+            EntityCategory::Loc => return self.location_names.iter().any(|n| n == key),
+            EntityCategory::Org => &self.org_names,
+            EntityCategory::Misc => &self.misc_names,
+            EntityCategory::Date => &self.date_names,
+            EntityCategory::Time => &self.time_names,
+            EntityCategory::Custom(c) => match self.custom_names.iter().find(|(other, _)| *other == c) {
+                Some((_, names)) => names,
+                None => return false,
+            },
+        };
+        ngrams.iter().any(|parts| parts.iter().map(String::as_str).eq(key.split(' ')))
+    }
+
+    /// Leitura de uma frase encontrada no gazetteer de `category`: outro gazetteer
+    /// que contenha a mesma frase com prior **maior** toma o lugar; empates mantêm `category`.
+    fn gazetteer_reading(&self, category: EntityCategory, key: &str, groups: RuleGroups) -> GazetteerReading {
+        let rule = |cat: EntityCategory| match GAZETTEER_RULES.iter().find(|(c, _, _)| *c == cat) {
+            Some(&(_, name, base)) => (name, base),
+            None => CUSTOM_GAZETTEER_RULE,
+        };
+        let mut best = (category, self.prior(category, key));
+        if self.priors.values().any(|p| p.contains_key(key)) {
+            for &(other, _, _) in &GAZETTEER_RULES {
+                let prior = self.prior(other, key);
+                if other != category
+                    && prior > best.1
+                    && groups.contains(RuleGroup::gazetteer(other))
+                    && self.gazetteer_contains(other, key)
+                {
+                    best = (other, prior);
+                }
+            }
+        }
+        let (rule_name, base) = rule(best.0);
+        GazetteerReading { category: best.0, rule_name, confidence: base * best.1 }
+    }
+
+    /// Registra (ou estende) uma classe de palavras para uso em padrões como `[classe]`.
+    ///
+    /// Nomes de classe devem estar em minúsculas. As classes embutidas `title`,
+    /// `org_suffix`, `person` e `location` usam as listas internas do motor.
+    pub fn add_word_class(&mut self, name: &str, words: &[&str]) {
+        self.word_classes
+            .entry(name.to_string())
+            .or_default()
+            .extend(words.iter().map(|w| w.to_lowercase()));
+    }
+
+    /// Compila e registra uma regra declarativa de padrão de tokens.
+    ///
+    /// Os tokens do grupo capturado (ou do casamento inteiro, sem grupo) recebem
+    /// `B-`/`I-` da categoria, desde que nenhuma regra anterior os tenha marcado.
+    pub fn add_pattern(
+        &mut self,
+        name: &str,
+        pattern: &str,
+        category: EntityCategory,
+        confidence: f64,
+    ) -> Result<(), PatternError> {
+        let pattern = TokenPattern::compile(pattern)?;
+        if let Some(unknown) = pattern.word_classes().find(|c| self.word_class(c).is_none()) {
+            return Err(PatternError::UnknownClass(unknown.to_string()));
+        }
+        self.patterns.push(PatternRule {
+            name: name.to_string(),
+            pattern,
+            category,
+            confidence,
+        });
+        Ok(())
+    }
+
+    /// Regras de padrão registradas, na ordem de aplicação.
+    pub fn patterns(&self) -> &[PatternRule] {
+        &self.patterns
+    }
+
+    /// Registra uma regra de expressão regular (sintaxe do crate `regex`).
+    ///
+    /// O padrão é procurado no texto dos tokens, unidos por um espaço quando estavam
+    /// separados no original. Cada casamento que começa e termina em fronteiras de token
+    /// vira uma entidade `B-`/`I-` da categoria, desde que nenhuma regra anterior tenha
+    /// marcado esses tokens. Com grupo de captura, só o grupo 1 é a entidade.
+    ///
+    /// ```rust
+    /// use ner_core::rule_based::RuleEngine;
+    /// use ner_core::tagger::{EntityCategory, Tag};
+    /// use ner_core::tokenizer::tokenize;
+    ///
+    /// let mut engine = RuleEngine::new();
+    /// engine.add_regex_rule("oab", r"OAB/[A-Z]{2} ?(\d{1,3}(?:\.\d{3})*)", EntityCategory::Misc, 0.95).unwrap();
+    ///
+    /// let matches = engine.apply(&tokenize("advogado inscrito na OAB/SP 123.456"));
+    /// let number = matches.iter().flatten().find(|m| m.rule_name == "oab").unwrap();
+    /// assert_eq!(number.tag, Tag::Begin(EntityCategory::Misc));
+    /// assert!(engine.add_regex_rule("quebrada", "(", EntityCategory::Misc, 0.9).is_err());
+    /// ```
+    ///
+    /// Numa [`RuleEngineSession`], os tokens em que um casamento ainda pode começar ou
+    /// crescer (ex: só o DDD de um telefone) ficam pendentes até a dúvida se resolver.
+    pub fn add_regex_rule(
+        &mut self,
+        name: &str,
+        pattern: &str,
+        category: EntityCategory,
+        confidence: f64,
+    ) -> Result<(), PatternError> {
+        let rule = RegexRule::compile(name, pattern, category, confidence)
+            .map_err(|e| PatternError::InvalidRegex(e.to_string()))?;
+        self.regex_rules.push(rule);
+        Ok(())
+    }
+
+    /// Regras de expressão regular registradas, na ordem de aplicação.
+    pub fn regex_rules(&self) -> &[RegexRule] {
+        &self.regex_rules
+    }
+
+    /// Lista de palavras de uma classe (embutida ou do usuário).
+    fn word_class(&self, name: &str) -> Option<&[String]> {
+        match name {
+            "title" => Some(&self.person_titles),
+            "org_suffix" => Some(&self.org_indicators),
+            "person" => Some(&self.person_names),
+            "location" => Some(&self.location_names),
+            _ => self.word_classes.get(name).map(Vec::as_slice),
+        }
+    }
+
+    fn in_class(&self, class: &str, word: &str) -> bool {
+        self.word_class(class).is_some_and(|words| words.iter().any(|w| w == word))
+    }
+
+    /// Inicia uma sessão incremental: tokens chegam um a um (ex: transcrição ao vivo).
+    pub fn session(&self) -> RuleEngineSession<'_> {
+        RuleEngineSession {
+            engine: self,
+            tokens: Vec::new(),
+            frozen: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Gazetteers de n-gramas, na ordem em que são aplicados.
+    fn ngram_gazetteers(&self) -> Vec<&[Vec<String>]> {
+        let builtin: [&[Vec<String>]; 4] = [&self.org_names, &self.misc_names, &self.date_names, &self.time_names];
+        builtin.into_iter().chain(self.custom_names.iter().map(|(_, names)| names.as_slice())).collect()
+    }
+
     /// Aplica todas as regras à sequência de tokens.
     ///
     /// # Ordem de Prioridade
@@ -97,190 +873,625 @@ impl RuleEngine {
     /// mas a ordem de execução no código define a "última palavra".
     ///
     /// 1. **Gazetteers Simples**: Casamento exato de token único (ex: "Lula" -> PER).
-    /// 2. **Gazetteers Compostos**: Casamento de n-gramas (ex: "Banco do Brasil" -> ORG).
-    /// 3. **Padrões de Contexto**: (ex: "Presidente [X]" -> X é PER).
-    /// 4. **Sufixos/Indicadores**: (ex: "[X] Ltda" -> X é ORG).
-    /// 5. **Regex**: Validação de formato (ex: CNPJ).
+    /// 2. **Gazetteers Compostos**: Casamento de n-gramas (ex: "Banco do Brasil" -> ORG, "século XX" -> DATE),
+    ///    e depois os das categorias personalizadas ([`RuleEngine::add_entry`]).
+    /// 3. **Citações Legais**: "art. 5º, §2º, da Lei nº 8.078/1990" -> MISC (regra `law_ref`).
+    /// 4. **Siglas de UF** após uma localização: "Campinas (SP)", "Campinas, SP" -> SP é LOC
+    ///    (regra `uf_code`, ver [`crate::brazil`]).
+    /// 5. **Padrões de Tokens**: declarativos, na ordem de registro
+    ///    (ex: `[title] ([Cap])` — "Presidente [X]" -> X é PER; `[Cap] [org_suffix]` — "[X] Ltda" -> ORG).
+    /// 6. **Regex**: Validação de formato (ex: CNPJ, horários como "14h30").
+    /// 7. **Regex do usuário**: na ordem de registro ([`RuleEngine::add_regex_rule`]).
     ///
     /// # Retorno
     /// Retorna um vetor do mesmo tamanho dos tokens, onde cada posição contém `Some(RuleMatch)`
     /// se alguma regra disparou para aquele token.
     pub fn apply(&self, tokens: &[Token]) -> Vec<Option<RuleMatch>> {
-        let mut result: Vec<Option<RuleMatch>> = vec![None; tokens.len()];
+        self.apply_with_lookups(tokens, &GazetteerLookups::keys_only(tokens))
+    }
+
+    /// Como [`RuleEngine::apply`], reaproveitando as chaves normalizadas de `lookups`
+    /// (montada sobre os mesmos `tokens`, ex: pelo pipeline, que a compartilha com a
+    /// extração de features). Cada chave distinta é consultada nas listas uma única vez.
+    pub fn apply_with_lookups(&self, tokens: &[Token], lookups: &GazetteerLookups) -> Vec<Option<RuleMatch>> {
+        self.apply_after(tokens, &[], lookups, RuleGroups::all())
+    }
+
+    /// Como [`RuleEngine::apply_with_lookups`], rodando apenas os grupos de regras em
+    /// `groups`. Um gazetteer desligado também não disputa a leitura de homógrafos.
+    pub fn apply_selected(&self, tokens: &[Token], lookups: &GazetteerLookups, groups: RuleGroups) -> Vec<Option<RuleMatch>> {
+        self.apply_after(tokens, &[], lookups, groups)
+    }
+
+    /// Como [`RuleEngine::apply`], mas os primeiros `frozen.len()` tokens já têm resultado
+    /// definitivo: nenhuma regra começa nem escreve neles, e eles servem só de contexto
+    /// (ex: continuidade `B-PER` → `I-PER`). Usado por [`RuleEngineSession`].
+    fn apply_after(
+        &self,
+        tokens: &[Token],
+        frozen: &[Option<RuleMatch>],
+        lookups: &GazetteerLookups,
+        groups: RuleGroups,
+    ) -> Vec<Option<RuleMatch>> {
+        let from = frozen.len();
+        let mut result: Vec<Option<RuleMatch>> = frozen.to_vec();
+        result.resize(tokens.len(), None);
+
+        // Pertinência às listas de token único, uma vez por chave distinta
+        let in_list = |category: EntityCategory, names: &[String]| -> Vec<bool> {
+            let enabled = groups.contains(RuleGroup::gazetteer(category));
+            lookups.entries().iter().map(|e| enabled && names.contains(&e.key)).collect()
+        };
+        let is_person = in_list(EntityCategory::Per, &self.person_names);
+        let is_location = in_list(EntityCategory::Loc, &self.location_names);
 
         // 1. Gazetteers de pessoa (token único)
-        for (i, token) in tokens.iter().enumerate() {
-            let lower = token.text.to_lowercase();
-            if self.person_names.contains(&lower) {
+        for i in from..tokens.len() {
+            let key = &lookups.entry(i).key;
+            if is_person[lookups.slot(i)] {
+                let reading = self.gazetteer_reading(EntityCategory::Per, key, groups);
+                let category = reading.category;
                 result[i] = Some(RuleMatch {
                     token_index: i,
-                    tag: if result
-                        .get(i.wrapping_sub(1))
-                        .and_then(|r| r.as_ref())
-                        .map(|r| matches!(r.tag, Tag::Begin(EntityCategory::Per) | Tag::Inside(EntityCategory::Per)))
-                        .unwrap_or(false)
+                    tag: if category == EntityCategory::Per
+                        && result
+                            .get(i.wrapping_sub(1))
+                            .and_then(|r| r.as_ref())
+                            .map(|r| matches!(r.tag, Tag::Begin(EntityCategory::Per) | Tag::Inside(EntityCategory::Per)))
+                            .unwrap_or(false)
                     {
                         Tag::Inside(EntityCategory::Per)
                     } else {
-                        Tag::Begin(EntityCategory::Per)
+                        Tag::Begin(category)
                     },
-                    rule_name: "person_gazetteer".to_string(),
-                    confidence: 0.92,
+                    rule_name: reading.rule_name.to_string(),
+                    confidence: reading.confidence,
+                    variant_of: variant_of(lookups, i),
                 });
             }
         }
 
         // 2. Gazetteers de localização (token único)
-        for (i, token) in tokens.iter().enumerate() {
+        for i in from..tokens.len() {
             if result[i].is_some() {
                 continue;
             }
-            let lower = token.text.to_lowercase();
-            if self.location_names.contains(&lower) {
+            if is_location[lookups.slot(i)] {
+                let reading = self.gazetteer_reading(EntityCategory::Loc, &lookups.entry(i).key, groups);
                 result[i] = Some(RuleMatch {
                     token_index: i,
-                    tag: Tag::Begin(EntityCategory::Loc),
-                    rule_name: "location_gazetteer".to_string(),
-                    confidence: 0.90,
+                    tag: Tag::Begin(reading.category),
+                    rule_name: reading.rule_name.to_string(),
+                    confidence: reading.confidence,
+                    variant_of: variant_of(lookups, i),
                 });
             }
         }
 
         // 3. Gazetteers de organização (n-gramas)
-        'outer_org: for (i, _) in tokens.iter().enumerate() {
-            if result[i].is_some() {
-                continue;
-            }
-            for org_parts in &self.org_names {
-                if i + org_parts.len() <= tokens.len() {
-                    let matches = org_parts.iter().enumerate().all(|(j, part)| {
-                        tokens[i + j].text.to_lowercase() == *part
-                    });
-                    if matches {
-                        result[i] = Some(RuleMatch {
-                            token_index: i,
-                            tag: Tag::Begin(EntityCategory::Org),
-                            rule_name: "org_gazetteer".to_string(),
-                            confidence: 0.93,
-                        });
-                        for j in 1..org_parts.len() {
-                            if i + j < result.len() {
-                                result[i + j] = Some(RuleMatch {
-                                    token_index: i + j,
-                                    tag: Tag::Inside(EntityCategory::Org),
-                                    rule_name: "org_gazetteer".to_string(),
-                                    confidence: 0.93,
-                                });
-                            }
-                        }
-                        continue 'outer_org;
-                    }
-                }
-            }
+        apply_ngram_gazetteer(self, lookups, groups, &mut result, EntityCategory::Org, |i| i >= from);
+
+        // 4. Gazetteers de misc e expressões temporais (n-gramas)
+        apply_ngram_gazetteer(self, lookups, groups, &mut result, EntityCategory::Misc, |i| i >= from);
+        // Meses em português são minúsculos: "Janeiro" em "Rio de Janeiro" é parte de um nome próprio
+        apply_ngram_gazetteer(self, lookups, groups, &mut result, EntityCategory::Date, |i| {
+            i >= from && !is_proper_name_tail(tokens, i)
+        });
+        apply_ngram_gazetteer(self, lookups, groups, &mut result, EntityCategory::Time, |i| i >= from);
+        for &(category, _) in &self.custom_names {
+            apply_ngram_gazetteer(self, lookups, groups, &mut result, EntityCategory::Custom(category), |i| i >= from);
         }
 
-        // 4. Gazetteers de misc (n-gramas)
-        'outer_misc: for (i, _) in tokens.iter().enumerate() {
-            if result[i].is_some() {
-                continue;
-            }
-            for misc_parts in &self.misc_names {
-                if i + misc_parts.len() <= tokens.len() {
-                    let matches = misc_parts.iter().enumerate().all(|(j, part)| {
-                        tokens[i + j].text.to_lowercase() == *part
-                    });
-                    if matches {
-                        result[i] = Some(RuleMatch {
-                            token_index: i,
-                            tag: Tag::Begin(EntityCategory::Misc),
-                            rule_name: "misc_gazetteer".to_string(),
-                            confidence: 0.88,
-                        });
-                        for j in 1..misc_parts.len() {
-                            if i + j < result.len() {
-                                result[i + j] = Some(RuleMatch {
-                                    token_index: i + j,
-                                    tag: Tag::Inside(EntityCategory::Misc),
-                                    rule_name: "misc_gazetteer".to_string(),
-                                    confidence: 0.88,
-                                });
-                            }
-                        }
-                        continue 'outer_misc;
-                    }
+        // 5. Citações legais ("art. 5º, §2º, da Lei nº 8.078/1990" → MISC, regra `law_ref`)
+        let mut i = if groups.contains(RuleGroup::LawReferences) { from } else { tokens.len() };
+        let words = if i < tokens.len() { law_ref_words(tokens) } else { Vec::new() };
+        while i < tokens.len() {
+            let end = match scan_law_ref(&words, i).end {
+                Some(end) if result[i..end].iter().all(Option::is_none) => end,
+                _ => {
+                    i += 1;
+                    continue;
                 }
+            };
+            for (j, slot) in result.iter_mut().enumerate().take(end).skip(i) {
+                *slot = Some(RuleMatch {
+                    token_index: j,
+                    tag: if j == i { Tag::Begin(EntityCategory::Misc) } else { Tag::Inside(EntityCategory::Misc) },
+                    rule_name: "law_ref".to_string(),
+                    confidence: 0.9,
+                    variant_of: None,
+                });
             }
+            i = end;
         }
 
-        // 5. Regra de título: "Presidente X" → X é PER
-        for i in 0..tokens.len().saturating_sub(1) {
-            if result[i + 1].is_some() {
-                continue;
-            }
-            let lower = tokens[i].text.to_lowercase();
-            if self.person_titles.contains(&lower) {
-                let next = &tokens[i + 1];
-                let next_first_upper = next
-                    .text
-                    .chars()
-                    .next()
-                    .map(|c| c.is_uppercase())
-                    .unwrap_or(false);
-                if next_first_upper {
-                    result[i + 1] = Some(RuleMatch {
-                        token_index: i + 1,
-                        tag: Tag::Begin(EntityCategory::Per),
-                        rule_name: "title_pattern".to_string(),
-                        confidence: 0.80,
-                    });
-                }
+        // 6. Siglas de UF após uma localização ou nome próprio: "Campinas (SP)", "Campinas, SP", "Campinas - SP"
+        let uf_start = if groups.contains(RuleGroup::UfCodes) { from.max(2) } else { tokens.len() };
+        for i in uf_start..tokens.len() {
+            let separator = tokens[i - 1].text.as_str();
+            let place = &tokens[i - 2];
+            if result[i].is_none()
+                && is_uf_code(&tokens[i].text)
+                && matches!(separator, "," | "(" | "-" | "–" | "/")
+                && place.is_capitalized()
+            {
+                result[i] = Some(RuleMatch {
+                    token_index: i,
+                    tag: Tag::Begin(EntityCategory::Loc),
+                    rule_name: "uf_code".to_string(),
+                    confidence: 0.85,
+                    variant_of: None,
+                });
             }
         }
 
-        // 6. Indicadores de organização: "X S.A." → X é ORG
-        for i in 1..tokens.len() {
-            let lower = tokens[i].text.to_lowercase();
-            if self.org_indicators.contains(&lower) && result[i - 1].is_none() {
-                let prev = &tokens[i - 1];
-                let prev_first_upper = prev
-                    .text
-                    .chars()
-                    .next()
-                    .map(|c| c.is_uppercase())
-                    .unwrap_or(false);
-                if prev_first_upper {
-                    result[i - 1] = Some(RuleMatch {
-                        token_index: i - 1,
-                        tag: Tag::Begin(EntityCategory::Org),
-                        rule_name: "org_suffix_pattern".to_string(),
-                        confidence: 0.85,
-                    });
-                    result[i] = Some(RuleMatch {
-                        token_index: i,
-                        tag: Tag::Inside(EntityCategory::Org),
-                        rule_name: "org_suffix_pattern".to_string(),
-                        confidence: 0.85,
+        // 7. Padrões de tokens (embutidos: título → PER, sufixo societário → ORG; depois os do usuário)
+        let in_class = |class: &str, word: &str| self.in_class(class, word);
+        for rule in self.patterns.iter().filter(|rule| groups.contains(RuleGroup::of_pattern(&rule.name))) {
+            let mut i = from;
+            while i < tokens.len() {
+                let Some(m) = rule.pattern.match_at(tokens, i, &in_class) else {
+                    i += 1;
+                    continue;
+                };
+                let target = m.target_start..m.target_end;
+                if result[target.clone()].iter().any(Option::is_some) {
+                    i += 1;
+                    continue;
+                }
+                for j in target {
+                    result[j] = Some(RuleMatch {
+                        token_index: j,
+                        tag: if j == m.target_start { Tag::Begin(rule.category) } else { Tag::Inside(rule.category) },
+                        rule_name: rule.name.clone(),
+                        confidence: rule.confidence,
+                        variant_of: None,
                     });
                 }
+                i = m.end.max(i + 1);
             }
         }
 
-        // 7. Regex: CNPJ (padrão XX.XXX.XXX/XXXX-XX → ORG próximo)
-        for (i, token) in tokens.iter().enumerate() {
+        // 8. Regex: CNPJ (padrão XX.XXX.XXX/XXXX-XX → ORG próximo)
+        let regex_from = if groups.contains(RuleGroup::RegexPatterns) { from } else { tokens.len() };
+        for (i, token) in tokens.iter().enumerate().skip(regex_from) {
             if is_cnpj(&token.text) && result[i].is_none() {
                 result[i] = Some(RuleMatch {
                     token_index: i,
                     tag: Tag::Begin(EntityCategory::Org),
                     rule_name: "cnpj_pattern".to_string(),
                     confidence: 0.99,
+                    variant_of: None,
                 });
             }
         }
 
+        // 9. Regex: horários (padrão 14h, 14h30, 14h30min → TIME)
+        for (i, token) in tokens.iter().enumerate().skip(regex_from) {
+            if is_time_expression(&token.text) && result[i].is_none() {
+                result[i] = Some(RuleMatch {
+                    token_index: i,
+                    tag: Tag::Begin(EntityCategory::Time),
+                    rule_name: "time_pattern".to_string(),
+                    confidence: 0.95,
+                    variant_of: None,
+                });
+            }
+        }
+
+        // 10. Regex do usuário, sobre o texto reconstituído dos tokens
+        if !self.regex_rules.is_empty() && groups.contains(RuleGroup::RegexPatterns) {
+            let surface = Surface::new(tokens);
+            for rule in &self.regex_rules {
+                for span in rule.token_spans(&surface) {
+                    if span.start < from || result[span.clone()].iter().any(Option::is_some) {
+                        continue;
+                    }
+                    let first = span.start;
+                    for j in span {
+                        result[j] = Some(RuleMatch {
+                            token_index: j,
+                            tag: if j == first { Tag::Begin(rule.category) } else { Tag::Inside(rule.category) },
+                            rule_name: rule.name.clone(),
+                            confidence: rule.confidence,
+                            variant_of: None,
+                        });
+                    }
+                }
+            }
+        }
+
         result
     }
 }
 
+/// Tokens anteriores mantidos como contexto: a continuidade `B-PER` → `I-PER` olha um
+/// token para trás e a exceção "Rio de Janeiro" das datas olha dois.
+const SESSION_CONTEXT: usize = 2;
+
+/// Avaliação **incremental** das regras sobre tokens que chegam aos poucos.
+///
+/// [`RuleEngine::apply`] exige a sequência inteira. Numa transcrição ao vivo, porém,
+/// queremos as entidades assim que possível. A sessão guarda apenas os tokens
+/// **pendentes**: aqueles que ainda podem ser afetados por um casamento parcial, como
+/// "Banco do" à espera de "Brasil" ou "presidente" à espera de um nome.
+///
+/// A cada token recebido, as regras são reavaliadas sobre os pendentes e todo token
+/// que nenhum casamento em aberto pode mais alcançar é **confirmado**: seus
+/// [`RuleMatch`] são emitidos (com `token_index` global) e nunca mudam. Ao final, a
+/// concatenação do que foi emitido é igual ao resultado de `apply` sobre a sequência completa.
+///
+/// ```rust
+/// use ner_core::rule_based::RuleEngine;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let mut engine = RuleEngine::new();
+/// engine.add_org("Banco do Brasil");
+/// let mut session = engine.session();
+///
+/// let mut tokens = tokenize("o Banco do Brasil lucrou").into_iter();
+/// assert!(session.push(tokens.next().unwrap()).is_empty()); // "o"
+/// assert!(session.push(tokens.next().unwrap()).is_empty()); // "Banco": à espera
+/// assert!(session.push(tokens.next().unwrap()).is_empty()); // "do": à espera
+/// // "Brasil" completa o n-grama, mas "Brasil S.A." ainda seria possível
+/// assert!(session.push(tokens.next().unwrap()).is_empty());
+/// let confirmed = session.push(tokens.next().unwrap()); // "lucrou"
+/// assert_eq!(confirmed.iter().map(|m| m.token_index).collect::<Vec<_>>(), vec![1, 2, 3]);
+/// assert!(session.finish().is_empty());
+/// ```
+pub struct RuleEngineSession<'e> {
+    engine: &'e RuleEngine,
+    /// Contexto já confirmado (até [`SESSION_CONTEXT`] tokens) seguido dos tokens pendentes.
+    tokens: Vec<Token>,
+    /// Resultados confirmados dos tokens de contexto.
+    frozen: Vec<Option<RuleMatch>>,
+    /// Índice global de `tokens[0]`.
+    offset: usize,
+}
+
+impl RuleEngineSession<'_> {
+    /// Recebe o próximo token e retorna os casamentos que acabaram de ser confirmados.
+    pub fn push(&mut self, mut token: Token) -> Vec<RuleMatch> {
+        token.index = self.offset + self.tokens.len();
+        self.tokens.push(token);
+        let result = self.engine.apply_after(&self.tokens, &self.frozen, &GazetteerLookups::keys_only(&self.tokens), RuleGroups::all());
+        let confirmed = self.settled_until();
+        self.confirm(result, confirmed)
+    }
+
+    /// Encerra a entrada: confirma todos os tokens pendentes.
+    pub fn finish(mut self) -> Vec<RuleMatch> {
+        let result = self.engine.apply_after(&self.tokens, &self.frozen, &GazetteerLookups::keys_only(&self.tokens), RuleGroups::all());
+        let len = self.tokens.len();
+        self.confirm(result, len)
+    }
+
+    /// Número de tokens recebidos e ainda não confirmados.
+    pub fn pending(&self) -> usize {
+        self.tokens.len() - self.frozen.len()
+    }
+
+    /// Primeira posição (local) que ainda pode mudar com novos tokens.
+    ///
+    /// Parte do início do casamento parcial mais à esquerda e recua enquanto houver
+    /// um casamento começando antes e terminando depois desse ponto (confirmar metade
+    /// dele seria prematuro: o resto ainda pode ser bloqueado por outra regra).
+    fn settled_until(&self) -> usize {
+        let tokens = &self.tokens;
+        let engine = self.engine;
+        let in_class = |class: &str, word: &str| engine.in_class(class, word);
+        let keys: Vec<String> = tokens.iter().map(|t| GazetteerKey::normalize(&t.text)).collect();
+        let words = law_ref_words(tokens);
+        let from = self.frozen.len();
+
+        // Casamentos de regex completos (os parciais são vistos por `may_continue`)
+        let surface = Surface::new(tokens);
+        let regex_spans: Vec<Range<usize>> = engine
+            .regex_rules
+            .iter()
+            .flat_map(|rule| rule.token_spans(&surface))
+            .filter(|span| span.start >= from)
+            .collect();
+
+        let ngram_at = |i: usize, parts: &[String]| {
+            parts.iter().zip(&keys[i..]).all(|(part, key)| part == key)
+        };
+        let is_partial = |i: usize| {
+            engine.ngram_gazetteers().iter().any(|names| {
+                names.iter().any(|parts| keys.len() - i < parts.len() && ngram_at(i, parts))
+            }) || engine.patterns.iter().any(|rule| rule.pattern.needs_more_tokens(tokens, i, &in_class))
+                || scan_law_ref(&words, i).reached_end
+                || engine.regex_rules.iter().any(|rule| rule.may_continue(&surface.text, surface.ranges[i].start))
+        };
+        let mut settled = (from..tokens.len()).find(|&i| is_partial(i)).unwrap_or(tokens.len());
+
+        loop {
+            let crossing = (from..settled).find(|&i| {
+                let ngram_crosses = engine.ngram_gazetteers().iter().any(|names| {
+                    names.iter().any(|parts| {
+                        i + parts.len() > settled && i + parts.len() <= keys.len() && ngram_at(i, parts)
+                    })
+                });
+                let pattern_crosses = engine.patterns.iter().any(|rule| {
+                    rule.pattern.match_at(tokens, i, &in_class).is_some_and(|m| m.end > settled)
+                });
+                let law_ref_crosses = scan_law_ref(&words, i).end.is_some_and(|end| end > settled);
+                let regex_crosses = regex_spans.iter().any(|span| span.start == i && span.end > settled);
+                ngram_crosses || pattern_crosses || law_ref_crosses || regex_crosses
+            });
+            match crossing {
+                Some(i) => settled = i,
+                None => return settled,
+            }
+        }
+    }
+
+    /// Confirma os tokens locais `[frozen.len(), until)` e descarta o contexto excedente.
+    fn confirm(&mut self, result: Vec<Option<RuleMatch>>, until: usize) -> Vec<RuleMatch> {
+        let from = self.frozen.len();
+        let emitted: Vec<RuleMatch> = result[from..until]
+            .iter()
+            .flatten()
+            .map(|m| RuleMatch { token_index: m.token_index + self.offset, ..m.clone() })
+            .collect();
+
+        let keep_from = until.saturating_sub(SESSION_CONTEXT);
+        self.frozen = result[keep_from..until].to_vec();
+        self.tokens.drain(..keep_from);
+        self.offset += keep_from;
+        emitted
+    }
+}
+
+/// Partes normalizadas de um nome composto, uma por token ("Banco do Brasil" → 3 partes).
+fn ngram_key(name: &str) -> Vec<String> {
+    GazetteerKey::normalize(name).split_whitespace().map(str::to_string).collect()
+}
+
+/// Aplica o gazetteer de n-gramas da categoria, marcando o primeiro token como `B-` e os
+/// demais como `I-` (da leitura de maior prior, ver [`RuleEngine::set_prior`]).
+///
+/// Tokens que já possuem uma regra aplicada não iniciam novos casamentos, assim como
+/// posições rejeitadas por `accept_start`.
+fn apply_ngram_gazetteer(
+    engine: &RuleEngine,
+    lookups: &GazetteerLookups,
+    groups: RuleGroups,
+    result: &mut [Option<RuleMatch>],
+    category: EntityCategory,
+    accept_start: impl Fn(usize) -> bool,
+) {
+    if !groups.contains(RuleGroup::gazetteer(category)) {
+        return;
+    }
+    let names: &[Vec<String>] = match category {
+        EntityCategory::Org => &engine.org_names,
+        EntityCategory::Misc => &engine.misc_names,
+        EntityCategory::Date => &engine.date_names,
+        EntityCategory::Time => &engine.time_names,
+        EntityCategory::Custom(c) => match engine.custom_names.iter().find(|(other, _)| *other == c) {
+            Some((_, names)) => names,
+            None => return,
+        },
+        EntityCategory::Per | EntityCategory::Loc => return,
+    };
+    let tolerance = engine.gap_tolerance;
+    let gapped = category == EntityCategory::Org && tolerance.max_gap > 0;
+    for i in 0..lookups.len() {
+        if result[i].is_some() || !accept_start(i) {
+            continue;
+        }
+        let exact = names.iter().find_map(|parts| {
+            let matches = i + parts.len() <= lookups.len()
+                && parts.iter().enumerate().all(|(j, part)| lookups.entry(i + j).key == *part);
+            matches.then_some((parts, i + parts.len(), 0))
+        });
+        let found = exact.or_else(|| {
+            // Casamento exato tem precedência; só então tenta com intercalações
+            gapped.then(|| names.iter().find_map(|parts| match_with_gaps(lookups, i, parts, tolerance).map(|(end, gap)| (parts, end, gap))))?
+        });
+        let Some((parts, end, gap)) = found else {
+            continue;
+        };
+        let reading = engine.gazetteer_reading(category, &parts.join(" "), groups);
+        let confidence = reading.confidence * tolerance.discount.powi(gap as i32);
+        for (j, slot) in result.iter_mut().enumerate().take(end).skip(i) {
+            *slot = Some(RuleMatch {
+                token_index: j,
+                tag: if j == i { Tag::Begin(reading.category) } else { Tag::Inside(reading.category) },
+                rule_name: reading.rule_name.to_string(),
+                confidence,
+                variant_of: variant_of(lookups, j),
+            });
+        }
+    }
+}
+
+/// Casa o nome `parts` a partir do token `i` com ao menos uma intercalação entre as
+/// partes (até `tolerance.max_gap` tokens no total). Retorna o fim (exclusivo) do
+/// trecho e o número de tokens intercalados.
+fn match_with_gaps(lookups: &GazetteerLookups, i: usize, parts: &[String], tolerance: GapTolerance) -> Option<(usize, usize)> {
+    let n = lookups.len();
+    let key = |p: usize| lookups.entry(p).key.as_str();
+    if parts.len() < 2 || key(i) != parts[0] {
+        return None;
+    }
+    let (mut pos, mut gap) = (i + 1, 0);
+    for part in &parts[1..] {
+        if pos < n && key(pos) == part {
+            pos += 1;
+            continue;
+        }
+        let budget = tolerance.max_gap - gap;
+        // Próximo token a casar com `part` depois da intercalação, e o tamanho dela
+        let (next, skipped) = if tolerance.parenthetical_only {
+            if pos >= n || key(pos) != "(" {
+                return None;
+            }
+            let close = (pos + 2..n.min(pos + budget + 2)).find(|&c| key(c) == ")")?;
+            (close + 1, close - pos - 1)
+        } else {
+            (1..=budget).map(|k| (pos + k, k)).find(|&(p, _)| p < n && key(p) == part)?
+        };
+        if next >= n || key(next) != part {
+            return None;
+        }
+        gap += skipped;
+        pos = next + 1;
+    }
+    (gap > 0).then_some((pos, gap))
+}
+
+/// Chave do gazetteer usada no token `i`, se ela veio de uma grafia variante.
+fn variant_of(lookups: &GazetteerLookups, i: usize) -> Option<String> {
+    let entry = lookups.entry(i);
+    entry.variant.as_ref().map(|_| entry.key.clone())
+}
+
+/// Cabeças de dispositivos legais, seguidas de um ou mais itens ("art. 5º", "§§ 1º e 2º").
+const PROVISION_HEADS: &[&str] = &[
+    "art.", "arts.", "artigo", "artigos", "§", "parágrafo", "parágrafos",
+    "inciso", "incisos", "alínea", "alíneas",
+];
+
+/// Tipos de atos normativos, seguidos de número ("Lei nº 8.078/1990", "EC 45").
+const ACT_HEADS: &[&[&str]] = &[
+    &["lei", "complementar"], &["emenda", "constitucional"], &["medida", "provisória"],
+    &["instrução", "normativa"], &["decreto-lei"], &["lei"], &["decreto"], &["portaria"],
+    &["resolução"], &["mp"], &["lc"], &["ec"],
+];
+
+/// Marcadores de número entre o ato e o número ("Lei **nº** 8.078").
+const NUMBER_MARKERS: &[&str] = &["nº", "n.º", "n°", "n.°"];
+
+/// Resultado da tentativa de casar uma citação legal a partir de um token.
+struct LawRefScan {
+    /// Fim (exclusivo) da citação, se houver uma.
+    end: Option<usize>,
+    /// A varredura precisou de um token além do fim da sequência: mais tokens podem
+    /// estender (ou criar) a citação. Usado por [`RuleEngineSession`].
+    reached_end: bool,
+}
+
+/// Cursor sobre os tokens (em minúsculas) que registra leituras além do fim.
+struct LawRefCursor<'a> {
+    words: &'a [String],
+    reached_end: bool,
+}
+
+impl LawRefCursor<'_> {
+    fn word(&mut self, i: usize) -> Option<&str> {
+        let word = self.words.get(i).map(String::as_str);
+        self.reached_end |= word.is_none();
+        word
+    }
+
+    fn is(&mut self, i: usize, options: &[&str]) -> bool {
+        self.word(i).is_some_and(|w| options.contains(&w))
+    }
+
+    /// Um item de dispositivo: número, ordinal, romano (incisos), letra (alíneas) ou "único".
+    fn item(&mut self, i: usize) -> bool {
+        self.word(i).is_some_and(|w| {
+            is_legal_number(w)
+                || w == "único"
+                || (w.len() <= 5 && w.chars().all(|c| matches!(c, 'i' | 'v' | 'x' | 'l')))
+                || (w.chars().count() == 1 && w.chars().all(|c| c.is_ascii_lowercase()))
+        })
+    }
+
+    /// "art. 5º, 6º e 7º" / "§§ 1º e 2º": retorna o fim do dispositivo.
+    fn provision(&mut self, mut i: usize) -> Option<usize> {
+        if !self.is(i, PROVISION_HEADS) {
+            return None;
+        }
+        while self.is(i + 1, &["§"]) {
+            i += 1;
+        }
+        if !self.item(i + 1) {
+            return None;
+        }
+        let mut end = i + 2;
+        while self.is(end, &[",", "e"]) && self.item(end + 1) {
+            end += 2;
+        }
+        Some(end)
+    }
+
+    /// "Lei nº 8.078/1990", "Emenda Constitucional 45": retorna o fim do ato.
+    fn act(&mut self, i: usize) -> Option<usize> {
+        let head = ACT_HEADS.iter().find(|parts| {
+            parts.iter().enumerate().all(|(k, part)| self.word(i + k) == Some(part))
+        })?;
+        let mut end = i + head.len();
+        if self.is(end, NUMBER_MARKERS) {
+            end += 1;
+        }
+        if !self.word(end).is_some_and(is_legal_number) {
+            return None;
+        }
+        end += 1;
+        if self.is(end, &["/"]) && self.word(end + 1).is_some_and(|w| w.chars().all(|c| c.is_ascii_digit())) {
+            end += 2;
+        }
+        Some(end)
+    }
+}
+
+/// Tenta casar uma citação legal começando em `start`.
+///
+/// A citação é uma sequência de componentes — dispositivos ("art. 5º", "§2º",
+/// "inciso III") e atos ("Lei nº 8.078/1990") — ligados por ",", "da" ou "do":
+/// "art. 5º, §2º, inciso III, da Lei nº 8.078/1990" é uma única citação.
+///
+/// `words` são os tokens em minúsculas ([`law_ref_words`]), calculados uma vez por
+/// aplicação: a varredura roda a partir de cada token.
+fn scan_law_ref(words: &[String], start: usize) -> LawRefScan {
+    let mut cursor = LawRefCursor { words, reached_end: false };
+
+    let mut end = match cursor.provision(start).or_else(|| cursor.act(start)) {
+        Some(end) => end,
+        None => return LawRefScan { end: None, reached_end: cursor.reached_end },
+    };
+    loop {
+        // Conectores só são consumidos se um novo componente vier em seguida
+        let mut next = end;
+        if cursor.is(next, &[","]) {
+            next += 1;
+        }
+        if cursor.is(next, &["da", "do"]) {
+            next += 1;
+        }
+        match cursor.provision(next).or_else(|| cursor.act(next)) {
+            Some(component_end) if next > end || component_end > next => end = component_end,
+            _ => break,
+        }
+    }
+    LawRefScan { end: Some(end), reached_end: cursor.reached_end }
+}
+
+/// Os tokens em minúsculas, como [`scan_law_ref`] os compara.
+fn law_ref_words(tokens: &[Token]) -> Vec<String> {
+    #[cfg(test)]
+    tests::LOWERCASED_TOKENS.with(|count| count.set(count.get() + tokens.len()));
+    tokens.iter().map(|t| t.text.to_lowercase()).collect()
+}
+
+/// Número de dispositivo ou de ato: "5", "8.078", "5º", "2ª", "1.º".
+fn is_legal_number(word: &str) -> bool {
+    let digits = word.trim_end_matches(['º', 'ª', '°']).trim_end_matches('.');
+    digits.starts_with(|c: char| c.is_ascii_digit()) && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// Verifica se o token `i` é a cauda capitalizada de um nome próprio composto,
+/// como "Janeiro" em "Rio de Janeiro" (Maiúscula + preposição + Maiúscula).
+fn is_proper_name_tail(tokens: &[Token], i: usize) -> bool {
+    i >= 2
+        && tokens[i].is_capitalized()
+        && matches!(tokens[i - 1].text.as_str(), "de" | "do" | "da" | "dos" | "das")
+        && tokens[i - 2].is_capitalized()
+}
+
 impl Default for RuleEngine {
     fn default() -> Self {
         Self::new()
@@ -327,6 +1538,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_uf_code_after_location() {
+        let engine = RuleEngine::new();
+        let tokens = tokenize("Em Campinas (SP), Natal, RN e Uberlândia - MG, se houver SE ou PA");
+        let matches = engine.apply(&tokens);
+        let ufs: Vec<&str> = matches
+            .iter()
+            .flatten()
+            .filter(|m| m.rule_name == "uf_code")
+            .map(|m| tokens[m.token_index].text.as_str())
+            .collect();
+        assert_eq!(ufs, ["SP", "RN", "MG"]);
+    }
+
+    #[test]
+    fn test_gazetteer_priors_scale_confidence_and_rank_homographs() {
+        let mut engine = RuleEngine::new();
+        engine.add_person("Santos");
+        engine.add_org("Santos");
+        engine.add_location("São Paulo");
+        engine.add_org("São Paulo");
+
+        // Sem priors vale a ordem de prioridade: PER antes de ORG, e só ORG tem n-gramas
+        let tokens = tokenize("Santos venceu em São Paulo");
+        let matches = engine.apply(&tokens);
+        assert_eq!(matches[0].as_ref().unwrap().rule_name, "person_gazetteer");
+        assert_eq!(matches[3].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Org));
+
+        engine.set_prior(EntityCategory::Per, "Santos", 0.3);
+        engine.set_prior(EntityCategory::Org, "São Paulo", 0.6);
+        let matches = engine.apply(&tokens);
+        let santos = matches[0].as_ref().unwrap();
+        assert_eq!(santos.tag, Tag::Begin(EntityCategory::Org));
+        assert_eq!(santos.rule_name, "org_gazetteer");
+        assert!((santos.confidence - 0.93).abs() < 1e-9);
+        assert_eq!(matches[3].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Loc));
+        assert_eq!(matches[4].as_ref().unwrap().tag, Tag::Inside(EntityCategory::Loc));
+
+        // O prior escala a confiança da própria leitura
+        engine.set_prior(EntityCategory::Org, "Santos", 0.2);
+        let santos = engine.apply(&tokens)[0].clone().unwrap();
+        assert_eq!(santos.rule_name, "person_gazetteer");
+        assert!((santos.confidence - 0.92 * 0.3).abs() < 1e-9);
+
+        // Um gazetteer desligado não disputa a leitura: "São Paulo" fica com o de ORG
+        let lookups = GazetteerLookups::keys_only(&tokens);
+        let groups = RuleGroups::all().without(RuleGroup::LocationGazetteer);
+        let matches = engine.apply_selected(&tokens, &lookups, groups);
+        assert_eq!(matches[3].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Org));
+        assert_eq!(matches[3].as_ref().unwrap().rule_name, "org_gazetteer");
+    }
+
     #[test]
     fn test_title_pattern() {
         let engine = RuleEngine::new();
@@ -360,4 +1623,228 @@ mod tests {
             Tag::Inside(EntityCategory::Org)
         );
     }
+
+    #[test]
+    fn test_org_gap_tolerance() {
+        let mut engine = RuleEngine::new();
+        engine.add_org("Banco Central do Brasil");
+        engine.add_org("Fundo Monetário Internacional");
+        let tags = |engine: &RuleEngine, text: &str| -> Vec<String> {
+            engine.apply(&tokenize(text)).iter().map(|m| m.as_ref().map_or("O".to_string(), |m| m.tag.label())).collect()
+        };
+        let text = "o Banco Central ( BC ) do Brasil e o Fundo Monetário ( FMI , ONU ) Internacional";
+
+        // Desligado por padrão
+        assert!(tags(&engine, text).iter().all(|t| t == "O"));
+
+        engine.set_gap_tolerance(GapTolerance::parenthetical(2));
+        let gapped = tags(&engine, text);
+        assert_eq!(&gapped[1..8], ["B-ORG", "I-ORG", "I-ORG", "I-ORG", "I-ORG", "I-ORG", "I-ORG"]);
+        // Três tokens entre parênteses excedem o limite
+        assert!(gapped[10..].iter().all(|t| t == "O"));
+
+        // Casamento exato mantém a confiança cheia; cada token intercalado a reduz
+        let exact = engine.apply(&tokenize("o Banco Central do Brasil"));
+        let gapped = engine.apply(&tokenize(text));
+        let (full, discounted) = (exact[1].as_ref().unwrap().confidence, gapped[1].as_ref().unwrap().confidence);
+        assert!((discounted - full * 0.9).abs() < 1e-9);
+
+        // Intercalação sem parênteses só com `any`
+        let bare = "o Banco Central nacional do Brasil";
+        assert!(tags(&engine, bare).iter().all(|t| t == "O"));
+        engine.set_gap_tolerance(GapTolerance::any(1).with_discount(0.5));
+        assert_eq!(&tags(&engine, bare)[1..], ["B-ORG", "I-ORG", "I-ORG", "I-ORG", "I-ORG"]);
+        assert!((engine.apply(&tokenize(bare))[1].as_ref().unwrap().confidence - full * 0.5).abs() < 1e-9);
+        // Outras categorias não toleram intercalações
+        engine.add_misc("Copa do Mundo");
+        assert!(tags(&engine, "a Copa do lindo Mundo").iter().all(|t| t == "O"));
+    }
+
+    #[test]
+    fn test_temporal_rules() {
+        let mut engine = RuleEngine::new();
+        engine.add_date("século XX");
+
+        let tokens = tokenize("no início do século XX , às 14h30");
+        let matches = engine.apply(&tokens);
+
+        assert_eq!(matches[3].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Date));
+        assert_eq!(matches[4].as_ref().unwrap().tag, Tag::Inside(EntityCategory::Date));
+        let time = matches[7].as_ref().unwrap();
+        assert_eq!(time.tag, Tag::Begin(EntityCategory::Time));
+        assert_eq!(time.rule_name, "time_pattern");
+
+        // Mês capitalizado dentro de nome próprio não é data
+        engine.add_date("janeiro");
+        let tokens = tokenize("do Rio de Janeiro em 15 de janeiro");
+        let matches = engine.apply(&tokens);
+        assert!(matches[3].as_ref().is_none_or(|m| m.rule_name != "date_gazetteer"));
+        assert_eq!(matches[7].as_ref().unwrap().tag, Tag::Begin(EntityCategory::Date));
+    }
+
+    #[test]
+    fn test_session_matches_batch_apply() {
+        let mut engine = RuleEngine::new();
+        engine.add_person("Lula");
+        engine.add_person("Inácio");
+        engine.add_org("Banco do Brasil");
+        engine.add_org("Banco Central");
+        engine.add_date("século XX");
+        engine.add_date("janeiro");
+        engine.add_location("Rio");
+
+        let text = "o presidente Lula Inácio visitou o Banco do Rio e o Banco Central \
+                    no Rio de Janeiro em janeiro , às 14h30 , e o Banco do Brasil no século XX";
+        let tokens = tokenize(text);
+        let batch: Vec<(usize, Tag, String)> = engine
+            .apply(&tokens)
+            .into_iter()
+            .flatten()
+            .map(|m| (m.token_index, m.tag, m.rule_name))
+            .collect();
+
+        let mut session = engine.session();
+        let mut streamed = Vec::new();
+        for token in tokens.clone() {
+            streamed.extend(session.push(token));
+            // A sessão guarda só os pendentes mais o contexto
+            assert!(session.pending() <= 3);
+        }
+        streamed.extend(session.finish());
+        let streamed: Vec<(usize, Tag, String)> =
+            streamed.into_iter().map(|m| (m.token_index, m.tag, m.rule_name)).collect();
+
+        assert_eq!(streamed, batch);
+    }
+
+    #[test]
+    fn test_regex_rules() {
+        let mut engine = RuleEngine::new();
+        let rules = [
+            ("processo_cnj", r"\d{7}-\d{2}\.\d{4}\.\d\.\d{2}\.\d{4}", EntityCategory::Misc),
+            ("email", r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+", EntityCategory::Misc),
+            ("telefone", r"\(\d{2}\) ?9?\d{4}-\d{4}", EntityCategory::Misc),
+            ("dinheiro", r"R\$ ?\d{1,3}(?:\.\d{3})*(?:,\d{2})?", EntityCategory::Misc),
+            ("data_numerica", r"\d{2}/\d{2}/\d{4}", EntityCategory::Date),
+        ];
+        for (name, pattern, category) in rules {
+            engine.add_regex_rule(name, pattern, category, 0.95).unwrap();
+        }
+        let text = "No processo 0001234-56.2023.8.26.0100, pago em 15/03/2024, a multa foi de R$ 1.500,00; \
+                    contato: joao.silva@exemplo.com.br ou (11) 98765-4321.";
+        let tokens = tokenize(text);
+        let matches = engine.apply(&tokens);
+        let key = |m: &RuleMatch| (m.token_index, m.tag.clone(), m.rule_name.clone());
+        let batch: Vec<_> = matches.iter().flatten().map(key).collect();
+        let found = |rule: &str| -> String {
+            let span: Vec<usize> = matches.iter().flatten().filter(|m| m.rule_name == rule).map(|m| m.token_index).collect();
+            assert!(!span.is_empty(), "{rule} não disparou");
+            text[tokens[span[0]].start..tokens[*span.last().unwrap()].end].to_string()
+        };
+        assert_eq!(found("processo_cnj"), "0001234-56.2023.8.26.0100");
+        assert_eq!(found("data_numerica"), "15/03/2024");
+        assert_eq!(found("dinheiro"), "R$ 1.500,00");
+        assert_eq!(found("email"), "joao.silva@exemplo.com.br");
+        assert_eq!(found("telefone"), "(11) 98765-4321");
+
+        // A regra é gravada como texto e recompilada
+        let json = serde_json::to_string(&engine).unwrap();
+        let restored: RuleEngine = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.regex_rules()[1].pattern(), rules[1].1);
+        assert_eq!(restored.apply(&tokens).iter().flatten().map(key).collect::<Vec<_>>(), batch);
+
+        // Casamentos que já chegaram ao último token esperam pelo próximo
+        let mut session = engine.session();
+        let mut streamed = Vec::new();
+        for token in tokens.clone() {
+            streamed.extend(session.push(token));
+        }
+        streamed.extend(session.finish());
+        assert_eq!(streamed.iter().map(key).collect::<Vec<_>>(), batch);
+
+        assert!(matches!(
+            engine.add_regex_rule("quebrada", "[a-", EntityCategory::Misc, 0.9),
+            Err(PatternError::InvalidRegex(_))
+        ));
+    }
+
+    thread_local! {
+        /// Tokens passados a [`law_ref_words`] nesta thread.
+        pub(super) static LOWERCASED_TOKENS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[test]
+    fn test_law_ref_lowercases_each_token_once() {
+        let engine = RuleEngine::new();
+        let text = "A Lei nº 8.078/1990 e o art. 5º do código. ".repeat(200);
+        let tokens = tokenize(&text);
+        LOWERCASED_TOKENS.with(|count| count.set(0));
+        let matches = engine.apply(&tokens);
+        assert_eq!(matches.iter().flatten().filter(|m| m.tag == Tag::Begin(EntityCategory::Misc)).count(), 400);
+        // Uma vez por aplicação, não uma vez por posição de início
+        assert!(LOWERCASED_TOKENS.with(|count| count.get()) <= tokens.len(), "{}", tokens.len());
+    }
+
+    #[test]
+    fn test_law_references() {
+        let engine = RuleEngine::new();
+        let tokens = tokenize(
+            "Conforme o art. 5º, §2º, inciso III, da Lei nº 8.078/1990 e a Emenda Constitucional 45, o réu recorreu.",
+        );
+        let matches = engine.apply(&tokens);
+        let spans: Vec<String> = tokens_to_spans_text(&tokens, &matches);
+        assert_eq!(spans, vec!["art. 5º , § 2º , inciso III , da Lei nº 8.078 / 1990", "Emenda Constitucional 45"]);
+        assert!(matches.iter().flatten().all(|m| m.rule_name == "law_ref"));
+
+        // Em sessão, a citação só é confirmada quando não pode mais crescer
+        let mut session = engine.session();
+        let mut streamed = Vec::new();
+        for token in tokens.clone() {
+            streamed.extend(session.push(token));
+        }
+        streamed.extend(session.finish());
+        let key = |m: RuleMatch| (m.token_index, m.tag, m.rule_name);
+        assert_eq!(
+            streamed.into_iter().map(key).collect::<Vec<_>>(),
+            matches.into_iter().flatten().map(key).collect::<Vec<_>>()
+        );
+
+        // Sem número não há citação; "Lei Áurea" fica para o gazetteer de MISC
+        assert!(engine.apply(&tokenize("o artigo publicado e a Lei Áurea")).iter().all(Option::is_none));
+        assert_eq!(
+            tokens_to_spans_text(&tokenize("nos arts. 1º e 2º e no parágrafo único"), &engine.apply(&tokenize("nos arts. 1º e 2º e no parágrafo único"))),
+            vec!["arts. 1º e 2º", "parágrafo único"]
+        );
+    }
+
+    /// Textos (tokens separados por espaço) das sequências B-/I- marcadas pelas regras.
+    fn tokens_to_spans_text(tokens: &[Token], matches: &[Option<RuleMatch>]) -> Vec<String> {
+        let mut spans: Vec<Vec<&str>> = Vec::new();
+        for (token, m) in tokens.iter().zip(matches) {
+            match m.as_ref().map(|m| &m.tag) {
+                Some(Tag::Begin(_)) => spans.push(vec![&token.text]),
+                Some(Tag::Inside(_)) => spans.last_mut().unwrap().push(&token.text),
+                _ => {}
+            }
+        }
+        spans.into_iter().map(|s| s.join(" ")).collect()
+    }
+
+    #[test]
+    fn test_rule_stats_merge() {
+        let mut a = RuleStats::new();
+        a.analyses = 1;
+        a.record("person_gazetteer", false, true);
+        a.record("person_gazetteer", true, true);
+
+        let mut total = RuleStats::new();
+        total.merge(&a);
+        total.merge(&a);
+
+        assert_eq!(total.analyses, 2);
+        assert_eq!(total.total_fired(), 4);
+        assert_eq!(total.rules["person_gazetteer"].disagreed, 4);
+        assert!((total.override_rate("person_gazetteer") - 0.5).abs() < 1e-9);
+        assert_eq!(total.override_rate("inexistente"), 0.0);
+    }
 }
@@ -0,0 +1,522 @@
+//! # Amostras de Demonstração e Fixtures de Avaliação
+//!
+//! Textos curtos, cada um com as **entidades esperadas** anotadas à mão. Servem a dois
+//! propósitos, mantidos separados do corpus de treinamento ([`crate::corpus`]):
+//!
+//! - **Interface**: os botões de exemplo da página de NER (`/demo-texts`).
+//! - **Smoke tests**: [`SampleSet::evaluate`] roda o pipeline em cada amostra e informa
+//!   quais entidades esperadas foram encontradas e quais faltaram.
+//! - **Manutenção dos dicionários**: [`suggest_gazetteer_additions`] agrupa as entidades
+//!   que faltaram (falsos negativos) por forma de superfície e sugere quais delas
+//!   acrescentar aos gazetteers, com contagens e sentenças de exemplo.
+//!
+//! As amostras são organizadas em **conjuntos** ([`SampleSet`]) reunidos num
+//! [`SampleRegistry`]. O registro já vem com o conjunto embutido [`DEMO_SET`], e o usuário
+//! pode registrar os seus próprios conjuntos (ex: textos do seu domínio).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::samples::{Sample, SampleRegistry, SampleSet};
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::{AlgorithmMode, NerPipeline};
+//!
+//! let mut registry = SampleRegistry::builtin();
+//! registry.register(SampleSet::new("meu-dominio").with_sample(
+//!     Sample::new("Petróleo", "A Petrobras anunciou lucro.")
+//!         .expect("Petrobras", EntityCategory::Org),
+//! ));
+//!
+//! let pipeline = NerPipeline::new();
+//! let checks = registry.get("meu-dominio").unwrap().evaluate(&pipeline, AlgorithmMode::RulesOnly);
+//! assert!(checks[0].missing.is_empty());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::features::Gazetteers;
+use crate::offsets::slice_lossy;
+use crate::pipeline::{AlgorithmMode, NerPipeline};
+use crate::tagger::{EntityCategory, EntitySpan};
+use crate::tokenizer::{sentence_ranges, tokenize, TokenizerMode};
+
+use EntityCategory::{Date, Loc, Misc, Org, Per, Time};
+
+/// Nome do conjunto embutido de textos de demonstração.
+pub const DEMO_SET: &str = "demo";
+
+/// Uma entidade que deve ser encontrada numa amostra.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedEntity {
+    pub text: String,
+    pub category: EntityCategory,
+}
+
+/// Um texto de exemplo com as entidades esperadas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    /// Título curto (ex: o domínio temático, "Saúde").
+    pub title: String,
+    pub text: String,
+    pub expected: Vec<ExpectedEntity>,
+}
+
+impl Sample {
+    pub fn new(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.to_string(), expected: Vec::new() }
+    }
+
+    /// Acrescenta uma entidade esperada.
+    pub fn expect(mut self, text: &str, category: EntityCategory) -> Self {
+        self.expected.push(ExpectedEntity { text: text.to_string(), category });
+        self
+    }
+
+    /// Compara as entidades previstas com as esperadas.
+    ///
+    /// Uma entidade esperada é encontrada quando alguma previsão tem a mesma categoria
+    /// e o mesmo texto (ignorando maiúsculas e espaços repetidos).
+    pub fn check(&self, predicted: &[EntitySpan]) -> SampleCheck {
+        let (found, missing) = self.expected.iter().cloned().partition(|e| {
+            predicted
+                .iter()
+                .any(|p| p.category == e.category && normalize(&p.text) == normalize(&e.text))
+        });
+        SampleCheck { title: self.title.clone(), found, missing }
+    }
+}
+
+/// Resultado de [`Sample::check`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleCheck {
+    pub title: String,
+    pub found: Vec<ExpectedEntity>,
+    pub missing: Vec<ExpectedEntity>,
+}
+
+impl SampleCheck {
+    /// Fração das entidades esperadas que foram encontradas (1.0 se não havia nenhuma).
+    pub fn recall(&self) -> f64 {
+        let total = self.found.len() + self.missing.len();
+        if total == 0 {
+            1.0
+        } else {
+            self.found.len() as f64 / total as f64
+        }
+    }
+}
+
+/// Quantas sentenças de exemplo cada [`GazetteerSuggestion`] guarda.
+pub const MAX_SUGGESTION_EXAMPLES: usize = 3;
+
+/// Candidata a entrada de gazetteer: uma entidade esperada que o pipeline não encontrou.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GazetteerSuggestion {
+    /// Forma de superfície (a primeira grafia vista).
+    pub text: String,
+    pub category: EntityCategory,
+    /// Quantas vezes a entidade faltou nas avaliações.
+    pub count: usize,
+    /// Até [`MAX_SUGGESTION_EXAMPLES`] sentenças em que ela aparece.
+    pub examples: Vec<String>,
+}
+
+/// Agrega os falsos negativos das avaliações em sugestões de gazetteer.
+///
+/// Recebe pares (amostra, resultado de [`Sample::check`]) — por exemplo
+/// `set.samples.iter().zip(&set.evaluate(...))` — e agrupa as entidades que faltaram
+/// pela forma normalizada e categoria. Ficam de fora:
+///
+/// - entidades que os gazetteers já cobrem ([`Gazetteers::contains_phrase`]): o erro
+///   está em outro lugar, acrescentá-las não ajudaria;
+/// - datas e horários, reconhecidos por padrões e não por dicionário.
+///
+/// As sugestões vêm ordenadas da mais frequente para a menos frequente.
+///
+/// ```rust
+/// use ner_core::features::Gazetteers;
+/// use ner_core::samples::{suggest_gazetteer_additions, Sample};
+/// use ner_core::tagger::EntityCategory;
+///
+/// let sample = Sample::new("t", "A Zubrex cresceu. Analistas elogiam a Zubrex.")
+///     .expect("Zubrex", EntityCategory::Org);
+/// let check = sample.check(&[]);
+///
+/// let suggestions = suggest_gazetteer_additions([(&sample, &check)], &Gazetteers::new());
+/// assert_eq!(suggestions[0].text, "Zubrex");
+/// assert_eq!(suggestions[0].examples, vec!["A Zubrex cresceu."]);
+/// ```
+pub fn suggest_gazetteer_additions<'a>(
+    results: impl IntoIterator<Item = (&'a Sample, &'a SampleCheck)>,
+    gazetteers: &Gazetteers,
+) -> Vec<GazetteerSuggestion> {
+    let mut suggestions: Vec<GazetteerSuggestion> = Vec::new();
+    let mut index: HashMap<(String, EntityCategory), usize> = HashMap::new();
+
+    for (sample, check) in results {
+        for entity in &check.missing {
+            if matches!(entity.category, EntityCategory::Date | EntityCategory::Time)
+                || gazetteers.contains_phrase(entity.category, &entity.text)
+            {
+                continue;
+            }
+            let key = (normalize(&entity.text), entity.category);
+            let slot = *index.entry(key).or_insert_with(|| {
+                suggestions.push(GazetteerSuggestion {
+                    text: entity.text.clone(),
+                    category: entity.category,
+                    count: 0,
+                    examples: Vec::new(),
+                });
+                suggestions.len() - 1
+            });
+            let suggestion = &mut suggestions[slot];
+            suggestion.count += 1;
+            if let Some(sentence) = sentence_containing(&sample.text, &entity.text) {
+                if suggestion.examples.len() < MAX_SUGGESTION_EXAMPLES && !suggestion.examples.contains(&sentence) {
+                    suggestion.examples.push(sentence);
+                }
+            }
+        }
+    }
+
+    // Ordenação estável: empates mantêm a ordem em que apareceram
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.count));
+    suggestions
+}
+
+/// A primeira sentença de `text` que contém `needle`.
+fn sentence_containing(text: &str, needle: &str) -> Option<String> {
+    let tokens = tokenize(text);
+    sentence_ranges(&tokens)
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| slice_lossy(text, tokens[range.start].start, tokens[range.end - 1].end))
+        .find(|sentence| sentence.contains(needle))
+        .map(str::to_string)
+}
+
+/// Um conjunto nomeado de amostras.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleSet {
+    pub name: String,
+    pub samples: Vec<Sample>,
+}
+
+impl SampleSet {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), samples: Vec::new() }
+    }
+
+    pub fn with_sample(mut self, sample: Sample) -> Self {
+        self.samples.push(sample);
+        self
+    }
+
+    /// Roda o pipeline em cada amostra e compara com as entidades esperadas.
+    pub fn evaluate(&self, pipeline: &NerPipeline, mode: AlgorithmMode) -> Vec<SampleCheck> {
+        self.samples
+            .iter()
+            .map(|sample| {
+                let (_, entities) =
+                    pipeline.analyze_with_mode(&sample.text, mode, TokenizerMode::Standard);
+                sample.check(&entities)
+            })
+            .collect()
+    }
+}
+
+/// Registro de conjuntos de amostras, em ordem de registro.
+#[derive(Debug, Clone, Default)]
+pub struct SampleRegistry {
+    sets: Vec<SampleSet>,
+}
+
+impl SampleRegistry {
+    /// Registro vazio.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registro com o conjunto embutido [`DEMO_SET`].
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(demo_samples());
+        registry
+    }
+
+    /// Registra um conjunto. Um conjunto com o mesmo nome é substituído.
+    pub fn register(&mut self, set: SampleSet) {
+        match self.sets.iter_mut().find(|s| s.name == set.name) {
+            Some(existing) => *existing = set,
+            None => self.sets.push(set),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SampleSet> {
+        self.sets.iter().find(|s| s.name == name)
+    }
+
+    pub fn sets(&self) -> &[SampleSet] {
+        &self.sets
+    }
+
+    /// Todas as amostras de todos os conjuntos, com o nome do conjunto.
+    pub fn samples(&self) -> impl Iterator<Item = (&str, &Sample)> {
+        self.sets
+            .iter()
+            .flat_map(|set| set.samples.iter().map(move |s| (set.name.as_str(), s)))
+    }
+}
+
+/// O conjunto embutido de textos de demonstração (um por domínio temático).
+pub fn demo_samples() -> SampleSet {
+    let mut set = SampleSet::new(DEMO_SET);
+    for (title, text, expected) in DEMO_SAMPLES {
+        let sample = expected
+            .iter()
+            .fold(Sample::new(title, text), |s, (entity, category)| s.expect(entity, *category));
+        set.samples.push(sample);
+    }
+    set
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+type StaticSample = (&'static str, &'static str, &'static [(&'static str, EntityCategory)]);
+
+const DEMO_SAMPLES: &[StaticSample] = &[
+    (
+        "Saúde",
+        "O Hospital Albert Einstein, localizado no Morumbi em São Paulo, é uma referência internacional em medicina de alta complexidade. Juntamente com o Instituto Butantan e a Fundação Oswaldo Cruz (Fiocruz), a instituição tem liderado pesquisas inovadoras no combate a doenças tropicais. A Agência Nacional de Vigilância Sanitária (Anvisa) aprovou recentemente novos protocolos clínicos densenvolvidos pela pesquisadora Margareth Dalcolmo para o tratamento de variantes da Covid-19.",
+        &[
+            ("Hospital Albert Einstein", Org),
+            ("Morumbi", Loc),
+            ("São Paulo", Loc),
+            ("Instituto Butantan", Org),
+            ("Fundação Oswaldo Cruz", Org),
+            ("Fiocruz", Org),
+            ("Agência Nacional de Vigilância Sanitária", Org),
+            ("Anvisa", Org),
+            ("Margareth Dalcolmo", Per),
+            ("Covid-19", Misc),
+        ],
+    ),
+    (
+        "História",
+        "Em 7 de setembro de 1822, Dom Pedro I proclamou a Independência do Brasil às margens do Rio Ipiranga. Décadas mais tarde, a Princesa Isabel sancionou a Lei Áurea em 13 de maio de 1888, encerrando oficialmente o ciclo da escravidão no país. Figuras como Zumbi dos Palmares, líder do maior quilombo das Américas, e Tiradentes, mártir da Inconfidência Mineira, são celebrados como heróis nacionais que lutaram pela liberdade e justiça social.",
+        &[
+            ("7 de setembro de 1822", Date),
+            ("Dom Pedro I", Per),
+            ("Independência do Brasil", Misc),
+            ("Rio Ipiranga", Loc),
+            ("Princesa Isabel", Per),
+            ("Lei Áurea", Misc),
+            ("13 de maio de 1888", Date),
+            ("Zumbi dos Palmares", Per),
+            ("Américas", Loc),
+            ("Tiradentes", Per),
+            ("Inconfidência Mineira", Misc),
+        ],
+    ),
+    (
+        "Tecnologia",
+        "A startup brasileira Nubank, fundada por David Vélez, Cristina Junqueira e Edward Wible, revolucionou o setor bancário na América Latina. Com sede em São Paulo, a empresa expandiu operações para o México e Colômbia, alcançando mais de 90 milhões de clientes. Recentemente, a Embraer anunciou uma parceria estratégica com a Boeing para o desenvolvimento de combustíveis sustentáveis de aviação, reforçando a posição do Brasil como líder em tecnologia aeroespacial.",
+        &[
+            ("Nubank", Org),
+            ("David Vélez", Per),
+            ("Cristina Junqueira", Per),
+            ("Edward Wible", Per),
+            ("América Latina", Loc),
+            ("São Paulo", Loc),
+            ("México", Loc),
+            ("Colômbia", Loc),
+            ("Embraer", Org),
+            ("Boeing", Org),
+            ("Brasil", Loc),
+        ],
+    ),
+    (
+        "Cultura",
+        "A Semana de Arte Moderna de 1922, realizada no Theatro Municipal de São Paulo, contou com a participação de Mário de Andrade, Oswald de Andrade e Tarsila do Amaral. O evento marcou o início do Modernismo no Brasil, rompendo com o conservadorismo acadêmico. Na música, Heitor Villa-Lobos e, posteriormente, Carmen Miranda, levaram a identidade cultural brasileira para os palcos internacionais, consolidando o samba e a bossa nova como gêneros de exportação.",
+        &[
+            ("Semana de Arte Moderna", Misc),
+            ("1922", Date),
+            ("Theatro Municipal", Loc),
+            ("São Paulo", Loc),
+            ("Mário de Andrade", Per),
+            ("Oswald de Andrade", Per),
+            ("Tarsila do Amaral", Per),
+            ("Modernismo", Misc),
+            ("Brasil", Loc),
+            ("Heitor Villa-Lobos", Per),
+            ("Carmen Miranda", Per),
+        ],
+    ),
+    (
+        "Desambiguação",
+        "A socialite Paris Hilton viajou para Paris, capital da França, na última semana. Durante a viagem, ela sentou em um banco próximo à Torre Eiffel após autorizar saques em sua conta no Banco do Brasil. O porta-voz do Estado do Rio de Janeiro confirmou que o estado de calamidade pública impede o envio de representantes.",
+        &[
+            ("Paris Hilton", Per),
+            ("Paris", Loc),
+            ("França", Loc),
+            ("Torre Eiffel", Loc),
+            ("Banco do Brasil", Org),
+            ("Estado do Rio de Janeiro", Loc),
+        ],
+    ),
+    (
+        "Tokenização",
+        "A Sra. Silva (nascida em 15/03/1980) comprou U$5.000,00 na bolsa de N.Y. às 14h30min usando seu e-mail ana.silva@exemplo.com.br! O site www.financas.com reportou que as ações da Apple Inc. subiram 2,5%. E aí, será que a Bovespa (IBOV) vai acompanhar essa alta-frequência de mercado?",
+        &[
+            ("Silva", Per),
+            ("15/03/1980", Date),
+            ("N.Y.", Loc),
+            ("14h30min", Time),
+            ("Apple Inc.", Org),
+            ("Bovespa", Org),
+            ("IBOV", Org),
+        ],
+    ),
+    (
+        "Esportes",
+        "Neymar Jr. marcou dois gols pelo Al-Hilal no estádio King Fahd em Riad, na Arábia Saudita. A Confederação Brasileira de Futebol (CBF) convocou Vinícius Jr., do Real Madrid, e Endrick, também do Real Madrid, para a Copa América. O técnico Dorival Júnior declarou que o Maracanã será palco do próximo amistoso contra a Argentina de Lionel Messi.",
+        &[
+            ("Neymar Jr.", Per),
+            ("Al-Hilal", Org),
+            ("King Fahd", Loc),
+            ("Riad", Loc),
+            ("Arábia Saudita", Loc),
+            ("Confederação Brasileira de Futebol", Org),
+            ("CBF", Org),
+            ("Vinícius Jr.", Per),
+            ("Real Madrid", Org),
+            ("Endrick", Per),
+            ("Copa América", Misc),
+            ("Dorival Júnior", Per),
+            ("Maracanã", Loc),
+            ("Argentina", Loc),
+            ("Lionel Messi", Per),
+        ],
+    ),
+    (
+        "Direito",
+        "O Supremo Tribunal Federal (STF), sob a presidência do Ministro Luís Roberto Barroso, julgou a constitucionalidade da Emenda Constitucional nº 45. O Procurador-Geral da República, Paulo Gonet, apresentou parecer ao Tribunal Superior Eleitoral (TSE) em Brasília. A Ordem dos Advogados do Brasil (OAB) emitiu nota conjunta com o Conselho Nacional de Justiça (CNJ) sobre a reforma do Código Penal.",
+        &[
+            ("Supremo Tribunal Federal", Org),
+            ("STF", Org),
+            ("Luís Roberto Barroso", Per),
+            ("Emenda Constitucional nº 45", Misc),
+            ("Paulo Gonet", Per),
+            ("Tribunal Superior Eleitoral", Org),
+            ("TSE", Org),
+            ("Brasília", Loc),
+            ("Ordem dos Advogados do Brasil", Org),
+            ("OAB", Org),
+            ("Conselho Nacional de Justiça", Org),
+            ("CNJ", Org),
+            ("Código Penal", Misc),
+        ],
+    ),
+    (
+        "Economia",
+        "O Banco Central do Brasil, presidido por Gabriel Galípolo, manteve a taxa Selic em 13,75%. O Fundo Monetário Internacional (FMI) revisou a previsão de crescimento do PIB brasileiro. A Petrobras anunciou investimentos de R$ 100 bilhões em parceria com a Shell e a TotalEnergies para exploração de petróleo na Bacia de Santos, litoral de São Paulo.",
+        &[
+            ("Banco Central do Brasil", Org),
+            ("Gabriel Galípolo", Per),
+            ("Selic", Misc),
+            ("Fundo Monetário Internacional", Org),
+            ("FMI", Org),
+            ("PIB", Misc),
+            ("Petrobras", Org),
+            ("Shell", Org),
+            ("TotalEnergies", Org),
+            ("Bacia de Santos", Loc),
+            ("São Paulo", Loc),
+        ],
+    ),
+    (
+        "Ciência",
+        "Pesquisadores do Instituto Nacional de Pesquisas Espaciais (INPE), em São José dos Campos, detectaram aumento no desmatamento da Amazônia usando satélites do programa CBERS, desenvolvido em parceria com a Agência Espacial Chinesa. A bióloga Natália Pasternak, do Instituto Questão de Ciência, publicou estudo na revista Nature sobre a eficácia de vacinas produzidas pelo Instituto Butantan em colaboração com a Universidade de Oxford.",
+        &[
+            ("Instituto Nacional de Pesquisas Espaciais", Org),
+            ("INPE", Org),
+            ("São José dos Campos", Loc),
+            ("Amazônia", Loc),
+            ("CBERS", Misc),
+            ("Agência Espacial Chinesa", Org),
+            ("Natália Pasternak", Per),
+            ("Instituto Questão de Ciência", Org),
+            ("Nature", Misc),
+            ("Instituto Butantan", Org),
+            ("Universidade de Oxford", Org),
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_samples_are_consistent() {
+        let set = demo_samples();
+        assert_eq!(set.samples.len(), 10);
+        for sample in &set.samples {
+            assert!(!sample.expected.is_empty(), "{}", sample.title);
+            for entity in &sample.expected {
+                assert!(sample.text.contains(&entity.text), "{}: {}", sample.title, entity.text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_and_recall() {
+        let sample = Sample::new("t", "A Petrobras e a Vale.")
+            .expect("Petrobras", Org)
+            .expect("Vale", Org);
+        let check = sample.check(&[EntitySpan::test_at("petrobras", "petrobras", Org), EntitySpan::test_at("Vale", "Vale", Loc)]);
+        assert_eq!(check.found.len(), 1);
+        assert_eq!(check.missing, vec![ExpectedEntity { text: "Vale".into(), category: Org }]);
+        assert_eq!(check.recall(), 0.5);
+    }
+
+    #[test]
+    fn test_suggest_gazetteer_additions() {
+        let first = Sample::new("a", "A Zubrex cresceu. Depois, Ana Tavares visitou Recife.")
+            .expect("Zubrex", Org)
+            .expect("Ana Tavares", Per)
+            .expect("Recife", Loc);
+        let second = Sample::new("b", "Em 3 de maio a ZUBREX abriu filial.")
+            .expect("ZUBREX", Org)
+            .expect("3 de maio", Date);
+        let checks = [first.check(&[EntitySpan::test_at("Ana Tavares", "Ana Tavares", Per)]), second.check(&[])];
+
+        let mut gazetteers = Gazetteers::new();
+        gazetteers.locations.insert("recife".to_string());
+        let suggestions = suggest_gazetteer_additions([&first, &second].into_iter().zip(&checks), &gazetteers);
+
+        // Recife já está no gazetteer e datas não viram sugestão
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].text, "Zubrex");
+        assert_eq!(suggestions[0].category, Org);
+        assert_eq!(suggestions[0].count, 2);
+        assert_eq!(suggestions[0].examples, vec!["A Zubrex cresceu.", "Em 3 de maio a ZUBREX abriu filial."]);
+    }
+
+    #[test]
+    fn test_register_replaces_by_name() {
+        let mut registry = SampleRegistry::builtin();
+        registry.register(SampleSet::new("extra").with_sample(Sample::new("a", "Texto.")));
+        registry.register(SampleSet::new("extra"));
+        assert_eq!(registry.sets().len(), 2);
+        assert!(registry.get("extra").unwrap().samples.is_empty());
+        assert_eq!(registry.samples().count(), 10);
+    }
+}
@@ -0,0 +1,295 @@
+//! # Autoavaliação no Corpus Embutido (Self-Check)
+//!
+//! Mudar um gazetteer, um prior ou um peso do CRF pode derrubar a qualidade de um modo
+//! sem que nenhum teste unitário perceba. A autoavaliação reserva uma fatia do corpus
+//! embutido (a sentença `i` fica de fora quando `i % holdout_every == 0`, intercalando os
+//! domínios como em [`crate::eval`]) e mede precisão, revocação e F1 **por entidade** de
+//! cada modo disponível nessa fatia.
+//!
+//! É opcional: [`crate::model::NerModel::build_with_self_check`] treina os modelos
+//! estatísticos só nas sentenças restantes e guarda o relatório no modelo, e
+//! [`crate::NerPipeline::run_self_check`] avalia um modelo já pronto (ex: lido com
+//! [`crate::model::NerModel::load`]). O relatório aparece em
+//! [`crate::Capabilities::self_check`] e pode ser gravado em JSON para comparar builds
+//! com [`SelfCheckReport::regressions`].
+//!
+//! Os gazetteers e os pesos do CRF são derivados do corpus inteiro, então as notas de
+//! `Hybrid`, `RulesOnly` e `CrfOnly` são otimistas: servem para detectar regressões
+//! entre builds, não para comparar com outros sistemas.
+//!
+//! ## Exemplo
+//!
+//! ```rust,no_run
+//! use ner_core::model::NerModel;
+//! use ner_core::language::LanguagePack;
+//! use ner_core::self_check::{SelfCheckConfig, SelfCheckReport};
+//! use ner_core::AlgorithmMode;
+//!
+//! let model = NerModel::build_with_self_check(&LanguagePack::pt_br(), &SelfCheckConfig::default());
+//! let report = model.self_check.as_ref().unwrap();
+//! for score in &report.scores {
+//!     println!("{:?}: F1 = {:.3}", score.mode, score.f1);
+//! }
+//!
+//! // Compara com o relatório de um build anterior
+//! let baseline = SelfCheckReport::load("self_check.json")?;
+//! assert!(report.regressions(&baseline, 0.02).is_empty());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::corpus::AnnotatedSentence;
+use crate::diff::GoldEntity;
+use crate::format;
+use crate::pipeline::{AlgorithmMode, NerPipeline};
+use crate::tagger::{EntityCategory, Tag};
+use crate::tokenizer::TokenizerMode;
+
+/// Qual fatia do corpus reservar e quais modos avaliar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfCheckConfig {
+    /// A sentença `i` vai para a avaliação quando `i % holdout_every == 0`. Padrão: 5
+    /// (um quinto do corpus).
+    pub holdout_every: usize,
+    /// Modos avaliados; `None` avalia todos os disponíveis no pipeline, exceto
+    /// `FeaturesOnly`. Modos indisponíveis são ignorados (não se avalia o fallback).
+    pub modes: Option<Vec<AlgorithmMode>>,
+}
+
+impl SelfCheckConfig {
+    /// Reserva uma a cada `n` sentenças (no mínimo 2, para sobrar treino).
+    pub fn with_holdout_every(mut self, n: usize) -> Self {
+        self.holdout_every = n.max(2);
+        self
+    }
+
+    pub fn with_modes(mut self, modes: impl IntoIterator<Item = AlgorithmMode>) -> Self {
+        self.modes = Some(modes.into_iter().collect());
+        self
+    }
+
+    /// A sentença de índice `i` do corpus fica fora do treino?
+    pub fn is_holdout(&self, i: usize) -> bool {
+        i.is_multiple_of(self.holdout_every.max(2))
+    }
+
+    /// Texto e entidades ouro (offsets de byte) das sentenças reservadas de `corpus`, no
+    /// formato de [`NerPipeline::tune_thresholds`] e [`NerPipeline::calibrate_confidence`].
+    ///
+    /// Sentenças cujas palavras anotadas não são encontradas em ordem no texto são puladas.
+    pub fn holdout_gold(&self, corpus: &[AnnotatedSentence]) -> Vec<(String, Vec<GoldEntity>)> {
+        corpus
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_holdout(*i))
+            .filter_map(|(_, sentence)| Some((sentence.text.to_string(), gold_entities(sentence)?)))
+            .collect()
+    }
+}
+
+impl Default for SelfCheckConfig {
+    fn default() -> Self {
+        Self { holdout_every: 5, modes: None }
+    }
+}
+
+/// Desempenho de um modo na fatia reservada.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModeScore {
+    pub mode: AlgorithmMode,
+    /// Entidades ouro na fatia.
+    pub gold: usize,
+    /// Entidades previstas na fatia.
+    pub predicted: usize,
+    /// Previstas com início, fim e categoria corretos.
+    pub correct: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Queda de F1 de um modo em relação a um relatório anterior.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelfCheckRegression {
+    pub mode: AlgorithmMode,
+    pub baseline_f1: f64,
+    pub f1: f64,
+}
+
+/// Resultado da autoavaliação de um modelo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelfCheckReport {
+    /// Variante da língua do modelo avaliado ([`crate::model::NerModel::language`]).
+    pub language: String,
+    pub holdout_sentences: usize,
+    pub train_sentences: usize,
+    pub scores: Vec<ModeScore>,
+}
+
+impl SelfCheckReport {
+    pub fn score(&self, mode: AlgorithmMode) -> Option<&ModeScore> {
+        self.scores.iter().find(|s| s.mode == mode)
+    }
+
+    /// Modos cujo F1 caiu mais que `tolerance` em relação a `baseline`. Modos ausentes
+    /// de um dos relatórios não são comparados.
+    pub fn regressions(&self, baseline: &SelfCheckReport, tolerance: f64) -> Vec<SelfCheckRegression> {
+        self.scores
+            .iter()
+            .filter_map(|score| {
+                let before = baseline.score(score.mode)?;
+                (before.f1 - score.f1 > tolerance).then_some(SelfCheckRegression {
+                    mode: score.mode,
+                    baseline_f1: before.f1,
+                    f1: score.f1,
+                })
+            })
+            .collect()
+    }
+
+    /// Grava o relatório em disco (JSON).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega um relatório salvo com [`SelfCheckReport::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+}
+
+/// Avalia os modos do pipeline na fatia de `corpus` reservada por `config`
+/// (ver [`SelfCheckConfig::holdout_gold`]).
+pub fn evaluate(pipeline: &NerPipeline, corpus: &[AnnotatedSentence], config: &SelfCheckConfig) -> SelfCheckReport {
+    let holdout: Vec<(String, HashSet<GoldKey>)> = config
+        .holdout_gold(corpus)
+        .into_iter()
+        .map(|(text, gold)| (text, gold.into_iter().map(|g| (g.start, g.end, g.category)).collect()))
+        .collect();
+
+    let modes = match &config.modes {
+        Some(modes) => modes.clone(),
+        None => AlgorithmMode::all().into_iter().filter(|&m| m != AlgorithmMode::FeaturesOnly).collect(),
+    };
+    let scores = modes
+        .into_iter()
+        .filter(|&mode| pipeline.is_available(mode))
+        .map(|mode| {
+            let (mut gold, mut predicted, mut correct) = (0, 0, 0);
+            for (text, expected) in &holdout {
+                let (_, entities) = pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard);
+                let found: HashSet<GoldKey> = entities.into_iter().map(|e| (e.start, e.end, e.category)).collect();
+                gold += expected.len();
+                predicted += found.len();
+                correct += expected.intersection(&found).count();
+            }
+            let precision = ratio(correct, predicted);
+            let recall = ratio(correct, gold);
+            let f1 = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+            ModeScore { mode, gold, predicted, correct, precision, recall, f1 }
+        })
+        .collect();
+
+    let holdout_sentences = (0..corpus.len()).filter(|&i| config.is_holdout(i)).count();
+    SelfCheckReport {
+        language: pipeline.model.language.clone(),
+        holdout_sentences,
+        train_sentences: corpus.len() - holdout_sentences,
+        scores,
+    }
+}
+
+/// Uma entidade ouro ou prevista como chave de comparação: offsets de byte e categoria.
+type GoldKey = (usize, usize, EntityCategory);
+
+/// Entidades ouro da sentença, com offsets de byte no texto, a partir das tags BIO.
+///
+/// `None` se alguma palavra anotada não for encontrada (em ordem) no texto.
+fn gold_entities(sentence: &AnnotatedSentence) -> Option<Vec<GoldEntity>> {
+    let mut offsets = Vec::with_capacity(sentence.annotations.len());
+    let mut cursor = 0;
+    for (word, _) in sentence.annotations {
+        let start = cursor + sentence.text.get(cursor..)?.find(word)?;
+        cursor = start + word.len();
+        offsets.push((start, cursor));
+    }
+
+    let mut gold = Vec::new();
+    let mut open: Option<(usize, usize, EntityCategory)> = None;
+    for (&(start, end), (_, label)) in offsets.iter().zip(sentence.annotations) {
+        match Tag::from_label(label) {
+            Some(Tag::Inside(category)) if open.is_some_and(|(_, _, c)| c == category) => {
+                if let Some(entity) = open.as_mut() {
+                    entity.1 = end;
+                }
+            }
+            Some(Tag::Begin(category) | Tag::Inside(category)) => {
+                gold.extend(open.replace((start, end, category)).map(|(s, e, c)| GoldEntity::new(s, e, c)));
+            }
+            _ => gold.extend(open.take().map(|(s, e, c)| GoldEntity::new(s, e, c))),
+        }
+    }
+    gold.extend(open.map(|(s, e, c)| GoldEntity::new(s, e, c)));
+    Some(gold)
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gold_entities_from_bio() {
+        let sentence = AnnotatedSentence {
+            text: "O presidente Lula visitou o Rio de Janeiro.",
+            domain: "teste",
+            annotations: &[
+                ("O", "O"),
+                ("presidente", "O"),
+                ("Lula", "B-PER"),
+                ("visitou", "O"),
+                ("o", "O"),
+                ("Rio", "B-LOC"),
+                ("de", "I-LOC"),
+                ("Janeiro", "I-LOC"),
+                (".", "O"),
+            ],
+        };
+        let gold = gold_entities(&sentence).unwrap();
+        let text: Vec<(&str, EntityCategory)> = gold.iter().map(|g| (&sentence.text[g.start..g.end], g.category)).collect();
+        assert_eq!(text, vec![("Lula", EntityCategory::Per), ("Rio de Janeiro", EntityCategory::Loc)]);
+
+        let missing = AnnotatedSentence { text: "Lula", domain: "teste", annotations: &[("Dilma", "B-PER")] };
+        assert_eq!(gold_entities(&missing), None);
+    }
+
+    #[test]
+    fn test_regressions_against_baseline() {
+        let score = |mode, f1| ModeScore { mode, gold: 10, predicted: 10, correct: 0, precision: f1, recall: f1, f1 };
+        let report = |scores| SelfCheckReport { language: "pt-BR".to_string(), holdout_sentences: 1, train_sentences: 4, scores };
+        let baseline = report(vec![score(AlgorithmMode::Hybrid, 0.9), score(AlgorithmMode::Hmm, 0.6)]);
+        let current = report(vec![
+            score(AlgorithmMode::Hybrid, 0.89),
+            score(AlgorithmMode::Hmm, 0.5),
+            score(AlgorithmMode::Perceptron, 0.1),
+        ]);
+
+        let regressions = current.regressions(&baseline, 0.02);
+        assert_eq!(regressions, vec![SelfCheckRegression { mode: AlgorithmMode::Hmm, baseline_f1: 0.6, f1: 0.5 }]);
+
+        let path = std::env::temp_dir().join(format!("ner_self_check_{}.json", std::process::id()));
+        current.save(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("\"format_version\""));
+        let loaded = SelfCheckReport::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, current);
+    }
+}
@@ -0,0 +1,135 @@
+//! # Pipeline Compartilhado entre Threads
+//!
+//! O [`NerPipeline`] é `Send + Sync` e só precisa de `&self` para analisar, então um
+//! `Arc<NerPipeline>` já serve vários clientes ao mesmo tempo. O que ele não permite é
+//! mudar o modelo enquanto está em uso. O [`SharedPipeline`] cobre esse caso:
+//!
+//! - Análises pegam uma leitura ([`SharedPipeline::read`]) e rodam em paralelo.
+//! - Atualizações de gazetteer ([`SharedPipeline::add_gazetteer_entry`], ou os atalhos
+//!   [`SharedPipeline::add_person`], [`SharedPipeline::add_location`]...) e a troca do
+//!   modelo inteiro ([`SharedPipeline::swap_model`]) esperam as leituras em curso
+//!   terminarem e bloqueiam as novas só durante a troca.
+//!
+//! Cada entrada vai para o motor de regras e para os gazetteers das features na mesma
+//! escrita, então as duas visões nunca divergem. O servidor web guarda o pipeline num
+//! `SharedPipeline` e aceita novas entradas em `POST /gazetteer` sem reiniciar.
+//!
+//! Assim uma análise nunca vê um gazetteer pela metade: ela enxerga o estado de antes ou
+//! o de depois de cada atualização. O número de [`SharedPipeline::generation`] muda a cada
+//! escrita e identifica a versão vista por uma leitura.
+//!
+//! Segure a leitura só durante a análise: ela impede as escritas (e não é `Send`, então
+//! não pode atravessar um `.await`).
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::shared::SharedPipeline;
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+//!
+//! let shared = SharedPipeline::new(NerPipeline::new());
+//! let count = |shared: &SharedPipeline| {
+//!     let pipeline = shared.read();
+//!     let (_, entities) = pipeline.analyze_with_mode("A Zentrolândia venceu.", AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+//!     entities.iter().filter(|e| e.text == "Zentrolândia").count()
+//! };
+//! assert_eq!(count(&shared), 0);
+//!
+//! shared.add_gazetteer_entry(EntityCategory::Loc, "Zentrolândia");
+//! assert_eq!(count(&shared), 1);
+//! assert_eq!(shared.generation(), 1);
+//!
+//! // Várias entradas numa única escrita
+//! shared.add_gazetteer_entries(EntityCategory::Per, ["Fulano Beltrano", "Sicrana Prado"]);
+//! shared.add_org("Zubrex");
+//! assert_eq!(shared.generation(), 3);
+//! ```
+
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+
+use crate::model::NerModel;
+use crate::pipeline::NerPipeline;
+use crate::tagger::EntityCategory;
+
+/// [`NerPipeline`] que aceita atualizações enquanto atende análises concorrentes.
+#[derive(Default)]
+pub struct SharedPipeline {
+    pipeline: RwLock<NerPipeline>,
+    /// Número de escritas feitas; só muda com a escrita travada.
+    generation: AtomicU64,
+}
+
+impl SharedPipeline {
+    pub fn new(pipeline: NerPipeline) -> Self {
+        Self { pipeline: RwLock::new(pipeline), generation: AtomicU64::new(0) }
+    }
+
+    /// Leitura do pipeline para uma análise. Enquanto ela existir, nenhuma atualização
+    /// acontece (e [`SharedPipeline::generation`] não muda).
+    pub fn read(&self) -> RwLockReadGuard<'_, NerPipeline> {
+        self.pipeline.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Número de atualizações aplicadas desde a criação.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Aplica `f` com acesso exclusivo ao pipeline (ex: trocar a fusão ou os limiares).
+    pub fn update<R>(&self, f: impl FnOnce(&mut NerPipeline) -> R) -> R {
+        let mut pipeline = self.pipeline.write().unwrap_or_else(|e| e.into_inner());
+        let result = f(&mut pipeline);
+        self.generation.fetch_add(1, Ordering::Release);
+        result
+    }
+
+    /// Adiciona uma entrada aos gazetteers do modelo (regras e features de uma vez).
+    pub fn add_gazetteer_entry(&self, category: EntityCategory, name: &str) {
+        self.update(|pipeline| pipeline.model.add_gazetteer_entry(category, name));
+    }
+
+    /// Como [`SharedPipeline::add_gazetteer_entry`] para vários nomes, numa única escrita
+    /// (as análises veem todos ou nenhum). Retorna quantos nomes foram adicionados.
+    pub fn add_gazetteer_entries<'a>(&self, category: EntityCategory, names: impl IntoIterator<Item = &'a str>) -> usize {
+        self.update(|pipeline| {
+            names.into_iter().filter(|name| !name.trim().is_empty()).fold(0, |added, name| {
+                pipeline.model.add_gazetteer_entry(category, name.trim());
+                added + 1
+            })
+        })
+    }
+
+    pub fn add_person(&self, name: &str) {
+        self.add_gazetteer_entry(EntityCategory::Per, name);
+    }
+
+    pub fn add_location(&self, name: &str) {
+        self.add_gazetteer_entry(EntityCategory::Loc, name);
+    }
+
+    pub fn add_org(&self, name: &str) {
+        self.add_gazetteer_entry(EntityCategory::Org, name);
+    }
+
+    pub fn add_misc(&self, name: &str) {
+        self.add_gazetteer_entry(EntityCategory::Misc, name);
+    }
+
+    /// Troca o modelo (ex: um recém-treinado lido com [`NerModel::load`]) e devolve o anterior.
+    pub fn swap_model(&self, model: NerModel) -> NerModel {
+        self.update(|pipeline| mem::replace(&mut pipeline.model, model))
+    }
+
+    pub fn into_inner(self) -> NerPipeline {
+        self.pipeline.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<NerPipeline> for SharedPipeline {
+    fn from(pipeline: NerPipeline) -> Self {
+        Self::new(pipeline)
+    }
+}
@@ -82,14 +82,65 @@ fn dot_product(v1: &[f32], v2: &[f32]) -> f32 {
 /// Simula o processo de um modelo SOTA Span-based:
 /// 1. Avalia todos os pedaços (spans) possíveis do texto até um certo tamanho max.
 /// 2. Para cada pedaço, tira o Dot Product contra os embeddings de TODAS as classes pedidas pelo user.
-/// 3. Retorna os pedaços com score > Threshold.
+/// 3. Retorna os pedaços com score > Threshold, em ordem de leitura (offset inicial,
+///    depois o span mais longo, depois o nome da classe) — a mesma ordem do pipeline.
+///
+/// A saída é **plana**: spans sobrepostos são resolvidos por NMS. Para manter todas as
+/// classes e spans aninhados, use [`simulate_gliner_overlapping`].
 pub fn simulate_gliner(
     tokens: &[Token],
     user_classes: &[String],
     threshold: f32,
     max_span_length: usize,
 ) -> Vec<SotaPrediction> {
+    let mut predictions = score_spans(tokens, user_classes, threshold, max_span_length);
+    let n = tokens.len();
+
+    // Resolução de NMS (Non-Maximum Suppression) simulada para evitar sobreposição
+    // Se há spans cobrindo a mesma área, mantém o de maior score
+    predictions.sort_by(|a, b| b.similarity_score.partial_cmp(&a.similarity_score).unwrap());
     
+    let mut final_preds = Vec::new();
+    let mut used_tokens = vec![false; n];
+    
+    for pred in predictions {
+        let range = pred.entity.start_token..=pred.entity.end_token;
+        let overlap = used_tokens[range.clone()].iter().any(|&used| used);
+        
+        if !overlap {
+            for used in &mut used_tokens[range] {
+                *used = true;
+            }
+            final_preds.push(pred);
+        }
+    }
+
+    sort_by_position(&mut final_preds);
+    final_preds
+}
+
+/// Variante multi-label de [`simulate_gliner`]: sem NMS.
+///
+/// O mesmo trecho pode aparecer com várias classes (uma predição por classe acima do
+/// limiar) e trechos aninhados são mantidos — o comportamento natural de um modelo GLiNER.
+pub fn simulate_gliner_overlapping(
+    tokens: &[Token],
+    user_classes: &[String],
+    threshold: f32,
+    max_span_length: usize,
+) -> Vec<SotaPrediction> {
+    let mut predictions = score_spans(tokens, user_classes, threshold, max_span_length);
+    sort_by_position(&mut predictions);
+    predictions
+}
+
+/// Passos 1 e 2: pontua todos os spans contra todas as classes, mantendo os acima do limiar.
+fn score_spans(
+    tokens: &[Token],
+    user_classes: &[String],
+    threshold: f32,
+    max_span_length: usize,
+) -> Vec<SotaPrediction> {
     // Computa o embedding para as classes solicitadas (uma única vez - "Prompting")
     let class_embeddings: Vec<(String, Embedding)> = user_classes
         .iter()
@@ -106,7 +157,7 @@ pub fn simulate_gliner(
     }
 
     // Processamento estonteante paralelo de todas as spans contra todas as classes via Rayon
-    let mut predictions: Vec<SotaPrediction> = span_ranges
+    span_ranges
         .par_iter()
         .flat_map(|&(start_tok, end_tok)| {
             let start_byte = tokens[start_tok].start;
@@ -147,31 +198,16 @@ pub fn simulate_gliner(
 
             local_preds
         })
-        .collect();
-
-    // Resolução de NMS (Non-Maximum Suppression) simulada para evitar sobreposição
-    // Se há spans cobrindo a mesma área, mantém o de maior score
-    predictions.sort_by(|a, b| b.similarity_score.partial_cmp(&a.similarity_score).unwrap());
-    
-    let mut final_preds = Vec::new();
-    let mut used_tokens = vec![false; n];
-    
-    for pred in predictions {
-        let mut overlap = false;
-        for i in pred.entity.start_token..=pred.entity.end_token {
-            if used_tokens[i] {
-                overlap = true;
-                break;
-            }
-        }
-        
-        if !overlap {
-            final_preds.push(pred.clone());
-            for i in pred.entity.start_token..=pred.entity.end_token {
-                used_tokens[i] = true;
-            }
-        }
-    }
+        .collect()
+}
 
-    final_preds
+/// Ordem estável por posição, como em `tagger::sort_entities`.
+fn sort_by_position(predictions: &mut [SotaPrediction]) {
+    predictions.sort_by(|a, b| {
+        a.entity
+            .start
+            .cmp(&b.entity.start)
+            .then_with(|| b.entity.end.cmp(&a.entity.end))
+            .then_with(|| a.class_name.cmp(&b.class_name))
+    });
 }
@@ -4,30 +4,143 @@
 //! Isso permite naturalmente lidar com **entidades aninhadas** e resolve problemas de consistência do BIO.
 //!
 //! ## Algoritmo
-//! 1. Gera todos os candidatos a span até um tamanho máximo (ex: 6 tokens).
+//! 1. Gera os candidatos a span até um tamanho máximo (ex: 6 tokens), já podados ([`SpanPruning`]).
 //! 2. Extrai features ricas para cada span (bordas, conteúdo, contexto).
 //! 3. Classifica cada span independentemente (ou com estrutura).
-//! 4. Retorna todos os spans classificados como entidade (score > limiar ou argmax != O).
+//! 4. Retorna os spans classificados como entidade (argmax != O), sem sobreposição
+//!    ([`SpanDecoding`]).
+//!
+//! ## Poda de candidatos
+//! Uma sentença de N tokens tem cerca de `N × 6` candidatos, e a grande maioria é
+//! obviamente "O" ("que o", "foi ao"). Filtros baratos, aplicados antes de extrair
+//! features, descartam esses spans:
+//!
+//! - o span precisa ter ao menos um token capitalizado ou de gazetteer;
+//! - o span não pode começar nem terminar em pontuação.
+//!
+//! Opcionalmente, um **beam** por posição inicial mantém apenas os `k` candidatos
+//! mais promissores (mais tokens capitalizados/de gazetteer, menos palavras comuns).
+//!
+//! ```rust
+//! use ner_core::span::{SpanModel, SpanPruning};
+//!
+//! let tokens: Vec<String> = "ontem o Banco do Brasil abriu , disse Ana .".split(' ').map(String::from).collect();
+//! let all = SpanModel::new().with_pruning(SpanPruning::none()).candidate_spans(&tokens);
+//! let pruned = SpanModel::new().candidate_spans(&tokens);
+//! let beam = SpanModel::new().with_pruning(SpanPruning::default().with_beam(2)).candidate_spans(&tokens);
+//! assert!(pruned.len() < all.len());
+//! assert!(beam.len() < all.len() / 2);
+//! assert!(beam.contains(&(2, 5))); // "Banco do Brasil"
+//! ```
+//!
+//! ## Resolução de sobreposições
+//! Cada candidato é classificado isoladamente, então "Banco do Brasil" (ORG) e "Brasil"
+//! (LOC) podem sair juntos. [`SpanModel::predict`] resolve os conflitos segundo o
+//! [`SpanDecoding`] do modelo:
+//!
+//! - **Greedy** (padrão): NMS — aceita os spans em ordem decrescente de probabilidade,
+//!   descartando os que sobrepõem um já aceito.
+//! - **Exact**: programação dinâmica que escolhe o conjunto sem sobreposição de maior
+//!   score total, com score `ln(p(label) / p(O))` por span (o ganho de rotular o trecho
+//!   em vez de deixá-lo como "O").
+//! - **Overlapping**: todos os spans, como vieram do classificador.
+//!
+//! A saída multi-label ([`SpanModel::predict_scored`]) nunca é resolvida.
+//!
+//! ```rust
+//! use ner_core::span::{resolve_overlaps, ScoredSpan, SpanDecoding};
+//!
+//! let span = |start, end, label: &str, p: f64| ScoredSpan { start, end, scores: vec![(label.into(), p), ("O".into(), 1.0 - p)] };
+//! let spans = vec![span(0, 3, "ORG", 0.7), span(0, 1, "PER", 0.8), span(2, 3, "LOC", 0.75)];
+//!
+//! // O NMS fica com o mais provável; a DP, com o par que soma mais evidência
+//! let greedy = resolve_overlaps(spans.clone(), SpanDecoding::Greedy);
+//! assert_eq!(greedy.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), [(0, 1), (2, 3)]);
+//! let exact = resolve_overlaps(vec![span(0, 3, "ORG", 0.95), span(0, 1, "PER", 0.6), span(2, 3, "LOC", 0.6)], SpanDecoding::Exact);
+//! assert_eq!(exact.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), [(0, 3)]);
+//! ```
 
 use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
-use crate::corpus::AnnotatedSentence;
-use crate::features::{FeatureVector, Gazetteers};
+pub use crate::backend::Span;
+use crate::backend::SpanPredictor;
+use crate::corpus::{AnnotatedSentence, OwnedAnnotatedSentence};
+use crate::features::{FeatureExtractor, FeatureVector, Gazetteers, SharedExtractor};
 use crate::tokenizer::Token;
+use crate::training::TrainingSchedule;
+
+/// Palavras comuns dentro de nomes compostos ("Banco do Brasil"), neutras no beam.
+const NAME_CONNECTORS: &[&str] = &["de", "da", "do", "das", "dos", "e"];
+
+/// Filtros aplicados aos candidatos antes da classificação (ver o módulo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanPruning {
+    /// Exige ao menos um token capitalizado ou presente em algum gazetteer.
+    pub require_signal: bool,
+    /// Descarta spans que começam ou terminam em pontuação.
+    pub skip_punctuation_edges: bool,
+    /// Máximo de candidatos por posição inicial (`None` = sem limite).
+    pub beam: Option<usize>,
+}
 
-/// Representa um span (intervalo) de tokens com uma label associada.
+impl Default for SpanPruning {
+    fn default() -> Self {
+        Self { require_signal: true, skip_punctuation_edges: true, beam: None }
+    }
+}
+
+impl SpanPruning {
+    /// Sem poda: todos os spans até o tamanho máximo (o comportamento original).
+    pub fn none() -> Self {
+        Self { require_signal: false, skip_punctuation_edges: false, beam: None }
+    }
+
+    pub fn with_beam(mut self, beam: usize) -> Self {
+        self.beam = Some(beam);
+        self
+    }
+}
+
+/// Como resolver spans previstos que se sobrepõem (ver o módulo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanDecoding {
+    /// Mantém todos os spans, inclusive os sobrepostos.
+    Overlapping,
+    /// NMS: o span mais provável vence cada conflito.
+    #[default]
+    Greedy,
+    /// Conjunto sem sobreposição de maior score total (programação dinâmica).
+    Exact,
+}
+
+/// Um span candidato com a distribuição de probabilidade sobre todos os labels.
 ///
-/// # Exemplo
-/// Em "Universidade de São Paulo", o span "São Paulo":
-/// `Span { start: 2, end: 4, label: "LOC" }`
+/// Usado na saída multi-label: o mesmo trecho pode ser plausivelmente "ORG" e "LOC"
+/// (ex: "São Paulo" como cidade ou como clube).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Span {
+pub struct ScoredSpan {
     /// Índice do token inicial (inclusivo)
     pub start: usize,
     /// Índice do token final (exclusivo)
     pub end: usize,
-    /// Rótulo da entidade (ex: "PER", "ORG")
-    pub label: String,
+    /// Pares (label, probabilidade) em ordem decrescente, incluindo "O".
+    pub scores: Vec<(String, f64)>,
+}
+
+impl ScoredSpan {
+    /// Label mais provável e sua probabilidade.
+    pub fn best(&self) -> (&str, f64) {
+        self.scores.first().map_or(("O", 0.0), |(label, p)| (label.as_str(), *p))
+    }
+
+    fn probability_of(&self, label: &str) -> f64 {
+        self.scores.iter().find(|(l, _)| l == label).map_or(0.0, |(_, p)| *p)
+    }
+
+    fn overlaps(&self, other: &ScoredSpan) -> bool {
+        self.start < other.end && other.start < self.end
+    }
 }
 
 /// Modelo NER baseado em Spans.
@@ -53,6 +166,15 @@ pub struct SpanModel {
     tags: Vec<String>,
     /// Tamanho máximo de span a ser considerado (otimização).
     max_span_len: usize,
+    /// Poda dos candidatos, no treino e na predição.
+    #[serde(default)]
+    pruning: SpanPruning,
+    /// Resolução das sobreposições em [`SpanModel::predict`].
+    #[serde(default)]
+    decoding: SpanDecoding,
+    /// Extrator das features de cada span ([`FeatureExtractor::extract_span`]; não serializado).
+    #[serde(skip)]
+    extractor: SharedExtractor,
 }
 
 impl SpanModel {
@@ -61,9 +183,56 @@ impl SpanModel {
             weights: HashMap::new(),
             tags: Vec::new(),
             max_span_len: 6,
+            pruning: SpanPruning::default(),
+            decoding: SpanDecoding::default(),
+            extractor: SharedExtractor::default(),
         }
     }
 
+    /// Troca o extrator de features (ver [`FeatureExtractor`]). Treine depois de trocá-lo.
+    pub fn with_extractor(mut self, extractor: impl FeatureExtractor + 'static) -> Self {
+        self.set_extractor(extractor);
+        self
+    }
+
+    pub fn set_extractor(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.extractor = SharedExtractor::new(extractor);
+    }
+
+    pub fn extractor(&self) -> &dyn FeatureExtractor {
+        &*self.extractor
+    }
+
+    /// Troca a poda de candidatos. Treine depois de trocá-la: o modelo aprende com os
+    /// mesmos candidatos que verá na predição.
+    pub fn with_pruning(mut self, pruning: SpanPruning) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    pub fn pruning(&self) -> SpanPruning {
+        self.pruning
+    }
+
+    /// Troca a resolução de sobreposições; não exige novo treino.
+    pub fn with_decoding(mut self, decoding: SpanDecoding) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    pub fn set_decoding(&mut self, decoding: SpanDecoding) {
+        self.decoding = decoding;
+    }
+
+    pub fn decoding(&self) -> SpanDecoding {
+        self.decoding
+    }
+
+    /// Indica se o modelo já foi treinado (possui tags conhecidas).
+    pub fn is_trained(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
     /// Treina o modelo Span-based.
     ///
     /// Utiliza um algoritmo do tipo Perceptron/SGD Estruturado ou Local:
@@ -74,13 +243,39 @@ impl SpanModel {
     ///    - Se o modelo prever errado para aquele span específico, atualiza os pesos.
     /// 3. **Observação**: Atualmente treina de forma independente (cada span é classificado isoladamente).
     pub fn train(&mut self, corpus: &[AnnotatedSentence], iterations: usize) {
+        self.train_stream(corpus.iter().map(OwnedAnnotatedSentence::from), iterations);
+    }
+
+    /// Como [`Self::train`], mas lendo as sentenças de um iterador (ex: um
+    /// [`crate::corpus_reader::CorpusReader`]); cada época percorre um clone de `corpus`.
+    pub fn train_stream(&mut self, corpus: impl Iterator<Item = OwnedAnnotatedSentence> + Clone, iterations: usize) {
+        self.collect_tags(corpus.clone());
+        let gaz = Gazetteers::new();
+        for _ in 0..iterations {
+            self.train_epoch(corpus.clone(), &gaz);
+        }
+    }
+
+    /// Como [`Self::train`], percorrendo cada época na ordem do `schedule`
+    /// (embaralhamento, currículo, sobreamostragem; ver [`crate::training`]).
+    pub fn train_scheduled(&mut self, corpus: &[AnnotatedSentence], iterations: usize, schedule: &TrainingSchedule) {
+        let owned: Vec<OwnedAnnotatedSentence> = corpus.iter().map(OwnedAnnotatedSentence::from).collect();
+        self.collect_tags(owned.iter().cloned());
+        let gaz = Gazetteers::new();
+        for epoch in 0..iterations {
+            let order = schedule.epoch_order(&owned, epoch);
+            self.train_epoch(order.into_iter().map(|i| owned[i].clone()), &gaz);
+        }
+    }
+
+    fn collect_tags(&mut self, corpus: impl Iterator<Item = OwnedAnnotatedSentence>) {
         // 1. Coleta tags (excluindo O/B-/I- prefixos, queremos apenas categorias reais + "O")
         let mut tag_set = HashSet::new();
         tag_set.insert("O".to_string());
         
         for s in corpus {
-            for (_i, (_word, tag)) in s.annotations.iter().enumerate() {
-                if tag != &"O" {
+            for (_word, tag) in s.annotations.iter() {
+                if tag != "O" {
                     let clean_tag = tag.trim_start_matches("B-").trim_start_matches("I-");
                     tag_set.insert(clean_tag.to_string());
                 }
@@ -88,43 +283,42 @@ impl SpanModel {
         }
         self.tags = tag_set.into_iter().collect();
         self.tags.sort();
+    }
 
-        let gaz = Gazetteers::new();
+    /// Uma passada pelas sentenças, atualizando os pesos a cada span classificado errado.
+    fn train_epoch(&mut self, sentences: impl Iterator<Item = OwnedAnnotatedSentence>, gaz: &Gazetteers) {
+        for sentence in sentences {
+            // Tokens
+            let tokens: Vec<Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
+                Token::new(text.to_string(), 0, 0, i)
+            }).collect();
+            
+            // Extrai Gold Spans do BIO (converte anotação sequencial para spans)
+            let bio_tags: Vec<&str> = sentence.annotations.iter().map(|(_, t)| t.as_str()).collect();
+            let gold_spans = bio_to_spans(&bio_tags);
+            // Set para busca rápida: (start, end, label)
+            let gold_span_set: HashSet<(usize, usize, String)> = gold_spans.iter()
+                .map(|s| (s.start, s.end, s.label.clone()))
+                .collect();
 
-        for _ in 0..iterations {
-            for sentence in corpus {
-                // Tokens
-                let tokens: Vec<Token> = sentence.annotations.iter().enumerate().map(|(i, (text, _))| {
-                    Token { text: text.to_string(), start: 0, end: 0, index: i }
-                }).collect();
-                
-                // Extrai Gold Spans do BIO (converte anotação sequencial para spans)
-                let bio_tags: Vec<&str> = sentence.annotations.iter().map(|(_, t)| *t).collect();
-                let gold_spans = bio_to_spans(&bio_tags);
-                // Set para busca rápida: (start, end, label)
-                let gold_span_set: HashSet<(usize, usize, String)> = gold_spans.iter()
-                    .map(|s| (s.start, s.end, s.label.clone()))
-                    .collect();
-
-                // Gera candidatos
-                let candidates = self.generate_candidates(tokens.len());
+            // Gera candidatos
+            let candidates = self.generate_candidates(&tokens, gaz);
+            
+            for (start, end) in candidates {
+                let fv = self.extractor.extract_span(&tokens, start, end, gaz);
                 
-                for (start, end) in candidates {
-                    let fv = self.extract_span_features(&tokens, start, end, &gaz);
-                    
-                    // Determina label correto para este span candidato
-                    // Se o span start..end estiver no gold set, usa aquele label. Caso contrário, é "O".
-                    let true_label = gold_span_set.iter()
-                        .find(|(s, e, _)| *s == start && *e == end)
-                        .map(|(_, _, l): &(usize, usize, String)| l.clone())
-                        .unwrap_or_else(|| "O".to_string());
-
-                    // Predição
-                    let pred_label = self.predict_single(&fv);
-
-                    if pred_label != true_label {
-                        self.update(&fv, &true_label, &pred_label);
-                    }
+                // Determina label correto para este span candidato
+                // Se o span start..end estiver no gold set, usa aquele label. Caso contrário, é "O".
+                let true_label = gold_span_set.iter()
+                    .find(|(s, e, _)| *s == start && *e == end)
+                    .map(|(_, _, l): &(usize, usize, String)| l.clone())
+                    .unwrap_or_else(|| "O".to_string());
+
+                // Predição
+                let pred_label = self.predict_single(&fv);
+
+                if pred_label != true_label {
+                    self.update(&fv, &true_label, &pred_label);
                 }
             }
         }
@@ -132,85 +326,105 @@ impl SpanModel {
 
     /// Prediz entidades em uma lista de tokens.
     ///
-    /// Retorna uma lista de objetos `Span` encontrados.
+    /// Retorna uma lista de objetos `Span` encontrados, com as sobreposições resolvidas
+    /// segundo [`SpanModel::decoding`] (ordenados pelo início, salvo em `Overlapping`).
     pub fn predict(&self, tokens: &[String]) -> Vec<Span> {
+        self.predict_decoded(tokens)
+            .into_iter()
+            .map(|scored| Span {
+                start: scored.start,
+                end: scored.end,
+                label: scored.scores[0].0.clone(),
+            })
+            .collect()
+    }
+
+    /// Como [`SpanModel::predict`], preservando a probabilidade de cada label.
+    pub fn predict_decoded(&self, tokens: &[String]) -> Vec<ScoredSpan> {
+        resolve_overlaps(self.predict_scored(tokens), self.decoding)
+    }
+
+    /// Todos os spans classificados, sem resolver sobreposições (saída multi-label).
+    ///
+    /// Retorna apenas os spans cujo label mais provável não é "O"; os scores vêm
+    /// de um softmax sobre os scores lineares de todos os labels.
+    pub fn predict_scored(&self, tokens: &[String]) -> Vec<ScoredSpan> {
         let gaz = Gazetteers::new();
         let input_tokens: Vec<Token> = tokens.iter().enumerate().map(|(i, text)| {
-             Token { text: text.clone(), start: 0, end: 0, index: i }
+             Token::new(text.clone(), 0, 0, i)
         }).collect();
 
-        let candidates = self.generate_candidates(tokens.len());
+        let candidates = self.generate_candidates(&input_tokens, &gaz);
         let mut results = Vec::new();
 
         for (start, end) in candidates {
-            let fv = self.extract_span_features(&input_tokens, start, end, &gaz);
-            let label = self.predict_single(&fv);
-            
-            if label != "O" {
-                results.push(Span {
-                    start,
-                    end,
-                    label,
-                });
+            let fv = self.extractor.extract_span(&input_tokens, start, end, &gaz);
+            let raw: Vec<f64> = self.tags.iter().map(|tag| self.score_label(&fv, tag)).collect();
+            let probs = crate::viterbi::scores_to_probs(&raw);
+
+            let mut scores: Vec<(String, f64)> = self.tags.iter().cloned().zip(probs).collect();
+            // Ordenação estável: em empate prevalece a ordem de `tags`, como no argmax
+            scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            if scores.first().is_some_and(|(label, _)| label != "O") {
+                results.push(ScoredSpan { start, end, scores });
             }
         }
-        
-        // Nota: Esta implementação ingênua pode retornar spans sobrepostos (ex: [0,2] PER e [0,1] LOC).
-        // Um sistema real aplicaria NMS (Non-Maximum Suppression) ou Programação Dinâmica para resolver conflitos.
+
         results
     }
 
-    fn generate_candidates(&self, n_tokens: usize) -> Vec<(usize, usize)> {
+    /// Candidatos `(start, end)` que o modelo classificaria em `tokens`, já podados.
+    pub fn candidate_spans(&self, tokens: &[String]) -> Vec<(usize, usize)> {
+        let input_tokens: Vec<Token> = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Token::new(text.clone(), 0, 0, i))
+            .collect();
+        self.generate_candidates(&input_tokens, &Gazetteers::new())
+    }
+
+    fn generate_candidates(&self, tokens: &[Token], gaz: &Gazetteers) -> Vec<(usize, usize)> {
+        let n_tokens = tokens.len();
+        let pruning = self.pruning;
+        // Classificação barata de cada token, feita uma vez por sentença
+        let signal: Vec<bool> = tokens
+            .iter()
+            .map(|t| {
+                t.text.chars().next().is_some_and(char::is_uppercase)
+                    || gaz.categories().into_iter().any(|c| gaz.contains_phrase(c, &t.text))
+            })
+            .collect();
+        let punctuation: Vec<bool> =
+            tokens.iter().map(|t| !t.text.chars().any(char::is_alphanumeric)).collect();
+        let connector: Vec<bool> =
+            tokens.iter().map(|t| NAME_CONNECTORS.contains(&t.text.to_lowercase().as_str())).collect();
+
         let mut spans = Vec::new();
-        for len in 1..=self.max_span_len {
-            for start in 0..n_tokens {
-                let end = start + len;
-                if end <= n_tokens {
-                    spans.push((start, end));
-                }
+        for start in 0..n_tokens {
+            if pruning.skip_punctuation_edges && punctuation[start] {
+                continue;
             }
+            let mut from_start: Vec<(usize, usize)> = (start + 1..=n_tokens.min(start + self.max_span_len))
+                .filter(|&end| !(pruning.skip_punctuation_edges && punctuation[end - 1]))
+                .filter(|&end| !pruning.require_signal || signal[start..end].contains(&true))
+                .map(|end| (start, end))
+                .collect();
+            if let Some(beam) = pruning.beam {
+                // Mais tokens de sinal e menos palavras comuns primeiro; em empate, o mais curto
+                let promise = |&(s, e): &(usize, usize)| -> i64 {
+                    (s..e).map(|i| if signal[i] { 1 } else if connector[i] { 0 } else { -1 }).sum()
+                };
+                from_start.sort_by_key(|span| (std::cmp::Reverse(promise(span)), span.1));
+                from_start.truncate(beam);
+            }
+            spans.extend(from_start);
         }
+        // Ordem do tamanho para o treino ver primeiro os spans curtos, como antes da poda
+        spans.sort_by_key(|&(s, e)| (e - s, s));
         spans
     }
 
-    fn extract_span_features(&self, tokens: &[Token], start: usize, end: usize, gaz: &Gazetteers) -> FeatureVector {
-        let mut fv = FeatureVector::new(start);
-        
-        // Features de borda (Boundary features)
-        let first_token = &tokens[start];
-        let last_token = &tokens[end - 1];
-        
-        fv.insert(format!("span_first={}", first_token.text.to_lowercase()), 1.0);
-        fv.insert(format!("span_last={}", last_token.text.to_lowercase()), 1.0);
-        
-        // Contexto
-        if start > 0 {
-            fv.insert(format!("ctx_prev={}", tokens[start - 1].text.to_lowercase()), 1.0);
-        }
-        if end < tokens.len() {
-            fv.insert(format!("ctx_next={}", tokens[end].text.to_lowercase()), 1.0);
-        }
-        
-        // Tamanho
-        fv.insert(format!("span_len={}", end - start), 1.0);
-        
-        // Bag of words interno
-        for i in start..end {
-            fv.insert(format!("in_span={}", tokens[i].text.to_lowercase()), 1.0);
-             if tokens[i].text.chars().next().unwrap().is_uppercase() {
-                 fv.insert("span_has_cap", 1.0);
-             }
-        }
-
-        // Gazetteer match (se o span inteiro bater com gazetteer)
-        let span_text: String = tokens[start..end].iter().map(|t| t.text.as_str()).collect::<Vec<_>>().join(" ").to_lowercase();
-        if gaz.persons.contains(&span_text) { fv.insert("span_is_person_gaz", 1.0); }
-        if gaz.locations.contains(&span_text) { fv.insert("span_is_loc_gaz", 1.0); }
-        if gaz.organizations.contains(&span_text) { fv.insert("span_is_org_gaz", 1.0); }
-
-        fv
-    }
-
     fn predict_single(&self, fv: &FeatureVector) -> String {
         let mut best_label = "O".to_string();
         let mut best_score = f64::NEG_INFINITY;
@@ -237,13 +451,106 @@ impl SpanModel {
 
     fn update(&mut self, fv: &FeatureVector, true_label: &str, pred_label: &str) {
         // Perceptron update simples
-        for (fname, _fval) in &fv.features {
+        for fname in fv.features.keys() {
             *self.weights.entry((fname.clone(), true_label.to_string())).or_insert(0.0) += 1.0;
             *self.weights.entry((fname.clone(), pred_label.to_string())).or_insert(0.0) -= 1.0;
         }
     }
 }
 
+impl Default for SpanModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpanPredictor for SpanModel {
+    fn name(&self) -> &str {
+        "span_model"
+    }
+
+    fn predict(&self, tokens: &[String]) -> Vec<Span> {
+        SpanModel::predict(self, tokens)
+    }
+
+    fn predict_with_confidence(&self, tokens: &[String]) -> Vec<(Span, f64)> {
+        self.predict_decoded(tokens)
+            .into_iter()
+            .map(|scored| {
+                let (label, probability) = scored.scores[0].clone();
+                (Span { start: scored.start, end: scored.end, label }, probability)
+            })
+            .collect()
+    }
+
+    fn is_trained(&self) -> bool {
+        SpanModel::is_trained(self)
+    }
+}
+
+/// Resolve as sobreposições entre spans previstos segundo `decoding` (ver o módulo).
+pub fn resolve_overlaps(spans: Vec<ScoredSpan>, decoding: SpanDecoding) -> Vec<ScoredSpan> {
+    let mut kept = match decoding {
+        SpanDecoding::Overlapping => return spans,
+        SpanDecoding::Greedy => {
+            let mut ranked = spans;
+            // Mais provável primeiro; em empate, o mais longo e depois o mais à esquerda
+            ranked.sort_by(|a, b| {
+                b.best().1.total_cmp(&a.best().1).then((b.end - b.start).cmp(&(a.end - a.start))).then(a.start.cmp(&b.start))
+            });
+            let mut kept: Vec<ScoredSpan> = Vec::new();
+            for span in ranked {
+                if !kept.iter().any(|k| k.overlaps(&span)) {
+                    kept.push(span);
+                }
+            }
+            kept
+        }
+        SpanDecoding::Exact => best_non_overlapping(spans),
+    };
+    kept.sort_by_key(|s| (s.start, s.end));
+    kept
+}
+
+/// Conjunto de spans sem sobreposição que maximiza a soma de `ln(p(label) / p(O))`.
+///
+/// `best[e]` é o melhor score usando apenas tokens antes de `e`: ou o token `e - 1` fica
+/// fora (`best[e - 1]`), ou algum span `(s, e)` termina ali (`best[s] + score`).
+fn best_non_overlapping(spans: Vec<ScoredSpan>) -> Vec<ScoredSpan> {
+    let n = spans.iter().map(|s| s.end).max().unwrap_or(0);
+    let mut ending: Vec<Vec<usize>> = vec![Vec::new(); n + 1];
+    for (i, span) in spans.iter().enumerate() {
+        ending[span.end].push(i);
+    }
+    // Zero para um span cujo melhor label é "O": nunca melhora o total
+    let gain = |span: &ScoredSpan| (span.best().1 / span.probability_of("O").max(f64::MIN_POSITIVE)).ln().max(0.0);
+    let mut best = vec![0.0; n + 1];
+    let mut choice: Vec<Option<usize>> = vec![None; n + 1];
+    for e in 1..=n {
+        best[e] = best[e - 1];
+        for &i in &ending[e] {
+            let score = best[spans[i].start] + gain(&spans[i]);
+            if score > best[e] {
+                best[e] = score;
+                choice[e] = Some(i);
+            }
+        }
+    }
+    let mut selected = Vec::new();
+    let mut e = n;
+    while e > 0 {
+        match choice[e] {
+            Some(i) => {
+                selected.push(i);
+                e = spans[i].start;
+            }
+            None => e -= 1,
+        }
+    }
+    let mut spans: Vec<Option<ScoredSpan>> = spans.into_iter().map(Some).collect();
+    selected.into_iter().filter_map(|i| spans[i].take()).collect()
+}
+
 /// Helper para converter tags BIO em spans
 pub fn bio_to_spans(tags: &[&str]) -> Vec<Span> {
     let mut spans = Vec::new();
@@ -251,27 +558,27 @@ pub fn bio_to_spans(tags: &[&str]) -> Vec<Span> {
     let mut current_label: Option<String> = None;
 
     for (i, tag) in tags.iter().enumerate() {
-        if tag.starts_with("B-") {
+        if let Some(cat) = tag.strip_prefix("B-") {
             if let Some(start) = current_start {
                 spans.push(Span { start, end: i, label: current_label.take().unwrap() });
             }
             current_start = Some(i);
-            current_label = Some(tag[2..].to_string());
-        } else if tag.starts_with("I-") {
+            current_label = Some(cat.to_string());
+        } else if let Some(cat) = tag.strip_prefix("I-") {
             // Verifica consistência: I deve seguir B ou I do mesmo tipo
             if let Some(ref label) = current_label {
-                if &tag[2..] != label {
+                if cat != label {
                     // Inconsistência (novo tipo começou sem B): trata como novo B
                      if let Some(start) = current_start {
                         spans.push(Span { start, end: i, label: current_label.take().unwrap() });
                     }
                     current_start = Some(i);
-                    current_label = Some(tag[2..].to_string());
+                    current_label = Some(cat.to_string());
                 }
             } else {
                  // Começou com I: trata como B
                 current_start = Some(i);
-                current_label = Some(tag[2..].to_string());
+                current_label = Some(cat.to_string());
             }
         } else { // O
             if let Some(start) = current_start {
@@ -303,6 +610,63 @@ mod tests {
         assert_eq!(spans[1], Span { start: 4, end: 5, label: "LOC".to_string() });
     }
 
+    #[test]
+    fn test_candidate_pruning_and_beam() {
+        let text = "Segundo a nota , o Banco do Brasil e a Caixa Econômica Federal anunciaram hoje em Brasília que o programa foi ampliado .";
+        let tokens: Vec<String> = text.split(' ').map(String::from).collect();
+        let all = SpanModel::new().with_pruning(SpanPruning::none()).candidate_spans(&tokens);
+        let pruned = SpanModel::new().candidate_spans(&tokens);
+        let beam = SpanModel::new().with_pruning(SpanPruning::default().with_beam(2)).candidate_spans(&tokens);
+
+        assert_eq!(all.len(), (1..=6).map(|len| tokens.len() + 1 - len).sum::<usize>());
+        assert!(pruned.len() * 3 < all.len() * 2);
+        for &(s, e) in &pruned {
+            assert!(tokens[s..e].iter().any(|t| t.starts_with(char::is_uppercase)));
+            assert!(tokens[s] != "," && tokens[e - 1] != "." && tokens[e - 1] != ",");
+        }
+        for span in [(5, 8), (10, 13), (16, 17)] {
+            assert!(pruned.contains(&span), "{span:?}");
+        }
+
+        assert!(beam.len() * 3 < all.len());
+        for start in 0..tokens.len() {
+            assert!(beam.iter().filter(|(s, _)| *s == start).count() <= 2);
+        }
+        // O beam prefere o nome inteiro a "Banco do Brasil e a"
+        assert!(beam.contains(&(5, 8)) && beam.contains(&(10, 13)));
+    }
+
+    #[test]
+    fn test_resolve_overlaps_greedy_and_exact() {
+        let span = |start, end, p: f64| ScoredSpan { start, end, scores: vec![("ORG".into(), p), ("O".into(), 1.0 - p)] };
+        // Candidatos determinísticos, com muitas sobreposições
+        let spans: Vec<ScoredSpan> = (0..12)
+            .flat_map(|start| (1..=3).map(move |len| (start, len)))
+            .map(|(start, len)| span(start, start + len, 0.5 + ((start * 7 + len * 3) % 10) as f64 / 21.0))
+            .collect();
+        let disjoint = |chosen: &[ScoredSpan]| chosen.windows(2).all(|w| w[0].end <= w[1].start);
+        let total = |chosen: &[ScoredSpan]| chosen.iter().map(|s| (s.best().1 / s.probability_of("O")).ln()).sum::<f64>();
+
+        let greedy = resolve_overlaps(spans.clone(), SpanDecoding::Greedy);
+        let exact = resolve_overlaps(spans.clone(), SpanDecoding::Exact);
+        assert!(disjoint(&greedy) && disjoint(&exact));
+        assert_eq!(resolve_overlaps(spans.clone(), SpanDecoding::Overlapping), spans);
+
+        // A DP acha o ótimo: confere por força bruta numa janela pequena
+        let window: Vec<ScoredSpan> = spans.iter().filter(|s| s.end <= 6).cloned().collect();
+        let mut optimum = 0.0f64;
+        for mask in 0u32..(1 << window.len()) {
+            let mut chosen: Vec<ScoredSpan> = (0..window.len()).filter(|i| mask & (1 << i) != 0).map(|i| window[i].clone()).collect();
+            chosen.sort_by_key(|s| s.start);
+            if disjoint(&chosen) {
+                optimum = optimum.max(total(&chosen));
+            }
+        }
+        let exact_window = resolve_overlaps(window, SpanDecoding::Exact);
+        assert!((total(&exact_window) - optimum).abs() < 1e-9);
+        assert!(total(&exact) >= total(&greedy) - 1e-9);
+    }
+
     #[test]
     fn test_span_learning() {
         let corpus = vec![
@@ -323,5 +687,11 @@ mod tests {
         assert_eq!(spans[0].label, "PER");
         assert_eq!(spans[0].start, 0);
         assert_eq!(spans[0].end, 1);
+        // Sem sobreposição na saída plana; a multi-label mantém todos os candidatos
+        let tokens: Vec<String> = "Lula e Dilma".split(' ').map(String::from).collect();
+        let model = model.with_decoding(SpanDecoding::Exact);
+        let flat = model.predict(&tokens);
+        assert!(flat.windows(2).all(|w| w[0].end <= w[1].start));
+        assert!(model.predict_scored(&tokens).len() >= flat.len());
     }
 }
@@ -0,0 +1,407 @@
+//! # Armazenamento Chave-Valor (Feedback, Cache de Linking e Índices)
+//!
+//! Os módulos que guardam estado entre análises — o [`crate::feedback::FeedbackStore`],
+//! o [`crate::nel::LinkCache`] e o [`crate::index::EntityIndex`] — não falam com o disco
+//! diretamente: gravam bytes sob uma chave em um [`Storage`]. Duas implementações vêm
+//! prontas:
+//!
+//! - [`MemoryStorage`]: um mapa em memória (testes, processos de vida curta).
+//! - [`FileStorage`]: um arquivo por chave dentro de um diretório, com escrita atômica.
+//!
+//! Servidores podem plugar um banco (Postgres, Redis) implementando o trait, sem tocar
+//! no pipeline. Como esses clientes costumam ser assíncronos, há também o
+//! [`AsyncStorage`], e dois adaptadores ligam um mundo ao outro:
+//!
+//! - [`AsyncAdapter`]: usa um `Storage` síncrono onde se espera um `AsyncStorage`.
+//! - [`BlockingAdapter`]: usa um `AsyncStorage` nos módulos síncronos, bloqueando a
+//!   thread até o futuro terminar (chame-o fora das tarefas do runtime, ex: dentro de
+//!   `tokio::task::spawn_blocking`).
+//!
+//! O trait assíncrono não depende de nenhum runtime: é só `Future`.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::storage::{get_json, put_json, AsyncAdapter, BlockingAdapter, MemoryStorage, Storage};
+//!
+//! let storage = MemoryStorage::new();
+//! put_json(&storage, "config/limiar", &0.8)?;
+//! assert_eq!(get_json::<f64>(&storage, "config/limiar")?, Some(0.8));
+//!
+//! // O mesmo armazenamento visto como assíncrono e de volta como síncrono
+//! let roundtrip = BlockingAdapter::new(AsyncAdapter::new(storage));
+//! assert_eq!(roundtrip.keys("config/")?, vec!["config/limiar".to_string()]);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::{self, Future};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Armazenamento chave-valor síncrono.
+///
+/// Chaves são texto livre (ex: `"feedback/doc-1/10-19"`); a hierarquia por `/` é só uma
+/// convenção, usada por [`Storage::keys`] com um prefixo.
+pub trait Storage: Send + Sync {
+    /// Valor da chave, ou `None` se ela não existe.
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    /// Grava (ou substitui) o valor da chave.
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()>;
+    /// Remove a chave. Retorna `false` se ela não existia.
+    fn delete(&self, key: &str) -> io::Result<bool>;
+    /// Chaves que começam com `prefix`, em ordem.
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+/// Versão assíncrona de [`Storage`], para clientes de banco assíncronos.
+pub trait AsyncStorage: Send + Sync {
+    fn get(&self, key: &str) -> impl Future<Output = io::Result<Option<Vec<u8>>>> + Send;
+    fn put(&self, key: &str, value: &[u8]) -> impl Future<Output = io::Result<()>> + Send;
+    fn delete(&self, key: &str) -> impl Future<Output = io::Result<bool>> + Send;
+    fn keys(&self, prefix: &str) -> impl Future<Output = io::Result<Vec<String>>> + Send;
+}
+
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).get(key)
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        (**self).put(key, value)
+    }
+
+    fn delete(&self, key: &str) -> io::Result<bool> {
+        (**self).delete(key)
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        (**self).keys(prefix)
+    }
+}
+
+/// Grava `value` como JSON sob `key`.
+pub fn put_json<T: Serialize + ?Sized>(storage: &dyn Storage, key: &str, value: &T) -> io::Result<()> {
+    storage.put(key, &serde_json::to_vec(value)?)
+}
+
+/// Lê o JSON gravado sob `key` com [`put_json`].
+pub fn get_json<T: DeserializeOwned>(storage: &dyn Storage, key: &str) -> io::Result<Option<T>> {
+    match storage.get(key)? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Como [`put_json`], em um [`AsyncStorage`].
+pub async fn put_json_async<T: Serialize + ?Sized>(storage: &impl AsyncStorage, key: &str, value: &T) -> io::Result<()> {
+    let bytes = serde_json::to_vec(value)?;
+    storage.put(key, &bytes).await
+}
+
+/// Como [`get_json`], em um [`AsyncStorage`].
+pub async fn get_json_async<T: DeserializeOwned>(storage: &impl AsyncStorage, key: &str) -> io::Result<Option<T>> {
+    match storage.get(key).await? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Armazenamento em memória.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: RwLock<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.read().unwrap_or_else(|e| e.into_inner()).get(key).cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.entries.write().unwrap_or_else(|e| e.into_inner()).insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> io::Result<bool> {
+        Ok(self.entries.write().unwrap_or_else(|e| e.into_inner()).remove(key).is_some())
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.range(prefix.to_string()..).map(|(k, _)| k).take_while(|k| k.starts_with(prefix)).cloned().collect())
+    }
+}
+
+/// Prefixo dos arquivos temporários de [`FileStorage`] (nunca produzido por [`encode_key`]).
+const TEMP_PREFIX: &str = "%tmp-";
+
+/// Sufixo de cada gravação: threads gravando a mesma chave não dividem o temporário.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Um arquivo por chave dentro de um diretório.
+///
+/// O nome do arquivo é a chave, com `%XX` no lugar de `/`, `%` e dos caracteres que os
+/// sistemas de arquivos rejeitam (`"links.json"` continua `links.json`). A gravação
+/// passa por um arquivo temporário renomeado no fim: um leitor nunca vê um valor pela metade.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    /// Armazenamento em `root`, criando o diretório se necessário.
+    pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&root)?;
+        Ok(Self { root: root.as_ref().to_path_buf() })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Arquivo que guarda `key`.
+    pub fn path_of(&self, key: &str) -> PathBuf {
+        self.root.join(encode_key(key))
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path_of(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        let name = encode_key(key);
+        let serial = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp = self.root.join(format!("{TEMP_PREFIX}{}-{serial}-{name}", std::process::id()));
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(value)?;
+        file.sync_all()?;
+        fs::rename(&temp, self.root.join(name))
+    }
+
+    fn delete(&self, key: &str) -> io::Result<bool> {
+        match fs::remove_file(self.path_of(key)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let name = entry?.file_name();
+            let Some(name) = name.to_str().filter(|n| !n.starts_with(TEMP_PREFIX)) else {
+                continue;
+            };
+            if let Some(key) = decode_key(name).filter(|k| k.starts_with(prefix)) {
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Nome de arquivo de uma chave: `%XX` nos bytes fora do conjunto seguro e nos pontos
+/// das chaves `.` e `..`. Também serve para embutir um nome qualquer num segmento de
+/// chave (sem `/`), como o `doc_id` de [`crate::feedback::FeedbackStore`].
+pub(crate) fn encode_key(key: &str) -> String {
+    let dots = key == "." || key == "..";
+    let mut name = Vec::with_capacity(key.len());
+    for byte in key.bytes() {
+        let unsafe_byte = byte < 0x20
+            || matches!(byte, b'/' | b'\\' | b'%' | b':' | b'*' | b'?' | b'"' | b'<' | b'>' | b'|' | 0x7f)
+            || (byte == b'.' && dots);
+        if unsafe_byte {
+            name.extend_from_slice(format!("%{byte:02X}").as_bytes());
+        } else {
+            name.push(byte);
+        }
+    }
+    // Só bytes ASCII foram trocados: os caracteres multibyte seguem intactos
+    String::from_utf8(name).expect("a codificação preserva o UTF-8 da chave")
+}
+
+/// Inverso de [`encode_key`]; `None` para nomes que não vieram dela.
+fn decode_key(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = name.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Um [`Storage`] síncrono visto como [`AsyncStorage`]: cada operação termina na hora.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncAdapter<S> {
+    inner: S,
+}
+
+impl<S: Storage> AsyncAdapter<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Storage> AsyncStorage for AsyncAdapter<S> {
+    fn get(&self, key: &str) -> impl Future<Output = io::Result<Option<Vec<u8>>>> + Send {
+        future::ready(self.inner.get(key))
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> impl Future<Output = io::Result<()>> + Send {
+        future::ready(self.inner.put(key, value))
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = io::Result<bool>> + Send {
+        future::ready(self.inner.delete(key))
+    }
+
+    fn keys(&self, prefix: &str) -> impl Future<Output = io::Result<Vec<String>>> + Send {
+        future::ready(self.inner.keys(prefix))
+    }
+}
+
+/// Um [`AsyncStorage`] visto como [`Storage`]: cada operação bloqueia a thread atual até
+/// o futuro terminar.
+///
+/// Não use de dentro de uma tarefa assíncrona: se o futuro depender do runtime que roda
+/// nessa thread, ele nunca termina.
+#[derive(Debug, Clone, Default)]
+pub struct BlockingAdapter<S> {
+    inner: S,
+}
+
+impl<S: AsyncStorage> BlockingAdapter<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncStorage> Storage for BlockingAdapter<S> {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        block_on(self.inner.get(key))
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        block_on(self.inner.put(key, value))
+    }
+
+    fn delete(&self, key: &str) -> io::Result<bool> {
+        block_on(self.inner.delete(key))
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        block_on(self.inner.keys(prefix))
+    }
+}
+
+/// Acorda a thread que espera o futuro.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Executor mínimo: faz `poll` no futuro e estaciona a thread até ser acordada.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(storage: &dyn Storage) {
+        assert_eq!(storage.get("feedback/a").unwrap(), None);
+        storage.put("feedback/a", b"1").unwrap();
+        storage.put("feedback/b|PER|lula", "ação".as_bytes()).unwrap();
+        storage.put("index", b"{}").unwrap();
+        storage.put("feedback/a", b"2").unwrap();
+        assert_eq!(storage.get("feedback/a").unwrap(), Some(b"2".to_vec()));
+        assert_eq!(storage.get("feedback/b|PER|lula").unwrap().unwrap(), "ação".as_bytes());
+        assert_eq!(storage.keys("feedback/").unwrap(), vec!["feedback/a", "feedback/b|PER|lula"]);
+        assert_eq!(storage.keys("").unwrap().len(), 3);
+        assert!(storage.delete("feedback/a").unwrap());
+        assert!(!storage.delete("feedback/a").unwrap());
+        assert_eq!(storage.keys("feedback/").unwrap(), vec!["feedback/b|PER|lula"]);
+    }
+
+    #[test]
+    fn test_memory_file_and_adapted_storage_agree() {
+        exercise(&MemoryStorage::new());
+        exercise(&BlockingAdapter::new(AsyncAdapter::new(MemoryStorage::new())));
+
+        let dir = std::env::temp_dir().join(format!("ner_storage_{}", std::process::id()));
+        let files = FileStorage::new(&dir).unwrap();
+        exercise(&files);
+        assert!(files.path_of("links.json").ends_with("links.json"));
+        assert!(files.path_of("feedback/b|PER|lula").ends_with("feedback%2Fb%7CPER%7Clula"));
+        assert_eq!(decode_key(&encode_key("..")).as_deref(), Some(".."));
+        assert_eq!(decode_key(&encode_key("São Paulo/ação")).as_deref(), Some("São Paulo/ação"));
+
+        // Gravações concorrentes da mesma chave: cada uma com o seu temporário
+        thread::scope(|scope| {
+            for i in 0..8u8 {
+                let files = &files;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        files.put("disputada", &[i; 64]).unwrap();
+                    }
+                });
+            }
+        });
+        let value = files.get("disputada").unwrap().unwrap();
+        assert!(value.len() == 64 && value.iter().all(|&b| b == value[0]));
+        assert!(files.keys("").unwrap().contains(&"disputada".to_string()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
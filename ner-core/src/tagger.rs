@@ -11,23 +11,39 @@
 //! | ORG     | Organização         | Petrobras, Embraer, FIFA          |
 //! | LOC     | Local/Geográfico    | São Paulo, Amazônia, Brasil       |
 //! | MISC    | Miscelânea          | Copa do Mundo, PIB, COVID-19      |
+//! | DATE    | Data/Período        | 13 de maio de 1888, 2023, século XX |
+//! | TIME    | Horário             | 14h30, meia-noite                 |
 //! | O       | Fora de entidade    | (qualquer palavra não-entidade)   |
 //!
+//! Categorias do domínio (ex: `LEGISLACAO`) podem ser acrescentadas em tempo de execução;
+//! ver [`crate::categories`].
+//!
 //! ## Esquema BIO
 //!
 //! - `B-TAG`: Begin — primeiro token de uma entidade
 //! - `I-TAG`: Inside — tokens subsequentes da mesma entidade
 //! - `O`: Outside — não é parte de nenhuma entidade
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::brazil::{lookup_place, PlaceMatch};
+use crate::categories::CustomCategory;
+use crate::offsets::{slice_lossy, OffsetMap};
 use crate::tokenizer::Token;
 
+/// Cores das categorias personalizadas.
+const CUSTOM_COLORS: [&str; 6] = ["#ef4444", "#84cc16", "#06b6d4", "#a855f7", "#f97316", "#64748b"];
+
 /// Categorias de entidade reconhecidas pelo sistema NER.
 ///
-/// Estas categorias definem o "vocabulário" semântico do modelo.
-/// Adicionar novas categorias exigiria retreinar o modelo e atualizar o corpus.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Estas categorias definem o "vocabulário" semântico do modelo. Além das seis
+/// embutidas, categorias do domínio (`LEGISLACAO`, `DOENCA`...) são declaradas em tempo
+/// de execução como [`EntityCategory::Custom`] (ver [`crate::categories`]); o modelo
+/// estatístico precisa ser treinado com elas para reconhecê-las.
+///
+/// Em formatos legíveis (JSON) a categoria é o nome da variante (`"Per"`) ou, se
+/// personalizada, o seu nome (`"LEGISLACAO"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EntityCategory {
     /// **Pessoa**: Nomes de humanos reais, fictícios ou grupos musicais. Ex: "Machado de Assis", "Beatles".
     Per,
@@ -37,9 +53,84 @@ pub enum EntityCategory {
     Loc,
     /// **Miscelânea**: O que não se encaixa nas anteriores (eventos, obras de arte, leis). Ex: "Copa 2014", "Lei Áurea".
     Misc,
+    /// **Data**: Datas absolutas, anos, dias da semana e períodos. Ex: "13 de maio de 1888", "2023", "século XX".
+    Date,
+    /// **Horário**: Horas do dia e marcos horários. Ex: "14h30", "meia-noite".
+    Time,
+    /// Categoria definida pelo usuário (ver [`crate::categories::CategorySet`]).
+    Custom(CustomCategory),
+}
+
+/// Forma binária (bincode) de [`EntityCategory`]: a mesma de um `derive`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "EntityCategory")]
+enum CategoryRepr {
+    Per,
+    Org,
+    Loc,
+    Misc,
+    Date,
+    Time,
+    Custom(CustomCategory),
+}
+
+impl Serialize for EntityCategory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(self.serde_name());
+        }
+        let repr = match *self {
+            EntityCategory::Per => CategoryRepr::Per,
+            EntityCategory::Org => CategoryRepr::Org,
+            EntityCategory::Loc => CategoryRepr::Loc,
+            EntityCategory::Misc => CategoryRepr::Misc,
+            EntityCategory::Date => CategoryRepr::Date,
+            EntityCategory::Time => CategoryRepr::Time,
+            EntityCategory::Custom(c) => CategoryRepr::Custom(c),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityCategory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let name = String::deserialize(deserializer)?;
+            return match EntityCategory::all().into_iter().find(|c| c.serde_name() == name) {
+                Some(category) => Ok(category),
+                // Só resolve nomes já registrados pela configuração do modelo: texto vindo de
+                // fora (ex: o JSON de uma requisição) não pode crescer o registro global
+                None => CustomCategory::lookup(&name)
+                    .map(EntityCategory::Custom)
+                    .ok_or_else(|| de::Error::custom(format!("categoria desconhecida \"{name}\""))),
+            };
+        }
+        Ok(match CategoryRepr::deserialize(deserializer)? {
+            CategoryRepr::Per => EntityCategory::Per,
+            CategoryRepr::Org => EntityCategory::Org,
+            CategoryRepr::Loc => EntityCategory::Loc,
+            CategoryRepr::Misc => EntityCategory::Misc,
+            CategoryRepr::Date => EntityCategory::Date,
+            CategoryRepr::Time => EntityCategory::Time,
+            CategoryRepr::Custom(c) => EntityCategory::Custom(c),
+        })
+    }
 }
 
 impl EntityCategory {
+    /// Todas as categorias embutidas em ordem (para iteração). As personalizadas de um
+    /// modelo estão em [`crate::categories::CategorySet::categories`].
+    pub fn all() -> [EntityCategory; 6] {
+        [
+            EntityCategory::Per,
+            EntityCategory::Org,
+            EntityCategory::Loc,
+            EntityCategory::Misc,
+            EntityCategory::Date,
+            EntityCategory::Time,
+        ]
+    }
+
     /// Nome da categoria como string (para serialização e UI)
     pub fn name(&self) -> &'static str {
         match self {
@@ -47,6 +138,22 @@ impl EntityCategory {
             EntityCategory::Org => "ORG",
             EntityCategory::Loc => "LOC",
             EntityCategory::Misc => "MISC",
+            EntityCategory::Date => "DATE",
+            EntityCategory::Time => "TIME",
+            EntityCategory::Custom(c) => c.name(),
+        }
+    }
+
+    /// Nome em JSON: o da variante (`"Per"`), ou o nome da categoria personalizada.
+    fn serde_name(&self) -> &'static str {
+        match self {
+            EntityCategory::Per => "Per",
+            EntityCategory::Org => "Org",
+            EntityCategory::Loc => "Loc",
+            EntityCategory::Misc => "Misc",
+            EntityCategory::Date => "Date",
+            EntityCategory::Time => "Time",
+            EntityCategory::Custom(c) => c.name(),
         }
     }
 
@@ -57,6 +164,13 @@ impl EntityCategory {
             EntityCategory::Org => "#10b981",  // verde esmeralda
             EntityCategory::Loc => "#f59e0b",  // âmbar
             EntityCategory::Misc => "#8b5cf6", // violeta
+            EntityCategory::Date => "#ec4899", // rosa
+            EntityCategory::Time => "#14b8a6", // turquesa
+            // Personalizadas: uma cor fixa por nome, fora da paleta das embutidas
+            EntityCategory::Custom(c) => {
+                let hash = c.name().bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(usize::from(b)));
+                CUSTOM_COLORS[hash % CUSTOM_COLORS.len()]
+            }
         }
     }
 
@@ -67,19 +181,33 @@ impl EntityCategory {
             EntityCategory::Org => "🏢",
             EntityCategory::Loc => "📍",
             EntityCategory::Misc => "🔖",
+            EntityCategory::Date => "📅",
+            EntityCategory::Time => "🕒",
+            EntityCategory::Custom(_) => "🏷️",
         }
     }
 
-    /// Tenta parsear a partir de string (ex: "PER" → Some(Per))
+    /// Tenta parsear a partir de string (ex: "PER" → Some(Per)).
+    ///
+    /// Nomes de categorias personalizadas só são reconhecidos depois de registrados
+    /// (ver [`CustomCategory::new`]): um rótulo com erro de digitação não vira categoria.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "PER" => Some(EntityCategory::Per),
             "ORG" => Some(EntityCategory::Org),
             "LOC" => Some(EntityCategory::Loc),
             "MISC" => Some(EntityCategory::Misc),
-            _ => None,
+            "DATE" => Some(EntityCategory::Date),
+            "TIME" => Some(EntityCategory::Time),
+            _ => CustomCategory::lookup(s).map(EntityCategory::Custom),
         }
     }
+
+    /// É uma categoria personalizada?
+    pub fn is_custom(&self) -> bool {
+        matches!(self, EntityCategory::Custom(_))
+    }
 }
 
 /// Tag BIO aplicada a um token.
@@ -107,9 +235,16 @@ impl Tag {
     }
 
     /// Índice numérico da tag para matrizes CRF/Viterbi.
-    /// Mapeia cada possibilidade para um inteiro 0..8.
+    /// Mapeia cada possibilidade embutida para um inteiro 0..12.
+    ///
+    /// # Panics
+    /// Tags de categorias personalizadas só têm índice dentro de um conjunto de
+    /// categorias: use [`crate::categories::CategorySet::tag_index`].
     pub fn index(&self) -> usize {
         match self {
+            Tag::Begin(EntityCategory::Custom(c)) | Tag::Inside(EntityCategory::Custom(c)) => {
+                panic!("a categoria {c} só tem índice dentro de um CategorySet")
+            }
             Tag::Outside => 0,
             Tag::Begin(EntityCategory::Per) => 1,
             Tag::Inside(EntityCategory::Per) => 2,
@@ -119,14 +254,18 @@ impl Tag {
             Tag::Inside(EntityCategory::Loc) => 6,
             Tag::Begin(EntityCategory::Misc) => 7,
             Tag::Inside(EntityCategory::Misc) => 8,
+            Tag::Begin(EntityCategory::Date) => 9,
+            Tag::Inside(EntityCategory::Date) => 10,
+            Tag::Begin(EntityCategory::Time) => 11,
+            Tag::Inside(EntityCategory::Time) => 12,
         }
     }
 
-    /// Número total de tags possíveis
-    pub const COUNT: usize = 9;
+    /// Número de tags embutidas
+    pub const COUNT: usize = 13;
 
-    /// Todas as tags em ordem (para iteração)
-    pub fn all() -> [Tag; 9] {
+    /// Todas as tags embutidas em ordem (para iteração)
+    pub fn all() -> [Tag; 13] {
         [
             Tag::Outside,
             Tag::Begin(EntityCategory::Per),
@@ -137,6 +276,10 @@ impl Tag {
             Tag::Inside(EntityCategory::Loc),
             Tag::Begin(EntityCategory::Misc),
             Tag::Inside(EntityCategory::Misc),
+            Tag::Begin(EntityCategory::Date),
+            Tag::Inside(EntityCategory::Date),
+            Tag::Begin(EntityCategory::Time),
+            Tag::Inside(EntityCategory::Time),
         ]
     }
 
@@ -216,6 +359,141 @@ pub struct EntitySpan {
     pub confidence: f64,
     /// Fonte: foi identificada por "rule" ou "crf"
     pub source: String,
+    /// Por que esta confiança? Preenchido apenas quando a análise pede `explain`
+    /// (ver `AnalysisOptions`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+impl EntitySpan {
+    /// Cópia da entidade com `start`/`end` levados ao texto de origem por `map`
+    /// (ver [`crate::offsets::OffsetMap`]).
+    ///
+    /// `text` e os índices de token continuam os do texto transformado. Retorna `None`
+    /// se o trecho não existe no mapa.
+    pub fn project(&self, map: &OffsetMap) -> Option<EntitySpan> {
+        let (start, end) = map.project(self.start, self.end)?;
+        Some(EntitySpan { start, end, ..self.clone() })
+    }
+
+    /// Estado, capital ou sigla de UF brasileira designada por uma entidade `LOC`, com o
+    /// subtipo do lugar (ver [`crate::brazil::lookup_place`]).
+    pub fn place(&self) -> Option<PlaceMatch> {
+        (self.category == EntityCategory::Loc).then(|| lookup_place(&self.text)).flatten()
+    }
+
+    /// Entidade de teste: a primeira ocorrência de `needle` em `text`, marcada pelo
+    /// "crf" com confiança 0.9 (ajuste os campos com `..` se o teste depender deles).
+    #[cfg(test)]
+    pub(crate) fn test_at(text: &str, needle: &str, category: EntityCategory) -> EntitySpan {
+        let start = text.find(needle).unwrap();
+        EntitySpan {
+            text: needle.to_string(),
+            category,
+            start_token: 0,
+            end_token: 0,
+            start,
+            end: start + needle.len(),
+            confidence: 0.9,
+            source: "crf".to_string(),
+            score_breakdown: None,
+        }
+    }
+}
+
+/// Decomposição do score de um [`EntitySpan`], para fins didáticos.
+///
+/// As contribuições do CRF são somadas sobre os tokens do trecho, usando as tags finais:
+/// `emission` = Σ pesos das features ativas; `transition` = Σ transições, incluindo a
+/// entrada no trecho a partir da tag do token anterior.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Contribuição das emissões do CRF (`None` se o CRF não rodou, ex: `RulesOnly`).
+    pub emission: Option<f64>,
+    /// Contribuição das transições do CRF (`None` se o CRF não rodou).
+    pub transition: Option<f64>,
+    /// Probabilidade média que o CRF deu às tags do trecho (`None` se o CRF não rodou).
+    pub model_confidence: Option<f64>,
+    /// Confiança média das regras que marcaram o trecho (`None` se nenhuma regra disparou).
+    pub rule_confidence: Option<f64>,
+    /// Ajuste aplicado por calibração à confiança final (0.0 sem calibração).
+    pub calibration: f64,
+}
+
+/// Um rótulo candidato de um [`MultiLabelSpan`] com o seu score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelScore {
+    pub category: EntityCategory,
+    /// Probabilidade (ou confiança) do rótulo, entre 0 e 1.
+    pub score: f64,
+}
+
+/// Entidade que pode carregar **vários rótulos** e se **sobrepor** a outras.
+///
+/// Diferente de [`EntitySpan`] (saída plana, um rótulo por trecho), modelos baseados em
+/// spans podem legitimamente atribuir mais de um rótulo ao mesmo trecho ou reconhecer
+/// entidades aninhadas ("Universidade de [São Paulo]").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiLabelSpan {
+    /// Texto do trecho
+    pub text: String,
+    /// Índice do primeiro token
+    pub start_token: usize,
+    /// Índice do último token (inclusivo)
+    pub end_token: usize,
+    /// Posição de byte inicial no texto original
+    pub start: usize,
+    /// Posição de byte final no texto original
+    pub end: usize,
+    /// Rótulos candidatos em ordem decrescente de score (nunca vazio)
+    pub labels: Vec<LabelScore>,
+    /// Modelo que produziu o trecho
+    pub source: String,
+}
+
+impl MultiLabelSpan {
+    /// Rótulo de maior score.
+    pub fn best(&self) -> &LabelScore {
+        &self.labels[0]
+    }
+}
+
+/// Critério de ordenação das entidades na saída do pipeline.
+///
+/// Cada algoritmo (regras, CRF, span, zero-shot) produz entidades em uma ordem ligeiramente
+/// diferente. O pipeline aplica [`sort_entities`] em um único ponto antes de emitir o
+/// evento `Done`, garantindo a mesma ordem para qualquer modo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityOrder {
+    /// Ordem de leitura: offset inicial, depois a mais longa primeiro, depois categoria.
+    #[default]
+    Position,
+    /// Maior confiança primeiro; empates seguem a ordem de [`EntityOrder::Position`].
+    Confidence,
+}
+
+/// Ordena as entidades de forma estável e determinística segundo o critério escolhido.
+///
+/// Na ordem por posição, entidades que começam no mesmo offset aparecem da mais longa
+/// para a mais curta (a entidade externa antes da aninhada), e a categoria desempata
+/// seguindo a ordem de [`EntityCategory::all`].
+pub fn sort_entities(entities: &mut [EntitySpan], order: EntityOrder) {
+    let category_rank = |cat: &EntityCategory| {
+        EntityCategory::all().iter().position(|c| c == cat).unwrap_or(usize::MAX)
+    };
+    let by_position = |a: &EntitySpan, b: &EntitySpan| {
+        a.start
+            .cmp(&b.start)
+            .then_with(|| b.end.saturating_sub(b.start).cmp(&a.end.saturating_sub(a.start)))
+            .then_with(|| category_rank(&a.category).cmp(&category_rank(&b.category)))
+    };
+    match order {
+        EntityOrder::Position => entities.sort_by(by_position),
+        EntityOrder::Confidence => entities.sort_by(|a, b| {
+            b.confidence.total_cmp(&a.confidence).then_with(|| by_position(a, b))
+        }),
+    }
 }
 
 /// Converte uma sequência de tokens classificados (BIO) em spans de entidades.
@@ -260,7 +538,7 @@ pub fn tokens_to_spans(tagged: &[TaggedToken], original_text: &str) -> Vec<Entit
                 break;
             }
 
-            let entity_text = original_text[start_byte..end_byte].trim().to_string();
+            let entity_text = slice_lossy(original_text, start_byte, end_byte).trim().to_string();
             spans.push(EntitySpan {
                 text: entity_text,
                 category: cat,
@@ -270,6 +548,7 @@ pub fn tokens_to_spans(tagged: &[TaggedToken], original_text: &str) -> Vec<Entit
                 end: end_byte,
                 confidence: conf_sum / count as f64,
                 source: "crf".to_string(),
+                score_breakdown: None,
             });
 
             i = j;
@@ -321,6 +600,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokens_to_spans_tolerates_bad_offsets() {
+        // Offsets corrompidos: fim no meio do "ã" e além do texto
+        let tagged = vec![
+            TaggedToken {
+                token: Token::new("São", 0, 2, 0),
+                tag: Tag::Begin(EntityCategory::Loc),
+                confidence: 1.0,
+            },
+            TaggedToken {
+                token: Token::new("Paulo", 5, 50, 1),
+                tag: Tag::Begin(EntityCategory::Loc),
+                confidence: 1.0,
+            },
+        ];
+        let spans = tokens_to_spans(&tagged, "São Paulo");
+        assert_eq!(spans[0].text, "Sã");
+        assert_eq!(spans[1].text, "Paulo");
+    }
+
     #[test]
     fn test_all_tags_have_unique_indices() {
         let all = Tag::all();
@@ -329,4 +628,38 @@ mod tests {
         indices.dedup();
         assert_eq!(indices.len(), Tag::COUNT);
     }
+
+    fn span(text: &str, category: EntityCategory, start: usize, end: usize, confidence: f64) -> EntitySpan {
+        EntitySpan {
+            text: text.to_string(),
+            category,
+            start_token: 0,
+            end_token: 0,
+            start,
+            end,
+            confidence,
+            source: "test".to_string(),
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_entities() {
+        let mut entities = vec![
+            span("Rio", EntityCategory::Loc, 10, 13, 0.7),
+            span("Paulo", EntityCategory::Per, 4, 9, 0.9),
+            span("São Paulo", EntityCategory::Org, 0, 10, 0.8),
+            span("São Paulo", EntityCategory::Loc, 0, 10, 0.9),
+            span("São", EntityCategory::Loc, 0, 4, 0.9),
+        ];
+
+        sort_entities(&mut entities, EntityOrder::Position);
+        let order: Vec<_> = entities.iter().map(|e| (e.start, e.category.name())).collect();
+        assert_eq!(order, vec![(0, "ORG"), (0, "LOC"), (0, "LOC"), (4, "PER"), (10, "LOC")]);
+        assert_eq!(entities[2].text, "São");
+
+        sort_entities(&mut entities, EntityOrder::Confidence);
+        let texts: Vec<_> = entities.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["São Paulo", "São", "Paulo", "São Paulo", "Rio"]);
+    }
 }
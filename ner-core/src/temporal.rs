@@ -0,0 +1,322 @@
+//! # Expressões Temporais e Numéricas (DATE, TIME, MONEY, PERCENT)
+//!
+//! Datas, horários, valores em dinheiro e percentuais seguem formatos fixos que
+//! expressões regulares reconhecem melhor que um modelo estatístico, e que pedem um
+//! valor normalizado além do trecho: "13 de maio de 1888" é `1888-05-13`, "R$ 50
+//! bilhões" é `BRL 50000000000`. [`find_expressions`] procura no texto os formatos
+//! brasileiros mais comuns:
+//!
+//! | Tipo | Exemplos | Valor normalizado |
+//! |------|----------|-------------------|
+//! | [`ExpressionKind::Date`] | "13 de maio de 1888", "1º de abril", "maio de 2023", "15/03/2024", "em 1822" | `1888-05-13`, `--04-01`, `2023-05`, `1822` |
+//! | [`ExpressionKind::Time`] | "14h30", "9h", "14:30", "meio-dia" | `14:30`, `09:00`, `12:00` |
+//! | [`ExpressionKind::Money`] | "R$ 50 bilhões", "R$ 1.500,00", "US$ 3,2 milhões", "10 mil reais" | `BRL 50000000000`, `BRL 1500`, `USD 3200000` |
+//! | [`ExpressionKind::Percent`] | "10,5%", "3 por cento" | `10.5%`, `3%` |
+//!
+//! Anos soltos só contam depois de "em", "desde", "até" ou "ano de" ("em 1822"), para
+//! não confundir quantidades ("2000 pessoas") com datas. Trechos sobrepostos ficam com
+//! o mais longo ("13 de maio de 1888" e não "maio de 1888").
+//!
+//! As expressões formam uma lista paralela às entidades: o pipeline as devolve em
+//! [`crate::pipeline::AnalysisReport::expressions`] (e no evento
+//! `PipelineEvent::ExpressionsFound`), sem alterar as entidades do modo escolhido.
+//! Datas e horários correspondem às categorias `Date` e `Time`
+//! ([`ExpressionKind::category`]); dinheiro e percentuais não têm categoria de entidade.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::temporal::{find_expressions, ExpressionKind};
+//!
+//! let text = "Em 13 de maio de 1888, às 14h30, o governo anunciou R$ 50 bilhões e juros de 10,5%.";
+//! let expressions = find_expressions(text);
+//! let found: Vec<(ExpressionKind, &str, &str)> = expressions
+//!     .iter()
+//!     .map(|e| (e.kind, &text[e.start..e.end], e.value.as_str()))
+//!     .collect();
+//! assert_eq!(found, vec![
+//!     (ExpressionKind::Date, "13 de maio de 1888", "1888-05-13"),
+//!     (ExpressionKind::Time, "14h30", "14:30"),
+//!     (ExpressionKind::Money, "R$ 50 bilhões", "BRL 50000000000"),
+//!     (ExpressionKind::Percent, "10,5%", "10.5%"),
+//! ]);
+//! ```
+
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::tagger::EntityCategory;
+
+/// Tipo de uma expressão temporal ou numérica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpressionKind {
+    Date,
+    Time,
+    Money,
+    Percent,
+}
+
+impl ExpressionKind {
+    /// Rótulo em maiúsculas, como nas tags (`"DATE"`, `"MONEY"`).
+    pub fn label(self) -> &'static str {
+        match self {
+            ExpressionKind::Date => "DATE",
+            ExpressionKind::Time => "TIME",
+            ExpressionKind::Money => "MONEY",
+            ExpressionKind::Percent => "PERCENT",
+        }
+    }
+
+    /// Categoria de entidade equivalente, se houver (só datas e horários).
+    pub fn category(self) -> Option<EntityCategory> {
+        match self {
+            ExpressionKind::Date => Some(EntityCategory::Date),
+            ExpressionKind::Time => Some(EntityCategory::Time),
+            ExpressionKind::Money | ExpressionKind::Percent => None,
+        }
+    }
+}
+
+/// Uma expressão encontrada no texto.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub text: String,
+    /// Offsets de byte no texto (`end` exclusivo).
+    pub start: usize,
+    pub end: usize,
+    /// Valor normalizado (ver a tabela do módulo).
+    pub value: String,
+}
+
+/// Um formato reconhecido: a regex e como normalizar o que ela casou. O trecho é o
+/// grupo `x`, se existir, ou o casamento inteiro; `normalize` devolve `None` para
+/// descartar o casamento (ex: "31/13/2020").
+struct Pattern {
+    kind: ExpressionKind,
+    regex: Regex,
+    normalize: fn(&Captures) -> Option<String>,
+}
+
+const MONTHS: &str = "janeiro|fevereiro|março|marco|abril|maio|junho|julho|agosto|setembro|outubro|novembro|dezembro";
+const MULTIPLIERS: &str = "mil|milhão|milhao|milhões|milhoes|bilhão|bilhao|bilhões|bilhoes|trilhão|trilhao|trilhões|trilhoes";
+const NUMBER: &str = r"(?P<int>\d{1,3}(?:\.\d{3})+|\d+)(?:,(?P<frac>\d+))?";
+
+fn patterns() -> &'static [Pattern] {
+    static PATTERNS: OnceLock<Vec<Pattern>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let pattern = |kind, regex: &str, normalize| Pattern { kind, regex: Regex::new(regex).unwrap(), normalize };
+        vec![
+            pattern(
+                ExpressionKind::Date,
+                &format!(r"(?i)\b(?P<d>3[01]|[12]\d|0?[1-9])(?:º|°)?\s+de\s+(?P<mon>{MONTHS})(?:\s+de\s+(?P<y>\d{{4}}))?\b"),
+                normalize_date,
+            ),
+            pattern(ExpressionKind::Date, &format!(r"(?i)\b(?P<mon>{MONTHS})\s+de\s+(?P<y>\d{{4}})\b"), normalize_date),
+            pattern(ExpressionKind::Date, r"\b(?P<d>\d{1,2})/(?P<m>\d{1,2})/(?P<y>\d{4}|\d{2})\b", normalize_date),
+            pattern(ExpressionKind::Date, r"\b(?P<y>\d{4})-(?P<m>\d{2})-(?P<d>\d{2})\b", normalize_date),
+            pattern(
+                ExpressionKind::Date,
+                r"(?i)\b(?:em|desde|até|ano\s+de)\s+(?P<x>(?P<y>1[5-9]\d{2}|20\d{2}))\b",
+                normalize_date,
+            ),
+            pattern(ExpressionKind::Time, r"\b(?P<h>2[0-3]|[01]?\d)h(?P<m>[0-5]\d)?(?:min)?\b", normalize_time),
+            pattern(ExpressionKind::Time, r"\b(?P<h>2[0-3]|[01]?\d):(?P<m>[0-5]\d)\b", normalize_time),
+            pattern(ExpressionKind::Time, r"(?i)\b(?P<word>meio-dia|meia-noite)\b", normalize_time),
+            pattern(
+                ExpressionKind::Money,
+                &format!(r"(?P<cur>R\$|US\$|U\$|€|£)\s?{NUMBER}(?:\s+(?P<mult>(?i:{MULTIPLIERS}))\b)?"),
+                normalize_money,
+            ),
+            pattern(
+                ExpressionKind::Money,
+                &format!(r"(?i)\b{NUMBER}(?:\s+(?P<mult>{MULTIPLIERS}))?\s+(?:de\s+)?(?P<cur>reais|real|dólares|dólar|euros|euro)\b"),
+                normalize_money,
+            ),
+            pattern(ExpressionKind::Percent, &format!(r"\b{NUMBER}\s?%"), normalize_percent),
+            pattern(ExpressionKind::Percent, &format!(r"(?i)\b{NUMBER}\s+por\s+cento\b"), normalize_percent),
+        ]
+    })
+}
+
+/// Datas, horários, valores em dinheiro e percentuais de `text`, em ordem de posição e
+/// sem sobreposição.
+pub fn find_expressions(text: &str) -> Vec<Expression> {
+    let mut found: Vec<Expression> = Vec::new();
+    for pattern in patterns() {
+        for caps in pattern.regex.captures_iter(text) {
+            let Some(value) = (pattern.normalize)(&caps) else { continue };
+            let span = caps.name("x").unwrap_or_else(|| caps.get(0).unwrap());
+            found.push(Expression {
+                kind: pattern.kind,
+                text: span.as_str().to_string(),
+                start: span.start(),
+                end: span.end(),
+                value,
+            });
+        }
+    }
+
+    // O mais longo vence; empates ficam com o formato listado primeiro
+    found.sort_by_key(|e| std::cmp::Reverse(e.end - e.start));
+    let mut kept: Vec<Expression> = Vec::new();
+    for expression in found {
+        if kept.iter().all(|k| expression.end <= k.start || expression.start >= k.end) {
+            kept.push(expression);
+        }
+    }
+    kept.sort_by_key(|e| e.start);
+    kept
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let month = match name.to_lowercase().as_str() {
+        "janeiro" => 1,
+        "fevereiro" => 2,
+        "março" | "marco" => 3,
+        "abril" => 4,
+        "maio" => 5,
+        "junho" => 6,
+        "julho" => 7,
+        "agosto" => 8,
+        "setembro" => 9,
+        "outubro" => 10,
+        "novembro" => 11,
+        "dezembro" => 12,
+        _ => return None,
+    };
+    Some(month)
+}
+
+fn number_group(caps: &Captures, name: &str) -> Option<u32> {
+    caps.name(name).map(|m| m.as_str().parse().ok()).unwrap_or(None)
+}
+
+/// ISO 8601 com o que a data tiver: `AAAA-MM-DD`, `AAAA-MM`, `AAAA` ou `--MM-DD` (sem
+/// ano). Anos de dois dígitos: até 49 são 20xx, de 50 em diante, 19xx.
+fn normalize_date(caps: &Captures) -> Option<String> {
+    let month = match caps.name("mon") {
+        Some(name) => Some(month_number(name.as_str())?),
+        None => number_group(caps, "m"),
+    };
+    let day = number_group(caps, "d");
+    let year = caps.name("y").map(|y| {
+        let value: u32 = y.as_str().parse().unwrap_or(0);
+        match y.as_str().len() {
+            2 if value < 50 => 2000 + value,
+            2 => 1900 + value,
+            _ => value,
+        }
+    });
+    if month.is_some_and(|m| !(1..=12).contains(&m)) || day.is_some_and(|d| !(1..=31).contains(&d)) {
+        return None;
+    }
+    match (year, month, day) {
+        (Some(y), Some(m), Some(d)) => Some(format!("{y:04}-{m:02}-{d:02}")),
+        (Some(y), Some(m), None) => Some(format!("{y:04}-{m:02}")),
+        (Some(y), None, None) => Some(format!("{y:04}")),
+        (None, Some(m), Some(d)) => Some(format!("--{m:02}-{d:02}")),
+        _ => None,
+    }
+}
+
+/// `HH:MM`.
+fn normalize_time(caps: &Captures) -> Option<String> {
+    if let Some(word) = caps.name("word") {
+        let hour = if word.as_str().to_lowercase() == "meio-dia" { 12 } else { 0 };
+        return Some(format!("{hour:02}:00"));
+    }
+    let hour = number_group(caps, "h")?;
+    let minute = number_group(caps, "m").unwrap_or(0);
+    Some(format!("{hour:02}:{minute:02}"))
+}
+
+/// Número no formato brasileiro ("1.500,25") com o multiplicador por extenso ("bilhões").
+fn parse_amount(caps: &Captures) -> Option<f64> {
+    let integer = caps.name("int")?.as_str().replace('.', "");
+    let number: f64 = match caps.name("frac") {
+        Some(frac) => format!("{integer}.{}", frac.as_str()).parse().ok()?,
+        None => integer.parse().ok()?,
+    };
+    let multiplier = match caps.name("mult").map(|m| m.as_str().to_lowercase()) {
+        None => 1.0,
+        Some(m) if m == "mil" => 1e3,
+        Some(m) if m.starts_with("milh") => 1e6,
+        Some(m) if m.starts_with("bilh") => 1e9,
+        Some(_) => 1e12,
+    };
+    // Arredonda aos centavos: "3,2 milhões" é 3200000, não 3200000.0000000005
+    Some((number * multiplier * 100.0).round() / 100.0)
+}
+
+/// Código ISO 4217 da moeda e o valor: `BRL 1500.5`.
+fn normalize_money(caps: &Captures) -> Option<String> {
+    let currency = match caps.name("cur")?.as_str().to_lowercase().as_str() {
+        "r$" | "reais" | "real" => "BRL",
+        "us$" | "u$" | "dólares" | "dólar" => "USD",
+        "€" | "euros" | "euro" => "EUR",
+        "£" => "GBP",
+        _ => return None,
+    };
+    Some(format!("{currency} {}", parse_amount(caps)?))
+}
+
+/// O número com ponto decimal: `10.5%`.
+fn normalize_percent(caps: &Captures) -> Option<String> {
+    Some(format!("{}%", parse_amount(caps)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<(ExpressionKind, String, String)> {
+        find_expressions(text).into_iter().map(|e| (e.kind, e.text, e.value)).collect()
+    }
+
+    #[test]
+    fn test_brazilian_formats() {
+        use ExpressionKind::*;
+        let expected = [
+            ("A Lei Áurea foi assinada em 13 de maio de 1888.", Date, "13 de maio de 1888", "1888-05-13"),
+            ("O feriado de 1º de Abril.", Date, "1º de Abril", "--04-01"),
+            ("Aprovada em maio de 2023.", Date, "maio de 2023", "2023-05"),
+            ("Pago em 15/03/2024.", Date, "15/03/2024", "2024-03-15"),
+            ("Vence em 05/01/99.", Date, "05/01/99", "1999-01-05"),
+            ("Publicado em 2024-03-15.", Date, "2024-03-15", "2024-03-15"),
+            ("A independência veio em 1822.", Date, "1822", "1822"),
+            ("A reunião começa às 9h.", Time, "9h", "09:00"),
+            ("Saiu às 14:05 de casa.", Time, "14:05", "14:05"),
+            ("Almoço ao meio-dia.", Time, "meio-dia", "12:00"),
+            ("A multa foi de R$ 1.500,00.", Money, "R$ 1.500,00", "BRL 1500"),
+            ("Captou US$ 3,2 milhões.", Money, "US$ 3,2 milhões", "USD 3200000"),
+            ("Custou 10 mil reais.", Money, "10 mil reais", "BRL 10000"),
+            ("Investiu 50 milhões de euros.", Money, "50 milhões de euros", "EUR 50000000"),
+            ("A inflação subiu 3 por cento.", Percent, "3 por cento", "3%"),
+        ];
+        for (text, kind, span, value) in expected {
+            assert_eq!(values(text), vec![(kind, span.to_string(), value.to_string())], "{text}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_ambiguous_and_invalid() {
+        // Quantidades, placares e datas impossíveis não são expressões
+        assert!(values("Cerca de 2000 pessoas compareceram.").is_empty());
+        assert!(values("O processo 1234 foi arquivado.").is_empty());
+        assert!(values("Data inválida: 31/13/2020.").is_empty());
+        assert!(values("Nasceu em 32 de maio.").is_empty());
+
+        // Sobreposição: fica o trecho mais longo, com offsets no texto original
+        let text = "Em 21 de abril de 1792 e em 1822.";
+        let found = find_expressions(text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(&text[found[0].start..found[0].end], "21 de abril de 1792");
+        assert_eq!(&text[found[1].start..found[1].end], "1822");
+        assert_eq!(ExpressionKind::Money.category(), None);
+        assert_eq!(ExpressionKind::Date.category(), Some(EntityCategory::Date));
+    }
+}
@@ -0,0 +1,262 @@
+//! # Limiares de Decisão por Categoria
+//!
+//! A confiança de uma entidade não tem a mesma "escala" em todas as categorias: um `PER`
+//! com 0.6 costuma estar certo, enquanto um `MISC` com 0.6 é quase sempre ruído. Um único
+//! `min_confidence` (ver [`crate::overlay::EntityFilter`]) corta demais de uma categoria e
+//! de menos de outra.
+//!
+//! O [`ThresholdTuner`] escolhe um limiar **por categoria** olhando previsões de um
+//! conjunto de desenvolvimento (dev) com ouro conhecido:
+//!
+//! 1. Para cada categoria, ordena as entidades previstas por confiança.
+//! 2. Testa como limiar cada valor de confiança observado, calculando precisão,
+//!    revocação e F1 do que sobraria (acerto = mesmas fronteiras e categoria do ouro).
+//! 3. Fica com o limiar de maior F1 ([`ThresholdObjective::MaxF1`]) ou com o mais baixo
+//!    que ainda garante uma precisão mínima ([`ThresholdObjective::PrecisionFloor`]).
+//!
+//! Os limiares ([`CategoryThresholds`]) ficam em `NerModel::thresholds` e são aplicados
+//! automaticamente pelo pipeline na etapa final, junto com a ordenação das entidades.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::diff::GoldEntity;
+//! use ner_core::tagger::{EntityCategory, EntitySpan};
+//! use ner_core::thresholds::ThresholdTuner;
+//!
+//! let entity = |start, category, confidence| EntitySpan {
+//!     text: String::new(),
+//!     category,
+//!     start_token: 0,
+//!     end_token: 0,
+//!     start,
+//!     end: start + 1,
+//!     confidence,
+//!     source: "crf".to_string(),
+//!     score_breakdown: None,
+//! };
+//! let gold = vec![GoldEntity::new(0, 1, EntityCategory::Misc)];
+//! let predicted = vec![entity(0, EntityCategory::Misc, 0.8), entity(5, EntityCategory::Misc, 0.4)];
+//!
+//! let thresholds = ThresholdTuner::new().tune(&[(gold, predicted)]);
+//! // 0.8 mantém o acerto e descarta o falso positivo de 0.4
+//! assert_eq!(thresholds.get(EntityCategory::Misc), Some(0.8));
+//! assert_eq!(thresholds.get(EntityCategory::Per), None);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::GoldEntity;
+use crate::tagger::{EntityCategory, EntitySpan};
+
+/// Confiança mínima de cada categoria; categorias sem limiar passam sempre.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryThresholds {
+    thresholds: HashMap<EntityCategory, f64>,
+}
+
+impl CategoryThresholds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_threshold(mut self, category: EntityCategory, threshold: f64) -> Self {
+        self.set(category, threshold);
+        self
+    }
+
+    pub fn set(&mut self, category: EntityCategory, threshold: f64) {
+        self.thresholds.insert(category, threshold);
+    }
+
+    pub fn remove(&mut self, category: EntityCategory) {
+        self.thresholds.remove(&category);
+    }
+
+    pub fn get(&self, category: EntityCategory) -> Option<f64> {
+        self.thresholds.get(&category).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    /// A entidade atinge o limiar da sua categoria?
+    pub fn keeps(&self, entity: &EntitySpan) -> bool {
+        self.get(entity.category).is_none_or(|min| entity.confidence >= min)
+    }
+
+    pub fn apply(&self, entities: Vec<EntitySpan>) -> Vec<EntitySpan> {
+        entities.into_iter().filter(|e| self.keeps(e)).collect()
+    }
+}
+
+/// O que o [`ThresholdTuner`] otimiza em cada categoria.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ThresholdObjective {
+    /// Maior F1; em empates, o limiar mais baixo (mais revocação).
+    #[default]
+    MaxF1,
+    /// Limiar mais baixo cuja precisão é pelo menos o valor dado. Se nenhum atinge o
+    /// piso, usa o de maior precisão.
+    PrecisionFloor(f64),
+}
+
+/// Escolhe [`CategoryThresholds`] a partir de previsões com ouro conhecido.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThresholdTuner {
+    pub objective: ThresholdObjective,
+}
+
+impl ThresholdTuner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_objective(mut self, objective: ThresholdObjective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Limiares para `dev`: pares `(ouro, previsto)`, um por documento.
+    ///
+    /// As previsões devem vir **sem** limiares aplicados, senão os valores mais baixos nem
+    /// chegam a ser testados. Categorias sem previsões ou sem ouro ficam sem limiar.
+    pub fn tune(&self, dev: &[(Vec<GoldEntity>, Vec<EntitySpan>)]) -> CategoryThresholds {
+        let mut thresholds = CategoryThresholds::new();
+        // categorias embutidas mais as personalizadas que aparecem no ouro
+        let mut categories = EntityCategory::all().to_vec();
+        for gold in dev.iter().flat_map(|(gold, _)| gold) {
+            if !categories.contains(&gold.category) {
+                categories.push(gold.category);
+            }
+        }
+        for category in categories {
+            let gold_count: usize = dev.iter().map(|(gold, _)| gold.iter().filter(|g| g.category == category).count()).sum();
+            // (confiança, acertou?) de cada previsão da categoria
+            let mut scored: Vec<(f64, bool)> = dev
+                .iter()
+                .flat_map(|(gold, predicted)| {
+                    predicted.iter().filter(|p| p.category == category).map(move |p| {
+                        let correct = gold.iter().any(|g| g.category == category && g.start == p.start && g.end == p.end);
+                        (p.confidence, correct)
+                    })
+                })
+                .collect();
+            if gold_count == 0 || scored.is_empty() {
+                continue;
+            }
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            if let Some(threshold) = self.choose(&scored, gold_count) {
+                thresholds.set(category, threshold);
+            }
+        }
+        thresholds
+    }
+
+    /// Varre os limiares candidatos (da confiança mais alta para a mais baixa).
+    fn choose(&self, scored: &[(f64, bool)], gold_count: usize) -> Option<f64> {
+        // (limiar, precisão, F1) de cada valor distinto de confiança
+        let mut points = Vec::new();
+        let mut correct = 0;
+        for (i, &(confidence, is_correct)) in scored.iter().enumerate() {
+            if is_correct {
+                correct += 1;
+            }
+            // Só avalia depois do último empate: o limiar mantém todas as iguais
+            if scored.get(i + 1).is_some_and(|next| next.0 == confidence) {
+                continue;
+            }
+            let precision = correct as f64 / (i + 1) as f64;
+            let recall = correct as f64 / gold_count as f64;
+            let f1 = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+            points.push((confidence, precision, f1));
+        }
+
+        // `points` vai do limiar mais alto ao mais baixo: `>=` prefere o mais baixo nos empates
+        let best_by = |key: fn(&(f64, f64, f64)) -> f64| {
+            points.iter().fold(None::<&(f64, f64, f64)>, |best, p| match best {
+                Some(b) if key(b) > key(p) => Some(b),
+                _ => Some(p),
+            })
+        };
+        match self.objective {
+            ThresholdObjective::MaxF1 => best_by(|p| p.2).map(|p| p.0),
+            ThresholdObjective::PrecisionFloor(floor) => points
+                .iter()
+                .rev()
+                .find(|p| p.1 >= floor)
+                .or_else(|| best_by(|p| p.1))
+                .map(|p| p.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(start: usize, category: EntityCategory, confidence: f64) -> EntitySpan {
+        EntitySpan {
+            text: String::new(),
+            category,
+            start_token: 0,
+            end_token: 0,
+            start,
+            end: start + 1,
+            confidence,
+            source: "crf".to_string(),
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn test_tuner_objectives() {
+        use EntityCategory::{Misc, Per};
+        let gold = vec![
+            GoldEntity::new(0, 1, Per),
+            GoldEntity::new(10, 11, Misc),
+            GoldEntity::new(20, 21, Misc),
+            GoldEntity::new(30, 31, Misc),
+        ];
+        let predicted = vec![
+            entity(0, Per, 0.3),
+            entity(10, Misc, 0.9),
+            entity(11, Misc, 0.8),
+            entity(20, Misc, 0.7),
+            entity(12, Misc, 0.6),
+            entity(13, Misc, 0.5),
+            entity(14, Misc, 0.45),
+            entity(30, Misc, 0.4),
+        ];
+        let dev = vec![(gold, predicted.clone())];
+
+        // MISC: F1 em 0.9 = 0.5, em 0.7 = 0.667, em 0.4 = 0.6 → 0.7.
+        // PER: a única previsão está certa → o limiar não corta nada.
+        let thresholds = ThresholdTuner::new().tune(&dev);
+        assert_eq!(thresholds.get(Misc), Some(0.7));
+        assert_eq!(thresholds.get(Per), Some(0.3));
+        let kept: Vec<usize> = thresholds.apply(predicted.clone()).iter().map(|e| e.start).collect();
+        assert_eq!(kept, vec![0, 10, 11, 20]);
+
+        // Piso de precisão: 0.9 é o único limiar de MISC com precisão 1.0
+        let strict = ThresholdTuner::new().with_objective(ThresholdObjective::PrecisionFloor(0.95)).tune(&dev);
+        assert_eq!(strict.get(Misc), Some(0.9));
+        let loose = ThresholdTuner::new().with_objective(ThresholdObjective::PrecisionFloor(0.5)).tune(&dev);
+        assert_eq!(loose.get(Misc), Some(0.6));
+
+        // Empates de confiança contam juntos; sem ouro, sem limiar
+        let tied = vec![(
+            vec![GoldEntity::new(0, 1, Misc)],
+            vec![entity(0, Misc, 0.5), entity(5, Misc, 0.5), entity(9, Per, 0.9)],
+        )];
+        let thresholds = ThresholdTuner::new().tune(&tied);
+        assert_eq!(thresholds.get(Misc), Some(0.5));
+        assert_eq!(thresholds.get(Per), None);
+
+        let json = serde_json::to_string(&thresholds).unwrap();
+        assert_eq!(serde_json::from_str::<CategoryThresholds>(&json).unwrap(), thresholds);
+    }
+}
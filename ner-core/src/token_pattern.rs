@@ -0,0 +1,436 @@
+//! # Padrões Sobre Sequências de Tokens (mini "TokensRegex")
+//!
+//! Expressões regulares operam sobre caracteres; para regras de NER é mais natural
+//! descrever sequências de **tokens**: "um título seguido de palavras capitalizadas",
+//! "uma palavra capitalizada seguida de Ltda". Este módulo implementa uma pequena
+//! linguagem de padrões compilada em um casador com backtracking.
+//!
+//! ## Sintaxe
+//!
+//! | Elemento | Casa com |
+//! |----------|----------|
+//! | `[Cap]` | token iniciado por maiúscula ("Lula") |
+//! | `[Upper]` | token todo em maiúsculas ("STF") |
+//! | `[Lower]` | token iniciado por minúscula |
+//! | `[Num]` | token numérico ("2024") |
+//! | `[Punct]` | token de pontuação |
+//! | `[Abbrev]` | abreviação com ponto ("Dr.", "Inc.") |
+//! | `[Url]`, `[Email]`, `[Hashtag]`, `[Mention]`, `[Money]` | token do tipo ([`crate::tokenizer::TokenKind`]) |
+//! | `[title]` | token pertencente à *classe de palavras* `title` (nomes em minúsculas) |
+//! | `"Ltda"` | o literal, sem diferenciar maiúsculas |
+//!
+//! Cada elemento aceita um quantificador: `+` (um ou mais), `*` (zero ou mais) ou
+//! `?` (opcional). Um único grupo `( ... )` delimita quais tokens recebem a tag; sem
+//! grupo, o casamento inteiro é a entidade.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::token_pattern::TokenPattern;
+//! use ner_core::tokenizer::tokenize;
+//!
+//! // Título seguido de nome: só o nome é a entidade
+//! let pattern = TokenPattern::compile("[title] ([Cap]+)").unwrap();
+//! let tokens = tokenize("o ministro Fernando Haddad falou");
+//! let is_title = |class: &str, word: &str| class == "title" && word == "ministro";
+//!
+//! let m = pattern.match_at(&tokens, 1, &is_title).unwrap();
+//! assert_eq!((m.target_start, m.target_end), (2, 4)); // "Fernando Haddad"
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokenizer::{Token, TokenKind};
+
+/// Erro de compilação de um padrão.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// O padrão não contém nenhum elemento.
+    Empty,
+    /// Um `[` ou `"` não foi fechado.
+    Unterminated(char),
+    /// Caractere inesperado na posição indicada.
+    UnexpectedChar { ch: char, position: usize },
+    /// Parênteses desbalanceados, aninhados ou mais de um grupo.
+    InvalidGroup,
+    /// O grupo de captura pode casar com zero tokens.
+    EmptyGroup,
+    /// Classe de palavras não registrada no motor de regras.
+    UnknownClass(String),
+    /// Expressão regular inválida (ver `RuleEngine::add_regex_rule`), com a mensagem do `regex`.
+    InvalidRegex(String),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "padrão vazio"),
+            PatternError::Unterminated(ch) => write!(f, "'{ch}' não foi fechado"),
+            PatternError::UnexpectedChar { ch, position } => {
+                write!(f, "caractere inesperado '{ch}' na posição {position}")
+            }
+            PatternError::InvalidGroup => {
+                write!(f, "é permitido apenas um grupo ( ... ), sem aninhamento")
+            }
+            PatternError::EmptyGroup => write!(f, "o grupo precisa casar com ao menos um token"),
+            PatternError::UnknownClass(name) => write!(f, "classe de palavras desconhecida: '{name}'"),
+            PatternError::InvalidRegex(message) => write!(f, "expressão regular inválida: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Teste aplicado a um único token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenMatcher {
+    Capitalized,
+    Upper,
+    Lower,
+    Number,
+    Punct,
+    /// Tipo atribuído pelo tokenizador (URL, e-mail, valor...).
+    Kind(TokenKind),
+    /// Classe de palavras resolvida pelo chamador (ex: "title").
+    WordClass(String),
+    /// Literal em minúsculas.
+    Literal(String),
+}
+
+impl TokenMatcher {
+    fn matches(&self, token: &Token, in_class: &dyn Fn(&str, &str) -> bool) -> bool {
+        let text = token.text.as_str();
+        let first = text.chars().next();
+        match self {
+            TokenMatcher::Capitalized => token.is_capitalized(),
+            TokenMatcher::Upper => {
+                text.chars().any(char::is_alphabetic)
+                    && text.chars().all(|c| !c.is_alphabetic() || c.is_uppercase())
+            }
+            TokenMatcher::Lower => first.is_some_and(char::is_lowercase),
+            TokenMatcher::Number => !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()),
+            TokenMatcher::Punct => !text.is_empty() && text.chars().all(|c| c.is_ascii_punctuation()),
+            TokenMatcher::Kind(kind) => token.kind == *kind,
+            TokenMatcher::WordClass(class) => in_class(class, &text.to_lowercase()),
+            TokenMatcher::Literal(lit) => text.to_lowercase() == *lit,
+        }
+    }
+}
+
+/// Quantidade de tokens que um elemento pode consumir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quantifier {
+    One,
+    Optional,
+    OneOrMore,
+    ZeroOrMore,
+}
+
+impl Quantifier {
+    fn bounds(self) -> (usize, usize) {
+        match self {
+            Quantifier::One => (1, 1),
+            Quantifier::Optional => (0, 1),
+            Quantifier::OneOrMore => (1, usize::MAX),
+            Quantifier::ZeroOrMore => (0, usize::MAX),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PatternElement {
+    matcher: TokenMatcher,
+    quantifier: Quantifier,
+}
+
+/// Resultado de um casamento: intervalos de tokens (fim exclusivo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// Primeiro token do casamento completo.
+    pub start: usize,
+    /// Fim (exclusivo) do casamento completo.
+    pub end: usize,
+    /// Primeiro token que recebe a tag (início do grupo).
+    pub target_start: usize,
+    /// Fim (exclusivo) dos tokens que recebem a tag.
+    pub target_end: usize,
+}
+
+/// Um padrão compilado.
+///
+/// Serializado como o texto de origem e recompilado ao ser lido.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TokenPattern {
+    source: String,
+    elements: Vec<PatternElement>,
+    /// Elementos `[inicio, fim)` que formam o grupo de captura.
+    group: (usize, usize),
+}
+
+impl TokenPattern {
+    /// Compila o texto de um padrão (ver a sintaxe no topo do módulo).
+    pub fn compile(source: &str) -> Result<Self, PatternError> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut elements = Vec::new();
+        let mut group_start: Option<usize> = None;
+        let mut group: Option<(usize, usize)> = None;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let ch = chars[i];
+            let matcher = match ch {
+                c if c.is_whitespace() => {
+                    i += 1;
+                    continue;
+                }
+                '(' => {
+                    if group_start.is_some() || group.is_some() {
+                        return Err(PatternError::InvalidGroup);
+                    }
+                    group_start = Some(elements.len());
+                    i += 1;
+                    continue;
+                }
+                ')' => {
+                    let start = group_start.take().ok_or(PatternError::InvalidGroup)?;
+                    group = Some((start, elements.len()));
+                    i += 1;
+                    continue;
+                }
+                '[' | '"' => {
+                    let close = if ch == '[' { ']' } else { '"' };
+                    let len = chars[i + 1..]
+                        .iter()
+                        .position(|&c| c == close)
+                        .ok_or(PatternError::Unterminated(ch))?;
+                    let content: String = chars[i + 1..i + 1 + len].iter().collect();
+                    i += len + 2;
+                    if ch == '"' {
+                        TokenMatcher::Literal(content.to_lowercase())
+                    } else {
+                        match content.trim() {
+                            "Cap" => TokenMatcher::Capitalized,
+                            "Upper" => TokenMatcher::Upper,
+                            "Lower" => TokenMatcher::Lower,
+                            "Num" => TokenMatcher::Number,
+                            "Punct" => TokenMatcher::Punct,
+                            "Abbrev" => TokenMatcher::Kind(TokenKind::Abbreviation),
+                            "Url" => TokenMatcher::Kind(TokenKind::Url),
+                            "Email" => TokenMatcher::Kind(TokenKind::Email),
+                            "Hashtag" => TokenMatcher::Kind(TokenKind::Hashtag),
+                            "Mention" => TokenMatcher::Kind(TokenKind::Mention),
+                            "Money" => TokenMatcher::Kind(TokenKind::Money),
+                            name if !name.is_empty() && name.chars().all(|c| c.is_lowercase() || c == '_') => {
+                                TokenMatcher::WordClass(name.to_string())
+                            }
+                            name => return Err(PatternError::UnknownClass(name.to_string())),
+                        }
+                    }
+                }
+                _ => return Err(PatternError::UnexpectedChar { ch, position: i }),
+            };
+
+            let quantifier = match chars.get(i) {
+                Some('+') => Quantifier::OneOrMore,
+                Some('*') => Quantifier::ZeroOrMore,
+                Some('?') => Quantifier::Optional,
+                _ => Quantifier::One,
+            };
+            if quantifier != Quantifier::One {
+                i += 1;
+            }
+            elements.push(PatternElement { matcher, quantifier });
+        }
+
+        if group_start.is_some() {
+            return Err(PatternError::InvalidGroup);
+        }
+        if elements.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        let group = group.unwrap_or((0, elements.len()));
+        let group_min: usize = elements[group.0..group.1]
+            .iter()
+            .map(|e| e.quantifier.bounds().0)
+            .sum();
+        if group_min == 0 {
+            return Err(PatternError::EmptyGroup);
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            elements,
+            group,
+        })
+    }
+
+    /// Texto original do padrão.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Nomes das classes de palavras usadas (ex: `["title"]`).
+    pub fn word_classes(&self) -> impl Iterator<Item = &str> {
+        self.elements.iter().filter_map(|e| match &e.matcher {
+            TokenMatcher::WordClass(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Tenta casar o padrão começando exatamente no token `start`.
+    ///
+    /// `in_class(classe, palavra_minúscula)` decide se uma palavra pertence a uma
+    /// classe de palavras. Quantificadores são gulosos, com backtracking.
+    pub fn match_at(
+        &self,
+        tokens: &[Token],
+        start: usize,
+        in_class: &dyn Fn(&str, &str) -> bool,
+    ) -> Option<PatternMatch> {
+        let mut consumed = Vec::with_capacity(self.elements.len());
+        let mut reached_end = false;
+        if !self.match_from(tokens, 0, start, in_class, &mut consumed, &mut reached_end) {
+            return None;
+        }
+        let end = consumed.last().map_or(start, |&(_, e)| e);
+        let target_start = consumed[self.group.0].0;
+        let target_end = consumed[self.group.1 - 1].1;
+        Some(PatternMatch { start, end, target_start, target_end })
+    }
+
+    /// Indica se o resultado de [`TokenPattern::match_at`] em `start` ainda pode mudar
+    /// quando mais tokens forem acrescentados ao fim de `tokens` (casamento parcial).
+    ///
+    /// Isso ocorre quando algum elemento parou de consumir tokens por ter chegado ao fim
+    /// da sequência, e não por um token incompatível: `"[title] ([Cap]+)"` sobre
+    /// `["ministro"]` ainda pode casar; sobre `["ministro", "Fernando"]` ainda pode crescer.
+    pub fn needs_more_tokens(
+        &self,
+        tokens: &[Token],
+        start: usize,
+        in_class: &dyn Fn(&str, &str) -> bool,
+    ) -> bool {
+        let mut consumed = Vec::with_capacity(self.elements.len());
+        let mut reached_end = false;
+        self.match_from(tokens, 0, start, in_class, &mut consumed, &mut reached_end);
+        reached_end
+    }
+
+    /// Backtracking: `consumed[k]` guarda o intervalo de tokens do elemento `k`.
+    /// `reached_end` é marcado quando algum elemento esbarra no fim da sequência.
+    fn match_from(
+        &self,
+        tokens: &[Token],
+        element: usize,
+        pos: usize,
+        in_class: &dyn Fn(&str, &str) -> bool,
+        consumed: &mut Vec<(usize, usize)>,
+        reached_end: &mut bool,
+    ) -> bool {
+        let Some(el) = self.elements.get(element) else {
+            return true;
+        };
+        let (min, max) = el.quantifier.bounds();
+        let mut available = 0;
+        while available < max
+            && pos + available < tokens.len()
+            && el.matcher.matches(&tokens[pos + available], in_class)
+        {
+            available += 1;
+        }
+        if available < max && pos + available == tokens.len() {
+            *reached_end = true;
+        }
+        if available < min {
+            return false;
+        }
+        for take in (min..=available).rev() {
+            consumed.push((pos, pos + take));
+            if self.match_from(tokens, element + 1, pos + take, in_class, consumed, reached_end) {
+                return true;
+            }
+            consumed.pop();
+        }
+        false
+    }
+}
+
+impl TryFrom<String> for TokenPattern {
+    type Error = PatternError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::compile(&source)
+    }
+}
+
+impl From<TokenPattern> for String {
+    fn from(pattern: TokenPattern) -> Self {
+        pattern.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::tokenize;
+
+    fn no_classes(_: &str, _: &str) -> bool {
+        false
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert_eq!(TokenPattern::compile("  "), Err(PatternError::Empty));
+        assert_eq!(TokenPattern::compile("[Cap"), Err(PatternError::Unterminated('[')));
+        assert_eq!(TokenPattern::compile("([Cap]"), Err(PatternError::InvalidGroup));
+        assert_eq!(TokenPattern::compile("([Cap]) ([Cap])"), Err(PatternError::InvalidGroup));
+        assert_eq!(TokenPattern::compile("[Cap] ([Num]?)"), Err(PatternError::EmptyGroup));
+        assert_eq!(TokenPattern::compile("[Foo]"), Err(PatternError::UnknownClass("Foo".into())));
+        assert!(matches!(TokenPattern::compile("Cap"), Err(PatternError::UnexpectedChar { ch: 'C', .. })));
+    }
+
+    #[test]
+    fn test_literal_and_backtracking() {
+        // [Cap]+ precisa devolver um token para que "Ltda" case
+        let pattern = TokenPattern::compile("[Cap]+ \"ltda\"").unwrap();
+        let tokens = tokenize("a Azul Ltda lucrou");
+        let m = pattern.match_at(&tokens, 1, &no_classes).unwrap();
+        assert_eq!((m.start, m.end), (1, 3));
+        assert_eq!((m.target_start, m.target_end), (1, 3));
+        assert!(pattern.match_at(&tokens, 0, &no_classes).is_none());
+    }
+
+    #[test]
+    fn test_group_and_optional() {
+        let pattern = TokenPattern::compile("\"em\" ([Num]) [Punct]?").unwrap();
+        let tokens = tokenize("em 2024 .");
+        let m = pattern.match_at(&tokens, 0, &no_classes).unwrap();
+        assert_eq!((m.target_start, m.target_end, m.end), (1, 2, 3));
+        assert_eq!(pattern.word_classes().count(), 0);
+    }
+
+    #[test]
+    fn test_token_kinds() {
+        // Valor monetário inteiro num token só, seguido de "por" e de uma menção
+        let pattern = TokenPattern::compile("([Money]) \"por\" [Mention]").unwrap();
+        let tokens = tokenize("pagou R$ 9.500,00 por @fornecedor");
+        let m = pattern.match_at(&tokens, 1, &no_classes).unwrap();
+        assert_eq!((m.target_start, m.target_end, m.end), (1, 2, 4));
+        assert!(TokenPattern::compile("[Email]+").unwrap().match_at(&tokenize("ana@exemplo.com"), 0, &no_classes).is_some());
+        assert!(TokenPattern::compile("[Url]").unwrap().match_at(&tokenize("exemplo"), 0, &no_classes).is_none());
+    }
+
+    #[test]
+    fn test_needs_more_tokens() {
+        let pattern = TokenPattern::compile("[Lower] ([Cap]+)").unwrap();
+        // Ainda falta o nome / o nome ainda pode crescer
+        assert!(pattern.needs_more_tokens(&tokenize("o"), 0, &no_classes));
+        assert!(pattern.needs_more_tokens(&tokenize("o Fernando"), 0, &no_classes));
+        // O token minúsculo encerra o nome; um token incompatível encerra a tentativa
+        assert!(!pattern.needs_more_tokens(&tokenize("o Fernando falou"), 0, &no_classes));
+        assert!(!pattern.needs_more_tokens(&tokenize("O"), 0, &no_classes));
+    }
+}
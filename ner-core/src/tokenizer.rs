@@ -6,11 +6,23 @@
 //!
 //! ## Esquema de Tokenização
 //!
-//! - **Standard**: Palavras separadas por espaços/pontuações. Preserva abreviações comuns.
+//! - **Standard**: Palavras separadas por espaços/pontuações. Preserva abreviações comuns
+//!   e indicadores ordinais de citações legais ("art. 5º, §2º" -> "art.", "5º", ",", "§", "2º";
+//!   "n.º" e "1.º" ficam inteiros).
 //! - **CharLevel**: Cada caractere é um token (bom para redes neurais profundas/OOV).
 //! - **Aggressive**: Separa sufixos comuns e clíticos (ex: "curou-se" -> "curou", "-", "se").
+//!   [`tokenize_aggressive_with`] permite proteger palavras (ex: nomes de entidades) da divisão.
 //! - **Conservative**: Preserva locuções e nomes compostos (ex: "São Paulo").
-//! - **BpeLite**: Simulação de BPE baseada em frequência de sub-palavras.
+//! - **BpeLite**: Simulação de BPE baseada em frequência de sub-palavras. A tabela de merges
+//!   ([`BpeMergeTable`]) pode ser salva e carregada por idioma/perfil.
+//! - **Bpe**: BPE de verdade, com os merges aprendidos de um corpus ([`BpeModel::train`]).
+//!
+//! Em todos os modos derivados do `Standard`, URLs ("www.financas.com"), e-mails, hashtags,
+//! menções ("@usuario") e valores monetários ("R$ 1.500,00") são um token só, com o tipo
+//! em [`Token::kind`], para que regras e features os reconheçam diretamente.
+//!
+//! [`split_sentences`] segmenta documentos em sentenças (sem quebrar em "Dr." ou "art."),
+//! usado por [`crate::NerPipeline::analyze_document`].
 //!
 //! ## Exemplo de Uso
 //!
@@ -26,8 +38,17 @@
 //! let aggressive = tokenize_with_mode(text, TokenizerMode::Aggressive);
 //! ```
 
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
+use crate::format;
+use crate::offsets::slice_lossy;
+
 /// Um token extraído do texto original.
 ///
 /// O `Token` é a unidade atômica de processamento do pipeline. Ele mantém a referência
@@ -45,17 +66,150 @@ pub struct Token {
     /// Índice sequencial do token na lista (0, 1, 2...).
     /// Útil para algoritmos que olham vizinhos (tokens[i-1]).
     pub index: usize,
+    /// Tipo do token, deduzido do texto ([`TokenKind::of`]).
+    #[serde(default)]
+    pub kind: TokenKind,
+    /// Forma do token ([`word_shape`]): "Xxxx" para "Lula", "XX-99" para "SP-12".
+    #[serde(default)]
+    pub shape: String,
+}
+
+impl Token {
+    /// Token com o tipo e a forma deduzidos do texto.
+    pub fn new(text: impl Into<String>, start: usize, end: usize, index: usize) -> Self {
+        let text = text.into();
+        let kind = TokenKind::of(&text);
+        let shape = word_shape(&text);
+        Self { text, start, end, index, kind, shape }
+    }
+
+    /// Começa com letra maiúscula ("Lula", "STF").
+    pub fn is_capitalized(&self) -> bool {
+        self.shape.starts_with('X')
+    }
+}
+
+/// Forma de uma palavra: maiúsculas viram `X`, minúsculas `x`, dígitos `9` e os demais
+/// caracteres ficam como estão. Sequências da mesma classe param em 4 caracteres, para
+/// que palavras longas compartilhem a forma ("Presidente" → "Xxxxx").
+///
+/// ```rust
+/// use ner_core::tokenizer::word_shape;
+///
+/// assert_eq!(word_shape("Lula"), "Xxxx");
+/// assert_eq!(word_shape("SP-12"), "XX-99");
+/// assert_eq!(word_shape("14h30min"), "99x99xxx");
+/// assert_eq!(word_shape("Presidente"), "Xxxxx");
+/// ```
+pub fn word_shape(text: &str) -> String {
+    let mut shape = String::new();
+    let (mut previous, mut run) = (None, 0);
+    for c in text.chars() {
+        let class = if c.is_uppercase() {
+            'X'
+        } else if c.is_lowercase() {
+            'x'
+        } else if c.is_numeric() {
+            '9'
+        } else {
+            c
+        };
+        run = if previous == Some(class) { run + 1 } else { 1 };
+        previous = Some(class);
+        if run <= MAX_SHAPE_RUN {
+            shape.push(class);
+        }
+    }
+    shape
+}
+
+/// Tipo de um [`Token`], deduzido do texto quando o token é criado.
+///
+/// Features, regras e padrões de tokens (`[Url]`, `[Abbrev]`...) consultam o tipo em
+/// vez de reexaminar o texto.
+///
+/// ```rust
+/// use ner_core::tokenizer::{tokenize, TokenKind};
+///
+/// let tokens = tokenize("Pagou R$ 1.500,00 via ana.silva@exemplo.com.br #pix");
+/// let kinds: Vec<(&str, TokenKind)> = tokens.iter().map(|t| (t.text.as_str(), t.kind)).collect();
+/// assert_eq!(kinds[1], ("R$ 1.500,00", TokenKind::Money));
+/// assert_eq!(kinds[3], ("ana.silva@exemplo.com.br", TokenKind::Email));
+/// assert_eq!(kinds[4], ("#pix", TokenKind::Hashtag));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// Palavra (ou qualquer token que não seja de outro tipo).
+    #[default]
+    Word,
+    /// Número, com separadores de milhar e decimais ou indicador ordinal ("2024", "1.234", "5º").
+    Number,
+    /// Só pontuação e símbolos (",", "§", "—").
+    Punctuation,
+    /// Abreviação com ponto ("Dr.", "N.Y.", "Inc.").
+    Abbreviation,
+    /// Endereço web iniciado por `http://`, `https://` ou `www.`.
+    Url,
+    /// Endereço de e-mail (`nome@dominio.com.br`).
+    Email,
+    /// `#` seguido de letras, dígitos ou `_`.
+    Hashtag,
+    /// `@` seguido de letras, dígitos ou `_`.
+    Mention,
+    /// Valor com símbolo de moeda (`R$ 100`, `US$ 5,50`, `€10`).
+    Money,
+}
+
+impl TokenKind {
+    /// Nome em minúsculas, como na serialização ("url", "money").
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Word => "word",
+            Self::Number => "number",
+            Self::Punctuation => "punctuation",
+            Self::Abbreviation => "abbreviation",
+            Self::Url => "url",
+            Self::Email => "email",
+            Self::Hashtag => "hashtag",
+            Self::Mention => "mention",
+            Self::Money => "money",
+        }
+    }
+
+    /// Tipo do texto de um token inteiro.
+    pub fn of(text: &str) -> Self {
+        match special_token(text) {
+            Some((len, kind)) if len == text.len() => return kind,
+            _ => {}
+        }
+        let Some(first) = text.chars().next() else {
+            return Self::Word;
+        };
+        if text.chars().all(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+            Self::Punctuation
+        } else if first.is_numeric()
+            && text.trim_end_matches(ORDINAL_INDICATORS).chars().all(|c| c.is_numeric() || c == '.' || c == ',')
+        {
+            Self::Number
+        } else if text.len() > 1 && text.ends_with('.') && text.chars().any(char::is_alphabetic) {
+            Self::Abbreviation
+        } else {
+            Self::Word
+        }
+    }
 }
 
 /// Estratégias de Tokenização disponíveis.
 ///
 /// A escolha do tokenizador impacta diretamente quais "unidades" o modelo verá.
 /// Diferentes estratégias podem ser úteis para diferentes tipos de texto ou modelos.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TokenizerMode {
     /// **Padrão**: Separa por espaços e pontuações, mas preserva abreviações comuns (ex: "Dr.", "Sra.")
     /// e números flutuantes. Ideal para textos formais e jornalísticos.
+    #[default]
     Standard,
     /// **Caractere**: Cada caractere é um token. Útil para modelos profundos (Deep Learning) que
     /// aprendem morfologia do zero ou para lidar com muito ruído (erros de digitação).
@@ -69,24 +223,34 @@ pub enum TokenizerMode {
     /// **Sub-word (BPE Lite)**: Simulação didática de Byte-Pair Encoding. Agrupa caracteres frequentes
     /// (ex: "q"+"u"+"e" -> "que"). Reduz o tamanho do vocabulário mantendo partes significativas.
     BpeLite,
-}
-
-impl Default for TokenizerMode {
-    fn default() -> Self {
-        TokenizerMode::Standard
-    }
+    /// **Sub-word (BPE treinado)**: aplica os merges de um [`BpeModel`] aprendido do corpus do
+    /// domínio (ver `NerPipeline::with_bpe_model`). Sem modelo (ex: em [`tokenize_with_mode`]),
+    /// equivale a `Standard`.
+    Bpe,
 }
 
 /// Abreviações comuns em PT-BR que não devem ter o ponto tratado como fim de sentença
-const ABBREVIATIONS: &[&str] = &[
+///
+/// Abreviações de outros domínios podem ser acrescentadas em [`TokenizerConfig`].
+pub(crate) const ABBREVIATIONS: &[&str] = &[
     "Dr", "Dra", "Sr", "Sra", "Prof", "Profa", "Gov", "Dep", "Sen", "Min",
     "Gen", "Cap", "Sgt", "Cel", "Brig", "Adm", "Des", "Pres", "Eng", "Arq",
     "km", "cm", "mm", "kg", "mg", "ml", "dl", "ha", "etc", "vol", "núm",
     "art", "pág", "pag", "cap", "tel", "fax", "av", "pg", "ibid", "op",
+    // Citações legais ("Art. 5º", "arts. 1º e 2º")
+    "Art", "Arts", "arts",
 ];
 
-/// Sufixos e clíticos para o modo Aggressive
-const CLITICS: &[&str] = &["-se", "-nos", "-lhe", "-lhes", "-me", "-te", "-o", "-a", "-los", "-las"];
+/// Indicadores ordinais ("5º", "2ª"). O sinal de grau ("5°") é digitado com frequência no lugar de "º".
+const ORDINAL_INDICATORS: &[char] = &['º', 'ª', '°'];
+
+/// Prefixos que aceitam indicador ordinal após o ponto ("n.º", "1.º").
+fn takes_ordinal_indicator(current: &str) -> bool {
+    matches!(current, "n" | "N") || (!current.is_empty() && current.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Sufixos e clíticos para o modo Aggressive (outros clíticos em [`TokenizerConfig`])
+pub(crate) const CLITICS: &[&str] = &["-se", "-nos", "-lhe", "-lhes", "-me", "-te", "-o", "-a", "-los", "-las"];
 const SUFFIXES: &[&str] = &["mente", "ção", "ções", "ista", "ismo", "dade"];
 
 /// Locuções comuns para o modo Conservative
@@ -96,6 +260,387 @@ const COMPOUNDS: &[&str] = &[
     "estados unidos", "reino unido", "nova iorque", "sem teto", "pôr do sol",
 ];
 
+/// Maior sequência de caracteres da mesma classe mantida em [`word_shape`].
+const MAX_SHAPE_RUN: usize = 4;
+
+/// Símbolos de moeda que formam um token com o valor seguinte ("R$ 1.500,00").
+const CURRENCY_SYMBOLS: &[&str] = &["R$", "US$", "U$", "€", "£"];
+
+/// Prefixos de URL (comparados sem diferenciar maiúsculas).
+const URL_PREFIXES: &[&str] = &["https://", "http://", "www."];
+
+/// Pares do modo `BpeLite` embutido (ordem importa: prioridade).
+const LITE_MERGES: &[(&str, &str)] = &[
+    ("e", "s"), ("a", "s"), ("o", "s"), // plurais
+    ("d", "e"), ("d", "o"), ("d", "a"), // preposições
+    ("q", "u"), ("u", "e"), ("e", "m"), // "que", "em"
+    ("ã", "o"), ("ç", "a"), ("ç", "o"), // nasais/cedilha
+    ("r", "e"), ("i", "n"), ("t", "e"), // prefixos/sufixos
+];
+
+/// Tabela de merges BPE de um idioma/perfil (ex: `"pt-BR-lite"`).
+///
+/// Um modelo treinado sobre tokens BPE só entende os sub-words gerados pela **mesma**
+/// tabela: trocar os merges muda os tokens e, portanto, as features. Por isso a tabela
+/// pode ser salva junto do modelo ([`BpeMergeTable::save`]) e é identificada por uma
+/// impressão digital ([`BpeMergeTable::fingerprint`]) que o pipeline confere antes de
+/// tokenizar (ver `NerPipeline::check_bpe_merges`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BpeMergeTable {
+    /// Idioma ou perfil a que a tabela pertence (apenas informativo).
+    pub profile: String,
+    /// Pares de merge em ordem de prioridade.
+    merges: Vec<(String, String)>,
+}
+
+impl BpeMergeTable {
+    pub fn new(profile: &str, merges: Vec<(String, String)>) -> Self {
+        Self { profile: profile.to_string(), merges }
+    }
+
+    /// A tabela embutida do modo [`TokenizerMode::BpeLite`].
+    pub fn lite() -> Self {
+        let merges = LITE_MERGES.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect();
+        Self::new("pt-BR-lite", merges)
+    }
+
+    pub fn merges(&self) -> &[(String, String)] {
+        &self.merges
+    }
+
+    /// Indica se `left + right` é um merge da tabela.
+    pub fn contains(&self, left: &str, right: &str) -> bool {
+        self.merges.iter().any(|(a, b)| a == left && b == right)
+    }
+
+    /// Impressão digital estável (FNV-1a) dos merges, na ordem.
+    ///
+    /// O nome do perfil não entra no cálculo: duas tabelas com os mesmos merges
+    /// produzem os mesmos tokens.
+    pub fn fingerprint(&self) -> u64 {
+        merges_fingerprint(&self.merges)
+    }
+
+    /// Tokeniza o texto aplicando os merges desta tabela.
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = tokenize_bpe_merges(text, self);
+        for (i, token) in tokens.iter_mut().enumerate() {
+            token.index = i;
+        }
+        tokens
+    }
+
+    /// Salva a tabela em disco (JSON, com a versão do formato; ver [`crate::format`]).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega uma tabela salva com [`BpeMergeTable::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+}
+
+/// Modelo BPE (Byte-Pair Encoding) aprendido de um corpus, usado pelo modo [`TokenizerMode::Bpe`].
+///
+/// Diferente da tabela do `BpeLite` (três passadas gulosas sobre pares fixos), os merges
+/// são aprendidos por frequência ([`BpeModel::train`]) e aplicados na ordem em que foram
+/// aprendidos até nenhum se aplicar, como em Sennrich et al. (2016). Os merges não
+/// atravessam palavras: o texto é antes segmentado pelo tokenizador `Standard`, e cada
+/// sub-word guarda os offsets de byte do texto original.
+///
+/// ```rust
+/// use ner_core::tokenizer::BpeModel;
+///
+/// let corpus = ["Ministério da Saúde", "Ministério da Fazenda", "ministra da Saúde"];
+/// let bpe = BpeModel::train(corpus, 40);
+/// let text = "O Ministério da Economia";
+/// let tokens = bpe.tokenize(text);
+/// assert!(tokens.iter().any(|t| t.text == "Ministério"));
+/// assert!(tokens.iter().all(|t| &text[t.start..t.end] == t.text));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BpeModel {
+    /// Idioma, domínio ou corpus de origem (apenas informativo).
+    pub profile: String,
+    /// Merges em ordem de aprendizado (prioridade).
+    merges: Vec<(String, String)>,
+}
+
+impl BpeModel {
+    pub fn new(profile: &str, merges: Vec<(String, String)>) -> Self {
+        Self { profile: profile.to_string(), merges }
+    }
+
+    /// Aprende merges dos textos até o vocabulário (caracteres do corpus mais um símbolo
+    /// por merge) chegar a `vocab_size`, ou até nenhum par aparecer mais de uma vez.
+    ///
+    /// A cada passo junta o par adjacente mais frequente; empates vão para o menor par
+    /// em ordem lexicográfica, para o treino ser determinístico.
+    pub fn train<'a>(texts: impl IntoIterator<Item = &'a str>, vocab_size: usize) -> Self {
+        let config = TokenizerConfig::new();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let texts: Vec<&str> = texts.into_iter().collect();
+        for text in &texts {
+            for token in tokenize_standard(text, &config) {
+                // Offsets do próprio tokenizador: sempre fronteiras de caractere de `text`
+                *counts.entry(&text[token.start..token.end]).or_default() += 1;
+            }
+        }
+        let mut words: Vec<(Vec<String>, usize)> =
+            counts.into_iter().map(|(word, count)| (word.chars().map(String::from).collect(), count)).collect();
+        let mut vocab: BTreeSet<String> = words.iter().flat_map(|(symbols, _)| symbols.iter().cloned()).collect();
+
+        let mut merges = Vec::new();
+        while vocab.len() < vocab_size {
+            let mut pairs: HashMap<(&str, &str), usize> = HashMap::new();
+            for (symbols, count) in &words {
+                for pair in symbols.windows(2) {
+                    *pairs.entry((pair[0].as_str(), pair[1].as_str())).or_default() += count;
+                }
+            }
+            let Some(((left, right), count)) =
+                pairs.into_iter().max_by(|(a, ca), (b, cb)| ca.cmp(cb).then_with(|| b.cmp(a)))
+            else {
+                break;
+            };
+            if count < 2 {
+                break;
+            }
+            let (left, right) = (left.to_string(), right.to_string());
+            let merged = format!("{left}{right}");
+            for (symbols, _) in &mut words {
+                let mut i = 0;
+                while i + 1 < symbols.len() {
+                    if symbols[i] == left && symbols[i + 1] == right {
+                        symbols[i] = merged.clone();
+                        symbols.remove(i + 1);
+                    }
+                    i += 1;
+                }
+            }
+            vocab.insert(merged);
+            merges.push((left, right));
+        }
+        Self::new("treinado", merges)
+    }
+
+    /// Define o perfil informativo do modelo.
+    pub fn with_profile(mut self, profile: &str) -> Self {
+        self.profile = profile.to_string();
+        self
+    }
+
+    pub fn merges(&self) -> &[(String, String)] {
+        &self.merges
+    }
+
+    /// Impressão digital estável dos merges, na ordem (como [`BpeMergeTable::fingerprint`]).
+    pub fn fingerprint(&self) -> u64 {
+        merges_fingerprint(&self.merges)
+    }
+
+    /// Tokeniza o texto em sub-words, com os offsets de byte do texto original.
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let ranks: HashMap<(&str, &str), usize> =
+            self.merges.iter().enumerate().map(|(rank, (a, b))| ((a.as_str(), b.as_str()), rank)).collect();
+        let mut tokens = Vec::new();
+        for word in tokenize_standard(text, &TokenizerConfig::new()) {
+            // `word` vem do tokenizador e os símbolos, de `char_indices`: todo intervalo
+            // fatiado abaixo começa e termina numa fronteira de caractere
+            let mut symbols: Vec<Range<usize>> = text[word.start..word.end]
+                .char_indices()
+                .map(|(i, c)| word.start + i..word.start + i + c.len_utf8())
+                .collect();
+            // O merge de menor posição na ordem de aprendizado primeiro, até não sobrar nenhum
+            while let Some((_, i)) = symbols
+                .windows(2)
+                .enumerate()
+                .filter_map(|(i, pair)| ranks.get(&(&text[pair[0].clone()], &text[pair[1].clone()])).map(|&rank| (rank, i)))
+                .min()
+            {
+                symbols[i].end = symbols[i + 1].end;
+                symbols.remove(i + 1);
+            }
+            tokens.extend(symbols.into_iter().map(|range| Token::new(text[range.clone()].to_string(), range.start, range.end, 0)));
+        }
+        for (i, token) in tokens.iter_mut().enumerate() {
+            token.index = i;
+        }
+        tokens
+    }
+
+    /// Salva o modelo em disco (JSON, com a versão do formato; ver [`crate::format`]).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega um modelo salvo com [`BpeModel::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+}
+
+/// Impressão digital FNV-1a de uma lista de merges, na ordem.
+fn merges_fingerprint(merges: &[(String, String)]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    let mut hash = OFFSET;
+    for (a, b) in merges {
+        // Separadores impedem que ("ab", "c") e ("a", "bc") colidam
+        for byte in a.bytes().chain([0]).chain(b.bytes()).chain([0xff]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+/// Ajustes do tokenizador além das listas embutidas.
+///
+/// Guarda abreviações extras (sem o ponto, diferenciando maiúsculas), tipicamente
+/// aprendidas de um corpus do domínio com [`crate::abbreviations::AbbreviationLearner`]:
+/// "ref. nº 12" deixa de virar "ref" + "." e de encerrar a sentença. Clíticos extras e
+/// terminações da mesóclise vêm da variante da língua ([`crate::language::LanguagePack`]).
+///
+/// ```rust
+/// use ner_core::tokenizer::{TokenizerConfig, TokenizerMode};
+///
+/// let config = TokenizerConfig::new().with_abbreviation("ref");
+/// let tokens = config.tokenize("Ver ref. nº 12.", TokenizerMode::Standard);
+/// assert_eq!(tokens[1].text, "ref.");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenizerConfig {
+    /// Abreviações além das embutidas.
+    #[serde(default)]
+    pub abbreviations: BTreeSet<String>,
+    /// Clíticos do modo `Aggressive` além dos embutidos, com o hífen ("-vos").
+    #[serde(default)]
+    pub clitics: BTreeSet<String>,
+    /// Terminações que seguem um clítico na mesóclise ("dir-se-á" → "dir", "-", "se",
+    /// "-", "á"). Vazio: a mesóclise não é separada.
+    #[serde(default)]
+    pub mesoclitic_endings: BTreeSet<String>,
+}
+
+impl TokenizerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_abbreviation(mut self, word: &str) -> Self {
+        self.add_abbreviation(word);
+        self
+    }
+
+    /// Acrescenta uma abreviação (o ponto final, se houver, é ignorado).
+    /// Retorna `false` se ela já era conhecida.
+    pub fn add_abbreviation(&mut self, word: &str) -> bool {
+        let word = word.strip_suffix('.').unwrap_or(word);
+        !self.is_abbreviation(word) && self.abbreviations.insert(word.to_string())
+    }
+
+    /// `word` (sem o ponto) é uma abreviação embutida ou desta configuração?
+    pub fn is_abbreviation(&self, word: &str) -> bool {
+        ABBREVIATIONS.contains(&word) || self.abbreviations.contains(word)
+    }
+
+    /// Acrescenta um clítico (o hífen inicial é opcional).
+    /// Retorna `false` se ele já era conhecido.
+    pub fn add_clitic(&mut self, clitic: &str) -> bool {
+        let clitic = format!("-{}", clitic.trim_start_matches('-'));
+        !self.is_clitic(&clitic) && self.clitics.insert(clitic)
+    }
+
+    /// `clitic` (com o hífen) é um clítico embutido ou desta configuração?
+    pub fn is_clitic(&self, clitic: &str) -> bool {
+        CLITICS.contains(&clitic) || self.clitics.contains(clitic)
+    }
+
+    /// Como [`tokenize_with_mode`], usando esta configuração.
+    pub fn tokenize(&self, text: &str, mode: TokenizerMode) -> Vec<Token> {
+        let mut tokens = split_with_mode(text, mode, self);
+        for (i, token) in tokens.iter_mut().enumerate() {
+            token.index = i;
+        }
+        tokens
+    }
+
+    /// Como [`tokenize_aggressive_with`], usando esta configuração.
+    pub fn tokenize_aggressive_with(&self, text: &str, protect: impl Fn(&Token) -> bool) -> Vec<Token> {
+        let mut tokens = split_aggressive(text, protect, self);
+        for (i, token) in tokens.iter_mut().enumerate() {
+            token.index = i;
+        }
+        tokens
+    }
+
+    /// Como [`split_sentences`], usando as abreviações desta configuração.
+    pub fn split_sentences(&self, text: &str) -> Vec<Range<usize>> {
+        let tokens = split_with_mode(text, TokenizerMode::Standard, self);
+        let mut ranges = Vec::new();
+        let mut start: Option<usize> = None;
+        for (i, token) in tokens.iter().enumerate() {
+            let begin = *start.get_or_insert(token.start);
+            let next = tokens.get(i + 1);
+            let ends_sentence = match next {
+                None => true,
+                // Aspas e parênteses que fecham a frase ficam com ela: «disse: "Vamos."»
+                Some(next) if SENTENCE_CLOSERS.contains(&next.text.as_str()) && next.start == token.end => false,
+                // Pontuação repetida ("...", "?!") encerra uma vez só
+                Some(next) if is_sentence_end(&next.text) && next.start == token.end => false,
+                Some(next) => {
+                    is_sentence_end(&token.text)
+                        || (SENTENCE_CLOSERS.contains(&token.text.as_str())
+                            && i > 0
+                            && tokens[i - 1].end == token.start
+                            && is_sentence_end(&tokens[i - 1].text))
+                        // Linha em branco: títulos e itens sem pontuação final (offsets de
+                        // tokens vizinhos de `split_with_mode`, fronteiras de caractere)
+                        || text[token.end..next.start].matches('\n').count() >= 2
+                }
+            };
+            if ends_sentence {
+                ranges.push(begin..token.end);
+                start = None;
+            }
+        }
+        ranges
+    }
+
+    /// Salva a configuração em disco (JSON, com a versão do formato).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        format::save_json(path, self)
+    }
+
+    /// Carrega uma configuração salva com [`TokenizerConfig::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        format::load_json(path)
+    }
+}
+
+/// A tabela de merges em uso difere da usada no treinamento do modelo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BpeMismatch {
+    /// Impressão digital registrada no modelo.
+    pub expected: u64,
+    /// Impressão digital da tabela em uso.
+    pub found: u64,
+}
+
+impl fmt::Display for BpeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tabela de merges BPE incompatível com o modelo (esperada {:016x}, em uso {:016x})",
+            self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for BpeMismatch {}
+
 /// Tokeniza um texto usando o algoritmo padrão (compatibilidade).
 pub fn tokenize(text: &str) -> Vec<Token> {
     tokenize_with_mode(text, TokenizerMode::Standard)
@@ -103,43 +648,132 @@ pub fn tokenize(text: &str) -> Vec<Token> {
 
 /// Tokeniza um texto com o modo especificado.
 pub fn tokenize_with_mode(text: &str, mode: TokenizerMode) -> Vec<Token> {
-    let mut tokens = match mode {
+    TokenizerConfig::new().tokenize(text, mode)
+}
+
+/// Tokens de `text` no modo `mode`, ainda sem os índices.
+fn split_with_mode(text: &str, mode: TokenizerMode, config: &TokenizerConfig) -> Vec<Token> {
+    match mode {
         // Caractere a caractere: bom para lidar com "typos" ou línguas sem espaçamento.
         TokenizerMode::CharLevel => tokenize_char_level(text),
         // Agressivo: remove sufixos (-mente) e clíticos (-se), normalizando o texto.
-        TokenizerMode::Aggressive => tokenize_aggressive(text),
+        TokenizerMode::Aggressive => split_aggressive(text, |_| false, config),
         // Conservador: Preserva "São Paulo" como um único token.
-        TokenizerMode::Conservative => tokenize_conservative(text),
+        TokenizerMode::Conservative => tokenize_conservative(text, config),
         // BPE Simulado: sub-words.
         TokenizerMode::BpeLite => tokenize_bpe_lite(text),
-        // Padrão: espaços e pontuações, preservando abreviações.
-        TokenizerMode::Standard => tokenize_standard(text),
-    };
+        // Padrão: espaços e pontuações, preservando abreviações. `Bpe` sem modelo também.
+        TokenizerMode::Standard | TokenizerMode::Bpe => tokenize_standard(text, config),
+    }
+}
 
-    // Re-indexa os tokens
-    for (i, token) in tokens.iter_mut().enumerate() {
-        token.index = i;
+/// Divide a sequência de tokens em sentenças, retornando os intervalos de índices.
+///
+/// Uma sentença termina em um token de pontuação final isolado (".", "!", "?", "…").
+/// Abreviações como "Dr." já chegam como um único token e não encerram a sentença.
+/// Tokens após a última pontuação formam uma sentença final.
+pub fn sentence_ranges(tokens: &[Token]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if matches!(token.text.as_str(), "." | "!" | "?" | "…" | "...") {
+            ranges.push(start..i + 1);
+            start = i + 1;
+        }
     }
-    tokens
+    if start < tokens.len() {
+        ranges.push(start..tokens.len());
+    }
+    ranges
 }
 
+/// Segmenta `text` em sentenças, retornando os intervalos de bytes de cada uma.
+///
+/// Usa o tokenizador `Standard`, de modo que abreviações ("Dr.", "Sr.", "art.", "n.º")
+/// não encerram a sentença. Além da pontuação final, uma linha em branco também separa
+/// sentenças; aspas e parênteses logo após o ponto ficam na sentença que fecham. Os
+/// intervalos começam no primeiro e terminam no último token (sem os espaços entre
+/// sentenças).
+///
+/// ```rust
+/// use ner_core::tokenizer::split_sentences;
+///
+/// let text = "O Dr. Silva chegou a Brasília. Ele disse: \"Vamos.\" Depois saiu!\n\nNovo parágrafo";
+/// let sentences: Vec<&str> = split_sentences(text).into_iter().map(|r| &text[r]).collect();
+/// assert_eq!(
+///     sentences,
+///     ["O Dr. Silva chegou a Brasília.", "Ele disse: \"Vamos.\"", "Depois saiu!", "Novo parágrafo"]
+/// );
+/// ```
+pub fn split_sentences(text: &str) -> Vec<Range<usize>> {
+    TokenizerConfig::new().split_sentences(text)
+}
+
+/// Tokens que encerram uma sentença.
+fn is_sentence_end(token: &str) -> bool {
+    matches!(token, "." | "!" | "?" | "…" | "...")
+}
+
+/// Tokens que, logo após a pontuação final, ainda pertencem à sentença.
+const SENTENCE_CLOSERS: &[&str] = &["\"", "”", "’", "'", ")", "]", "»"];
+
 fn tokenize_char_level(text: &str) -> Vec<Token> {
     text.char_indices()
-        .map(|(i, c)| Token {
-            text: c.to_string(),
-            start: i,
-            end: i + c.len_utf8(),
-            index: 0,
-        })
+        .map(|(i, c)| Token::new(c.to_string(), i, i + c.len_utf8(), 0))
         .collect()
 }
 
-fn tokenize_aggressive(text: &str) -> Vec<Token> {
+/// Tokeniza no modo `Aggressive` sem dividir os tokens para os quais `protect` retorna `true`.
+///
+/// Dividir sufixos e clíticos ajuda a reduzir o vocabulário, mas destrói a evidência
+/// de entidades: "Consolidação" vira "Consolida" + "ção", e nem o gazetteer nem a
+/// capitalização reconhecem mais a palavra. O pipeline usa esta função para proteger
+/// palavras capitalizadas e de gazetteer (ver `NerPipeline::entity_safe_aggressive`).
+///
+/// ```rust
+/// use ner_core::tokenizer::{tokenize_aggressive_with, tokenize_with_mode, TokenizerMode};
+///
+/// let text = "a Consolidação rapidamente";
+/// let raw: Vec<String> = tokenize_with_mode(text, TokenizerMode::Aggressive).into_iter().map(|t| t.text).collect();
+/// assert_eq!(raw, ["a", "Consolida", "ção", "rapida", "mente"]);
+///
+/// let capitalized = |t: &ner_core::Token| t.text.starts_with(char::is_uppercase);
+/// let safe: Vec<String> = tokenize_aggressive_with(text, capitalized).into_iter().map(|t| t.text).collect();
+/// assert_eq!(safe, ["a", "Consolidação", "rapida", "mente"]);
+/// ```
+pub fn tokenize_aggressive_with(text: &str, protect: impl Fn(&Token) -> bool) -> Vec<Token> {
+    TokenizerConfig::new().tokenize_aggressive_with(text, protect)
+}
+
+/// Partes de uma forma mesoclítica ("dir-se-á"), se `config` conhece o clítico e a terminação.
+fn split_mesoclisis<'a>(word: &'a str, config: &TokenizerConfig) -> Option<[&'a str; 5]> {
+    let (base, rest) = word.split_once('-')?;
+    let (clitic, ending) = rest.split_once('-')?;
+    let known = config.mesoclitic_endings.contains(ending) && config.is_clitic(&format!("-{clitic}"));
+    (known && !base.is_empty()).then_some([base, "-", clitic, "-", ending])
+}
+
+fn split_aggressive(text: &str, protect: impl Fn(&Token) -> bool, config: &TokenizerConfig) -> Vec<Token> {
     // Primeiro tokeniza standard, depois pós-processa
-    let standard_tokens = tokenize_standard(text);
+    let standard_tokens = tokenize_standard(text, config);
     let mut expanded_tokens = Vec::new();
 
     for token in standard_tokens {
+        if protect(&token) || token.kind != TokenKind::Word {
+            expanded_tokens.push(token);
+            continue;
+        }
+
+        // Mesóclise (ex: dir-se-á): base, "-", clítico, "-", terminação
+        if let Some(parts) = split_mesoclisis(&token.text, config) {
+            let mut start = token.start;
+            for part in parts {
+                expanded_tokens.push(Token::new(part.to_string(), start, start + part.len(), 0));
+                start += part.len();
+            }
+            continue;
+        }
+
         // Verifica clíticos (ex: encontrou-se)
         let mut handled = false;
         
@@ -147,32 +781,18 @@ fn tokenize_aggressive(text: &str) -> Vec<Token> {
         if let Some((base, clitic)) = token.text.rsplit_once('-') {
              // Reconstrói o clítico com hífen para checar na lista (ex: "-se")
             let clitic_with_hyphen = format!("-{}", clitic);
-            if CLITICS.contains(&clitic_with_hyphen.as_str()) && !base.is_empty() {
+            if config.is_clitic(&clitic_with_hyphen) && !base.is_empty() {
                 // Split: base, "-", clitic
                 let base_len = base.len();
                 let hyphen_len = 1; // assumindo 1 byte '-'
                 
                 // Base
-                expanded_tokens.push(Token {
-                    text: base.to_string(),
-                    start: token.start,
-                    end: token.start + base_len,
-                    index: 0,
-                });
+                expanded_tokens.push(Token::new(base.to_string(), token.start, token.start + base_len, 0));
                 // Hífen
-                expanded_tokens.push(Token {
-                    text: "-".to_string(),
-                    start: token.start + base_len,
-                    end: token.start + base_len + hyphen_len,
-                    index: 0,
-                });
+                let hyphen_end = token.start + base_len + hyphen_len;
+                expanded_tokens.push(Token::new("-", token.start + base_len, hyphen_end, 0));
                 // Clítico
-                expanded_tokens.push(Token {
-                    text: clitic.to_string(),
-                    start: token.start + base_len + hyphen_len,
-                    end: token.end,
-                    index: 0,
-                });
+                expanded_tokens.push(Token::new(clitic.to_string(), hyphen_end, token.end, 0));
                 handled = true;
             }
         }
@@ -189,20 +809,10 @@ fn tokenize_aggressive(text: &str) -> Vec<Token> {
                          let (base, suf) = token.text.split_at(split_idx);
                          
                          // Base
-                         expanded_tokens.push(Token {
-                             text: base.to_string(),
-                             start: token.start,
-                             end: token.start + base.len(),
-                             index: 0,
-                         });
+                         expanded_tokens.push(Token::new(base.to_string(), token.start, token.start + base.len(), 0));
                          // Sufixo (marcado com + para visualização, mas texto original preservado na teoria)
                          // Aqui vamos apenas quebrar
-                         expanded_tokens.push(Token {
-                             text: suf.to_string(),
-                             start: token.start + base.len(),
-                             end: token.end,
-                             index: 0,
-                         });
+                         expanded_tokens.push(Token::new(suf.to_string(), token.start + base.len(), token.end, 0));
                          suffix_handled = true;
                          break;
                      }
@@ -218,8 +828,8 @@ fn tokenize_aggressive(text: &str) -> Vec<Token> {
     expanded_tokens
 }
 
-fn tokenize_conservative(text: &str) -> Vec<Token> {
-    let standard = tokenize_standard(text);
+fn tokenize_conservative(text: &str, config: &TokenizerConfig) -> Vec<Token> {
+    let standard = tokenize_standard(text, config);
     if standard.is_empty() { return standard; }
 
     let mut merged = Vec::new();
@@ -236,7 +846,7 @@ fn tokenize_conservative(text: &str) -> Vec<Token> {
             let candidate_slice = &standard[i..i+window];
             // Verifica se os tokens são adjacentes no texto original
             let is_adjacent = candidate_slice.windows(2).all(|w| w[1].start == w[0].end || 
-                (w[1].start > w[0].end && text[w[0].end..w[1].start].trim().is_empty()));
+                (w[1].start > w[0].end && slice_lossy(text, w[0].end, w[1].start).trim().is_empty()));
              
              if is_adjacent {
                  let combined_text = candidate_slice.iter().map(|t| t.text.as_str()).collect::<Vec<_>>().join(" ");
@@ -250,12 +860,7 @@ fn tokenize_conservative(text: &str) -> Vec<Token> {
             // Cria token mergeado
             let first = &standard[i];
             let last = &standard[i + best_match_len - 1];
-            merged.push(Token {
-                text: text[first.start..last.end].to_string(),
-                start: first.start,
-                end: last.end,
-                index: 0,
-            });
+            merged.push(Token::new(slice_lossy(text, first.start, last.end).to_string(), first.start, last.end, 0));
             i += best_match_len;
         } else {
             merged.push(standard[i].clone());
@@ -267,20 +872,15 @@ fn tokenize_conservative(text: &str) -> Vec<Token> {
 }
 
 fn tokenize_bpe_lite(text: &str) -> Vec<Token> {
+    tokenize_bpe_merges(text, &BpeMergeTable::lite())
+}
+
+fn tokenize_bpe_merges(text: &str, table: &BpeMergeTable) -> Vec<Token> {
     // Simulação simplificada de BPE:
     // 1. Quebra em caracteres
-    // 2. Faz merges de pares frequentes conhecidos (hardcoded para demonstração)
+    // 2. Faz merges dos pares da tabela (ordem importa: prioridade)
     let mut tokens = tokenize_char_level(text);
-    
-    // Pares para merge (ordem importa: prioridade)
-    let merges = &[
-        ("e", "s"), ("a", "s"), ("o", "s"), // plurais
-        ("d", "e"), ("d", "o"), ("d", "a"), // preposições
-        ("q", "u"), ("u", "e"), ("e", "m"), // "que", "em"
-        ("ã", "o"), ("ç", "a"), ("ç", "o"), // nasais/cedilha
-        ("r", "e"), ("i", "n"), ("t", "e"), // prefixos/sufixos
-    ];
-    
+
     // Aplica N passadas de merge
     for _ in 0..3 {
         let mut new_tokens = Vec::new();
@@ -291,18 +891,10 @@ fn tokenize_bpe_lite(text: &str) -> Vec<Token> {
                 let t2 = &tokens[i+1];
                 
                 // Só merge se forem adjacentes
-                if t1.end == t2.start {
-                    let pair = (t1.text.as_str(), t2.text.as_str());
-                    if merges.contains(&pair) {
-                        new_tokens.push(Token {
-                            text: format!("{}{}", t1.text, t2.text),
-                            start: t1.start,
-                            end: t2.end,
-                            index: 0,
-                        });
-                        i += 2;
-                        continue;
-                    }
+                if t1.end == t2.start && table.contains(&t1.text, &t2.text) {
+                    new_tokens.push(Token::new(format!("{}{}", t1.text, t2.text), t1.start, t2.end, 0));
+                    i += 2;
+                    continue;
                 }
             }
             new_tokens.push(tokens[i].clone());
@@ -314,7 +906,7 @@ fn tokenize_bpe_lite(text: &str) -> Vec<Token> {
     tokens
 }
 
-fn tokenize_standard(text: &str) -> Vec<Token> {
+fn tokenize_standard(text: &str, config: &TokenizerConfig) -> Vec<Token> {
     let mut tokens = Vec::new();
     let mut current_start = 0;
     let mut current_text = String::new();
@@ -324,14 +916,36 @@ fn tokenize_standard(text: &str) -> Vec<Token> {
     while i < chars.len() {
         let (byte_pos, ch) = chars[i];
 
+        // URLs, e-mails, hashtags, menções e valores começam um token e ficam inteiros
+        if current_text.is_empty() {
+            // `byte_pos` vem de `char_indices` e `len` é o tamanho de um prefixo casado
+            if let Some((len, _)) = special_token(&text[byte_pos..]) {
+                let end = byte_pos + len;
+                push_token(&mut tokens, text[byte_pos..end].to_string(), byte_pos, end);
+                while i < chars.len() && chars[i].0 < end {
+                    i += 1;
+                }
+                continue;
+            }
+        }
+
         if ch.is_alphanumeric() || ch == '-' && !current_text.is_empty() {
             if current_text.is_empty() {
                 current_start = byte_pos;
             }
             current_text.push(ch);
+        } else if ch == '°' && takes_ordinal_indicator(&current_text) {
+            // "5°" usado como ordinal: o sinal de grau não é alfanumérico, mas pertence ao número
+            current_text.push(ch);
+        } else if ch == '.'
+            && takes_ordinal_indicator(&current_text)
+            && chars.get(i + 1).is_some_and(|(_, c)| ORDINAL_INDICATORS.contains(c))
+        {
+            // "n.º 45", "1.º de maio": o ponto faz parte do ordinal
+            current_text.push('.');
         } else if ch == '.' && !current_text.is_empty() {
             // Verifica se é abreviação (ex: "Dr.")
-            let is_abbrev = ABBREVIATIONS.contains(&current_text.as_str());
+            let is_abbrev = config.is_abbreviation(&current_text);
             // Lógica simplificada para número (ex: 1.234)
             let current_is_num = current_text.chars().all(char::is_numeric);
              let next_is_num = chars
@@ -378,15 +992,72 @@ fn tokenize_standard(text: &str) -> Vec<Token> {
     tokens
 }
 
+/// Token especial no início de `rest`: o tamanho em bytes e o tipo.
+fn special_token(rest: &str) -> Option<(usize, TokenKind)> {
+    match rest.chars().next()? {
+        '#' | '@' => {
+            let len = prefix_len(&rest[1..], |c| c.is_alphanumeric() || c == '_');
+            let kind = if rest.starts_with('#') { TokenKind::Hashtag } else { TokenKind::Mention };
+            (len > 0).then_some((1 + len, kind))
+        }
+        _ => url_len(rest)
+            .map(|len| (len, TokenKind::Url))
+            .or_else(|| email_len(rest).map(|len| (len, TokenKind::Email)))
+            .or_else(|| money_len(rest).map(|len| (len, TokenKind::Money))),
+    }
+}
+
+/// Bytes do maior prefixo de `s` cujos caracteres satisfazem `accept`.
+fn prefix_len(s: &str, accept: impl Fn(char) -> bool) -> usize {
+    s.char_indices().find(|&(_, c)| !accept(c)).map_or(s.len(), |(i, _)| i)
+}
+
+fn url_len(rest: &str) -> Option<usize> {
+    let prefix = URL_PREFIXES.iter().find(|p| rest.get(..p.len()).is_some_and(|head| head.eq_ignore_ascii_case(p)))?;
+    let body = prefix_len(rest, |c| !c.is_whitespace() && !matches!(c, '<' | '>' | '"'));
+    // A pontuação no fim pertence à frase ("Veja www.financas.com.")
+    let len = rest[..body].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']).len();
+    (len > prefix.len()).then_some(len)
+}
+
+fn email_len(rest: &str) -> Option<usize> {
+    if !rest.starts_with(char::is_alphanumeric) {
+        return None;
+    }
+    let local = prefix_len(rest, |c| c.is_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'));
+    let domain_start = local + rest[local..].strip_prefix('@').map(|_| 1)?;
+    let domain_len = prefix_len(&rest[domain_start..], |c| c.is_alphanumeric() || matches!(c, '.' | '-'));
+    let domain = rest[domain_start..domain_start + domain_len].trim_end_matches('.');
+    let valid = domain.contains('.') && !domain.starts_with('.') && !domain.contains("..");
+    valid.then_some(domain_start + domain.len())
+}
+
+fn money_len(rest: &str) -> Option<usize> {
+    let symbol = CURRENCY_SYMBOLS.iter().find(|s| rest.starts_with(**s))?;
+    let mut len = symbol.len();
+    if rest[len..].starts_with(' ') {
+        len += 1;
+    }
+    // Dígitos com separadores de milhar e decimais ("1.500,00"); o separador final fica de fora
+    let bytes = &rest.as_bytes()[len..];
+    let (mut i, mut amount) = (0, 0);
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() {
+            i += 1;
+            amount = i;
+        } else if matches!(bytes[i], b'.' | b',') && amount == i && amount > 0 {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    (amount > 0).then_some(len + amount)
+}
+
 /// Fecha o token acumulado e adiciona à lista (se não vazio)
 fn flush_token(tokens: &mut Vec<Token>, text: &mut String, start: usize, end: usize) {
     if !text.is_empty() {
-        let t = Token {
-            text: text.clone(),
-            start,
-            end,
-            index: 0, // será atribuído depois
-        };
+        let t = Token::new(text.clone(), start, end, 0); // índice atribuído depois
         tokens.push(t);
         text.clear();
     }
@@ -394,12 +1065,7 @@ fn flush_token(tokens: &mut Vec<Token>, text: &mut String, start: usize, end: us
 
 /// Adiciona um token de pontuação diretamente
 fn push_token(tokens: &mut Vec<Token>, text: String, start: usize, end: usize) {
-    tokens.push(Token {
-        text,
-        start,
-        end,
-        index: 0,
-    });
+    tokens.push(Token::new(text, start, end, 0));
 }
 
 #[cfg(test)]
@@ -412,6 +1078,42 @@ mod tests {
         assert_eq!(tokens.len(), 4);
     }
     
+    #[test]
+    fn test_sentence_ranges() {
+        let tokens = tokenize("O Dr. Silva chegou. Saiu cedo! E depois");
+        let ranges = sentence_ranges(&tokens);
+        assert_eq!(ranges, vec![0..5, 5..8, 8..10]);
+        assert!(sentence_ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let sentences = |text: &'static str| split_sentences(text).into_iter().map(|r| &text[r]).collect::<Vec<_>>();
+        assert_eq!(
+            sentences("A Sra. Ana foi ao STF. Segundo o art. 5º, todos são iguais... Será?  Sim (disse ele.) Fim"),
+            ["A Sra. Ana foi ao STF.", "Segundo o art. 5º, todos são iguais...", "Será?", "Sim (disse ele.)", "Fim"]
+        );
+        assert_eq!(sentences("Título\n\nCorpo do texto.\nMesma sentença"), ["Título", "Corpo do texto.", "Mesma sentença"]);
+        assert_eq!(sentences("Uma linha\nquebrada"), ["Uma linha\nquebrada"]);
+        assert_eq!(sentences("Fim. \"Outra\" frase."), ["Fim.", "\"Outra\" frase."]);
+        assert!(split_sentences("  \n ").is_empty());
+
+        let config = TokenizerConfig::new().with_abbreviation("proc");
+        let text = "Ver proc. 123 do TJSP. Fim";
+        assert_eq!(split_sentences(text).len(), 3);
+        assert_eq!(config.split_sentences(text), vec![0..22, 23..26]);
+    }
+
+    #[test]
+    fn test_tokenize_legal_citations() {
+        let texts = |text: &str| tokenize(text).into_iter().map(|t| t.text).collect::<Vec<_>>();
+        assert_eq!(texts("Art. 5º, §2º"), vec!["Art.", "5º", ",", "§", "2º"]);
+        assert_eq!(texts("arts. 1° e 2ª"), vec!["arts.", "1°", "e", "2ª"]);
+        assert_eq!(texts("Lei n.º 8.078/1990"), vec!["Lei", "n.º", "8.078", "/", "1990"]);
+        assert_eq!(texts("no 1.º dia. Fim"), vec!["no", "1.º", "dia", ".", "Fim"]);
+        assert_eq!(texts("§§ 3º"), vec!["§", "§", "3º"]);
+    }
+
     #[test]
     fn test_tokenize_char_level() {
         let tokens = tokenize_with_mode("Oi", TokenizerMode::CharLevel);
@@ -448,8 +1150,126 @@ mod tests {
         // merges: q+u, u+e ...
         let tokens = tokenize_with_mode("quem", TokenizerMode::BpeLite);
         // q, u, e, m -> qu, e, m -> que, m -> quem (se tiver e+m)
-        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
         // Verificar se houve algum merge
         assert!(tokens.len() < 4); 
     }
+
+    #[test]
+    fn test_bpe_merge_table_roundtrip() {
+        let lite = BpeMergeTable::lite();
+        let texts = |tokens: Vec<Token>| tokens.into_iter().map(|t| t.text).collect::<Vec<_>>();
+        assert_eq!(texts(lite.tokenize("quem")), texts(tokenize_with_mode("quem", TokenizerMode::BpeLite)));
+
+        let path = std::env::temp_dir().join(format!("ner_bpe_{}.json", std::process::id()));
+        lite.save(&path).unwrap();
+        let loaded = BpeMergeTable::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, lite);
+        assert_eq!(loaded.fingerprint(), lite.fingerprint());
+
+        // Outro perfil com os mesmos merges tem a mesma impressão digital; outros merges, não
+        let renamed = BpeMergeTable::new("pt-PT", lite.merges().to_vec());
+        assert_eq!(renamed.fingerprint(), lite.fingerprint());
+        let split = |a: &str, b: &str| BpeMergeTable::new("x", vec![(a.into(), b.into())]).fingerprint();
+        assert_ne!(split("ab", "c"), split("a", "bc"));
+    }
+
+    #[test]
+    fn test_bpe_model_train_and_offsets() {
+        let corpus = ["O Ministério da Saúde", "o ministério da Fazenda", "a Saúde pública", "Ministério Público"];
+        let bpe = BpeModel::train(corpus, 200);
+        assert!(!bpe.merges().is_empty());
+        // Determinístico
+        assert_eq!(BpeModel::train(corpus, 200), bpe);
+        // Sem pares repetidos, para antes do tamanho pedido
+        assert!(BpeModel::train(["abc"], 100).merges().is_empty());
+
+        let text = "Ministério da Saúde, São Paulo.";
+        let tokens = bpe.tokenize(text);
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(&text[token.start..token.end], token.text);
+            assert_eq!(token.index, i);
+        }
+        // Nenhum merge atravessa palavras; palavras fora do corpus viram sub-words
+        assert!(tokens.iter().all(|t| !t.text.contains(' ')));
+        assert!(tokens.iter().any(|t| t.text == "Saúde"));
+        assert!(tokens.iter().filter(|t| t.end <= "Ministério da Saúde, São".len() && t.start >= "Ministério da Saúde, ".len()).count() > 1);
+
+        // Sem modelo, o modo equivale ao Standard
+        let texts = |tokens: Vec<Token>| tokens.into_iter().map(|t| t.text).collect::<Vec<_>>();
+        assert_eq!(texts(tokenize_with_mode(text, TokenizerMode::Bpe)), texts(tokenize_with_mode(text, TokenizerMode::Standard)));
+
+        let path = std::env::temp_dir().join(format!("ner_bpe_model_{}.json", std::process::id()));
+        bpe.save(&path).unwrap();
+        let loaded = BpeModel::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, bpe);
+        assert_eq!(loaded.fingerprint(), bpe.fingerprint());
+    }
+
+    #[test]
+    fn test_special_token_kinds() {
+        let text = "Contato: ana.silva@exemplo.com.br, site www.financas.com. Custou R$ 1.500,00 e US$5. \
+                    Siga @ana_silva #Eleições2024 em https://exemplo.com.br/a?b=1 (ou R$ 10,).";
+        let tokens = tokenize(text);
+        let kinds: Vec<(&str, TokenKind)> =
+            tokens.iter().filter(|t| !matches!(t.kind, TokenKind::Word | TokenKind::Punctuation)).map(|t| (t.text.as_str(), t.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("ana.silva@exemplo.com.br", TokenKind::Email),
+                ("www.financas.com", TokenKind::Url),
+                ("R$ 1.500,00", TokenKind::Money),
+                ("US$5", TokenKind::Money),
+                ("@ana_silva", TokenKind::Mention),
+                ("#Eleições2024", TokenKind::Hashtag),
+                ("https://exemplo.com.br/a?b=1", TokenKind::Url),
+                ("R$ 10", TokenKind::Money),
+            ]
+        );
+        for token in &tokens {
+            assert_eq!(&text[token.start..token.end], token.text);
+        }
+        // A pontuação depois do token especial continua separada
+        let after_url = tokens.iter().position(|t| t.text == "www.financas.com").unwrap();
+        assert_eq!(tokens[after_url + 1].text, ".");
+
+        // Sem domínio com ponto, sem valor ou sem nome: tokens comuns
+        let plain: Vec<String> = tokenize("a@b R$ x # @").into_iter().map(|t| t.text).collect();
+        assert_eq!(plain, ["a", "@", "b", "R", "$", "x", "#", "@"]);
+        assert_eq!(TokenKind::of("#pix"), TokenKind::Hashtag);
+        assert_eq!(TokenKind::of("Brasil"), TokenKind::Word);
+
+        // O modo Aggressive não divide os tokens especiais
+        let aggressive = tokenize_with_mode("veja www.site-se.com.br", TokenizerMode::Aggressive);
+        assert_eq!(aggressive[1].text, "www.site-se.com.br");
+    }
+
+    #[test]
+    fn test_token_kind_and_shape() {
+        let tokens = tokenize("O Dr. Silva pagou 1.234 reais em 5º lugar — SP-12, Sra. Ana");
+        let info: Vec<(&str, TokenKind, &str)> = tokens.iter().map(|t| (t.text.as_str(), t.kind, t.shape.as_str())).collect();
+        assert_eq!(
+            info,
+            [
+                ("O", TokenKind::Word, "X"),
+                ("Dr.", TokenKind::Abbreviation, "Xx."),
+                ("Silva", TokenKind::Word, "Xxxxx"),
+                ("pagou", TokenKind::Word, "xxxx"),
+                ("1.234", TokenKind::Number, "9.999"),
+                ("reais", TokenKind::Word, "xxxx"),
+                ("em", TokenKind::Word, "xx"),
+                ("5º", TokenKind::Number, "9x"),
+                ("lugar", TokenKind::Word, "xxxx"),
+                ("—", TokenKind::Punctuation, "—"),
+                ("SP-12", TokenKind::Word, "XX-99"),
+                (",", TokenKind::Punctuation, ","),
+                ("Sra.", TokenKind::Abbreviation, "Xxx."),
+                ("Ana", TokenKind::Word, "Xxx"),
+            ]
+        );
+        assert!(tokens[2].is_capitalized() && !tokens[3].is_capitalized());
+        // Tokens de outros modos também têm tipo e forma
+        assert!(tokenize_with_mode("Oi!", TokenizerMode::CharLevel).iter().all(|t| !t.shape.is_empty()));
+    }
 }
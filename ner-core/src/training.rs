@@ -0,0 +1,176 @@
+//! # Ordem das Sentenças no Treino
+//!
+//! Os aprendizes online (Perceptron, MaxEnt via SGD, modelo de spans) atualizam os pesos
+//! a cada sentença, então a **ordem** em que o corpus é percorrido importa:
+//!
+//! - **Embaralhamento por época**: um corpus ordenado por domínio ("saúde", depois
+//!   "esporte"...) faz os pesos oscilarem; embaralhar a cada época (com semente, para
+//!   ser reprodutível) suaviza a convergência. Só a ordem das sentenças muda: os tokens
+//!   de cada sentença continuam na ordem original.
+//! - **Currículo** (*short first*): sentenças curtas primeiro, as longas depois — os
+//!   padrões básicos são aprendidos antes dos casos com muito contexto.
+//! - **Sobreamostragem**: em corpora desbalanceados uma categoria rara (tipicamente `MISC`)
+//!   quase não gera atualizações; repetir as sentenças que a contêm equilibra as classes.
+//!
+//! [`TrainingSchedule`] calcula a ordem de cada época ([`TrainingSchedule::epoch_order`])
+//! e os modelos a aplicam em `train_scheduled`.
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::tagger::EntityCategory;
+//! use ner_core::training::TrainingSchedule;
+//!
+//! let schedule = TrainingSchedule::new()
+//!     .with_shuffle(42)
+//!     .with_curriculum()
+//!     .with_oversampling(EntityCategory::Misc, 3);
+//!
+//! # #[cfg(feature = "statistical")] {
+//! let mut model = ner_core::perceptron::PerceptronModel::new();
+//! model.train_scheduled(&ner_core::corpus::get_corpus(), 5, &schedule);
+//! assert!(model.is_trained());
+//! # }
+//! ```
+
+use crate::corpus::OwnedAnnotatedSentence;
+use crate::synthetic::SplitMix64;
+use crate::tagger::{EntityCategory, Tag};
+
+/// Como o corpus é percorrido em cada época.
+///
+/// O padrão ([`TrainingSchedule::new`]) percorre o corpus na ordem original, uma vez por época.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingSchedule {
+    /// Semente do embaralhamento; `None` mantém a ordem do corpus.
+    pub shuffle_seed: Option<u64>,
+    /// Currículo por tamanho: sentenças com menos tokens primeiro.
+    pub short_first: bool,
+    /// Quantas vezes por época aparece cada sentença que contém a categoria.
+    ///
+    /// Uma sentença com várias categorias listadas usa o maior fator.
+    pub oversample: Vec<(EntityCategory, usize)>,
+}
+
+impl TrainingSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embaralha as sentenças a cada época (ordem diferente por época, reprodutível pela semente).
+    pub fn with_shuffle(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Ordena cada época da sentença mais curta para a mais longa.
+    ///
+    /// Com embaralhamento, sentenças de mesmo tamanho ficam em ordem embaralhada.
+    pub fn with_curriculum(mut self) -> Self {
+        self.short_first = true;
+        self
+    }
+
+    /// Repete `times` vezes por época as sentenças que contêm `category`.
+    pub fn with_oversampling(mut self, category: EntityCategory, times: usize) -> Self {
+        self.oversample.retain(|(c, _)| *c != category);
+        self.oversample.push((category, times));
+        self
+    }
+
+    /// Índices de `corpus` na ordem da época `epoch` (com repetições, se houver sobreamostragem).
+    ///
+    /// Ordem das etapas: repetições da sobreamostragem, embaralhamento, currículo.
+    pub fn epoch_order(&self, corpus: &[OwnedAnnotatedSentence], epoch: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = Vec::with_capacity(corpus.len());
+        for (i, sentence) in corpus.iter().enumerate() {
+            for _ in 0..self.repetitions(sentence) {
+                order.push(i);
+            }
+        }
+
+        if let Some(seed) = self.shuffle_seed {
+            // Uma semente por época: a época 3 é a mesma em todo treino com esta semente
+            let mut rng = SplitMix64::new(seed ^ (epoch as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            // Fisher-Yates
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.below(i + 1));
+            }
+        }
+
+        if self.short_first {
+            // Estável: empates mantêm a ordem (original ou embaralhada)
+            order.sort_by_key(|&i| corpus[i].annotations.len());
+        }
+        order
+    }
+
+    /// Quantas vezes a sentença aparece em uma época (pelo menos uma).
+    fn repetitions(&self, sentence: &OwnedAnnotatedSentence) -> usize {
+        self.oversample
+            .iter()
+            .filter(|(category, _)| {
+                sentence
+                    .annotations
+                    .iter()
+                    .any(|(_, tag)| Tag::from_label(tag).and_then(|t| t.category()) == Some(*category))
+            })
+            .map(|(_, times)| *times)
+            .max()
+            .unwrap_or(1)
+            .max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentence(tags: &[&str]) -> OwnedAnnotatedSentence {
+        OwnedAnnotatedSentence {
+            text: String::new(),
+            domain: String::new(),
+            annotations: tags.iter().map(|t| ("w".to_string(), t.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_epoch_order() {
+        let corpus = vec![
+            sentence(&["O", "O", "O", "O"]),
+            sentence(&["B-MISC", "I-MISC"]),
+            sentence(&["B-PER", "O", "O"]),
+            sentence(&["O"]),
+        ];
+
+        // Padrão: ordem original
+        assert_eq!(TrainingSchedule::new().epoch_order(&corpus, 0), vec![0, 1, 2, 3]);
+
+        // Embaralhamento: permutação, reprodutível, diferente entre épocas
+        let shuffled = TrainingSchedule::new().with_shuffle(7);
+        let orders: Vec<Vec<usize>> = (0..6).map(|e| shuffled.epoch_order(&corpus, e)).collect();
+        for order in &orders {
+            let mut sorted = order.clone();
+            sorted.sort();
+            assert_eq!(sorted, vec![0, 1, 2, 3]);
+        }
+        assert_eq!(orders[2], shuffled.epoch_order(&corpus, 2));
+        assert!(orders.iter().any(|o| o != &orders[0]));
+
+        // Currículo: curtas primeiro
+        let curriculum = TrainingSchedule::new().with_curriculum();
+        assert_eq!(curriculum.epoch_order(&corpus, 0), vec![3, 1, 2, 0]);
+
+        // Sobreamostragem: a sentença com MISC aparece 3 vezes; o último fator vale
+        let balanced = TrainingSchedule::new()
+            .with_oversampling(EntityCategory::Misc, 2)
+            .with_oversampling(EntityCategory::Misc, 3);
+        assert_eq!(balanced.oversample.len(), 1);
+        assert_eq!(balanced.epoch_order(&corpus, 0), vec![0, 1, 1, 1, 2, 3]);
+
+        // Tudo junto: as repetições também são embaralhadas, e o currículo vem por último
+        let all = balanced.with_shuffle(7).with_curriculum();
+        let order = all.epoch_order(&corpus, 1);
+        assert_eq!(order, vec![3, 1, 1, 1, 2, 0]);
+    }
+}
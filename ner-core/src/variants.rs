@@ -0,0 +1,202 @@
+//! # Variantes Ortográficas
+//!
+//! Textos antigos, transcrições e digitação apressada trazem grafias que os gazetteers
+//! não conhecem: "Sam Paulo", "Bahia"/"Baía", "Brazil", "Nictheroy", "Egypto". Com
+//! [`SpellingVariants`] ligado no pipeline ([`crate::pipeline::NerPipeline::with_spelling_variants`]),
+//! a chave de um token que não está em nenhum gazetteer é comparada às chaves dos
+//! gazetteers por meio de uma função geradora de variantes. Havendo coincidência, a
+//! consulta passa a usar a chave do gazetteer: features e regras enxergam "são" onde o
+//! texto diz "Sam", e a correspondência de regra guarda a grafia casada em
+//! [`crate::rule_based::RuleMatch::variant_of`].
+//!
+//! A geradora padrão, [`portuguese_variants`], reduz a palavra a um esqueleto em que as
+//! diferenças de ortografia histórica somem (acentos, "ph"/"th"/"y", "h" entre vogais,
+//! consoantes mudas e dobradas, "z" com som de "s"). Outras regras cabem em
+//! [`SpellingVariants::with_generator`].
+//!
+//! Chaves encontradas como estão nunca são trocadas, a troca só vale para chaves com
+//! pelo menos [`GazetteerKey::MIN_WORD_CHARS`] caracteres e, no pipeline, só para tokens
+//! capitalizados (o "para" minúsculo não vira "Pará").
+//!
+//! ## Exemplo
+//!
+//! ```rust
+//! use ner_core::features::{GazetteerKey, Gazetteers};
+//! use ner_core::variants::{portuguese_variants, SpellingVariants};
+//!
+//! assert_eq!(portuguese_variants("Bahia"), portuguese_variants("Baía"));
+//! assert_eq!(portuguese_variants("Nictheroy"), portuguese_variants("Niterói"));
+//!
+//! let mut gaz = Gazetteers::new();
+//! gaz.locations.extend(GazetteerKey::words("São Paulo"));
+//! let variants = SpellingVariants::new();
+//! assert_eq!(variants.canonical(&gaz, "sam").as_deref(), Some("são"));
+//! assert_eq!(variants.canonical(&gaz, "paulo"), None); // já está no gazetteer
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::features::{transliterate, GazetteerKey, Gazetteers};
+
+/// Função geradora de variantes: formas comparáveis de uma chave normalizada.
+///
+/// Duas chaves são grafias da mesma palavra quando as listas têm alguma forma em comum.
+pub type VariantGenerator = fn(&str) -> Vec<String>;
+
+/// Geradora padrão: o esqueleto ortográfico da palavra em português.
+///
+/// - acentos removidos e minúsculas ([`transliterate`]): "Baía" → "baia";
+/// - "são" arcaico: "Sam" → "sao";
+/// - grafia etimológica: "ph" → "f", "th" → "t", "ch" antes de "r"/"l" → "c", "y" → "i";
+/// - "h" entre vogais: "Bahia" → "baia";
+/// - consoantes mudas antes de "t": "Egypto" → "egito", "Victor" → "vitor";
+/// - "z" entre vogais ou no fim: "Brazil" → "brasil", "Souza" → "sousa";
+/// - letras dobradas: "Mattos" → "matos".
+pub fn portuguese_variants(word: &str) -> Vec<String> {
+    let folded = transliterate(word);
+    if folded == "sam" {
+        return vec!["sao".to_string()];
+    }
+    let folded = folded.replace("ph", "f").replace("th", "t").replace("chr", "cr").replace("chl", "cl").replace('y', "i");
+
+    let chars: Vec<char> = folded.chars().collect();
+    let is_vowel = |c: Option<&char>| c.is_some_and(|c| "aeiou".contains(*c));
+    let mut skeleton: Vec<char> = Vec::with_capacity(chars.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let (prev, next) = (i.checked_sub(1).and_then(|p| chars.get(p)), chars.get(i + 1));
+        match c {
+            'h' if is_vowel(prev) && (is_vowel(next) || next.is_none()) => continue,
+            'c' | 'p' if is_vowel(prev) && next == Some(&'t') => continue,
+            'z' if is_vowel(prev) && (is_vowel(next) || next.is_none()) => skeleton.push('s'),
+            _ if skeleton.last() == Some(&c) && c.is_alphabetic() => continue,
+            _ => skeleton.push(c),
+        }
+    }
+    vec![skeleton.into_iter().collect()]
+}
+
+/// Casamento de grafias variantes com as chaves dos gazetteers.
+///
+/// Guarda um índice forma → chave do gazetteer, montado na primeira consulta e refeito
+/// quando o número de chaves muda (ex: depois de [`crate::model::NerModel::add_gazetteer_entry`]).
+pub struct SpellingVariants {
+    generator: VariantGenerator,
+    index: RwLock<Option<Arc<VariantIndex>>>,
+}
+
+struct VariantIndex {
+    /// Número de chaves dos gazetteers quando o índice foi montado.
+    keys: usize,
+    canonical: HashMap<String, String>,
+}
+
+impl SpellingVariants {
+    /// Variantes com a geradora padrão ([`portuguese_variants`]).
+    pub fn new() -> Self {
+        Self::with_generator(portuguese_variants)
+    }
+
+    /// Variantes com outra função geradora.
+    pub fn with_generator(generator: VariantGenerator) -> Self {
+        Self { generator, index: RwLock::new(None) }
+    }
+
+    /// Formas comparáveis de uma chave, segundo a geradora.
+    pub fn variants(&self, key: &str) -> Vec<String> {
+        (self.generator)(key)
+    }
+
+    /// Chave do gazetteer de que `key` é uma grafia variante.
+    ///
+    /// `None` se `key` já está em algum gazetteer, é curta demais ou não coincide com
+    /// nenhuma chave. Se várias chaves coincidem, vale a menor em ordem alfabética.
+    pub fn canonical(&self, gazetteers: &Gazetteers, key: &str) -> Option<String> {
+        if key.chars().count() < GazetteerKey::MIN_WORD_CHARS || !gazetteers.hits(key).is_empty() {
+            return None;
+        }
+        let index = self.index(gazetteers);
+        self.variants(key).iter().find_map(|form| index.canonical.get(form)).cloned()
+    }
+
+    fn index(&self, gazetteers: &Gazetteers) -> Arc<VariantIndex> {
+        let keys = gazetteer_sets(gazetteers).map(|set| set.len()).sum();
+        if let Some(index) = self.index.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            if index.keys == keys {
+                return Arc::clone(index);
+            }
+        }
+
+        let mut canonical: HashMap<String, String> = HashMap::new();
+        for key in gazetteer_sets(gazetteers).flatten() {
+            for form in self.variants(key) {
+                canonical
+                    .entry(form)
+                    .and_modify(|current| {
+                        if key < current {
+                            *current = key.to_string();
+                        }
+                    })
+                    .or_insert_with(|| key.to_string());
+            }
+        }
+        let index = Arc::new(VariantIndex { keys, canonical });
+        *self.index.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&index));
+        index
+    }
+}
+
+/// Conjuntos de chaves de todas as categorias, embutidas e personalizadas.
+fn gazetteer_sets(gazetteers: &Gazetteers) -> impl Iterator<Item = &HashSet<String>> {
+    [&gazetteers.persons, &gazetteers.locations, &gazetteers.organizations, &gazetteers.misc, &gazetteers.dates, &gazetteers.times]
+        .into_iter()
+        .chain(gazetteers.custom.values())
+}
+
+impl Default for SpellingVariants {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SpellingVariants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indexed = self.index.read().unwrap_or_else(|e| e.into_inner()).as_ref().map(|index| index.canonical.len());
+        f.debug_struct("SpellingVariants").field("indexed_forms", &indexed).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_historical_spellings_share_skeleton() {
+        let same = |a: &str, b: &str| portuguese_variants(a) == portuguese_variants(b);
+        assert!(same("Sam", "São"));
+        assert!(same("Bahia", "Baía"));
+        assert!(same("Brazil", "Brasil"));
+        assert!(same("Egypto", "Egito"));
+        assert!(same("Christovam", "Cristovam"));
+        assert!(same("Souza", "Sousa"));
+        assert!(same("Mattos", "Matos"));
+        assert!(same("Piauhy", "Piauí"));
+        assert!(!same("Paulo", "Paula"));
+        assert!(!same("Chile", "Cile"), "\"ch\" só cai antes de r/l");
+    }
+
+    #[test]
+    fn test_index_follows_gazetteer_updates() {
+        let mut gaz = Gazetteers::new();
+        gaz.locations.insert("brasil".to_string());
+        let variants = SpellingVariants::new();
+        assert_eq!(variants.canonical(&gaz, "brazil").as_deref(), Some("brasil"));
+        assert_eq!(variants.canonical(&gaz, "nictheroy"), None);
+
+        gaz.locations.insert("niterói".to_string());
+        assert_eq!(variants.canonical(&gaz, "nictheroy").as_deref(), Some("niterói"));
+        // Curtas demais para trocar
+        assert_eq!(variants.canonical(&gaz, "br"), None);
+    }
+}
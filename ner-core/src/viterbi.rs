@@ -5,8 +5,8 @@
 //!
 //! ## Intuição
 //!
-//! Imagine que para cada token temos 9 tags possíveis. Uma busca exaustiva
-//! teria complexidade `O(9^N)` para N tokens — impraticável. O Viterbi explora
+//! Imagine que para cada token temos 13 tags possíveis. Uma busca exaustiva
+//! teria complexidade `O(13^N)` para N tokens — impraticável. O Viterbi explora
 //! que a **melhor sequência até o token i com tag t** depende apenas da
 //! **melhor sequência até o token i-1 com alguma tag anterior** → `O(N × T²)`.
 //!
@@ -19,10 +19,30 @@
 //!
 //! Backtracking: reconstruo o caminho ótimo de trás pra frente
 //! ```
+//!
+//! ## Busca em Feixe
+//!
+//! [`beam_decode`] troca a exatidão por velocidade: a cada token só as `K` melhores tags
+//! (o feixe) seguem adiante, e a recursão olha apenas essas como anteriores →
+//! `O(N × K × T)`. Com `K ≥ T` o resultado é o do Viterbi; com `K = 1` é a decodificação
+//! gulosa. O [`Decoder`] escolhe entre os dois no pipeline.
+//!
+//! ## Marginais (Forward-Backward)
+//!
+//! O Viterbi diz qual é a melhor sequência, não o quanto o modelo acredita em cada tag.
+//! [`marginals`] soma (em vez de maximizar) sobre todos os caminhos: `alpha[i][t]` cobre
+//! os prefixos que terminam em `t`, `beta[i][t]` os sufixos que partem de `t`, e
+//!
+//! ```text
+//! P(y_i = t | x) = exp(alpha[i][t] + beta[i][t] - log Z)
+//! ```
+//!
+//! É essa posterior que o pipeline usa como confiança do CRF, em vez do softmax dos
+//! scores de um passo do Viterbi (que só olha o melhor caminho até cada tag).
 
 use serde::{Deserialize, Serialize};
 
-use crate::crf::{compute_emission_scores, CrfModel};
+use crate::crf::{compute_emission_scores, log_sum_exp, CrfModel};
 use crate::features::FeatureVector;
 use crate::tagger::Tag;
 
@@ -42,6 +62,99 @@ pub struct ViterbiStep {
     pub best_score: f64,
 }
 
+impl ViterbiStep {
+    /// Cópia do passo mantendo apenas as `k` tags de maior score (em ordem decrescente).
+    pub fn top_k(&self, k: usize) -> ViterbiStep {
+        let mut scores = self.scores.clone();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores.truncate(k);
+        ViterbiStep { scores, ..self.clone() }
+    }
+}
+
+/// Quantas tags o modo [`ViterbiDetail::Compact`] mantém por passo.
+pub const COMPACT_TOP_K: usize = 3;
+
+/// Nível de detalhe dos passos do Viterbi enviados como eventos.
+///
+/// Em textos longos a tabela completa (todas as tags, a cada token) pode sobrecarregar
+/// o WebSocket; o cliente escolhe o nível no pedido de análise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViterbiDetail {
+    /// Um passo por token com o score de todas as tags.
+    #[default]
+    Full,
+    /// Um passo por token apenas com as [`COMPACT_TOP_K`] melhores tags.
+    Compact,
+    /// Um resumo por sentença, sem passos individuais.
+    Summary,
+}
+
+/// Resumo da decodificação de uma sentença (modo [`ViterbiDetail::Summary`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViterbiSentenceSummary {
+    /// Índice do primeiro token da sentença.
+    pub start_token: usize,
+    /// Índice exclusivo do último token da sentença.
+    pub end_token: usize,
+    /// Tags da melhor sequência para os tokens da sentença.
+    pub best_tags: Vec<String>,
+    /// Probabilidade média (softmax do passo) da tag escolhida em cada token.
+    pub mean_confidence: f64,
+    /// Token em que a tag escolhida teve a menor probabilidade (o ponto mais incerto).
+    pub least_confident_token: usize,
+    /// Probabilidade da tag escolhida nesse token.
+    pub min_confidence: f64,
+}
+
+/// Resume o resultado do Viterbi por sentença.
+///
+/// `sentences` são intervalos de índices de token (ver [`crate::tokenizer::sentence_ranges`]).
+/// Intervalos vazios ou fora do resultado são ignorados.
+pub fn summarize_sentences(
+    result: &ViterbiResult,
+    sentences: &[std::ops::Range<usize>],
+) -> Vec<ViterbiSentenceSummary> {
+    let n = result.best_sequence.len().min(result.steps.len());
+    sentences
+        .iter()
+        .map(|r| r.start..r.end.min(n))
+        .filter(|r| !r.is_empty())
+        .map(|range| {
+            let mut best_tags = Vec::with_capacity(range.len());
+            let mut total = 0.0;
+            let (mut least_confident_token, mut min_confidence) = (range.start, f64::INFINITY);
+            for i in range.clone() {
+                let label = result.best_sequence[i].label();
+                let step = &result.steps[i];
+                let scores: Vec<f64> = step.scores.iter().map(|s| s.score).collect();
+                let probs = scores_to_probs(&scores);
+                let confidence = step
+                    .scores
+                    .iter()
+                    .position(|s| s.tag == label)
+                    .map(|p| probs[p])
+                    .unwrap_or(0.0);
+                total += confidence;
+                if confidence < min_confidence {
+                    least_confident_token = i;
+                    min_confidence = confidence;
+                }
+                best_tags.push(label);
+            }
+            ViterbiSentenceSummary {
+                start_token: range.start,
+                end_token: range.end,
+                mean_confidence: total / best_tags.len() as f64,
+                best_tags,
+                least_confident_token,
+                min_confidence,
+            }
+        })
+        .collect()
+}
+
 /// Score de uma tag individual no Viterbi.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagScore {
@@ -79,126 +192,418 @@ pub struct ViterbiResult {
 ///    para reconstruir o caminho ótimo reverso.
 ///
 /// # Performance
-/// - Complexidade Temporal: $O(N \cdot T^2)$, onde $N$ é o número de tokens e $T$ o número de tags (9).
+/// - Complexidade Temporal: $O(N \cdot T^2)$, onde $N$ é o número de tokens e $T$ o número de tags
+///   (13 embutidas, mais 2 por categoria personalizada do modelo).
 /// - Complexidade Espacial: $O(N \cdot T)$ para armazenar a tabela e backpointers.
+///
+/// Para documentos longos, [`ViterbiScratch::best_path`] decodifica sem a tabela de
+/// visualização e reaproveita os buffers entre chamadas.
 pub fn viterbi_decode(model: &CrfModel, feature_vectors: &[FeatureVector]) -> ViterbiResult {
+    ViterbiScratch::new().decode(model, feature_vectors)
+}
+
+/// Decodificação por busca em feixe: como [`viterbi_decode`], mas cada token só estende
+/// as `beam_width` tags de maior score do token anterior.
+///
+/// A pontuação é a mesma do Viterbi (emissão, transição e penalidade de transição BIO
+/// inválida). Os `steps` trazem o score de todas as tags, inclusive das que ficaram fora
+/// do feixe, para que a visualização mostre o que foi podado. Largura 0 vale como 1.
+///
+/// ```rust
+/// use ner_core::viterbi::{beam_decode, viterbi_decode};
+/// use ner_core::features::{extract_features, Gazetteers};
+/// use ner_core::crf::CrfModel;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let model = CrfModel::new();
+/// let features = extract_features(&tokenize("O presidente Lula visitou Brasília."), &Gazetteers::new());
+/// let exact = viterbi_decode(&model, &features);
+/// // Um feixe com todas as tags é o Viterbi exato
+/// assert_eq!(beam_decode(&model, &features, model.tags().len()).best_sequence, exact.best_sequence);
+/// // Um feixe estreito nunca passa do score ótimo
+/// assert!(beam_decode(&model, &features, 2).best_score <= exact.best_score + 1e-9);
+/// ```
+pub fn beam_decode(model: &CrfModel, feature_vectors: &[FeatureVector], beam_width: usize) -> ViterbiResult {
     if feature_vectors.is_empty() {
-        return ViterbiResult {
-            best_sequence: vec![],
-            best_score: 0.0,
-            steps: vec![],
-        };
+        return ViterbiResult { best_sequence: vec![], best_score: 0.0, steps: vec![] };
     }
-
-    let n_tokens = feature_vectors.len();
-    let tags = Tag::all();
+    let tags = model.tags();
     let n_tags = tags.len();
-
-    // Pré-calcula scores de emissão: emission[i][t]
+    let width = beam_width.clamp(1, n_tags);
     let emission = compute_emission_scores(model, feature_vectors);
 
-    // Tabela Viterbi: viterbi[t] = melhor score acumulado para tag t no token atual
-    let mut viterbi: Vec<f64> = vec![f64::NEG_INFINITY; n_tags];
-    // Backpointer: backptr[i][t] = índice da tag anterior que maximiza o score
-    let mut backptr: Vec<Vec<usize>> = vec![vec![0usize; n_tags]; n_tokens];
-    // Steps para visualização
-    let mut steps: Vec<ViterbiStep> = Vec::with_capacity(n_tokens);
-
-    // === Inicialização (token 0) ===
-    // Sem transição para o primeiro token, só usamos o score de emissão
-    for t in 0..n_tags {
-        viterbi[t] = emission[0][t];
-        backptr[0][t] = t; // aponta para si mesmo
-    }
-
-    let (best_tag_0, best_score_0) = best_in_slice(&viterbi);
-    steps.push(ViterbiStep {
-        token_index: 0,
-        scores: (0..n_tags)
-            .map(|t| TagScore {
-                tag: tags[t].label(),
-                score: viterbi[t],
-                best_prev: tags[t].label(), // sem anterior no primeiro token
-                emission: emission[0][t],
-                transition: 0.0,
-            })
-            .collect(),
-        best_tag: tags[best_tag_0].label(),
-        best_score: best_score_0,
-    });
-
-    // === Recursão (tokens 1..N-1) ===
-    for i in 1..n_tokens {
-        let mut new_viterbi = vec![f64::NEG_INFINITY; n_tags];
+    let mut backptr: Vec<Vec<usize>> = Vec::with_capacity(feature_vectors.len());
+    let mut steps: Vec<ViterbiStep> = Vec::with_capacity(feature_vectors.len());
+    let mut scores: Vec<f64> = emission[0].clone();
+    let mut beam: Vec<usize> = Vec::new();
 
+    for i in 0..feature_vectors.len() {
         let mut step_scores = Vec::with_capacity(n_tags);
-
-        for t in 0..n_tags {
-            // Encontra a melhor tag anterior para esta tag t
-            let mut best_prev_score = f64::NEG_INFINITY;
-            let mut best_prev_tag = 0;
-            let mut best_transition = 0.0;
-
-            for prev_t in 0..n_tags {
-                let trans = model.transition_score(&tags[prev_t], &tags[t]);
-                let score = viterbi[prev_t] + trans;
-                if score > best_prev_score {
-                    best_prev_score = score;
-                    best_prev_tag = prev_t;
-                    best_transition = trans;
-                }
+        let mut pointers = Vec::with_capacity(n_tags);
+        if i == 0 {
+            // Sem anterior: o score é a emissão e o backpointer aponta para a própria tag
+            for t in 0..n_tags {
+                pointers.push(t);
+                step_scores.push(TagScore {
+                    tag: tags[t].label(),
+                    score: scores[t],
+                    best_prev: tags[t].label(),
+                    emission: emission[0][t],
+                    transition: 0.0,
+                });
             }
-
-            // Penaliza transições inválidas no esquema BIO
-            if !Tag::is_valid_transition(&tags[best_prev_tag], &tags[t]) {
-                // Pequena penalidade para manter o esquema BIO
-                new_viterbi[t] = best_prev_score + emission[i][t] - 10.0;
-            } else {
-                new_viterbi[t] = best_prev_score + emission[i][t];
+        } else {
+            let mut next = vec![f64::NEG_INFINITY; n_tags];
+            for t in 0..n_tags {
+                // Melhor anterior entre as tags do feixe, com a regra BIO do Viterbi
+                let (mut best_prev, mut best_prev_score, mut best_transition) = (beam[0], f64::NEG_INFINITY, 0.0);
+                for &prev_t in &beam {
+                    let trans = model.transition_weights[prev_t][t];
+                    if scores[prev_t] + trans > best_prev_score {
+                        (best_prev, best_prev_score, best_transition) = (prev_t, scores[prev_t] + trans, trans);
+                    }
+                }
+                let penalty = if Tag::is_valid_transition(&tags[best_prev], &tags[t]) { 0.0 } else { 10.0 };
+                next[t] = best_prev_score + emission[i][t] - penalty;
+                pointers.push(best_prev);
+                step_scores.push(TagScore {
+                    tag: tags[t].label(),
+                    score: next[t],
+                    best_prev: tags[best_prev].label(),
+                    emission: emission[i][t],
+                    transition: best_transition,
+                });
             }
-
-            backptr[i][t] = best_prev_tag;
-
-            step_scores.push(TagScore {
-                tag: tags[t].label(),
-                score: new_viterbi[t],
-                best_prev: tags[best_prev_tag].label(),
-                emission: emission[i][t],
-                transition: best_transition,
-            });
+            scores = next;
         }
 
-        viterbi = new_viterbi;
+        // Poda: só as `width` melhores tags seguem para o próximo token. O feixe volta à
+        // ordem das tags para desempatar como o Viterbi
+        beam = (0..n_tags).collect();
+        beam.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        beam.truncate(width);
+        beam.sort_unstable();
 
-        let (best_t, best_s) = best_in_slice(&viterbi);
+        let (best_t, best_s) = best_in_slice(&scores);
         steps.push(ViterbiStep {
             token_index: i,
             scores: step_scores,
             best_tag: tags[best_t].label(),
             best_score: best_s,
         });
+        backptr.push(pointers);
     }
 
-    // === Backtracking ===
-    // O Viterbi constrói o caminho de trás para frente.
-    // Começamos na última posição com a tag que tem o maior score total.
-    let (mut best_last_tag_index, best_total_score) = best_in_slice(&viterbi);
-    
-    // Inicializa o vetor de resultado
-    let mut best_sequence: Vec<Tag> = vec![Tag::Outside; n_tokens];
-    best_sequence[n_tokens - 1] = tags[best_last_tag_index].clone();
+    // Backtracking a partir da melhor tag do último token (todas têm backpointer)
+    let (mut best, best_score) = best_in_slice(&scores);
+    let mut best_sequence = vec![Tag::Outside; feature_vectors.len()];
+    for i in (0..feature_vectors.len()).rev() {
+        best_sequence[i] = tags[best].clone();
+        best = backptr[i][best];
+    }
+    ViterbiResult { best_sequence, best_score, steps }
+}
+
+/// Probabilidade posterior de cada tag em cada token (`[token][tag]`, na ordem de
+/// [`CrfModel::tags`]), por forward-backward.
+///
+/// Usa os mesmos scores do Viterbi: emissão, transição e a penalidade de transição BIO
+/// inválida. Cada linha soma 1.
+///
+/// ```rust
+/// use ner_core::viterbi::{marginals, viterbi_decode};
+/// use ner_core::features::{extract_features, Gazetteers};
+/// use ner_core::model::NerModel;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let model = NerModel::build();
+/// let features = extract_features(&tokenize("Lula visitou Brasília."), &model.gazetteers());
+/// let posteriors = marginals(&model.crf, &features);
+/// assert!(posteriors.iter().all(|row| (row.iter().sum::<f64>() - 1.0).abs() < 1e-9));
+/// ```
+pub fn marginals(model: &CrfModel, feature_vectors: &[FeatureVector]) -> Vec<Vec<f64>> {
+    let tags = model.tags();
+    let emission = compute_emission_scores(model, feature_vectors);
+    forward_backward(
+        feature_vectors.len(),
+        tags.len(),
+        |t| emission[0][t],
+        |i, u, t| {
+            let penalty = if Tag::is_valid_transition(&tags[u], &tags[t]) { 0.0 } else { 10.0 };
+            model.transition_weights[u][t] + emission[i][t] - penalty
+        },
+    )
+}
+
+/// Marginais por token de uma cadeia com scores em log-space: `first(t)` é o score da
+/// tag `t` no primeiro token e `step(i, u, t)` o de ir de `u` (token `i - 1`) para `t`
+/// (token `i`), já com a emissão. Também serve a HMM e MEMM.
+pub(crate) fn forward_backward(
+    n_tokens: usize,
+    n_tags: usize,
+    first: impl Fn(usize) -> f64,
+    step: impl Fn(usize, usize, usize) -> f64,
+) -> Vec<Vec<f64>> {
+    if n_tokens == 0 || n_tags == 0 {
+        return Vec::new();
+    }
+    // Os scores de transição não dependem do caminho: calcula uma vez por token
+    let steps: Vec<Vec<Vec<f64>>> =
+        (1..n_tokens).map(|i| (0..n_tags).map(|u| (0..n_tags).map(|t| step(i, u, t)).collect()).collect()).collect();
 
-    // Reconstrói o caminho seguindo os ponteiros `backptr`
+    let mut alpha = vec![vec![f64::NEG_INFINITY; n_tags]; n_tokens];
+    for (t, a) in alpha[0].iter_mut().enumerate() {
+        *a = first(t);
+    }
+    for i in 1..n_tokens {
+        for t in 0..n_tags {
+            alpha[i][t] = log_sum_exp((0..n_tags).map(|u| alpha[i - 1][u] + steps[i - 1][u][t]));
+        }
+    }
+    let mut beta = vec![vec![0.0; n_tags]; n_tokens];
     for i in (0..n_tokens - 1).rev() {
-        let prev_tag_index = backptr[i + 1][best_last_tag_index];
-        best_sequence[i] = tags[prev_tag_index].clone();
-        best_last_tag_index = prev_tag_index;
+        for u in 0..n_tags {
+            beta[i][u] = log_sum_exp((0..n_tags).map(|t| steps[i][u][t] + beta[i + 1][t]));
+        }
     }
+    let log_z = log_sum_exp(alpha[n_tokens - 1].iter().copied());
 
-    ViterbiResult {
-        best_sequence,
-        best_score: best_total_score,
-        steps,
+    alpha
+        .iter()
+        .zip(&beta)
+        .map(|(a, b)| {
+            if log_z == f64::NEG_INFINITY {
+                return vec![1.0 / n_tags as f64; n_tags];
+            }
+            a.iter().zip(b).map(|(a, b)| (a + b - log_z).exp()).collect()
+        })
+        .collect()
+}
+
+/// Algoritmo que decodifica as emissões do CRF no pipeline.
+///
+/// Serializado como `"viterbi"` ou `{"beam": {"width": 4}}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decoder {
+    /// Viterbi exato, `O(N × T²)`.
+    #[default]
+    Viterbi,
+    /// Busca em feixe de largura `width`, `O(N × width × T)` (ver [`beam_decode`]).
+    Beam { width: usize },
+}
+
+impl Decoder {
+    pub fn decode(&self, model: &CrfModel, feature_vectors: &[FeatureVector]) -> ViterbiResult {
+        match *self {
+            Decoder::Viterbi => viterbi_decode(model, feature_vectors),
+            Decoder::Beam { width } => beam_decode(model, feature_vectors, width),
+        }
+    }
+
+    /// Apenas a melhor sequência e seu score, para quando ninguém vai ver os `steps`.
+    ///
+    /// O Viterbi decodifica em `scratch` ([`ViterbiScratch::best_path`]), sem a matriz de
+    /// emissões nem a tabela de visualização; o feixe já guarda só `width` hipóteses.
+    pub fn best_path(&self, model: &CrfModel, feature_vectors: &[FeatureVector], scratch: &mut ViterbiScratch) -> (Vec<Tag>, f64) {
+        match *self {
+            Decoder::Viterbi => scratch.best_path(model, feature_vectors),
+            Decoder::Beam { width } => {
+                let result = beam_decode(model, feature_vectors, width);
+                (result.best_sequence, result.best_score)
+            }
+        }
+    }
+}
+
+/// Buffers reutilizáveis da decodificação Viterbi.
+///
+/// Os backpointers ficam em uma tabela plana de `u8` (`backptr[i * T + t]`): com 13 tags,
+/// cada token ocupa 13 bytes, contra `13 × 8` bytes (mais o cabeçalho de um `Vec` por
+/// token) de um `Vec<Vec<usize>>`. Decodificar várias sentenças com o mesmo scratchpad
+/// evita realocar a tabela a cada uma.
+///
+/// ```rust
+/// use ner_core::viterbi::{viterbi_decode, ViterbiScratch};
+/// use ner_core::features::{extract_features, Gazetteers};
+/// use ner_core::crf::CrfModel;
+/// use ner_core::tokenizer::tokenize;
+///
+/// let model = CrfModel::new();
+/// let mut scratch = ViterbiScratch::new();
+/// for sentence in ["Lula visitou Brasília.", "O Brasil venceu."] {
+///     let features = extract_features(&tokenize(sentence), &Gazetteers::new());
+///     let (path, _) = scratch.best_path(&model, &features);
+///     assert_eq!(path, viterbi_decode(&model, &features).best_sequence);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ViterbiScratch {
+    /// Backpointers: índice da melhor tag anterior, por token e tag.
+    backptr: Vec<u8>,
+    /// Scores acumulados do token atual e do próximo.
+    current: Vec<f64>,
+    next: Vec<f64>,
+    /// Scores de emissão do token sendo processado.
+    emission: Vec<f64>,
+}
+
+impl ViterbiScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes reservados pela tabela de backpointers (cresce até o maior documento visto).
+    pub fn backpointer_bytes(&self) -> usize {
+        self.backptr.capacity()
+    }
+
+    /// Decodificação completa, com a tabela de `steps` para visualização.
+    pub fn decode(&mut self, model: &CrfModel, feature_vectors: &[FeatureVector]) -> ViterbiResult {
+        if feature_vectors.is_empty() {
+            return ViterbiResult {
+                best_sequence: vec![],
+                best_score: 0.0,
+                steps: vec![],
+            };
+        }
+
+        let tags = model.tags();
+        let n_tags = tags.len();
+        // Pré-calcula scores de emissão: emission[i][t]
+        let emission = compute_emission_scores(model, feature_vectors);
+
+        // Steps para visualização
+        let mut steps: Vec<ViterbiStep> = Vec::with_capacity(feature_vectors.len());
+        let mut step_scores = Vec::with_capacity(n_tags);
+        let (best_sequence, best_score) = self.forward(
+            model,
+            feature_vectors.len(),
+            |i, row| row.copy_from_slice(&emission[i]),
+            |i, t, score, best_prev, transition| {
+                step_scores.push(TagScore {
+                    tag: tags[t].label(),
+                    score,
+                    // No primeiro token não há anterior: aponta para si mesmo
+                    best_prev: tags[best_prev].label(),
+                    emission: emission[i][t],
+                    transition,
+                });
+                if t == n_tags - 1 {
+                    let scores: Vec<f64> = step_scores.iter().map(|s| s.score).collect();
+                    let (best_t, best_s) = best_in_slice(&scores);
+                    steps.push(ViterbiStep {
+                        token_index: i,
+                        scores: std::mem::take(&mut step_scores),
+                        best_tag: tags[best_t].label(),
+                        best_score: best_s,
+                    });
+                }
+            },
+        );
+
+        ViterbiResult {
+            best_sequence,
+            best_score,
+            steps,
+        }
+    }
+
+    /// Apenas a melhor sequência e seu score, sem `steps` nem a matriz de emissões:
+    /// a memória extra é a tabela de backpointers (`N × T` bytes) e três linhas de `T` scores.
+    pub fn best_path(&mut self, model: &CrfModel, feature_vectors: &[FeatureVector]) -> (Vec<Tag>, f64) {
+        if feature_vectors.is_empty() {
+            return (vec![], 0.0);
+        }
+        self.forward(
+            model,
+            feature_vectors.len(),
+            |i, row| model.emission_row_into(&feature_vectors[i], row),
+            |_, _, _, _, _| {},
+        )
+    }
+
+    /// Recursão e backtracking do Viterbi.
+    ///
+    /// `emission_row(i, row)` preenche as emissões do token `i`; `record(i, t, score,
+    /// best_prev, transition)` é chamado para cada célula da tabela, em ordem.
+    fn forward(
+        &mut self,
+        model: &CrfModel,
+        n_tokens: usize,
+        mut emission_row: impl FnMut(usize, &mut [f64]),
+        mut record: impl FnMut(usize, usize, f64, usize, f64),
+    ) -> (Vec<Tag>, f64) {
+        let tags = model.tags();
+        let n_tags = tags.len();
+        debug_assert!(n_tags <= usize::from(u8::MAX) + 1, "backpointers u8 suportam até 256 tags");
+
+        self.backptr.clear();
+        self.backptr.resize(n_tokens * n_tags, 0);
+        for row in [&mut self.current, &mut self.next, &mut self.emission] {
+            row.clear();
+            row.resize(n_tags, f64::NEG_INFINITY);
+        }
+
+        // === Inicialização (token 0) ===
+        // Sem transição para o primeiro token, só usamos o score de emissão
+        emission_row(0, &mut self.emission);
+        for t in 0..n_tags {
+            self.current[t] = self.emission[t];
+            self.backptr[t] = t as u8; // aponta para si mesmo
+            record(0, t, self.current[t], t, 0.0);
+        }
+
+        // === Recursão (tokens 1..N-1) ===
+        for i in 1..n_tokens {
+            emission_row(i, &mut self.emission);
+            for t in 0..n_tags {
+                // Encontra a melhor tag anterior para esta tag t
+                let mut best_prev_score = f64::NEG_INFINITY;
+                let mut best_prev_tag = 0;
+                let mut best_transition = 0.0;
+
+                for prev_t in 0..n_tags {
+                    let trans = model.transition_weights[prev_t][t];
+                    let score = self.current[prev_t] + trans;
+                    if score > best_prev_score {
+                        best_prev_score = score;
+                        best_prev_tag = prev_t;
+                        best_transition = trans;
+                    }
+                }
+
+                // Penaliza transições inválidas no esquema BIO
+                self.next[t] = if !Tag::is_valid_transition(&tags[best_prev_tag], &tags[t]) {
+                    // Pequena penalidade para manter o esquema BIO
+                    best_prev_score + self.emission[t] - 10.0
+                } else {
+                    best_prev_score + self.emission[t]
+                };
+
+                self.backptr[i * n_tags + t] = best_prev_tag as u8;
+                record(i, t, self.next[t], best_prev_tag, best_transition);
+            }
+            std::mem::swap(&mut self.current, &mut self.next);
+        }
+
+        // === Backtracking ===
+        // O Viterbi constrói o caminho de trás para frente.
+        // Começamos na última posição com a tag que tem o maior score total.
+        let (mut best_last_tag_index, best_total_score) = best_in_slice(&self.current);
+
+        // Inicializa o vetor de resultado
+        let mut best_sequence: Vec<Tag> = vec![Tag::Outside; n_tokens];
+        best_sequence[n_tokens - 1] = tags[best_last_tag_index].clone();
+
+        // Reconstrói o caminho seguindo os ponteiros `backptr`
+        for i in (0..n_tokens - 1).rev() {
+            let prev_tag_index = usize::from(self.backptr[(i + 1) * n_tags + best_last_tag_index]);
+            best_sequence[i] = tags[prev_tag_index].clone();
+            best_last_tag_index = prev_tag_index;
+        }
+
+        (best_sequence, best_total_score)
     }
 }
 
@@ -280,6 +685,99 @@ mod tests {
         assert_eq!(result.best_sequence[0], Tag::Begin(EntityCategory::Per));
     }
 
+    #[test]
+    fn test_scratch_reuse_matches_full_decode() {
+        let mut model = CrfModel::new();
+        model.set_emission("is_capitalized", &Tag::Begin(EntityCategory::Per), 5.0);
+        model.set_emission("is_capitalized", &Tag::Outside, -3.0);
+        model.set_transition(&Tag::Begin(EntityCategory::Per), &Tag::Inside(EntityCategory::Per), 3.0);
+
+        let long: Vec<FeatureVector> = (0..50).map(|i| make_fv_with_capitalized(i, i % 3 != 2)).collect();
+        let short: Vec<FeatureVector> = (0..4).map(|i| make_fv_with_capitalized(i, i == 0)).collect();
+
+        let mut scratch = ViterbiScratch::new();
+        for fvs in [&long, &short, &long] {
+            let full = viterbi_decode(&model, fvs);
+            let (path, score) = scratch.best_path(&model, fvs);
+            assert_eq!(path, full.best_sequence);
+            assert!((score - full.best_score).abs() < 1e-9);
+        }
+        for decoder in [Decoder::Viterbi, Decoder::Beam { width: 3 }] {
+            let (path, _) = decoder.best_path(&model, &long, &mut scratch);
+            assert_eq!(path, decoder.decode(&model, &long).best_sequence, "{decoder:?}");
+        }
+        // A tabela cresce até o maior documento e é reaproveitada: 1 byte por token e tag
+        assert!(scratch.backpointer_bytes() >= 50 * Tag::all().len());
+        assert!(scratch.backpointer_bytes() < 50 * Tag::all().len() * std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn test_beam_width_trades_exactness() {
+        let mut model = CrfModel::new();
+        model.set_emission("is_capitalized", &Tag::Begin(EntityCategory::Per), 5.0);
+        model.set_emission("is_capitalized", &Tag::Outside, -3.0);
+        model.set_transition(&Tag::Begin(EntityCategory::Per), &Tag::Inside(EntityCategory::Per), 3.0);
+        let fvs: Vec<FeatureVector> = (0..12).map(|i| make_fv_with_capitalized(i, i % 4 != 3)).collect();
+
+        let exact = viterbi_decode(&model, &fvs);
+        let full_beam = beam_decode(&model, &fvs, Tag::all().len());
+        assert_eq!(full_beam.best_sequence, exact.best_sequence);
+        assert!((full_beam.best_score - exact.best_score).abs() < 1e-9);
+        assert_eq!(Decoder::Beam { width: 100 }.decode(&model, &fvs).best_sequence, exact.best_sequence);
+
+        for width in [0, 1, 2, 3] {
+            let beam = beam_decode(&model, &fvs, width);
+            assert_eq!(beam.best_sequence.len(), fvs.len());
+            assert!(beam.best_score <= exact.best_score + 1e-9, "largura {width}");
+            // Todas as tags continuam no passo, mesmo as podadas
+            assert!(beam.steps.iter().all(|step| step.scores.len() == Tag::all().len()));
+        }
+        assert!(beam_decode(&model, &[], 3).best_sequence.is_empty());
+        assert_eq!(serde_json::to_string(&Decoder::Beam { width: 4 }).unwrap(), r#"{"beam":{"width":4}}"#);
+    }
+
+    #[test]
+    fn test_marginals_match_brute_force() {
+        let mut model = CrfModel::new();
+        model.set_emission("is_capitalized", &Tag::Begin(EntityCategory::Per), 2.0);
+        model.set_transition(&Tag::Begin(EntityCategory::Per), &Tag::Inside(EntityCategory::Per), 1.5);
+        let fvs: Vec<FeatureVector> = (0..3).map(|i| make_fv_with_capitalized(i, i < 2)).collect();
+        let posteriors = marginals(&model, &fvs);
+
+        // Soma explícita sobre as T³ sequências
+        let tags = model.tags();
+        let n = tags.len();
+        let emission = compute_emission_scores(&model, &fvs);
+        let score = |path: [usize; 3]| {
+            (0..3).map(|i| emission[i][path[i]]).sum::<f64>()
+                + (1..3)
+                    .map(|i| {
+                        let penalty = if Tag::is_valid_transition(&tags[path[i - 1]], &tags[path[i]]) { 0.0 } else { 10.0 };
+                        model.transition_weights[path[i - 1]][path[i]] - penalty
+                    })
+                    .sum::<f64>()
+        };
+        let mut expected = vec![vec![0.0; n]; 3];
+        let mut z = 0.0;
+        for a in 0..n {
+            for b in 0..n {
+                for c in 0..n {
+                    let p = score([a, b, c]).exp();
+                    z += p;
+                    expected[0][a] += p;
+                    expected[1][b] += p;
+                    expected[2][c] += p;
+                }
+            }
+        }
+        for (row, expected) in posteriors.iter().zip(&expected) {
+            for (p, e) in row.iter().zip(expected) {
+                assert!((p - e / z).abs() < 1e-9);
+            }
+        }
+        assert!(marginals(&model, &[]).is_empty());
+    }
+
     #[test]
     fn test_viterbi_empty() {
         let model = CrfModel::new();
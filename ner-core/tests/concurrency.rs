@@ -0,0 +1,214 @@
+//! Testes de estresse do pipeline compartilhado entre threads.
+//!
+//! Cada teste roda um número fixo de threads e de iterações, sincronizadas por uma
+//! `Barrier`, e confere o resultado de toda análise contra uma expectativa calculada
+//! antes (sequencialmente). O entrelaçamento varia de execução para execução, mas as
+//! asserções não dependem dele: qualquer corrida, visão parcial de gazetteer ou troca de
+//! modelo no meio de uma análise aparece como divergência. Deadlocks viram falha por
+//! tempo esgotado em vez de travar o `cargo test`.
+
+#![cfg(feature = "rules")]
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+use ner_core::language::LanguagePack;
+use ner_core::model::NerModel;
+use ner_core::shared::SharedPipeline;
+use ner_core::tagger::{EntityCategory, EntitySpan};
+use ner_core::{AlgorithmMode, NerPipeline, TokenizerMode};
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 6;
+/// Tempo máximo de cada teste; muito acima do normal mesmo em build de debug.
+const DEADLINE: Duration = Duration::from_secs(240);
+
+const TEXTS: [&str; 4] = [
+    "O presidente Lula visitou a Petrobras em Brasília.",
+    "A Vale anunciou lucro recorde no Rio de Janeiro em março de 2023.",
+    "Dilma Rousseff e Fernando Haddad participaram do evento da USP.",
+    "O Flamengo venceu o Palmeiras no Maracanã às 16h.",
+];
+
+/// Entidades comparáveis entre execuções (confiança arredondada).
+type Summary = Vec<(usize, usize, EntityCategory, String, i64)>;
+
+fn summary(entities: &[EntitySpan]) -> Summary {
+    entities
+        .iter()
+        .map(|e| (e.start, e.end, e.category, e.text.clone(), (e.confidence * 1e6).round() as i64))
+        .collect()
+}
+
+/// Roda `f` em outra thread e falha se ela não terminar dentro de [`DEADLINE`].
+fn within_deadline(name: &str, f: impl FnOnce() + Send + 'static) {
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        f();
+        let _ = tx.send(());
+    });
+    match rx.recv_timeout(DEADLINE) {
+        Ok(()) => handle.join().unwrap(),
+        // A thread morreu sem avisar: repassa o pânico da asserção
+        Err(RecvTimeoutError::Disconnected) => std::panic::resume_unwind(handle.join().unwrap_err()),
+        Err(RecvTimeoutError::Timeout) => panic!("{name}: não terminou em {DEADLINE:?} (deadlock?)"),
+    }
+}
+
+#[test]
+fn pipeline_types_are_thread_safe() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<NerPipeline>();
+    assert_send_sync::<SharedPipeline>();
+    assert_send_sync::<NerModel>();
+    #[cfg(feature = "linking")]
+    assert_send_sync::<ner_core::nel::KnowledgeBase>();
+}
+
+#[test]
+fn concurrent_analyses_match_sequential_results() {
+    let pipeline = Arc::new(NerPipeline::new());
+    let modes = [AlgorithmMode::RulesOnly, AlgorithmMode::CrfOnly, AlgorithmMode::Hybrid];
+    let analyze = |pipeline: &NerPipeline, text: &str, mode| {
+        summary(&pipeline.analyze_with_mode(text, mode, TokenizerMode::Standard).1)
+    };
+
+    pipeline.reset_rule_stats();
+    let expected: Vec<Vec<Summary>> =
+        TEXTS.iter().map(|text| modes.iter().map(|&mode| analyze(&pipeline, text, mode)).collect()).collect();
+    let analyses_per_pass = pipeline.rule_stats().analyses;
+    pipeline.reset_rule_stats();
+
+    let shared = Arc::clone(&pipeline);
+    within_deadline("análises concorrentes", move || {
+        let barrier = Barrier::new(THREADS);
+        thread::scope(|scope| {
+            for worker in 0..THREADS {
+                let (pipeline, barrier, expected) = (&shared, &barrier, &expected);
+                scope.spawn(move || {
+                    barrier.wait();
+                    for round in 0..ROUNDS {
+                        // Cada thread percorre os textos em outra ordem
+                        for i in 0..TEXTS.len() {
+                            let t = (i + worker + round) % TEXTS.len();
+                            for (m, &mode) in modes.iter().enumerate() {
+                                assert_eq!(analyze(pipeline, TEXTS[t], mode), expected[t][m], "texto {t}, modo {mode:?}");
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    });
+
+    // Nenhuma atualização das estatísticas de regras se perdeu
+    assert_eq!(pipeline.rule_stats().analyses, analyses_per_pass * (THREADS * ROUNDS) as u64);
+}
+
+#[test]
+fn hot_gazetteer_updates_are_seen_atomically() {
+    // Nomes inventados: nenhum está nos gazetteers embutidos
+    const NAMES: [&str; 6] = ["Zentrolândia", "Quorvale", "Brimópolis", "Taxerina", "Vulmaré", "Ostrávia"];
+    let text = format!("A comitiva passou por {} e {}.", NAMES[..5].join(", "), NAMES[5]);
+    let shared = Arc::new(SharedPipeline::new(NerPipeline::new()));
+
+    let found = move |pipeline: &NerPipeline| -> Vec<String> {
+        let (_, entities) = pipeline.analyze_with_mode(&text, AlgorithmMode::RulesOnly, TokenizerMode::Standard);
+        entities
+            .iter()
+            .filter(|e| e.category == EntityCategory::Loc && NAMES.contains(&e.text.as_str()))
+            .map(|e| e.text.clone())
+            .collect()
+    };
+    assert!(found(&shared.read()).is_empty());
+
+    let (readers, reader_found) = (Arc::clone(&shared), found.clone());
+    within_deadline("gazetteers a quente", move || {
+        let barrier = Barrier::new(THREADS + 1);
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                let (shared, barrier, found) = (&readers, &barrier, &reader_found);
+                scope.spawn(move || {
+                    barrier.wait();
+                    let mut last_generation = 0;
+                    for _ in 0..ROUNDS * 4 {
+                        let pipeline = shared.read();
+                        let generation = shared.generation() as usize;
+                        // Na geração g, exatamente os g primeiros nomes já entraram
+                        let expected: Vec<String> = NAMES[..generation].iter().map(|n| n.to_string()).collect();
+                        assert_eq!(found(&pipeline), expected, "geração {generation}");
+                        assert!(generation >= last_generation, "a geração voltou atrás");
+                        last_generation = generation;
+                    }
+                });
+            }
+
+            let (shared, barrier) = (&readers, &barrier);
+            scope.spawn(move || {
+                barrier.wait();
+                for name in NAMES {
+                    shared.add_gazetteer_entry(EntityCategory::Loc, name);
+                    thread::yield_now();
+                }
+            });
+        });
+    });
+
+    assert_eq!(shared.generation(), NAMES.len() as u64);
+    assert_eq!(found(&shared.read()).len(), NAMES.len());
+}
+
+#[test]
+fn model_hot_swap_never_mixes_models() {
+    let text = "O primeiro-ministro Albuquerque reuniu-se com a Galp em Coimbra.";
+    let analyze = |pipeline: &NerPipeline| {
+        summary(&pipeline.analyze_with_mode(text, AlgorithmMode::Hybrid, TokenizerMode::Standard).1)
+    };
+    let brazilian = NerModel::build_for(&LanguagePack::pt_br());
+    let european = NerModel::build_for(&LanguagePack::pt_pt());
+    let expected_br = analyze(&NerPipeline::from_model(NerModel::build_for(&LanguagePack::pt_br())));
+    let expected_pt = analyze(&NerPipeline::from_model(NerModel::build_for(&LanguagePack::pt_pt())));
+    assert_ne!(expected_br, expected_pt, "o texto precisa distinguir os dois modelos");
+
+    let shared = Arc::new(SharedPipeline::new(NerPipeline::from_model(brazilian)));
+    let swaps = ROUNDS * 2;
+    let readers = Arc::clone(&shared);
+    within_deadline("troca de modelo", move || {
+        let barrier = Barrier::new(THREADS + 1);
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                let (shared, barrier, analyze) = (&readers, &barrier, &analyze);
+                let (expected_br, expected_pt) = (&expected_br, &expected_pt);
+                scope.spawn(move || {
+                    barrier.wait();
+                    for _ in 0..ROUNDS * 2 {
+                        let pipeline = shared.read();
+                        let expected = match pipeline.model.language.as_str() {
+                            "pt-BR" => expected_br,
+                            "pt-PT" => expected_pt,
+                            other => panic!("modelo inesperado: {other}"),
+                        };
+                        // Idioma e saída vêm do mesmo modelo, mesmo com trocas em volta
+                        assert_eq!(&analyze(&pipeline), expected, "modelo {}", pipeline.model.language);
+                    }
+                });
+            }
+
+            let (shared, barrier) = (&readers, &barrier);
+            scope.spawn(move || {
+                barrier.wait();
+                let mut spare = european;
+                for _ in 0..swaps {
+                    spare = shared.swap_model(spare);
+                    thread::yield_now();
+                }
+            });
+        });
+    });
+
+    // Número par de trocas: o modelo brasileiro volta ao lugar
+    assert_eq!(shared.generation(), swaps as u64);
+    assert_eq!(shared.read().model.language, "pt-BR");
+}
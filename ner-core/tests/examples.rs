@@ -0,0 +1,100 @@
+//! Executa os programas de `examples/` como testes: cada exemplo expõe uma função
+//! `run` que o `main` usa, e aqui ela é chamada com entradas conhecidas. Assim a API
+//! mostrada nos exemplos continua compilando **e** funcionando.
+
+use std::path::PathBuf;
+
+use ner_core::NerPipeline;
+
+#[allow(dead_code)]
+#[path = "../examples/annotate_files.rs"]
+mod annotate_files;
+
+#[allow(dead_code)]
+#[path = "../examples/compare_modes.rs"]
+mod compare_modes;
+
+#[cfg(feature = "rules")]
+#[allow(dead_code)]
+#[path = "../examples/custom_rules.rs"]
+mod custom_rules;
+
+#[cfg(feature = "statistical")]
+#[allow(dead_code)]
+#[path = "../examples/train_perceptron.rs"]
+mod train_perceptron;
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ner_example_{}_{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn example_annotate_files_writes_one_json_line_per_file() {
+    let files = vec![
+        temp_file("a.txt", "A presidente Dilma Rousseff visitou o Brasil."),
+        temp_file("b.txt", "A Petrobras anunciou lucro."),
+    ];
+    let mut out = Vec::new();
+    let total = annotate_files::run(&NerPipeline::new(), &files, &mut out).unwrap();
+
+    let lines: Vec<serde_json::Value> =
+        String::from_utf8(out).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["file"], files[1].display().to_string());
+    let counted: usize = lines.iter().map(|l| l["entities"].as_array().unwrap().len()).sum();
+    assert_eq!(counted, total);
+    assert!(total > 0);
+    for file in files {
+        std::fs::remove_file(file).unwrap();
+    }
+}
+
+#[test]
+#[cfg(feature = "statistical")]
+fn example_train_perceptron_learns_from_conll_file() {
+    let corpus = "Ana B-PER\nvisitou O\nRecife B-LOC\n\nBruno B-PER\nvisitou O\nNatal B-LOC\n\n\
+                  Ana B-PER\nconheceu O\nNatal B-LOC\n";
+    let path = temp_file("train.conll", corpus);
+    let tagged = train_perceptron::run(&path, "Bruno visitou Recife").unwrap();
+    let tags: Vec<&str> = tagged.iter().map(|(_, tag)| tag.as_str()).collect();
+    assert_eq!(tags, vec!["B-PER", "O", "B-LOC"]);
+
+    // Corpus malformado: o erro aparece antes do treino
+    let broken = temp_file("broken.conll", "Ana B-PER\nsolto\n");
+    assert!(train_perceptron::run(&broken, "Ana").is_err());
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(broken).unwrap();
+}
+
+#[test]
+#[cfg(feature = "rules")]
+fn example_custom_rules_extend_the_rule_engine() {
+    use ner_core::tagger::EntityCategory;
+
+    let pipeline = custom_rules::build_pipeline().unwrap();
+    let entities = custom_rules::run(&pipeline, "A Zubrex contratou a analista Joana Prado por R$ 9.500,00 para o Pix Automático.");
+    let found: Vec<(&str, EntityCategory)> = entities.iter().map(|e| (e.text.as_str(), e.category)).collect();
+    assert!(found.contains(&("Zubrex", EntityCategory::Org)), "{found:?}");
+    assert!(found.contains(&("Joana Prado", EntityCategory::Per)), "{found:?}");
+    assert!(found.contains(&("Pix Automático", EntityCategory::Misc)), "{found:?}");
+    assert!(found.contains(&("R$ 9.500,00", EntityCategory::Misc)), "{found:?}");
+}
+
+#[test]
+fn example_compare_modes_scores_each_mode() {
+    let data = compare_modes::builtin_gold();
+    assert!(!data.is_empty());
+    let modes = [ner_core::AlgorithmMode::RulesOnly, ner_core::AlgorithmMode::Hybrid];
+    let scores = compare_modes::run(&NerPipeline::new(), &data, &modes);
+    assert_eq!(scores.len(), 2);
+    for score in &scores {
+        assert!(score.gold > 0);
+        assert!(score.correct <= score.predicted.min(score.gold));
+        assert!((0.0..=1.0).contains(&score.f1()));
+    }
+    // Sem a feature `rules`, RulesOnly cai para FeaturesOnly, que não produz entidades
+    #[cfg(feature = "rules")]
+    assert!(scores[0].f1() > 0.0);
+}
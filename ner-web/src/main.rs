@@ -1,42 +1,76 @@
 //! Servidor web Axum com HTMX e WebSocket para visualização do NER em tempo real
 
+mod tenants;
+mod ws_protocol;
+
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Json, Response,
     },
-    http::StatusCode,
-    response::{Html, IntoResponse, Json},
     routing::{get, post},
     Router,
 };
 use askama::Template;
+use futures_util::stream;
 use ner_core::{
-    corpus::demo_texts,
-    pipeline::{AlgorithmMode, NerPipeline, PipelineEvent},
-    tokenizer::TokenizerMode,
+    coref::{resolve_pronouns, CorefConfig, PronounMention},
+    pipeline::{AlgorithmMode, AnalysisOptions, AnalysisReport, AnalysisWarning, NerPipeline, PipelineEvent, SentenceConfidence},
+    diff::{count_kinds, DiffKind, GoldEntity, SpanDiff},
+    export::{to_brat, to_spacy},
+    language::LanguagePack,
+    model::NerModel,
+    nel::{KnowledgeBase, LinkCache},
+    label_map::LabelMapping,
+    render::{to_conll_mapped, to_html},
+    rule_based::{RuleGroup, RuleGroups},
+    samples::SampleRegistry,
+    self_check::SelfCheckConfig,
+    shared::SharedPipeline,
+    tagger::EntityCategory,
+    temporal::Expression,
+    tokenizer::{BpeModel, Token, TokenizerMode},
+    viterbi::ViterbiDetail,
 };
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tenants::{Tenant, TenantRegistry};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
-use tracing::info;
+use tracing::{info, warn};
+use ws_protocol::{select_rule_groups, WsMessage, WsRequest, WsSession};
 
 /// Estado compartilhado da aplicação
 ///
 /// O Axum exige que o estado seja `Clone` e `Send` + `Sync` para ser compartilhado entre threads.
 /// Envolvemos o `pipeline` em um `Arc` (Atomic Reference Counting) implicitamente ao colocar
-/// no `AppState` que será envolto em `Arc` na main.
+/// no `AppState` que será envolto em `Arc` na main. Todos os campos já são `Send + Sync`
+/// (o `NerPipeline` inclusive, ver o teste `test_pipeline_is_send_and_sync` do `ner-core`),
+/// sem `unsafe impl`.
 ///
-/// O `NerPipeline` é imutável após a criação (só leitura do modelo), então é thread-safe.
+/// O `NerPipeline` fica num `SharedPipeline`: as análises leem em paralelo e
+/// `POST /gazetteer` acrescenta entradas aos gazetteers sem reiniciar o servidor.
+/// Segure a leitura (`pipeline.read()`) só enquanto analisa, sem atravessar um `.await`.
 struct AppState {
-    pipeline: NerPipeline,
+    pipeline: SharedPipeline,
+    /// Chave exigida (`X-Admin-Key`) para alterar os gazetteers; sem ela, `POST /gazetteer` fica desligado.
+    admin_key: Option<String>,
+    /// Conjuntos de amostras exibidos como textos de demonstração.
+    samples: SampleRegistry,
+    /// Base de conhecimento do NEL, com cache de linking em memória.
+    kb: KnowledgeBase,
+    /// Dicionários e filtros por cliente, aplicados sobre o `pipeline` compartilhado.
+    tenants: TenantRegistry,
 }
 
-// NerPipeline somente usa &self → é seguro compartilhar entre threads
-unsafe impl Send for AppState {}
-unsafe impl Sync for AppState {}
-
 #[derive(Deserialize)]
 struct AnalyzeRequest {
     text: String,
@@ -44,6 +78,75 @@ struct AnalyzeRequest {
     mode: Option<AlgorithmMode>,
     #[serde(default)]
     tokenizer_mode: Option<TokenizerMode>,
+    /// Limiar de abstenção por sentença (ver `AnalysisOptions::abstain_below`).
+    #[serde(default)]
+    abstain_below: Option<f64>,
+    /// Limite de tokens analisados (ver `AnalysisOptions::max_tokens`).
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    /// Grupos de regras ativos (ver `AnalysisOptions::rule_groups`); ausente, todos.
+    #[serde(default)]
+    rule_groups: Option<RuleGroups>,
+    /// Grupos desligados nesta análise (ex: `["misc_gazetteer"]`).
+    #[serde(default)]
+    disable_rules: Vec<RuleGroup>,
+    /// Liga "ele/ela/dele/dela" à pessoa mencionada mais recentemente (ver `ner_core::coref`).
+    #[serde(default)]
+    resolve_pronouns: bool,
+}
+
+/// Formato da resposta de `/analyze`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    Json,
+    /// `token<TAB>tag` por linha (ver `ner_core::render::to_conll`).
+    Conll,
+    /// Texto com as entidades em `<mark>` (ver `ner_core::render::to_html`).
+    Html,
+    /// JSON do spaCy, com `ents` e tokens (ver `ner_core::export::to_spacy`).
+    Spacy,
+    /// Arquivo `.ann` do BRAT (ver `ner_core::export::to_brat`).
+    Brat,
+}
+
+impl ResponseFormat {
+    /// Nome aceito no parâmetro `?format=`.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "conll" => Some(Self::Conll),
+            "html" => Some(Self::Html),
+            "spacy" => Some(Self::Spacy),
+            "brat" => Some(Self::Brat),
+            _ => None,
+        }
+    }
+
+    /// Primeiro tipo reconhecido no cabeçalho `Accept`; JSON se nenhum for.
+    fn from_accept(headers: &HeaderMap) -> Self {
+        let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or("");
+        accept
+            .split(',')
+            .filter_map(|media| match media.split(';').next().unwrap_or("").trim() {
+                "application/json" => Some(Self::Json),
+                "text/conll" | "text/x-conll" | "text/plain" => Some(Self::Conll),
+                "text/html" => Some(Self::Html),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(Self::Json)
+    }
+}
+
+/// Parâmetros de query de `/analyze`.
+#[derive(Deserialize)]
+struct AnalyzeQuery {
+    /// `json`, `conll`, `html`, `spacy` ou `brat`; tem precedência sobre o cabeçalho `Accept`.
+    #[serde(default)]
+    format: Option<String>,
+    /// Padrão das tags no CoNLL: `ontonotes` ou `conll2003` (ver `ner_core::label_map`).
+    #[serde(default)]
+    labels: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -59,22 +162,38 @@ struct SotaRequest {
     classes: String, // vírgula separadas
 }
 
-/// Mensagem WebSocket recebida do cliente
+/// Texto com anotações ouro, para comparar com a previsão de um modo.
 #[derive(Deserialize)]
-struct WsRequest {
+struct DiffRequest {
     text: String,
+    gold: Vec<GoldEntity>,
     #[serde(default)]
     mode: Option<AlgorithmMode>,
     #[serde(default)]
     tokenizer_mode: Option<TokenizerMode>,
 }
 
+#[derive(Serialize)]
+struct DiffResponse {
+    records: Vec<SpanDiff>,
+    counts: Vec<(DiffKind, usize)>,
+}
+
 #[derive(Serialize)]
 struct AnalyzeResponse {
     entities: Vec<ner_core::tagger::EntitySpan>,
     tagged_tokens: Vec<ner_core::tagger::TaggedToken>,
     processing_ms: u64,
     total_tokens: usize,
+    sentences: Vec<SentenceConfidence>,
+    needs_review: bool,
+    /// Avisos estruturados (entidade longa, conflito de gazetteer, entrada cortada...).
+    warnings: Vec<AnalysisWarning>,
+    /// Pronomes resolvidos; `refers_to` é o índice em `entities`. Só com `resolve_pronouns`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pronouns: Vec<PronounMention>,
+    /// Datas, horários, valores e percentuais (ver `ner_core::temporal`).
+    expressions: Vec<Expression>,
 }
 
 #[tokio::main]
@@ -83,9 +202,53 @@ async fn main() {
         .with_env_filter("info")
         .init();
 
-    let pipeline = NerPipeline::new();
-    let state = Arc::new(AppState { pipeline });
+    // NER_SELF_CHECK: constrói o modelo com autoavaliação (notas em /capabilities)
+    let pipeline = if std::env::var_os("NER_SELF_CHECK").is_some() {
+        let model = NerModel::build_with_self_check(&LanguagePack::pt_br(), &SelfCheckConfig::default());
+        if let Some(report) = &model.self_check {
+            info!("Autoavaliação em {} sentença(s) reservada(s)", report.holdout_sentences);
+            for score in &report.scores {
+                info!("  {:?}: P={:.3} R={:.3} F1={:.3}", score.mode, score.precision, score.recall, score.f1);
+            }
+        }
+        NerPipeline::from_model(model)
+    } else {
+        NerPipeline::new()
+    };
+    // NER_BPE_MODEL: modelo BPE treinado (BpeModel::save) para o tokenizer_mode "bpe"
+    let pipeline = match std::env::var_os("NER_BPE_MODEL") {
+        Some(path) => match BpeModel::load(&path) {
+            Ok(bpe) => {
+                info!("Modelo BPE '{}' com {} merge(s)", bpe.profile, bpe.merges().len());
+                pipeline.with_bpe_model(bpe)
+            }
+            Err(e) => {
+                warn!("Modelo BPE em {:?} ignorado: {}", path, e);
+                pipeline
+            }
+        },
+        None => pipeline,
+    };
+    let kb = KnowledgeBase::new().with_cache(LinkCache::new(Some(Duration::from_secs(3600))));
+    let tenants = TenantRegistry::from_env().expect("arquivo de tenants (NER_TENANTS) inválido");
+    info!("{} tenant(s) configurado(s)", tenants.len());
+    // NER_ADMIN_KEY: habilita POST /gazetteer para quem enviar a mesma chave em X-Admin-Key
+    let admin_key = std::env::var("NER_ADMIN_KEY").ok().filter(|key| !key.is_empty());
+    let state = Arc::new(AppState {
+        pipeline: SharedPipeline::new(pipeline),
+        admin_key,
+        samples: SampleRegistry::builtin(),
+        kb,
+        tenants,
+    });
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    info!("🚀 Servidor NER iniciado em http://localhost:3000");
+    axum::serve(listener, app(state)).await.unwrap();
+}
 
+/// Rotas do servidor sobre o estado `state`.
+fn app(state: Arc<AppState>) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
@@ -97,11 +260,17 @@ async fn main() {
         .expect("workspace root")
         .join("docs");
 
-    let app = Router::new()
+    Router::new()
         .route("/", get(index_handler))
         .route("/analyze", post(analyze_handler))
+        .route("/analyze/batch", post(analyze_batch_handler))
+        .route("/analyze/stream", get(analyze_stream_get_handler).post(analyze_stream_post_handler))
+        .route("/diff", post(diff_handler))
+        .route("/gazetteer", post(gazetteer_handler))
         .route("/ws", get(ws_handler))
         .route("/demo-texts", get(demo_texts_handler))
+        .route("/rule-stats", get(rule_stats_handler))
+        .route("/capabilities", get(capabilities_handler))
         .route("/tokenizer", get(tokenizer_page_handler))
         .route("/ned", get(ned_page_handler))
         .route("/nel", get(nel_page_handler))
@@ -111,13 +280,10 @@ async fn main() {
         .route("/htmx/ned", post(htmx_ned_handler))
         .route("/htmx/nel", post(htmx_nel_handler))
         .route("/htmx/sota", post(htmx_sota_handler))
+        .route("/htmx/rule-stats", get(htmx_rule_stats_handler))
         .nest_service("/docs", ServeDir::new(docs_dir))
         .layer(cors)
-        .with_state(state);
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    info!("🚀 Servidor NER iniciado em http://localhost:3000");
-    axum::serve(listener, app).await.unwrap();
+        .with_state(state)
 }
 
 #[derive(Template)]
@@ -199,11 +365,13 @@ async fn htmx_ned_handler(
     let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
     
     // 1. Roda a pipeline normal para extrair entidades e tokens
-    let (tagged_tokens, entities) = state.pipeline.analyze_with_mode(&req.text, mode, tokenizer_mode);
+    let pipeline = state.pipeline.read();
+    let (tagged_tokens, entities) = pipeline.analyze_with_mode(&req.text, mode, tokenizer_mode);
     let tokens: Vec<_> = tagged_tokens.into_iter().map(|t| t.token).collect();
     
     // 2. Roda a desambiguação com base no contexto
-    let results = ner_core::ned::disambiguate(&tokens, &entities);
+    let results = ner_core::ned::disambiguate_with_aliases(&tokens, &entities, &pipeline.model.aliases);
+    drop(pipeline);
     
     Html(NedResultsTemplate { results }.render().unwrap())
 }
@@ -222,15 +390,16 @@ async fn htmx_nel_handler(
     let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
     
     // 1. NER
-    let (tagged_tokens, entities) = state.pipeline.analyze_with_mode(&req.text, mode, tokenizer_mode);
+    let pipeline = state.pipeline.read();
+    let (tagged_tokens, entities) = pipeline.analyze_with_mode(&req.text, mode, tokenizer_mode);
     let tokens: Vec<_> = tagged_tokens.into_iter().map(|t| t.token).collect();
     
     // 2. Desambiguação (NED)
-    let disambiguated = ner_core::ned::disambiguate(&tokens, &entities);
+    let disambiguated = ner_core::ned::disambiguate_with_aliases(&tokens, &entities, &pipeline.model.aliases);
+    drop(pipeline);
     
-    // 3. Entity Linking em KB mokada
-    let kb = ner_core::nel::KnowledgeBase::new();
-    let results = kb.link(&disambiguated);
+    // 3. Entity Linking em KB mokada (com cache compartilhado entre requisições)
+    let results = state.kb.link(&disambiguated);
     
     Html(NelResultsTemplate { results }.render().unwrap())
 }
@@ -261,127 +430,551 @@ async fn htmx_sota_handler(
 }
 
 /// Análise NER via HTTP POST (sem streaming)
+///
+/// Com o cabeçalho `X-Api-Key`, o dicionário e o filtro do cliente são aplicados às entidades.
+///
+/// A resposta é JSON por padrão; `?format=conll|html` ou o cabeçalho `Accept`
+/// (`text/plain`, `text/conll`, `text/html`) pedem CoNLL ou o texto destacado em HTML.
+/// `?format=spacy` e `?format=brat` exportam para o spaCy (JSON com `ents`) e para o
+/// BRAT (o conteúdo do `.ann`):
+///
+/// ```text
+/// curl -s localhost:3000/analyze?format=conll -H 'Content-Type: application/json' -d '{"text": "Lula visitou Recife."}'
+/// ```
+///
+/// Com `?labels=ontonotes` (ou `conll2003`), as tags do CoNLL saem nesse padrão (`B-PERSON`, `B-GPE`).
 async fn analyze_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<AnalyzeQuery>,
     Json(req): Json<AnalyzeRequest>,
 ) -> impl IntoResponse {
-    if req.text.trim().is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Texto vazio"})),
-        )
-            .into_response();
+    let format = match query.format.as_deref() {
+        Some(name) => match ResponseFormat::from_name(name) {
+            Some(format) => format,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Formato desconhecido (use json, conll, html, spacy ou brat)"})),
+                )
+                    .into_response();
+            }
+        },
+        None => ResponseFormat::from_accept(&headers),
+    };
+    let labels = match query.labels.as_deref() {
+        Some(name) => match LabelMapping::preset(name) {
+            Some(mapping) => mapping,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Padrão de rótulos desconhecido (use ontonotes ou conll2003)"})),
+                )
+                    .into_response();
+            }
+        },
+        None => LabelMapping::new(),
+    };
+    let tenant = match state.tenants.resolve(&headers) {
+        Ok(tenant) => tenant,
+        Err(status) => {
+            return (status, Json(serde_json::json!({"error": "Chave de API desconhecida"}))).into_response();
+        }
+    };
+    let analysis = match run_analysis(&state.pipeline.read(), tenant.as_deref(), &req) {
+        Ok(analysis) => analysis,
+        Err(err) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": err}))).into_response(),
+    };
+    let report = &analysis.report;
+    match format {
+        ResponseFormat::Conll => {
+            let conll = to_conll_mapped(&report.tagged_tokens, &labels);
+            ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], conll).into_response()
+        }
+        ResponseFormat::Html => Html(to_html(&req.text, &report.entities)).into_response(),
+        ResponseFormat::Spacy => Json(to_spacy(&req.text, &report.entities).with_tokens(&analysis.tokens)).into_response(),
+        ResponseFormat::Brat => {
+            let ann = to_brat(&req.text, &report.entities);
+            ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], ann).into_response()
+        }
+        ResponseFormat::Json => Json(AnalyzeResponse::new(analysis, &req)).into_response(),
     }
+}
+
+/// Uma requisição de `/analyze` já processada, antes de escolher o formato da resposta.
+struct Analysis {
+    /// Relatório do pipeline, com as entidades já filtradas pelo tenant.
+    report: AnalysisReport,
+    tokens: Vec<Token>,
+    needs_review: bool,
+}
 
+/// Roda uma requisição de `/analyze`: opções, pipeline e dicionário do cliente.
+/// O erro (texto vazio, opções inválidas) vai na resposta 400.
+fn run_analysis(pipeline: &NerPipeline, tenant: Option<&Tenant>, req: &AnalyzeRequest) -> Result<Analysis, String> {
+    if req.text.trim().is_empty() {
+        return Err("Texto vazio".to_string());
+    }
     let mode = req.mode.unwrap_or_default();
     let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
-    let (tagged, entities) = state.pipeline.analyze_with_mode(&req.text, mode, tokenizer_mode);
-    let total_tokens = tagged.len();
-
-    Json(AnalyzeResponse {
-        processing_ms: 0,
-        entities,
-        tagged_tokens: tagged,
-        total_tokens,
+    let options = AnalysisOptions {
+        abstain_below: req.abstain_below,
+        max_tokens: req.max_tokens,
+        rule_groups: select_rule_groups(req.rule_groups, &req.disable_rules),
+        ..Default::default()
+    };
+    options.validate().map_err(|err| err.to_string())?;
+    let mut report = pipeline.analyze_report(&req.text, mode, tokenizer_mode, options);
+    let needs_review = report.needs_review();
+    let tokens: Vec<_> = report.tagged_tokens.iter().map(|t| t.token.clone()).collect();
+    if let Some(tenant) = tenant {
+        report.entities = tenant.apply(&req.text, &tokens, std::mem::take(&mut report.entities));
+    }
+    Ok(Analysis { report, tokens, needs_review })
+}
+
+impl AnalyzeResponse {
+    /// Resposta JSON de `/analyze`; resolve os pronomes se `req.resolve_pronouns`.
+    fn new(analysis: Analysis, req: &AnalyzeRequest) -> Self {
+        let Analysis { report, tokens, needs_review } = analysis;
+        let pronouns = if req.resolve_pronouns {
+            resolve_pronouns(&tokens, &report.entities, &CorefConfig::default())
+        } else {
+            Vec::new()
+        };
+        AnalyzeResponse {
+            processing_ms: 0,
+            total_tokens: report.tagged_tokens.len(),
+            entities: report.entities,
+            tagged_tokens: report.tagged_tokens,
+            sentences: report.sentences,
+            needs_review,
+            warnings: report.warnings,
+            pronouns,
+            expressions: report.expressions,
+        }
+    }
+}
+
+/// Máximo de itens por requisição de `/analyze/batch`.
+const MAX_BATCH_ITEMS: usize = 256;
+
+/// Lote de `/analyze/batch`: cada item tem os mesmos campos de `/analyze`.
+#[derive(Deserialize)]
+struct BatchRequest {
+    items: Vec<AnalyzeRequest>,
+    /// Modo dos itens que não escolhem um.
+    #[serde(default)]
+    mode: Option<AlgorithmMode>,
+    /// Tokenizador dos itens que não escolhem um.
+    #[serde(default)]
+    tokenizer_mode: Option<TokenizerMode>,
+}
+
+/// Análise de vários textos numa requisição só. Responde um array com um
+/// `AnalyzeResponse` por item, na ordem dos itens:
+///
+/// ```text
+/// curl -s localhost:3000/analyze/batch -H 'Content-Type: application/json' \
+///   -d '{"mode": "rules_only", "items": [{"text": "Lula visitou Recife."}, {"text": "A Vale lucrou.", "mode": "crf_only"}]}'
+/// ```
+///
+/// Os itens rodam em paralelo (`rayon`) numa thread de `spawn_blocking`, fora do loop do
+/// Tokio. Um item inválido (ex: texto vazio) recusa o lote inteiro com 400, indicando o
+/// índice. Aceita `X-Api-Key` como `/analyze`; a resposta é sempre JSON.
+async fn analyze_batch_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(batch): Json<BatchRequest>,
+) -> impl IntoResponse {
+    let tenant = match state.tenants.resolve(&headers) {
+        Ok(tenant) => tenant,
+        Err(status) => {
+            return (status, Json(serde_json::json!({"error": "Chave de API desconhecida"}))).into_response();
+        }
+    };
+    if batch.items.is_empty() || batch.items.len() > MAX_BATCH_ITEMS {
+        let error = format!("O lote deve ter de 1 a {MAX_BATCH_ITEMS} itens");
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": error}))).into_response();
+    }
+    let mut items = batch.items;
+    for item in &mut items {
+        item.mode = item.mode.or(batch.mode);
+        item.tokenizer_mode = item.tokenizer_mode.or(batch.tokenizer_mode);
+    }
+
+    info!("Analisando lote de {} texto(s)", items.len());
+    let worker_state = Arc::clone(&state);
+    let results = tokio::task::spawn_blocking(move || {
+        let guard = worker_state.pipeline.read();
+        let pipeline: &NerPipeline = &guard;
+        items
+            .par_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                run_analysis(pipeline, tenant.as_deref(), item)
+                    .map(|analysis| AnalyzeResponse::new(analysis, item))
+                    .map_err(|err| format!("item {i}: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
     })
-    .into_response()
+    .await;
+
+    match results {
+        Ok(Ok(responses)) => Json(responses).into_response(),
+        Ok(Err(err)) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": err}))).into_response(),
+        Err(err) => {
+            warn!("Análise em lote falhou: {}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Falha na análise do lote"}))).into_response()
+        }
+    }
 }
 
-/// Retorna textos de demonstração
-async fn demo_texts_handler() -> impl IntoResponse {
-    let texts: Vec<serde_json::Value> = demo_texts()
+/// Diff ouro × previsto para a página de avaliação (offsets de byte, como em `/analyze`)
+async fn diff_handler(State(state): State<Arc<AppState>>, Json(req): Json<DiffRequest>) -> impl IntoResponse {
+    let mode = req.mode.unwrap_or_default();
+    let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
+    let records = state.pipeline.read().diff_against_gold(&req.text, &req.gold, mode, tokenizer_mode);
+    let counts = count_kinds(&records);
+    Json(DiffResponse { records, counts })
+}
+
+/// Entradas novas para os gazetteers do modelo compartilhado.
+#[derive(Deserialize)]
+struct GazetteerRequest {
+    /// Categoria como no JSON das entidades (`"Per"`, `"Loc"`, `"Org"`, `"Misc"` ou uma personalizada).
+    category: EntityCategory,
+    names: Vec<String>,
+}
+
+/// Acrescenta nomes aos gazetteers (regras e features) sem reiniciar o servidor; as
+/// análises seguintes já os reconhecem. Exige `X-Admin-Key` igual a `NER_ADMIN_KEY`:
+///
+/// ```text
+/// curl -s localhost:3000/gazetteer -H 'X-Admin-Key: segredo' -H 'Content-Type: application/json' \
+///   -d '{"category": "Per", "names": ["Fulano Beltrano"]}'
+/// ```
+async fn gazetteer_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<GazetteerRequest>,
+) -> impl IntoResponse {
+    let Some(admin_key) = &state.admin_key else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "Atualização de gazetteers desligada (defina NER_ADMIN_KEY)"})),
+        );
+    };
+    // Comparação em tempo constante: o tempo de resposta não revela o prefixo correto da chave
+    let authorized = headers
+        .get("x-admin-key")
+        .is_some_and(|v| bool::from(v.as_bytes().ct_eq(admin_key.as_bytes())));
+    if !authorized {
+        return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": "X-Admin-Key inválida"})));
+    }
+    let added = state.pipeline.add_gazetteer_entries(req.category, req.names.iter().map(String::as_str));
+    info!("{} entrada(s) de {} adicionada(s) aos gazetteers", added, req.category.name());
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({"added": added, "generation": state.pipeline.generation()})),
+    )
+}
+
+/// Estatísticas acumuladas de disparo das regras (JSON)
+async fn rule_stats_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.pipeline.read().rule_stats())
+}
+
+/// Modos disponíveis, features compiladas e categorias (JSON)
+async fn capabilities_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.pipeline.read().capabilities())
+}
+
+struct RuleStatsRow {
+    name: String,
+    fired: u64,
+    overridden: u64,
+    disagreed: u64,
+    override_rate: f64,
+    disagree_rate: f64,
+}
+
+#[derive(Template)]
+#[template(path = "components/rule_stats.html")]
+struct RuleStatsTemplate {
+    analyses: u64,
+    rows: Vec<RuleStatsRow>,
+}
+
+/// Painel HTMX com as estatísticas de regras, das que mais disparam para as que menos disparam
+async fn htmx_rule_stats_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let stats = state.pipeline.read().rule_stats();
+    let mut rows: Vec<RuleStatsRow> = stats
+        .rules
         .iter()
-        .map(|(domain, text)| {
+        .map(|(name, c)| RuleStatsRow {
+            name: name.clone(),
+            fired: c.fired,
+            overridden: c.overridden,
+            disagreed: c.disagreed,
+            override_rate: stats.override_rate(name),
+            disagree_rate: if c.fired > 0 { c.disagreed as f64 / c.fired as f64 } else { 0.0 },
+        })
+        .collect();
+    rows.sort_by_key(|r| std::cmp::Reverse(r.fired));
+
+    Html(RuleStatsTemplate { analyses: stats.analyses, rows }.render().unwrap())
+}
+
+/// Retorna textos de demonstração
+async fn demo_texts_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let texts: Vec<serde_json::Value> = state
+        .samples
+        .samples()
+        .map(|(set, sample)| {
             serde_json::json!({
-                "domain": domain,
-                "text": text
+                "set": set,
+                "domain": sample.title,
+                "text": sample.text,
+                "expected": sample.expected
             })
         })
         .collect();
     Json(texts)
 }
 
+/// Parâmetros de query de `GET /analyze/stream`: os campos de valor simples de
+/// [`WsRequest`]. Listas e objetos (`feature_events`, `rule_groups`, `disable_rules`) não
+/// cabem numa query string e ficam para o `POST`.
+#[derive(Deserialize)]
+struct StreamQuery {
+    text: String,
+    #[serde(default)]
+    mode: Option<AlgorithmMode>,
+    #[serde(default)]
+    tokenizer_mode: Option<TokenizerMode>,
+    #[serde(default)]
+    viterbi_detail: Option<ViterbiDetail>,
+    #[serde(default)]
+    explain: bool,
+    #[serde(default)]
+    abstain_below: Option<f64>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+}
+
+impl From<StreamQuery> for WsRequest {
+    fn from(query: StreamQuery) -> Self {
+        WsRequest {
+            text: query.text,
+            mode: query.mode,
+            tokenizer_mode: query.tokenizer_mode,
+            viterbi_detail: query.viterbi_detail,
+            feature_events: None,
+            explain: query.explain,
+            abstain_below: query.abstain_below,
+            max_tokens: query.max_tokens,
+            rule_groups: None,
+            disable_rules: Vec::new(),
+        }
+    }
+}
+
+/// Análise com os eventos do pipeline em Server-Sent Events, enviados à medida que são
+/// produzidos. Alternativa ao `/ws` para clientes simples (curl, `EventSource`): cada
+/// `PipelineEvent` vira um evento SSE com o nome da variante (`event: TokenizationDone`,
+/// ..., `event: Done`) e, como dado, o mesmo JSON que o `/ws` envia. O stream termina
+/// depois do `Done` (ou de um `Error`, se o pipeline falhar).
+///
+/// `GET` lê o pedido da query ([`StreamQuery`]: só as opções de valor simples), para o
+/// `EventSource` do navegador; `POST` aceita o JSON completo de uma mensagem do `/ws`
+/// (inclusive `feature_events`, `rule_groups` e `disable_rules`). Com `X-Api-Key`, o
+/// dicionário do cliente é aplicado ao `Done`:
+///
+/// ```text
+/// curl -N 'localhost:3000/analyze/stream?text=Lula+visitou+Recife.&viterbi_detail=summary'
+/// curl -N localhost:3000/analyze/stream -H 'Content-Type: application/json' -d '{"text": "Lula visitou Recife."}'
+/// ```
+async fn analyze_stream_get_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> Response {
+    stream_analysis(state, &headers, query.into())
+}
+
+/// `POST` de `/analyze/stream` (ver [`analyze_stream_get_handler`]).
+async fn analyze_stream_post_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<WsRequest>,
+) -> Response {
+    stream_analysis(state, &headers, req)
+}
+
+fn stream_analysis(state: Arc<AppState>, headers: &HeaderMap, req: WsRequest) -> Response {
+    let tenant = match state.tenants.resolve(headers) {
+        Ok(tenant) => tenant,
+        Err(status) => {
+            return (status, Json(serde_json::json!({"error": "Chave de API desconhecida"}))).into_response();
+        }
+    };
+    if req.text.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Texto vazio"}))).into_response();
+    }
+    let options = req.options();
+    if let Err(err) = options.validate() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": err.to_string()}))).into_response();
+    }
+    let mode = req.mode.unwrap_or_default();
+    let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
+    let text = req.text;
+    info!("Analisando via SSE [{:?} | {:?}]: {} chars", mode, tokenizer_mode, text.len());
+
+    // Como no `/ws`: o pipeline roda em spawn_blocking e cada evento segue pelo canal
+    // assim que é produzido, sem esperar a análise terminar
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<PipelineEvent>();
+    let panic_tx = tx.clone();
+    let text_for_thread = text.clone();
+    let handle = tokio::task::spawn_blocking(move || {
+        state.pipeline.read().analyze_with_callback(&text_for_thread, mode, tokenizer_mode, options, |event| {
+            // Erro = cliente já desconectou; a análise termina sem ninguém ouvindo
+            let _ = tx.send(event);
+        });
+    });
+    // Se a análise panicar, o `Done` nunca vem: o cliente recebe um `Error` no lugar
+    tokio::spawn(async move {
+        if handle.await.is_err() {
+            let _ = panic_tx.send(PipelineEvent::Error { message: "Erro interno no pipeline".to_string() });
+        }
+    });
+
+    let events = stream::unfold((rx, text, tenant), |(mut rx, text, tenant)| async move {
+        let mut event = rx.recv().await?;
+        apply_tenant(tenant.as_deref(), &text, &mut event);
+        Some((sse_event(&event), (rx, text, tenant)))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Um `PipelineEvent` como evento SSE, com o nome da variante.
+fn sse_event(event: &PipelineEvent) -> Result<Event, serde_json::Error> {
+    let json = serde_json::to_value(event)?;
+    let name = json["type"].as_str().unwrap_or("message").to_string();
+    Ok(Event::default().event(name).data(json.to_string()))
+}
+
+/// Aplica o dicionário e o filtro do cliente às entidades de um evento `Done`.
+fn apply_tenant(tenant: Option<&Tenant>, text: &str, event: &mut PipelineEvent) {
+    if let (Some(tenant), PipelineEvent::Done { entities, tagged_tokens, .. }) = (tenant, event) {
+        let tokens: Vec<_> = tagged_tokens.iter().map(|t| t.token.clone()).collect();
+        *entities = tenant.apply(text, &tokens, std::mem::take(entities));
+    }
+}
+
 /// Upgrade HTTP → WebSocket
 ///
 /// Rota que inicia o handshake WebSocket. Se bem sucedido, transfere o controle
-/// para `handle_websocket`.
+/// para `handle_websocket`. O cliente (`X-Api-Key`) é resolvido uma vez, no handshake.
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(socket, state))
+    match state.tenants.resolve(&headers) {
+        Ok(tenant) => ws.on_upgrade(move |socket| handle_websocket(socket, state, tenant)),
+        Err(status) => status.into_response(),
+    }
 }
 
 /// Lógica do WebSocket: recebe texto, executa pipeline e envia eventos em tempo real.
 ///
 /// # Protocolo
-/// 1. Cliente envia JSON: `{"text": "...", "mode": "hybrid", "tokenizer_mode": "standard"}`
+/// 1. Cliente envia JSON: `{"text": "...", "mode": "hybrid", "tokenizer_mode": "standard", "viterbi_detail": "compact"}`
+///    (`viterbi_detail` é opcional; textos longos devem pedir `"compact"` ou `"summary"`;
+///    `"explain": true` anexa `score_breakdown` às entidades; `"abstain_below": 0.6` omite
+///    as entidades de sentenças menos confiantes, reportadas em `SentencesScored`;
+///    `"max_tokens": 5000` corta textos maiores, com um `Warning` de código `truncated_input`;
+///    `"disable_rules": ["misc_gazetteer"]` desliga grupos de regras só nesta análise;
+///    `"feature_events": "entity_tokens"`, `"per_sentence"` ou `{"every_nth": 50}` reduz os
+///    `FeaturesComputed` de documentos longos).
+///    A primeira mensagem pode ser `{"protocol": 2}`, que liga a validação estrita: pedidos
+///    malformados viram um evento `Error` com o esquema esperado (ver `ws_protocol`).
 /// 2. Servidor responde com fluxo de eventos JSON:
 ///    - `TokenizationDone`
 ///    - `FeaturesComputed`...
 ///    - `Done`
 ///
 /// A análise roda em uma thread dedicada (`spawn_blocking`) para não travar o loop de eventos assíncrono do Tokio,
-/// já que o pipeline é CPU-bound e síncrono.
-async fn handle_websocket(mut socket: WebSocket, state: Arc<AppState>) {
+/// já que o pipeline é CPU-bound e síncrono. Os eventos chegam ao cliente enquanto ela ainda roda.
+///
+/// As camadas do cliente, se houver, são aplicadas às entidades do evento `Done`.
+async fn handle_websocket(mut socket: WebSocket, state: Arc<AppState>, tenant: Option<Arc<Tenant>>) {
     info!("WebSocket conectado");
+    let mut session = WsSession::new();
 
     while let Some(Ok(msg)) = socket.recv().await {
         match msg {
             Message::Text(text) => {
-                // Tenta parsear como JSON {text, mode, tokenizer_mode}; senão usa como texto puro
-                let (text_str, mode, tokenizer_mode) = if let Ok(req) =
-                    serde_json::from_str::<WsRequest>(&text)
-                {
-                    let m = req.mode.unwrap_or_default();
-                    let t = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
-                    (req.text.trim().to_string(), m, t)
-                } else {
-                    (text.trim().to_string(), AlgorithmMode::Hybrid, TokenizerMode::Standard)
+                let req = match session.parse(&text) {
+                    WsMessage::Analyze(req) => req,
+                    WsMessage::Reply(reply) => {
+                        if socket.send(Message::Text(reply.to_json())).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                    WsMessage::Close(reply) => {
+                        let _ = socket.send(Message::Text(reply.to_json())).await;
+                        let _ = socket.send(Message::Close(None)).await;
+                        return;
+                    }
+                    WsMessage::Ignore => continue,
                 };
-
-                if text_str.is_empty() {
+                let options = req.options();
+                let text_str = req.text;
+                let mode = req.mode.unwrap_or_default();
+                let tokenizer_mode = req.tokenizer_mode.unwrap_or(TokenizerMode::Standard);
+                if let Err(err) = options.validate() {
+                    let _ = socket.send(Message::Text(serde_json::json!({
+                        "type": "Error",
+                        "data": { "message": err.to_string() }
+                    }).to_string())).await;
                     continue;
                 }
 
-                info!("Analisando via WebSocket [{:?} | {:?}]: {} chars", mode, tokenizer_mode, text_str.len());
-
-                // Executa o pipeline em um tokio::task::spawn_blocking para não bloquear o runtime
-                let (tx_std, rx_std) = std::sync::mpsc::channel::<PipelineEvent>();
+                let tenant_name = tenant.as_ref().map_or("-", |t| t.name.as_str());
+                info!("Analisando via WebSocket [{:?} | {:?} | tenant {}]: {} chars", mode, tokenizer_mode, tenant_name, text_str.len());
 
-                // Cria um Arc clone para o closure da thread
+                // O pipeline é síncrono: roda em spawn_blocking e entrega cada evento a um
+                // canal do Tokio assim que é produzido, enquanto este loop os repassa ao cliente
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<PipelineEvent>();
                 let pipeline_arc = Arc::clone(&state);
                 let text_for_thread = text_str.clone();
-
-                // Roda pipeline em thread separada (é síncrono)
                 let handle = tokio::task::spawn_blocking(move || {
-                    pipeline_arc.pipeline.analyze_streaming(&text_for_thread, mode, tokenizer_mode, tx_std);
+                    pipeline_arc.pipeline.read().analyze_with_callback(&text_for_thread, mode, tokenizer_mode, options, |event| {
+                        // Erro = cliente já desconectou; a análise termina sem ninguém ouvindo
+                        let _ = tx.send(event);
+                    });
                 });
 
-                // Aguarda o término do processamento
+                while let Some(mut event) = rx.recv().await {
+                    apply_tenant(tenant.as_deref(), &text_str, &mut event);
+                    if let Ok(json) = serde_json::to_string(&event) {
+                        if socket.send(Message::Text(json)).await.is_err() {
+                            return; // cliente desconectou
+                        }
+                        // Pequena pausa para animação visual (passo a passo) no front-end ficar fluida
+                        tokio::time::sleep(tokio::time::Duration::from_millis(35)).await;
+                    }
+                }
+
+                // O canal fecha quando a análise termina; se ela panicou, o `Done` nunca veio
                 if handle.await.is_err() {
-                    // Se a thread panicar
                     let _ = socket.send(Message::Text(serde_json::json!({
                         "type": "Error",
                         "data": { "message": "Erro interno no pipeline" }
-                    }).to_string().into())).await;
-                    continue;
-                }
-
-                // Coleta todos os eventos numa Vec (o rx_std não é Async, então consumimos tudo de uma vez após o término)
-                // OBS: Numa implementação real de streaming, o canal deveria ser consumido enquanto a thread produz.
-                // Mas como o mpsc std bloqueia, e queremos async await no socket send, essa abordagem de bufferizar
-                // é um compromisso simples para este demo.
-                let events: Vec<PipelineEvent> = rx_std.try_iter().collect();
-
-                for event in events {
-                     if let Ok(json) = serde_json::to_string(&event) {
-                         if socket.send(Message::Text(json.into())).await.is_err() {
-                             return; // cliente desconectou
-                         }
-                         // Pequena pausa para animação visual (passo a passo) no front-end ficar fluida
-                         tokio::time::sleep(tokio::time::Duration::from_millis(35)).await;
-                     }
+                    }).to_string())).await;
                 }
             }
             Message::Close(_) => {
@@ -395,3 +988,117 @@ async fn handle_websocket(mut socket: WebSocket, state: Arc<AppState>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use std::sync::OnceLock;
+    use tower::ServiceExt;
+
+    const ADMIN_KEY: &str = "segredo";
+
+    /// Um estado por processo: construir o pipeline é o passo caro dos testes.
+    fn state() -> Arc<AppState> {
+        static STATE: OnceLock<Arc<AppState>> = OnceLock::new();
+        Arc::clone(STATE.get_or_init(|| {
+            Arc::new(AppState {
+                pipeline: SharedPipeline::new(NerPipeline::new()),
+                admin_key: Some(ADMIN_KEY.to_string()),
+                samples: SampleRegistry::builtin(),
+                kb: KnowledgeBase::new(),
+                tenants: TenantRegistry::default(),
+            })
+        }))
+    }
+
+    /// Envia `request` às rotas do servidor; devolve o status e o corpo inteiro.
+    async fn send(request: Request<Body>) -> (StatusCode, String) {
+        let response = app(state()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn get(uri: &str, headers: &[(&str, &str)]) -> Request<Body> {
+        let builder = headers.iter().fold(Request::get(uri), |b, (name, value)| b.header(*name, *value));
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn post_json(uri: &str, headers: &[(&str, &str)], json: serde_json::Value) -> Request<Body> {
+        let builder = Request::post(uri).header(header::CONTENT_TYPE, "application/json");
+        let builder = headers.iter().fold(builder, |b, (name, value)| b.header(*name, *value));
+        builder.body(Body::from(json.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_analyze_stream() {
+        let uri = "/analyze/stream?text=Lula+visitou+Recife.&mode=rules_only&viterbi_detail=summary&explain=true";
+        let (status, body) = send(get(uri, &[])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("event: TokenizationDone") && body.contains("event: Done"), "{body}");
+
+        // Listas só pelo POST
+        let json = serde_json::json!({"text": "A Vale lucrou.", "disable_rules": ["misc_gazetteer"], "feature_events": "per_sentence"});
+        let (status, body) = send(post_json("/analyze/stream", &[], json)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("event: Done"), "{body}");
+
+        let (status, body) = send(get("/analyze/stream?text=+", &[])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("Texto vazio"), "{body}");
+
+        let (status, _) = send(get("/analyze/stream?text=Lula", &[("x-api-key", "desconhecida")])).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_batch() {
+        let json = serde_json::json!({
+            "mode": "rules_only",
+            "items": [{"text": "Lula visitou Recife."}, {"text": "A Vale lucrou.", "mode": "crf_only"}]
+        });
+        let (status, body) = send(post_json("/analyze/batch", &[], json)).await;
+        assert_eq!(status, StatusCode::OK);
+        let responses: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses[0]["entities"].as_array().unwrap().iter().any(|e| e["text"] == "Recife"), "{body}");
+
+        let json = serde_json::json!({"items": [{"text": "Lula"}, {"text": "  "}]});
+        let (status, body) = send(post_json("/analyze/batch", &[], json)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("item 1"), "{body}");
+        let (status, _) = send(post_json("/analyze/batch", &[], serde_json::json!({"items": []}))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let json = serde_json::json!({"items": [{"text": "Lula"}]});
+        let (status, _) = send(post_json("/analyze/batch", &[("x-api-key", "desconhecida")], json)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_gazetteer_requires_admin_key() {
+        let json = || serde_json::json!({"category": "Org", "names": ["Zubrex Pagamentos"]});
+        let (status, _) = send(post_json("/gazetteer", &[], json())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(post_json("/gazetteer", &[("x-admin-key", "errada")], json())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(post_json("/gazetteer", &[("x-admin-key", "segred")], json())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Categoria nunca registrada: recusada na leitura do JSON, sem entrar no registro
+        let unknown = serde_json::json!({"category": "CATEGORIA_NOVA", "names": ["x"]});
+        let (status, _) = send(post_json("/gazetteer", &[("x-admin-key", ADMIN_KEY)], unknown)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let blank = serde_json::json!({"category": "Org", "names": ["", "  "]});
+        let (status, body) = send(post_json("/gazetteer", &[("x-admin-key", ADMIN_KEY)], blank)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["added"], 0, "{body}");
+
+        let (status, body) = send(post_json("/gazetteer", &[("x-admin-key", ADMIN_KEY)], json())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["added"], 1, "{body}");
+    }
+}
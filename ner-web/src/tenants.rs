@@ -0,0 +1,103 @@
+//! Clientes (tenants) com dicionários e filtros próprios sobre o modelo compartilhado.
+//!
+//! O arquivo indicado pela variável de ambiente `NER_TENANTS` mapeia cada chave de API
+//! para a configuração do cliente:
+//!
+//! ```json
+//! {
+//!   "chave-da-acme": {
+//!     "name": "acme",
+//!     "dictionaries": { "Org": ["Zubrex", "Zubrex Pay"], "Per": ["Ana Tavares"] },
+//!     "filter": { "min_confidence": 0.5, "categories": ["Org", "Per"] }
+//!   }
+//! }
+//! ```
+//!
+//! A requisição identifica o cliente pelo cabeçalho `X-Api-Key`. Sem o cabeçalho, a
+//! resposta vem do modelo base sem camadas; uma chave desconhecida é recusada (401).
+
+use axum::http::{HeaderMap, StatusCode};
+use ner_core::overlay::{DictionaryOverlay, EntityFilter};
+use ner_core::tagger::{EntityCategory, EntitySpan};
+use ner_core::tokenizer::Token;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Cabeçalho HTTP com a chave de API do cliente.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Configuração de um cliente, como lida do arquivo de tenants.
+#[derive(Debug, Deserialize)]
+struct TenantConfig {
+    name: String,
+    #[serde(default)]
+    dictionaries: HashMap<EntityCategory, Vec<String>>,
+    #[serde(default)]
+    filter: EntityFilter,
+}
+
+/// Camadas de um cliente, aplicadas sobre as entidades do pipeline compartilhado.
+pub struct Tenant {
+    pub name: String,
+    overlay: DictionaryOverlay,
+    filter: EntityFilter,
+}
+
+impl Tenant {
+    fn from_config(config: TenantConfig) -> Self {
+        let mut overlay = DictionaryOverlay::new();
+        for (category, phrases) in &config.dictionaries {
+            for phrase in phrases {
+                overlay.add(*category, phrase);
+            }
+        }
+        Self { name: config.name, overlay, filter: config.filter }
+    }
+
+    /// Aplica o dicionário e depois o filtro de saída do cliente.
+    pub fn apply(&self, text: &str, tokens: &[Token], entities: Vec<EntitySpan>) -> Vec<EntitySpan> {
+        self.filter.apply(self.overlay.apply(text, tokens, entities))
+    }
+}
+
+/// Clientes indexados pela chave de API.
+#[derive(Default)]
+pub struct TenantRegistry {
+    by_key: HashMap<String, Arc<Tenant>>,
+}
+
+impl TenantRegistry {
+    /// Carrega o arquivo de `NER_TENANTS`; sem a variável, nenhum cliente é registrado.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("NER_TENANTS") {
+            Ok(path) => {
+                let json = std::fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?;
+                Self::from_json(&json).map_err(|e| format!("{path}: {e}"))
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let configs: HashMap<String, TenantConfig> = serde_json::from_str(json)?;
+        let by_key = configs
+            .into_iter()
+            .map(|(key, config)| (key, Arc::new(Tenant::from_config(config))))
+            .collect();
+        Ok(Self { by_key })
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    /// Cliente da requisição: `None` sem `X-Api-Key`, 401 para uma chave desconhecida.
+    pub fn resolve(&self, headers: &HeaderMap) -> Result<Option<Arc<Tenant>>, StatusCode> {
+        let Some(key) = headers.get(API_KEY_HEADER) else {
+            return Ok(None);
+        };
+        let key = key.to_str().map_err(|_| StatusCode::UNAUTHORIZED)?;
+        self.by_key.get(key).cloned().map(Some).ok_or(StatusCode::UNAUTHORIZED)
+    }
+}
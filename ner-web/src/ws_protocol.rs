@@ -0,0 +1,241 @@
+//! Protocolo das mensagens do WebSocket `/ws`.
+//!
+//! A primeira mensagem pode negociar a versão do protocolo:
+//!
+//! ```json
+//! { "protocol": 2 }
+//! ```
+//!
+//! O servidor responde `{"type": "Hello", "data": {"protocol": 2, "supported": [1, 2], "strict": true}}`,
+//! ou um `Error` de código `unsupported_protocol` (e fecha a conexão) se a versão for desconhecida.
+//!
+//! - **Versão 1** (padrão, sem negociação): tolerante. Uma mensagem que não é um
+//!   pedido JSON válido é analisada como texto puro.
+//! - **Versão 2**: estrita. Uma mensagem malformada gera um evento `Error` com o erro do
+//!   serde (linha e coluna) e o esquema esperado, e nada é analisado:
+//!
+//! ```json
+//! {"type": "Error", "data": {"code": "invalid_request", "message": "missing field `text` at line 1 column 18",
+//!  "line": 1, "column": 18, "expected": {"text": "string (obrigatório)", ...}}}
+//! ```
+
+use ner_core::pipeline::{AlgorithmMode, AnalysisOptions, FeatureEvents};
+use ner_core::rule_based::{RuleGroup, RuleGroups};
+use ner_core::tokenizer::TokenizerMode;
+use ner_core::viterbi::ViterbiDetail;
+use serde::{Deserialize, Serialize};
+
+/// Versões do protocolo aceitas na negociação.
+pub const SUPPORTED_PROTOCOLS: [u32; 2] = [1, 2];
+
+/// Versão usada quando o cliente não negocia.
+pub const DEFAULT_PROTOCOL: u32 = 1;
+
+/// Mensagem de análise enviada pelo cliente.
+#[derive(Deserialize)]
+pub struct WsRequest {
+    pub text: String,
+    #[serde(default)]
+    pub mode: Option<AlgorithmMode>,
+    #[serde(default)]
+    pub tokenizer_mode: Option<TokenizerMode>,
+    /// Detalhe dos eventos do Viterbi: "full" (padrão), "compact" ou "summary".
+    #[serde(default)]
+    pub viterbi_detail: Option<ViterbiDetail>,
+    /// Eventos `FeaturesComputed`: "all" (padrão), "entity_tokens", "per_sentence" ou `{"every_nth": n}`.
+    #[serde(default)]
+    pub feature_events: Option<FeatureEvents>,
+    /// Anexa a decomposição do score a cada entidade (`score_breakdown`).
+    #[serde(default)]
+    pub explain: bool,
+    /// Limiar de abstenção por sentença (ver `AnalysisOptions::abstain_below`).
+    #[serde(default)]
+    pub abstain_below: Option<f64>,
+    /// Limite de tokens analisados (ver `AnalysisOptions::max_tokens`).
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Grupos de regras ativos (ver `AnalysisOptions::rule_groups`); ausente, todos.
+    #[serde(default)]
+    pub rule_groups: Option<RuleGroups>,
+    /// Grupos desligados nesta análise (ex: `["misc_gazetteer"]`).
+    #[serde(default)]
+    pub disable_rules: Vec<RuleGroup>,
+}
+
+impl WsRequest {
+    /// Opções da análise pedidas na mensagem (sem validar).
+    pub fn options(&self) -> AnalysisOptions {
+        AnalysisOptions {
+            viterbi_detail: self.viterbi_detail.unwrap_or_default(),
+            feature_events: self.feature_events.unwrap_or_default(),
+            explain: self.explain,
+            abstain_below: self.abstain_below,
+            max_tokens: self.max_tokens,
+            rule_groups: select_rule_groups(self.rule_groups, &self.disable_rules),
+        }
+    }
+}
+
+/// Grupos de regras de um pedido: `enabled` (ou todos) menos os de `disabled`.
+pub fn select_rule_groups(enabled: Option<RuleGroups>, disabled: &[RuleGroup]) -> RuleGroups {
+    disabled.iter().fold(enabled.unwrap_or_default(), |groups, &group| groups.without(group))
+}
+
+/// Primeira mensagem opcional, que escolhe a versão do protocolo.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WsHello {
+    protocol: u32,
+}
+
+/// Código de um erro de protocolo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WsErrorCode {
+    /// A mensagem não é JSON ou não segue o esquema de [`WsRequest`].
+    InvalidRequest,
+    /// O campo `text` está vazio.
+    EmptyText,
+    /// A versão pedida na negociação não é suportada.
+    UnsupportedProtocol,
+}
+
+/// Resposta do protocolo (`Hello` ou `Error`), no mesmo envelope `{type, data}` dos eventos do pipeline.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum WsReply {
+    Hello {
+        protocol: u32,
+        supported: [u32; 2],
+        strict: bool,
+    },
+    Error {
+        code: WsErrorCode,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        column: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expected: Option<serde_json::Value>,
+    },
+}
+
+impl WsReply {
+    fn error(code: WsErrorCode, message: impl Into<String>) -> Self {
+        Self::Error { code, message: message.into(), line: None, column: None, expected: None }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("resposta sempre serializável")
+    }
+}
+
+/// O que fazer com uma mensagem de texto recebida.
+pub enum WsMessage {
+    /// Analisar o pedido.
+    Analyze(WsRequest),
+    /// Responder (negociação aceita ou erro) e seguir esperando mensagens.
+    Reply(WsReply),
+    /// Responder com o erro e encerrar a conexão.
+    Close(WsReply),
+    /// Nada a fazer (texto vazio no modo tolerante).
+    Ignore,
+}
+
+/// Estado do protocolo de uma conexão.
+pub struct WsSession {
+    protocol: u32,
+    /// Ainda não chegou nenhuma mensagem (a negociação só vale na primeira).
+    first: bool,
+}
+
+impl Default for WsSession {
+    fn default() -> Self {
+        Self { protocol: DEFAULT_PROTOCOL, first: true }
+    }
+}
+
+impl WsSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A versão atual rejeita mensagens malformadas em vez de tratá-las como texto?
+    pub fn strict(&self) -> bool {
+        self.protocol >= 2
+    }
+
+    /// Interpreta uma mensagem de texto segundo a versão negociada.
+    pub fn parse(&mut self, message: &str) -> WsMessage {
+        if std::mem::take(&mut self.first) {
+            if let Ok(hello) = serde_json::from_str::<WsHello>(message) {
+                if !SUPPORTED_PROTOCOLS.contains(&hello.protocol) {
+                    let message = format!(
+                        "versão de protocolo {} não suportada; use uma de {:?}",
+                        hello.protocol, SUPPORTED_PROTOCOLS
+                    );
+                    return WsMessage::Close(WsReply::error(WsErrorCode::UnsupportedProtocol, message));
+                }
+                self.protocol = hello.protocol;
+                return WsMessage::Reply(WsReply::Hello {
+                    protocol: self.protocol,
+                    supported: SUPPORTED_PROTOCOLS,
+                    strict: self.strict(),
+                });
+            }
+        }
+
+        match serde_json::from_str::<WsRequest>(message) {
+            Ok(mut req) => {
+                req.text = req.text.trim().to_string();
+                if !req.text.is_empty() {
+                    WsMessage::Analyze(req)
+                } else if self.strict() {
+                    WsMessage::Reply(WsReply::error(WsErrorCode::EmptyText, "o campo `text` está vazio"))
+                } else {
+                    WsMessage::Ignore
+                }
+            }
+            Err(e) if self.strict() => WsMessage::Reply(WsReply::Error {
+                code: WsErrorCode::InvalidRequest,
+                message: e.to_string(),
+                line: Some(e.line()),
+                column: Some(e.column()),
+                expected: Some(request_schema()),
+            }),
+            // Versão 1: qualquer outra coisa é texto puro
+            Err(_) => match message.trim() {
+                "" => WsMessage::Ignore,
+                text => WsMessage::Analyze(WsRequest {
+                    text: text.to_string(),
+                    mode: None,
+                    tokenizer_mode: None,
+                    viterbi_detail: None,
+                    feature_events: None,
+                    explain: false,
+                    abstain_below: None,
+                    max_tokens: None,
+                    rule_groups: None,
+                    disable_rules: Vec::new(),
+                }),
+            },
+        }
+    }
+}
+
+/// Esquema de [`WsRequest`], devolvido junto dos erros de validação.
+pub fn request_schema() -> serde_json::Value {
+    serde_json::json!({
+        "text": "string (obrigatório)",
+        "mode": "hybrid | rules_only | crf_only | features_only | hmm | max_ent | memm | perceptron | span_based",
+        "tokenizer_mode": "standard | aggressive | conservative | char_level | bpe_lite | bpe",
+        "viterbi_detail": "full | compact | summary",
+        "feature_events": "all | entity_tokens | per_sentence | {\"every_nth\": n}",
+        "explain": "boolean",
+        "abstain_below": "number entre 0 e 1",
+        "max_tokens": "inteiro positivo",
+        "rule_groups": "lista de grupos de regras ativos (person_gazetteer, ..., regex_patterns)",
+        "disable_rules": "lista de grupos de regras desligados",
+    })
+}